@@ -0,0 +1,85 @@
+//! Measures `FilesystemStorage::store` throughput for each `storage.durability` level
+//!
+//! Run with `cargo bench --bench storage_durability`. Set `BENCH_STORAGE_DIR` to
+//! benchmark a specific disk; the default is a directory under the system temp dir.
+//! Results are recorded in `docs/development/storage-durability.md`.
+
+use std::io::Cursor;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use media_management_service::domain::value_objects::ContentHash;
+use media_management_service::infrastructure::config::StorageDurability;
+use media_management_service::infrastructure::storage::{FileStorage, FilesystemStorage};
+
+const FILE_SIZES: [usize; 3] = [64 * 1024, 1024 * 1024, 16 * 1024 * 1024];
+const TOTAL_BYTES_PER_CASE: usize = 64 * 1024 * 1024;
+
+#[tokio::main]
+async fn main() {
+    let root = std::env::var("BENCH_STORAGE_DIR").map_or_else(
+        |_| std::env::temp_dir().join(format!("storage-durability-{}", std::process::id())),
+        PathBuf::from,
+    );
+
+    println!(
+        "{:<20} {:>10} {:>8} {:>12} {:>10}",
+        "durability", "file size", "files", "MiB/s", "files/s"
+    );
+
+    for durability in
+        [StorageDurability::None, StorageDurability::File, StorageDurability::FileAndDirectory]
+    {
+        for file_size in FILE_SIZES {
+            let dir = root.join(format!("{durability:?}-{file_size}"));
+            let storage = FilesystemStorage::new(dir.join("media"))
+                .with_temp_dir(dir.join("tmp"))
+                .with_durability(durability);
+
+            let files = (TOTAL_BYTES_PER_CASE / file_size).clamp(4, 512);
+            let elapsed = store_files(&storage, file_size, files).await;
+            let seconds = elapsed.as_secs_f64();
+
+            #[allow(clippy::cast_precision_loss)]
+            let mib_per_second = (file_size * files) as f64 / (1024.0 * 1024.0) / seconds;
+            #[allow(clippy::cast_precision_loss)]
+            let files_per_second = files as f64 / seconds;
+
+            println!(
+                "{:<20} {:>10} {:>8} {:>12.1} {:>10.1}",
+                format!("{durability:?}"),
+                format_size(file_size),
+                files,
+                mib_per_second,
+                files_per_second
+            );
+
+            let _ = std::fs::remove_dir_all(&dir);
+        }
+    }
+
+    let _ = std::fs::remove_dir_all(&root);
+}
+
+/// Store `files` distinct files of `file_size` bytes, returning the time taken
+async fn store_files(storage: &FilesystemStorage, file_size: usize, files: usize) -> Duration {
+    let mut content = vec![0u8; file_size];
+    let start = Instant::now();
+
+    for i in 0..files {
+        // Distinct content and hash per file so deduplication never short-circuits
+        content[..8].copy_from_slice(&(i as u64).to_le_bytes());
+        let hash = ContentHash::new(&format!("{i:064x}")).expect("valid hash");
+        storage.store(&hash, &mut Cursor::new(&content)).await.expect("store succeeds");
+    }
+
+    start.elapsed()
+}
+
+fn format_size(bytes: usize) -> String {
+    if bytes >= 1024 * 1024 {
+        format!("{} MiB", bytes / (1024 * 1024))
+    } else {
+        format!("{} KiB", bytes / 1024)
+    }
+}
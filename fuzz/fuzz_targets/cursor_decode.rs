@@ -0,0 +1,21 @@
+//! Pagination cursors arrive verbatim in the `cursor` query parameter
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use media_management_service::infrastructure::persistence::pagination::{
+    decode_cursor, encode_cursor, PageRequest, MAX_PAGE_SIZE,
+};
+
+fuzz_target!(|input: (&str, u32)| {
+    let (cursor, limit) = input;
+
+    // A cursor that decodes must be one `encode_cursor` could have produced
+    if let Ok(last_id) = decode_cursor(cursor) {
+        assert_eq!(decode_cursor(&encode_cursor(last_id)), Ok(last_id));
+    }
+
+    if let Ok(request) = PageRequest::new(Some(cursor), limit) {
+        assert!((1..=MAX_PAGE_SIZE).contains(&request.limit));
+        assert_eq!(request.after, decode_cursor(cursor).ok());
+    }
+});
@@ -0,0 +1,49 @@
+//! The deprecated direct upload endpoint parses an arbitrary multipart body
+#![no_main]
+
+use std::sync::{Arc, OnceLock};
+
+use arbitrary::Arbitrary;
+use axum::{body::Body, http::header, http::Request, Router};
+use libfuzzer_sys::fuzz_target;
+use media_management_service::{
+    infrastructure::storage::{InMemoryStorage, PresignedUrlConfig, PresignedUrlService},
+    presentation::{handlers::media::AppState, routes},
+    test_utils::mocks::InMemoryMediaRepository,
+};
+use tokio::runtime::Runtime;
+use tower::ServiceExt;
+
+#[derive(Debug, Arbitrary)]
+struct Input<'a> {
+    boundary: &'a str,
+    body: &'a [u8],
+}
+
+fn router() -> &'static (Runtime, Router) {
+    static ROUTER: OnceLock<(Runtime, Router)> = OnceLock::new();
+    ROUTER.get_or_init(|| {
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+        let app_state = AppState::new(
+            Arc::new(InMemoryMediaRepository::new()),
+            Arc::new(InMemoryStorage::new()),
+            PresignedUrlService::new(PresignedUrlConfig::default()),
+            1024 * 1024,
+        );
+        (runtime, routes::create_routes(app_state))
+    })
+}
+
+fuzz_target!(|input: Input<'_>| {
+    let Ok(request) = Request::post("/api/v1/media-management/media")
+        .header(header::CONTENT_TYPE, format!("multipart/form-data; boundary={}", input.boundary))
+        .body(Body::from(input.body.to_vec()))
+    else {
+        return;
+    };
+
+    // Malformed bodies must be rejected as client errors, never as failures
+    let (runtime, router) = router();
+    let response = runtime.block_on(router.clone().oneshot(request)).unwrap();
+    assert!(!response.status().is_server_error(), "{}", response.status());
+});
@@ -0,0 +1,66 @@
+//! Presigned upload URLs carry the token in the path and the signature, expiry,
+//! size and content type in the query string, all of them client-controlled
+#![no_main]
+
+use std::sync::{Arc, OnceLock};
+
+use arbitrary::Arbitrary;
+use axum::{body::Body, http::Request, Router};
+use libfuzzer_sys::fuzz_target;
+use media_management_service::{
+    infrastructure::storage::{InMemoryStorage, PresignedUrlConfig, PresignedUrlService},
+    presentation::{handlers::media::AppState, routes},
+    test_utils::mocks::InMemoryMediaRepository,
+};
+use tokio::runtime::Runtime;
+use tower::ServiceExt;
+
+#[derive(Debug, Arbitrary)]
+struct Input<'a> {
+    token: &'a str,
+    query: &'a str,
+    signature: &'a str,
+    expires: i64,
+    size: u64,
+    content_type: &'a str,
+    body: &'a [u8],
+}
+
+fn service() -> &'static PresignedUrlService {
+    static SERVICE: OnceLock<PresignedUrlService> = OnceLock::new();
+    SERVICE.get_or_init(|| PresignedUrlService::new(PresignedUrlConfig::default()))
+}
+
+fn router() -> &'static (Runtime, Router) {
+    static ROUTER: OnceLock<(Runtime, Router)> = OnceLock::new();
+    ROUTER.get_or_init(|| {
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+        let app_state = AppState::new(
+            Arc::new(InMemoryMediaRepository::new()),
+            Arc::new(InMemoryStorage::new()),
+            service().clone(),
+            1024 * 1024,
+        );
+        (runtime, routes::create_routes(app_state))
+    })
+}
+
+fuzz_target!(|input: Input<'_>| {
+    let _ = service().validate_upload_url(
+        input.token,
+        input.signature,
+        input.expires,
+        input.size,
+        input.content_type,
+    );
+    let _ = service().verify_signature(input.token, input.signature);
+
+    // Malformed parameters must be rejected as client errors, never as failures
+    let uri = format!("/api/v1/media-management/media/upload/{}?{}", input.token, input.query);
+    let Ok(request) = Request::put(uri).body(Body::from(input.body.to_vec())) else {
+        return;
+    };
+    let (runtime, router) = router();
+    let response = runtime.block_on(router.clone().oneshot(request)).unwrap();
+    assert!(!response.status().is_server_error(), "{}", response.status());
+});
@@ -0,0 +1,16 @@
+-- Resumable (tus) upload sessions. Chunk data lives in the service's temp path;
+-- this table tracks how much of each upload has been received.
+CREATE TABLE IF NOT EXISTS recipe_manager.resumable_uploads (
+    upload_id      UUID PRIMARY KEY,
+    user_id        UUID        NOT NULL,
+    upload_length  BIGINT      NOT NULL CHECK (upload_length >= 0),
+    upload_offset  BIGINT      NOT NULL DEFAULT 0 CHECK (upload_offset >= 0),
+    filename       TEXT,
+    content_type   TEXT,
+    created_at     TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at     TIMESTAMPTZ NOT NULL,
+    CHECK (upload_offset <= upload_length)
+);
+
+CREATE INDEX IF NOT EXISTS idx_resumable_uploads_expires_at
+    ON recipe_manager.resumable_uploads (expires_at);
@@ -0,0 +1,16 @@
+-- Presigned upload sessions. The upload token handed out at initiation maps to the
+-- pending media record and the file details the client committed to.
+CREATE TABLE IF NOT EXISTS recipe_manager.upload_sessions (
+    upload_token   TEXT PRIMARY KEY,
+    media_id       BIGINT      NOT NULL
+        REFERENCES recipe_manager.media (media_id) ON DELETE CASCADE,
+    user_id        UUID        NOT NULL,
+    filename       TEXT        NOT NULL,
+    content_type   TEXT        NOT NULL,
+    expected_size  BIGINT      NOT NULL CHECK (expected_size > 0),
+    created_at     TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at     TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_upload_sessions_expires_at
+    ON recipe_manager.upload_sessions (expires_at);
@@ -0,0 +1,10 @@
+-- Support fetching associated media by recipe, ingredient, and step in a single join,
+-- ordered by media_id.
+CREATE INDEX IF NOT EXISTS idx_recipe_media_recipe_media
+    ON recipe_manager.recipe_media (recipe_id, media_id);
+
+CREATE INDEX IF NOT EXISTS idx_ingredient_media_recipe_ingredient_media
+    ON recipe_manager.ingredient_media (recipe_id, ingredient_id, media_id);
+
+CREATE INDEX IF NOT EXISTS idx_step_media_recipe_step_media
+    ON recipe_manager.step_media (recipe_id, step_id, media_id);
@@ -0,0 +1,5 @@
+-- Alternative encodings (e.g. WebP, AVIF) generated from the original upload.
+-- Each entry records the variant name, content hash, media type, and file size;
+-- the encoded bytes are stored content-addressed alongside the original.
+ALTER TABLE recipe_manager.media
+    ADD COLUMN IF NOT EXISTS variants JSONB NOT NULL DEFAULT '[]'::jsonb;
@@ -0,0 +1,4 @@
+-- Error reported by the last failed processing run (e.g. ffmpeg output),
+-- surfaced to clients through the upload status endpoint.
+ALTER TABLE recipe_manager.media
+    ADD COLUMN IF NOT EXISTS processing_error TEXT;
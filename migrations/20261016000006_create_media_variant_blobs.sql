@@ -0,0 +1,23 @@
+-- Registry of stored variant blobs. Variants are content-addressed, so identical
+-- encodings produced for different originals share one blob; reference_count
+-- tracks how many media variant entries point at it, and the blob may only be
+-- removed from storage once the count drops to zero.
+CREATE TABLE IF NOT EXISTS recipe_manager.media_variant_blobs (
+    content_hash     TEXT        PRIMARY KEY,
+    media_type       TEXT        NOT NULL,
+    file_size        BIGINT      NOT NULL CHECK (file_size >= 0),
+    reference_count  BIGINT      NOT NULL CHECK (reference_count >= 0),
+    created_at       TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at       TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Register variants generated before the registry existed
+INSERT INTO recipe_manager.media_variant_blobs
+    (content_hash, media_type, file_size, reference_count)
+SELECT variant->>'content_hash',
+       MIN(variant->>'media_type'),
+       MAX((variant->>'file_size')::BIGINT),
+       COUNT(*)
+FROM recipe_manager.media, jsonb_array_elements(variants) AS variant
+GROUP BY variant->>'content_hash'
+ON CONFLICT (content_hash) DO NOTHING;
@@ -0,0 +1,16 @@
+-- Support searching a user's media. Filename substring matches (ILIKE '%...%')
+-- use a trigram index; the remaining filters narrow the user's rows by upload
+-- date, MIME type prefix (LIKE 'image/%') and size.
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+CREATE INDEX IF NOT EXISTS idx_media_original_filename_trgm
+    ON recipe_manager.media USING GIN (original_filename gin_trgm_ops);
+
+CREATE INDEX IF NOT EXISTS idx_media_user_created_at
+    ON recipe_manager.media (user_id, created_at);
+
+CREATE INDEX IF NOT EXISTS idx_media_user_media_type
+    ON recipe_manager.media (user_id, media_type text_pattern_ops);
+
+CREATE INDEX IF NOT EXISTS idx_media_user_file_size
+    ON recipe_manager.media (user_id, file_size);
@@ -0,0 +1,15 @@
+-- Support keyset pagination of a user's media sorted by upload time, size or
+-- filename. Ties are broken by media_id, and filenames are compared byte-wise
+-- (COLLATE "C") so the order does not depend on the database locale.
+CREATE INDEX IF NOT EXISTS idx_media_user_created_at_media
+    ON recipe_manager.media (user_id, created_at, media_id);
+
+CREATE INDEX IF NOT EXISTS idx_media_user_file_size_media
+    ON recipe_manager.media (user_id, file_size, media_id);
+
+CREATE INDEX IF NOT EXISTS idx_media_user_filename_media
+    ON recipe_manager.media (user_id, original_filename COLLATE "C", media_id);
+
+-- Covered by the indexes above
+DROP INDEX IF EXISTS recipe_manager.idx_media_user_created_at;
+DROP INDEX IF EXISTS recipe_manager.idx_media_user_file_size;
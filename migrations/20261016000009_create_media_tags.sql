@@ -0,0 +1,12 @@
+-- User-defined tags on media. Tags are stored normalized (trimmed, lowercase),
+-- so the primary key also prevents the same tag appearing twice on one file.
+CREATE TABLE IF NOT EXISTS recipe_manager.media_tags (
+    media_id    BIGINT      NOT NULL REFERENCES recipe_manager.media (media_id) ON DELETE CASCADE,
+    tag         TEXT        NOT NULL CHECK (char_length(tag) BETWEEN 1 AND 32),
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (media_id, tag)
+);
+
+-- Support filtering media by tag
+CREATE INDEX IF NOT EXISTS idx_media_tags_tag_media
+    ON recipe_manager.media_tags (tag, media_id);
@@ -0,0 +1,15 @@
+-- Requests made with an Idempotency-Key. A row without a status is still in
+-- flight; once the response is stored it is replayed to retries until the row
+-- expires, after which the key may be reused.
+CREATE TABLE IF NOT EXISTS recipe_manager.idempotency_keys (
+    idempotency_key  TEXT PRIMARY KEY,
+    request          TEXT        NOT NULL,
+    status           SMALLINT,
+    content_type     TEXT,
+    body             BYTEA,
+    created_at       TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at       TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_idempotency_keys_expires_at
+    ON recipe_manager.idempotency_keys (expires_at);
@@ -0,0 +1,8 @@
+-- Events recorded in the same transaction as the media change that raised
+-- them. The event relay job publishes them in event_id order and deletes each
+-- once published, so an event is never lost between commit and publish.
+CREATE TABLE IF NOT EXISTS recipe_manager.event_outbox (
+    event_id    BIGSERIAL PRIMARY KEY,
+    event       JSONB       NOT NULL,
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
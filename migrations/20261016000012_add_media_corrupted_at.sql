@@ -0,0 +1,9 @@
+-- When an integrity scan last found the stored content of the media (or one
+-- of its variants) missing or no longer matching its content hash. Cleared
+-- once a later scan finds the content intact again.
+ALTER TABLE recipe_manager.media
+    ADD COLUMN IF NOT EXISTS corrupted_at TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS idx_media_corrupted_at
+    ON recipe_manager.media (corrupted_at)
+    WHERE corrupted_at IS NOT NULL;
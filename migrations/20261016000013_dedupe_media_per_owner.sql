@@ -0,0 +1,37 @@
+-- Deduplicate uploads per owner instead of across every user: two users
+-- uploading the same bytes each get a media row of their own, while the blob
+-- stored under the content hash is still shared.
+CREATE UNIQUE INDEX IF NOT EXISTS idx_media_content_hash_user
+    ON recipe_manager.media (content_hash, user_id);
+
+-- Drop the global uniqueness of content_hash, whatever the schema named it
+DO $$
+DECLARE
+    content_hash_column SMALLINT;
+    name TEXT;
+BEGIN
+    SELECT attnum INTO content_hash_column
+    FROM pg_attribute
+    WHERE attrelid = 'recipe_manager.media'::regclass AND attname = 'content_hash';
+
+    FOR name IN
+        SELECT conname FROM pg_constraint
+        WHERE conrelid = 'recipe_manager.media'::regclass
+          AND contype = 'u'
+          AND conkey = ARRAY[content_hash_column]
+    LOOP
+        EXECUTE format('ALTER TABLE recipe_manager.media DROP CONSTRAINT %I', name);
+    END LOOP;
+
+    FOR name IN
+        SELECT index_class.relname
+        FROM pg_index
+        JOIN pg_class index_class ON index_class.oid = pg_index.indexrelid
+        WHERE pg_index.indrelid = 'recipe_manager.media'::regclass
+          AND pg_index.indisunique
+          AND NOT pg_index.indisprimary
+          AND pg_index.indkey::SMALLINT[] = ARRAY[content_hash_column]
+    LOOP
+        EXECUTE format('DROP INDEX recipe_manager.%I', name);
+    END LOOP;
+END $$;
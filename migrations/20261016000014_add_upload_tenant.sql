@@ -0,0 +1,8 @@
+-- Client ID of the token an upload was started with, so the tenant's format
+-- policy can be applied when the content arrives in a later request. NULL for
+-- uploads started without a token.
+ALTER TABLE recipe_manager.upload_sessions
+    ADD COLUMN IF NOT EXISTS tenant TEXT;
+
+ALTER TABLE recipe_manager.resumable_uploads
+    ADD COLUMN IF NOT EXISTS tenant TEXT;
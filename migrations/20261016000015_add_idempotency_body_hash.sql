@@ -0,0 +1,5 @@
+-- SHA-256 of the body of the request a key was first used with, so a key sent
+-- again with a different body is rejected instead of replaying the response.
+-- Keys reserved before this column existed match no body and expire within a day.
+ALTER TABLE recipe_manager.idempotency_keys
+    ADD COLUMN IF NOT EXISTS body_hash TEXT NOT NULL DEFAULT '';
@@ -0,0 +1,5 @@
+-- Client ID of the token the media was uploaded with, so capacity can be
+-- reported per tenant. NULL for media uploaded without a token or before this
+-- column existed.
+ALTER TABLE recipe_manager.media
+    ADD COLUMN IF NOT EXISTS tenant TEXT;
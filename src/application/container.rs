@@ -0,0 +1,336 @@
+//! Composition root for the application layer
+//!
+//! Use cases are built once from their shared dependencies when the application
+//! state is assembled, rather than per request by each handler. Every use case
+//! is wrapped in a [`Decorated`] here, which is also where retries are enabled:
+//! only read-only use cases are retried. The use cases that change media publish
+//! to one [`EventBus`], subscribed to here.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::{
+    application::decorators::{Decorated, RetryPolicy},
+    application::events::{AuditLog, EventBus, EventMetrics, EventPublisher, LiveEvents},
+    application::use_cases::{
+        AllowedTypes, BackfillVariantsUseCase, BatchGetMediaUseCase, CapacityReportUseCase,
+        CheckProcessingSlaUseCase, CompletePresignedUploadUseCase, DeleteMediaUseCase,
+        DownloadMediaUseCase, GetMediaByIngredientUseCase, GetMediaByRecipeUseCase,
+        GetMediaByStepUseCase, GetMediaUseCase, GetStorageUsageUseCase, IdempotencyKeys,
+        InitiateUploadUseCase, ListMediaUseCase, MediaAssociationsUseCase, ProcessMediaUseCase,
+        ProcessingProgress, ReadOnlyMode, RelayEventsUseCase, RenderCache, RenderMediaUseCase,
+        ReprocessMediaUseCase, ResumableUploadUseCase, SearchMediaUseCase, SetMediaTagsUseCase,
+        UploadFingerprints, UploadLocks, UploadMediaUseCase, VariantBackfills,
+    },
+    domain::{
+        repositories::{
+            MediaRepository, ResumableUploadRepository, UploadSessionRepository, VariantRepository,
+        },
+        services::Clock,
+        value_objects::{
+            CacheControlPolicy, DownloadRedirectPolicy, FormatPolicy, HealthPolicy,
+            LegacyUploadPolicy, LoadSheddingPolicy, ProcessingSla, RenderPolicy, RouteLimits,
+            StorageQuota, UuidVersion,
+        },
+    },
+    infrastructure::{
+        business_metrics::{BusinessMetrics, UploadFlow},
+        cdn::CdnPurges,
+        event_stream::EventStream,
+        jobs::JobRegistry,
+        processing::{ImagePipelineRollout, MalwareScan, RenderProvider, VideoProcessor},
+        storage::{FileStorage, PresignedUrlService, UploadStaging},
+        webhooks::Webhooks,
+    },
+    presentation::middleware::error::AppError,
+};
+
+pub type DynMediaRepository = dyn MediaRepository<Error = AppError>;
+pub type DynFileStorage = dyn FileStorage;
+pub type DynVariantRepository = dyn VariantRepository<Error = AppError>;
+pub type DynUploadSessionRepository = dyn UploadSessionRepository<Error = AppError>;
+pub type DynResumableUploadRepository = dyn ResumableUploadRepository<Error = AppError>;
+
+/// Retry policy for use cases that only read state
+const READ_RETRY_POLICY: RetryPolicy = RetryPolicy::new(3, Duration::from_millis(50));
+
+/// Everything the use cases are built from
+#[derive(Clone)]
+pub struct Dependencies {
+    pub repository: Arc<DynMediaRepository>,
+    pub storage: Arc<DynFileStorage>,
+    pub variants: Arc<DynVariantRepository>,
+    pub upload_sessions: Arc<DynUploadSessionRepository>,
+    pub resumable_uploads: Arc<DynResumableUploadRepository>,
+    pub upload_staging: UploadStaging,
+    pub presigned_url_service: PresignedUrlService,
+    pub upload_locks: UploadLocks,
+    pub upload_fingerprints: UploadFingerprints,
+    pub idempotency_keys: IdempotencyKeys,
+    pub variant_backfills: VariantBackfills,
+    pub processing_progress: ProcessingProgress,
+    pub live_events: LiveEvents,
+    pub jobs: JobRegistry,
+    pub image_pipeline: ImagePipelineRollout,
+    pub video_processor: Option<VideoProcessor>,
+    pub malware_scan: Option<MalwareScan>,
+    pub max_file_size: u64,
+    pub resumable_upload_expiry: Duration,
+    pub quota: StorageQuota,
+    pub allowed_types: AllowedTypes,
+    pub format_policy: FormatPolicy,
+    pub download_redirect: DownloadRedirectPolicy,
+    pub legacy_upload: LegacyUploadPolicy,
+    pub processing_sla: ProcessingSla,
+    pub render: RenderPolicy,
+    pub render_cache: RenderCache,
+    pub render_provider: Option<RenderProvider>,
+    pub clock: Arc<dyn Clock>,
+    pub uuid_version: UuidVersion,
+    pub business_metrics: BusinessMetrics,
+    pub read_only: ReadOnlyMode,
+    pub health: HealthPolicy,
+    pub cache_control: CacheControlPolicy,
+    pub route_limits: RouteLimits,
+    pub load_shedding: LoadSheddingPolicy,
+    pub cdn: CdnPurges,
+    pub webhooks: Webhooks,
+    pub event_stream: EventStream,
+}
+
+/// The application's use cases, wired to shared dependencies
+pub struct Container {
+    pub upload_media: Decorated<UploadMediaUseCase<DynMediaRepository, DynFileStorage>>,
+    pub initiate_upload:
+        Decorated<InitiateUploadUseCase<DynMediaRepository, DynUploadSessionRepository>>,
+    pub complete_presigned_upload: Decorated<
+        CompletePresignedUploadUseCase<
+            DynUploadSessionRepository,
+            DynMediaRepository,
+            DynFileStorage,
+        >,
+    >,
+    pub resumable_upload: Decorated<
+        ResumableUploadUseCase<DynResumableUploadRepository, DynMediaRepository, DynFileStorage>,
+    >,
+    pub process_media:
+        Decorated<ProcessMediaUseCase<DynMediaRepository, DynFileStorage, DynVariantRepository>>,
+    pub get_media: Decorated<GetMediaUseCase<DynMediaRepository>>,
+    pub batch_get_media: Decorated<BatchGetMediaUseCase<DynMediaRepository>>,
+    pub list_media: Decorated<ListMediaUseCase<DynMediaRepository, DynFileStorage>>,
+    pub search_media: Decorated<SearchMediaUseCase<DynMediaRepository>>,
+    pub download_media: Decorated<DownloadMediaUseCase<DynMediaRepository, DynFileStorage>>,
+    pub render_media:
+        Decorated<RenderMediaUseCase<DynMediaRepository, DynFileStorage, DynVariantRepository>>,
+    pub delete_media:
+        Decorated<DeleteMediaUseCase<DynMediaRepository, DynFileStorage, DynVariantRepository>>,
+    pub get_media_by_recipe: Decorated<GetMediaByRecipeUseCase<DynMediaRepository>>,
+    pub get_media_by_ingredient: Decorated<GetMediaByIngredientUseCase<DynMediaRepository>>,
+    pub get_media_by_step: Decorated<GetMediaByStepUseCase<DynMediaRepository>>,
+    pub get_storage_usage: Decorated<GetStorageUsageUseCase<DynMediaRepository>>,
+    pub associate_media: Decorated<MediaAssociationsUseCase<DynMediaRepository>>,
+    pub dissociate_media: Decorated<MediaAssociationsUseCase<DynMediaRepository>>,
+    pub set_media_tags: Decorated<SetMediaTagsUseCase<DynMediaRepository>>,
+    pub reprocess_media: Decorated<ReprocessMediaUseCase<DynMediaRepository>>,
+    pub backfill_variants: Decorated<BackfillVariantsUseCase<DynMediaRepository>>,
+    pub check_processing_sla: Decorated<CheckProcessingSlaUseCase<DynMediaRepository>>,
+    pub capacity_report: Decorated<CapacityReportUseCase<DynMediaRepository>>,
+    pub relay_events: Decorated<RelayEventsUseCase<DynMediaRepository>>,
+}
+
+impl Container {
+    /// Build and decorate every use case from the given dependencies
+    #[must_use]
+    #[allow(clippy::too_many_lines)]
+    pub fn new(deps: &Dependencies) -> Self {
+        let events: Arc<dyn EventPublisher> = Arc::new(
+            EventBus::new()
+                .subscribe(Arc::new(EventMetrics))
+                .subscribe(Arc::new(AuditLog))
+                .subscribe(Arc::new(deps.webhooks.clone()))
+                .subscribe(Arc::new(deps.cdn.clone()))
+                .subscribe(Arc::new(deps.event_stream.clone()))
+                .subscribe(Arc::new(deps.live_events.clone())),
+        );
+
+        Self {
+            upload_media: Decorated::new(
+                "upload_media",
+                UploadMediaUseCase::new(
+                    deps.repository.clone(),
+                    deps.storage.clone(),
+                    deps.max_file_size,
+                )
+                .with_upload_locks(deps.upload_locks.clone())
+                .with_quota(deps.quota)
+                .with_allowed_types(deps.allowed_types.clone())
+                .with_format_policy(deps.format_policy.clone())
+                .with_metrics(deps.business_metrics, UploadFlow::Direct)
+                .with_events(events.clone()),
+            ),
+            initiate_upload: Decorated::new(
+                "initiate_upload",
+                InitiateUploadUseCase::new(
+                    deps.repository.clone(),
+                    deps.upload_sessions.clone(),
+                    deps.presigned_url_service.clone(),
+                    deps.max_file_size,
+                )
+                .with_quota(deps.quota)
+                .with_clock(deps.clock.clone())
+                .with_upload_locks(deps.upload_locks.clone())
+                .with_fingerprints(deps.upload_fingerprints.clone())
+                .with_metrics(deps.business_metrics),
+            ),
+            complete_presigned_upload: Decorated::new(
+                "complete_presigned_upload",
+                CompletePresignedUploadUseCase::new(
+                    deps.upload_sessions.clone(),
+                    deps.repository.clone(),
+                    deps.storage.clone(),
+                    deps.presigned_url_service.clone(),
+                )
+                .with_upload_locks(deps.upload_locks.clone())
+                .with_clock(deps.clock.clone())
+                .with_allowed_types(deps.allowed_types.clone())
+                .with_format_policy(deps.format_policy.clone())
+                .with_metrics(deps.business_metrics)
+                .with_events(events.clone()),
+            ),
+            resumable_upload: Decorated::new(
+                "resumable_upload",
+                ResumableUploadUseCase::new(
+                    deps.resumable_uploads.clone(),
+                    deps.upload_staging.clone(),
+                    deps.repository.clone(),
+                    deps.storage.clone(),
+                    deps.max_file_size,
+                    deps.resumable_upload_expiry,
+                )
+                .with_upload_locks(deps.upload_locks.clone())
+                .with_quota(deps.quota)
+                .with_clock(deps.clock.clone())
+                .with_uuid_version(deps.uuid_version)
+                .with_allowed_types(deps.allowed_types.clone())
+                .with_format_policy(deps.format_policy.clone())
+                .with_metrics(deps.business_metrics)
+                .with_events(events.clone()),
+            ),
+            process_media: Decorated::new(
+                "process_media",
+                ProcessMediaUseCase::new(
+                    deps.repository.clone(),
+                    deps.storage.clone(),
+                    deps.variants.clone(),
+                )
+                .with_image_pipeline(deps.image_pipeline)
+                .with_video_processor(deps.video_processor.clone())
+                .with_malware_scan(deps.malware_scan.clone())
+                .with_metrics(deps.business_metrics)
+                .with_sla(deps.processing_sla)
+                .with_events(events.clone())
+                .with_progress(deps.processing_progress.clone()),
+            ),
+            get_media: Decorated::new("get_media", GetMediaUseCase::new(deps.repository.clone()))
+                .with_retry(READ_RETRY_POLICY),
+            batch_get_media: Decorated::new(
+                "batch_get_media",
+                BatchGetMediaUseCase::new(deps.repository.clone()),
+            )
+            .with_retry(READ_RETRY_POLICY),
+            list_media: Decorated::new(
+                "list_media",
+                ListMediaUseCase::new(deps.repository.clone(), deps.storage.clone()),
+            )
+            .with_retry(READ_RETRY_POLICY),
+            search_media: Decorated::new(
+                "search_media",
+                SearchMediaUseCase::new(deps.repository.clone()),
+            )
+            .with_retry(READ_RETRY_POLICY),
+            download_media: Decorated::new(
+                "download_media",
+                DownloadMediaUseCase::new(deps.repository.clone(), deps.storage.clone())
+                    .with_redirect(deps.download_redirect),
+            )
+            .with_retry(READ_RETRY_POLICY),
+            render_media: Decorated::new(
+                "render_media",
+                RenderMediaUseCase::new(
+                    deps.repository.clone(),
+                    deps.storage.clone(),
+                    deps.variants.clone(),
+                )
+                .with_policy(deps.render.clone())
+                .with_read_only(deps.read_only.clone())
+                .with_cache(deps.render_cache.clone())
+                .with_provider(deps.render_provider.clone()),
+            ),
+            delete_media: Decorated::new(
+                "delete_media",
+                DeleteMediaUseCase::new(
+                    deps.repository.clone(),
+                    deps.storage.clone(),
+                    deps.variants.clone(),
+                )
+                .with_upload_locks(deps.upload_locks.clone())
+                .with_events(events.clone()),
+            ),
+            get_media_by_recipe: Decorated::new(
+                "get_media_by_recipe",
+                GetMediaByRecipeUseCase::new(deps.repository.clone()),
+            )
+            .with_retry(READ_RETRY_POLICY),
+            get_media_by_ingredient: Decorated::new(
+                "get_media_by_ingredient",
+                GetMediaByIngredientUseCase::new(deps.repository.clone()),
+            )
+            .with_retry(READ_RETRY_POLICY),
+            get_media_by_step: Decorated::new(
+                "get_media_by_step",
+                GetMediaByStepUseCase::new(deps.repository.clone()),
+            )
+            .with_retry(READ_RETRY_POLICY),
+            get_storage_usage: Decorated::new(
+                "get_storage_usage",
+                GetStorageUsageUseCase::new(deps.repository.clone(), deps.quota),
+            )
+            .with_retry(READ_RETRY_POLICY),
+            associate_media: Decorated::new(
+                "associate_media",
+                MediaAssociationsUseCase::new(deps.repository.clone()).with_events(events.clone()),
+            ),
+            dissociate_media: Decorated::new(
+                "dissociate_media",
+                MediaAssociationsUseCase::new(deps.repository.clone()).with_events(events.clone()),
+            ),
+            set_media_tags: Decorated::new(
+                "set_media_tags",
+                SetMediaTagsUseCase::new(deps.repository.clone()).with_events(events.clone()),
+            ),
+            reprocess_media: Decorated::new(
+                "reprocess_media",
+                ReprocessMediaUseCase::new(deps.repository.clone()),
+            ),
+            backfill_variants: Decorated::new(
+                "backfill_variants",
+                BackfillVariantsUseCase::new(deps.repository.clone())
+                    .with_video_processing(deps.video_processor.is_some()),
+            ),
+            check_processing_sla: Decorated::new(
+                "check_processing_sla",
+                CheckProcessingSlaUseCase::new(deps.repository.clone(), deps.processing_sla),
+            )
+            .with_retry(READ_RETRY_POLICY),
+            capacity_report: Decorated::new(
+                "capacity_report",
+                CapacityReportUseCase::new(deps.repository.clone()).with_clock(deps.clock.clone()),
+            )
+            .with_retry(READ_RETRY_POLICY),
+            relay_events: Decorated::new(
+                "relay_events",
+                RelayEventsUseCase::new(deps.repository.clone(), events),
+            ),
+        }
+    }
+}
@@ -0,0 +1,103 @@
+//! Request-scoped deadlines
+//!
+//! A caller can say how long it is willing to wait for a response. The HTTP layer
+//! turns that budget into a [`Deadline`] and runs the request within
+//! [`Deadline::scope`], which cancels the request's work once the deadline
+//! passes. Code running on behalf of the request reads it with [`current`] to
+//! avoid starting work, such as a retry, that could not finish in time.
+//!
+//! Work spawned onto its own task, like media processing, is not bound by the
+//! deadline of the request that queued it.
+
+use std::future::Future;
+use std::time::{Duration, SystemTime};
+
+use tokio::time::Instant;
+
+use crate::presentation::middleware::error::AppError;
+
+tokio::task_local! {
+    static CURRENT: Deadline;
+}
+
+/// The instant by which a request must be answered
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Deadline {
+    at: Instant,
+}
+
+impl Deadline {
+    /// A deadline `budget` from now
+    #[must_use]
+    pub fn after(budget: Duration) -> Self {
+        Self { at: Instant::now() + budget }
+    }
+
+    /// A deadline at the wall-clock time `at`, already passed if `at` is not in the future
+    #[must_use]
+    pub fn at(at: SystemTime) -> Self {
+        Self::after(at.duration_since(SystemTime::now()).unwrap_or(Duration::ZERO))
+    }
+
+    /// Time left before the deadline, zero once it has passed
+    #[must_use]
+    pub fn remaining(self) -> Duration {
+        self.at.saturating_duration_since(Instant::now())
+    }
+
+    /// Whether the deadline has passed
+    #[must_use]
+    pub fn is_expired(self) -> bool {
+        self.remaining().is_zero()
+    }
+
+    /// Run `future` with this deadline as the [`current`] one, cancelling it
+    /// once the deadline passes
+    ///
+    /// # Errors
+    /// * `Timeout` - The deadline passed before `future` completed
+    pub async fn scope<F: Future>(self, future: F) -> Result<F::Output, AppError> {
+        CURRENT
+            .scope(self, tokio::time::timeout_at(self.at, future))
+            .await
+            .map_err(|_| self.exceeded())
+    }
+
+    /// The error reported when the deadline has passed
+    #[must_use]
+    pub fn exceeded(self) -> AppError {
+        AppError::Timeout { message: "The request deadline was exceeded".to_string() }
+    }
+}
+
+/// The deadline of the request being served on this task, if it set one
+#[must_use]
+pub fn current() -> Option<Deadline> {
+    CURRENT.try_with(|deadline| *deadline).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_scope_cancels_work_past_the_deadline() {
+        let deadline = Deadline::after(Duration::from_millis(100));
+
+        let result = deadline
+            .scope(async {
+                assert_eq!(current(), Some(deadline));
+                tokio::time::sleep(Duration::from_secs(1)).await;
+            })
+            .await;
+
+        assert!(matches!(result, Err(AppError::Timeout { .. })));
+        assert!(deadline.is_expired());
+        assert_eq!(current(), None);
+    }
+
+    #[test]
+    fn test_past_wall_clock_deadline_is_expired() {
+        assert!(Deadline::at(SystemTime::now() - Duration::from_secs(1)).is_expired());
+    }
+}
@@ -0,0 +1,258 @@
+//! Cross-cutting behavior applied to every use case
+//!
+//! [`Decorated`] wraps a use case with timing metrics, structured logging and an
+//! optional retry policy. The container decides which use cases are retried, so
+//! handlers only pick the operation to run.
+
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+use crate::{application::deadline, presentation::middleware::error::AppError};
+
+/// How often a failed use case is attempted again
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Total attempts, including the first one
+    pub max_attempts: u32,
+    /// Delay before the first retry; doubled for every further retry
+    pub initial_backoff: Duration,
+}
+
+impl RetryPolicy {
+    /// Run once and never retry
+    #[must_use]
+    pub const fn none() -> Self {
+        Self { max_attempts: 1, initial_backoff: Duration::ZERO }
+    }
+
+    /// Retry transient failures up to `max_attempts` in total
+    #[must_use]
+    pub const fn new(max_attempts: u32, initial_backoff: Duration) -> Self {
+        Self { max_attempts, initial_backoff }
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::none()
+    }
+}
+
+/// A use case wrapped with metrics, logging and retries
+///
+/// Each call records `use_case_duration_seconds` labelled with the use case and
+/// its outcome, and logs the result with the same fields. Only errors for which
+/// [`AppError::is_transient`] holds are retried, and not when the request's
+/// [deadline](deadline::current) would pass before the retry starts.
+pub struct Decorated<U> {
+    name: &'static str,
+    inner: U,
+    retry: RetryPolicy,
+}
+
+impl<U> Decorated<U> {
+    /// Wrap a use case; `name` labels its logs and metrics
+    pub fn new(name: &'static str, inner: U) -> Self {
+        Self { name, inner, retry: RetryPolicy::none() }
+    }
+
+    /// Retry transient failures of [`Decorated::run`] according to `retry`
+    #[must_use]
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// The name used in logs and metrics
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// The wrapped use case, for calls that should bypass the decorators
+    pub fn inner(&self) -> &U {
+        &self.inner
+    }
+
+    /// Run an operation on the use case, retrying per the configured policy
+    ///
+    /// `operation` is called again for every attempt, so it must be able to
+    /// rebuild its inputs.
+    ///
+    /// # Errors
+    /// Returns the error of the last attempt
+    pub async fn run<'a, T, F, Fut>(&'a self, mut operation: F) -> Result<T, AppError>
+    where
+        F: FnMut(&'a U) -> Fut,
+        Fut: Future<Output = Result<T, AppError>>,
+    {
+        let started = Instant::now();
+        let mut attempt = 1;
+        let mut backoff = self.retry.initial_backoff;
+
+        let result = loop {
+            match operation(&self.inner).await {
+                Err(e)
+                    if e.is_transient()
+                        && attempt < self.retry.max_attempts
+                        && deadline::current()
+                            .is_none_or(|deadline| deadline.remaining() > backoff) =>
+                {
+                    tracing::warn!(
+                        use_case = self.name,
+                        attempt,
+                        error = %e,
+                        "Use case failed with a transient error, retrying"
+                    );
+                    metrics::counter!("use_case_retries_total", "use_case" => self.name)
+                        .increment(1);
+                    tokio::time::sleep(backoff).await;
+                    backoff = backoff.saturating_mul(2);
+                    attempt += 1;
+                }
+                result => break result,
+            }
+        };
+
+        self.record(started.elapsed(), attempt, &result);
+        result
+    }
+
+    /// Run an operation that consumes its inputs, without retries
+    ///
+    /// # Errors
+    /// Returns the operation's error
+    pub async fn run_once<'a, T, F, Fut>(&'a self, operation: F) -> Result<T, AppError>
+    where
+        F: FnOnce(&'a U) -> Fut,
+        Fut: Future<Output = Result<T, AppError>>,
+    {
+        let started = Instant::now();
+        let result = operation(&self.inner).await;
+        self.record(started.elapsed(), 1, &result);
+        result
+    }
+
+    fn record<T>(&self, elapsed: Duration, attempts: u32, result: &Result<T, AppError>) {
+        let duration_ms = elapsed.as_millis();
+        let outcome = match result {
+            Ok(_) => {
+                tracing::info!(use_case = self.name, duration_ms, attempts, "Use case completed");
+                "success"
+            }
+            Err(e) if e.should_log_as_error() => {
+                tracing::error!(
+                    use_case = self.name,
+                    duration_ms,
+                    attempts,
+                    error = %e,
+                    "Use case failed"
+                );
+                "error"
+            }
+            Err(e) => {
+                tracing::info!(
+                    use_case = self.name,
+                    duration_ms,
+                    attempts,
+                    error_type = e.error_type(),
+                    "Use case rejected request"
+                );
+                "rejected"
+            }
+        };
+
+        metrics::histogram!(
+            "use_case_duration_seconds",
+            "use_case" => self.name,
+            "outcome" => outcome
+        )
+        .record(elapsed.as_secs_f64());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    struct Flaky {
+        calls: AtomicU32,
+        failures: u32,
+        error: fn() -> AppError,
+    }
+
+    impl Flaky {
+        fn new(failures: u32, error: fn() -> AppError) -> Self {
+            Self { calls: AtomicU32::new(0), failures, error }
+        }
+
+        fn execute(&self) -> std::future::Ready<Result<u32, AppError>> {
+            let call = self.calls.fetch_add(1, Ordering::SeqCst) + 1;
+            std::future::ready(if call <= self.failures { Err((self.error)()) } else { Ok(call) })
+        }
+    }
+
+    fn database_error() -> AppError {
+        AppError::Database { message: "connection reset".to_string() }
+    }
+
+    fn not_found_error() -> AppError {
+        AppError::NotFound { resource: "media".to_string() }
+    }
+
+    #[tokio::test]
+    async fn test_run_retries_transient_errors() {
+        let use_case = Decorated::new("flaky", Flaky::new(2, database_error))
+            .with_retry(RetryPolicy::new(3, Duration::from_millis(1)));
+
+        let result = use_case.run(Flaky::execute).await;
+
+        assert_eq!(result.unwrap(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_run_gives_up_after_max_attempts() {
+        let use_case = Decorated::new("flaky", Flaky::new(5, database_error))
+            .with_retry(RetryPolicy::new(2, Duration::from_millis(1)));
+
+        let result = use_case.run(Flaky::execute).await;
+
+        assert!(matches!(result, Err(AppError::Database { .. })));
+        assert_eq!(use_case.inner().calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_run_does_not_retry_client_errors() {
+        let use_case = Decorated::new("flaky", Flaky::new(1, not_found_error))
+            .with_retry(RetryPolicy::new(3, Duration::from_millis(1)));
+
+        let result = use_case.run(Flaky::execute).await;
+
+        assert!(matches!(result, Err(AppError::NotFound { .. })));
+        assert_eq!(use_case.inner().calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_run_does_not_retry_past_the_request_deadline() {
+        let use_case = Decorated::new("flaky", Flaky::new(1, database_error))
+            .with_retry(RetryPolicy::new(3, Duration::from_secs(1)));
+
+        let result = deadline::Deadline::after(Duration::from_millis(100))
+            .scope(use_case.run(Flaky::execute))
+            .await
+            .unwrap();
+
+        assert!(matches!(result, Err(AppError::Database { .. })));
+        assert_eq!(use_case.inner().calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_run_without_policy_runs_once() {
+        let use_case = Decorated::new("flaky", Flaky::new(1, database_error));
+
+        let result = use_case.run(Flaky::execute).await;
+
+        assert!(result.is_err());
+        assert_eq!(use_case.inner().calls.load(Ordering::SeqCst), 1);
+    }
+}
@@ -1,9 +1,17 @@
-use crate::domain::{entities::MediaId, value_objects::ProcessingStatus};
+use crate::domain::{
+    entities::{Media, MediaId},
+    value_objects::{
+        ImageFit, ImageRender, MediaSort, MediaSortField, ProcessingStatus, RenderSize, SortOrder,
+    },
+};
 use serde::{Deserialize, Serialize};
+use std::time::SystemTime;
+use utoipa::{IntoParams, ToSchema};
 
 /// Data Transfer Object for media information
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct MediaDto {
+    #[schema(value_type = i64, minimum = 1)]
     pub id: MediaId,
     pub content_hash: String,
     pub original_filename: String,
@@ -13,26 +21,116 @@ pub struct MediaDto {
     pub processing_status: ProcessingStatus,
     pub uploaded_at: String, // ISO 8601 timestamp
     pub updated_at: String,  // ISO 8601 timestamp
+    /// Why processing failed, present only for `Failed` media
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub processing_error: Option<String>,
+    /// When an integrity scan found the stored content damaged or missing; absent while intact
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub corrupted_at: Option<String>,
+    /// User-defined tags, sorted alphabetically
+    pub tags: Vec<String>,
+    /// Alternative encodings generated from the original; List Media only includes
+    /// them when requested via `?include=variants`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub variants: Option<Vec<MediaVariantDto>>,
+}
+
+impl From<Media> for MediaDto {
+    fn from(media: Media) -> Self {
+        let last_modified = to_rfc3339(media.updated_at);
+        let variants = media
+            .variants
+            .into_iter()
+            .map(|variant| MediaVariantDto {
+                name: variant.name,
+                content_type: Some(variant.media_type.mime_type().to_string()),
+                file_size: variant.file_size,
+                last_modified: last_modified.clone(),
+            })
+            .collect();
+
+        Self {
+            id: media.id,
+            content_hash: media.content_hash.as_str().to_string(),
+            original_filename: media.original_filename,
+            media_type: media.media_type.mime_type().to_string(),
+            media_path: media.media_path,
+            file_size: media.file_size,
+            processing_status: media.processing_status,
+            uploaded_at: to_rfc3339(media.uploaded_at),
+            updated_at: to_rfc3339(media.updated_at),
+            processing_error: media.processing_error,
+            corrupted_at: media.corrupted_at.map(to_rfc3339),
+            tags: media.tags.into_iter().map(String::from).collect(),
+            variants: Some(variants),
+        }
+    }
+}
+
+/// Format a timestamp as ISO 8601, falling back to now for pre-epoch times
+pub(crate) fn to_rfc3339(time: SystemTime) -> String {
+    time.duration_since(std::time::UNIX_EPOCH).map_or_else(
+        |_| chrono::Utc::now().to_rfc3339(),
+        |d| {
+            chrono::DateTime::<chrono::Utc>::from_timestamp(d.as_secs() as i64, d.subsec_nanos())
+                .unwrap_or_default()
+                .to_rfc3339()
+        },
+    )
+}
+
+/// Data Transfer Object for a stored media variant
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct MediaVariantDto {
+    pub name: String,
+    pub content_type: Option<String>,
+    pub file_size: u64,
+    pub last_modified: String, // ISO 8601 timestamp
+}
+
+/// A stored rendition of a media file: the original upload or a generated variant
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct MediaRenditionDto {
+    /// `original`, or the variant name, e.g. `webp`, `avif`, `mp4` or `poster`
+    pub name: String,
+    pub content_hash: String,
+    pub media_type: String, // MIME type string
+    pub file_size: u64,
+}
+
+/// Response DTO listing a media file's renditions
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct MediaVariantsResponse {
+    #[schema(value_type = i64, minimum = 1)]
+    pub media_id: MediaId,
+    pub processing_status: ProcessingStatus,
+    /// The original first, then each generated variant
+    pub variants: Vec<MediaRenditionDto>,
 }
 
 /// Request DTO for uploading media (legacy direct upload)
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, ToSchema)]
 pub struct UploadMediaRequest {
     pub filename: String,
     // File content will be handled separately as a stream
 }
 
 /// Request DTO for initiating a presigned upload session
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct InitiateUploadRequest {
     pub filename: String,
     pub content_type: String,
     pub file_size: u64,
+    /// Leading hex characters (8 to 64) of the file's SHA-256, used to recognise
+    /// repeated submits of the same upload
+    #[serde(default)]
+    pub content_hash_prefix: Option<String>,
 }
 
 /// Response DTO for upload initiation
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct InitiateUploadResponse {
+    #[schema(value_type = i64, minimum = 1)]
     pub media_id: MediaId,
     pub upload_url: String,
     pub upload_token: String,
@@ -41,11 +139,13 @@ pub struct InitiateUploadResponse {
 }
 
 /// Response DTO for upload status checking
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, ToSchema)]
 pub struct UploadStatusResponse {
+    #[schema(value_type = i64, minimum = 1)]
     pub media_id: MediaId,
     pub status: ProcessingStatus,
-    pub progress: Option<u8>, // 0-100 percentage
+    /// 0-100 percentage; `None` while processing on another instance
+    pub progress: Option<u8>,
     pub error_message: Option<String>,
     pub download_url: Option<String>,
     pub processing_time_ms: Option<u64>,
@@ -53,17 +153,56 @@ pub struct UploadStatusResponse {
     pub completed_at: Option<String>,
 }
 
+/// Processing progress of a media file, streamed as Server-Sent Events
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct ProcessingProgressEvent {
+    #[schema(value_type = i64, minimum = 1)]
+    pub media_id: MediaId,
+    pub status: ProcessingStatus,
+    /// Share of the pipeline done, 0-100; `0` while pending or after a failure
+    pub progress: u8,
+    pub error_message: Option<String>,
+}
+
+impl ProcessingProgressEvent {
+    /// Progress of media that is not being processed, `100` once complete and `0` otherwise
+    #[must_use]
+    pub fn new(media_id: MediaId, status: ProcessingStatus, error_message: Option<String>) -> Self {
+        let progress = if status.is_complete() { 100 } else { 0 };
+        Self { media_id, status, progress, error_message }
+    }
+
+    /// Whether processing has ended, so no further progress will follow
+    #[must_use]
+    pub fn is_final(&self) -> bool {
+        self.status.is_complete() || self.status.is_failed()
+    }
+}
+
 /// Response DTO for successful upload
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct UploadMediaResponse {
+    #[schema(value_type = i64, minimum = 1)]
     pub media_id: MediaId,
     pub content_hash: String,
     pub processing_status: ProcessingStatus,
     pub upload_url: Option<String>, // For direct file access
 }
 
+/// Response DTO for a user's storage consumption
+///
+/// `max_bytes` and `max_files` are `null` when that dimension is unlimited.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct StorageUsageResponse {
+    pub used_bytes: u64,
+    pub used_files: u64,
+    pub max_bytes: Option<u64>,
+    pub max_files: Option<u64>,
+}
+
 /// Query parameters for paginated media listing
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct PaginatedMediaQuery {
     /// Cursor for pagination (base64 encoded)
     pub cursor: Option<String>,
@@ -71,10 +210,110 @@ pub struct PaginatedMediaQuery {
     pub limit: Option<u32>,
     /// Filter by processing status
     pub status: Option<ProcessingStatus>,
+    /// Comma-separated list of related data to include (e.g. `variants`)
+    pub include: Option<String>,
+    /// Field to sort by (default: media ID, i.e. upload order)
+    pub sort_by: Option<MediaSortField>,
+    /// Sort direction (default `asc`)
+    pub order: Option<SortOrder>,
+    /// Comma-separated tags the media must all carry
+    pub tags: Option<String>,
+}
+
+impl PaginatedMediaQuery {
+    /// The requested sort order
+    pub fn sort(&self) -> MediaSort {
+        MediaSort { field: self.sort_by, order: self.order.unwrap_or_default() }
+    }
+
+    /// Check whether the given related data was requested via `include`
+    pub fn includes(&self, name: &str) -> bool {
+        self.include
+            .as_deref()
+            .is_some_and(|include| include.split(',').any(|item| item.trim() == name))
+    }
+}
+
+/// Query parameters for searching media; every given filter must match
+#[derive(Debug, Clone, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SearchMediaQuery {
+    /// Cursor for pagination (base64 encoded)
+    pub cursor: Option<String>,
+    /// Maximum number of items per page (default 50, max 100)
+    pub limit: Option<u32>,
+    /// Case-insensitive substring of the original filename
+    pub filename: Option<String>,
+    /// MIME type family, e.g. `image` or `video`
+    #[serde(rename = "type")]
+    #[param(rename = "type")]
+    pub type_family: Option<String>,
+    /// Smallest file size in bytes, inclusive
+    pub min_size: Option<u64>,
+    /// Largest file size in bytes, inclusive
+    pub max_size: Option<u64>,
+    /// Only media uploaded at or after this time (RFC 3339)
+    pub uploaded_since: Option<chrono::DateTime<chrono::Utc>>,
+    /// Only media uploaded before this time (RFC 3339)
+    pub uploaded_before: Option<chrono::DateTime<chrono::Utc>>,
+    /// Filter by processing status
+    pub status: Option<ProcessingStatus>,
+    /// Comma-separated tags the media must all carry
+    pub tags: Option<String>,
+}
+
+/// Request DTO for replacing the tags on a media file
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SetMediaTagsRequest {
+    /// The complete new set of tags; an empty list removes every tag
+    pub tags: Vec<String>,
+}
+
+/// Response DTO listing a media file's tags
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct MediaTagsResponse {
+    #[schema(value_type = i64, minimum = 1)]
+    pub media_id: MediaId,
+    /// Normalized tags, sorted alphabetically
+    pub tags: Vec<String>,
+}
+
+/// Query parameters for media attached to a recipe, ingredient or step
+#[derive(Debug, Clone, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct AssociatedMediaQuery {
+    /// Cursor for pagination (base64 encoded)
+    pub cursor: Option<String>,
+    /// Maximum number of items per page (default 50, max 100)
+    pub limit: Option<u32>,
+    /// Return a bare array of every associated media ID instead of a page of media
+    #[serde(default)]
+    pub ids_only: bool,
+}
+
+/// Query parameters for rendering an image at another size
+#[derive(Debug, Clone, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct RenderQuery {
+    /// Width in pixels; leave out to follow the aspect ratio
+    pub w: Option<u32>,
+    /// Height in pixels; leave out to follow the aspect ratio
+    pub h: Option<u32>,
+    /// How the image is fitted to the dimensions (default `contain`)
+    pub fit: Option<ImageFit>,
+}
+
+impl RenderQuery {
+    /// The requested render, or `None` if neither dimension is given
+    pub fn render(&self) -> Option<ImageRender> {
+        let size = RenderSize { width: self.w, height: self.h };
+        (size.width.is_some() || size.height.is_some())
+            .then(|| ImageRender::new(size, self.fit.unwrap_or_default()))
+    }
 }
 
 /// Pagination metadata for cursor-based pagination
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct PaginationInfo {
     /// Cursor for the next page (if available)
     pub next_cursor: Option<String>,
@@ -89,7 +328,7 @@ pub struct PaginationInfo {
 }
 
 /// Paginated response for media listing
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct PaginatedMediaResponse {
     /// List of media items for current page
     pub data: Vec<MediaDto>,
@@ -97,6 +336,233 @@ pub struct PaginatedMediaResponse {
     pub pagination: PaginationInfo,
 }
 
+impl PaginatedMediaResponse {
+    /// Wrap one forward page; `has_prev` is whether the page was requested with a cursor
+    pub fn from_page(
+        data: Vec<MediaDto>,
+        next_cursor: Option<String>,
+        has_next: bool,
+        has_prev: bool,
+    ) -> Self {
+        let pagination = PaginationInfo {
+            next_cursor,
+            prev_cursor: None,
+            page_size: data.len() as u32,
+            has_next,
+            has_prev,
+        };
+        Self { data, pagination }
+    }
+}
+
+/// Request DTO for looking up several media in one call
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct BatchGetMediaRequest {
+    #[schema(value_type = Vec<i64>, max_items = 100)]
+    pub ids: Vec<MediaId>,
+}
+
+/// Whether one ID of a batch lookup was found
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchGetStatus {
+    Found,
+    NotFound,
+    /// The media belongs to another user
+    Forbidden,
+}
+
+/// Result of looking up one ID of a batch
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct BatchGetMediaItem {
+    #[schema(value_type = i64, minimum = 1)]
+    pub id: MediaId,
+    pub status: BatchGetStatus,
+    /// Media metadata, present when `status` is `found`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub media: Option<MediaDto>,
+}
+
+/// Response DTO for a batch lookup, with one result per distinct requested ID in request order
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct BatchGetMediaResponse {
+    pub results: Vec<BatchGetMediaItem>,
+}
+
+/// Request DTO for deleting several media in one call
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct BatchDeleteMediaRequest {
+    #[schema(value_type = Vec<i64>, max_items = 100)]
+    pub ids: Vec<MediaId>,
+}
+
+/// How deleting one ID of a batch ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchDeleteStatus {
+    Deleted,
+    NotFound,
+    /// The media belongs to another user and was kept
+    Forbidden,
+    /// Deleting failed on the server; see `error`
+    Failed,
+}
+
+/// Result of deleting one ID of a batch
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct BatchDeleteMediaItem {
+    #[schema(value_type = i64, minimum = 1)]
+    pub id: MediaId,
+    pub status: BatchDeleteStatus,
+    /// Why deleting failed, present when `status` is `failed`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Response DTO for a batch delete, with one result per distinct requested ID in request order
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct BatchDeleteMediaResponse {
+    pub results: Vec<BatchDeleteMediaItem>,
+}
+
+/// A generated variant kind that existing media can be backfilled with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum VariantKind {
+    Webp,
+    Avif,
+    Thumbnail,
+    Mp4,
+    Poster,
+}
+
+impl VariantKind {
+    /// The variant name recorded on media, e.g. `webp`
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Webp => "webp",
+            Self::Avif => "avif",
+            Self::Thumbnail => "thumbnail",
+            Self::Mp4 => "mp4",
+            Self::Poster => "poster",
+        }
+    }
+}
+
+/// Which media a variant backfill reprocesses
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BackfillSelection {
+    /// Processed media that lacks the variant
+    Missing,
+    /// Media whose processing failed
+    Failed,
+    /// Processed or failed media, regenerating variants it already has
+    All,
+}
+
+/// Request DTO for starting a variant backfill
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct BackfillVariantsRequest {
+    pub kind: VariantKind,
+    pub status: BackfillSelection,
+    /// Media processed at once before pausing; defaults to 25, at most 500
+    #[serde(default)]
+    pub batch_size: Option<u32>,
+    /// Pause between batches in milliseconds; defaults to 1000
+    #[serde(default)]
+    pub batch_interval_ms: Option<u64>,
+}
+
+/// Whether a variant backfill is still going
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BackfillState {
+    Running,
+    Completed,
+    /// Stopped early; see `error`
+    Failed,
+}
+
+/// Progress of the current or last variant backfill
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct VariantBackfillProgress {
+    pub kind: VariantKind,
+    pub status: BackfillSelection,
+    pub state: BackfillState,
+    pub batch_size: u32,
+    /// Media examined so far
+    pub scanned: u64,
+    /// Media reset to `Pending` and queued for processing
+    pub enqueued: u64,
+    /// Queued media that finished processing
+    pub processed: u64,
+    /// Queued media whose processing failed
+    pub failed: u64,
+    /// Last media examined; the next batch starts after it
+    #[schema(value_type = Option<i64>, minimum = 1)]
+    pub last_media_id: Option<MediaId>,
+    pub started_at: String, // ISO 8601 timestamp
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<String>, // ISO 8601 timestamp
+    /// Why the backfill stopped early, present when `state` is `failed`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Format of a report
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportFormat {
+    #[default]
+    Json,
+    Csv,
+}
+
+/// Query parameters for the capacity report
+#[derive(Debug, Clone, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct CapacityReportQuery {
+    /// Weeks covered, counting the current one (default 12, max 104)
+    pub weeks: Option<u32>,
+    /// `json` (default) or `csv`
+    pub format: Option<ReportFormat>,
+}
+
+/// Media added during one week under one type or tenant
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct CapacityGrowthDto {
+    /// Monday the week starts on, as `YYYY-MM-DD` (UTC)
+    pub week_start: String,
+    /// MIME type or owning user ID
+    pub key: String,
+    pub media_added: u64,
+    pub bytes_added: u64,
+    /// Media under this key by the end of the week, including earlier weeks
+    pub total_media: u64,
+    /// Bytes under this key by the end of the week, including earlier weeks
+    pub total_bytes: u64,
+}
+
+/// Weekly growth of the media library, for capacity planning
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct CapacityReport {
+    /// Weeks covered, counting the current one
+    pub weeks: u32,
+    /// Monday the first week covered starts on, as `YYYY-MM-DD` (UTC)
+    pub since: String,
+    /// Growth by MIME type
+    pub by_type: Vec<CapacityGrowthDto>,
+    /// Growth by tenant, the client the media was uploaded with
+    pub by_tenant: Vec<CapacityGrowthDto>,
+}
+
+/// Format a timestamp as an ISO 8601 date (UTC)
+pub(crate) fn to_date(time: SystemTime) -> String {
+    chrono::DateTime::<chrono::Utc>::from(time).format("%Y-%m-%d").to_string()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -113,6 +579,10 @@ mod tests {
             processing_status: ProcessingStatus::Complete,
             uploaded_at: "2023-01-01T00:00:00Z".to_string(),
             updated_at: "2023-01-01T00:00:00Z".to_string(),
+            processing_error: None,
+            corrupted_at: None,
+            tags: Vec::new(),
+            variants: None,
         }
     }
 
@@ -144,6 +614,10 @@ mod tests {
             processing_status: ProcessingStatus::Processing,
             uploaded_at: "2023-01-01T00:00:00Z".to_string(),
             updated_at: "2023-01-01T01:00:00Z".to_string(),
+            processing_error: None,
+            corrupted_at: None,
+            tags: Vec::new(),
+            variants: None,
         };
 
         let json = serde_json::to_string(&dto).unwrap();
@@ -231,6 +705,56 @@ mod tests {
         assert_eq!(query.status, None);
     }
 
+    #[test]
+    fn test_paginated_media_query_includes() {
+        let json = r#"{"include": "tags, variants"}"#;
+        let query: PaginatedMediaQuery = serde_json::from_str(json).unwrap();
+
+        assert!(query.includes("variants"));
+        assert!(query.includes("tags"));
+        assert!(!query.includes("owner"));
+
+        let query: PaginatedMediaQuery = serde_json::from_str("{}").unwrap();
+        assert!(!query.includes("variants"));
+    }
+
+    #[test]
+    fn test_associated_media_query_defaults_to_pages() {
+        let query: AssociatedMediaQuery = serde_json::from_str("{}").unwrap();
+        assert!(!query.ids_only);
+
+        let query: AssociatedMediaQuery =
+            serde_json::from_str(r#"{"ids_only": true, "limit": 5}"#).unwrap();
+        assert!(query.ids_only);
+        assert_eq!(query.limit, Some(5));
+    }
+
+    #[test]
+    fn test_render_query_needs_a_dimension() {
+        let query: RenderQuery = serde_json::from_str(r#"{"fit": "cover"}"#).unwrap();
+        assert_eq!(query.render(), None);
+
+        let query: RenderQuery =
+            serde_json::from_str(r#"{"w": 320, "h": 240, "fit": "cover"}"#).unwrap();
+        assert_eq!(query.render().unwrap().variant_name(), "render-320x240-cover");
+    }
+
+    #[test]
+    fn test_paginated_media_response_from_page() {
+        let response = PaginatedMediaResponse::from_page(
+            vec![create_test_media_dto()],
+            Some("next".to_string()),
+            true,
+            false,
+        );
+
+        assert_eq!(response.pagination.page_size, 1);
+        assert_eq!(response.pagination.next_cursor.as_deref(), Some("next"));
+        assert!(response.pagination.has_next);
+        assert!(!response.pagination.has_prev);
+        assert!(response.pagination.prev_cursor.is_none());
+    }
+
     #[test]
     fn test_pagination_info_serialization() {
         let pagination = PaginationInfo {
@@ -0,0 +1,209 @@
+//! Domain events published by the use cases
+//!
+//! Use cases publish a [`DomainEvent`] once a change is stored, instead of
+//! calling every side effect themselves. Each [`EventSubscriber`] on the
+//! [`EventBus`] reacts on its own: webhooks, CDN purges, metrics and the audit
+//! log. Subscribers are called in turn while publishing, so anything slow must
+//! be spawned into the background.
+//!
+//! Metrics:
+//! - `domain_events_total` - published events, labelled by `event`
+//! - `live_event_listeners` - open connections following their user's events
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, PoisonError};
+use tokio::sync::broadcast;
+
+use crate::domain::entities::UserId;
+pub use crate::domain::events::DomainEvent;
+
+/// Where use cases send their events
+pub trait EventPublisher: Send + Sync {
+    fn publish(&self, event: &DomainEvent);
+}
+
+/// A reaction to published events
+///
+/// Called while the event is published, so it must not block; work that waits
+/// on other systems belongs in a spawned task.
+pub trait EventSubscriber: Send + Sync {
+    fn handle(&self, event: &DomainEvent);
+}
+
+/// Publishes every event to each of its subscribers, in the order they subscribed
+///
+/// Cloning is cheap; clones share the same subscribers.
+#[derive(Clone, Default)]
+pub struct EventBus {
+    subscribers: Vec<Arc<dyn EventSubscriber>>,
+}
+
+impl EventBus {
+    /// A bus without subscribers, where events are dropped
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Also deliver events to `subscriber`
+    #[must_use]
+    pub fn subscribe(mut self, subscriber: Arc<dyn EventSubscriber>) -> Self {
+        self.subscribers.push(subscriber);
+        self
+    }
+}
+
+impl EventPublisher for EventBus {
+    fn publish(&self, event: &DomainEvent) {
+        for subscriber in &self.subscribers {
+            subscriber.handle(event);
+        }
+    }
+}
+
+/// Counts published events in `domain_events_total`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EventMetrics;
+
+impl EventSubscriber for EventMetrics {
+    fn handle(&self, event: &DomainEvent) {
+        metrics::counter!("domain_events_total", "event" => event.name()).increment(1);
+    }
+}
+
+/// Logs every change to media under the `audit` target
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AuditLog;
+
+impl EventSubscriber for AuditLog {
+    fn handle(&self, event: &DomainEvent) {
+        tracing::info!(
+            target: "audit",
+            event = event.name(),
+            media_id = %event.media_id(),
+            details = ?event,
+            "Media changed"
+        );
+    }
+}
+
+/// Events a slow listener may fall behind by before it misses some
+const LIVE_EVENT_BUFFER: usize = 64;
+
+/// Hands each user's events to the connections they have open, e.g. on `/media/ws`
+///
+/// Every listener gets its own copy of each event about media its user uploaded.
+/// Nothing is kept for users without listeners. Cloning is cheap; clones share
+/// the same listeners.
+#[derive(Debug, Clone, Default)]
+pub struct LiveEvents {
+    channels: Arc<Mutex<HashMap<UserId, broadcast::Sender<DomainEvent>>>>,
+}
+
+impl LiveEvents {
+    /// Create a hub without listeners
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Receive events about media uploaded by `user_id` until the receiver is dropped
+    ///
+    /// A receiver that falls more than a few dozen events behind skips the
+    /// oldest, and is told how many it missed.
+    pub fn listen(&self, user_id: UserId) -> broadcast::Receiver<DomainEvent> {
+        let mut channels = self.lock();
+        channels.retain(|_, channel| channel.receiver_count() > 0);
+        let receiver = channels
+            .entry(user_id)
+            .or_insert_with(|| broadcast::Sender::new(LIVE_EVENT_BUFFER))
+            .subscribe();
+        record_listeners(&channels);
+        receiver
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<UserId, broadcast::Sender<DomainEvent>>> {
+        self.channels.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl EventSubscriber for LiveEvents {
+    fn handle(&self, event: &DomainEvent) {
+        let mut channels = self.lock();
+        let user_id = event.uploaded_by();
+        let Some(channel) = channels.get(&user_id) else {
+            return;
+        };
+
+        // Sending only fails once every listener of the user has gone
+        if channel.send(event.clone()).is_err() {
+            channels.remove(&user_id);
+            record_listeners(&channels);
+        }
+    }
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn record_listeners(channels: &HashMap<UserId, broadcast::Sender<DomainEvent>>) {
+    let listeners: usize = channels.values().map(broadcast::Sender::receiver_count).sum();
+    metrics::gauge!("live_event_listeners").set(listeners as f64);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::entities::MediaId;
+    use std::sync::Mutex;
+
+    /// Subscriber that keeps the names of the events it saw, tagged with `label`
+    struct Recorder {
+        label: &'static str,
+        seen: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl EventSubscriber for Recorder {
+        fn handle(&self, event: &DomainEvent) {
+            self.seen.lock().unwrap().push(format!("{}:{}", self.label, event.name()));
+        }
+    }
+
+    #[test]
+    fn test_events_reach_every_subscriber_in_order() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let bus = EventBus::new()
+            .subscribe(Arc::new(Recorder { label: "first", seen: seen.clone() }))
+            .subscribe(Arc::new(Recorder { label: "second", seen: seen.clone() }));
+
+        let owner = UserId::new();
+        bus.clone()
+            .publish(&DomainEvent::MediaDeleted { media_id: MediaId::new(5), uploaded_by: owner });
+        assert_eq!(*seen.lock().unwrap(), ["first:media_deleted", "second:media_deleted"]);
+
+        EventBus::new().publish(&DomainEvent::MediaTagsChanged {
+            media_id: MediaId::new(5),
+            uploaded_by: owner,
+        });
+        assert_eq!(seen.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_live_events_only_reach_the_uploader() {
+        let live = LiveEvents::new();
+        let (owner, other) = (UserId::new(), UserId::new());
+        let mut first = live.listen(owner);
+        let mut second = live.listen(owner);
+        let mut unrelated = live.listen(other);
+
+        let deleted = DomainEvent::MediaDeleted { media_id: MediaId::new(5), uploaded_by: owner };
+        live.handle(&deleted);
+
+        assert_eq!(first.recv().await.unwrap(), deleted);
+        assert_eq!(second.recv().await.unwrap(), deleted);
+        assert!(unrelated.try_recv().is_err());
+
+        // Once every listener of a user has gone, nothing is kept for them
+        drop((first, second));
+        live.handle(&deleted);
+        assert_eq!(live.lock().len(), 1);
+    }
+}
@@ -1,3 +1,7 @@
+pub mod container;
+pub mod deadline;
+pub mod decorators;
 pub mod dto;
+pub mod events;
 pub mod ports;
 pub mod use_cases;
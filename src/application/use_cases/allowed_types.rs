@@ -0,0 +1,51 @@
+use std::sync::{Arc, RwLock};
+
+/// Content types uploads are accepted as, by their sniffed content
+///
+/// Empty accepts every type. Cloning is cheap; clones share the same list, so a
+/// configuration reload applies to every upload flow at once.
+#[derive(Debug, Clone, Default)]
+pub struct AllowedTypes {
+    types: Arc<RwLock<Arc<[String]>>>,
+}
+
+impl AllowedTypes {
+    /// Accept only `types`, or every type if empty
+    #[must_use]
+    pub fn new(types: Vec<String>) -> Self {
+        Self { types: Arc::new(RwLock::new(types.into())) }
+    }
+
+    /// The types currently accepted
+    #[must_use]
+    pub fn get(&self) -> Arc<[String]> {
+        self.types.read().unwrap_or_else(std::sync::PoisonError::into_inner).clone()
+    }
+
+    /// Replace the accepted types
+    pub fn set(&self, types: Vec<String>) {
+        *self.types.write().unwrap_or_else(std::sync::PoisonError::into_inner) = types.into();
+    }
+}
+
+impl From<Vec<String>> for AllowedTypes {
+    fn from(types: Vec<String>) -> Self {
+        Self::new(types)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clones_share_the_list() {
+        let allowed = AllowedTypes::new(vec!["image/png".to_string()]);
+        let shared = allowed.clone();
+
+        allowed.set(vec!["image/webp".to_string(), "image/avif".to_string()]);
+
+        assert_eq!(&*shared.get(), ["image/webp", "image/avif"]);
+        assert!(AllowedTypes::default().get().is_empty());
+    }
+}
@@ -0,0 +1,401 @@
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::SystemTime;
+
+use super::repository_error;
+use crate::{
+    application::dto::{
+        to_rfc3339, BackfillSelection, BackfillState, BackfillVariantsRequest,
+        VariantBackfillProgress, VariantKind,
+    },
+    domain::{
+        entities::{Media, MediaId},
+        repositories::MediaRepository,
+        value_objects::ProcessingStatus,
+    },
+    infrastructure::processing::{ImageVariantEncoder, VideoProcessor},
+    presentation::middleware::error::AppError,
+};
+
+/// Media processed at once when the request does not say
+pub const DEFAULT_BACKFILL_BATCH_SIZE: u32 = 25;
+
+/// Largest batch a backfill may process at once
+pub const MAX_BACKFILL_BATCH_SIZE: u32 = 500;
+
+/// Media claimed for reprocessing by one batch of a backfill
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackfillBatch {
+    /// Media reset to `Pending`, ready to be processed
+    pub media_ids: Vec<MediaId>,
+    /// Media examined to find them
+    pub scanned: u64,
+    /// Last media examined, or `None` once the whole library has been
+    pub last_media_id: Option<MediaId>,
+}
+
+/// Use case for finding existing media that lacks a generated variant
+///
+/// The library is walked in media ID order; each call claims the next batch by
+/// resetting matching media to `Pending`, after which the caller processes it as
+/// it would a new upload. Only media whose type produces the requested variant is
+/// claimed, so a backfill never loops on media that cannot gain it.
+pub struct BackfillVariantsUseCase<R>
+where
+    R: MediaRepository + ?Sized,
+{
+    repository: Arc<R>,
+    video_processing: bool,
+}
+
+impl<R> BackfillVariantsUseCase<R>
+where
+    R: MediaRepository + ?Sized,
+{
+    /// Create a new backfill use case
+    ///
+    /// Video variants cannot be backfilled unless [`Self::with_video_processing`] is used.
+    pub fn new(repository: Arc<R>) -> Self {
+        Self { repository, video_processing: false }
+    }
+
+    /// Whether uploaded videos are transcoded, and so can gain video variants
+    #[must_use]
+    pub fn with_video_processing(mut self, video_processing: bool) -> Self {
+        self.video_processing = video_processing;
+        self
+    }
+
+    /// Check that a backfill of `kind` can produce anything
+    ///
+    /// # Errors
+    /// * `BadRequest` - The kind is a video variant and videos are not transcoded
+    pub fn validate(&self, kind: VariantKind) -> Result<(), AppError> {
+        if is_video_kind(kind) && !self.video_processing {
+            return Err(AppError::BadRequest {
+                message: format!(
+                    "Cannot backfill {} variants: video processing is not configured",
+                    kind.name()
+                ),
+            });
+        }
+        Ok(())
+    }
+
+    /// Claim up to `limit` media after `after` for reprocessing
+    ///
+    /// # Errors
+    /// * `Database` - Reading or resetting media failed
+    pub async fn claim_batch(
+        &self,
+        kind: VariantKind,
+        selection: BackfillSelection,
+        after: Option<MediaId>,
+        limit: u32,
+    ) -> Result<BackfillBatch, AppError> {
+        let limit = limit.clamp(1, MAX_BACKFILL_BATCH_SIZE);
+        let mut batch = BackfillBatch { media_ids: Vec::new(), scanned: 0, last_media_id: after };
+
+        while batch.media_ids.len() < limit as usize {
+            let page = self
+                .repository
+                .find_after(batch.last_media_id, limit)
+                .await
+                .map_err(repository_error("Failed to scan media"))?;
+            let exhausted = page.len() < limit as usize;
+
+            for mut media in page {
+                batch.scanned += 1;
+                batch.last_media_id = Some(media.id);
+                if !needs_backfill(&media, kind, selection) {
+                    continue;
+                }
+
+                media.set_processing_status(ProcessingStatus::Pending);
+                self.repository.update(&media).await.map_err(repository_error(format!(
+                    "Failed to reset media {} for reprocessing",
+                    media.id
+                )))?;
+                batch.media_ids.push(media.id);
+                if batch.media_ids.len() == limit as usize {
+                    return Ok(batch);
+                }
+            }
+
+            if exhausted {
+                batch.last_media_id = None;
+                break;
+            }
+        }
+
+        Ok(batch)
+    }
+}
+
+fn is_video_kind(kind: VariantKind) -> bool {
+    VideoProcessor::VARIANT_NAMES.contains(&kind.name())
+}
+
+/// Whether processing `media` again would give it a `kind` variant it should have
+fn needs_backfill(media: &Media, kind: VariantKind, selection: BackfillSelection) -> bool {
+    let content_type = media.media_type.mime_type();
+    let produces = if is_video_kind(kind) {
+        VideoProcessor::supports(content_type)
+    } else {
+        ImageVariantEncoder::variant_names(content_type).contains(&kind.name())
+    };
+    if !produces {
+        return false;
+    }
+
+    match selection {
+        BackfillSelection::Missing => {
+            media.processing_status == ProcessingStatus::Complete
+                && !media.variants.iter().any(|variant| variant.name == kind.name())
+        }
+        BackfillSelection::Failed => media.processing_status == ProcessingStatus::Failed,
+        BackfillSelection::All => {
+            matches!(media.processing_status, ProcessingStatus::Complete | ProcessingStatus::Failed)
+        }
+    }
+}
+
+/// Progress of the variant backfill running on this instance, or the last one
+///
+/// Only one backfill runs at a time. Cloning is cheap; clones share the same state.
+#[derive(Debug, Clone, Default)]
+pub struct VariantBackfills {
+    progress: Arc<Mutex<Option<VariantBackfillProgress>>>,
+}
+
+impl VariantBackfills {
+    /// Create a tracker with no backfill recorded
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the start of a backfill
+    ///
+    /// # Errors
+    /// * `Conflict` - Another backfill is still running
+    pub fn start(
+        &self,
+        request: &BackfillVariantsRequest,
+        batch_size: u32,
+    ) -> Result<VariantBackfillProgress, AppError> {
+        let mut progress = self.lock();
+        if progress.as_ref().is_some_and(|p| p.state == BackfillState::Running) {
+            return Err(AppError::Conflict {
+                message: "A variant backfill is already running".to_string(),
+            });
+        }
+
+        let started = VariantBackfillProgress {
+            kind: request.kind,
+            status: request.status,
+            state: BackfillState::Running,
+            batch_size,
+            scanned: 0,
+            enqueued: 0,
+            processed: 0,
+            failed: 0,
+            last_media_id: None,
+            started_at: to_rfc3339(SystemTime::now()),
+            finished_at: None,
+            error: None,
+        };
+        *progress = Some(started.clone());
+        Ok(started)
+    }
+
+    /// Add the outcome of one batch, returning the running totals
+    pub fn record_batch(
+        &self,
+        batch: &BackfillBatch,
+        processed: u64,
+        failed: u64,
+    ) -> Option<VariantBackfillProgress> {
+        let mut progress = self.lock();
+        let current = progress.as_mut()?;
+        current.scanned += batch.scanned;
+        current.enqueued += batch.media_ids.len() as u64;
+        current.processed += processed;
+        current.failed += failed;
+        current.last_media_id = batch.last_media_id.or(current.last_media_id);
+        Some(current.clone())
+    }
+
+    /// Record the end of the running backfill, failed if `error` is set
+    pub fn finish(&self, error: Option<String>) {
+        if let Some(current) = self.lock().as_mut() {
+            current.state =
+                if error.is_some() { BackfillState::Failed } else { BackfillState::Completed };
+            current.finished_at = Some(to_rfc3339(SystemTime::now()));
+            current.error = error;
+        }
+    }
+
+    /// Progress of the running or last backfill
+    #[must_use]
+    pub fn current(&self) -> Option<VariantBackfillProgress> {
+        self.lock().clone()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Option<VariantBackfillProgress>> {
+        self.progress.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        domain::{
+            entities::{MediaVariant, UnsavedMedia, UserId},
+            value_objects::{ContentHash, MediaType},
+        },
+        test_utils::mocks::InMemoryMediaRepository,
+    };
+
+    const HASH: &str = "abcdef1234567890abcdef1234567890abcdef1234567890abcdef1234567890";
+
+    async fn save(
+        repository: &InMemoryMediaRepository,
+        mime_type: &str,
+        status: ProcessingStatus,
+        variants: &[&str],
+    ) -> MediaId {
+        let id = repository
+            .save(&UnsavedMedia::new(
+                ContentHash::new(HASH).unwrap(),
+                "file".to_string(),
+                MediaType::new(mime_type),
+                "ab/cd/ef/file".to_string(),
+                1024,
+                UserId::new(),
+            ))
+            .await
+            .unwrap();
+        let mut media = repository.find_by_id(id).await.unwrap().unwrap();
+        media.processing_status = status;
+        media.variants = variants
+            .iter()
+            .map(|name| MediaVariant {
+                name: (*name).to_string(),
+                content_hash: ContentHash::new(HASH).unwrap(),
+                media_type: MediaType::new("image/webp"),
+                file_size: 10,
+            })
+            .collect();
+        repository.update(&media).await.unwrap();
+        id
+    }
+
+    #[tokio::test]
+    async fn test_claim_batch_resets_media_missing_the_variant() {
+        let repository = Arc::new(InMemoryMediaRepository::new());
+        let missing = save(&repository, "image/png", ProcessingStatus::Complete, &["avif"]).await;
+        let _has_it =
+            save(&repository, "image/png", ProcessingStatus::Complete, &["webp", "avif"]).await;
+        let _webp_source = save(&repository, "image/webp", ProcessingStatus::Complete, &[]).await;
+        let _failed = save(&repository, "image/jpeg", ProcessingStatus::Failed, &[]).await;
+        let _text = save(&repository, "text/plain", ProcessingStatus::Complete, &[]).await;
+        let use_case = BackfillVariantsUseCase::new(repository.clone());
+
+        let batch = use_case
+            .claim_batch(VariantKind::Webp, BackfillSelection::Missing, None, 10)
+            .await
+            .unwrap();
+
+        assert_eq!(batch.media_ids, vec![missing]);
+        assert_eq!(batch.scanned, 5);
+        assert_eq!(batch.last_media_id, None);
+        let media = repository.find_by_id(missing).await.unwrap().unwrap();
+        assert_eq!(media.processing_status, ProcessingStatus::Pending);
+    }
+
+    #[tokio::test]
+    async fn test_claim_batch_of_thumbnails_includes_webp_sources() {
+        let repository = Arc::new(InMemoryMediaRepository::new());
+        let png =
+            save(&repository, "image/png", ProcessingStatus::Complete, &["webp", "avif"]).await;
+        let webp = save(&repository, "image/webp", ProcessingStatus::Complete, &["avif"]).await;
+        let _has_it =
+            save(&repository, "image/jpeg", ProcessingStatus::Complete, &["thumbnail"]).await;
+        let _video = save(&repository, "video/mp4", ProcessingStatus::Complete, &[]).await;
+        let use_case = BackfillVariantsUseCase::new(repository);
+        assert!(use_case.validate(VariantKind::Thumbnail).is_ok());
+
+        let batch = use_case
+            .claim_batch(VariantKind::Thumbnail, BackfillSelection::Missing, None, 10)
+            .await
+            .unwrap();
+
+        assert_eq!(batch.media_ids, vec![png, webp]);
+    }
+
+    #[tokio::test]
+    async fn test_claim_batch_resumes_after_last_media() {
+        let repository = Arc::new(InMemoryMediaRepository::new());
+        let first = save(&repository, "image/png", ProcessingStatus::Failed, &[]).await;
+        let second = save(&repository, "image/png", ProcessingStatus::Failed, &[]).await;
+        let use_case = BackfillVariantsUseCase::new(repository);
+
+        let batch = use_case
+            .claim_batch(VariantKind::Avif, BackfillSelection::Failed, None, 1)
+            .await
+            .unwrap();
+        assert_eq!(batch.media_ids, vec![first]);
+        assert_eq!(batch.last_media_id, Some(first));
+
+        let batch = use_case
+            .claim_batch(VariantKind::Avif, BackfillSelection::Failed, batch.last_media_id, 1)
+            .await
+            .unwrap();
+        assert_eq!(batch.media_ids, vec![second]);
+    }
+
+    #[tokio::test]
+    async fn test_claim_batch_of_all_regenerates_existing_variants() {
+        let repository = Arc::new(InMemoryMediaRepository::new());
+        let has_it = save(&repository, "image/png", ProcessingStatus::Complete, &["webp"]).await;
+        let failed = save(&repository, "image/jpeg", ProcessingStatus::Failed, &[]).await;
+        let _pending = save(&repository, "image/png", ProcessingStatus::Pending, &[]).await;
+        let _webp_source = save(&repository, "image/webp", ProcessingStatus::Complete, &[]).await;
+        let use_case = BackfillVariantsUseCase::new(repository);
+
+        let batch = use_case
+            .claim_batch(VariantKind::Webp, BackfillSelection::All, None, 10)
+            .await
+            .unwrap();
+
+        assert_eq!(batch.media_ids, vec![has_it, failed]);
+    }
+
+    #[test]
+    fn test_video_kinds_need_video_processing() {
+        let use_case = BackfillVariantsUseCase::new(Arc::new(InMemoryMediaRepository::new()));
+
+        assert!(matches!(use_case.validate(VariantKind::Poster), Err(AppError::BadRequest { .. })));
+        assert!(use_case.validate(VariantKind::Webp).is_ok());
+        assert!(use_case.with_video_processing(true).validate(VariantKind::Mp4).is_ok());
+    }
+
+    #[test]
+    fn test_only_one_backfill_runs_at_a_time() {
+        let backfills = VariantBackfills::new();
+        let request = BackfillVariantsRequest {
+            kind: VariantKind::Webp,
+            status: BackfillSelection::Missing,
+            batch_size: None,
+            batch_interval_ms: None,
+        };
+
+        backfills.start(&request, 25).unwrap();
+        assert!(matches!(backfills.start(&request, 25), Err(AppError::Conflict { .. })));
+
+        backfills.finish(None);
+        assert_eq!(backfills.current().unwrap().state, BackfillState::Completed);
+        assert!(backfills.start(&request, 25).is_ok());
+    }
+}
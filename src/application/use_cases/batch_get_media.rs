@@ -0,0 +1,133 @@
+use std::{collections::HashMap, sync::Arc};
+
+use super::{distinct_batch_ids, ensure_owner, repository_error};
+use crate::{
+    application::dto::{BatchGetMediaItem, BatchGetMediaResponse, BatchGetStatus, MediaDto},
+    domain::{
+        entities::{MediaId, UserId},
+        repositories::MediaRepository,
+    },
+    presentation::middleware::error::AppError,
+};
+
+/// Use case for retrieving the metadata of several media in one call
+///
+/// Missing media and media owned by another user are reported per ID instead of
+/// failing the whole batch.
+pub struct BatchGetMediaUseCase<R>
+where
+    R: MediaRepository + ?Sized,
+{
+    repository: Arc<R>,
+}
+
+impl<R> BatchGetMediaUseCase<R>
+where
+    R: MediaRepository + ?Sized,
+{
+    /// Create a new batch get media use case
+    pub fn new(repository: Arc<R>) -> Self {
+        Self { repository }
+    }
+
+    /// Look up every requested ID, ignoring repeats
+    ///
+    /// # Errors
+    /// * `BadRequest` - More than [`super::MAX_BATCH_IDS`] distinct IDs were requested
+    pub async fn execute(
+        &self,
+        ids: Vec<MediaId>,
+        requester: Option<UserId>,
+    ) -> Result<BatchGetMediaResponse, AppError> {
+        let distinct = distinct_batch_ids(ids)?;
+
+        tracing::info!("Getting {} media in one batch", distinct.len());
+
+        let mut found: HashMap<MediaId, _> = self
+            .repository
+            .find_by_ids(&distinct)
+            .await
+            .map_err(repository_error("Failed to query media"))?
+            .into_iter()
+            .map(|media| (media.id, media))
+            .collect();
+
+        let results = distinct
+            .into_iter()
+            .map(|id| match found.remove(&id) {
+                None => BatchGetMediaItem { id, status: BatchGetStatus::NotFound, media: None },
+                Some(media) if ensure_owner(&media, requester).is_err() => {
+                    BatchGetMediaItem { id, status: BatchGetStatus::Forbidden, media: None }
+                }
+                Some(media) => BatchGetMediaItem {
+                    id,
+                    status: BatchGetStatus::Found,
+                    media: Some(MediaDto::from(media)),
+                },
+            })
+            .collect();
+
+        Ok(BatchGetMediaResponse { results })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        domain::{
+            entities::UnsavedMedia,
+            value_objects::{ContentHash, MediaType},
+        },
+        test_utils::mocks::InMemoryMediaRepository,
+    };
+
+    fn media(id: i64, owner: UserId) -> crate::domain::entities::Media {
+        UnsavedMedia::new(
+            ContentHash::new(&format!("{id:064}")).unwrap(),
+            format!("photo-{id}.jpg"),
+            MediaType::new("image/jpeg"),
+            format!("aa/aa/aa/{id}"),
+            1024,
+            owner,
+        )
+        .into_media(MediaId::new(id))
+    }
+
+    #[tokio::test]
+    async fn test_batch_get_reports_each_id_in_request_order() {
+        let owner = UserId::new();
+        let repo = InMemoryMediaRepository::new()
+            .with_media(media(1, owner))
+            .with_media(media(2, UserId::new()))
+            .with_media(media(3, owner));
+        let use_case = BatchGetMediaUseCase::new(Arc::new(repo));
+
+        let ids = [3, 9, 2, 1, 3].map(MediaId::new).to_vec();
+        let response = use_case.execute(ids, Some(owner)).await.unwrap();
+
+        let statuses: Vec<_> = response.results.iter().map(|r| (r.id.as_i64(), r.status)).collect();
+        assert_eq!(
+            statuses,
+            vec![
+                (3, BatchGetStatus::Found),
+                (9, BatchGetStatus::NotFound),
+                (2, BatchGetStatus::Forbidden),
+                (1, BatchGetStatus::Found),
+            ]
+        );
+        assert_eq!(response.results[0].media.as_ref().unwrap().original_filename, "photo-3.jpg");
+        assert!(response.results[1].media.is_none());
+        assert!(response.results[2].media.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_batch_get_rejects_too_many_ids() {
+        let use_case = BatchGetMediaUseCase::new(Arc::new(InMemoryMediaRepository::new()));
+
+        let ids = (1..=101).map(MediaId::new).collect();
+        let result = use_case.execute(ids, None).await;
+
+        assert!(matches!(result, Err(AppError::BadRequest { .. })));
+    }
+}
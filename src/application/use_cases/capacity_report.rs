@@ -0,0 +1,153 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use super::repository_error;
+use crate::{
+    application::dto::{to_date, CapacityGrowthDto, CapacityReport},
+    domain::{
+        repositories::MediaRepository,
+        services::{Clock, SystemClock},
+        value_objects::{week_start, CapacityDimension, CapacityGrowth},
+    },
+    presentation::middleware::error::AppError,
+};
+
+/// Weeks covered when the request does not say
+pub const DEFAULT_CAPACITY_REPORT_WEEKS: u32 = 12;
+
+/// Most weeks one capacity report may cover
+pub const MAX_CAPACITY_REPORT_WEEKS: u32 = 104;
+
+/// Use case for reporting weekly growth of the media library by type and tenant
+///
+/// Replaces running analytics queries against the database by hand. Tenants are
+/// the clients whose tokens the media was uploaded with.
+pub struct CapacityReportUseCase<R>
+where
+    R: MediaRepository + ?Sized,
+{
+    repository: Arc<R>,
+    clock: Arc<dyn Clock>,
+}
+
+impl<R> CapacityReportUseCase<R>
+where
+    R: MediaRepository + ?Sized,
+{
+    /// Create a new capacity report use case
+    pub fn new(repository: Arc<R>) -> Self {
+        Self { repository, clock: Arc::new(SystemClock) }
+    }
+
+    /// Read the current week from `clock`; the default is the system clock
+    #[must_use]
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Report the last `weeks` weeks, counting the current one
+    ///
+    /// `weeks` is clamped to between 1 and [`MAX_CAPACITY_REPORT_WEEKS`].
+    ///
+    /// # Errors
+    /// * `Database` - Aggregating media failed
+    pub async fn execute(&self, weeks: u32) -> Result<CapacityReport, AppError> {
+        let weeks = weeks.clamp(1, MAX_CAPACITY_REPORT_WEEKS);
+        let since = week_start(self.clock.now()) - Duration::from_hours(7 * 24) * (weeks - 1);
+
+        let by_type = self.growth(CapacityDimension::MediaType, since).await?;
+        let by_tenant = self.growth(CapacityDimension::Tenant, since).await?;
+
+        Ok(CapacityReport { weeks, since: to_date(since), by_type, by_tenant })
+    }
+
+    async fn growth(
+        &self,
+        dimension: CapacityDimension,
+        since: std::time::SystemTime,
+    ) -> Result<Vec<CapacityGrowthDto>, AppError> {
+        let growth = self
+            .repository
+            .capacity_growth(dimension, since)
+            .await
+            .map_err(repository_error("Failed to aggregate capacity growth"))?;
+        Ok(growth.into_iter().map(to_dto).collect())
+    }
+}
+
+fn to_dto(growth: CapacityGrowth) -> CapacityGrowthDto {
+    CapacityGrowthDto {
+        week_start: to_date(growth.week_start),
+        key: growth.key,
+        media_added: growth.media_added,
+        bytes_added: growth.bytes_added,
+        total_media: growth.total_media,
+        total_bytes: growth.total_bytes,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        domain::{
+            entities::{Media, MediaId, UserId},
+            value_objects::{ContentHash, MediaType, ProcessingStatus},
+        },
+        test_utils::mocks::{InMemoryMediaRepository, ManualClock},
+    };
+    use std::time::SystemTime;
+
+    fn at(rfc3339: &str) -> SystemTime {
+        chrono::DateTime::parse_from_rfc3339(rfc3339).unwrap().into()
+    }
+
+    fn media(id: i64, tenant: &str, mime_type: &str, uploaded_at: &str) -> Media {
+        Media::with_id(
+            MediaId::new(id),
+            ContentHash::new(&format!("{id:064x}")).unwrap(),
+            format!("{id}.bin"),
+            MediaType::new(mime_type),
+            format!("media/{id}"),
+            1000,
+            ProcessingStatus::Complete,
+        )
+        .uploaded_by(UserId::new())
+        .uploaded_at(at(uploaded_at))
+        .tenant(Some(tenant.to_string()))
+        .build()
+    }
+
+    #[tokio::test]
+    async fn test_report_covers_recent_weeks_with_running_totals() {
+        let repository = InMemoryMediaRepository::new()
+            .with_media(media(1, "web-client", "image/png", "2024-12-02T09:00:00Z"))
+            .with_media(media(2, "web-client", "image/png", "2025-01-14T09:00:00Z"))
+            .with_media(media(3, "ios-client", "video/mp4", "2025-01-07T09:00:00Z"));
+        let use_case = CapacityReportUseCase::new(Arc::new(repository))
+            .with_clock(Arc::new(ManualClock::new(at("2025-01-15T12:00:00Z"))));
+
+        let report = use_case.execute(2).await.unwrap();
+
+        assert_eq!(report.since, "2025-01-06");
+        let by_type: Vec<_> = report
+            .by_type
+            .iter()
+            .map(|g| (g.week_start.as_str(), g.key.as_str(), g.media_added, g.total_media))
+            .collect();
+        assert_eq!(
+            by_type,
+            vec![("2025-01-06", "video/mp4", 1, 1), ("2025-01-13", "image/png", 1, 2)]
+        );
+        let by_tenant: Vec<_> = report
+            .by_tenant
+            .iter()
+            .map(|g| (g.week_start.as_str(), g.key.as_str(), g.total_media, g.total_bytes))
+            .collect();
+        assert_eq!(
+            by_tenant,
+            vec![("2025-01-06", "ios-client", 1, 1000), ("2025-01-13", "web-client", 2, 2000)]
+        );
+    }
+}
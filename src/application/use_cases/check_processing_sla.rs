@@ -0,0 +1,178 @@
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, SystemTime};
+
+use super::repository_error;
+use crate::{
+    domain::{entities::MediaId, repositories::MediaRepository, value_objects::ProcessingSla},
+    infrastructure::business_metrics,
+    presentation::middleware::error::AppError,
+};
+
+/// Most overdue media looked at by one check
+pub const MAX_OVERDUE_MEDIA: u32 = 100;
+
+/// Media found past the processing SLA by one check
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SlaCheck {
+    /// Every overdue media found, longest waiting first
+    pub overdue: Vec<MediaId>,
+    /// Those not reported by an earlier check
+    pub newly_overdue: Vec<MediaId>,
+}
+
+/// Use case for finding media still unprocessed past the processing SLA
+///
+/// Catches media whose processing never finishes, e.g. because the instance
+/// processing it was restarted, which [`ProcessMediaUseCase`](super::ProcessMediaUseCase)
+/// cannot report itself. Each overdue media is warned about once; it is reported
+/// again only if it becomes overdue a second time.
+pub struct CheckProcessingSlaUseCase<R>
+where
+    R: MediaRepository + ?Sized,
+{
+    repository: Arc<R>,
+    sla: ProcessingSla,
+    reported: Mutex<HashSet<MediaId>>,
+}
+
+impl<R> CheckProcessingSlaUseCase<R>
+where
+    R: MediaRepository + ?Sized,
+{
+    /// Create a new SLA check against `sla`
+    pub fn new(repository: Arc<R>, sla: ProcessingSla) -> Self {
+        Self { repository, sla, reported: Mutex::new(HashSet::new()) }
+    }
+
+    /// Find overdue media, warning about any not reported before
+    ///
+    /// Sets the `media_processing_sla_overdue` gauge to the number found, capped
+    /// at [`MAX_OVERDUE_MEDIA`]. Nothing is overdue while the SLA is disabled.
+    ///
+    /// # Errors
+    /// * `Database` - Querying unprocessed media failed
+    pub async fn execute(&self) -> Result<SlaCheck, AppError> {
+        let now = SystemTime::now();
+        let Some(cutoff) = self.sla.overdue_before(now) else {
+            return Ok(SlaCheck::default());
+        };
+
+        let overdue = self
+            .repository
+            .find_unprocessed_before(cutoff, MAX_OVERDUE_MEDIA)
+            .await
+            .map_err(repository_error("Failed to query unprocessed media"))?;
+        metrics::gauge!("media_processing_sla_overdue").set(overdue.len() as f64);
+
+        let mut reported = self.reported.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut check = SlaCheck::default();
+        for media in &overdue {
+            check.overdue.push(media.id);
+            if !reported.insert(media.id) {
+                continue;
+            }
+
+            let waited = now.duration_since(media.updated_at).unwrap_or(Duration::ZERO);
+            tracing::warn!(
+                "Media {} has been {} for {}s since its upload, over the processing SLA",
+                media.id,
+                media.processing_status,
+                waited.as_secs()
+            );
+            metrics::counter!(
+                "media_processing_sla_breaches_total",
+                "media_kind" => business_metrics::media_kind(&media.media_type),
+                "stage" => "unfinished"
+            )
+            .increment(1);
+            check.newly_overdue.push(media.id);
+        }
+
+        // Forget media that has since been processed, so it is reported again if reset
+        reported.retain(|id| check.overdue.contains(id));
+        Ok(check)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        domain::{
+            entities::{UnsavedMedia, UserId, PENDING_UPLOAD_PATH},
+            value_objects::{ContentHash, MediaType, ProcessingStatus},
+        },
+        test_utils::mocks::InMemoryMediaRepository,
+    };
+
+    async fn save(
+        repository: &InMemoryMediaRepository,
+        hash: char,
+        status: ProcessingStatus,
+        path: &str,
+        waited: Duration,
+    ) -> MediaId {
+        let id = repository
+            .save(&UnsavedMedia::new(
+                ContentHash::new(&hash.to_string().repeat(64)).unwrap(),
+                "file.png".to_string(),
+                MediaType::new("image/png"),
+                path.to_string(),
+                1024,
+                UserId::new(),
+            ))
+            .await
+            .unwrap();
+        let mut media = repository.find_by_id(id).await.unwrap().unwrap();
+        media.processing_status = status;
+        media.updated_at = SystemTime::now() - waited;
+        repository.update(&media).await.unwrap();
+        id
+    }
+
+    #[tokio::test]
+    async fn test_reports_each_overdue_media_once() {
+        let repository = Arc::new(InMemoryMediaRepository::new());
+        let stuck =
+            save(&repository, 'a', ProcessingStatus::Processing, "a/file", Duration::from_mins(10))
+                .await;
+        let _recent =
+            save(&repository, 'b', ProcessingStatus::Pending, "b/file", Duration::from_secs(5))
+                .await;
+        let _done =
+            save(&repository, 'c', ProcessingStatus::Complete, "c/file", Duration::from_mins(10))
+                .await;
+        let _awaiting_upload = save(
+            &repository,
+            'd',
+            ProcessingStatus::Pending,
+            PENDING_UPLOAD_PATH,
+            Duration::from_mins(10),
+        )
+        .await;
+        let use_case =
+            CheckProcessingSlaUseCase::new(repository, ProcessingSla::new(Duration::from_mins(5)));
+
+        let first = use_case.execute().await.unwrap();
+        assert_eq!(first.overdue, vec![stuck]);
+        assert_eq!(first.newly_overdue, vec![stuck]);
+
+        let second = use_case.execute().await.unwrap();
+        assert_eq!(second.overdue, vec![stuck]);
+        assert!(second.newly_overdue.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_disabled_sla_finds_nothing() {
+        let repository = Arc::new(InMemoryMediaRepository::new());
+        save(&repository, 'a', ProcessingStatus::Pending, "a/file", Duration::from_hours(24)).await;
+
+        let check = CheckProcessingSlaUseCase::new(repository, ProcessingSla::disabled())
+            .execute()
+            .await
+            .unwrap();
+
+        assert_eq!(check, SlaCheck::default());
+    }
+}
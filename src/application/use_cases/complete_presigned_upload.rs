@@ -0,0 +1,530 @@
+use std::sync::Arc;
+use tokio::io::AsyncRead;
+
+use super::{
+    apply_format_policy, discard_unsaved_content, repository_error, verify_content_type,
+    AllowedTypes,
+};
+use crate::{
+    application::{
+        dto::UploadMediaResponse,
+        events::{DomainEvent, EventBus, EventPublisher},
+    },
+    domain::{
+        entities::PresignedUploadSession,
+        repositories::{MediaChange, MediaRepository, UploadSessionRepository},
+        services::{Clock, SystemClock},
+        value_objects::{ContentHash, FormatPolicy, MediaType},
+    },
+    infrastructure::{
+        business_metrics::{BusinessMetrics, UploadFlow},
+        storage::{
+            utils::generate_content_hash_async, FileStorage, PresignedUrlError,
+            PresignedUrlService, StorageError,
+        },
+    },
+    presentation::middleware::error::AppError,
+};
+
+use super::UploadLocks;
+
+/// Use case for receiving the file behind a presigned upload URL
+///
+/// Resolves the upload token to its persisted session and fills in the pending
+/// media record created when the upload was initiated.
+pub struct CompletePresignedUploadUseCase<U, R, S>
+where
+    U: UploadSessionRepository + ?Sized,
+    R: MediaRepository + ?Sized,
+    S: FileStorage + ?Sized,
+{
+    sessions: Arc<U>,
+    repository: Arc<R>,
+    storage: Arc<S>,
+    presigned_service: PresignedUrlService,
+    upload_locks: UploadLocks,
+    clock: Arc<dyn Clock>,
+    allowed_types: AllowedTypes,
+    format_policy: FormatPolicy,
+    metrics: BusinessMetrics,
+    events: Arc<dyn EventPublisher>,
+}
+
+impl<U, R, S> CompletePresignedUploadUseCase<U, R, S>
+where
+    U: UploadSessionRepository + ?Sized,
+    R: MediaRepository + ?Sized,
+    S: FileStorage + ?Sized,
+{
+    /// Create a new complete presigned upload use case
+    pub fn new(
+        sessions: Arc<U>,
+        repository: Arc<R>,
+        storage: Arc<S>,
+        presigned_service: PresignedUrlService,
+    ) -> Self {
+        Self {
+            sessions,
+            repository,
+            storage,
+            presigned_service,
+            upload_locks: UploadLocks::new(),
+            clock: Arc::new(SystemClock),
+            allowed_types: AllowedTypes::default(),
+            format_policy: FormatPolicy::unrestricted(),
+            metrics: BusinessMetrics::disabled(),
+            events: Arc::new(EventBus::new()),
+        }
+    }
+
+    /// Share an upload lock registry with other upload paths
+    #[must_use]
+    pub fn with_upload_locks(mut self, upload_locks: UploadLocks) -> Self {
+        self.upload_locks = upload_locks;
+        self
+    }
+
+    /// Read the time from `clock` when recording completed uploads
+    ///
+    /// Session expiry is checked by the presigned URL service's own clock.
+    #[must_use]
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Only accept content of these types; an empty list accepts any type
+    #[must_use]
+    pub fn with_allowed_types(mut self, allowed_types: impl Into<AllowedTypes>) -> Self {
+        self.allowed_types = allowed_types.into();
+        self
+    }
+
+    /// Limit the formats each tenant accepts, converting images into an accepted format
+    ///
+    /// The tenant is the one recorded on the session when the upload was initiated.
+    #[must_use]
+    pub fn with_format_policy(mut self, format_policy: FormatPolicy) -> Self {
+        self.format_policy = format_policy;
+        self
+    }
+
+    /// Record the outcome of presigned uploads
+    #[must_use]
+    pub fn with_metrics(mut self, metrics: BusinessMetrics) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    /// Announce newly stored content to `events`
+    #[must_use]
+    pub fn with_events(mut self, events: Arc<dyn EventPublisher>) -> Self {
+        self.events = events;
+        self
+    }
+
+    /// Find the session for an upload token, rejecting unknown or expired sessions
+    ///
+    /// # Errors
+    /// Returns `NotFound` for unknown tokens, `BadRequest` for expired sessions and
+    /// `Authentication` if the signature was not issued for this session
+    pub async fn find_session(
+        &self,
+        upload_token: &str,
+        signature: &str,
+    ) -> Result<PresignedUploadSession, AppError> {
+        let session = self
+            .sessions
+            .find_by_token(upload_token)
+            .await
+            .map_err(|e| AppError::Internal {
+                message: format!("Failed to query upload session: {e}"),
+            })?
+            .ok_or_else(|| AppError::NotFound {
+                resource: format!("Upload session {upload_token}"),
+            })?;
+
+        match self.presigned_service.verify_session_signature(&session, signature) {
+            Ok(()) => Ok(session),
+            Err(PresignedUrlError::Expired { expired_at }) => {
+                tracing::info!("Upload session {} has expired", upload_token);
+                self.discard(&session).await;
+                Err(PresignedUrlError::Expired { expired_at }.into())
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Receive the uploaded file for a session
+    ///
+    /// If identical content is already stored, the pending record is dropped and the
+    /// existing media is returned instead.
+    ///
+    /// # Errors
+    /// Returns `BadRequest` if the file does not match the size or content type
+    /// declared when the upload was initiated, plus the errors of [`Self::find_session`]
+    pub async fn execute<Reader>(
+        &self,
+        upload_token: &str,
+        signature: &str,
+        file_reader: Reader,
+    ) -> Result<UploadMediaResponse, AppError>
+    where
+        Reader: AsyncRead + Send + Unpin,
+    {
+        let _session_guard = self.upload_locks.acquire_session(upload_token).await;
+        let session = self.find_session(upload_token, signature).await?;
+
+        let result = self.receive(&session, file_reader).await;
+        self.metrics.record_failure(UploadFlow::Presigned, &result);
+        result
+    }
+
+    /// Store the uploaded file and fill in the session's pending media record
+    async fn receive<Reader>(
+        &self,
+        session: &PresignedUploadSession,
+        file_reader: Reader,
+    ) -> Result<UploadMediaResponse, AppError>
+    where
+        Reader: AsyncRead + Send + Unpin,
+    {
+        let upload_token = session.upload_token.as_str();
+        let (content_hash, file_data, content_type) =
+            self.read_content(session, file_reader).await?;
+
+        let upload_guard = self.upload_locks.acquire(&content_hash).await;
+
+        if let Ok(Some(existing)) =
+            self.repository.find_by_content_hash(&content_hash, Some(session.user_id)).await
+        {
+            tracing::info!(
+                "Upload session {} matches existing media {}, dropping pending record {}",
+                upload_token,
+                existing.id,
+                session.media_id
+            );
+            self.discard(session).await;
+            self.metrics.upload_completed(UploadFlow::Presigned, file_data.len() as u64, true);
+
+            return Ok(UploadMediaResponse {
+                media_id: existing.id,
+                content_hash: content_hash.as_str().to_string(),
+                processing_status: existing.processing_status,
+                upload_url: None,
+            });
+        }
+
+        let mut media = self
+            .repository
+            .find_by_id(session.media_id)
+            .await
+            .map_err(repository_error("Failed to query media"))?
+            .ok_or_else(|| AppError::NotFound {
+                resource: format!("Media {}", session.media_id),
+            })?;
+
+        // Content already in storage belongs to other media or variants, so it must
+        // survive a failed save below
+        let stored_before = self.storage.exists(&content_hash).await.unwrap_or(true);
+        let mut cursor = std::io::Cursor::new(&file_data);
+        let storage_path =
+            self.storage.store(&content_hash, &mut cursor).await.map_err(|e| match e {
+                StorageError::StorageFull => {
+                    AppError::BadRequest { message: "Storage is full".to_string() }
+                }
+                _ => AppError::Internal { message: format!("Storage error: {e}") },
+            })?;
+
+        media.content_hash = content_hash.clone();
+        media.media_type = MediaType::new(&content_type);
+        media.media_path = storage_path;
+        media.file_size = file_data.len() as u64;
+        media.updated_at = self.clock.now();
+
+        let uploaded = DomainEvent::MediaUploaded {
+            media_id: media.id,
+            uploaded_by: media.uploaded_by,
+            media_type: media.media_type.clone(),
+            file_size: media.file_size,
+        };
+        let recorded = match self
+            .repository
+            .apply(MediaChange::Update(&media), std::slice::from_ref(&uploaded))
+            .await
+        {
+            Ok(recorded) => recorded,
+            Err(e) => {
+                if !stored_before {
+                    discard_unsaved_content(
+                        &*self.repository,
+                        &*self.storage,
+                        &content_hash,
+                        &upload_guard,
+                    )
+                    .await;
+                }
+                return Err(repository_error("Failed to save media metadata")(e));
+            }
+        };
+
+        if let Err(e) = self.sessions.delete(upload_token).await {
+            tracing::warn!("Failed to delete upload session {}: {}", upload_token, e);
+        }
+
+        tracing::info!("Presigned upload {} completed as media {}", upload_token, media.id);
+        self.metrics.upload_completed(UploadFlow::Presigned, media.file_size, false);
+        if !recorded.events_recorded {
+            self.events.publish(&uploaded);
+        }
+
+        Ok(UploadMediaResponse {
+            media_id: media.id,
+            content_hash: content_hash.as_str().to_string(),
+            processing_status: media.processing_status,
+            upload_url: None,
+        })
+    }
+
+    /// Read the uploaded file, check it against the session, and apply the
+    /// format policy of the tenant that initiated the session
+    async fn read_content<Reader>(
+        &self,
+        session: &PresignedUploadSession,
+        file_reader: Reader,
+    ) -> Result<(ContentHash, Vec<u8>, String), AppError>
+    where
+        Reader: AsyncRead + Send + Unpin,
+    {
+        let (content_hash, file_data) =
+            generate_content_hash_async(file_reader).await.map_err(|e| AppError::BadRequest {
+                message: format!("Failed to process file: {e}"),
+            })?;
+
+        if file_data.len() as u64 != session.expected_size {
+            return Err(AppError::BadRequest {
+                message: format!(
+                    "File size mismatch: expected {} bytes, got {} bytes",
+                    session.expected_size,
+                    file_data.len()
+                ),
+            });
+        }
+
+        let content_type = verify_content_type(
+            &file_data,
+            &session.filename,
+            Some(&session.content_type),
+            &self.allowed_types.get(),
+        )?;
+
+        apply_format_policy(
+            &self.format_policy,
+            session.tenant.as_deref(),
+            self.presigned_service.max_file_size(),
+            content_hash,
+            file_data,
+            content_type,
+            Some(session.content_type.clone()),
+        )
+        .await
+    }
+
+    /// Remove a session along with the pending media record it reserved
+    async fn discard(&self, session: &PresignedUploadSession) {
+        if let Err(e) = self.sessions.delete(&session.upload_token).await {
+            tracing::warn!("Failed to delete upload session {}: {}", session.upload_token, e);
+        }
+        if let Err(e) = self.repository.delete(session.media_id).await {
+            tracing::warn!("Failed to delete pending media {}: {}", session.media_id, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    use crate::{
+        application::{dto::InitiateUploadRequest, use_cases::InitiateUploadUseCase},
+        domain::entities::UserId,
+        infrastructure::{
+            persistence::InMemoryUploadSessionRepository,
+            storage::{FilesystemStorage, PresignedUrlConfig},
+        },
+        test_utils::mocks::InMemoryMediaRepository,
+    };
+
+    /// PNG signature padded to 11 bytes
+    const PNG_DATA: &[u8] = b"\x89PNG\r\n\x1a\nabc";
+
+    struct Fixture {
+        _temp_dir: TempDir,
+        sessions: Arc<InMemoryUploadSessionRepository>,
+        repository: Arc<InMemoryMediaRepository>,
+        storage: Arc<FilesystemStorage>,
+        presigned_service: PresignedUrlService,
+        /// Who initiates every upload
+        user_id: UserId,
+    }
+
+    impl Fixture {
+        fn new(expiration: Duration) -> Self {
+            let temp_dir = TempDir::new().unwrap();
+            let storage = Arc::new(FilesystemStorage::new(temp_dir.path()));
+            Self {
+                _temp_dir: temp_dir,
+                sessions: Arc::new(InMemoryUploadSessionRepository::new()),
+                repository: Arc::new(InMemoryMediaRepository::new()),
+                storage,
+                presigned_service: PresignedUrlService::new(PresignedUrlConfig {
+                    default_expiration: expiration,
+                    ..PresignedUrlConfig::default()
+                }),
+                user_id: UserId::new(),
+            }
+        }
+
+        /// Initiate an upload, returning its token and signature
+        async fn initiate(
+            &self,
+            filename: &str,
+            content_type: &str,
+            size: u64,
+        ) -> (String, String) {
+            let response = InitiateUploadUseCase::new(
+                self.repository.clone(),
+                self.sessions.clone(),
+                self.presigned_service.clone(),
+                1024 * 1024,
+            )
+            .execute(
+                InitiateUploadRequest {
+                    filename: filename.to_string(),
+                    content_type: content_type.to_string(),
+                    file_size: size,
+                    content_hash_prefix: None,
+                },
+                self.user_id,
+            )
+            .await
+            .unwrap();
+
+            let signature =
+                response.upload_url.split("signature=").nth(1).unwrap().split('&').next().unwrap();
+            (response.upload_token, signature.to_string())
+        }
+
+        fn use_case(
+            &self,
+        ) -> CompletePresignedUploadUseCase<
+            InMemoryUploadSessionRepository,
+            InMemoryMediaRepository,
+            FilesystemStorage,
+        > {
+            CompletePresignedUploadUseCase::new(
+                self.sessions.clone(),
+                self.repository.clone(),
+                self.storage.clone(),
+                self.presigned_service.clone(),
+            )
+        }
+    }
+
+    #[tokio::test]
+    async fn test_upload_fills_in_pending_media_record() {
+        let fixture = Fixture::new(Duration::from_mins(15));
+        let (token, signature) = fixture.initiate("image.png", "image/png", 11).await;
+        let pending_id = fixture.sessions.find_by_token(&token).await.unwrap().unwrap().media_id;
+
+        let response = fixture
+            .use_case()
+            .execute(&token, &signature, Cursor::new(PNG_DATA.to_vec()))
+            .await
+            .unwrap();
+
+        assert_eq!(response.media_id, pending_id);
+        let media = fixture.repository.find_by_id(pending_id).await.unwrap().unwrap();
+        assert_eq!(media.content_hash.as_str(), response.content_hash);
+        assert_eq!(media.file_size, 11);
+        assert!(!media.is_awaiting_upload());
+
+        // Sessions are single use
+        assert!(fixture.sessions.find_by_token(&token).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_unknown_session_is_rejected() {
+        let fixture = Fixture::new(Duration::from_mins(15));
+
+        let result = fixture
+            .use_case()
+            .execute("upload_unknown", "sig", Cursor::new(b"data".to_vec()))
+            .await;
+
+        assert!(matches!(result, Err(AppError::NotFound { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_expired_session_is_rejected_and_discarded() {
+        let fixture = Fixture::new(Duration::ZERO);
+        let (token, signature) = fixture.initiate("image.png", "image/png", 11).await;
+        let pending_id = fixture.sessions.find_by_token(&token).await.unwrap().unwrap().media_id;
+
+        let result =
+            fixture.use_case().execute(&token, &signature, Cursor::new(PNG_DATA.to_vec())).await;
+
+        assert!(matches!(result, Err(AppError::BadRequest { .. })));
+        assert!(fixture.sessions.find_by_token(&token).await.unwrap().is_none());
+        assert!(fixture.repository.find_by_id(pending_id).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_forged_signature_is_rejected() {
+        let fixture = Fixture::new(Duration::from_mins(15));
+        let (token, _) = fixture.initiate("image.png", "image/png", 11).await;
+
+        let result =
+            fixture.use_case().execute(&token, "forged", Cursor::new(PNG_DATA.to_vec())).await;
+
+        assert!(matches!(result, Err(AppError::Authentication { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_size_mismatch_is_rejected() {
+        let fixture = Fixture::new(Duration::from_mins(15));
+        let (token, signature) = fixture.initiate("image.png", "image/png", 11).await;
+
+        let result =
+            fixture.use_case().execute(&token, &signature, Cursor::new(b"hello".to_vec())).await;
+
+        assert!(matches!(result, Err(AppError::BadRequest { .. })));
+        assert!(fixture.sessions.find_by_token(&token).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_content_reuses_existing_media() {
+        let fixture = Fixture::new(Duration::from_mins(15));
+        let (first_token, first_signature) = fixture.initiate("a.png", "image/png", 11).await;
+        let (second_token, second_signature) = fixture.initiate("b.png", "image/png", 11).await;
+        let second_pending =
+            fixture.sessions.find_by_token(&second_token).await.unwrap().unwrap().media_id;
+
+        let first = fixture
+            .use_case()
+            .execute(&first_token, &first_signature, Cursor::new(PNG_DATA.to_vec()))
+            .await
+            .unwrap();
+        let second = fixture
+            .use_case()
+            .execute(&second_token, &second_signature, Cursor::new(PNG_DATA.to_vec()))
+            .await
+            .unwrap();
+
+        assert_eq!(second.media_id, first.media_id);
+        assert!(fixture.repository.find_by_id(second_pending).await.unwrap().is_none());
+    }
+}
@@ -1,8 +1,17 @@
 use std::sync::Arc;
 use tracing::{info, warn};
 
+use super::{distinct_batch_ids, ensure_owner, release_variant, UploadLocks};
 use crate::{
-    domain::{entities::MediaId, repositories::MediaRepository},
+    application::{
+        dto::{BatchDeleteMediaItem, BatchDeleteMediaResponse, BatchDeleteStatus},
+        events::{DomainEvent, EventBus, EventPublisher},
+    },
+    domain::{
+        entities::{MediaId, UserId},
+        repositories::{MediaChange, MediaRepository, VariantRepository},
+        value_objects::ContentHash,
+    },
     infrastructure::storage::FileStorage,
     presentation::middleware::error::AppError,
 };
@@ -13,26 +22,53 @@ use crate::{
 /// - Validating the media exists
 /// - Removing the file from storage
 /// - Removing the database record
+/// - Releasing its variants, deleting blobs no other media shares
 /// - Handling partial failures gracefully
-pub struct DeleteMediaUseCase<R: ?Sized, S> {
+pub struct DeleteMediaUseCase<R: ?Sized, S: ?Sized, V: ?Sized> {
     repository: Arc<R>,
     storage: Arc<S>,
+    variants: Arc<V>,
+    events: Arc<dyn EventPublisher>,
+    upload_locks: UploadLocks,
 }
 
-impl<R: ?Sized, S> DeleteMediaUseCase<R, S>
+impl<R: ?Sized, S: ?Sized, V: ?Sized> DeleteMediaUseCase<R, S, V>
 where
     R: MediaRepository,
     S: FileStorage,
+    V: VariantRepository,
 {
     /// Create a new delete media use case
-    pub fn new(repository: Arc<R>, storage: Arc<S>) -> Self {
-        Self { repository, storage }
+    pub fn new(repository: Arc<R>, storage: Arc<S>, variants: Arc<V>) -> Self {
+        Self {
+            repository,
+            storage,
+            variants,
+            events: Arc::new(EventBus::new()),
+            upload_locks: UploadLocks::new(),
+        }
+    }
+
+    /// Share the upload lock registry, so content is never deleted while an
+    /// upload of the same content is deduplicating against it
+    #[must_use]
+    pub fn with_upload_locks(mut self, upload_locks: UploadLocks) -> Self {
+        self.upload_locks = upload_locks;
+        self
+    }
+
+    /// Announce deleted media to `events`
+    #[must_use]
+    pub fn with_events(mut self, events: Arc<dyn EventPublisher>) -> Self {
+        self.events = events;
+        self
     }
 
     /// Execute the delete media use case
     ///
     /// # Arguments
     /// * `media_id` - The ID of the media to delete
+    /// * `requester` - The authenticated user, who must have uploaded the media
     ///
     /// # Returns
     /// * `Ok(())` if the media was successfully deleted
@@ -40,8 +76,13 @@ where
     ///
     /// # Errors
     /// * `NotFound` - Media with the given ID doesn't exist
-    /// * `Internal` - Storage or database operation failed
-    pub async fn execute(&self, media_id: MediaId) -> Result<(), AppError>
+    /// * `Authorization` - The media was uploaded by another user
+    /// * `Internal` - The database operation failed; storage failures are only logged
+    pub async fn execute(
+        &self,
+        media_id: MediaId,
+        requester: Option<UserId>,
+    ) -> Result<(), AppError>
     where
         R::Error: Into<AppError>,
         S: FileStorage,
@@ -53,6 +94,7 @@ where
             self.repository.find_by_id(media_id).await.map_err(Into::into)?.ok_or_else(|| {
                 AppError::NotFound { resource: format!("Media with ID {media_id}") }
             })?;
+        ensure_owner(&media, requester)?;
 
         info!(
             "Found media to delete: {} (hash: {})",
@@ -60,36 +102,27 @@ where
             media.content_hash.as_str()
         );
 
-        // Delete from storage first - if this fails, we haven't modified the database yet
-        let storage_deleted = match self.storage.delete(&media.content_hash).await {
-            Ok(deleted) => {
-                if deleted {
-                    info!(
-                        "Successfully deleted file from storage: {}",
-                        media.content_hash.as_str()
-                    );
-                } else {
-                    warn!(
-                        "File not found in storage (may have been already deleted): {}",
-                        media.content_hash.as_str()
-                    );
-                }
-                deleted
-            }
-            Err(e) => {
-                warn!("Failed to delete file from storage: {}", e);
-                // Continue with database deletion even if storage deletion failed
-                // This handles cases where the file might have been manually deleted
-                false
-            }
-        };
-
-        // Delete from database
-        let db_deleted = self.repository.delete(media_id).await.map_err(Into::into)?;
+        // Delete the record first so the reference check below no longer counts it
+        let deleted = DomainEvent::MediaDeleted { media_id, uploaded_by: media.uploaded_by };
+        let recorded = self
+            .repository
+            .apply(MediaChange::Delete(media_id), std::slice::from_ref(&deleted))
+            .await
+            .map_err(Into::into)?;
+        let db_deleted = recorded.outcome;
 
         if !db_deleted {
             return Err(AppError::NotFound { resource: format!("Media with ID {media_id}") });
         }
+        if !recorded.events_recorded {
+            self.events.publish(&deleted);
+        }
+
+        let storage_deleted = self.release_content(&media.content_hash).await;
+
+        for variant in &media.variants {
+            release_variant(&*self.repository, &*self.variants, &*self.storage, variant).await;
+        }
 
         info!(
             "Media deletion completed: ID={}, storage_deleted={}, db_deleted={}",
@@ -98,6 +131,99 @@ where
 
         Ok(())
     }
+
+    /// Delete several media, each on its own, reporting the outcome per ID
+    ///
+    /// A media that is missing, owned by another user or fails to delete doesn't
+    /// stop the others; repeated IDs are deleted once.
+    ///
+    /// # Errors
+    /// * `BadRequest` - More than [`super::MAX_BATCH_IDS`] distinct IDs were requested
+    pub async fn execute_many(
+        &self,
+        ids: Vec<MediaId>,
+        requester: Option<UserId>,
+    ) -> Result<BatchDeleteMediaResponse, AppError>
+    where
+        R::Error: Into<AppError>,
+    {
+        let ids = distinct_batch_ids(ids)?;
+        info!("Deleting {} media in one batch", ids.len());
+
+        let mut results = Vec::with_capacity(ids.len());
+        for id in ids {
+            let (status, error) = match self.execute(id, requester).await {
+                Ok(()) => (BatchDeleteStatus::Deleted, None),
+                Err(AppError::NotFound { .. }) => (BatchDeleteStatus::NotFound, None),
+                Err(AppError::Authorization { .. }) => (BatchDeleteStatus::Forbidden, None),
+                Err(e) => {
+                    warn!("Failed to delete media {} in batch: {}", id, e);
+                    (BatchDeleteStatus::Failed, Some(e.to_string()))
+                }
+            };
+            results.push(BatchDeleteMediaItem { id, status, error });
+        }
+
+        Ok(BatchDeleteMediaResponse { results })
+    }
+
+    /// Delete a deleted media's content from storage unless it is still referenced
+    ///
+    /// Deduplicated uploads share one blob between media records, and a variant can
+    /// have the same hash as an original. The blob is kept while any record or
+    /// variant registration still points at it, and also when that cannot be
+    /// determined: a leaked blob wastes space, a missing one breaks downloads.
+    ///
+    /// The check and the delete hold the upload lock of the content, so an upload
+    /// reusing the blob either saves its record before the check or stores the
+    /// blob again after the delete. The blob is kept if that lock could not be
+    /// taken on every instance.
+    async fn release_content(&self, hash: &ContentHash) -> bool {
+        let upload_guard = self.upload_locks.acquire(hash).await;
+        if !upload_guard.is_exclusive() {
+            warn!("Content {} could not be locked on every instance, leaving it in storage", hash);
+            return false;
+        }
+
+        match self.repository.exists_by_content_hash(hash).await {
+            Ok(false) => {}
+            Ok(true) => {
+                info!("Content {} is shared with other media, leaving it in storage", hash);
+                return false;
+            }
+            Err(e) => {
+                warn!("Failed to check whether content {} is still referenced: {}", hash, e);
+                return false;
+            }
+        }
+
+        match self.variants.reference_count(hash).await {
+            Ok(0) => {}
+            Ok(_) => {
+                info!("Content {} is also a variant, leaving it in storage", hash);
+                return false;
+            }
+            Err(e) => {
+                warn!("Failed to check whether content {} is a variant: {}", hash, e);
+                return false;
+            }
+        }
+
+        match self.storage.delete(hash).await {
+            Ok(true) => {
+                info!("Successfully deleted file from storage: {}", hash);
+                true
+            }
+            Ok(false) => {
+                warn!("File not found in storage (may have been already deleted): {}", hash);
+                false
+            }
+            Err(e) => {
+                warn!("Failed to delete file from storage: {}", e);
+                false
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -105,10 +231,11 @@ mod tests {
     use super::*;
     use crate::{
         domain::{
-            entities::{Media, UserId},
+            entities::{Media, MediaVariant, UserId},
+            repositories::{ContentLease, ContentLockRepository},
             value_objects::{ContentHash, MediaType, ProcessingStatus},
         },
-        infrastructure::storage::StorageError,
+        infrastructure::{persistence::InMemoryVariantRepository, storage::StorageError},
         test_utils::mocks::InMemoryMediaRepository,
     };
     use async_trait::async_trait;
@@ -144,10 +271,11 @@ mod tests {
 
     #[async_trait]
     impl FileStorage for MockStorage {
-        async fn store<R>(&self, hash: &ContentHash, mut reader: R) -> Result<String, StorageError>
-        where
-            R: AsyncRead + Send + Unpin,
-        {
+        async fn store(
+            &self,
+            hash: &ContentHash,
+            reader: &mut (dyn AsyncRead + Send + Unpin),
+        ) -> Result<String, StorageError> {
             let mut buffer = Vec::new();
             reader
                 .read_to_end(&mut buffer)
@@ -216,6 +344,11 @@ mod tests {
         }
     }
 
+    /// The user uploading the test media
+    fn owner() -> UserId {
+        UserId::from_uuid(uuid::Uuid::from_u128(1))
+    }
+
     fn create_test_media(id: i64) -> Media {
         let content_hash =
             ContentHash::new("abcdef1234567890abcdef1234567890abcdef1234567890abcdef1234567890")
@@ -230,7 +363,7 @@ mod tests {
             1024,
             ProcessingStatus::Complete,
         )
-        .uploaded_by(UserId::new())
+        .uploaded_by(owner())
         .build()
     }
 
@@ -243,8 +376,12 @@ mod tests {
         let storage =
             Arc::new(MockStorage::new().with_file(content_hash.as_str(), b"test content".to_vec()));
 
-        let delete_use_case = DeleteMediaUseCase::new(repository, storage.clone());
-        let result = delete_use_case.execute(MediaId::new(1)).await;
+        let delete_use_case = DeleteMediaUseCase::new(
+            repository,
+            storage.clone(),
+            Arc::new(InMemoryVariantRepository::new()),
+        );
+        let result = delete_use_case.execute(MediaId::new(1), Some(owner())).await;
 
         assert!(result.is_ok());
 
@@ -252,13 +389,59 @@ mod tests {
         assert!(!storage.exists(&content_hash).await.unwrap());
     }
 
+    #[tokio::test]
+    async fn test_delete_media_of_another_user_is_rejected() {
+        let media = create_test_media(1);
+        let content_hash = media.content_hash.clone();
+
+        let repository = Arc::new(InMemoryMediaRepository::new().with_media(media));
+        let storage =
+            Arc::new(MockStorage::new().with_file(content_hash.as_str(), b"test content".to_vec()));
+
+        let delete_use_case = DeleteMediaUseCase::new(
+            repository.clone(),
+            storage.clone(),
+            Arc::new(InMemoryVariantRepository::new()),
+        );
+        let result = delete_use_case.execute(MediaId::new(1), Some(UserId::new())).await;
+
+        assert!(matches!(result, Err(AppError::Authorization { .. })));
+        assert!(repository.find_by_id(MediaId::new(1)).await.unwrap().is_some());
+        assert!(storage.exists(&content_hash).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_delete_media_without_requester_is_rejected() {
+        let media = create_test_media(1);
+        let content_hash = media.content_hash.clone();
+
+        let repository = Arc::new(InMemoryMediaRepository::new().with_media(media));
+        let storage =
+            Arc::new(MockStorage::new().with_file(content_hash.as_str(), b"test content".to_vec()));
+
+        let delete_use_case = DeleteMediaUseCase::new(
+            repository.clone(),
+            storage.clone(),
+            Arc::new(InMemoryVariantRepository::new()),
+        );
+        let result = delete_use_case.execute(MediaId::new(1), None).await;
+
+        assert!(matches!(result, Err(AppError::Authorization { .. })));
+        assert!(repository.find_by_id(MediaId::new(1)).await.unwrap().is_some());
+        assert!(storage.exists(&content_hash).await.unwrap());
+    }
+
     #[tokio::test]
     async fn test_delete_media_not_found() {
         let repository = Arc::new(InMemoryMediaRepository::new());
         let storage = Arc::new(MockStorage::new());
 
-        let delete_use_case = DeleteMediaUseCase::new(repository, storage);
-        let result = delete_use_case.execute(MediaId::new(999)).await;
+        let delete_use_case = DeleteMediaUseCase::new(
+            repository,
+            storage,
+            Arc::new(InMemoryVariantRepository::new()),
+        );
+        let result = delete_use_case.execute(MediaId::new(999), Some(owner())).await;
 
         assert!(result.is_err());
         if let Err(AppError::NotFound { resource }) = result {
@@ -280,8 +463,12 @@ mod tests {
                 .with_delete_failure(),
         );
 
-        let delete_use_case = DeleteMediaUseCase::new(repository.clone(), storage);
-        let result = delete_use_case.execute(MediaId::new(1)).await;
+        let delete_use_case = DeleteMediaUseCase::new(
+            repository.clone(),
+            storage,
+            Arc::new(InMemoryVariantRepository::new()),
+        );
+        let result = delete_use_case.execute(MediaId::new(1), Some(owner())).await;
 
         // Should succeed despite storage failure
         assert!(result.is_ok());
@@ -298,8 +485,12 @@ mod tests {
         let repository = Arc::new(InMemoryMediaRepository::new().with_media(media));
         let storage = Arc::new(MockStorage::new()); // No file in storage
 
-        let delete_use_case = DeleteMediaUseCase::new(repository.clone(), storage);
-        let result = delete_use_case.execute(MediaId::new(1)).await;
+        let delete_use_case = DeleteMediaUseCase::new(
+            repository.clone(),
+            storage,
+            Arc::new(InMemoryVariantRepository::new()),
+        );
+        let result = delete_use_case.execute(MediaId::new(1), Some(owner())).await;
 
         // Should succeed even if file not in storage
         assert!(result.is_ok());
@@ -309,12 +500,45 @@ mod tests {
         assert!(media_check.is_none());
     }
 
+    #[tokio::test]
+    async fn test_execute_many_reports_each_id() {
+        let owner = UserId::new();
+        let own = Media { uploaded_by: owner, ..create_test_media(1) };
+        let foreign = create_test_media(2);
+        let repository =
+            Arc::new(InMemoryMediaRepository::new().with_media(own).with_media(foreign));
+
+        let delete_use_case = DeleteMediaUseCase::new(
+            repository.clone(),
+            Arc::new(MockStorage::new()),
+            Arc::new(InMemoryVariantRepository::new()),
+        );
+        let ids = [1, 2, 3, 1].map(MediaId::new).to_vec();
+        let response = delete_use_case.execute_many(ids, Some(owner)).await.unwrap();
+
+        let statuses: Vec<_> = response.results.iter().map(|r| (r.id.as_i64(), r.status)).collect();
+        assert_eq!(
+            statuses,
+            vec![
+                (1, BatchDeleteStatus::Deleted),
+                (2, BatchDeleteStatus::Forbidden),
+                (3, BatchDeleteStatus::NotFound),
+            ]
+        );
+        assert!(repository.find_by_id(MediaId::new(1)).await.unwrap().is_none());
+        assert!(repository.find_by_id(MediaId::new(2)).await.unwrap().is_some());
+    }
+
     #[tokio::test]
     async fn test_delete_use_case_creation() {
         let repository = Arc::new(InMemoryMediaRepository::new());
         let storage = Arc::new(MockStorage::new());
 
-        let delete_use_case = DeleteMediaUseCase::new(repository, storage);
+        let delete_use_case = DeleteMediaUseCase::new(
+            repository,
+            storage,
+            Arc::new(InMemoryVariantRepository::new()),
+        );
 
         // Test that the use case was created successfully
         // This validates the constructor works
@@ -327,14 +551,18 @@ mod tests {
         let repository = Arc::new(InMemoryMediaRepository::new().with_media(media));
         let storage = Arc::new(MockStorage::new());
 
-        let delete_use_case = DeleteMediaUseCase::new(repository, storage);
+        let delete_use_case = DeleteMediaUseCase::new(
+            repository,
+            storage,
+            Arc::new(InMemoryVariantRepository::new()),
+        );
 
         // First delete should succeed
-        let result1 = delete_use_case.execute(MediaId::new(1)).await;
+        let result1 = delete_use_case.execute(MediaId::new(1), Some(owner())).await;
         assert!(result1.is_ok());
 
         // Second delete should fail with NotFound
-        let result2 = delete_use_case.execute(MediaId::new(1)).await;
+        let result2 = delete_use_case.execute(MediaId::new(1), Some(owner())).await;
         assert!(result2.is_err());
         if let Err(AppError::NotFound { resource }) = result2 {
             assert!(resource.contains("Media with ID 1"));
@@ -360,7 +588,7 @@ mod tests {
             1024,
             ProcessingStatus::Complete,
         )
-        .uploaded_by(UserId::new())
+        .uploaded_by(owner())
         .build();
 
         let media2 = Media::with_id(
@@ -372,7 +600,7 @@ mod tests {
             2048,
             ProcessingStatus::Complete,
         )
-        .uploaded_by(UserId::new())
+        .uploaded_by(owner())
         .build();
 
         let repository =
@@ -383,11 +611,15 @@ mod tests {
                 .with_file(content_hash2.as_str(), b"video content".to_vec()),
         );
 
-        let delete_use_case = DeleteMediaUseCase::new(repository, storage.clone());
+        let delete_use_case = DeleteMediaUseCase::new(
+            repository,
+            storage.clone(),
+            Arc::new(InMemoryVariantRepository::new()),
+        );
 
         // Delete both media files
-        let result1 = delete_use_case.execute(MediaId::new(1)).await;
-        let result2 = delete_use_case.execute(MediaId::new(2)).await;
+        let result1 = delete_use_case.execute(MediaId::new(1), Some(owner())).await;
+        let result2 = delete_use_case.execute(MediaId::new(2), Some(owner())).await;
 
         assert!(result1.is_ok());
         assert!(result2.is_ok());
@@ -396,4 +628,182 @@ mod tests {
         assert!(!storage.exists(&content_hash1).await.unwrap());
         assert!(!storage.exists(&content_hash2).await.unwrap());
     }
+
+    #[tokio::test]
+    async fn test_shared_content_deleted_with_last_media() {
+        let first = create_test_media(1);
+        let second = create_test_media(2);
+        let content_hash = first.content_hash.clone();
+
+        let repository =
+            Arc::new(InMemoryMediaRepository::new().with_media(first).with_media(second));
+        let storage =
+            Arc::new(MockStorage::new().with_file(content_hash.as_str(), b"shared".to_vec()));
+        let delete_use_case = DeleteMediaUseCase::new(
+            repository,
+            storage.clone(),
+            Arc::new(InMemoryVariantRepository::new()),
+        );
+
+        delete_use_case.execute(MediaId::new(1), Some(owner())).await.unwrap();
+        assert!(storage.exists(&content_hash).await.unwrap());
+
+        delete_use_case.execute(MediaId::new(2), Some(owner())).await.unwrap();
+        assert!(!storage.exists(&content_hash).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_content_is_kept_for_an_upload_reusing_it_meanwhile() {
+        let media = create_test_media(1);
+        let content_hash = media.content_hash.clone();
+        let repository = Arc::new(InMemoryMediaRepository::new().with_media(media));
+        let storage =
+            Arc::new(MockStorage::new().with_file(content_hash.as_str(), b"test content".to_vec()));
+        let upload_locks = UploadLocks::new();
+        let delete_use_case = DeleteMediaUseCase::new(
+            repository.clone(),
+            storage.clone(),
+            Arc::new(InMemoryVariantRepository::new()),
+        )
+        .with_upload_locks(upload_locks.clone());
+
+        // An upload of the same content is deduplicating against the blob
+        let upload_guard = upload_locks.acquire(&content_hash).await;
+        let deletion =
+            tokio::spawn(
+                async move { delete_use_case.execute(MediaId::new(1), Some(owner())).await },
+            );
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        assert!(!deletion.is_finished());
+        assert!(storage.exists(&content_hash).await.unwrap());
+
+        // ...and saves its own record before letting go of the lock
+        repository.update(&create_test_media(2)).await.unwrap();
+        drop(upload_guard);
+        deletion.await.unwrap().unwrap();
+
+        assert!(repository.find_by_id(MediaId::new(1)).await.unwrap().is_none());
+        assert!(storage.exists(&content_hash).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_content_is_kept_when_other_instances_cannot_be_locked() {
+        struct UnavailableLocks;
+
+        #[async_trait]
+        impl ContentLockRepository for UnavailableLocks {
+            type Error = AppError;
+
+            async fn lock(&self, _hash: &ContentHash) -> Result<ContentLease, Self::Error> {
+                Err(AppError::Database { message: "connection refused".to_string() })
+            }
+        }
+
+        let media = create_test_media(1);
+        let content_hash = media.content_hash.clone();
+        let repository = Arc::new(InMemoryMediaRepository::new().with_media(media));
+        let storage =
+            Arc::new(MockStorage::new().with_file(content_hash.as_str(), b"test content".to_vec()));
+        let delete_use_case = DeleteMediaUseCase::new(
+            repository.clone(),
+            storage.clone(),
+            Arc::new(InMemoryVariantRepository::new()),
+        )
+        .with_upload_locks(UploadLocks::new().with_shared_locks(Arc::new(UnavailableLocks)));
+
+        delete_use_case.execute(MediaId::new(1), Some(owner())).await.unwrap();
+
+        assert!(repository.find_by_id(MediaId::new(1)).await.unwrap().is_none());
+        assert!(storage.exists(&content_hash).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_content_registered_as_variant_is_kept() {
+        let media = create_test_media(1);
+        let variant = MediaVariant {
+            name: "webp".to_string(),
+            content_hash: media.content_hash.clone(),
+            media_type: MediaType::new("image/webp"),
+            file_size: 7,
+        };
+
+        let variants = Arc::new(InMemoryVariantRepository::new());
+        variants.acquire(&variant).await.unwrap();
+        let repository = Arc::new(InMemoryMediaRepository::new().with_media(media));
+        let storage = Arc::new(
+            MockStorage::new().with_file(variant.content_hash.as_str(), b"variant".to_vec()),
+        );
+        let delete_use_case = DeleteMediaUseCase::new(repository, storage.clone(), variants);
+
+        delete_use_case.execute(MediaId::new(1), Some(owner())).await.unwrap();
+
+        assert!(storage.exists(&variant.content_hash).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_shared_variant_deleted_with_last_reference() {
+        let variant = MediaVariant {
+            name: "webp".to_string(),
+            content_hash: ContentHash::new(
+                "3333333333333333333333333333333333333333333333333333333333333333",
+            )
+            .unwrap(),
+            media_type: MediaType::new("image/webp"),
+            file_size: 7,
+        };
+        let mut first = create_test_media(1);
+        first.variants = vec![variant.clone()];
+        let mut second = create_test_media(2);
+        second.content_hash =
+            ContentHash::new("4444444444444444444444444444444444444444444444444444444444444444")
+                .unwrap();
+        second.variants = vec![variant.clone()];
+
+        let variants = Arc::new(InMemoryVariantRepository::new());
+        variants.acquire(&variant).await.unwrap();
+        variants.acquire(&variant).await.unwrap();
+        let repository =
+            Arc::new(InMemoryMediaRepository::new().with_media(first).with_media(second));
+        let storage = Arc::new(
+            MockStorage::new().with_file(variant.content_hash.as_str(), b"variant".to_vec()),
+        );
+        let delete_use_case =
+            DeleteMediaUseCase::new(repository, storage.clone(), variants.clone());
+
+        delete_use_case.execute(MediaId::new(1), Some(owner())).await.unwrap();
+        assert!(storage.exists(&variant.content_hash).await.unwrap());
+        assert_eq!(variants.reference_count(&variant.content_hash).await.unwrap(), 1);
+
+        delete_use_case.execute(MediaId::new(2), Some(owner())).await.unwrap();
+        assert!(!storage.exists(&variant.content_hash).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_unregistered_variant_is_kept() {
+        let variant = MediaVariant {
+            name: "avif".to_string(),
+            content_hash: ContentHash::new(
+                "5555555555555555555555555555555555555555555555555555555555555555",
+            )
+            .unwrap(),
+            media_type: MediaType::new("image/avif"),
+            file_size: 7,
+        };
+        let mut media = create_test_media(1);
+        media.variants = vec![variant.clone()];
+
+        let repository = Arc::new(InMemoryMediaRepository::new().with_media(media));
+        let storage = Arc::new(
+            MockStorage::new().with_file(variant.content_hash.as_str(), b"variant".to_vec()),
+        );
+        let delete_use_case = DeleteMediaUseCase::new(
+            repository,
+            storage.clone(),
+            Arc::new(InMemoryVariantRepository::new()),
+        );
+
+        delete_use_case.execute(MediaId::new(1), Some(owner())).await.unwrap();
+
+        assert!(storage.exists(&variant.content_hash).await.unwrap());
+    }
 }
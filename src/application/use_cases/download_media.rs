@@ -1,12 +1,17 @@
 use std::sync::Arc;
-use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio::io::AsyncRead;
 
+use super::{ensure_downloadable, repository_error, storage_error, variant_filename};
 use crate::{
     domain::{
-        entities::{Media, MediaId},
+        entities::{Media, MediaId, MediaVariant, UserId},
         repositories::MediaRepository,
+        value_objects::{ContentHash, DownloadRedirectPolicy, ImageRender},
+    },
+    infrastructure::{
+        processing::ImageVariantEncoder,
+        storage::{FileStorage, StorageError},
     },
-    infrastructure::storage::{FileStorage, StorageError},
     presentation::middleware::error::AppError,
 };
 
@@ -18,15 +23,96 @@ where
 {
     repository: Arc<R>,
     storage: Arc<S>,
+    redirect: DownloadRedirectPolicy,
 }
 
-/// Download response containing file data and metadata
-#[derive(Debug)]
+/// Download response containing a reader for the file data and its metadata
 pub struct DownloadResponse {
-    pub content: Vec<u8>,
+    /// The requested bytes: the whole representation, or only `range` when set;
+    /// empty when `redirect_url` is set
+    pub content: Box<dyn AsyncRead + Send + Unpin>,
+    /// Number of bytes `content` yields
+    pub content_length: u64,
+    /// Hash of the representation's content, a strong entity tag for it
+    pub content_hash: ContentHash,
     pub content_type: String,
     pub filename: String,
+    /// Size of the whole representation
     pub file_size: u64,
+    /// Whether the representation was chosen from the `Accept` header
+    pub negotiated: bool,
+    /// The byte range served, if the request asked for a satisfiable one
+    pub range: Option<ByteRange>,
+    /// Storage URL to redirect the client to instead of serving `content`
+    pub redirect_url: Option<String>,
+}
+
+impl std::fmt::Debug for DownloadResponse {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DownloadResponse")
+            .field("content_length", &self.content_length)
+            .field("content_hash", &self.content_hash)
+            .field("content_type", &self.content_type)
+            .field("filename", &self.filename)
+            .field("file_size", &self.file_size)
+            .field("negotiated", &self.negotiated)
+            .field("range", &self.range)
+            .field("redirect_url", &self.redirect_url)
+            .finish_non_exhaustive()
+    }
+}
+
+/// An inclusive byte range within a representation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ByteRange {
+    pub start: u64,
+    pub end: u64,
+}
+
+impl ByteRange {
+    /// Number of bytes in the range
+    #[must_use]
+    pub fn length(&self) -> u64 {
+        self.end - self.start + 1
+    }
+
+    /// Resolve a `Range` header against a representation of `size` bytes
+    ///
+    /// Supports a single `bytes=` range in any of its forms (`0-99`, `100-`, `-100`).
+    /// Other units, multiple ranges and malformed values resolve to `None`, meaning
+    /// the whole representation is served, as RFC 9110 allows.
+    ///
+    /// # Errors
+    /// Returns `RangeNotSatisfiable` if the range starts past the end of the content
+    pub fn resolve(header: &str, size: u64) -> Result<Option<Self>, AppError> {
+        let Some(spec) = header.trim().strip_prefix("bytes=") else { return Ok(None) };
+        let Some((first, last)) = spec.split_once('-') else { return Ok(None) };
+        if spec.contains(',') {
+            return Ok(None);
+        }
+        let (first, last) = (first.trim(), last.trim());
+
+        let range = if first.is_empty() {
+            let Ok(suffix) = last.parse::<u64>() else { return Ok(None) };
+            if suffix == 0 || size == 0 {
+                return Err(AppError::RangeNotSatisfiable { size });
+            }
+            Self { start: size.saturating_sub(suffix), end: size - 1 }
+        } else {
+            let Ok(start) = first.parse::<u64>() else { return Ok(None) };
+            let end = match last.parse::<u64>() {
+                Ok(end) if end >= start => end,
+                Err(_) if last.is_empty() => u64::MAX,
+                _ => return Ok(None),
+            };
+            if start >= size {
+                return Err(AppError::RangeNotSatisfiable { size });
+            }
+            Self { start, end: end.min(size - 1) }
+        };
+
+        Ok(Some(range))
+    }
 }
 
 impl<R, S> DownloadMediaUseCase<R, S>
@@ -36,103 +122,379 @@ where
 {
     /// Create a new download media use case
     pub fn new(repository: Arc<R>, storage: Arc<S>) -> Self {
-        Self { repository, storage }
+        Self { repository, storage, redirect: DownloadRedirectPolicy::disabled() }
+    }
+
+    /// Redirect the downloads `redirect` covers to the storage backend, if it
+    /// can provide download URLs
+    #[must_use]
+    pub fn with_redirect(mut self, redirect: DownloadRedirectPolicy) -> Self {
+        self.redirect = redirect;
+        self
+    }
+
+    /// Execute the download media use case, always serving the whole original
+    pub async fn execute(
+        &self,
+        media_id: MediaId,
+        requester: Option<UserId>,
+    ) -> Result<DownloadResponse, AppError> {
+        self.execute_negotiated(media_id, None, None, requester).await
     }
 
-    /// Execute the download media use case
-    pub async fn execute(&self, media_id: MediaId) -> Result<DownloadResponse, AppError> {
+    /// Execute the download, serving the smallest representation allowed by `accept`
+    ///
+    /// The original and each generated variant are candidates; any the `Accept`
+    /// header gives a quality of zero are excluded. The original is served when no
+    /// header is given or nothing else is acceptable. A `range` header is resolved
+    /// against the chosen representation, and only those bytes are read. A
+    /// `requester` other than the uploader is rejected with `Authorization`, and
+    /// no `requester` at all with `Authentication`.
+    ///
+    /// Downloads covered by the redirect policy return a `redirect_url` instead
+    /// of content, leaving ranges to the storage backend. If the backend can't
+    /// provide a URL, the content is served as usual.
+    pub async fn execute_negotiated(
+        &self,
+        media_id: MediaId,
+        accept: Option<&str>,
+        range: Option<&str>,
+        requester: Option<UserId>,
+    ) -> Result<DownloadResponse, AppError> {
         tracing::info!("Downloading media with ID: {}", media_id);
 
-        // Get media metadata from database
-        let media =
-            self.repository.find_by_id(media_id).await.map_err(|e| AppError::Internal {
-                message: format!("Failed to query media: {e}"),
-            })?;
+        let media = self.find_downloadable(media_id, requester).await?;
+        let negotiated = media.variants.iter().any(|variant| is_alternative(&media, variant));
 
-        let Some(media) = media else {
-            tracing::warn!("Media not found with ID: {}", media_id);
-            return Err(AppError::NotFound { resource: format!("Media with ID {media_id}") });
-        };
+        if let Some(variant) = accept.and_then(|accept| select_variant(&media, accept)) {
+            if let Some(response) = self.serve_variant(&media, variant, range, requester).await? {
+                return Ok(DownloadResponse { negotiated, ..response });
+            }
+        }
 
-        // Check if media processing is complete
-        if !media.is_ready() {
-            tracing::warn!(
-                "Media not ready for download: {} (status: {:?})",
-                media_id,
-                media.processing_status
-            );
-            return Err(AppError::BadRequest {
-                message: format!(
-                    "Media is not ready for download. Status: {:?}",
-                    media.processing_status
-                ),
-            });
+        let content_type = media.media_type.mime_type().to_string();
+        let redirect = self.redirect_to_storage(
+            requester,
+            &media.content_hash,
+            &media.original_filename,
+            &content_type,
+            media.file_size,
+        );
+        if let Some(response) = redirect.await {
+            return Ok(DownloadResponse { negotiated, ..response });
         }
 
         tracing::info!("Retrieving file from storage: {}", media.content_hash.as_str());
 
-        // Get file from storage
-        let mut file_reader =
-            self.storage.retrieve(&media.content_hash).await.map_err(|e| match e {
-                StorageError::FileNotFound { .. } => {
-                    AppError::NotFound { resource: format!("File content for media {media_id}") }
-                }
-                _ => AppError::Internal { message: format!("Storage error: {e}") },
-            })?;
+        let byte_range =
+            range.map(|h| ByteRange::resolve(h, media.file_size)).transpose()?.flatten();
 
-        // Read file content
-        let mut content = Vec::new();
-        file_reader.read_to_end(&mut content).await.map_err(|e| AppError::Internal {
-            message: format!("Failed to read file content: {e}"),
-        })?;
+        let content =
+            self.open(&media.content_hash, byte_range).await.map_err(storage_error(media_id))?;
 
         tracing::info!(
-            "Successfully downloaded media: {} ({} bytes)",
+            "Successfully opened media for download: {} ({} bytes)",
             media.original_filename,
-            content.len()
+            media.file_size
         );
 
         Ok(DownloadResponse {
             content,
-            content_type: media.media_type.mime_type().to_string(),
+            content_length: byte_range.map_or(media.file_size, |r| r.length()),
+            content_hash: media.content_hash,
+            content_type,
             filename: media.original_filename,
             file_size: media.file_size,
+            negotiated,
+            range: byte_range,
+            redirect_url: None,
         })
     }
 
-    /// Execute download and return streaming reader (for large files)
-    /// This method returns the reader directly without loading the entire file into memory
-    pub async fn execute_stream(
+    /// Serve a generated variant of `media`, or `None` if it can't be read
+    async fn serve_variant(
+        &self,
+        media: &Media,
+        variant: &MediaVariant,
+        range: Option<&str>,
+        requester: Option<UserId>,
+    ) -> Result<Option<DownloadResponse>, AppError> {
+        let filename = variant_filename(media, variant);
+        let content_type = variant.media_type.mime_type().to_string();
+
+        let redirect = self.redirect_to_storage(
+            requester,
+            &variant.content_hash,
+            &filename,
+            &content_type,
+            variant.file_size,
+        );
+        if let Some(response) = redirect.await {
+            return Ok(Some(response));
+        }
+
+        let byte_range =
+            range.map(|h| ByteRange::resolve(h, variant.file_size)).transpose()?.flatten();
+
+        match self.open(&variant.content_hash, byte_range).await {
+            Ok(content) => {
+                tracing::info!(
+                    "Serving {} variant of media: {} ({} bytes)",
+                    variant.name,
+                    media.original_filename,
+                    variant.file_size
+                );
+
+                Ok(Some(DownloadResponse {
+                    content,
+                    content_length: byte_range.map_or(variant.file_size, |r| r.length()),
+                    content_hash: variant.content_hash.clone(),
+                    content_type,
+                    filename,
+                    file_size: variant.file_size,
+                    negotiated: true,
+                    range: byte_range,
+                    redirect_url: None,
+                }))
+            }
+            Err(e) => {
+                tracing::warn!(
+                    "Failed to read {} variant of media {}, serving original: {}",
+                    variant.name,
+                    media.id,
+                    e
+                );
+                Ok(None)
+            }
+        }
+    }
+
+    /// A redirect to a storage URL for a stored file, or `None` to serve it
+    /// through the service
+    async fn redirect_to_storage(
+        &self,
+        requester: Option<UserId>,
+        hash: &ContentHash,
+        filename: &str,
+        content_type: &str,
+        file_size: u64,
+    ) -> Option<DownloadResponse> {
+        if !self.redirect.applies_to(requester) {
+            return None;
+        }
+
+        let url = self
+            .storage
+            .download_url(hash, filename, content_type, self.redirect.expires_in)
+            .await
+            .inspect_err(|e| {
+                tracing::warn!("Failed to create download URL for {}, serving it: {}", hash, e);
+            })
+            .ok()??;
+
+        Some(DownloadResponse {
+            content: Box::new(tokio::io::empty()),
+            content_length: 0,
+            content_hash: hash.clone(),
+            content_type: content_type.to_string(),
+            filename: filename.to_string(),
+            file_size,
+            negotiated: false,
+            range: None,
+            redirect_url: Some(url),
+        })
+    }
+
+    /// Describe the download `execute_negotiated` would serve, without reading it
+    ///
+    /// The representation is chosen from `accept` the same way, and redirected
+    /// the same way, but its length comes from the storage metadata and
+    /// `content` is always empty.
+    pub async fn execute_head(
         &self,
         media_id: MediaId,
-    ) -> Result<(Box<dyn AsyncRead + Send + Unpin>, Media), AppError> {
-        tracing::info!("Streaming media with ID: {}", media_id);
+        accept: Option<&str>,
+        requester: Option<UserId>,
+    ) -> Result<DownloadResponse, AppError> {
+        let media = self.find_downloadable(media_id, requester).await?;
+        let negotiated = media.variants.iter().any(|variant| is_alternative(&media, variant));
+
+        if let Some(variant) = accept.and_then(|accept| select_variant(&media, accept)) {
+            let filename = variant_filename(&media, variant);
+            let content_type = variant.media_type.mime_type();
+            match self
+                .describe(
+                    requester,
+                    &variant.content_hash,
+                    &filename,
+                    content_type,
+                    variant.file_size,
+                )
+                .await
+            {
+                Ok(response) => return Ok(DownloadResponse { negotiated, ..response }),
+                Err(e) => tracing::warn!(
+                    "Failed to read {} variant of media {}, describing original: {}",
+                    variant.name,
+                    media.id,
+                    e
+                ),
+            }
+        }
 
-        // Get media metadata from database
-        let media =
-            self.repository.find_by_id(media_id).await.map_err(|e| AppError::Internal {
-                message: format!("Failed to query media: {e}"),
-            })?;
+        let content_type = media.media_type.mime_type();
+        let response = self
+            .describe(
+                requester,
+                &media.content_hash,
+                &media.original_filename,
+                content_type,
+                media.file_size,
+            )
+            .await
+            .map_err(storage_error(media_id))?;
+        Ok(DownloadResponse { negotiated, ..response })
+    }
+
+    /// A redirect to a stored file, or its length and type without its content
+    async fn describe(
+        &self,
+        requester: Option<UserId>,
+        hash: &ContentHash,
+        filename: &str,
+        content_type: &str,
+        file_size: u64,
+    ) -> Result<DownloadResponse, StorageError> {
+        let redirect = self.redirect_to_storage(requester, hash, filename, content_type, file_size);
+        if let Some(response) = redirect.await {
+            return Ok(response);
+        }
+
+        let metadata = self.storage.metadata(hash).await?;
+        Ok(DownloadResponse {
+            content: Box::new(tokio::io::empty()),
+            content_length: metadata.size,
+            content_hash: hash.clone(),
+            content_type: content_type.to_string(),
+            filename: filename.to_string(),
+            file_size: metadata.size,
+            negotiated: false,
+            range: None,
+            redirect_url: None,
+        })
+    }
+
+    /// Execute the download of the media whose original has content `hash`,
+    /// or of its generated `variant`
+    ///
+    /// Content addressed by its hash never changes, so it is always served
+    /// rather than redirected, for a CDN in front of the service to keep. A
+    /// `range` header is honored as in `execute_negotiated`. Returns the ID of
+    /// the media too, for the CDN to purge the content by when it is deleted.
+    pub async fn execute_by_hash(
+        &self,
+        hash: &ContentHash,
+        variant: Option<&str>,
+        range: Option<&str>,
+        requester: Option<UserId>,
+    ) -> Result<(MediaId, DownloadResponse), AppError> {
+        tracing::info!("Downloading media with content hash: {}", hash);
+
+        let media = self
+            .repository
+            .find_by_content_hash(hash, requester)
+            .await
+            .map_err(repository_error("Failed to query media"))?;
+        let Some(media) = media else {
+            tracing::warn!("Media not found with content hash: {}", hash);
+            return Err(AppError::NotFound { resource: format!("Media with content hash {hash}") });
+        };
+        ensure_downloadable(&media, requester)?;
+
+        let (content_hash, content_type, filename, file_size) = match variant {
+            None => (
+                media.content_hash.clone(),
+                media.media_type.mime_type().to_string(),
+                media.original_filename.clone(),
+                media.file_size,
+            ),
+            Some(name) => {
+                let Some(variant) = media.variants.iter().find(|variant| variant.name == name)
+                else {
+                    return Err(AppError::NotFound {
+                        resource: format!("Variant {name} of media {}", media.id),
+                    });
+                };
+                (
+                    variant.content_hash.clone(),
+                    variant.media_type.mime_type().to_string(),
+                    variant_filename(&media, variant),
+                    variant.file_size,
+                )
+            }
+        };
+
+        let byte_range = range.map(|h| ByteRange::resolve(h, file_size)).transpose()?.flatten();
+        let content =
+            self.open(&content_hash, byte_range).await.map_err(storage_error(media.id))?;
+
+        let response = DownloadResponse {
+            content,
+            content_length: byte_range.map_or(file_size, |r| r.length()),
+            content_hash,
+            content_type,
+            filename,
+            file_size,
+            negotiated: false,
+            range: byte_range,
+            redirect_url: None,
+        };
+        Ok((media.id, response))
+    }
+
+    /// The media `requester` may download, once processing has completed
+    async fn find_downloadable(
+        &self,
+        media_id: MediaId,
+        requester: Option<UserId>,
+    ) -> Result<Media, AppError> {
+        let media = self
+            .repository
+            .find_by_id(media_id)
+            .await
+            .map_err(repository_error("Failed to query media"))?;
 
         let Some(media) = media else {
             tracing::warn!("Media not found with ID: {}", media_id);
             return Err(AppError::NotFound { resource: format!("Media with ID {media_id}") });
         };
+        ensure_downloadable(&media, requester)?;
 
-        // Check if media processing is complete
-        if !media.is_ready() {
-            tracing::warn!(
-                "Media not ready for streaming: {} (status: {:?})",
-                media_id,
-                media.processing_status
-            );
-            return Err(AppError::BadRequest {
-                message: format!(
-                    "Media is not ready for download. Status: {:?}",
-                    media.processing_status
-                ),
-            });
+        Ok(media)
+    }
+
+    /// Open a stored file, or only the given range of it
+    async fn open(
+        &self,
+        hash: &ContentHash,
+        range: Option<ByteRange>,
+    ) -> Result<Box<dyn AsyncRead + Send + Unpin>, StorageError> {
+        match range {
+            Some(range) => self.storage.retrieve_range(hash, range.start, range.length()).await,
+            None => self.storage.retrieve(hash).await,
         }
+    }
+
+    /// Execute download and return streaming reader (for large files)
+    /// This method returns the reader directly without loading the entire file into memory
+    pub async fn execute_stream(
+        &self,
+        media_id: MediaId,
+        requester: Option<UserId>,
+    ) -> Result<(Box<dyn AsyncRead + Send + Unpin>, Media), AppError> {
+        tracing::info!("Streaming media with ID: {}", media_id);
+
+        let media = self.find_downloadable(media_id, requester).await?;
 
         tracing::info!("Streaming file from storage: {}", media.content_hash.as_str());
 
@@ -151,6 +513,72 @@ where
     }
 }
 
+/// Pick the variant to serve for an `Accept` header, or `None` for the original
+///
+/// Chooses the smallest acceptable representation, preferring the original on ties.
+fn select_variant<'a>(media: &'a Media, accept: &str) -> Option<&'a MediaVariant> {
+    let original_acceptable = accept_quality(accept, media.media_type.mime_type()) > 0.0;
+
+    let smallest = media
+        .variants
+        .iter()
+        .filter(|variant| is_alternative(media, variant))
+        .filter(|variant| accept_quality(accept, variant.media_type.mime_type()) > 0.0)
+        .min_by_key(|variant| variant.file_size)?;
+
+    if original_acceptable && media.file_size <= smallest.file_size {
+        None
+    } else {
+        Some(smallest)
+    }
+}
+
+/// Whether a variant is another encoding of the original rather than derived content
+///
+/// Only variants of the same top-level type qualify; a video's poster frame, for
+/// instance, is never served in place of the video, nor a thumbnail or other
+/// resized copy in place of the full image.
+fn is_alternative(media: &Media, variant: &MediaVariant) -> bool {
+    let top_level = |media_type: &str| media_type.split('/').next().map(str::to_owned);
+    top_level(media.media_type.mime_type()) == top_level(variant.media_type.mime_type())
+        && variant.name != ImageVariantEncoder::THUMBNAIL
+        && !ImageRender::is_render_variant(&variant.name)
+}
+
+/// Quality value an `Accept` header assigns to a content type
+///
+/// The most specific matching media range wins (`image/webp` over `image/*` over
+/// `*/*`); a type matching no range gets zero.
+fn accept_quality(accept: &str, content_type: &str) -> f32 {
+    let (main_type, _) = content_type.split_once('/').unwrap_or((content_type, ""));
+    let mut best: Option<(u8, f32)> = None;
+
+    for range in accept.split(',') {
+        let mut parts = range.split(';');
+        let media_range = parts.next().unwrap_or_default().trim().to_ascii_lowercase();
+        let quality = parts
+            .filter_map(|param| param.trim().strip_prefix("q="))
+            .find_map(|q| q.trim().parse::<f32>().ok())
+            .unwrap_or(1.0);
+
+        let specificity = if media_range == content_type {
+            3
+        } else if media_range.strip_suffix("/*") == Some(main_type) {
+            2
+        } else if media_range == "*/*" {
+            1
+        } else {
+            continue;
+        };
+
+        if best.is_none_or(|(current, _)| specificity > current) {
+            best = Some((specificity, quality));
+        }
+    }
+
+    best.map_or(0.0, |(_, quality)| quality)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -172,6 +600,7 @@ mod tests {
     pub struct MockDownloadStorage {
         files: Arc<Mutex<HashMap<String, Vec<u8>>>>,
         simulate_error: Arc<Mutex<Option<StorageError>>>,
+        url_base: Option<String>,
     }
 
     impl MockDownloadStorage {
@@ -179,9 +608,15 @@ mod tests {
             Self {
                 files: Arc::new(Mutex::new(HashMap::new())),
                 simulate_error: Arc::new(Mutex::new(None)),
+                url_base: None,
             }
         }
 
+        pub fn with_download_urls(mut self, url_base: &str) -> Self {
+            self.url_base = Some(url_base.to_string());
+            self
+        }
+
         pub fn with_file(self, hash: &str, content: Vec<u8>) -> Self {
             {
                 let mut files = self.files.lock().unwrap();
@@ -198,10 +633,11 @@ mod tests {
 
     #[async_trait]
     impl FileStorage for MockDownloadStorage {
-        async fn store<R>(&self, hash: &ContentHash, mut reader: R) -> Result<String, StorageError>
-        where
-            R: AsyncRead + Send + Unpin,
-        {
+        async fn store(
+            &self,
+            hash: &ContentHash,
+            reader: &mut (dyn AsyncRead + Send + Unpin),
+        ) -> Result<String, StorageError> {
             let mut buffer = Vec::new();
             reader
                 .read_to_end(&mut buffer)
@@ -259,6 +695,18 @@ mod tests {
             format!("mock/path/{}", hash.as_str())
         }
 
+        async fn download_url(
+            &self,
+            hash: &ContentHash,
+            filename: &str,
+            _content_type: &str,
+            expires_in: std::time::Duration,
+        ) -> Result<Option<String>, StorageError> {
+            Ok(self.url_base.as_ref().map(|base| {
+                format!("{base}/{}?filename={filename}&expires={}", hash, expires_in.as_secs())
+            }))
+        }
+
         async fn metadata(
             &self,
             hash: &ContentHash,
@@ -279,6 +727,17 @@ mod tests {
         }
     }
 
+    async fn read_content(mut content: Box<dyn AsyncRead + Send + Unpin>) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        content.read_to_end(&mut buffer).await.unwrap();
+        buffer
+    }
+
+    /// The user uploading the test media
+    fn owner() -> UserId {
+        UserId::from_uuid(uuid::Uuid::from_u128(1))
+    }
+
     fn create_test_media(id: MediaId, status: ProcessingStatus) -> Media {
         let content_hash =
             ContentHash::new("abcdef1234567890abcdef1234567890abcdef1234567890abcdef1234567890")
@@ -293,10 +752,51 @@ mod tests {
             1024,
             status,
         )
-        .uploaded_by(UserId::new())
+        .uploaded_by(owner())
         .build()
     }
 
+    const WEBP_HASH: &str = "1111111111111111111111111111111111111111111111111111111111111111";
+    const AVIF_HASH: &str = "2222222222222222222222222222222222222222222222222222222222222222";
+    const THUMBNAIL_HASH: &str = "3333333333333333333333333333333333333333333333333333333333333333";
+
+    fn create_media_with_variants() -> Media {
+        let mut media = create_test_media(MediaId::new(1), ProcessingStatus::Complete);
+        media.variants = vec![
+            MediaVariant {
+                name: "webp".to_string(),
+                content_hash: ContentHash::new(WEBP_HASH).unwrap(),
+                media_type: MediaType::new("image/webp"),
+                file_size: 800,
+            },
+            MediaVariant {
+                name: "avif".to_string(),
+                content_hash: ContentHash::new(AVIF_HASH).unwrap(),
+                media_type: MediaType::new("image/avif"),
+                file_size: 500,
+            },
+            // Smallest, but never negotiated in place of the full image
+            MediaVariant {
+                name: "thumbnail".to_string(),
+                content_hash: ContentHash::new(THUMBNAIL_HASH).unwrap(),
+                media_type: MediaType::new("image/jpeg"),
+                file_size: 100,
+            },
+        ];
+        media
+    }
+
+    fn create_variant_storage() -> MockDownloadStorage {
+        MockDownloadStorage::new()
+            .with_file(
+                "abcdef1234567890abcdef1234567890abcdef1234567890abcdef1234567890",
+                b"jpeg".to_vec(),
+            )
+            .with_file(WEBP_HASH, b"webp".to_vec())
+            .with_file(AVIF_HASH, b"avif".to_vec())
+            .with_file(THUMBNAIL_HASH, b"thumbnail".to_vec())
+    }
+
     #[tokio::test]
     async fn test_download_media_success() {
         let test_content = b"test image content".to_vec();
@@ -311,11 +811,13 @@ mod tests {
         );
 
         let use_case = DownloadMediaUseCase::new(repository, storage);
-        let result = use_case.execute(MediaId::new(1)).await;
+        let result = use_case.execute(MediaId::new(1), Some(owner())).await;
 
         assert!(result.is_ok());
         let response = result.unwrap();
-        assert_eq!(response.content, test_content);
+        assert_eq!(response.content_length, 1024);
+        assert_eq!(response.range, None);
+        assert_eq!(read_content(response.content).await, test_content);
         assert_eq!(response.filename, "test.jpg");
         assert_eq!(response.content_type, "image/jpeg");
         assert_eq!(response.file_size, 1024);
@@ -327,7 +829,7 @@ mod tests {
         let storage = Arc::new(MockDownloadStorage::new());
 
         let use_case = DownloadMediaUseCase::new(repository, storage);
-        let result = use_case.execute(MediaId::new(999)).await;
+        let result = use_case.execute(MediaId::new(999), Some(owner())).await;
 
         assert!(result.is_err());
         match result.err().unwrap() {
@@ -343,7 +845,7 @@ mod tests {
         let storage = Arc::new(MockDownloadStorage::new());
 
         let use_case = DownloadMediaUseCase::new(repository, storage);
-        let result = use_case.execute(MediaId::new(1)).await;
+        let result = use_case.execute(MediaId::new(1), Some(owner())).await;
 
         assert!(result.is_err());
         match result.err().unwrap() {
@@ -359,7 +861,7 @@ mod tests {
         let storage = Arc::new(MockDownloadStorage::new());
 
         let use_case = DownloadMediaUseCase::new(repository, storage);
-        let result = use_case.execute(MediaId::new(1)).await;
+        let result = use_case.execute(MediaId::new(1), Some(owner())).await;
 
         assert!(result.is_err());
         match result.err().unwrap() {
@@ -376,7 +878,7 @@ mod tests {
         storage.set_error(StorageError::IoError { message: "Disk error".to_string() });
 
         let use_case = DownloadMediaUseCase::new(repository, storage);
-        let result = use_case.execute(MediaId::new(1)).await;
+        let result = use_case.execute(MediaId::new(1), Some(owner())).await;
 
         assert!(result.is_err());
         match result.err().unwrap() {
@@ -399,7 +901,7 @@ mod tests {
         );
 
         let use_case = DownloadMediaUseCase::new(repository, storage);
-        let result = use_case.execute_stream(MediaId::new(1)).await;
+        let result = use_case.execute_stream(MediaId::new(1), Some(owner())).await;
 
         assert!(result.is_ok());
         let (mut reader, returned_media) = result.unwrap();
@@ -417,7 +919,7 @@ mod tests {
         let storage = Arc::new(MockDownloadStorage::new());
 
         let use_case = DownloadMediaUseCase::new(repository, storage);
-        let result = use_case.execute_stream(MediaId::new(1)).await;
+        let result = use_case.execute_stream(MediaId::new(1), Some(owner())).await;
 
         assert!(result.is_err());
         match result.err().unwrap() {
@@ -427,19 +929,261 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_download_response_creation() {
-        let content = b"test content".to_vec();
-        let response = DownloadResponse {
-            content: content.clone(),
-            content_type: "text/plain".to_string(),
-            filename: "test.txt".to_string(),
-            file_size: content.len() as u64,
-        };
+    async fn test_download_negotiates_smallest_acceptable_variant() {
+        let repository =
+            Arc::new(InMemoryMediaRepository::new().with_media(create_media_with_variants()));
+        let use_case = DownloadMediaUseCase::new(repository, Arc::new(create_variant_storage()));
+
+        let avif = use_case
+            .execute_negotiated(
+                MediaId::new(1),
+                Some("image/avif,image/webp,*/*;q=0.8"),
+                None,
+                Some(owner()),
+            )
+            .await
+            .unwrap();
+        assert_eq!(avif.content_type, "image/avif");
+        assert_eq!(avif.filename, "test.avif");
+        assert_eq!(avif.file_size, 500);
+        assert!(avif.negotiated);
+        assert_eq!(read_content(avif.content).await, b"avif");
+
+        let webp = use_case
+            .execute_negotiated(MediaId::new(1), Some("image/webp,image/jpeg"), None, Some(owner()))
+            .await
+            .unwrap();
+        assert_eq!(webp.content_type, "image/webp");
+        assert_eq!(webp.filename, "test.webp");
+    }
+
+    #[tokio::test]
+    async fn test_head_describes_the_negotiated_representation_from_storage() {
+        let repository =
+            Arc::new(InMemoryMediaRepository::new().with_media(create_media_with_variants()));
+        let use_case =
+            DownloadMediaUseCase::new(repository.clone(), Arc::new(create_variant_storage()));
+
+        let avif = use_case
+            .execute_head(MediaId::new(1), Some("image/avif"), Some(owner()))
+            .await
+            .unwrap();
+        assert_eq!(avif.content_type, "image/avif");
+        assert_eq!(avif.content_hash.as_str(), AVIF_HASH);
+        // The length stored, not the one recorded with the variant
+        assert_eq!(avif.content_length, 4);
+        assert!(avif.negotiated);
+        assert!(read_content(avif.content).await.is_empty());
+
+        let use_case = DownloadMediaUseCase::new(repository, Arc::new(MockDownloadStorage::new()));
+        let result = use_case.execute_head(MediaId::new(1), None, Some(owner())).await;
+        assert!(matches!(result, Err(AppError::NotFound { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_download_by_hash_serves_the_original_or_a_named_variant() {
+        let media = create_media_with_variants();
+        let hash = media.content_hash.clone();
+        let repository = Arc::new(InMemoryMediaRepository::new().with_media(media));
+        // Redirects are configured, but content addressed by hash is always served
+        let storage = create_variant_storage().with_download_urls("https://bucket.example");
+        let use_case = DownloadMediaUseCase::new(repository, Arc::new(storage)).with_redirect(
+            DownloadRedirectPolicy { private: true, ..DownloadRedirectPolicy::disabled() },
+        );
+
+        let (media_id, original) =
+            use_case.execute_by_hash(&hash, None, None, Some(owner())).await.unwrap();
+        assert_eq!(media_id, MediaId::new(1));
+        assert_eq!(original.content_type, "image/jpeg");
+        assert!(original.redirect_url.is_none());
+        assert_eq!(read_content(original.content).await, b"jpeg");
+
+        let (_, avif) = use_case
+            .execute_by_hash(&hash, Some("avif"), Some("bytes=1-"), Some(owner()))
+            .await
+            .unwrap();
+        assert_eq!(avif.content_hash.as_str(), AVIF_HASH);
+        assert_eq!(avif.filename, "test.avif");
+        assert_eq!(read_content(avif.content).await, b"vif");
+
+        let missing = use_case.execute_by_hash(&hash, Some("heic"), None, Some(owner())).await;
+        assert!(matches!(missing, Err(AppError::NotFound { .. })));
+        let unknown = ContentHash::new(WEBP_HASH).unwrap();
+        let missing = use_case.execute_by_hash(&unknown, None, None, Some(owner())).await;
+        assert!(matches!(missing, Err(AppError::NotFound { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_download_serves_original_without_acceptable_variant() {
+        let repository =
+            Arc::new(InMemoryMediaRepository::new().with_media(create_media_with_variants()));
+        let use_case = DownloadMediaUseCase::new(repository, Arc::new(create_variant_storage()));
+
+        let excluded = use_case
+            .execute_negotiated(
+                MediaId::new(1),
+                Some("image/jpeg, image/*;q=0"),
+                None,
+                Some(owner()),
+            )
+            .await
+            .unwrap();
+        assert_eq!(excluded.content_type, "image/jpeg");
+        assert!(excluded.negotiated);
+
+        let no_header = use_case.execute(MediaId::new(1), Some(owner())).await.unwrap();
+        assert_eq!(no_header.filename, "test.jpg");
+        assert_eq!(read_content(no_header.content).await, b"jpeg");
+    }
+
+    #[tokio::test]
+    async fn test_download_falls_back_to_original_when_variant_missing() {
+        let repository =
+            Arc::new(InMemoryMediaRepository::new().with_media(create_media_with_variants()));
+        let storage = MockDownloadStorage::new().with_file(
+            "abcdef1234567890abcdef1234567890abcdef1234567890abcdef1234567890",
+            b"jpeg".to_vec(),
+        );
+        let use_case = DownloadMediaUseCase::new(repository, Arc::new(storage));
+
+        let response = use_case
+            .execute_negotiated(MediaId::new(1), Some("image/avif"), None, Some(owner()))
+            .await
+            .unwrap();
+
+        assert_eq!(response.content_type, "image/jpeg");
+        assert_eq!(read_content(response.content).await, b"jpeg");
+    }
+
+    #[tokio::test]
+    async fn test_downloads_by_the_uploader_are_redirected() {
+        let repository =
+            Arc::new(InMemoryMediaRepository::new().with_media(create_media_with_variants()));
+        let storage = create_variant_storage().with_download_urls("https://bucket.example");
+        let redirect =
+            DownloadRedirectPolicy { private: true, expires_in: std::time::Duration::from_mins(1) };
+        let use_case =
+            DownloadMediaUseCase::new(repository, Arc::new(storage)).with_redirect(redirect);
+
+        let private = use_case
+            .execute_negotiated(
+                MediaId::new(1),
+                Some("image/avif"),
+                Some("bytes=0-1"),
+                Some(owner()),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            private.redirect_url.as_deref(),
+            Some(
+                format!("https://bucket.example/{AVIF_HASH}?filename=test.avif&expires=60")
+                    .as_str()
+            )
+        );
+        assert_eq!(private.range, None);
+        assert!(private.negotiated);
+    }
+
+    #[tokio::test]
+    async fn test_only_the_uploader_can_download() {
+        let media = create_media_with_variants();
+        let hash = media.content_hash.clone();
+        let repository = Arc::new(InMemoryMediaRepository::new().with_media(media));
+        let use_case = DownloadMediaUseCase::new(repository, Arc::new(create_variant_storage()));
+
+        let anonymous = use_case.execute(MediaId::new(1), None).await;
+        assert!(matches!(anonymous, Err(AppError::Authentication { .. })));
+        let anonymous = use_case.execute_head(MediaId::new(1), None, None).await;
+        assert!(matches!(anonymous, Err(AppError::Authentication { .. })));
+        let anonymous = use_case.execute_by_hash(&hash, None, None, None).await;
+        assert!(matches!(anonymous, Err(AppError::Authentication { .. })));
+        let anonymous = use_case.execute_stream(MediaId::new(1), None).await;
+        assert!(matches!(anonymous, Err(AppError::Authentication { .. })));
+
+        let other_user = use_case.execute(MediaId::new(1), Some(UserId::new())).await;
+        assert!(matches!(other_user, Err(AppError::Authorization { .. })));
+        let other_user = use_case.execute_by_hash(&hash, None, None, Some(UserId::new())).await;
+        assert!(matches!(other_user, Err(AppError::NotFound { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_download_serves_content_when_storage_has_no_urls() {
+        let repository =
+            Arc::new(InMemoryMediaRepository::new().with_media(create_media_with_variants()));
+        let redirect =
+            DownloadRedirectPolicy { private: true, ..DownloadRedirectPolicy::disabled() };
+        let use_case = DownloadMediaUseCase::new(repository, Arc::new(create_variant_storage()))
+            .with_redirect(redirect);
+
+        let response = use_case.execute(MediaId::new(1), Some(owner())).await.unwrap();
+
+        assert!(response.redirect_url.is_none());
+        assert_eq!(read_content(response.content).await, b"jpeg");
+    }
+
+    #[test]
+    fn test_accept_quality() {
+        let accept = "image/avif;q=0.9, image/*;q=0.5, */*;q=0.1, text/html;q=0";
+
+        assert!((accept_quality(accept, "image/avif") - 0.9).abs() < f32::EPSILON);
+        assert!((accept_quality(accept, "image/png") - 0.5).abs() < f32::EPSILON);
+        assert!((accept_quality(accept, "video/mp4") - 0.1).abs() < f32::EPSILON);
+        assert!(accept_quality(accept, "text/html").abs() < f32::EPSILON);
+        assert!(accept_quality("image/png", "image/webp").abs() < f32::EPSILON);
+    }
 
-        assert_eq!(response.content, content);
-        assert_eq!(response.content_type, "text/plain");
-        assert_eq!(response.filename, "test.txt");
-        assert_eq!(response.file_size, content.len() as u64);
+    #[test]
+    fn test_byte_range_resolve() {
+        let range = |header| ByteRange::resolve(header, 1000).unwrap();
+
+        assert_eq!(range("bytes=0-99"), Some(ByteRange { start: 0, end: 99 }));
+        assert_eq!(range("bytes=900-"), Some(ByteRange { start: 900, end: 999 }));
+        assert_eq!(range("bytes=-100"), Some(ByteRange { start: 900, end: 999 }));
+        assert_eq!(range("bytes=990-2000"), Some(ByteRange { start: 990, end: 999 }));
+        assert_eq!(range("bytes=-5000"), Some(ByteRange { start: 0, end: 999 }));
+        assert_eq!(range("bytes=0-99").map(|r| r.length()), Some(100));
+
+        // Ignored: the whole representation is served instead
+        assert_eq!(range("items=0-1"), None);
+        assert_eq!(range("bytes=0-1,5-6"), None);
+        assert_eq!(range("bytes=20-10"), None);
+        assert_eq!(range("bytes=abc"), None);
+
+        assert!(matches!(
+            ByteRange::resolve("bytes=1000-", 1000),
+            Err(AppError::RangeNotSatisfiable { size: 1000 })
+        ));
+        assert!(matches!(
+            ByteRange::resolve("bytes=-0", 1000),
+            Err(AppError::RangeNotSatisfiable { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_download_serves_requested_range() {
+        let mut media = create_test_media(MediaId::new(1), ProcessingStatus::Complete);
+        media.file_size = 10;
+        let repository = Arc::new(InMemoryMediaRepository::new().with_media(media));
+        let storage = MockDownloadStorage::new().with_file(
+            "abcdef1234567890abcdef1234567890abcdef1234567890abcdef1234567890",
+            b"0123456789".to_vec(),
+        );
+        let use_case = DownloadMediaUseCase::new(repository, Arc::new(storage));
+
+        let partial = use_case
+            .execute_negotiated(MediaId::new(1), None, Some("bytes=2-5"), Some(owner()))
+            .await
+            .unwrap();
+        assert_eq!(partial.range, Some(ByteRange { start: 2, end: 5 }));
+        assert_eq!(partial.content_length, 4);
+        assert_eq!(partial.file_size, 10);
+        assert_eq!(read_content(partial.content).await, b"2345");
+
+        let unsatisfiable = use_case
+            .execute_negotiated(MediaId::new(1), None, Some("bytes=10-"), Some(owner()))
+            .await;
+        assert!(matches!(unsatisfiable, Err(AppError::RangeNotSatisfiable { size: 10 })));
     }
 
     #[tokio::test]
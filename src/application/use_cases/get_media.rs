@@ -1,9 +1,10 @@
 use std::sync::Arc;
 
+use super::{ensure_owner, repository_error};
 use crate::{
-    application::dto::MediaDto,
+    application::dto::{MediaDto, MediaRenditionDto, MediaVariantsResponse},
     domain::{
-        entities::{Media, MediaId},
+        entities::{Media, MediaId, UserId},
         repositories::MediaRepository,
     },
     presentation::middleware::error::AppError,
@@ -27,57 +28,74 @@ where
     }
 
     /// Execute the get media use case
-    pub async fn execute(&self, media_id: MediaId) -> Result<MediaDto, AppError> {
+    ///
+    /// A `requester` other than the uploader is rejected with `Authorization`.
+    pub async fn execute(
+        &self,
+        media_id: MediaId,
+        requester: Option<UserId>,
+    ) -> Result<MediaDto, AppError> {
         tracing::info!("Getting media with ID: {}", media_id);
 
-        let media =
-            self.repository.find_by_id(media_id).await.map_err(|e| AppError::Internal {
-                message: format!("Failed to query media: {e}"),
-            })?;
+        let media = self.find_owned(media_id, requester).await?;
+        Ok(MediaDto::from(media))
+    }
+
+    /// List the media's renditions: the original upload followed by each variant
+    /// the processing pipeline generated
+    ///
+    /// Media that hasn't finished processing lists only its original.
+    ///
+    /// # Errors
+    /// * `NotFound` - Media with the given ID doesn't exist
+    /// * `Authorization` - The media was uploaded by another user
+    pub async fn list_variants(
+        &self,
+        media_id: MediaId,
+        requester: Option<UserId>,
+    ) -> Result<MediaVariantsResponse, AppError> {
+        tracing::info!("Listing variants of media with ID: {}", media_id);
+
+        let media = self.find_owned(media_id, requester).await?;
+
+        let original = MediaRenditionDto {
+            name: "original".to_string(),
+            content_hash: media.content_hash.as_str().to_string(),
+            media_type: media.media_type.mime_type().to_string(),
+            file_size: media.file_size,
+        };
+        let variants = std::iter::once(original)
+            .chain(media.variants.iter().map(|variant| MediaRenditionDto {
+                name: variant.name.clone(),
+                content_hash: variant.content_hash.as_str().to_string(),
+                media_type: variant.media_type.mime_type().to_string(),
+                file_size: variant.file_size,
+            }))
+            .collect();
+
+        Ok(MediaVariantsResponse { media_id, processing_status: media.processing_status, variants })
+    }
+
+    async fn find_owned(
+        &self,
+        media_id: MediaId,
+        requester: Option<UserId>,
+    ) -> Result<Media, AppError> {
+        let media = self
+            .repository
+            .find_by_id(media_id)
+            .await
+            .map_err(repository_error("Failed to query media"))?;
 
         if let Some(media) = media {
             tracing::info!("Found media: {} ({})", media.original_filename, media.id);
-            Ok(Self::media_to_dto(media))
+            ensure_owner(&media, requester)?;
+            Ok(media)
         } else {
             tracing::warn!("Media not found with ID: {}", media_id);
             Err(AppError::NotFound { resource: format!("Media with ID {media_id}") })
         }
     }
-
-    /// Convert Media entity to `MediaDto`
-    fn media_to_dto(media: Media) -> MediaDto {
-        MediaDto {
-            id: media.id,
-            content_hash: media.content_hash.as_str().to_string(),
-            original_filename: media.original_filename,
-            media_type: media.media_type.mime_type().to_string(),
-            media_path: media.media_path,
-            file_size: media.file_size,
-            processing_status: media.processing_status,
-            uploaded_at: media.uploaded_at.duration_since(std::time::UNIX_EPOCH).map_or_else(
-                |_| chrono::Utc::now().to_rfc3339(),
-                |d| {
-                    chrono::DateTime::<chrono::Utc>::from_timestamp(
-                        d.as_secs() as i64,
-                        d.subsec_nanos(),
-                    )
-                    .unwrap_or_default()
-                    .to_rfc3339()
-                },
-            ),
-            updated_at: media.updated_at.duration_since(std::time::UNIX_EPOCH).map_or_else(
-                |_| chrono::Utc::now().to_rfc3339(),
-                |d| {
-                    chrono::DateTime::<chrono::Utc>::from_timestamp(
-                        d.as_secs() as i64,
-                        d.subsec_nanos(),
-                    )
-                    .unwrap_or_default()
-                    .to_rfc3339()
-                },
-            ),
-        }
-    }
 }
 
 #[cfg(test)]
@@ -85,31 +103,32 @@ mod tests {
     use super::*;
     use crate::{
         domain::{
-            entities::{MediaId, UserId},
-            value_objects::{ContentHash, MediaType},
+            entities::{MediaId, MediaVariant, UnsavedMedia, UserId},
+            value_objects::{ContentHash, MediaType, ProcessingStatus},
         },
         test_utils::mocks::InMemoryMediaRepository,
     };
 
     #[tokio::test]
     async fn test_get_media_success() {
+        let owner = UserId::new();
         let media_id = MediaId::new(123);
         let content_hash =
             ContentHash::new("abcdef1234567890abcdef1234567890abcdef1234567890abcdef1234567890")
                 .unwrap();
-        let mut expected_media = Media::new(
+        let expected_media = UnsavedMedia::new(
             content_hash.clone(),
             "test.jpg".to_string(),
             MediaType::new("image/jpeg"),
             "/path/to/file".to_string(),
             1024,
-            UserId::new(),
-        );
-        expected_media.id = media_id;
+            owner,
+        )
+        .into_media(media_id);
 
         let repo = InMemoryMediaRepository::new().with_media(expected_media);
         let use_case = GetMediaUseCase::new(Arc::new(repo));
-        let result = use_case.execute(media_id).await;
+        let result = use_case.execute(media_id, Some(owner)).await;
 
         assert!(result.is_ok());
         let dto = result.unwrap();
@@ -120,13 +139,97 @@ mod tests {
         assert_eq!(dto.file_size, 1024);
     }
 
+    #[tokio::test]
+    async fn test_get_media_enforces_ownership() {
+        let owner = UserId::new();
+        let media = UnsavedMedia::new(
+            ContentHash::new("abcdef1234567890abcdef1234567890abcdef1234567890abcdef1234567890")
+                .unwrap(),
+            "private.jpg".to_string(),
+            MediaType::new("image/jpeg"),
+            "/path/to/file".to_string(),
+            1024,
+            owner,
+        )
+        .into_media(MediaId::new(1));
+
+        let repo = InMemoryMediaRepository::new().with_media(media);
+        let use_case = GetMediaUseCase::new(Arc::new(repo));
+
+        assert!(use_case.execute(MediaId::new(1), Some(owner)).await.is_ok());
+        let result = use_case.execute(MediaId::new(1), Some(UserId::new())).await;
+        assert!(matches!(result, Err(AppError::Authorization { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_get_media_rejects_anonymous_requests() {
+        let media = UnsavedMedia::new(
+            ContentHash::new(&"a".repeat(64)).unwrap(),
+            "private.jpg".to_string(),
+            MediaType::new("image/jpeg"),
+            "/path/to/file".to_string(),
+            1024,
+            UserId::new(),
+        )
+        .into_media(MediaId::new(1));
+
+        let repo = InMemoryMediaRepository::new().with_media(media);
+        let use_case = GetMediaUseCase::new(Arc::new(repo));
+
+        let result = use_case.execute(MediaId::new(1), None).await;
+        assert!(matches!(result, Err(AppError::Authorization { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_list_variants_starts_with_original() {
+        let owner = UserId::new();
+        let mut media = UnsavedMedia::new(
+            ContentHash::new(&"a".repeat(64)).unwrap(),
+            "pancakes.jpg".to_string(),
+            MediaType::new("image/jpeg"),
+            "aa/aa/aa/aaaa".to_string(),
+            4096,
+            owner,
+        )
+        .into_media(MediaId::new(1));
+        media.set_processing_status(ProcessingStatus::Complete);
+        media.variants = vec![
+            MediaVariant {
+                name: "webp".to_string(),
+                content_hash: ContentHash::new(&"b".repeat(64)).unwrap(),
+                media_type: MediaType::new("image/webp"),
+                file_size: 1024,
+            },
+            MediaVariant {
+                name: "thumbnail".to_string(),
+                content_hash: ContentHash::new(&"c".repeat(64)).unwrap(),
+                media_type: MediaType::new("image/jpeg"),
+                file_size: 256,
+            },
+        ];
+
+        let repo = InMemoryMediaRepository::new().with_media(media);
+        let use_case = GetMediaUseCase::new(Arc::new(repo));
+        let response = use_case.list_variants(MediaId::new(1), Some(owner)).await.unwrap();
+
+        let names: Vec<&str> = response.variants.iter().map(|v| v.name.as_str()).collect();
+        assert_eq!(names, vec!["original", "webp", "thumbnail"]);
+        assert_eq!(response.variants[0].file_size, 4096);
+        assert_eq!(response.variants[1].media_type, "image/webp");
+        assert_eq!(response.variants[2].file_size, 256);
+        assert_eq!(response.processing_status, ProcessingStatus::Complete);
+
+        let result = use_case.list_variants(MediaId::new(1), Some(UserId::new())).await;
+        assert!(matches!(result, Err(AppError::Authorization { .. })));
+    }
+
     #[tokio::test]
     async fn test_get_media_not_found() {
         let repo = InMemoryMediaRepository::new();
         let media_id = MediaId::new(999);
 
         let use_case = GetMediaUseCase::new(Arc::new(repo));
-        let result = use_case.execute(media_id).await;
+        let result = use_case.execute(media_id, None).await;
 
         assert!(result.is_err());
         match result.unwrap_err() {
@@ -137,6 +240,19 @@ mod tests {
         }
     }
 
-    // Repository error testing would require more complex error injection
-    // This is better tested with integration tests
+    #[tokio::test]
+    async fn test_get_media_database_unavailable() {
+        use crate::infrastructure::persistence::DisconnectedMediaRepository;
+
+        let repo = DisconnectedMediaRepository::new("connection refused".to_string());
+        let use_case = GetMediaUseCase::new(Arc::new(repo));
+        let result = use_case.execute(MediaId::new(1), None).await;
+
+        match result {
+            Err(AppError::ServiceUnavailable { last_error, .. }) => {
+                assert_eq!(last_error.as_deref(), Some("connection refused"));
+            }
+            other => panic!("Expected ServiceUnavailable, got {other:?}"),
+        }
+    }
 }
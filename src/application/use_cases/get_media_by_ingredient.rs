@@ -1,10 +1,13 @@
 use std::sync::Arc;
 
+use super::repository_error;
 use crate::{
+    application::dto::{AssociatedMediaQuery, MediaDto, PaginatedMediaResponse},
     domain::{
         entities::{IngredientId, MediaId, RecipeId},
         repositories::MediaRepository,
     },
+    infrastructure::persistence::pagination::{self, PageRequest},
     presentation::middleware::error::AppError,
 };
 
@@ -37,9 +40,7 @@ where
             .repository
             .find_media_ids_by_recipe_ingredient(recipe_id, ingredient_id)
             .await
-            .map_err(|e| AppError::Internal {
-                message: format!("Failed to query media by recipe ingredient: {e}"),
-            })?;
+            .map_err(repository_error("Failed to query media by recipe ingredient"))?;
 
         tracing::info!(
             "Found {} media files for recipe: {} ingredient: {}",
@@ -50,6 +51,43 @@ where
 
         Ok(media_ids)
     }
+
+    /// Execute the use case to get one page of full media details for a recipe ingredient
+    pub async fn execute_with_details(
+        &self,
+        recipe_id: RecipeId,
+        ingredient_id: IngredientId,
+        query: AssociatedMediaQuery,
+    ) -> Result<PaginatedMediaResponse, AppError> {
+        tracing::info!("Getting media for recipe: {} ingredient: {}", recipe_id, ingredient_id);
+
+        // Set default limit and validate, rejecting malformed cursors as client errors
+        let page_request = PageRequest::new(
+            query.cursor.as_deref(),
+            query.limit.unwrap_or(pagination::DEFAULT_PAGE_SIZE),
+        )?;
+
+        let (media, next_cursor, has_more) = self
+            .repository
+            .find_media_by_recipe_ingredient(
+                recipe_id,
+                ingredient_id,
+                query.cursor.clone(),
+                page_request.limit,
+            )
+            .await
+            .map_err(repository_error("Failed to query media by recipe ingredient"))?;
+
+        tracing::info!(
+            "Found {} media files for recipe: {} ingredient: {}",
+            media.len(),
+            recipe_id,
+            ingredient_id
+        );
+
+        let data = media.into_iter().map(MediaDto::from).collect();
+        Ok(PaginatedMediaResponse::from_page(data, next_cursor, has_more, query.cursor.is_some()))
+    }
 }
 
 #[cfg(test)]
@@ -72,13 +110,13 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_get_media_by_ingredient_zero_ids() {
+    async fn test_get_media_by_ingredient_large_ids() {
         let repo = InMemoryMediaRepository::new();
         let use_case = GetMediaByIngredientUseCase::new(Arc::new(repo));
 
-        // Test with zero recipe ID
-        let recipe_id = RecipeId::new(0);
-        let ingredient_id = IngredientId::new(1);
+        // Test with large IDs
+        let recipe_id = RecipeId::new(999_999_999);
+        let ingredient_id = IngredientId::new(888_888_888);
         let result = use_case.execute(recipe_id, ingredient_id).await;
 
         assert!(result.is_ok());
@@ -86,134 +124,187 @@ mod tests {
         assert!(media_ids.is_empty());
     }
 
-    #[tokio::test]
-    async fn test_get_media_by_ingredient_zero_ingredient_id() {
-        let repo = InMemoryMediaRepository::new();
-        let use_case = GetMediaByIngredientUseCase::new(Arc::new(repo));
+    use crate::domain::entities::*;
+    use crate::domain::repositories::MediaRepository;
+    use async_trait::async_trait;
 
-        // Test with zero ingredient ID
-        let recipe_id = RecipeId::new(1);
-        let ingredient_id = IngredientId::new(0);
-        let result = use_case.execute(recipe_id, ingredient_id).await;
+    /// Repository whose every query fails
+    struct ErrorMediaRepository;
 
-        assert!(result.is_ok());
-        let media_ids = result.unwrap();
-        assert!(media_ids.is_empty());
-    }
+    #[async_trait]
+    impl MediaRepository for ErrorMediaRepository {
+        type Error = AppError;
 
-    #[tokio::test]
-    async fn test_get_media_by_ingredient_large_ids() {
-        let repo = InMemoryMediaRepository::new();
-        let use_case = GetMediaByIngredientUseCase::new(Arc::new(repo));
+        async fn save(
+            &self,
+            _media: &crate::domain::entities::UnsavedMedia,
+        ) -> Result<crate::domain::entities::MediaId, Self::Error> {
+            Err(AppError::Internal { message: "Database error".to_string() })
+        }
 
-        // Test with large IDs
-        let recipe_id = RecipeId::new(999_999_999);
-        let ingredient_id = IngredientId::new(888_888_888);
-        let result = use_case.execute(recipe_id, ingredient_id).await;
+        async fn find_by_id(
+            &self,
+            _id: MediaId,
+        ) -> Result<Option<crate::domain::entities::Media>, Self::Error> {
+            Err(AppError::Internal { message: "Database error".to_string() })
+        }
 
-        assert!(result.is_ok());
-        let media_ids = result.unwrap();
-        assert!(media_ids.is_empty());
-    }
+        async fn find_by_content_hash(
+            &self,
+            _hash: &crate::domain::value_objects::ContentHash,
+            _owner: Option<UserId>,
+        ) -> Result<Option<crate::domain::entities::Media>, Self::Error> {
+            Err(AppError::Internal { message: "Database error".to_string() })
+        }
 
-    #[tokio::test]
-    async fn test_get_media_by_ingredient_repository_error() {
-        // Create a mock repository that always returns an error
-        use crate::domain::entities::*;
-        use crate::domain::repositories::MediaRepository;
-        use async_trait::async_trait;
+        async fn find_by_user(
+            &self,
+            _user_id: UserId,
+        ) -> Result<Vec<crate::domain::entities::Media>, Self::Error> {
+            Err(AppError::Internal { message: "Database error".to_string() })
+        }
 
-        struct ErrorMediaRepository;
+        async fn find_after(
+            &self,
+            _after: Option<MediaId>,
+            _limit: u32,
+        ) -> Result<Vec<crate::domain::entities::Media>, Self::Error> {
+            Err(AppError::Internal { message: "Database error".to_string() })
+        }
 
-        #[async_trait]
-        impl MediaRepository for ErrorMediaRepository {
-            type Error = AppError;
+        async fn find_unprocessed_before(
+            &self,
+            _updated_before: std::time::SystemTime,
+            _limit: u32,
+        ) -> Result<Vec<crate::domain::entities::Media>, Self::Error> {
+            Err(AppError::Internal { message: "Database error".to_string() })
+        }
 
-            async fn save(
-                &self,
-                _media: &crate::domain::entities::Media,
-            ) -> Result<crate::domain::entities::MediaId, Self::Error> {
-                Err(AppError::Internal { message: "Database error".to_string() })
-            }
+        async fn find_by_user_paginated(
+            &self,
+            _user_id: UserId,
+            _cursor: Option<String>,
+            _limit: u32,
+            _status_filter: Option<crate::domain::value_objects::ProcessingStatus>,
+            _tags: &[crate::domain::value_objects::MediaTag],
+            _sort: crate::domain::value_objects::MediaSort,
+        ) -> Result<(Vec<crate::domain::entities::Media>, Option<String>, bool), Self::Error>
+        {
+            Err(AppError::Internal { message: "Database error".to_string() })
+        }
 
-            async fn find_by_id(
-                &self,
-                _id: MediaId,
-            ) -> Result<Option<crate::domain::entities::Media>, Self::Error> {
-                Err(AppError::Internal { message: "Database error".to_string() })
-            }
+        async fn search_by_user(
+            &self,
+            _user_id: UserId,
+            _search: &crate::domain::value_objects::MediaSearch,
+            _cursor: Option<String>,
+            _limit: u32,
+        ) -> Result<(Vec<crate::domain::entities::Media>, Option<String>, bool), Self::Error>
+        {
+            Err(AppError::Internal { message: "Database error".to_string() })
+        }
 
-            async fn find_by_content_hash(
-                &self,
-                _hash: &crate::domain::value_objects::ContentHash,
-            ) -> Result<Option<crate::domain::entities::Media>, Self::Error> {
-                Err(AppError::Internal { message: "Database error".to_string() })
-            }
+        async fn update(&self, _media: &crate::domain::entities::Media) -> Result<(), Self::Error> {
+            Err(AppError::Internal { message: "Database error".to_string() })
+        }
 
-            async fn find_by_user(
-                &self,
-                _user_id: UserId,
-            ) -> Result<Vec<crate::domain::entities::Media>, Self::Error> {
-                Err(AppError::Internal { message: "Database error".to_string() })
-            }
+        async fn set_tags(
+            &self,
+            _media_id: MediaId,
+            _tags: &[crate::domain::value_objects::MediaTag],
+        ) -> Result<bool, Self::Error> {
+            Err(AppError::Internal { message: "Database error".to_string() })
+        }
 
-            async fn find_by_user_paginated(
-                &self,
-                _user_id: UserId,
-                _cursor: Option<String>,
-                _limit: u32,
-                _status_filter: Option<crate::domain::value_objects::ProcessingStatus>,
-            ) -> Result<(Vec<crate::domain::entities::Media>, Option<String>, bool), Self::Error>
-            {
-                Err(AppError::Internal { message: "Database error".to_string() })
-            }
+        async fn delete(&self, _id: MediaId) -> Result<bool, Self::Error> {
+            Err(AppError::Internal { message: "Database error".to_string() })
+        }
 
-            async fn update(
-                &self,
-                _media: &crate::domain::entities::Media,
-            ) -> Result<(), Self::Error> {
-                Err(AppError::Internal { message: "Database error".to_string() })
-            }
+        async fn exists_by_content_hash(
+            &self,
+            _hash: &crate::domain::value_objects::ContentHash,
+        ) -> Result<bool, Self::Error> {
+            Err(AppError::Internal { message: "Database error".to_string() })
+        }
 
-            async fn delete(&self, _id: MediaId) -> Result<bool, Self::Error> {
-                Err(AppError::Internal { message: "Database error".to_string() })
-            }
+        async fn find_media_ids_by_recipe(
+            &self,
+            _recipe_id: RecipeId,
+        ) -> Result<Vec<MediaId>, Self::Error> {
+            Err(AppError::Internal { message: "Database error".to_string() })
+        }
 
-            async fn exists_by_content_hash(
-                &self,
-                _hash: &crate::domain::value_objects::ContentHash,
-            ) -> Result<bool, Self::Error> {
-                Err(AppError::Internal { message: "Database error".to_string() })
-            }
+        async fn find_media_ids_by_recipe_ingredient(
+            &self,
+            _recipe_id: RecipeId,
+            _ingredient_id: IngredientId,
+        ) -> Result<Vec<MediaId>, Self::Error> {
+            Err(AppError::Internal { message: "Database connection failed".to_string() })
+        }
 
-            async fn find_media_ids_by_recipe(
-                &self,
-                _recipe_id: RecipeId,
-            ) -> Result<Vec<MediaId>, Self::Error> {
-                Err(AppError::Internal { message: "Database error".to_string() })
-            }
+        async fn find_media_ids_by_recipe_step(
+            &self,
+            _recipe_id: RecipeId,
+            _step_id: StepId,
+        ) -> Result<Vec<MediaId>, Self::Error> {
+            Err(AppError::Internal { message: "Database error".to_string() })
+        }
 
-            async fn find_media_ids_by_recipe_ingredient(
-                &self,
-                _recipe_id: RecipeId,
-                _ingredient_id: IngredientId,
-            ) -> Result<Vec<MediaId>, Self::Error> {
-                Err(AppError::Internal { message: "Database connection failed".to_string() })
-            }
+        async fn add_association(
+            &self,
+            _media_id: MediaId,
+            _association: crate::domain::entities::MediaAssociation,
+        ) -> Result<bool, Self::Error> {
+            Err(AppError::Internal { message: "Database error".to_string() })
+        }
 
-            async fn find_media_ids_by_recipe_step(
-                &self,
-                _recipe_id: RecipeId,
-                _step_id: StepId,
-            ) -> Result<Vec<MediaId>, Self::Error> {
-                Err(AppError::Internal { message: "Database error".to_string() })
-            }
+        async fn remove_association(
+            &self,
+            _media_id: MediaId,
+            _association: crate::domain::entities::MediaAssociation,
+        ) -> Result<bool, Self::Error> {
+            Err(AppError::Internal { message: "Database error".to_string() })
+        }
 
-            async fn health_check(&self) -> Result<(), Self::Error> {
-                Err(AppError::Internal { message: "Database error".to_string() })
-            }
+        async fn find_media_by_recipe(
+            &self,
+            _recipe_id: RecipeId,
+            _cursor: Option<String>,
+            _limit: u32,
+        ) -> Result<(Vec<crate::domain::entities::Media>, Option<String>, bool), Self::Error>
+        {
+            Err(AppError::Internal { message: "Database error".to_string() })
+        }
+
+        async fn find_media_by_recipe_ingredient(
+            &self,
+            _recipe_id: RecipeId,
+            _ingredient_id: IngredientId,
+            _cursor: Option<String>,
+            _limit: u32,
+        ) -> Result<(Vec<crate::domain::entities::Media>, Option<String>, bool), Self::Error>
+        {
+            Err(AppError::Internal { message: "Database connection failed".to_string() })
+        }
+
+        async fn find_media_by_recipe_step(
+            &self,
+            _recipe_id: RecipeId,
+            _step_id: StepId,
+            _cursor: Option<String>,
+            _limit: u32,
+        ) -> Result<(Vec<crate::domain::entities::Media>, Option<String>, bool), Self::Error>
+        {
+            Err(AppError::Internal { message: "Database error".to_string() })
         }
 
+        async fn health_check(&self) -> Result<(), Self::Error> {
+            Err(AppError::Internal { message: "Database error".to_string() })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_media_by_ingredient_repository_error() {
         let repo = ErrorMediaRepository;
         let use_case = GetMediaByIngredientUseCase::new(Arc::new(repo));
 
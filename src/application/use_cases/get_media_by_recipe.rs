@@ -1,10 +1,13 @@
 use std::sync::Arc;
 
+use super::repository_error;
 use crate::{
+    application::dto::{AssociatedMediaQuery, MediaDto, PaginatedMediaResponse},
     domain::{
         entities::{MediaId, RecipeId},
         repositories::MediaRepository,
     },
+    infrastructure::persistence::pagination::{self, PageRequest},
     presentation::middleware::error::AppError,
 };
 
@@ -29,14 +32,42 @@ where
     pub async fn execute(&self, recipe_id: RecipeId) -> Result<Vec<MediaId>, AppError> {
         tracing::info!("Getting media IDs for recipe: {}", recipe_id);
 
-        let media_ids = self.repository.find_media_ids_by_recipe(recipe_id).await.map_err(|e| {
-            AppError::Internal { message: format!("Failed to query media by recipe: {e}") }
-        })?;
+        let media_ids = self
+            .repository
+            .find_media_ids_by_recipe(recipe_id)
+            .await
+            .map_err(repository_error("Failed to query media by recipe"))?;
 
         tracing::info!("Found {} media files for recipe: {}", media_ids.len(), recipe_id);
 
         Ok(media_ids)
     }
+
+    /// Execute the use case to get one page of full media details for a recipe
+    pub async fn execute_with_details(
+        &self,
+        recipe_id: RecipeId,
+        query: AssociatedMediaQuery,
+    ) -> Result<PaginatedMediaResponse, AppError> {
+        tracing::info!("Getting media for recipe: {}", recipe_id);
+
+        // Set default limit and validate, rejecting malformed cursors as client errors
+        let page_request = PageRequest::new(
+            query.cursor.as_deref(),
+            query.limit.unwrap_or(pagination::DEFAULT_PAGE_SIZE),
+        )?;
+
+        let (media, next_cursor, has_more) = self
+            .repository
+            .find_media_by_recipe(recipe_id, query.cursor.clone(), page_request.limit)
+            .await
+            .map_err(repository_error("Failed to query media by recipe"))?;
+
+        tracing::info!("Found {} media files for recipe: {}", media.len(), recipe_id);
+
+        let data = media.into_iter().map(MediaDto::from).collect();
+        Ok(PaginatedMediaResponse::from_page(data, next_cursor, has_more, query.cursor.is_some()))
+    }
 }
 
 #[cfg(test)]
@@ -44,6 +75,115 @@ mod tests {
     use super::*;
     use crate::test_utils::mocks::InMemoryMediaRepository;
 
+    #[tokio::test]
+    async fn test_get_media_by_recipe_with_details() {
+        use crate::domain::{
+            entities::{UnsavedMedia, UserId},
+            value_objects::{ContentHash, MediaType},
+        };
+
+        let media = UnsavedMedia::new(
+            ContentHash::new(&"a".repeat(64)).unwrap(),
+            "cover.jpg".to_string(),
+            MediaType::new("image/jpeg"),
+            "aa/aa/aa/aaaa".to_string(),
+            2048,
+            UserId::new(),
+        )
+        .into_media(MediaId::new(5));
+
+        let recipe_id = RecipeId::new(1);
+        let repo = InMemoryMediaRepository::new()
+            .with_media(media)
+            .with_recipe_media(recipe_id, vec![MediaId::new(5), MediaId::new(99)]);
+        let use_case = GetMediaByRecipeUseCase::new(Arc::new(repo));
+
+        let page = use_case
+            .execute_with_details(recipe_id, AssociatedMediaQuery::default())
+            .await
+            .unwrap();
+        let media = page.data;
+
+        // Associations to missing media are skipped, as with an inner join
+        assert_eq!(media.len(), 1);
+        assert_eq!(media[0].id, MediaId::new(5));
+        assert_eq!(media[0].original_filename, "cover.jpg");
+        assert_eq!(media[0].file_size, 2048);
+        assert!(!page.pagination.has_next);
+        assert!(!page.pagination.has_prev);
+    }
+
+    #[tokio::test]
+    async fn test_get_media_by_recipe_with_details_pages_through_results() {
+        use crate::domain::{
+            entities::{UnsavedMedia, UserId},
+            value_objects::{ContentHash, MediaType},
+        };
+
+        let recipe_id = RecipeId::new(1);
+        let mut repo = InMemoryMediaRepository::new();
+        for id in 1..=3 {
+            let media = UnsavedMedia::new(
+                ContentHash::new(&format!("{id}").repeat(64)).unwrap(),
+                format!("step-{id}.jpg"),
+                MediaType::new("image/jpeg"),
+                format!("{id}{id}/{id}{id}/{id}{id}/{id}{id}{id}{id}"),
+                1024,
+                UserId::new(),
+            )
+            .into_media(MediaId::new(id));
+            repo = repo.with_media(media);
+        }
+        let repo = repo
+            .with_recipe_media(recipe_id, vec![MediaId::new(3), MediaId::new(1), MediaId::new(2)]);
+        let use_case = GetMediaByRecipeUseCase::new(Arc::new(repo));
+
+        let first = use_case
+            .execute_with_details(
+                recipe_id,
+                AssociatedMediaQuery { limit: Some(2), ..Default::default() },
+            )
+            .await
+            .unwrap();
+        let ids: Vec<_> = first.data.iter().map(|m| m.id).collect();
+        assert_eq!(ids, vec![MediaId::new(1), MediaId::new(2)]);
+        assert!(first.pagination.has_next);
+        assert!(!first.pagination.has_prev);
+
+        let second = use_case
+            .execute_with_details(
+                recipe_id,
+                AssociatedMediaQuery {
+                    cursor: first.pagination.next_cursor,
+                    limit: Some(2),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        let ids: Vec<_> = second.data.iter().map(|m| m.id).collect();
+        assert_eq!(ids, vec![MediaId::new(3)]);
+        assert!(!second.pagination.has_next);
+        assert!(second.pagination.has_prev);
+    }
+
+    #[tokio::test]
+    async fn test_get_media_by_recipe_with_details_rejects_bad_cursor() {
+        let use_case = GetMediaByRecipeUseCase::new(Arc::new(InMemoryMediaRepository::new()));
+
+        let result = use_case
+            .execute_with_details(
+                RecipeId::new(1),
+                AssociatedMediaQuery {
+                    cursor: Some("not-a-cursor".to_string()),
+                    ..Default::default()
+                },
+            )
+            .await;
+
+        assert!(matches!(result, Err(AppError::BadRequest { .. })));
+    }
+
     #[tokio::test]
     async fn test_get_media_by_recipe_empty() {
         let repo = InMemoryMediaRepository::new();
@@ -1,10 +1,13 @@
 use std::sync::Arc;
 
+use super::repository_error;
 use crate::{
+    application::dto::{AssociatedMediaQuery, MediaDto, PaginatedMediaResponse},
     domain::{
         entities::{MediaId, RecipeId, StepId},
         repositories::MediaRepository,
     },
+    infrastructure::persistence::pagination::{self, PageRequest},
     presentation::middleware::error::AppError,
 };
 
@@ -33,12 +36,11 @@ where
     ) -> Result<Vec<MediaId>, AppError> {
         tracing::info!("Getting media IDs for recipe: {} step: {}", recipe_id, step_id);
 
-        let media_ids =
-            self.repository.find_media_ids_by_recipe_step(recipe_id, step_id).await.map_err(
-                |e| AppError::Internal {
-                    message: format!("Failed to query media by recipe step: {e}"),
-                },
-            )?;
+        let media_ids = self
+            .repository
+            .find_media_ids_by_recipe_step(recipe_id, step_id)
+            .await
+            .map_err(repository_error("Failed to query media by recipe step"))?;
 
         tracing::info!(
             "Found {} media files for recipe: {} step: {}",
@@ -49,6 +51,38 @@ where
 
         Ok(media_ids)
     }
+
+    /// Execute the use case to get one page of full media details for a recipe step
+    pub async fn execute_with_details(
+        &self,
+        recipe_id: RecipeId,
+        step_id: StepId,
+        query: AssociatedMediaQuery,
+    ) -> Result<PaginatedMediaResponse, AppError> {
+        tracing::info!("Getting media for recipe: {} step: {}", recipe_id, step_id);
+
+        // Set default limit and validate, rejecting malformed cursors as client errors
+        let page_request = PageRequest::new(
+            query.cursor.as_deref(),
+            query.limit.unwrap_or(pagination::DEFAULT_PAGE_SIZE),
+        )?;
+
+        let (media, next_cursor, has_more) = self
+            .repository
+            .find_media_by_recipe_step(recipe_id, step_id, query.cursor.clone(), page_request.limit)
+            .await
+            .map_err(repository_error("Failed to query media by recipe step"))?;
+
+        tracing::info!(
+            "Found {} media files for recipe: {} step: {}",
+            media.len(),
+            recipe_id,
+            step_id
+        );
+
+        let data = media.into_iter().map(MediaDto::from).collect();
+        Ok(PaginatedMediaResponse::from_page(data, next_cursor, has_more, query.cursor.is_some()))
+    }
 }
 
 #[cfg(test)]
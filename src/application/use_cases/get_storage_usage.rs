@@ -0,0 +1,90 @@
+use std::sync::Arc;
+
+use super::repository_error;
+use crate::{
+    application::dto::StorageUsageResponse,
+    domain::{entities::UserId, repositories::MediaRepository, value_objects::StorageQuota},
+    presentation::middleware::error::AppError,
+};
+
+/// Use case for reporting a user's storage consumption against their quota
+pub struct GetStorageUsageUseCase<R>
+where
+    R: MediaRepository + ?Sized,
+{
+    repository: Arc<R>,
+    quota: StorageQuota,
+}
+
+impl<R> GetStorageUsageUseCase<R>
+where
+    R: MediaRepository + ?Sized,
+{
+    /// Create a new get storage usage use case
+    pub fn new(repository: Arc<R>, quota: StorageQuota) -> Self {
+        Self { repository, quota }
+    }
+
+    /// Execute the use case for the given user
+    pub async fn execute(&self, user_id: UserId) -> Result<StorageUsageResponse, AppError> {
+        let usage = self
+            .repository
+            .usage_by_user(user_id)
+            .await
+            .map_err(repository_error("Failed to read storage usage"))?;
+
+        Ok(StorageUsageResponse {
+            used_bytes: usage.bytes,
+            used_files: usage.files,
+            max_bytes: self.quota.max_bytes,
+            max_files: self.quota.max_files,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{
+        entities::{Media, MediaId},
+        value_objects::{ContentHash, MediaType, ProcessingStatus},
+    };
+    use crate::test_utils::mocks::InMemoryMediaRepository;
+
+    fn media(id: i64, owner: UserId, file_size: u64) -> Media {
+        Media::with_id(
+            MediaId::new(id),
+            ContentHash::new(&format!("{id:064x}")).unwrap(),
+            format!("{id}.jpg"),
+            MediaType::new("image/jpeg"),
+            format!("media/{id}"),
+            file_size,
+            ProcessingStatus::Complete,
+        )
+        .uploaded_by(owner)
+        .build()
+    }
+
+    #[tokio::test]
+    async fn test_reports_only_the_users_media() {
+        let owner = UserId::new();
+        let repository = InMemoryMediaRepository::new()
+            .with_media(media(1, owner, 100))
+            .with_media(media(2, owner, 250))
+            .with_media(media(3, UserId::new(), 1000));
+        let quota = StorageQuota { max_bytes: Some(1024), max_files: None };
+        let use_case = GetStorageUsageUseCase::new(Arc::new(repository), quota);
+
+        let usage = use_case.execute(owner).await.unwrap();
+
+        assert_eq!(
+            usage,
+            StorageUsageResponse {
+                used_bytes: 350,
+                used_files: 2,
+                max_bytes: Some(1024),
+                max_files: None,
+            }
+        );
+    }
+}
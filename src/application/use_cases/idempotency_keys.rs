@@ -0,0 +1,257 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use super::repository_error;
+use crate::{
+    domain::{
+        entities::{IdempotencyRecord, StoredResponse, UserId},
+        repositories::IdempotencyRepository,
+        services::{Clock, SystemClock},
+    },
+    presentation::middleware::error::AppError,
+};
+
+/// How long a key stays reserved by a request that never stores its response,
+/// e.g. because its instance went away, before retries may run it again
+const RESERVATION_LEASE: Duration = Duration::from_mins(10);
+
+/// How a request carrying an `Idempotency-Key` is to be handled
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IdempotentRequest {
+    /// First use of the key: run the request, then complete or release the record
+    First(IdempotencyRecord),
+    /// A retry: answer with the response stored for the first request
+    Replay(StoredResponse),
+}
+
+/// Responses stored by `Idempotency-Key`, for replaying to retried requests
+///
+/// Keys are scoped to the authenticated caller, so two users picking the same
+/// key never see each other's responses. A key is reserved while its first request runs and
+/// holds that request's response for `ttl` afterwards. Cloning is cheap; clones
+/// share the same store.
+#[derive(Clone)]
+pub struct IdempotencyKeys {
+    repository: Arc<dyn IdempotencyRepository<Error = AppError>>,
+    ttl: Duration,
+    clock: Arc<dyn Clock>,
+}
+
+impl IdempotencyKeys {
+    /// Store responses in `repository`, replaying them for `ttl`
+    #[must_use]
+    pub fn new(
+        repository: Arc<dyn IdempotencyRepository<Error = AppError>>,
+        ttl: Duration,
+    ) -> Self {
+        Self { repository, ttl, clock: Arc::new(SystemClock) }
+    }
+
+    /// Read the time from `clock`; the default is the system clock
+    #[must_use]
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Reserve `owner`'s `key` for `request`, or find the response stored for it
+    ///
+    /// `request` identifies the endpoint, e.g. `POST /media/upload-request`, and
+    /// `body_hash` the body sent to it.
+    ///
+    /// # Errors
+    /// * `Conflict` - The first request with this key is still in flight
+    /// * `IdempotencyKeyMismatch` - The key was used for a different request or body
+    /// * `Internal` - The store could not be read or written
+    pub async fn begin(
+        &self,
+        key: &str,
+        owner: UserId,
+        request: &str,
+        body_hash: &str,
+    ) -> Result<IdempotentRequest, AppError> {
+        let now = self.clock.now();
+        let record = IdempotencyRecord {
+            key: format!("{owner}:{key}"),
+            request: request.to_string(),
+            body_hash: body_hash.to_string(),
+            response: None,
+            created_at: now,
+            expires_at: now + RESERVATION_LEASE,
+        };
+        if self
+            .repository
+            .reserve(&record)
+            .await
+            .map_err(repository_error("Failed to reserve idempotency key"))?
+        {
+            return Ok(IdempotentRequest::First(record));
+        }
+
+        let held = self
+            .repository
+            .find(&record.key, now)
+            .await
+            .map_err(repository_error("Failed to look up idempotency key"))?;
+        match held {
+            Some(held) if held.request != record.request => Err(AppError::IdempotencyKeyMismatch {
+                message: format!(
+                    "Idempotency-Key was already used for {}; use a new key for each request",
+                    held.request
+                ),
+                original_request: held.request,
+            }),
+            Some(held) if held.body_hash != record.body_hash => {
+                Err(AppError::IdempotencyKeyMismatch {
+                    message: format!(
+                        "Idempotency-Key was already used for {} with a different body; use a \
+                         new key for each request",
+                        held.request
+                    ),
+                    original_request: held.request,
+                })
+            }
+            Some(IdempotencyRecord { response: Some(response), .. }) => {
+                tracing::info!("Replaying stored response for {}", record.request);
+                Ok(IdempotentRequest::Replay(response))
+            }
+            // Still in flight, or expired a moment ago; either way the retry is early
+            _ => Err(AppError::Conflict {
+                message: "A request with this Idempotency-Key is still in progress; retry it later"
+                    .to_string(),
+            }),
+        }
+    }
+
+    /// Store the response to a reserved request, to replay to its retries
+    ///
+    /// # Errors
+    /// Returns `Internal` if the response could not be stored
+    pub async fn complete(
+        &self,
+        mut record: IdempotencyRecord,
+        response: StoredResponse,
+    ) -> Result<(), AppError> {
+        record.response = Some(response);
+        record.expires_at = self.clock.now() + self.ttl;
+        self.repository
+            .complete(&record)
+            .await
+            .map_err(repository_error("Failed to store idempotent response"))
+    }
+
+    /// Free the key of a reserved request that produced no response worth
+    /// replaying, so a retry runs it again
+    ///
+    /// # Errors
+    /// Returns `Internal` if the reservation could not be removed
+    pub async fn release(&self, record: &IdempotencyRecord) -> Result<(), AppError> {
+        self.repository
+            .delete(&record.key)
+            .await
+            .map(|_| ())
+            .map_err(repository_error("Failed to release idempotency key"))
+    }
+
+    /// Delete every record past its expiry, returning how many were deleted
+    ///
+    /// Expired keys are already free for reuse; this only reclaims their space.
+    ///
+    /// # Errors
+    /// Returns `Internal` if expired records could not be deleted
+    pub async fn remove_expired(&self) -> Result<u64, AppError> {
+        let removed = self
+            .repository
+            .delete_expired(self.clock.now())
+            .await
+            .map_err(repository_error("Failed to delete expired idempotency keys"))?;
+        if removed > 0 {
+            tracing::info!("Removed {} expired idempotency keys", removed);
+        }
+        Ok(removed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        infrastructure::persistence::InMemoryIdempotencyRepository, test_utils::mocks::ManualClock,
+    };
+
+    /// Hash of the body the requests in these tests are sent with
+    const BODY: &str = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+
+    fn response() -> StoredResponse {
+        StoredResponse {
+            status: 201,
+            content_type: Some("application/json".to_string()),
+            body: br#"{"media_id":5}"#.to_vec(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retries_replay_the_first_response_until_it_expires() {
+        let clock = ManualClock::default();
+        let keys = IdempotencyKeys::new(
+            Arc::new(InMemoryIdempotencyRepository::new()),
+            Duration::from_hours(24),
+        )
+        .with_clock(Arc::new(clock.clone()));
+        let owner = UserId::new();
+
+        let IdempotentRequest::First(record) =
+            keys.begin("retry-1", owner, "POST /media/", BODY).await.unwrap()
+        else {
+            panic!("expected the key to be free");
+        };
+        let in_flight = keys.begin("retry-1", owner, "POST /media/", BODY).await.unwrap_err();
+        assert!(matches!(in_flight, AppError::Conflict { .. }));
+
+        keys.complete(record, response()).await.unwrap();
+        assert_eq!(
+            keys.begin("retry-1", owner, "POST /media/", BODY).await.unwrap(),
+            IdempotentRequest::Replay(response())
+        );
+        let other_request =
+            keys.begin("retry-1", owner, "POST /media/upload-request", BODY).await.unwrap_err();
+        assert!(matches!(
+            other_request,
+            AppError::IdempotencyKeyMismatch { ref original_request, .. }
+                if original_request == "POST /media/"
+        ));
+        let other_body = keys.begin("retry-1", owner, "POST /media/", "0f").await.unwrap_err();
+        assert!(matches!(other_body, AppError::IdempotencyKeyMismatch { .. }));
+        // Another user's key of the same name is unrelated
+        let other_user = keys.begin("retry-1", UserId::new(), "POST /media/", BODY).await;
+        assert!(matches!(other_user, Ok(IdempotentRequest::First(_))));
+
+        clock.advance(Duration::from_hours(24));
+        assert_eq!(keys.remove_expired().await.unwrap(), 2);
+        assert!(matches!(
+            keys.begin("retry-1", owner, "POST /media/", BODY).await,
+            Ok(IdempotentRequest::First(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_released_keys_run_again() {
+        let keys = IdempotencyKeys::new(
+            Arc::new(InMemoryIdempotencyRepository::new()),
+            Duration::from_hours(24),
+        );
+        let owner = UserId::new();
+
+        let IdempotentRequest::First(record) =
+            keys.begin("retry-2", owner, "POST /media/", BODY).await.unwrap()
+        else {
+            panic!("expected the key to be free");
+        };
+        keys.release(&record).await.unwrap();
+
+        assert!(matches!(
+            keys.begin("retry-2", owner, "POST /media/", BODY).await,
+            Ok(IdempotentRequest::First(_))
+        ));
+    }
+}
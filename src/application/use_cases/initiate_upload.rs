@@ -1,45 +1,192 @@
 use std::sync::Arc;
+use std::time::SystemTime;
 
+use super::{
+    ensure_within_quota, file_too_large, UploadFingerprint, UploadFingerprints, UploadLocks,
+};
 use crate::{
     application::dto::{InitiateUploadRequest, InitiateUploadResponse},
     domain::{
-        entities::{Media, UserId},
-        repositories::MediaRepository,
-        value_objects::{ContentHash, MediaType, ProcessingStatus},
+        entities::{PresignedUploadSession, UnsavedMedia, UserId, PENDING_UPLOAD_PATH},
+        repositories::{MediaRepository, UploadSessionRepository},
+        services::{Clock, SystemClock},
+        value_objects::{ContentHash, MediaType, ProcessingStatus, StorageQuota},
+    },
+    infrastructure::{
+        business_metrics::{BusinessMetrics, UploadFlow},
+        storage::PresignedUrlService,
     },
-    infrastructure::storage::PresignedUrlService,
     presentation::middleware::error::AppError,
 };
 
+/// Shortest content hash prefix accepted for upload fingerprinting
+const MIN_HASH_PREFIX_LEN: usize = 8;
+
 /// Use case for initiating presigned URL uploads
-pub struct InitiateUploadUseCase<R>
+pub struct InitiateUploadUseCase<R, U>
 where
     R: MediaRepository + ?Sized,
+    U: UploadSessionRepository + ?Sized,
 {
     repository: Arc<R>,
+    sessions: Arc<U>,
     presigned_service: PresignedUrlService,
     max_file_size: u64,
+    quota: StorageQuota,
+    clock: Arc<dyn Clock>,
+    upload_locks: UploadLocks,
+    fingerprints: UploadFingerprints,
+    metrics: BusinessMetrics,
 }
 
-impl<R> InitiateUploadUseCase<R>
+impl<R, U> InitiateUploadUseCase<R, U>
 where
     R: MediaRepository + ?Sized,
+    U: UploadSessionRepository + ?Sized,
 {
     /// Create a new initiate upload use case
     pub fn new(
         repository: Arc<R>,
+        sessions: Arc<U>,
         presigned_service: PresignedUrlService,
         max_file_size: u64,
     ) -> Self {
-        Self { repository, presigned_service, max_file_size }
+        Self {
+            repository,
+            sessions,
+            presigned_service,
+            max_file_size,
+            quota: StorageQuota::unlimited(),
+            clock: Arc::new(SystemClock),
+            upload_locks: UploadLocks::new(),
+            fingerprints: UploadFingerprints::disabled(),
+            metrics: BusinessMetrics::disabled(),
+        }
+    }
+
+    /// Limit how much each user may store
+    #[must_use]
+    pub fn with_quota(mut self, quota: StorageQuota) -> Self {
+        self.quota = quota;
+        self
+    }
+
+    /// Read the time from `clock` when recording new sessions
+    #[must_use]
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Share an upload lock registry with other upload paths
+    #[must_use]
+    pub fn with_upload_locks(mut self, upload_locks: UploadLocks) -> Self {
+        self.upload_locks = upload_locks;
+        self
+    }
+
+    /// Collapse repeated initiations of the same upload into the first one
+    #[must_use]
+    pub fn with_fingerprints(mut self, fingerprints: UploadFingerprints) -> Self {
+        self.fingerprints = fingerprints;
+        self
+    }
+
+    /// Count initiated uploads as started, and rejected ones as failed
+    #[must_use]
+    pub fn with_metrics(mut self, metrics: BusinessMetrics) -> Self {
+        self.metrics = metrics;
+        self
     }
 
     /// Execute the upload initiation
+    ///
+    /// With fingerprinting enabled, a request carrying a content hash prefix that
+    /// repeats a recent one from the same user gets the earlier session back.
     pub async fn execute(
         &self,
         request: InitiateUploadRequest,
         user_id: UserId,
     ) -> Result<InitiateUploadResponse, AppError> {
+        self.execute_for_tenant(None, request, user_id).await
+    }
+
+    /// Execute the upload initiation on behalf of a tenant
+    ///
+    /// The tenant is recorded on the session, so its format policy applies when
+    /// the file arrives at the presigned URL.
+    pub async fn execute_for_tenant(
+        &self,
+        tenant: Option<&str>,
+        request: InitiateUploadRequest,
+        user_id: UserId,
+    ) -> Result<InitiateUploadResponse, AppError> {
+        Self::validate_upload_request(&request)?;
+
+        let fingerprint = self.fingerprint(&request, user_id);
+        let Some(fingerprint) = fingerprint else {
+            return self.initiate(tenant, request, user_id).await.map(|(response, _)| response);
+        };
+
+        // Serialize repeats so the second one sees the session the first created
+        let _fingerprint_guard = self.upload_locks.acquire_fingerprint(&fingerprint).await;
+
+        if let Some(response) = self.fingerprints.recent(&fingerprint, self.clock.now()) {
+            tracing::info!(
+                "Upload of {} repeats a recent upload, reusing media {}",
+                request.filename,
+                response.media_id
+            );
+            metrics::counter!("media_upload_fingerprint_hits_total").increment(1);
+            return Ok(response);
+        }
+
+        let initiated_at = self.clock.now();
+        let (response, expires_at) = self.initiate(tenant, request, user_id).await?;
+        self.fingerprints.record(fingerprint, initiated_at, expires_at, &response);
+
+        Ok(response)
+    }
+
+    fn fingerprint(
+        &self,
+        request: &InitiateUploadRequest,
+        user_id: UserId,
+    ) -> Option<UploadFingerprint> {
+        if !self.fingerprints.is_enabled() {
+            return None;
+        }
+        let hash_prefix = request.content_hash_prefix.as_ref()?;
+        Some(UploadFingerprint {
+            user_id,
+            filename: request.filename.clone(),
+            file_size: request.file_size,
+            hash_prefix: hash_prefix.to_ascii_lowercase(),
+        })
+    }
+
+    /// Start a new presigned upload; repeats collapsed by fingerprint don't count
+    async fn initiate(
+        &self,
+        tenant: Option<&str>,
+        request: InitiateUploadRequest,
+        user_id: UserId,
+    ) -> Result<(InitiateUploadResponse, SystemTime), AppError> {
+        self.metrics.upload_started(UploadFlow::Presigned);
+        let result = self.create_session(tenant, request, user_id).await;
+        self.metrics.record_failure(UploadFlow::Presigned, &result);
+        result
+    }
+
+    /// Create the placeholder media record and presigned session for an upload
+    ///
+    /// Returns the response along with when the session expires.
+    async fn create_session(
+        &self,
+        tenant: Option<&str>,
+        request: InitiateUploadRequest,
+        user_id: UserId,
+    ) -> Result<(InitiateUploadResponse, SystemTime), AppError> {
         tracing::info!(
             "Initiating upload session for file: {} (size: {} bytes, type: {})",
             request.filename,
@@ -47,19 +194,14 @@ where
             request.content_type
         );
 
-        // Validate the upload request
-        Self::validate_upload_request(&request)?;
-
         // Validate file size
         if request.file_size > self.max_file_size {
-            return Err(AppError::BadRequest {
-                message: format!(
-                    "File size {} bytes exceeds maximum allowed size of {} bytes",
-                    request.file_size, self.max_file_size
-                ),
-            });
+            return Err(file_too_large(request.file_size, self.max_file_size));
         }
 
+        // The placeholder saved below reserves the declared size against the quota
+        ensure_within_quota(&*self.repository, self.quota, user_id, request.file_size).await?;
+
         // Validate content type
         let media_type = MediaType::new(&request.content_type);
 
@@ -70,7 +212,8 @@ where
             &media_type,
             request.file_size,
             user_id,
-        );
+        )
+        .with_tenant(tenant);
 
         // Save placeholder to database to get media ID
         let media_id = self.repository.save(&placeholder_media).await.map_err(|e| {
@@ -92,19 +235,41 @@ where
                 AppError::Internal { message: format!("Failed to generate upload URL: {e}") }
             })?;
 
+        // Persist the session so the upload token can be resolved to the media record
+        let session = PresignedUploadSession {
+            upload_token: upload_session.upload_token.clone(),
+            media_id,
+            user_id,
+            tenant: tenant.map(String::from),
+            filename: request.filename.clone(),
+            content_type: request.content_type.clone(),
+            expected_size: request.file_size,
+            created_at: self.clock.now(),
+            expires_at: upload_session.expires_at.into(),
+        };
+
+        if let Err(e) = self.sessions.create(&session).await {
+            tracing::error!("Failed to persist upload session: {}", e);
+            let _ = self.repository.delete(media_id).await;
+            return Err(AppError::Internal {
+                message: format!("Failed to create upload session: {e}"),
+            });
+        }
+
         tracing::info!(
             "Upload session created successfully - media_id: {}, expires at: {}",
             media_id,
             upload_session.expires_at
         );
 
-        Ok(InitiateUploadResponse {
+        let response = InitiateUploadResponse {
             media_id,
             upload_url: upload_session.upload_url,
             upload_token: upload_session.upload_token,
             expires_at: upload_session.expires_at.to_rfc3339(),
             status: ProcessingStatus::Pending,
-        })
+        };
+        Ok((response, session.expires_at))
     }
 
     /// Create a placeholder media entity for the upload session
@@ -113,19 +278,22 @@ where
         media_type: &MediaType,
         file_size: u64,
         user_id: UserId,
-    ) -> Media {
-        // Create a placeholder content hash - will be updated when file is uploaded
-        let placeholder_hash = ContentHash::new(
-            &"0".repeat(64), // Placeholder hash
-        )
+    ) -> UnsavedMedia {
+        // Create a unique placeholder content hash - will be updated when file is uploaded.
+        // Content hashes are unique, so a shared placeholder would collide across sessions
+        let placeholder_hash = ContentHash::new(&format!(
+            "{}{}",
+            uuid::Uuid::new_v4().simple(),
+            uuid::Uuid::new_v4().simple()
+        ))
         .unwrap();
 
         // Create placeholder media entity
-        let mut media = Media::new(
+        let mut media = UnsavedMedia::new(
             placeholder_hash,
             filename.to_string(),
             media_type.clone(),
-            "pending".to_string(), // Placeholder path
+            PENDING_UPLOAD_PATH.to_string(),
             file_size,
             user_id,
         );
@@ -167,6 +335,18 @@ where
             });
         }
 
+        if let Some(prefix) = &request.content_hash_prefix {
+            let valid = (MIN_HASH_PREFIX_LEN..=64).contains(&prefix.len())
+                && prefix.chars().all(|c| c.is_ascii_hexdigit());
+            if !valid {
+                return Err(AppError::BadRequest {
+                    message: format!(
+                        "content_hash_prefix must be {MIN_HASH_PREFIX_LEN} to 64 hex characters"
+                    ),
+                });
+            }
+        }
+
         Ok(())
     }
 }
@@ -175,21 +355,33 @@ where
 mod tests {
     use super::*;
     use crate::{
-        infrastructure::storage::PresignedUrlConfig, test_utils::mocks::InMemoryMediaRepository,
+        infrastructure::{
+            persistence::InMemoryUploadSessionRepository, storage::PresignedUrlConfig,
+        },
+        test_utils::mocks::{InMemoryMediaRepository, ManualClock},
     };
-    use std::time::Duration;
+    use std::time::{Duration, SystemTime};
 
-    fn create_test_use_case() -> InitiateUploadUseCase<InMemoryMediaRepository> {
+    type TestUseCase =
+        InitiateUploadUseCase<InMemoryMediaRepository, InMemoryUploadSessionRepository>;
+
+    fn create_test_use_case() -> TestUseCase {
+        create_test_use_case_with_sessions(Arc::new(InMemoryUploadSessionRepository::new()))
+    }
+
+    fn create_test_use_case_with_sessions(
+        sessions: Arc<InMemoryUploadSessionRepository>,
+    ) -> TestUseCase {
         let repository = Arc::new(InMemoryMediaRepository::new());
         let config = PresignedUrlConfig {
             secret_key: "test-secret".to_string(),
             base_url: "http://localhost:3000".to_string(),
-            default_expiration: Duration::from_secs(900),
+            default_expiration: Duration::from_mins(15),
             max_file_size: 10 * 1024 * 1024, // 10MB
         };
         let presigned_service = PresignedUrlService::new(config);
 
-        InitiateUploadUseCase::new(repository, presigned_service, 10 * 1024 * 1024)
+        InitiateUploadUseCase::new(repository, sessions, presigned_service, 10 * 1024 * 1024)
     }
 
     #[tokio::test]
@@ -200,7 +392,8 @@ mod tests {
         let request = InitiateUploadRequest {
             filename: "test.jpg".to_string(),
             content_type: "image/jpeg".to_string(),
-            file_size: 1024 * 1024, // 1MB
+            file_size: 1024 * 1024,
+            content_hash_prefix: None, // 1MB
         };
 
         let result = use_case.execute(request, user_id).await;
@@ -221,17 +414,18 @@ mod tests {
         let request = InitiateUploadRequest {
             filename: "large.jpg".to_string(),
             content_type: "image/jpeg".to_string(),
-            file_size: 50 * 1024 * 1024, // 50MB (exceeds 10MB limit)
+            file_size: 50 * 1024 * 1024,
+            content_hash_prefix: None, // 50MB (exceeds 10MB limit)
         };
 
         let result = use_case.execute(request, user_id).await;
 
         assert!(result.is_err());
         match result.unwrap_err() {
-            AppError::BadRequest { message } => {
+            AppError::PayloadTooLarge { message } => {
                 assert!(message.contains("exceeds maximum allowed size"));
             }
-            _ => panic!("Expected BadRequest error"),
+            _ => panic!("Expected PayloadTooLarge error"),
         }
     }
 
@@ -244,10 +438,10 @@ mod tests {
             filename: String::new(),
             content_type: "image/jpeg".to_string(),
             file_size: 1024,
+            content_hash_prefix: None,
         };
 
-        let result =
-            InitiateUploadUseCase::<InMemoryMediaRepository>::validate_upload_request(&request);
+        let result = TestUseCase::validate_upload_request(&request);
 
         assert!(result.is_err());
         match result.unwrap_err() {
@@ -277,10 +471,10 @@ mod tests {
                 filename: filename.to_string(),
                 content_type: "application/octet-stream".to_string(),
                 file_size: 1024,
+                content_hash_prefix: None,
             };
 
-            let result =
-                InitiateUploadUseCase::<InMemoryMediaRepository>::validate_upload_request(&request);
+            let result = TestUseCase::validate_upload_request(&request);
             assert!(result.is_err(), "Should reject dangerous file: {filename}");
         }
     }
@@ -293,10 +487,10 @@ mod tests {
             filename: "empty.txt".to_string(),
             content_type: "text/plain".to_string(),
             file_size: 0,
+            content_hash_prefix: None,
         };
 
-        let result =
-            InitiateUploadUseCase::<InMemoryMediaRepository>::validate_upload_request(&request);
+        let result = TestUseCase::validate_upload_request(&request);
 
         assert!(result.is_err());
         match result.unwrap_err() {
@@ -315,10 +509,10 @@ mod tests {
             filename: "test.txt".to_string(),
             content_type: "invalid_content_type".to_string(), // Missing slash
             file_size: 1024,
+            content_hash_prefix: None,
         };
 
-        let result =
-            InitiateUploadUseCase::<InMemoryMediaRepository>::validate_upload_request(&request);
+        let result = TestUseCase::validate_upload_request(&request);
 
         assert!(result.is_err());
         match result.unwrap_err() {
@@ -338,6 +532,7 @@ mod tests {
             filename: "secure.png".to_string(),
             content_type: "image/png".to_string(),
             file_size: 2048,
+            content_hash_prefix: None,
         };
 
         let result = use_case.execute(request, user_id).await.unwrap();
@@ -348,4 +543,141 @@ mod tests {
         assert!(result.upload_url.contains("size=2048"));
         assert!(result.upload_url.contains("type=image%2Fpng"));
     }
+
+    #[tokio::test]
+    async fn test_upload_session_is_persisted() {
+        let sessions = Arc::new(InMemoryUploadSessionRepository::new());
+        let use_case = create_test_use_case_with_sessions(sessions.clone());
+        let user_id = UserId::new();
+
+        let request = InitiateUploadRequest {
+            filename: "photo.jpg".to_string(),
+            content_type: "image/jpeg".to_string(),
+            file_size: 4096,
+            content_hash_prefix: None,
+        };
+
+        let response = use_case.execute(request, user_id).await.unwrap();
+
+        let session = sessions.find_by_token(&response.upload_token).await.unwrap().unwrap();
+        assert_eq!(session.media_id, response.media_id);
+        assert_eq!(session.user_id, user_id);
+        assert_eq!(session.filename, "photo.jpg");
+        assert_eq!(session.expected_size, 4096);
+        assert!(!session.is_expired_at(session.created_at));
+    }
+
+    #[tokio::test]
+    async fn test_session_times_come_from_the_clock() {
+        let start = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let clock: Arc<dyn Clock> = Arc::new(ManualClock::new(start));
+        let sessions = Arc::new(InMemoryUploadSessionRepository::new());
+        let use_case = InitiateUploadUseCase::new(
+            Arc::new(InMemoryMediaRepository::new()),
+            sessions.clone(),
+            PresignedUrlService::new(PresignedUrlConfig::default()).with_clock(clock.clone()),
+            10 * 1024 * 1024,
+        )
+        .with_clock(clock);
+
+        let request = InitiateUploadRequest {
+            filename: "photo.jpg".to_string(),
+            content_type: "image/jpeg".to_string(),
+            file_size: 4096,
+            content_hash_prefix: None,
+        };
+        let response = use_case.execute(request, UserId::new()).await.unwrap();
+
+        let session = sessions.find_by_token(&response.upload_token).await.unwrap().unwrap();
+        assert_eq!(session.created_at, start);
+        assert_eq!(session.expires_at, start + Duration::from_mins(15));
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_sessions_get_distinct_media_records() {
+        let use_case = create_test_use_case();
+        let request = InitiateUploadRequest {
+            filename: "photo.jpg".to_string(),
+            content_type: "image/jpeg".to_string(),
+            file_size: 4096,
+            content_hash_prefix: None,
+        };
+
+        let first = use_case.execute(request.clone(), UserId::new()).await.unwrap();
+        let second = use_case.execute(request, UserId::new()).await.unwrap();
+
+        assert_ne!(first.media_id, second.media_id);
+    }
+
+    #[tokio::test]
+    async fn test_initiate_upload_counts_pending_uploads_against_quota() {
+        let use_case = create_test_use_case()
+            .with_quota(StorageQuota { max_bytes: Some(3 * 1024 * 1024), max_files: None });
+        let user_id = UserId::new();
+        let request = |file_size| InitiateUploadRequest {
+            filename: "test.jpg".to_string(),
+            content_type: "image/jpeg".to_string(),
+            file_size,
+            content_hash_prefix: None,
+        };
+
+        use_case.execute(request(2 * 1024 * 1024), user_id).await.unwrap();
+        let result = use_case.execute(request(2 * 1024 * 1024), user_id).await;
+
+        match result {
+            Err(AppError::QuotaExceeded { usage, .. }) => {
+                assert_eq!(usage.bytes, 2 * 1024 * 1024);
+                assert_eq!(usage.files, 1);
+            }
+            other => panic!("expected QuotaExceeded, got {other:?}"),
+        }
+        assert!(use_case.execute(request(1024 * 1024), UserId::new()).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_repeated_uploads_within_window_share_one_session() {
+        let clock = Arc::new(ManualClock::default());
+        let use_case = create_test_use_case()
+            .with_clock(clock.clone())
+            .with_fingerprints(UploadFingerprints::new(Duration::from_secs(10)));
+        let user_id = UserId::new();
+        let request = |prefix: &str| InitiateUploadRequest {
+            filename: "pancakes.jpg".to_string(),
+            content_type: "image/jpeg".to_string(),
+            file_size: 2048,
+            content_hash_prefix: Some(prefix.to_string()),
+        };
+
+        let first = use_case.execute(request("ABCDEF0123"), user_id).await.unwrap();
+        let repeat = use_case.execute(request("abcdef0123"), user_id).await.unwrap();
+        assert_eq!(repeat.media_id, first.media_id);
+        assert_eq!(repeat.upload_token, first.upload_token);
+
+        let other_file = use_case.execute(request("abcdef0124"), user_id).await.unwrap();
+        let other_user = use_case.execute(request("abcdef0123"), UserId::new()).await.unwrap();
+        let without_prefix = InitiateUploadRequest { content_hash_prefix: None, ..request("") };
+        let unfingerprinted = use_case.execute(without_prefix, user_id).await.unwrap();
+        clock.advance(Duration::from_secs(10));
+        let after_window = use_case.execute(request("abcdef0123"), user_id).await.unwrap();
+
+        for response in [other_file, other_user, unfingerprinted, after_window] {
+            assert_ne!(response.media_id, first.media_id);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_invalid_content_hash_prefix_is_rejected() {
+        let use_case = create_test_use_case();
+
+        for prefix in ["abc123", "not-hex-at-all", &"a".repeat(65)] {
+            let request = InitiateUploadRequest {
+                filename: "pancakes.jpg".to_string(),
+                content_type: "image/jpeg".to_string(),
+                file_size: 2048,
+                content_hash_prefix: Some(prefix.to_string()),
+            };
+            let result = use_case.execute(request, UserId::new()).await;
+            assert!(matches!(result, Err(AppError::BadRequest { .. })), "{prefix}");
+        }
+    }
 }
@@ -1,29 +1,44 @@
 use std::sync::Arc;
+use tokio::task::JoinSet;
 
+use super::{repository_error, tag_filter};
 use crate::{
-    application::dto::{MediaDto, PaginatedMediaQuery, PaginatedMediaResponse, PaginationInfo},
+    application::dto::{
+        MediaDto, MediaVariantDto, PaginatedMediaQuery, PaginatedMediaResponse, PaginationInfo,
+    },
     domain::{
-        entities::{Media, UserId},
+        entities::{Media, MediaVariant, UserId},
         repositories::MediaRepository,
+        value_objects::MediaSortKey,
+    },
+    infrastructure::{
+        persistence::pagination::{self, KeysetPageRequest, PageRequest},
+        storage::FileStorage,
     },
     presentation::middleware::error::AppError,
 };
 
+/// Maximum number of concurrent variant metadata lookups per list request
+const VARIANT_PREFETCH_CONCURRENCY: usize = 8;
+
 /// Use case for listing media with pagination and filtering
-pub struct ListMediaUseCase<R>
+pub struct ListMediaUseCase<R, S>
 where
     R: MediaRepository + ?Sized,
+    S: FileStorage + ?Sized,
 {
     repository: Arc<R>,
+    storage: Arc<S>,
 }
 
-impl<R> ListMediaUseCase<R>
+impl<R, S> ListMediaUseCase<R, S>
 where
     R: MediaRepository + ?Sized,
+    S: FileStorage + ?Sized + 'static,
 {
     /// Create a new list media use case
-    pub fn new(repository: Arc<R>) -> Self {
-        Self { repository }
+    pub fn new(repository: Arc<R>, storage: Arc<S>) -> Self {
+        Self { repository, storage }
     }
 
     /// Execute the list media use case
@@ -35,22 +50,50 @@ where
     ) -> Result<PaginatedMediaResponse, AppError> {
         tracing::info!("Listing paginated media for user: {} with query: {:?}", user_id, query);
 
-        // Set default limit and validate
-        let limit = query.limit.unwrap_or(50).clamp(1, 100);
+        // Set default limit and validate, rejecting malformed cursors as client errors.
+        // Sorted listings use keyset cursors that only fit the sort they were issued for.
+        let sort = query.sort();
+        let requested_limit = query.limit.unwrap_or(pagination::DEFAULT_PAGE_SIZE);
+        let limit = if sort.is_default() {
+            PageRequest::new(query.cursor.as_deref(), requested_limit)?.limit
+        } else {
+            KeysetPageRequest::<MediaSortKey>::new(
+                sort.name(),
+                sort.is_descending(),
+                query.cursor.as_deref(),
+                requested_limit,
+            )?
+            .limit
+        };
+        let include_variants = query.includes("variants");
+        let tags = tag_filter(query.tags.as_deref())?;
 
         // Use repository pagination
         let (media_list, next_cursor, has_more) = self
             .repository
-            .find_by_user_paginated(user_id, query.cursor.clone(), limit, query.status)
+            .find_by_user_paginated(user_id, query.cursor.clone(), limit, query.status, &tags, sort)
             .await
-            .map_err(|e| AppError::Internal {
-                message: format!("Failed to query paginated media: {e}"),
-            })?;
+            .map_err(repository_error("Failed to query paginated media"))?;
 
         tracing::info!("Found {} media files for user (paginated)", media_list.len());
 
         // Convert to DTOs
-        let media_dtos: Vec<MediaDto> = media_list.into_iter().map(Self::media_to_dto).collect();
+        let media_dtos: Vec<MediaDto> = if include_variants {
+            let variants = self.prefetch_variants(&media_list).await;
+            media_list
+                .into_iter()
+                .zip(variants)
+                .map(|(media, variants)| MediaDto {
+                    variants: Some(variants),
+                    ..MediaDto::from(media)
+                })
+                .collect()
+        } else {
+            media_list
+                .into_iter()
+                .map(|media| MediaDto { variants: None, ..MediaDto::from(media) })
+                .collect()
+        };
 
         // Determine if there's a previous page based on cursor presence
         let has_prev = query.cursor.is_some();
@@ -82,38 +125,71 @@ where
         Ok(response)
     }
 
-    /// Convert Media entity to `MediaDto`
-    fn media_to_dto(media: Media) -> MediaDto {
-        MediaDto {
-            id: media.id,
-            content_hash: media.content_hash.as_str().to_string(),
-            original_filename: media.original_filename,
-            media_type: media.media_type.mime_type().to_string(),
-            media_path: media.media_path,
-            file_size: media.file_size,
-            processing_status: media.processing_status,
-            uploaded_at: media.uploaded_at.duration_since(std::time::UNIX_EPOCH).map_or_else(
-                |_| chrono::Utc::now().to_rfc3339(),
-                |d| {
-                    chrono::DateTime::<chrono::Utc>::from_timestamp(
-                        d.as_secs() as i64,
-                        d.subsec_nanos(),
-                    )
-                    .unwrap_or_default()
-                    .to_rfc3339()
-                },
-            ),
-            updated_at: media.updated_at.duration_since(std::time::UNIX_EPOCH).map_or_else(
-                |_| chrono::Utc::now().to_rfc3339(),
-                |d| {
-                    chrono::DateTime::<chrono::Utc>::from_timestamp(
-                        d.as_secs() as i64,
-                        d.subsec_nanos(),
-                    )
-                    .unwrap_or_default()
-                    .to_rfc3339()
-                },
-            ),
+    /// Look up the stored variants of each item, issuing storage lookups concurrently
+    ///
+    /// Every variant recorded on the media gets its own lookup, with at most
+    /// `VARIANT_PREFETCH_CONCURRENCY` in flight at once. A variant whose lookup
+    /// fails is left out rather than failing the page.
+    async fn prefetch_variants(&self, media_list: &[Media]) -> Vec<Vec<MediaVariantDto>> {
+        let mut lookups: JoinSet<(usize, usize, Option<MediaVariantDto>)> = JoinSet::new();
+        let mut results: Vec<Vec<Option<MediaVariantDto>>> =
+            media_list.iter().map(|media| vec![None; media.variants.len()]).collect();
+
+        let variants: Vec<(usize, usize, MediaVariant)> = media_list
+            .iter()
+            .enumerate()
+            .flat_map(|(index, media)| {
+                media
+                    .variants
+                    .iter()
+                    .cloned()
+                    .enumerate()
+                    .map(move |(at, variant)| (index, at, variant))
+            })
+            .collect();
+        for (index, position, variant) in variants {
+            if lookups.len() >= VARIANT_PREFETCH_CONCURRENCY {
+                if let Some(Ok((done, at, variant))) = lookups.join_next().await {
+                    results[done][at] = variant;
+                }
+            }
+
+            let storage = Arc::clone(&self.storage);
+            lookups
+                .spawn(async move { (index, position, lookup_variant(&*storage, variant).await) });
+        }
+
+        while let Some(joined) = lookups.join_next().await {
+            if let Ok((done, at, variant)) = joined {
+                results[done][at] = variant;
+            }
+        }
+
+        results.into_iter().map(|variants| variants.into_iter().flatten().collect()).collect()
+    }
+}
+
+/// Look up the stored content of a single variant
+async fn lookup_variant<S>(storage: &S, variant: MediaVariant) -> Option<MediaVariantDto>
+where
+    S: FileStorage + ?Sized,
+{
+    match storage.metadata(&variant.content_hash).await {
+        Ok(metadata) => Some(MediaVariantDto {
+            name: variant.name,
+            content_type: Some(variant.media_type.mime_type().to_string()),
+            file_size: metadata.size,
+            last_modified: chrono::DateTime::<chrono::Utc>::from(metadata.last_modified)
+                .to_rfc3339(),
+        }),
+        Err(e) => {
+            tracing::warn!(
+                "Failed to look up variant {} ({}): {}",
+                variant.name,
+                variant.content_hash,
+                e
+            );
+            None
         }
     }
 }
@@ -123,11 +199,19 @@ mod tests {
     use super::*;
     use crate::{
         domain::{
-            entities::{MediaId, UserId},
-            value_objects::{ContentHash, MediaType, ProcessingStatus},
+            entities::{Media, MediaId, MediaVariant, UnsavedMedia, UserId},
+            value_objects::{ContentHash, MediaSortField, MediaType, ProcessingStatus, SortOrder},
         },
+        infrastructure::storage::{utils::generate_content_hash, FilesystemStorage},
         test_utils::mocks::InMemoryMediaRepository,
     };
+    use tempfile::TempDir;
+
+    fn create_test_storage() -> (TempDir, Arc<FilesystemStorage>) {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = Arc::new(FilesystemStorage::new(temp_dir.path()));
+        (temp_dir, storage)
+    }
 
     fn create_test_media(
         id: i64,
@@ -136,14 +220,15 @@ mod tests {
         user_id: UserId,
     ) -> Media {
         let content_hash = ContentHash::new(&format!("{:0>64}", id.to_string())).unwrap();
-        let mut media = Media::new(
+        let mut media = UnsavedMedia::new(
             content_hash,
             filename.to_string(),
             MediaType::new("image/jpeg"),
             format!("/path/to/{filename}"),
             1024,
             user_id,
-        );
+        )
+        .into_media(MediaId::new(id));
         media.set_processing_status(status);
         media
     }
@@ -152,18 +237,25 @@ mod tests {
     async fn test_list_media_success() {
         let user_id = UserId::new();
 
-        let mut media1 = create_test_media(1, "file1.jpg", ProcessingStatus::Complete, user_id);
-        media1.id = MediaId::new(1);
-        let mut media2 = create_test_media(2, "file2.jpg", ProcessingStatus::Pending, user_id);
-        media2.id = MediaId::new(2);
-        let mut media3 = create_test_media(3, "file3.jpg", ProcessingStatus::Complete, user_id);
-        media3.id = MediaId::new(3);
+        let media1 = create_test_media(1, "file1.jpg", ProcessingStatus::Complete, user_id);
+        let media2 = create_test_media(2, "file2.jpg", ProcessingStatus::Pending, user_id);
+        let media3 = create_test_media(3, "file3.jpg", ProcessingStatus::Complete, user_id);
 
         let repo =
             InMemoryMediaRepository::new().with_media(media1).with_media(media2).with_media(media3);
 
-        let use_case = ListMediaUseCase::new(Arc::new(repo));
-        let query = PaginatedMediaQuery { cursor: None, limit: None, status: None };
+        let (_temp_dir, storage) = create_test_storage();
+
+        let use_case = ListMediaUseCase::new(Arc::new(repo), storage);
+        let query = PaginatedMediaQuery {
+            cursor: None,
+            limit: None,
+            status: None,
+            include: None,
+            sort_by: None,
+            order: None,
+            tags: None,
+        };
 
         let result = use_case.execute(query, user_id).await;
 
@@ -182,23 +274,26 @@ mod tests {
     async fn test_list_media_with_status_filter() {
         let user_id = UserId::new();
 
-        let mut media1 = create_test_media(1, "file1.jpg", ProcessingStatus::Complete, user_id);
-        media1.id = MediaId::new(1);
+        let media1 = create_test_media(1, "file1.jpg", ProcessingStatus::Complete, user_id);
 
-        let mut media2 = create_test_media(2, "file2.jpg", ProcessingStatus::Pending, user_id);
-        media2.id = MediaId::new(2);
+        let media2 = create_test_media(2, "file2.jpg", ProcessingStatus::Pending, user_id);
 
-        let mut media3 = create_test_media(3, "file3.jpg", ProcessingStatus::Complete, user_id);
-        media3.id = MediaId::new(3);
+        let media3 = create_test_media(3, "file3.jpg", ProcessingStatus::Complete, user_id);
 
         let repo =
             InMemoryMediaRepository::new().with_media(media1).with_media(media2).with_media(media3);
 
-        let use_case = ListMediaUseCase::new(Arc::new(repo));
+        let (_temp_dir, storage) = create_test_storage();
+
+        let use_case = ListMediaUseCase::new(Arc::new(repo), storage);
         let query = PaginatedMediaQuery {
             cursor: None,
             limit: None,
             status: Some(ProcessingStatus::Complete),
+            include: None,
+            sort_by: None,
+            order: None,
+            tags: None,
         };
 
         let result = use_case.execute(query, user_id).await;
@@ -216,14 +311,10 @@ mod tests {
     async fn test_list_media_with_pagination() {
         let user_id = UserId::new();
 
-        let mut media1 = create_test_media(1, "file1.jpg", ProcessingStatus::Complete, user_id);
-        media1.id = MediaId::new(1);
-        let mut media2 = create_test_media(2, "file2.jpg", ProcessingStatus::Complete, user_id);
-        media2.id = MediaId::new(2);
-        let mut media3 = create_test_media(3, "file3.jpg", ProcessingStatus::Complete, user_id);
-        media3.id = MediaId::new(3);
-        let mut media4 = create_test_media(4, "file4.jpg", ProcessingStatus::Complete, user_id);
-        media4.id = MediaId::new(4);
+        let media1 = create_test_media(1, "file1.jpg", ProcessingStatus::Complete, user_id);
+        let media2 = create_test_media(2, "file2.jpg", ProcessingStatus::Complete, user_id);
+        let media3 = create_test_media(3, "file3.jpg", ProcessingStatus::Complete, user_id);
+        let media4 = create_test_media(4, "file4.jpg", ProcessingStatus::Complete, user_id);
 
         let repo = InMemoryMediaRepository::new()
             .with_media(media1)
@@ -231,8 +322,18 @@ mod tests {
             .with_media(media3)
             .with_media(media4);
 
-        let use_case = ListMediaUseCase::new(Arc::new(repo));
-        let query = PaginatedMediaQuery { cursor: None, limit: Some(2), status: None };
+        let (_temp_dir, storage) = create_test_storage();
+
+        let use_case = ListMediaUseCase::new(Arc::new(repo), storage);
+        let query = PaginatedMediaQuery {
+            cursor: None,
+            limit: Some(2),
+            status: None,
+            include: None,
+            sort_by: None,
+            order: None,
+            tags: None,
+        };
 
         let result = use_case.execute(query, user_id).await;
 
@@ -248,8 +349,18 @@ mod tests {
         let repo = InMemoryMediaRepository::new();
         let user_id = UserId::new();
 
-        let use_case = ListMediaUseCase::new(Arc::new(repo));
-        let query = PaginatedMediaQuery { cursor: None, limit: None, status: None };
+        let (_temp_dir, storage) = create_test_storage();
+
+        let use_case = ListMediaUseCase::new(Arc::new(repo), storage);
+        let query = PaginatedMediaQuery {
+            cursor: None,
+            limit: None,
+            status: None,
+            include: None,
+            sort_by: None,
+            order: None,
+            tags: None,
+        };
 
         let result = use_case.execute(query, user_id).await;
 
@@ -262,18 +373,25 @@ mod tests {
     async fn test_list_media_paginated_first_page() {
         let user_id = UserId::new();
 
-        let mut media1 = create_test_media(1, "file1.jpg", ProcessingStatus::Complete, user_id);
-        media1.id = MediaId::new(1);
-        let mut media2 = create_test_media(2, "file2.jpg", ProcessingStatus::Complete, user_id);
-        media2.id = MediaId::new(2);
-        let mut media3 = create_test_media(3, "file3.jpg", ProcessingStatus::Complete, user_id);
-        media3.id = MediaId::new(3);
+        let media1 = create_test_media(1, "file1.jpg", ProcessingStatus::Complete, user_id);
+        let media2 = create_test_media(2, "file2.jpg", ProcessingStatus::Complete, user_id);
+        let media3 = create_test_media(3, "file3.jpg", ProcessingStatus::Complete, user_id);
 
         let repo =
             InMemoryMediaRepository::new().with_media(media1).with_media(media2).with_media(media3);
 
-        let use_case = ListMediaUseCase::new(Arc::new(repo));
-        let query = PaginatedMediaQuery { cursor: None, limit: Some(2), status: None };
+        let (_temp_dir, storage) = create_test_storage();
+
+        let use_case = ListMediaUseCase::new(Arc::new(repo), storage);
+        let query = PaginatedMediaQuery {
+            cursor: None,
+            limit: Some(2),
+            status: None,
+            include: None,
+            sort_by: None,
+            order: None,
+            tags: None,
+        };
 
         let result = use_case.execute(query, user_id).await;
 
@@ -289,20 +407,27 @@ mod tests {
     async fn test_list_media_paginated_with_cursor() {
         let user_id = UserId::new();
 
-        let mut media1 = create_test_media(1, "file1.jpg", ProcessingStatus::Complete, user_id);
-        media1.id = MediaId::new(1);
-        let mut media2 = create_test_media(2, "file2.jpg", ProcessingStatus::Complete, user_id);
-        media2.id = MediaId::new(2);
-        let mut media3 = create_test_media(3, "file3.jpg", ProcessingStatus::Complete, user_id);
-        media3.id = MediaId::new(3);
+        let media1 = create_test_media(1, "file1.jpg", ProcessingStatus::Complete, user_id);
+        let media2 = create_test_media(2, "file2.jpg", ProcessingStatus::Complete, user_id);
+        let media3 = create_test_media(3, "file3.jpg", ProcessingStatus::Complete, user_id);
 
         let repo =
             InMemoryMediaRepository::new().with_media(media1).with_media(media2).with_media(media3);
 
-        let use_case = ListMediaUseCase::new(Arc::new(repo));
+        let (_temp_dir, storage) = create_test_storage();
+
+        let use_case = ListMediaUseCase::new(Arc::new(repo), storage);
 
         // Get first page
-        let first_query = PaginatedMediaQuery { cursor: None, limit: Some(1), status: None };
+        let first_query = PaginatedMediaQuery {
+            cursor: None,
+            limit: Some(1),
+            status: None,
+            include: None,
+            sort_by: None,
+            order: None,
+            tags: None,
+        };
 
         let first_result = use_case.execute(first_query, user_id).await;
         assert!(first_result.is_ok());
@@ -315,6 +440,10 @@ mod tests {
             cursor: first_response.pagination.next_cursor,
             limit: Some(1),
             status: None,
+            include: None,
+            sort_by: None,
+            order: None,
+            tags: None,
         };
 
         let second_result = use_case.execute(second_query, user_id).await;
@@ -328,21 +457,24 @@ mod tests {
     async fn test_list_media_paginated_with_status_filter() {
         let user_id = UserId::new();
 
-        let mut media1 = create_test_media(1, "file1.jpg", ProcessingStatus::Complete, user_id);
-        media1.id = MediaId::new(1);
-        let mut media2 = create_test_media(2, "file2.jpg", ProcessingStatus::Pending, user_id);
-        media2.id = MediaId::new(2);
-        let mut media3 = create_test_media(3, "file3.jpg", ProcessingStatus::Complete, user_id);
-        media3.id = MediaId::new(3);
+        let media1 = create_test_media(1, "file1.jpg", ProcessingStatus::Complete, user_id);
+        let media2 = create_test_media(2, "file2.jpg", ProcessingStatus::Pending, user_id);
+        let media3 = create_test_media(3, "file3.jpg", ProcessingStatus::Complete, user_id);
 
         let repo =
             InMemoryMediaRepository::new().with_media(media1).with_media(media2).with_media(media3);
 
-        let use_case = ListMediaUseCase::new(Arc::new(repo));
+        let (_temp_dir, storage) = create_test_storage();
+
+        let use_case = ListMediaUseCase::new(Arc::new(repo), storage);
         let query = PaginatedMediaQuery {
             cursor: None,
             limit: Some(10),
             status: Some(ProcessingStatus::Complete),
+            include: None,
+            sort_by: None,
+            order: None,
+            tags: None,
         };
 
         let result = use_case.execute(query, user_id).await;
@@ -362,8 +494,18 @@ mod tests {
         let repo = InMemoryMediaRepository::new();
         let user_id = UserId::new();
 
-        let use_case = ListMediaUseCase::new(Arc::new(repo));
-        let query = PaginatedMediaQuery { cursor: None, limit: Some(50), status: None };
+        let (_temp_dir, storage) = create_test_storage();
+
+        let use_case = ListMediaUseCase::new(Arc::new(repo), storage);
+        let query = PaginatedMediaQuery {
+            cursor: None,
+            limit: Some(50),
+            status: None,
+            include: None,
+            sort_by: None,
+            order: None,
+            tags: None,
+        };
 
         let result = use_case.execute(query, user_id).await;
 
@@ -379,30 +521,236 @@ mod tests {
     async fn test_list_media_paginated_limit_validation() {
         let user_id = UserId::new();
 
-        let mut media1 = create_test_media(1, "file1.jpg", ProcessingStatus::Complete, user_id);
-        media1.id = MediaId::new(1);
+        let media1 = create_test_media(1, "file1.jpg", ProcessingStatus::Complete, user_id);
 
         let repo = InMemoryMediaRepository::new().with_media(media1);
-        let use_case = ListMediaUseCase::new(Arc::new(repo));
+        let (_temp_dir, storage) = create_test_storage();
+        let use_case = ListMediaUseCase::new(Arc::new(repo), storage);
 
         // Test default limit
-        let query_no_limit = PaginatedMediaQuery { cursor: None, limit: None, status: None };
+        let query_no_limit = PaginatedMediaQuery {
+            cursor: None,
+            limit: None,
+            status: None,
+            include: None,
+            sort_by: None,
+            order: None,
+            tags: None,
+        };
 
         let result = use_case.execute(query_no_limit, user_id).await;
         assert!(result.is_ok());
 
         // Test limit too high (should be capped at 100)
-        let query_high_limit = PaginatedMediaQuery { cursor: None, limit: Some(200), status: None };
+        let query_high_limit = PaginatedMediaQuery {
+            cursor: None,
+            limit: Some(200),
+            status: None,
+            include: None,
+            sort_by: None,
+            order: None,
+            tags: None,
+        };
 
         let result = use_case.execute(query_high_limit, user_id).await;
         assert!(result.is_ok());
 
         // Test limit too low (should be minimum 1)
-        let query_low_limit = PaginatedMediaQuery { cursor: None, limit: Some(0), status: None };
+        let query_low_limit = PaginatedMediaQuery {
+            cursor: None,
+            limit: Some(0),
+            status: None,
+            include: None,
+            sort_by: None,
+            order: None,
+            tags: None,
+        };
 
         let result = use_case.execute(query_low_limit, user_id).await;
         assert!(result.is_ok());
     }
 
+    #[tokio::test]
+    async fn test_list_media_rejects_malformed_cursor() {
+        let user_id = UserId::new();
+        let repo = InMemoryMediaRepository::new();
+        let (_temp_dir, storage) = create_test_storage();
+        let use_case = ListMediaUseCase::new(Arc::new(repo), storage);
+
+        let query = PaginatedMediaQuery {
+            cursor: Some("not-a-cursor!".to_string()),
+            limit: None,
+            status: None,
+            include: None,
+            sort_by: None,
+            order: None,
+            tags: None,
+        };
+
+        let result = use_case.execute(query, user_id).await;
+        assert!(matches!(result, Err(AppError::BadRequest { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_list_media_omits_variants_by_default() {
+        let user_id = UserId::new();
+        let media1 = create_test_media(1, "file1.jpg", ProcessingStatus::Complete, user_id);
+
+        let repo = InMemoryMediaRepository::new().with_media(media1);
+        let (_temp_dir, storage) = create_test_storage();
+        let use_case = ListMediaUseCase::new(Arc::new(repo), storage);
+        let query = PaginatedMediaQuery {
+            cursor: None,
+            limit: None,
+            status: None,
+            include: None,
+            sort_by: None,
+            order: None,
+            tags: None,
+        };
+
+        let response = use_case.execute(query, user_id).await.unwrap();
+
+        assert_eq!(response.data.len(), 1);
+        assert!(response.data[0].variants.is_none());
+    }
+
+    /// A WebP variant whose content is `content`
+    fn webp_variant(content: &[u8]) -> MediaVariant {
+        MediaVariant {
+            name: "webp".to_string(),
+            content_hash: generate_content_hash(content).unwrap(),
+            media_type: MediaType::new("image/webp"),
+            file_size: content.len() as u64,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_list_media_includes_variants() {
+        let user_id = UserId::new();
+        let mut media1 = create_test_media(1, "file1.jpg", ProcessingStatus::Complete, user_id);
+        let media2 = create_test_media(2, "file2.jpg", ProcessingStatus::Complete, user_id);
+
+        let (_temp_dir, storage) = create_test_storage();
+        let webp = webp_variant(b"webp content");
+        storage.store(&webp.content_hash, &mut &b"webp content"[..]).await.unwrap();
+        // Recorded on the media, but its content is gone from storage
+        let avif = MediaVariant { name: "avif".to_string(), ..webp_variant(b"avif content") };
+        media1.variants = vec![webp, avif];
+
+        let repo = InMemoryMediaRepository::new().with_media(media1).with_media(media2);
+        let use_case = ListMediaUseCase::new(Arc::new(repo), storage);
+        let query = PaginatedMediaQuery {
+            cursor: None,
+            limit: None,
+            status: None,
+            include: Some("variants".to_string()),
+            sort_by: None,
+            order: None,
+            tags: None,
+        };
+
+        let response = use_case.execute(query, user_id).await.unwrap();
+
+        assert_eq!(response.data.len(), 2);
+        for dto in &response.data {
+            let variants = dto.variants.as_ref().expect("variants should be populated");
+            if dto.original_filename == "file1.jpg" {
+                // Missing variants are left out rather than failing the page
+                assert_eq!(variants.len(), 1);
+                assert_eq!(variants[0].name, "webp");
+                assert_eq!(variants[0].content_type.as_deref(), Some("image/webp"));
+                assert_eq!(variants[0].file_size, 12);
+            } else {
+                assert!(variants.is_empty());
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_list_media_variants_beyond_concurrency_limit() {
+        let user_id = UserId::new();
+        let (_temp_dir, storage) = create_test_storage();
+        let mut repo = InMemoryMediaRepository::new();
+
+        let item_count = VARIANT_PREFETCH_CONCURRENCY as i64 * 3;
+        for id in 1..=item_count {
+            let mut media = create_test_media(
+                id,
+                &format!("file{id}.jpg"),
+                ProcessingStatus::Complete,
+                user_id,
+            );
+            let content = vec![0u8; id as usize];
+            let variant = webp_variant(&content);
+            storage.store(&variant.content_hash, &mut content.as_slice()).await.unwrap();
+            media.variants = vec![variant];
+            repo = repo.with_media(media);
+        }
+
+        let use_case = ListMediaUseCase::new(Arc::new(repo), storage);
+        let query = PaginatedMediaQuery {
+            cursor: None,
+            limit: Some(100),
+            status: None,
+            include: Some("variants".to_string()),
+            sort_by: None,
+            order: None,
+            tags: None,
+        };
+
+        let response = use_case.execute(query, user_id).await.unwrap();
+
+        assert_eq!(response.data.len() as i64, item_count);
+        for dto in &response.data {
+            let variants = dto.variants.as_ref().unwrap();
+            assert_eq!(variants.len(), 1);
+            assert_eq!(variants[0].file_size, dto.id.as_i64() as u64);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_list_media_sorted_pages_are_stable() {
+        let user_id = UserId::new();
+        let mut repo = InMemoryMediaRepository::new();
+        for (id, filename) in [(1, "c.jpg"), (2, "a.jpg"), (3, "b.jpg"), (4, "a.jpg")] {
+            repo = repo.with_media(create_test_media(
+                id,
+                filename,
+                ProcessingStatus::Complete,
+                user_id,
+            ));
+        }
+        let (_temp_dir, storage) = create_test_storage();
+        let use_case = ListMediaUseCase::new(Arc::new(repo), storage);
+
+        let mut query = PaginatedMediaQuery {
+            cursor: None,
+            limit: Some(3),
+            status: None,
+            include: None,
+            sort_by: Some(MediaSortField::Filename),
+            order: Some(SortOrder::Desc),
+            tags: None,
+        };
+        let first = use_case.execute(query.clone(), user_id).await.unwrap();
+        query.cursor.clone_from(&first.pagination.next_cursor);
+        let second = use_case.execute(query.clone(), user_id).await.unwrap();
+
+        let ids: Vec<i64> =
+            first.data.iter().chain(&second.data).map(|dto| dto.id.as_i64()).collect();
+        assert_eq!(ids, vec![1, 3, 4, 2]);
+        assert!(!second.pagination.has_next);
+
+        // A cursor only fits the sort it was issued for
+        query.order = Some(SortOrder::Asc);
+        let result = use_case.execute(query.clone(), user_id).await;
+        assert!(matches!(result, Err(AppError::BadRequest { .. })));
+
+        query.sort_by = None;
+        let result = use_case.execute(query, user_id).await;
+        assert!(matches!(result, Err(AppError::BadRequest { .. })));
+    }
+
     // Repository error testing is better handled in integration tests
 }
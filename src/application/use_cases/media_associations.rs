@@ -0,0 +1,247 @@
+use std::sync::Arc;
+
+use super::{ensure_owner, repository_error};
+use crate::{
+    application::events::{DomainEvent, EventBus, EventPublisher},
+    domain::{
+        entities::{MediaAssociation, MediaId, UserId},
+        repositories::{MediaChange, MediaRepository},
+    },
+    presentation::middleware::error::AppError,
+};
+
+/// Use case for attaching media to recipes, ingredients and steps, and detaching it
+///
+/// Only the uploader may attach or detach their media. Recipes are owned by the
+/// recipe service, so their IDs are not checked here.
+pub struct MediaAssociationsUseCase<R>
+where
+    R: MediaRepository + ?Sized,
+{
+    repository: Arc<R>,
+    events: Arc<dyn EventPublisher>,
+}
+
+impl<R> MediaAssociationsUseCase<R>
+where
+    R: MediaRepository + ?Sized,
+{
+    /// Create a new media associations use case
+    pub fn new(repository: Arc<R>) -> Self {
+        Self { repository, events: Arc::new(EventBus::new()) }
+    }
+
+    /// Announce attached and detached media to `events`
+    #[must_use]
+    pub fn with_events(mut self, events: Arc<dyn EventPublisher>) -> Self {
+        self.events = events;
+        self
+    }
+
+    /// Attach media to a recipe, ingredient or step
+    ///
+    /// Returns false if the media was already attached there, which is not an error.
+    ///
+    /// # Errors
+    /// * `NotFound` - Media with the given ID doesn't exist
+    /// * `Authorization` - The media was uploaded by another user
+    pub async fn associate(
+        &self,
+        media_id: MediaId,
+        association: MediaAssociation,
+        requester: Option<UserId>,
+    ) -> Result<bool, AppError> {
+        tracing::info!("Attaching media {} to {}", media_id, association);
+
+        let uploaded_by = self.ensure_media_owner(media_id, requester).await?;
+
+        let attached = DomainEvent::MediaAttached { media_id, uploaded_by, association };
+        let recorded = self
+            .repository
+            .apply(
+                MediaChange::AddAssociation(media_id, association),
+                std::slice::from_ref(&attached),
+            )
+            .await
+            .map_err(repository_error("Failed to attach media"))?;
+        let added = recorded.outcome;
+
+        if added {
+            if !recorded.events_recorded {
+                self.events.publish(&attached);
+            }
+        } else {
+            tracing::info!("Media {} was already attached to {}", media_id, association);
+        }
+
+        Ok(added)
+    }
+
+    /// Detach media from a recipe, ingredient or step
+    ///
+    /// # Errors
+    /// * `NotFound` - The media doesn't exist or isn't attached there
+    /// * `Authorization` - The media was uploaded by another user
+    pub async fn dissociate(
+        &self,
+        media_id: MediaId,
+        association: MediaAssociation,
+        requester: Option<UserId>,
+    ) -> Result<(), AppError> {
+        tracing::info!("Detaching media {} from {}", media_id, association);
+
+        let uploaded_by = self.ensure_media_owner(media_id, requester).await?;
+
+        let detached = DomainEvent::MediaDetached { media_id, uploaded_by, association };
+        let recorded = self
+            .repository
+            .apply(
+                MediaChange::RemoveAssociation(media_id, association),
+                std::slice::from_ref(&detached),
+            )
+            .await
+            .map_err(repository_error("Failed to detach media"))?;
+
+        if recorded.outcome {
+            if !recorded.events_recorded {
+                self.events.publish(&detached);
+            }
+            Ok(())
+        } else {
+            Err(AppError::NotFound {
+                resource: format!("Media {media_id} attached to {association}"),
+            })
+        }
+    }
+
+    async fn ensure_media_owner(
+        &self,
+        media_id: MediaId,
+        requester: Option<UserId>,
+    ) -> Result<UserId, AppError> {
+        let media = self
+            .repository
+            .find_by_id(media_id)
+            .await
+            .map_err(repository_error("Failed to query media"))?
+            .ok_or_else(|| AppError::NotFound { resource: format!("Media with ID {media_id}") })?;
+
+        ensure_owner(&media, requester)?;
+        Ok(media.uploaded_by)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        domain::{
+            entities::{IngredientId, RecipeId, StepId, UnsavedMedia},
+            value_objects::{ContentHash, MediaType},
+        },
+        test_utils::mocks::{InMemoryMediaRepository, RecordedEvents},
+    };
+
+    fn setup(
+        owner: UserId,
+    ) -> (MediaAssociationsUseCase<InMemoryMediaRepository>, Arc<InMemoryMediaRepository>) {
+        let media = UnsavedMedia::new(
+            ContentHash::new(&"a".repeat(64)).unwrap(),
+            "cover.jpg".to_string(),
+            MediaType::new("image/jpeg"),
+            "aa/aa/aa/aaaa".to_string(),
+            2048,
+            owner,
+        )
+        .into_media(MediaId::new(5));
+
+        let repo = Arc::new(InMemoryMediaRepository::new().with_media(media));
+        (MediaAssociationsUseCase::new(repo.clone()), repo)
+    }
+
+    #[tokio::test]
+    async fn test_associate_is_idempotent() {
+        let owner = UserId::new();
+        let (use_case, repo) = setup(owner);
+        let events = RecordedEvents::new();
+        let use_case = use_case.with_events(Arc::new(events.clone()));
+        let recipe_id = RecipeId::new(42);
+        let association = MediaAssociation::Recipe(recipe_id);
+
+        assert!(use_case.associate(MediaId::new(5), association, Some(owner)).await.unwrap());
+        assert!(!use_case.associate(MediaId::new(5), association, Some(owner)).await.unwrap());
+
+        let ids = repo.find_media_ids_by_recipe(recipe_id).await.unwrap();
+        assert_eq!(ids, vec![MediaId::new(5)]);
+        // Only the first call changed anything
+        assert_eq!(
+            events.events(),
+            [DomainEvent::MediaAttached {
+                media_id: MediaId::new(5),
+                uploaded_by: owner,
+                association
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_associations_are_scoped_to_their_target() {
+        let owner = UserId::new();
+        let (use_case, repo) = setup(owner);
+        let recipe_id = RecipeId::new(42);
+
+        use_case
+            .associate(
+                MediaId::new(5),
+                MediaAssociation::Ingredient(recipe_id, IngredientId::new(7)),
+                Some(owner),
+            )
+            .await
+            .unwrap();
+
+        let ingredient =
+            repo.find_media_ids_by_recipe_ingredient(recipe_id, IngredientId::new(7)).await;
+        assert_eq!(ingredient.unwrap(), vec![MediaId::new(5)]);
+        assert!(repo.find_media_ids_by_recipe(recipe_id).await.unwrap().is_empty());
+        assert!(repo
+            .find_media_ids_by_recipe_step(recipe_id, StepId::new(7))
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_dissociate_removes_and_reports_missing() {
+        let owner = UserId::new();
+        let (use_case, repo) = setup(owner);
+        let recipe_id = RecipeId::new(42);
+        let association = MediaAssociation::Step(recipe_id, StepId::new(3));
+
+        use_case.associate(MediaId::new(5), association, Some(owner)).await.unwrap();
+        use_case.dissociate(MediaId::new(5), association, Some(owner)).await.unwrap();
+
+        assert!(repo
+            .find_media_ids_by_recipe_step(recipe_id, StepId::new(3))
+            .await
+            .unwrap()
+            .is_empty());
+
+        let result = use_case.dissociate(MediaId::new(5), association, Some(owner)).await;
+        assert!(matches!(result, Err(AppError::NotFound { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_rejects_missing_and_foreign_media() {
+        let (use_case, _) = setup(UserId::new());
+        let association = MediaAssociation::Recipe(RecipeId::new(42));
+
+        let result = use_case.associate(MediaId::new(6), association, None).await;
+        assert!(matches!(result, Err(AppError::NotFound { .. })));
+
+        let result = use_case.associate(MediaId::new(5), association, Some(UserId::new())).await;
+        assert!(matches!(result, Err(AppError::Authorization { .. })));
+
+        let result = use_case.dissociate(MediaId::new(5), association, Some(UserId::new())).await;
+        assert!(matches!(result, Err(AppError::Authorization { .. })));
+    }
+}
@@ -1,19 +1,529 @@
+use crate::{
+    domain::{
+        entities::{Media, MediaId, MediaVariant, UserId},
+        repositories::{MediaRepository, VariantRepository},
+        value_objects::{ContentHash, FormatPolicy, MediaTag, MediaType, QuotaLimit, StorageQuota},
+    },
+    infrastructure::{
+        processing::{ImageVariantEncoder, ProcessingError},
+        storage::{
+            detect_content_type, generate_content_hash_async, has_known_signature,
+            sniff_content_type, FileStorage, StorageError,
+        },
+    },
+    presentation::middleware::error::AppError,
+};
+
+mod allowed_types;
+mod backfill_variants;
+mod batch_get_media;
+mod capacity_report;
+mod check_processing_sla;
+mod complete_presigned_upload;
 mod delete_media;
 mod download_media;
 mod get_media;
 mod get_media_by_ingredient;
 mod get_media_by_recipe;
 mod get_media_by_step;
+mod get_storage_usage;
+mod idempotency_keys;
 mod initiate_upload;
 mod list_media;
+mod media_associations;
+mod process_media;
+mod processing_progress;
+mod read_only_mode;
+mod relay_events;
+mod render_cache;
+mod render_media;
+mod repair_replicas;
+mod reprocess_media;
+mod resumable_upload;
+mod scan_integrity;
+mod search_media;
+mod set_media_tags;
+mod upload_fingerprints;
+mod upload_locks;
 mod upload_media;
 
+pub use allowed_types::AllowedTypes;
+pub use backfill_variants::{
+    BackfillBatch, BackfillVariantsUseCase, VariantBackfills, DEFAULT_BACKFILL_BATCH_SIZE,
+    MAX_BACKFILL_BATCH_SIZE,
+};
+pub use batch_get_media::BatchGetMediaUseCase;
+pub use capacity_report::{
+    CapacityReportUseCase, DEFAULT_CAPACITY_REPORT_WEEKS, MAX_CAPACITY_REPORT_WEEKS,
+};
+pub use check_processing_sla::{CheckProcessingSlaUseCase, SlaCheck, MAX_OVERDUE_MEDIA};
+pub use complete_presigned_upload::CompletePresignedUploadUseCase;
 pub use delete_media::DeleteMediaUseCase;
-pub use download_media::DownloadMediaUseCase;
+pub use download_media::{DownloadMediaUseCase, DownloadResponse};
 pub use get_media::GetMediaUseCase;
 pub use get_media_by_ingredient::GetMediaByIngredientUseCase;
 pub use get_media_by_recipe::GetMediaByRecipeUseCase;
 pub use get_media_by_step::GetMediaByStepUseCase;
+pub use get_storage_usage::GetStorageUsageUseCase;
+pub use idempotency_keys::{IdempotencyKeys, IdempotentRequest};
 pub use initiate_upload::InitiateUploadUseCase;
 pub use list_media::ListMediaUseCase;
+pub use media_associations::MediaAssociationsUseCase;
+pub use process_media::ProcessMediaUseCase;
+pub use processing_progress::ProcessingProgress;
+pub use read_only_mode::ReadOnlyMode;
+pub use relay_events::RelayEventsUseCase;
+pub use render_cache::{RenderCache, RenderedImage};
+pub use render_media::RenderMediaUseCase;
+pub use repair_replicas::{RepairReplicasUseCase, ReplicaRepairs};
+pub use reprocess_media::ReprocessMediaUseCase;
+pub use resumable_upload::{AppendChunkResult, ResumableUploadUseCase};
+pub use scan_integrity::{IntegrityScan, ScanIntegrityUseCase};
+pub use search_media::SearchMediaUseCase;
+pub use set_media_tags::SetMediaTagsUseCase;
+pub use upload_fingerprints::{UploadFingerprint, UploadFingerprints};
+pub use upload_locks::{ContentGuard, UploadLocks};
 pub use upload_media::UploadMediaUseCase;
+
+/// Most media IDs accepted by one batch request
+pub const MAX_BATCH_IDS: usize = 100;
+
+/// Drop repeated IDs from a batch request, keeping the first of each
+///
+/// # Errors
+/// Returns `BadRequest` if more than [`MAX_BATCH_IDS`] distinct IDs remain
+pub(crate) fn distinct_batch_ids(ids: Vec<MediaId>) -> Result<Vec<MediaId>, AppError> {
+    let mut distinct = Vec::with_capacity(ids.len());
+    for id in ids {
+        if !distinct.contains(&id) {
+            distinct.push(id);
+        }
+    }
+
+    if distinct.len() > MAX_BATCH_IDS {
+        return Err(AppError::BadRequest {
+            message: format!(
+                "At most {MAX_BATCH_IDS} media IDs can be requested at once, got {}",
+                distinct.len()
+            ),
+        });
+    }
+
+    Ok(distinct)
+}
+
+/// Parse a comma-separated tag filter such as `plated,dessert`
+///
+/// # Errors
+/// Returns `BadRequest` if any tag is invalid
+pub(crate) fn tag_filter(tags: Option<&str>) -> Result<Vec<MediaTag>, AppError> {
+    let tags: Vec<&str> =
+        tags.unwrap_or_default().split(',').filter(|tag| !tag.trim().is_empty()).collect();
+    MediaTag::normalize_all(&tags).map_err(|e| AppError::BadRequest { message: e.to_string() })
+}
+
+/// Map a media repository error into an `AppError`
+///
+/// `ServiceUnavailable` passes through unchanged so an unreachable database is
+/// reported as a 503 with its reconnect ETA; anything else becomes an `Internal`
+/// error prefixed with `context`.
+pub(crate) fn repository_error<E>(context: impl std::fmt::Display) -> impl FnOnce(E) -> AppError
+where
+    E: std::error::Error + Send + Sync + 'static,
+{
+    move |error| {
+        let error: Box<dyn std::error::Error + Send + Sync> = Box::new(error);
+        match error.downcast::<AppError>().map(|error| *error) {
+            Ok(error @ AppError::ServiceUnavailable { .. }) => error,
+            Ok(error) => AppError::Internal { message: format!("{context}: {error}") },
+            Err(error) => AppError::Internal { message: format!("{context}: {error}") },
+        }
+    }
+}
+
+/// Reject requests for media by anyone but its uploader
+///
+/// `requester` is `None` for unauthenticated requests, which are rejected too.
+pub(crate) fn ensure_owner(media: &Media, requester: Option<UserId>) -> Result<(), AppError> {
+    match requester {
+        Some(user_id) if user_id == media.uploaded_by => Ok(()),
+        Some(user_id) => {
+            tracing::warn!("User {} denied access to media {}", user_id, media.id);
+            Err(AppError::Authorization {
+                message: format!("Media {} belongs to another user", media.id),
+            })
+        }
+        None => {
+            tracing::warn!("Anonymous request denied access to media {}", media.id);
+            Err(AppError::Authorization {
+                message: format!("Media {} is only available to its owner", media.id),
+            })
+        }
+    }
+}
+
+/// Reject a download of `media` by anyone but its uploader, or before processing has completed
+///
+/// Download routes don't require a token, so a request without one (`requester`
+/// of `None`) is asked to authenticate rather than refused outright.
+pub(crate) fn ensure_downloadable(
+    media: &Media,
+    requester: Option<UserId>,
+) -> Result<(), AppError> {
+    if requester.is_none() {
+        tracing::warn!("Anonymous request denied download of media {}", media.id);
+        return Err(AppError::Authentication {
+            message: format!("Media {} can only be downloaded by its owner", media.id),
+        });
+    }
+    ensure_owner(media, requester)?;
+
+    // Check if media processing is complete
+    if !media.is_ready() {
+        tracing::warn!(
+            "Media not ready for download: {} (status: {:?})",
+            media.id,
+            media.processing_status
+        );
+        return Err(AppError::BadRequest {
+            message: format!(
+                "Media is not ready for download. Status: {:?}",
+                media.processing_status
+            ),
+        });
+    }
+
+    Ok(())
+}
+
+/// Map a failure to read the content of `media_id` to an error response
+pub(crate) fn storage_error(media_id: MediaId) -> impl Fn(StorageError) -> AppError {
+    move |e| match e {
+        StorageError::FileNotFound { .. } => {
+            AppError::NotFound { resource: format!("File content for media {media_id}") }
+        }
+        _ => AppError::Internal { message: format!("Storage error: {e}") },
+    }
+}
+
+/// Name of a variant download: the original's, with the variant's extension
+pub(crate) fn variant_filename(media: &Media, variant: &MediaVariant) -> String {
+    std::path::Path::new(&media.original_filename)
+        .with_extension(variant.media_type.file_extension())
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// Error for a file of `size` bytes over the `max_size` limit
+pub(crate) fn file_too_large(size: u64, max_size: u64) -> AppError {
+    AppError::PayloadTooLarge {
+        message: format!("File size {size} bytes exceeds maximum allowed size of {max_size} bytes"),
+    }
+}
+
+/// Reject an upload of `bytes` that would take the user past their quota
+///
+/// The usage query is skipped entirely when the quota has no limits.
+pub(crate) async fn ensure_within_quota<R>(
+    repository: &R,
+    quota: StorageQuota,
+    user_id: UserId,
+    bytes: u64,
+) -> Result<(), AppError>
+where
+    R: MediaRepository + ?Sized,
+{
+    if quota.is_unlimited() {
+        return Ok(());
+    }
+
+    let usage = repository
+        .usage_by_user(user_id)
+        .await
+        .map_err(repository_error("Failed to read storage usage"))?;
+
+    let message = match quota.exceeded_by(usage, bytes) {
+        None => return Ok(()),
+        Some(QuotaLimit::Bytes) => format!(
+            "Uploading {bytes} bytes would exceed the storage quota of {} bytes ({} bytes used)",
+            quota.max_bytes.unwrap_or_default(),
+            usage.bytes
+        ),
+        Some(QuotaLimit::Files) => format!(
+            "File quota of {} files reached ({} files stored)",
+            quota.max_files.unwrap_or_default(),
+            usage.files
+        ),
+    };
+
+    tracing::info!("User {} over quota: {}", user_id, message);
+    Err(AppError::QuotaExceeded { message, usage, quota })
+}
+
+/// Determine an upload's media type from its magic bytes rather than the client's word
+///
+/// Content whose signature contradicts the declared type is rejected, as is a
+/// declared type (or, when none is declared, a filename extension) naming a format
+/// with a known signature the content lacks. `application/octet-stream` counts as
+/// undeclared. An empty `allowed_types` allows every type.
+pub(crate) fn verify_content_type(
+    data: &[u8],
+    filename: &str,
+    declared_type: Option<&str>,
+    allowed_types: &[String],
+) -> Result<String, AppError> {
+    let declared_type = declared_type
+        .and_then(|declared| declared.split(';').next())
+        .map(|declared| declared.trim().to_ascii_lowercase())
+        .filter(|declared| !declared.is_empty() && declared != "application/octet-stream");
+    let sniffed_type = sniff_content_type(data);
+    let rejected = |message: String, detected_type: Option<&str>| {
+        tracing::info!("Rejected upload {}: {}", filename, message);
+        AppError::UnprocessableContent {
+            message,
+            declared_type: declared_type.clone(),
+            detected_type: detected_type.map(String::from),
+            allowed_types: allowed_types.to_vec(),
+        }
+    };
+
+    let content_type = match (sniffed_type, &declared_type) {
+        (Some(sniffed), Some(declared)) if sniffed != declared => {
+            return Err(rejected(
+                format!("Content is {sniffed} but was declared as {declared}"),
+                Some(sniffed),
+            ));
+        }
+        (Some(sniffed), _) => sniffed.to_string(),
+        (None, declared) => {
+            let claimed =
+                declared.clone().unwrap_or_else(|| detect_content_type(&[], Some(filename)));
+            if has_known_signature(&claimed) {
+                return Err(rejected(format!("Content is not valid {claimed}"), None));
+            }
+            claimed
+        }
+    };
+
+    if !allowed_types.is_empty() && !allowed_types.contains(&content_type) {
+        return Err(rejected(
+            format!("Content type {content_type} is not allowed"),
+            Some(&content_type),
+        ));
+    }
+
+    Ok(content_type)
+}
+
+/// Convert an upload into a format `tenant` accepts, if it doesn't already
+///
+/// Content of a type the tenant's [`FormatPolicy`] doesn't accept is converted into
+/// the first accepted format it can be converted to, and rejected if there is none.
+/// Uploads without a tenant, and tenants without an entry, are left as they are.
+/// Returns the content hash, data and type of what should be stored.
+pub(crate) async fn apply_format_policy(
+    policy: &FormatPolicy,
+    tenant: Option<&str>,
+    max_file_size: u64,
+    content_hash: ContentHash,
+    file_data: Vec<u8>,
+    content_type: String,
+    declared_type: Option<String>,
+) -> Result<(ContentHash, Vec<u8>, String), AppError> {
+    let Some((tenant, allowed)) =
+        tenant.and_then(|tenant| Some((tenant, policy.allowed_formats(tenant)?)))
+    else {
+        return Ok((content_hash, file_data, content_type));
+    };
+    if allowed.contains(&content_type) {
+        return Ok((content_hash, file_data, content_type));
+    }
+
+    let rejected = |message: String| {
+        tracing::info!("Rejected upload for client {}: {}", tenant, message);
+        AppError::UnprocessableContent {
+            message,
+            declared_type: declared_type.clone(),
+            detected_type: Some(content_type.clone()),
+            allowed_types: allowed.to_vec(),
+        }
+    };
+
+    let Some(target) =
+        allowed.iter().find(|target| ImageVariantEncoder::can_convert(&content_type, target))
+    else {
+        return Err(rejected(format!(
+            "Content type {content_type} is not accepted for this client and cannot be converted to {}",
+            allowed.join(", ")
+        )));
+    };
+
+    let (source, output) = (content_type.clone(), target.clone());
+    let converted = tokio::task::spawn_blocking(move || {
+        ImageVariantEncoder::new().convert(&file_data, &source, &output)
+    })
+    .await
+    .map_err(|e| AppError::Internal { message: format!("Conversion task failed: {e}") })?
+    .map_err(|e| rejected(format!("Failed to convert {content_type} to {target}: {e}")))?;
+
+    if converted.len() as u64 > max_file_size {
+        return Err(file_too_large(converted.len() as u64, max_file_size));
+    }
+
+    tracing::info!(
+        "Converted upload for client {} from {} to {} ({} bytes)",
+        tenant,
+        content_type,
+        target,
+        converted.len()
+    );
+    metrics::counter!(
+        "media_format_conversions_total",
+        "from" => content_type.clone(),
+        "to" => target.clone()
+    )
+    .increment(1);
+
+    let (content_hash, file_data) =
+        generate_content_hash_async(std::io::Cursor::new(converted)).await.map_err(|e| {
+            AppError::Internal { message: format!("Failed to hash converted file: {e}") }
+        })?;
+    Ok((content_hash, file_data, target.clone()))
+}
+
+/// Drop a reference to a variant blob, deleting the blob once nothing uses it
+///
+/// Blobs are shared by content hash, so one is kept while the registry still
+/// counts references or an original upload has the same hash. Failures are only
+/// logged: a leaked blob wastes space, whereas failing would leave the caller's
+/// media half-updated.
+pub(crate) async fn release_variant<R, V, S>(
+    repository: &R,
+    variants: &V,
+    storage: &S,
+    variant: &MediaVariant,
+) where
+    R: MediaRepository + ?Sized,
+    V: VariantRepository + ?Sized,
+    S: FileStorage + ?Sized,
+{
+    let hash = &variant.content_hash;
+    match variants.release(hash).await {
+        Ok(Some(0)) => {}
+        Ok(Some(_)) => return,
+        Ok(None) => {
+            tracing::debug!("Variant {} is not registered, leaving it in storage", hash);
+            return;
+        }
+        Err(e) => {
+            tracing::warn!("Failed to release {} variant {}: {}", variant.name, hash, e);
+            return;
+        }
+    }
+
+    match repository.exists_by_content_hash(hash).await {
+        Ok(false) => {}
+        Ok(true) => return,
+        Err(e) => {
+            tracing::warn!("Failed to check whether variant {} is an original: {}", hash, e);
+            return;
+        }
+    }
+
+    match storage.delete(hash).await {
+        Ok(_) => tracing::info!("Deleted unreferenced {} variant {}", variant.name, hash),
+        Err(e) => tracing::warn!("Failed to delete {} variant {}: {}", variant.name, hash, e),
+    }
+}
+
+/// Delete the blob an upload stored before its media record failed to save
+///
+/// Only call this for content the upload wrote itself. The blob is still kept
+/// unless `upload_guard` locked the content on every instance and no media row
+/// has the hash, since another upload may have started using it in the meantime.
+pub(crate) async fn discard_unsaved_content<R, S>(
+    repository: &R,
+    storage: &S,
+    hash: &ContentHash,
+    upload_guard: &ContentGuard,
+) where
+    R: MediaRepository + ?Sized,
+    S: FileStorage + ?Sized,
+{
+    if !upload_guard.is_exclusive() {
+        tracing::warn!(
+            "Content {} could not be locked on every instance, leaving it in storage",
+            hash
+        );
+        return;
+    }
+
+    match repository.exists_by_content_hash(hash).await {
+        Ok(false) => {}
+        Ok(true) => {
+            tracing::info!("Content {} is used by other media, leaving it in storage", hash);
+            return;
+        }
+        Err(e) => {
+            tracing::warn!("Failed to check whether content {} is referenced: {}", hash, e);
+            return;
+        }
+    }
+
+    match storage.delete(hash).await {
+        Ok(_) => tracing::info!("Deleted content {} of an unsaved upload", hash),
+        Err(e) => tracing::warn!("Failed to delete content {} of an unsaved upload: {}", hash, e),
+    }
+}
+
+/// Store a generated variant by its own content hash and register the reference
+///
+/// A blob already referenced by other media is not written again. The caller
+/// owns the reference and must release it if the variant is not recorded.
+pub(crate) async fn store_variant<R, V, S>(
+    repository: &R,
+    variants: &V,
+    storage: &S,
+    name: &str,
+    content_type: &str,
+    data: Vec<u8>,
+) -> Result<MediaVariant, ProcessingError>
+where
+    R: MediaRepository + ?Sized,
+    V: VariantRepository + ?Sized,
+    S: FileStorage + ?Sized,
+{
+    let file_size = data.len() as u64;
+    let (content_hash, data) = generate_content_hash_async(std::io::Cursor::new(data))
+        .await
+        .map_err(|e| ProcessingError::Io { message: format!("Failed to hash variant: {e}") })?;
+    let media_variant = MediaVariant {
+        name: name.to_string(),
+        content_hash,
+        media_type: MediaType::new(content_type),
+        file_size,
+    };
+
+    let references = variants.acquire(&media_variant).await.map_err(|e| {
+        ProcessingError::Storage { message: format!("Failed to register {name} variant: {e}") }
+    })?;
+    if references > 1 && storage.exists(&media_variant.content_hash).await.unwrap_or(false) {
+        tracing::debug!(
+            "Reusing stored {} variant {} ({} references)",
+            name,
+            media_variant.content_hash,
+            references
+        );
+        return Ok(media_variant);
+    }
+
+    let mut cursor = std::io::Cursor::new(data);
+    if let Err(e) = storage.store(&media_variant.content_hash, &mut cursor).await {
+        release_variant(repository, variants, storage, &media_variant).await;
+        return Err(ProcessingError::Storage {
+            message: format!("Failed to store {name} variant: {e}"),
+        });
+    }
+
+    Ok(media_variant)
+}
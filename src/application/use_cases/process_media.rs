@@ -0,0 +1,819 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+use tokio::io::AsyncReadExt;
+
+use super::{release_variant, repository_error, store_variant, ProcessingProgress};
+use crate::{
+    application::events::{DomainEvent, EventBus, EventPublisher},
+    domain::{
+        entities::{Media, MediaId, MediaVariant},
+        repositories::{MediaChange, MediaRepository, VariantRepository},
+        value_objects::{ProcessingSla, ProcessingStatus},
+    },
+    infrastructure::{
+        business_metrics::{self, BusinessMetrics},
+        processing::{
+            EncodedVariant, ImagePipelineRollout, ImageVariantEncoder, MalwareScan,
+            ProcessingError, ScanVerdict, VideoProcessor,
+        },
+        storage::FileStorage,
+    },
+    presentation::middleware::error::AppError,
+};
+
+/// Use case for post-upload processing of media files
+///
+/// Images get WebP and AVIF variants; videos are transcoded to a web MP4 plus a
+/// poster frame when a [`VideoProcessor`] is configured. Variants are stored by
+/// their own content hash, so identical encodings of different originals share
+/// one blob, counted in the variant registry. Media moves from
+/// `Pending` through `Processing` to `Complete`, or to `Failed` with the error
+/// recorded if processing fails.
+///
+/// With a [`MalwareScan`] configured the original is scanned first; infected
+/// files are moved from storage into quarantine and fail with the signature found.
+///
+/// Image variants come from the stable encoder unless an [`ImagePipelineRollout`]
+/// sends a share of images to a candidate encoder.
+///
+/// Each stage reached is reported to a [`ProcessingProgress`] for live progress.
+pub struct ProcessMediaUseCase<R, S, V>
+where
+    R: MediaRepository + ?Sized,
+    S: FileStorage + ?Sized,
+    V: VariantRepository + ?Sized,
+{
+    repository: Arc<R>,
+    storage: Arc<S>,
+    variants: Arc<V>,
+    image_pipeline: ImagePipelineRollout,
+    video_processor: Option<VideoProcessor>,
+    malware_scan: Option<MalwareScan>,
+    metrics: BusinessMetrics,
+    sla: ProcessingSla,
+    events: Arc<dyn EventPublisher>,
+    progress: ProcessingProgress,
+}
+
+impl<R, S, V> ProcessMediaUseCase<R, S, V>
+where
+    R: MediaRepository + ?Sized,
+    S: FileStorage + ?Sized,
+    V: VariantRepository + ?Sized,
+{
+    /// Create a new process media use case
+    ///
+    /// Videos complete without variants unless [`Self::with_video_processor`] is used.
+    pub fn new(repository: Arc<R>, storage: Arc<S>, variants: Arc<V>) -> Self {
+        Self {
+            repository,
+            storage,
+            variants,
+            image_pipeline: ImagePipelineRollout::default(),
+            video_processor: None,
+            malware_scan: None,
+            metrics: BusinessMetrics::disabled(),
+            sla: ProcessingSla::disabled(),
+            events: Arc::new(EventBus::new()),
+            progress: ProcessingProgress::new(),
+        }
+    }
+
+    /// Transcode uploaded videos with the given processor
+    #[must_use]
+    pub fn with_video_processor(mut self, video_processor: Option<VideoProcessor>) -> Self {
+        self.video_processor = video_processor;
+        self
+    }
+
+    /// Split image encoding between the stable and a candidate pipeline
+    #[must_use]
+    pub fn with_image_pipeline(mut self, image_pipeline: ImagePipelineRollout) -> Self {
+        self.image_pipeline = image_pipeline;
+        self
+    }
+
+    /// Scan originals for malware before processing them
+    #[must_use]
+    pub fn with_malware_scan(mut self, malware_scan: Option<MalwareScan>) -> Self {
+        self.malware_scan = malware_scan;
+        self
+    }
+
+    /// Record how long processing takes
+    #[must_use]
+    pub fn with_metrics(mut self, metrics: BusinessMetrics) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    /// Warn about media that took longer than `sla` from upload to processed
+    #[must_use]
+    pub fn with_sla(mut self, sla: ProcessingSla) -> Self {
+        self.sla = sla;
+        self
+    }
+
+    /// Announce how processing ended to `events`
+    #[must_use]
+    pub fn with_events(mut self, events: Arc<dyn EventPublisher>) -> Self {
+        self.events = events;
+        self
+    }
+
+    /// Report each stage of processing to `progress`
+    #[must_use]
+    pub fn with_progress(mut self, progress: ProcessingProgress) -> Self {
+        self.progress = progress;
+        self
+    }
+
+    /// Process a newly uploaded media file
+    ///
+    /// Media that is not `Pending` has already been processed (e.g. a deduplicated
+    /// upload) and is left untouched. Media reset to `Pending` is processed again,
+    /// replacing its variants.
+    pub async fn execute(&self, media_id: MediaId) -> Result<Media, AppError> {
+        self.process(media_id, false).await
+    }
+
+    /// Process media that was reset to `Pending`, e.g. by a variant backfill
+    ///
+    /// Works like [`Self::execute`], but the outcome is published as reprocessed
+    /// so subscribers can tell it from a new upload.
+    pub async fn reprocess(&self, media_id: MediaId) -> Result<Media, AppError> {
+        self.process(media_id, true).await
+    }
+
+    async fn process(&self, media_id: MediaId, reprocessed: bool) -> Result<Media, AppError> {
+        let mut media = self
+            .repository
+            .find_by_id(media_id)
+            .await
+            .map_err(repository_error("Failed to query media"))?
+            .ok_or_else(|| AppError::NotFound { resource: format!("Media with ID {media_id}") })?;
+
+        if media.processing_status != ProcessingStatus::Pending {
+            return Ok(media);
+        }
+
+        // Pending media was last updated when its upload completed
+        let uploaded_at = media.updated_at;
+        media.set_processing_status(ProcessingStatus::Processing);
+        self.save(&media).await?;
+        self.progress.advance(media_id, 5);
+
+        let started = Instant::now();
+        let processed = match self.scan_for_malware(&media).await {
+            Ok(()) => {
+                self.progress.advance(media_id, 20);
+                self.generate_variants(&media).await
+            }
+            Err(e) => Err(e),
+        };
+        self.metrics.processing_finished(&media.media_type, processed.is_ok(), started.elapsed());
+
+        let mut replaced = Vec::new();
+        match processed {
+            Ok(variants) => {
+                tracing::info!("Generated {} variants for media {}", variants.len(), media_id);
+                replaced = std::mem::replace(&mut media.variants, variants);
+                media.set_processing_status(ProcessingStatus::Complete);
+            }
+            Err(e) => {
+                tracing::error!("Processing failed for media {}: {}", media_id, e);
+                media.mark_failed(e.to_string());
+            }
+        }
+
+        let finished = if media.has_failed() {
+            DomainEvent::ProcessingFailed {
+                media_id,
+                uploaded_by: media.uploaded_by,
+                media_type: media.media_type.clone(),
+                error: media.processing_error.clone().unwrap_or_default(),
+                reprocessed,
+            }
+        } else {
+            DomainEvent::ProcessingCompleted {
+                media_id,
+                uploaded_by: media.uploaded_by,
+                media_type: media.media_type.clone(),
+                reprocessed,
+            }
+        };
+        let recorded = match self
+            .repository
+            .apply(MediaChange::Update(&media), std::slice::from_ref(&finished))
+            .await
+        {
+            Ok(recorded) => recorded,
+            Err(e) => {
+                self.progress.forget(media_id);
+                return Err(repository_error(format!("Failed to update media {media_id}"))(e));
+            }
+        };
+        self.progress.finish(&media);
+        self.record_latency(&media, uploaded_at);
+        if !recorded.events_recorded {
+            self.events.publish(&finished);
+        }
+
+        // Reprocessed media, e.g. from a backfill, drops its references to the old variants
+        for variant in &replaced {
+            self.release(variant).await;
+        }
+        Ok(media)
+    }
+
+    /// Record the time from upload to processed, warning if it breached the SLA
+    fn record_latency(&self, media: &Media, uploaded_at: SystemTime) {
+        let latency = uploaded_at.elapsed().unwrap_or(Duration::ZERO);
+        let media_kind = business_metrics::media_kind(&media.media_type);
+        metrics::histogram!(
+            "media_processing_latency_seconds",
+            "media_kind" => media_kind,
+            "outcome" => business_metrics::outcome(!media.has_failed())
+        )
+        .record(latency.as_secs_f64());
+
+        if self.sla.is_breached_by(latency) {
+            tracing::warn!(
+                "Media {} finished processing as {} {}s after upload, over the processing SLA",
+                media.id,
+                media.processing_status,
+                latency.as_secs()
+            );
+            metrics::counter!(
+                "media_processing_sla_breaches_total",
+                "media_kind" => media_kind,
+                "stage" => "finished"
+            )
+            .increment(1);
+        }
+    }
+
+    /// Scan the original, quarantining it if it is infected
+    ///
+    /// A scan that fails is reported as an error rather than letting unscanned
+    /// content through.
+    async fn scan_for_malware(&self, media: &Media) -> Result<(), ProcessingError> {
+        let Some(malware_scan) = &self.malware_scan else {
+            return Ok(());
+        };
+
+        let verdict = match self.storage.retrieve(&media.content_hash).await {
+            Ok(mut reader) => malware_scan.scanner().scan(&mut reader).await,
+            Err(e) => {
+                Err(ProcessingError::Storage { message: format!("Failed to read original: {e}") })
+            }
+        };
+        let result = match &verdict {
+            Ok(ScanVerdict::Clean) => "clean",
+            Ok(ScanVerdict::Infected { .. }) => "infected",
+            Err(_) => "error",
+        };
+        metrics::counter!("media_scans_total", "result" => result).increment(1);
+
+        let ScanVerdict::Infected { signature } = verdict? else {
+            return Ok(());
+        };
+
+        tracing::warn!("Media {} is infected with {}, quarantining", media.id, signature);
+        self.quarantine(malware_scan, media).await;
+        Err(ProcessingError::MalwareDetected { signature })
+    }
+
+    /// Move infected content out of storage so it can no longer be served
+    async fn quarantine(&self, malware_scan: &MalwareScan, media: &Media) {
+        match self.storage.retrieve(&media.content_hash).await {
+            Ok(mut reader) => {
+                match malware_scan.quarantine(&media.content_hash, &mut reader).await {
+                    Ok(path) => tracing::info!("Quarantined media {} at {:?}", media.id, path),
+                    Err(e) => tracing::error!("Failed to quarantine media {}: {}", media.id, e),
+                }
+            }
+            Err(e) => tracing::error!("Failed to read media {} for quarantine: {}", media.id, e),
+        }
+
+        // Remove it even if the copy failed; infected content must not stay downloadable
+        if let Err(e) = self.storage.delete(&media.content_hash).await {
+            tracing::error!("Failed to remove infected media {} from storage: {}", media.id, e);
+        }
+    }
+
+    /// Encode and store the variants for an image or video, or none for other media
+    async fn generate_variants(&self, media: &Media) -> Result<Vec<MediaVariant>, ProcessingError> {
+        let content_type = media.media_type.mime_type().to_string();
+
+        let encoded = if ImageVariantEncoder::supports(&content_type) {
+            let original = self.read_original(media).await?;
+            let image_pipeline = self.image_pipeline;
+            let media_id = media.id;
+            let (version, variants) = tokio::task::spawn_blocking(move || {
+                image_pipeline.encode(media_id, &original, &content_type)
+            })
+            .await
+            .map_err(|e| ProcessingError::Io { message: format!("Encoder task failed: {e}") })??;
+            tracing::debug!(
+                "Encoded media {} with the {} image pipeline",
+                media_id,
+                version.as_str()
+            );
+            variants
+        } else if let Some(video_processor) =
+            self.video_processor.as_ref().filter(|_| VideoProcessor::supports(&content_type))
+        {
+            let mut reader = self.storage.retrieve(&media.content_hash).await.map_err(|e| {
+                ProcessingError::Storage { message: format!("Failed to read original: {e}") }
+            })?;
+            video_processor.process(&mut reader).await?
+        } else {
+            return Ok(Vec::new());
+        };
+
+        // Encoding is done; storing the variants takes the rest up to 95%
+        self.progress.advance(media.id, 60);
+        let total = encoded.len();
+        let mut variants = Vec::with_capacity(total);
+        for variant in encoded {
+            match self.store_variant(variant).await {
+                Ok(variant) => {
+                    variants.push(variant);
+                    let stored = u8::try_from(variants.len() * 35 / total).unwrap_or(35);
+                    self.progress.advance(media.id, 60 + stored);
+                }
+                Err(e) => {
+                    // Don't leave references behind for variants the media won't record
+                    for stored in &variants {
+                        self.release(stored).await;
+                    }
+                    return Err(e);
+                }
+            }
+        }
+
+        Ok(variants)
+    }
+
+    async fn read_original(&self, media: &Media) -> Result<Vec<u8>, ProcessingError> {
+        let read_failed = |e: &dyn std::fmt::Display| ProcessingError::Storage {
+            message: format!("Failed to read original: {e}"),
+        };
+
+        let mut reader =
+            self.storage.retrieve(&media.content_hash).await.map_err(|e| read_failed(&e))?;
+        let mut original = Vec::new();
+        reader.read_to_end(&mut original).await.map_err(|e| read_failed(&e))?;
+        Ok(original)
+    }
+
+    async fn store_variant(
+        &self,
+        variant: EncodedVariant,
+    ) -> Result<MediaVariant, ProcessingError> {
+        store_variant(
+            &*self.repository,
+            &*self.variants,
+            &*self.storage,
+            variant.name,
+            variant.content_type,
+            variant.data,
+        )
+        .await
+    }
+
+    async fn release(&self, variant: &MediaVariant) {
+        release_variant(&*self.repository, &*self.variants, &*self.storage, variant).await;
+    }
+
+    async fn save(&self, media: &Media) -> Result<(), AppError> {
+        self.repository
+            .update(media)
+            .await
+            .map_err(repository_error(format!("Failed to update media {}", media.id)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{ImageBuffer, Rgb};
+    use tempfile::TempDir;
+
+    use crate::{
+        application::use_cases::UploadMediaUseCase,
+        domain::value_objects::MediaType,
+        infrastructure::{persistence::InMemoryVariantRepository, storage::FilesystemStorage},
+        test_utils::{
+            images::create_test_png,
+            mocks::{InMemoryMediaRepository, RecordedEvents},
+        },
+    };
+
+    /// The same pixels as [`create_test_png`], encoded to different bytes
+    fn create_recompressed_test_png() -> Vec<u8> {
+        use image::codecs::png::{CompressionType, FilterType, PngEncoder};
+
+        let image = ImageBuffer::from_fn(8, 8, |x, y| Rgb([(x * 32) as u8, (y * 32) as u8, 0]));
+        let mut data = Vec::new();
+        image
+            .write_with_encoder(PngEncoder::new_with_quality(
+                &mut data,
+                CompressionType::Best,
+                FilterType::Paeth,
+            ))
+            .unwrap();
+        data
+    }
+
+    /// Minimal MP4 header, enough for content sniffing to detect `video/mp4`
+    fn create_test_mp4() -> Vec<u8> {
+        let mut data = vec![0, 0, 0, 0x18];
+        data.extend_from_slice(b"ftypmp42");
+        data.extend_from_slice(&[0, 0, 0, 0]);
+        data.extend_from_slice(b"mp42isom");
+        data.extend_from_slice(&[0; 16]);
+        data
+    }
+
+    async fn upload(
+        repository: &Arc<InMemoryMediaRepository>,
+        storage: &Arc<FilesystemStorage>,
+        data: Vec<u8>,
+        filename: &str,
+    ) -> MediaId {
+        UploadMediaUseCase::new(repository.clone(), storage.clone(), 1024 * 1024)
+            .execute_with_default_user(std::io::Cursor::new(data), filename.to_string(), None)
+            .await
+            .unwrap()
+            .media_id
+    }
+
+    #[tokio::test]
+    async fn test_image_gets_webp_avif_and_thumbnail_variants() {
+        let temp_dir = TempDir::new().unwrap();
+        let repository = Arc::new(InMemoryMediaRepository::new());
+        let storage = Arc::new(FilesystemStorage::new(temp_dir.path()));
+        let media_id = upload(&repository, &storage, create_test_png(), "photo.png").await;
+
+        let media = ProcessMediaUseCase::new(
+            repository.clone(),
+            storage.clone(),
+            Arc::new(InMemoryVariantRepository::new()),
+        )
+        .execute(media_id)
+        .await
+        .unwrap();
+
+        assert_eq!(media.processing_status, ProcessingStatus::Complete);
+        let names: Vec<&str> = media.variants.iter().map(|v| v.name.as_str()).collect();
+        assert_eq!(names, vec!["webp", "avif", "thumbnail"]);
+        for variant in &media.variants {
+            assert!(storage.exists(&variant.content_hash).await.unwrap());
+        }
+
+        let stored = repository.find_by_id(media_id).await.unwrap().unwrap();
+        assert_eq!(stored.variants, media.variants);
+    }
+
+    #[tokio::test]
+    async fn test_progress_is_reported_until_complete() {
+        let temp_dir = TempDir::new().unwrap();
+        let repository = Arc::new(InMemoryMediaRepository::new());
+        let storage = Arc::new(FilesystemStorage::new(temp_dir.path()));
+        let media_id = upload(&repository, &storage, create_test_png(), "photo.png").await;
+        let progress = ProcessingProgress::new();
+        let mut receiver =
+            progress.subscribe(crate::application::dto::ProcessingProgressEvent::new(
+                media_id,
+                ProcessingStatus::Pending,
+                None,
+            ));
+        let seen = tokio::spawn(async move {
+            let mut seen = Vec::new();
+            while receiver.changed().await.is_ok() {
+                seen.push(receiver.borrow_and_update().progress);
+            }
+            seen
+        });
+
+        ProcessMediaUseCase::new(repository, storage, Arc::new(InMemoryVariantRepository::new()))
+            .with_progress(progress.clone())
+            .execute(media_id)
+            .await
+            .unwrap();
+
+        // Updates may be coalesced, but they only ever move forward
+        let seen = seen.await.unwrap();
+        assert_eq!(seen.last(), Some(&100));
+        assert!(seen.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(progress.current(media_id), None);
+    }
+
+    #[tokio::test]
+    async fn test_identical_variants_share_one_blob() {
+        let temp_dir = TempDir::new().unwrap();
+        let repository = Arc::new(InMemoryMediaRepository::new());
+        let storage = Arc::new(FilesystemStorage::new(temp_dir.path()));
+        let variants = Arc::new(InMemoryVariantRepository::new());
+        let original = create_test_png();
+        let recompressed = create_recompressed_test_png();
+        assert_ne!(original, recompressed);
+        let first_id = upload(&repository, &storage, original, "photo.png").await;
+        let second_id = upload(&repository, &storage, recompressed, "copy.png").await;
+        let use_case = ProcessMediaUseCase::new(repository, storage.clone(), variants.clone());
+
+        let first = use_case.execute(first_id).await.unwrap();
+        let second = use_case.execute(second_id).await.unwrap();
+
+        assert_ne!(first.content_hash, second.content_hash);
+        assert_eq!(first.variants, second.variants);
+        for variant in &first.variants {
+            assert_eq!(variants.reference_count(&variant.content_hash).await.unwrap(), 2);
+            assert!(storage.exists(&variant.content_hash).await.unwrap());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_non_image_completes_without_variants() {
+        let temp_dir = TempDir::new().unwrap();
+        let repository = Arc::new(InMemoryMediaRepository::new());
+        let storage = Arc::new(FilesystemStorage::new(temp_dir.path()));
+        let media_id = upload(&repository, &storage, b"plain text".to_vec(), "notes.txt").await;
+
+        let media = ProcessMediaUseCase::new(
+            repository,
+            storage,
+            Arc::new(InMemoryVariantRepository::new()),
+        )
+        .execute(media_id)
+        .await
+        .unwrap();
+
+        assert_eq!(media.processing_status, ProcessingStatus::Complete);
+        assert!(media.variants.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_late_processing_counts_as_sla_breach() {
+        let temp_dir = TempDir::new().unwrap();
+        let repository = Arc::new(InMemoryMediaRepository::new());
+        let storage = Arc::new(FilesystemStorage::new(temp_dir.path()));
+        let media_id = upload(&repository, &storage, b"plain text".to_vec(), "notes.txt").await;
+        let mut media = repository.find_by_id(media_id).await.unwrap().unwrap();
+        media.updated_at = SystemTime::now() - Duration::from_mins(10);
+        repository.update(&media).await.unwrap();
+        let recorder = metrics_exporter_prometheus::PrometheusBuilder::new().build_recorder();
+        let handle = recorder.handle();
+        let _guard = metrics::set_default_local_recorder(&recorder);
+
+        ProcessMediaUseCase::new(repository, storage, Arc::new(InMemoryVariantRepository::new()))
+            .with_sla(ProcessingSla::new(Duration::from_mins(5)))
+            .execute(media_id)
+            .await
+            .unwrap();
+
+        let rendered = handle.render();
+        assert!(rendered.contains(
+            r#"media_processing_sla_breaches_total{media_kind="other",stage="finished"} 1"#
+        ));
+        assert!(rendered.contains("media_processing_latency_seconds_count"));
+    }
+
+    #[tokio::test]
+    async fn test_undecodable_image_fails() {
+        let temp_dir = TempDir::new().unwrap();
+        let repository = Arc::new(InMemoryMediaRepository::new());
+        let storage = Arc::new(FilesystemStorage::new(temp_dir.path()));
+        let mut corrupt = create_test_png();
+        corrupt.truncate(20);
+        let media_id = upload(&repository, &storage, corrupt, "broken.png").await;
+
+        let media = ProcessMediaUseCase::new(
+            repository,
+            storage,
+            Arc::new(InMemoryVariantRepository::new()),
+        )
+        .execute(media_id)
+        .await
+        .unwrap();
+
+        assert_eq!(media.processing_status, ProcessingStatus::Failed);
+        assert!(media.processing_error.unwrap().starts_with("Failed to decode media"));
+    }
+
+    #[tokio::test]
+    async fn test_video_without_processor_completes_without_variants() {
+        let temp_dir = TempDir::new().unwrap();
+        let repository = Arc::new(InMemoryMediaRepository::new());
+        let storage = Arc::new(FilesystemStorage::new(temp_dir.path()));
+        let media_id = upload(&repository, &storage, create_test_mp4(), "clip.mp4").await;
+
+        let media = ProcessMediaUseCase::new(
+            repository,
+            storage,
+            Arc::new(InMemoryVariantRepository::new()),
+        )
+        .execute(media_id)
+        .await
+        .unwrap();
+
+        assert_eq!(media.processing_status, ProcessingStatus::Complete);
+        assert!(media.variants.is_empty());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_video_transcoding_failure_records_error() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        let fake_ffmpeg = temp_dir.path().join("ffmpeg");
+        std::fs::write(&fake_ffmpeg, "#!/bin/sh\necho 'moov atom not found' >&2\nexit 1\n")
+            .unwrap();
+        std::fs::set_permissions(&fake_ffmpeg, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let repository = Arc::new(InMemoryMediaRepository::new());
+        let storage = Arc::new(FilesystemStorage::new(temp_dir.path().join("media")));
+        let media_id = upload(&repository, &storage, create_test_mp4(), "clip.mp4").await;
+        let processor = VideoProcessor::new(
+            fake_ffmpeg,
+            temp_dir.path().join("work"),
+            std::time::Duration::from_secs(5),
+        );
+
+        let media = ProcessMediaUseCase::new(
+            repository.clone(),
+            storage,
+            Arc::new(InMemoryVariantRepository::new()),
+        )
+        .with_video_processor(Some(processor))
+        .execute(media_id)
+        .await
+        .unwrap();
+
+        assert_eq!(media.processing_status, ProcessingStatus::Failed);
+        let stored = repository.find_by_id(media_id).await.unwrap().unwrap();
+        assert!(stored.processing_error.unwrap().contains("moov atom not found"));
+    }
+
+    #[tokio::test]
+    async fn test_processed_media_is_not_reprocessed() {
+        let temp_dir = TempDir::new().unwrap();
+        let repository = Arc::new(InMemoryMediaRepository::new());
+        let storage = Arc::new(FilesystemStorage::new(temp_dir.path()));
+        let media_id = upload(&repository, &storage, create_test_png(), "photo.png").await;
+        let use_case = ProcessMediaUseCase::new(
+            repository,
+            storage,
+            Arc::new(InMemoryVariantRepository::new()),
+        );
+
+        let first = use_case.execute(media_id).await.unwrap();
+        let second = use_case.execute(media_id).await.unwrap();
+
+        assert_eq!(second.updated_at, first.updated_at);
+    }
+
+    #[tokio::test]
+    async fn test_reprocessing_replaces_variant_references() {
+        let temp_dir = TempDir::new().unwrap();
+        let repository = Arc::new(InMemoryMediaRepository::new());
+        let storage = Arc::new(FilesystemStorage::new(temp_dir.path()));
+        let variants = Arc::new(InMemoryVariantRepository::new());
+        let media_id = upload(&repository, &storage, create_test_png(), "photo.png").await;
+        let use_case = ProcessMediaUseCase::new(repository.clone(), storage, variants.clone());
+        let mut media = use_case.execute(media_id).await.unwrap();
+
+        media.set_processing_status(ProcessingStatus::Pending);
+        repository.update(&media).await.unwrap();
+        let reprocessed = use_case.execute(media_id).await.unwrap();
+
+        assert_eq!(reprocessed.processing_status, ProcessingStatus::Complete);
+        assert_eq!(reprocessed.variants, media.variants);
+        for variant in &reprocessed.variants {
+            assert_eq!(variants.reference_count(&variant.content_hash).await.unwrap(), 1);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_outcome_is_published_marked_when_reprocessed() {
+        let temp_dir = TempDir::new().unwrap();
+        let repository = Arc::new(InMemoryMediaRepository::new());
+        let storage = Arc::new(FilesystemStorage::new(temp_dir.path()));
+        let media_id = upload(&repository, &storage, create_test_png(), "photo.png").await;
+        let events = RecordedEvents::new();
+        let use_case = ProcessMediaUseCase::new(
+            repository.clone(),
+            storage,
+            Arc::new(InMemoryVariantRepository::new()),
+        )
+        .with_events(Arc::new(events.clone()));
+
+        let mut media = use_case.execute(media_id).await.unwrap();
+        media.set_processing_status(ProcessingStatus::Pending);
+        repository.update(&media).await.unwrap();
+        use_case.reprocess(media_id).await.unwrap();
+        // Already processed, so nothing happens and nothing is published
+        use_case.execute(media_id).await.unwrap();
+
+        let completed = |reprocessed| DomainEvent::ProcessingCompleted {
+            media_id,
+            uploaded_by: media.uploaded_by,
+            media_type: MediaType::new("image/png"),
+            reprocessed,
+        };
+        assert_eq!(events.events(), [completed(false), completed(true)]);
+    }
+
+    /// Scanner returning a fixed verdict, or failing when there is none
+    struct StubScanner(Option<ScanVerdict>);
+
+    #[async_trait::async_trait]
+    impl crate::infrastructure::processing::Scanner for StubScanner {
+        async fn scan(
+            &self,
+            _source: &mut (dyn tokio::io::AsyncRead + Send + Unpin),
+        ) -> Result<ScanVerdict, ProcessingError> {
+            self.0.clone().ok_or(ProcessingError::ScanFailed { message: "clamd down".to_string() })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_infected_upload_is_quarantined_and_fails() {
+        let temp_dir = TempDir::new().unwrap();
+        let repository = Arc::new(InMemoryMediaRepository::new());
+        let storage = Arc::new(FilesystemStorage::new(temp_dir.path().join("media")));
+        let media_id = upload(&repository, &storage, create_test_png(), "photo.png").await;
+        let quarantine_dir = temp_dir.path().join("quarantine");
+        let scanner = StubScanner(Some(ScanVerdict::Infected { signature: "Eicar".to_string() }));
+
+        let media = ProcessMediaUseCase::new(
+            repository.clone(),
+            storage.clone(),
+            Arc::new(InMemoryVariantRepository::new()),
+        )
+        .with_malware_scan(Some(MalwareScan::new(Arc::new(scanner), &quarantine_dir)))
+        .execute(media_id)
+        .await
+        .unwrap();
+
+        assert_eq!(media.processing_status, ProcessingStatus::Failed);
+        assert!(media.variants.is_empty());
+        let stored = repository.find_by_id(media_id).await.unwrap().unwrap();
+        assert_eq!(stored.processing_error.as_deref(), Some("Malware detected: Eicar"));
+        assert!(!storage.exists(&media.content_hash).await.unwrap());
+        let quarantined = quarantine_dir.join(media.content_hash.as_str());
+        assert_eq!(tokio::fs::read(quarantined).await.unwrap(), create_test_png());
+    }
+
+    #[tokio::test]
+    async fn test_clean_upload_is_processed() {
+        let temp_dir = TempDir::new().unwrap();
+        let repository = Arc::new(InMemoryMediaRepository::new());
+        let storage = Arc::new(FilesystemStorage::new(temp_dir.path()));
+        let media_id = upload(&repository, &storage, create_test_png(), "photo.png").await;
+
+        let media = ProcessMediaUseCase::new(
+            repository,
+            storage,
+            Arc::new(InMemoryVariantRepository::new()),
+        )
+        .with_malware_scan(Some(MalwareScan::new(
+            Arc::new(StubScanner(Some(ScanVerdict::Clean))),
+            temp_dir.path().join("quarantine"),
+        )))
+        .execute(media_id)
+        .await
+        .unwrap();
+
+        assert_eq!(media.processing_status, ProcessingStatus::Complete);
+        assert_eq!(media.variants.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_failed_scan_fails_processing_but_keeps_content() {
+        let temp_dir = TempDir::new().unwrap();
+        let repository = Arc::new(InMemoryMediaRepository::new());
+        let storage = Arc::new(FilesystemStorage::new(temp_dir.path()));
+        let media_id = upload(&repository, &storage, create_test_png(), "photo.png").await;
+
+        let media = ProcessMediaUseCase::new(
+            repository,
+            storage.clone(),
+            Arc::new(InMemoryVariantRepository::new()),
+        )
+        .with_malware_scan(Some(MalwareScan::new(
+            Arc::new(StubScanner(None)),
+            temp_dir.path().join("quarantine"),
+        )))
+        .execute(media_id)
+        .await
+        .unwrap();
+
+        assert_eq!(media.processing_status, ProcessingStatus::Failed);
+        assert!(media.processing_error.unwrap().contains("clamd down"));
+        assert!(storage.exists(&media.content_hash).await.unwrap());
+    }
+}
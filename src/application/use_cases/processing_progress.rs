@@ -0,0 +1,152 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, PoisonError};
+use tokio::sync::watch;
+
+use crate::{
+    application::dto::ProcessingProgressEvent,
+    domain::{
+        entities::{Media, MediaId},
+        value_objects::ProcessingStatus,
+    },
+};
+
+/// Live progress of media being processed on this instance
+///
+/// Processing reports each stage it reaches, and listeners follow a media's
+/// progress through a [`watch::Receiver`]. A listener may subscribe before
+/// processing starts and sees every later stage. Entries are dropped once
+/// processing finishes, or once nothing listens to media still pending.
+/// Cloning is cheap; clones share the same state.
+#[derive(Debug, Clone, Default)]
+pub struct ProcessingProgress {
+    channels: Arc<Mutex<HashMap<MediaId, watch::Sender<ProcessingProgressEvent>>>>,
+}
+
+impl ProcessingProgress {
+    /// Create a tracker with no media in progress
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record that processing of `media_id` is `progress` percent done
+    pub fn advance(&self, media_id: MediaId, progress: u8) {
+        let update = ProcessingProgressEvent {
+            media_id,
+            status: ProcessingStatus::Processing,
+            progress: progress.min(99),
+            error_message: None,
+        };
+        let mut channels = self.lock();
+        if let Some(channel) = channels.get(&media_id) {
+            channel.send_replace(update);
+        } else {
+            channels.insert(media_id, watch::Sender::new(update));
+        }
+    }
+
+    /// Record how processing of `media` ended, telling listeners and dropping its entry
+    pub fn finish(&self, media: &Media) {
+        if let Some(channel) = self.lock().remove(&media.id) {
+            channel.send_replace(ProcessingProgressEvent::new(
+                media.id,
+                media.processing_status.clone(),
+                media.processing_error.clone(),
+            ));
+        }
+    }
+
+    /// Stop tracking `media_id` without an outcome, e.g. when it could not be saved
+    ///
+    /// Listeners see their channel close and must look the outcome up themselves.
+    pub fn forget(&self, media_id: MediaId) {
+        self.lock().remove(&media_id);
+    }
+
+    /// How far processing of `media_id` has got, if it is being processed here
+    #[must_use]
+    pub fn current(&self, media_id: MediaId) -> Option<u8> {
+        self.lock().get(&media_id).map(|channel| channel.borrow().progress)
+    }
+
+    /// Follow the progress of `initial.media_id`, starting from `initial`
+    ///
+    /// While the media is being processed here the receiver starts from its
+    /// current stage instead.
+    pub fn subscribe(
+        &self,
+        initial: ProcessingProgressEvent,
+    ) -> watch::Receiver<ProcessingProgressEvent> {
+        let mut channels = self.lock();
+        channels.retain(|_, channel| {
+            channel.receiver_count() > 0 || channel.borrow().status.is_processing()
+        });
+
+        let media_id = initial.media_id;
+        channels.entry(media_id).or_insert_with(|| watch::Sender::new(initial)).subscribe()
+    }
+
+    fn lock(
+        &self,
+    ) -> std::sync::MutexGuard<'_, HashMap<MediaId, watch::Sender<ProcessingProgressEvent>>> {
+        self.channels.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{
+        entities::{UnsavedMedia, UserId},
+        value_objects::{ContentHash, MediaType},
+    };
+
+    #[tokio::test]
+    async fn test_listeners_follow_processing_to_the_end() {
+        let progress = ProcessingProgress::new();
+        let media_id = MediaId::new(5);
+        let mut receiver = progress.subscribe(ProcessingProgressEvent::new(
+            media_id,
+            ProcessingStatus::Pending,
+            None,
+        ));
+
+        progress.advance(media_id, 20);
+        receiver.changed().await.unwrap();
+        assert_eq!(receiver.borrow_and_update().progress, 20);
+        assert_eq!(progress.current(media_id), Some(20));
+
+        let mut media = UnsavedMedia::new(
+            ContentHash::new(&"a".repeat(64)).unwrap(),
+            "cover.jpg".to_string(),
+            MediaType::new("image/jpeg"),
+            "aa/aa/aa/a".to_string(),
+            2048,
+            UserId::new(),
+        )
+        .into_media(media_id);
+        media.mark_failed("Unsupported color profile");
+        progress.finish(&media);
+
+        receiver.changed().await.unwrap();
+        let finished = receiver.borrow_and_update().clone();
+        assert_eq!(finished.status, ProcessingStatus::Failed);
+        assert_eq!(finished.error_message.as_deref(), Some("Unsupported color profile"));
+        assert_eq!(progress.current(media_id), None);
+        assert!(receiver.changed().await.is_err());
+    }
+
+    #[test]
+    fn test_pending_entries_are_dropped_without_listeners() {
+        let progress = ProcessingProgress::new();
+        let pending =
+            |id| ProcessingProgressEvent::new(MediaId::new(id), ProcessingStatus::Pending, None);
+
+        drop(progress.subscribe(pending(5)));
+        progress.advance(MediaId::new(6), 5);
+        let _listener = progress.subscribe(pending(7));
+
+        assert_eq!(progress.lock().len(), 2);
+        assert_eq!(progress.current(MediaId::new(5)), None);
+    }
+}
@@ -0,0 +1,57 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// Whether this instance currently refuses writes
+///
+/// Used during storage failovers: while enabled, mutating endpoints answer
+/// `503 Service Unavailable` and reads and downloads keep working. Cloning is
+/// cheap; clones share the same switch, so an admin toggle applies to every
+/// request at once. The `service_read_only` gauge follows the switch.
+#[derive(Debug, Clone, Default)]
+pub struct ReadOnlyMode {
+    enabled: Arc<AtomicBool>,
+}
+
+impl ReadOnlyMode {
+    /// Create a switch, starting enabled if `enabled`
+    #[must_use]
+    pub fn new(enabled: bool) -> Self {
+        let mode = Self::default();
+        mode.set(enabled);
+        mode
+    }
+
+    /// Accept writes
+    #[must_use]
+    pub fn disabled() -> Self {
+        Self::default()
+    }
+
+    /// Whether writes are currently refused
+    #[must_use]
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// Start or stop refusing writes, returning whether they were refused before
+    pub fn set(&self, enabled: bool) -> bool {
+        metrics::gauge!("service_read_only").set(if enabled { 1.0 } else { 0.0 });
+        self.enabled.swap(enabled, Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clones_share_the_switch() {
+        let mode = ReadOnlyMode::disabled();
+        let shared = mode.clone();
+
+        assert!(!shared.set(true));
+        assert!(mode.is_enabled());
+        assert!(mode.set(false));
+        assert!(!shared.is_enabled());
+    }
+}
@@ -0,0 +1,103 @@
+use std::sync::Arc;
+
+use super::repository_error;
+use crate::{
+    application::events::{DomainEvent, EventPublisher},
+    domain::repositories::MediaRepository,
+    presentation::middleware::error::AppError,
+};
+
+/// Events taken from the outbox at a time
+const RELAY_BATCH_SIZE: u32 = 100;
+
+/// Use case for publishing events that repositories recorded with the changes raising them
+///
+/// A change and its events commit together, so they are published even if the
+/// instance that made the change stops right after committing. Relayed events
+/// go to the same event bus use cases publish to. An event relayed just before
+/// a crash may be published twice, never not at all.
+pub struct RelayEventsUseCase<R>
+where
+    R: MediaRepository + ?Sized,
+{
+    repository: Arc<R>,
+    events: Arc<dyn EventPublisher>,
+}
+
+impl<R> RelayEventsUseCase<R>
+where
+    R: MediaRepository + ?Sized,
+{
+    /// Create a relay publishing to `events`
+    pub fn new(repository: Arc<R>, events: Arc<dyn EventPublisher>) -> Self {
+        Self { repository, events }
+    }
+
+    /// Publish every recorded event, oldest first, returning how many were published
+    ///
+    /// # Errors
+    /// * `Database` - Reading or removing recorded events failed
+    pub async fn execute(&self) -> Result<usize, AppError> {
+        let events = self.events.clone();
+        let publish = move |event: &DomainEvent| events.publish(event);
+        let mut relayed = 0;
+        loop {
+            let batch = self
+                .repository
+                .relay_events(RELAY_BATCH_SIZE, &publish)
+                .await
+                .map_err(repository_error("Failed to relay recorded events"))?;
+            relayed += batch;
+            if batch < RELAY_BATCH_SIZE as usize {
+                break;
+            }
+        }
+
+        if relayed > 0 {
+            tracing::debug!("Relayed {} recorded events", relayed);
+        }
+        Ok(relayed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        application::{dto::SetMediaTagsRequest, use_cases::SetMediaTagsUseCase},
+        domain::{
+            entities::{MediaId, UnsavedMedia, UserId},
+            value_objects::{ContentHash, MediaType},
+        },
+        test_utils::mocks::{InMemoryMediaRepository, RecordedEvents},
+    };
+
+    #[tokio::test]
+    async fn test_recorded_events_are_published_once_relayed() {
+        let owner = UserId::new();
+        let media = UnsavedMedia::new(
+            ContentHash::new(&"a".repeat(64)).unwrap(),
+            "cover.jpg".to_string(),
+            MediaType::new("image/jpeg"),
+            "aa/aa/aa/cover".to_string(),
+            2048,
+            owner,
+        )
+        .into_media(MediaId::new(5));
+        let repo = Arc::new(InMemoryMediaRepository::new().with_media(media).with_outbox());
+        let events = RecordedEvents::new();
+        let set_tags = SetMediaTagsUseCase::new(repo.clone()).with_events(Arc::new(events.clone()));
+        let relay = RelayEventsUseCase::new(repo, Arc::new(events.clone()));
+
+        let request = SetMediaTagsRequest { tags: vec!["dessert".to_string()] };
+        set_tags.execute(MediaId::new(5), request, Some(owner)).await.unwrap();
+        assert!(events.events().is_empty());
+
+        assert_eq!(relay.execute().await.unwrap(), 1);
+        assert_eq!(
+            events.events(),
+            vec![DomainEvent::MediaTagsChanged { media_id: MediaId::new(5), uploaded_by: owner }]
+        );
+        assert_eq!(relay.execute().await.unwrap(), 0);
+    }
+}
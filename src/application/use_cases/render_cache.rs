@@ -0,0 +1,141 @@
+use bytes::Bytes;
+use moka::{future::Cache, policy::EvictionPolicy};
+
+use crate::domain::entities::{MediaId, MediaVariant};
+
+/// A rendered image held in memory, with the variant describing it
+#[derive(Debug, Clone)]
+pub struct RenderedImage {
+    pub variant: MediaVariant,
+    pub data: Bytes,
+}
+
+/// Images rendered on demand, by media and render, held in memory
+///
+/// Repeated requests for a size are served without reading storage or decoding
+/// the original again, including while the service is read-only and renders are
+/// not kept. Once the images held exceed the byte budget, the least recently used
+/// are evicted. Every instance has its own entries. Disabled by default.
+#[derive(Debug, Clone, Default)]
+pub struct RenderCache {
+    entries: Option<Cache<(MediaId, String), RenderedImage>>,
+}
+
+impl RenderCache {
+    /// Hold rendered images up to `max_bytes` in total
+    #[must_use]
+    pub fn new(max_bytes: u64) -> Self {
+        let entries = Cache::builder()
+            .max_capacity(max_bytes)
+            .weigher(|_, image: &RenderedImage| u32::try_from(image.data.len()).unwrap_or(u32::MAX))
+            .eviction_policy(EvictionPolicy::lru())
+            .build();
+        Self { entries: Some(entries) }
+    }
+
+    /// Hold no rendered images
+    #[must_use]
+    pub fn disabled() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn is_enabled(&self) -> bool {
+        self.entries.is_some()
+    }
+
+    /// The image rendered for media `media_id` as the variant `name`, if held
+    ///
+    /// Lookups are counted in `render_cache_requests_total` by `result`.
+    pub async fn get(&self, media_id: MediaId, name: &str) -> Option<RenderedImage> {
+        let entries = self.entries.as_ref()?;
+        let image = entries.get(&(media_id, name.to_string())).await;
+        let result = if image.is_some() { "hit" } else { "miss" };
+        metrics::counter!("render_cache_requests_total", "result" => result).increment(1);
+        image
+    }
+
+    /// Hold the image rendered for media `media_id` as the variant `name`
+    pub async fn insert(&self, media_id: MediaId, name: &str, image: RenderedImage) {
+        if let Some(entries) = &self.entries {
+            entries.insert((media_id, name.to_string()), image).await;
+        }
+    }
+
+    /// Drop the images rendered for `media_id`, or every image when `None`,
+    /// returning how many were dropped
+    pub async fn purge(&self, media_id: Option<MediaId>) -> u64 {
+        let Some(entries) = &self.entries else { return 0 };
+        let mut purged = 0;
+        for (key, _) in entries {
+            if media_id.is_none_or(|id| key.0 == id) {
+                entries.invalidate(&*key).await;
+                purged += 1;
+            }
+        }
+        purged
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::value_objects::{ContentHash, MediaType};
+
+    fn image(name: &str, size: usize) -> RenderedImage {
+        RenderedImage {
+            variant: MediaVariant {
+                name: name.to_string(),
+                content_hash: ContentHash::new(&"a".repeat(64)).unwrap(),
+                media_type: MediaType::new("image/png"),
+                file_size: size as u64,
+            },
+            data: Bytes::from(vec![0; size]),
+        }
+    }
+
+    fn media_id(id: i64) -> MediaId {
+        MediaId::new(id)
+    }
+
+    #[tokio::test]
+    async fn test_disabled_cache_holds_nothing() {
+        let cache = RenderCache::disabled();
+        cache.insert(media_id(1), "render-4x4", image("render-4x4", 16)).await;
+
+        assert!(cache.get(media_id(1), "render-4x4").await.is_none());
+        assert_eq!(cache.purge(None).await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_least_recently_used_images_are_evicted_over_budget() {
+        let cache = RenderCache::new(100);
+        cache.insert(media_id(1), "render-4x4", image("render-4x4", 40)).await;
+        cache.insert(media_id(2), "render-4x4", image("render-4x4", 40)).await;
+        cache.entries.as_ref().unwrap().run_pending_tasks().await;
+        assert!(cache.get(media_id(1), "render-4x4").await.is_some());
+        cache.entries.as_ref().unwrap().run_pending_tasks().await;
+
+        cache.insert(media_id(3), "render-4x4", image("render-4x4", 40)).await;
+        cache.entries.as_ref().unwrap().run_pending_tasks().await;
+
+        assert!(cache.get(media_id(1), "render-4x4").await.is_some());
+        assert!(cache.get(media_id(2), "render-4x4").await.is_none());
+        assert!(cache.get(media_id(3), "render-4x4").await.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_purge_drops_the_images_of_one_media() {
+        let cache = RenderCache::new(1024);
+        cache.insert(media_id(1), "render-4x4", image("render-4x4", 16)).await;
+        cache.insert(media_id(1), "render-x2", image("render-x2", 16)).await;
+        cache.insert(media_id(2), "render-4x4", image("render-4x4", 16)).await;
+
+        assert_eq!(cache.purge(Some(media_id(1))).await, 2);
+        assert!(cache.get(media_id(1), "render-4x4").await.is_none());
+        assert!(cache.get(media_id(2), "render-4x4").await.is_some());
+
+        assert_eq!(cache.purge(None).await, 1);
+        assert!(cache.get(media_id(2), "render-4x4").await.is_none());
+    }
+}
@@ -0,0 +1,642 @@
+use std::sync::Arc;
+use tokio::io::AsyncReadExt;
+
+use super::{
+    download_media::ByteRange, ensure_downloadable, release_variant, repository_error,
+    storage_error, store_variant, variant_filename, DownloadResponse, ReadOnlyMode, RenderCache,
+    RenderedImage,
+};
+use crate::{
+    domain::{
+        entities::{Media, MediaId, MediaVariant, UserId},
+        repositories::{MediaRepository, VariantRepository},
+        value_objects::{ContentHash, ImageRender, MediaType, RenderPolicy},
+    },
+    infrastructure::{
+        processing::{ImageVariantEncoder, RenderProvider},
+        storage::{generate_content_hash, FileStorage},
+    },
+    presentation::middleware::error::AppError,
+};
+
+/// Use case for serving images resized on demand
+///
+/// Only the sizes the [`RenderPolicy`] lists are rendered. Each size and fit is
+/// rendered the first time it is asked for and kept as a variant of the media
+/// named after it, e.g. `render-320x240-cover`, so later requests read it from
+/// storage; it is released with the media's other variants. While the service
+/// is read-only, new sizes are rendered but not kept. With a [`RenderCache`],
+/// rendered images are also held in memory, so repeated requests read neither
+/// storage nor the original. With a [`RenderProvider`], nothing is rendered here:
+/// clients are redirected to the provider's signed URL for the size instead.
+pub struct RenderMediaUseCase<R, S, V>
+where
+    R: MediaRepository + ?Sized,
+    S: FileStorage + ?Sized,
+    V: VariantRepository + ?Sized,
+{
+    repository: Arc<R>,
+    storage: Arc<S>,
+    variants: Arc<V>,
+    encoder: ImageVariantEncoder,
+    policy: RenderPolicy,
+    read_only: ReadOnlyMode,
+    cache: RenderCache,
+    provider: Option<RenderProvider>,
+}
+
+impl<R, S, V> RenderMediaUseCase<R, S, V>
+where
+    R: MediaRepository + ?Sized,
+    S: FileStorage + ?Sized,
+    V: VariantRepository + ?Sized,
+{
+    /// Create a new render media use case, allowing the default sizes
+    pub fn new(repository: Arc<R>, storage: Arc<S>, variants: Arc<V>) -> Self {
+        Self {
+            repository,
+            storage,
+            variants,
+            encoder: ImageVariantEncoder::new(),
+            policy: RenderPolicy::default(),
+            read_only: ReadOnlyMode::disabled(),
+            cache: RenderCache::disabled(),
+            provider: None,
+        }
+    }
+
+    /// Render only the sizes `policy` allows
+    #[must_use]
+    pub fn with_policy(mut self, policy: RenderPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Keep no newly rendered images while `read_only` is enabled
+    #[must_use]
+    pub fn with_read_only(mut self, read_only: ReadOnlyMode) -> Self {
+        self.read_only = read_only;
+        self
+    }
+
+    /// Hold rendered images in `cache`, serving later requests from memory
+    #[must_use]
+    pub fn with_cache(mut self, cache: RenderCache) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    /// Redirect renders to `provider` instead of rendering them
+    #[must_use]
+    pub fn with_provider(mut self, provider: Option<RenderProvider>) -> Self {
+        self.provider = provider;
+        self
+    }
+
+    /// Serve an image resized per `render`, rendering and keeping it if it is new
+    ///
+    /// A `range` header is resolved against the resized image, as on downloads.
+    /// With a provider, the response carries its URL in `redirect_url` instead.
+    ///
+    /// # Errors
+    /// * `BadRequest` - The size is not allowed, the media is not ready, or it is
+    ///   not an image that can be rendered
+    /// * `NotFound` - Media with the given ID or its content doesn't exist
+    /// * `Authorization` - The media was uploaded by another user
+    /// * `RangeNotSatisfiable` - The range starts past the end of the image
+    /// * `Internal` - Rendering or keeping the image failed
+    pub async fn execute(
+        &self,
+        media_id: MediaId,
+        render: ImageRender,
+        range: Option<&str>,
+        requester: Option<UserId>,
+    ) -> Result<DownloadResponse, AppError> {
+        if !self.policy.allows(render.size) {
+            let allowed: Vec<String> =
+                self.policy.sizes().iter().map(ToString::to_string).collect();
+            return Err(AppError::BadRequest {
+                message: format!(
+                    "Images cannot be rendered at {}; allowed sizes are {}",
+                    render.size,
+                    allowed.join(", ")
+                ),
+            });
+        }
+
+        let media = self
+            .repository
+            .find_by_id(media_id)
+            .await
+            .map_err(repository_error("Failed to query media"))?
+            .ok_or_else(|| AppError::NotFound { resource: format!("Media with ID {media_id}") })?;
+        ensure_downloadable(&media, requester)?;
+
+        let content_type = media.media_type.mime_type().to_string();
+        if !ImageVariantEncoder::supports(&content_type) {
+            return Err(AppError::BadRequest {
+                message: format!("Media {media_id} is {content_type}, which cannot be rendered"),
+            });
+        }
+
+        if let Some(provider) = &self.provider {
+            return Ok(delegate(provider, &media, render));
+        }
+
+        let name = render.variant_name();
+        if let Some(image) = self.cache.get(media_id, &name).await {
+            tracing::debug!("Serving cached {} render of media {}", name, media_id);
+            return serve(&media, image, range);
+        }
+
+        let image = match media.variants.iter().find(|variant| variant.name == name) {
+            Some(variant) if !self.cache.is_enabled() => {
+                return self.open_kept(&media, variant, range).await;
+            }
+            Some(variant) => {
+                tracing::debug!("Serving kept {} variant of media {}", name, media_id);
+                RenderedImage {
+                    variant: variant.clone(),
+                    data: self.read(&media, &variant.content_hash).await?.into(),
+                }
+            }
+            None => self.render(&media, render, content_type).await?,
+        };
+        self.cache.insert(media_id, &name, image.clone()).await;
+        serve(&media, image, range)
+    }
+
+    /// Stream a kept render from storage, or only the requested range of it
+    async fn open_kept(
+        &self,
+        media: &Media,
+        variant: &MediaVariant,
+        range: Option<&str>,
+    ) -> Result<DownloadResponse, AppError> {
+        tracing::debug!("Serving kept {} variant of media {}", variant.name, media.id);
+        let byte_range =
+            range.map(|h| ByteRange::resolve(h, variant.file_size)).transpose()?.flatten();
+        let content = match byte_range {
+            Some(range) => {
+                self.storage
+                    .retrieve_range(&variant.content_hash, range.start, range.length())
+                    .await
+            }
+            None => self.storage.retrieve(&variant.content_hash).await,
+        }
+        .map_err(storage_error(media.id))?;
+
+        Ok(DownloadResponse {
+            content,
+            content_length: byte_range.map_or(variant.file_size, |r| r.length()),
+            content_hash: variant.content_hash.clone(),
+            content_type: variant.media_type.mime_type().to_string(),
+            filename: variant_filename(media, variant),
+            file_size: variant.file_size,
+            negotiated: false,
+            range: byte_range,
+            redirect_url: None,
+        })
+    }
+
+    /// Render a new size of an image, keeping it unless the service is read-only
+    async fn render(
+        &self,
+        media: &Media,
+        render: ImageRender,
+        content_type: String,
+    ) -> Result<RenderedImage, AppError> {
+        let (media_id, name) = (media.id, render.variant_name());
+        let original = self.read(media, &media.content_hash).await?;
+        let encoder = self.encoder;
+        let (rendered_type, data) =
+            tokio::task::spawn_blocking(move || encoder.render(&original, &content_type, render))
+                .await
+                .map_err(|e| AppError::Internal { message: format!("Render task failed: {e}") })?
+                .map_err(|e| AppError::Internal {
+                    message: format!("Failed to render media {media_id} as {name}: {e}"),
+                })?;
+        tracing::info!("Rendered media {} as {} ({} bytes)", media_id, name, data.len());
+
+        let variant = if self.read_only.is_enabled() {
+            let content_hash = generate_content_hash(&data).map_err(|e| AppError::Internal {
+                message: format!("Failed to hash {name}: {e}"),
+            })?;
+            MediaVariant {
+                name,
+                content_hash,
+                media_type: MediaType::new(rendered_type),
+                file_size: data.len() as u64,
+            }
+        } else {
+            self.keep(media_id, &name, rendered_type, data.clone()).await?
+        };
+
+        Ok(RenderedImage { variant, data: data.into() })
+    }
+
+    /// Read a stored file of `media` into memory
+    async fn read(&self, media: &Media, hash: &ContentHash) -> Result<Vec<u8>, AppError> {
+        let mut reader = self.storage.retrieve(hash).await.map_err(storage_error(media.id))?;
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data).await.map_err(|e| AppError::Internal {
+            message: format!("Failed to read {hash} of media {}: {e}", media.id),
+        })?;
+        Ok(data)
+    }
+
+    /// Store a resized image and record it on the media
+    ///
+    /// If a concurrent request recorded it first, or the media was deleted, the
+    /// reference taken here is released again.
+    async fn keep(
+        &self,
+        media_id: MediaId,
+        name: &str,
+        content_type: &str,
+        data: Vec<u8>,
+    ) -> Result<MediaVariant, AppError> {
+        let variant = store_variant(
+            &*self.repository,
+            &*self.variants,
+            &*self.storage,
+            name,
+            content_type,
+            data,
+        )
+        .await
+        .map_err(|e| AppError::Internal { message: format!("Failed to keep {name}: {e}") })?;
+
+        match self.record(media_id, &variant).await {
+            Ok(true) => {}
+            Ok(false) => self.release(&variant).await,
+            Err(e) => {
+                self.release(&variant).await;
+                return Err(e);
+            }
+        }
+        Ok(variant)
+    }
+
+    /// Add a resized image to the media's variants, returning whether it was added
+    async fn record(&self, media_id: MediaId, variant: &MediaVariant) -> Result<bool, AppError> {
+        self.repository
+            .add_variant(media_id, variant)
+            .await
+            .map_err(repository_error(format!("Failed to update media {media_id}")))
+    }
+
+    async fn release(&self, variant: &MediaVariant) {
+        release_variant(&*self.repository, &*self.variants, &*self.storage, variant).await;
+    }
+}
+
+/// The response redirecting a render to the provider's URL for it
+fn delegate(provider: &RenderProvider, media: &Media, render: ImageRender) -> DownloadResponse {
+    tracing::debug!(
+        "Redirecting {} render of media {} to {}",
+        render.variant_name(),
+        media.id,
+        provider.name()
+    );
+    DownloadResponse {
+        content: Box::new(tokio::io::empty()),
+        content_length: 0,
+        content_hash: media.content_hash.clone(),
+        content_type: media.media_type.mime_type().to_string(),
+        filename: media.original_filename.clone(),
+        file_size: media.file_size,
+        negotiated: false,
+        range: None,
+        redirect_url: Some(provider.url(&media.content_hash, render)),
+    }
+}
+
+/// The response for a rendered image held in memory, or only the requested range of it
+fn serve(
+    media: &Media,
+    image: RenderedImage,
+    range: Option<&str>,
+) -> Result<DownloadResponse, AppError> {
+    let file_size = image.data.len() as u64;
+    let byte_range = range.map(|h| ByteRange::resolve(h, file_size)).transpose()?.flatten();
+    let data = match byte_range {
+        Some(range) => image.data.slice(range.start as usize..=range.end as usize),
+        None => image.data,
+    };
+
+    Ok(DownloadResponse {
+        filename: variant_filename(media, &image.variant),
+        content_length: data.len() as u64,
+        content: Box::new(std::io::Cursor::new(data)),
+        content_type: image.variant.media_type.mime_type().to_string(),
+        content_hash: image.variant.content_hash,
+        file_size,
+        negotiated: false,
+        range: byte_range,
+        redirect_url: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    use crate::{
+        application::use_cases::{DownloadMediaUseCase, ProcessMediaUseCase, UploadMediaUseCase},
+        domain::value_objects::ImageFit,
+        infrastructure::{
+            config::{RenderProviderConfig, RenderProviderKind},
+            persistence::InMemoryVariantRepository,
+            storage::FilesystemStorage,
+        },
+        test_utils::{images::create_test_png, mocks::InMemoryMediaRepository},
+    };
+
+    struct Fixture {
+        _temp_dir: TempDir,
+        repository: Arc<InMemoryMediaRepository>,
+        storage: Arc<FilesystemStorage>,
+        variants: Arc<InMemoryVariantRepository>,
+    }
+
+    impl Fixture {
+        fn new() -> Self {
+            let temp_dir = TempDir::new().unwrap();
+            let storage = Arc::new(FilesystemStorage::new(temp_dir.path()));
+            Self {
+                _temp_dir: temp_dir,
+                repository: Arc::new(InMemoryMediaRepository::new()),
+                storage,
+                variants: Arc::new(InMemoryVariantRepository::new()),
+            }
+        }
+
+        /// Upload and process a file, as a client would before rendering it
+        async fn upload(&self, data: Vec<u8>, filename: &str) -> MediaId {
+            let media_id =
+                UploadMediaUseCase::new(self.repository.clone(), self.storage.clone(), 1024 * 1024)
+                    .execute(std::io::Cursor::new(data), filename.to_string(), owner(), None)
+                    .await
+                    .unwrap()
+                    .media_id;
+            ProcessMediaUseCase::new(
+                self.repository.clone(),
+                self.storage.clone(),
+                self.variants.clone(),
+            )
+            .execute(media_id)
+            .await
+            .unwrap();
+            media_id
+        }
+
+        fn use_case(
+            &self,
+        ) -> RenderMediaUseCase<InMemoryMediaRepository, FilesystemStorage, InMemoryVariantRepository>
+        {
+            let sizes = ["4x4", "x2"].iter().map(|size| size.parse().unwrap()).collect();
+            RenderMediaUseCase::new(
+                self.repository.clone(),
+                self.storage.clone(),
+                self.variants.clone(),
+            )
+            .with_policy(RenderPolicy::new(sizes))
+        }
+    }
+
+    /// The user uploading the test media
+    fn owner() -> UserId {
+        UserId::from_uuid(uuid::Uuid::from_u128(1))
+    }
+
+    fn render(size: &str, fit: ImageFit) -> ImageRender {
+        ImageRender::new(size.parse().unwrap(), fit)
+    }
+
+    async fn read(response: DownloadResponse) -> Vec<u8> {
+        let mut data = Vec::new();
+        let mut content = response.content;
+        content.read_to_end(&mut data).await.unwrap();
+        data
+    }
+
+    #[tokio::test]
+    async fn test_size_is_rendered_once_and_kept_as_a_variant() {
+        let fixture = Fixture::new();
+        let media_id = fixture.upload(create_test_png(), "photo.png").await;
+        let use_case = fixture.use_case();
+
+        let first = use_case
+            .execute(media_id, render("4x4", ImageFit::Cover), None, Some(owner()))
+            .await
+            .unwrap();
+        assert_eq!(first.content_type, "image/png");
+        let hash = first.content_hash.clone();
+        let image = image::load_from_memory(&read(first).await).unwrap();
+        assert_eq!((image.width(), image.height()), (4, 4));
+
+        let media = fixture.repository.find_by_id(media_id).await.unwrap().unwrap();
+        let kept = media.variants.iter().find(|v| v.name == "render-4x4-cover").unwrap();
+        assert_eq!(kept.content_hash, hash);
+        assert!(fixture.storage.exists(&hash).await.unwrap());
+
+        let again = use_case
+            .execute(media_id, render("4x4", ImageFit::Cover), None, Some(owner()))
+            .await
+            .unwrap();
+        assert_eq!(again.content_hash, hash);
+        assert_eq!(fixture.variants.reference_count(&hash).await.unwrap(), 1);
+
+        // The smaller render is never negotiated in place of the original
+        let download =
+            DownloadMediaUseCase::new(fixture.repository.clone(), fixture.storage.clone())
+                .execute_negotiated(media_id, Some("image/png"), None, Some(owner()))
+                .await
+                .unwrap();
+        assert_eq!(download.content_hash, media.content_hash);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_concurrent_renders_keep_every_variant_once() {
+        let fixture = Fixture::new();
+        let media_id = fixture.upload(create_test_png(), "photo.png").await;
+        let use_case = Arc::new(fixture.use_case());
+
+        let renders =
+            [("4x4", ImageFit::Cover), ("4x4", ImageFit::Fill), ("x2", ImageFit::Contain)];
+        let tasks: Vec<_> = renders
+            .iter()
+            .cycle()
+            .take(renders.len() * 4)
+            .map(|&(size, fit)| {
+                let use_case = use_case.clone();
+                tokio::spawn(async move {
+                    use_case.execute(media_id, render(size, fit), None, Some(owner())).await
+                })
+            })
+            .collect();
+        for task in tasks {
+            task.await.unwrap().unwrap();
+        }
+
+        let media = fixture.repository.find_by_id(media_id).await.unwrap().unwrap();
+        let kept: Vec<_> =
+            media.variants.iter().filter(|v| ImageRender::is_render_variant(&v.name)).collect();
+        assert_eq!(kept.len(), renders.len());
+        // Renders lost to a concurrent request released their reference again
+        for variant in &kept {
+            let sharing = kept.iter().filter(|v| v.content_hash == variant.content_hash).count();
+            let references = fixture.variants.reference_count(&variant.content_hash).await;
+            assert_eq!(references.unwrap(), sharing as u64);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_only_allowed_sizes_of_images_are_rendered() {
+        let fixture = Fixture::new();
+        let image_id = fixture.upload(create_test_png(), "photo.png").await;
+        let text_id = fixture.upload(b"just some notes".to_vec(), "notes.txt").await;
+        let use_case = fixture.use_case();
+
+        let error = use_case
+            .execute(image_id, render("5x5", ImageFit::Contain), None, Some(owner()))
+            .await
+            .unwrap_err();
+        assert!(matches!(&error, AppError::BadRequest { message } if message.contains("4x4, x2")));
+        let error = use_case
+            .execute(text_id, render("x2", ImageFit::Contain), None, Some(owner()))
+            .await
+            .unwrap_err();
+        assert!(matches!(error, AppError::BadRequest { .. }));
+        let error = use_case
+            .execute(image_id, render("x2", ImageFit::Contain), None, Some(UserId::new()))
+            .await
+            .unwrap_err();
+        assert!(matches!(error, AppError::Authorization { .. }));
+        let error = use_case
+            .execute(image_id, render("x2", ImageFit::Contain), None, None)
+            .await
+            .unwrap_err();
+        assert!(matches!(error, AppError::Authentication { .. }));
+        let error = use_case
+            .execute(MediaId::new(999), render("x2", ImageFit::Contain), None, Some(owner()))
+            .await
+            .unwrap_err();
+        assert!(matches!(error, AppError::NotFound { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_renders_are_redirected_to_the_provider() {
+        let fixture = Fixture::new();
+        let media_id = fixture.upload(create_test_png(), "photo.png").await;
+        let provider = RenderProvider::from_config(&RenderProviderConfig {
+            enabled: true,
+            kind: RenderProviderKind::Imgix,
+            base_url: "https://recipes.imgix.net".to_string(),
+            signing_key: "s3cr3t".into(),
+        });
+        let use_case = fixture.use_case().with_provider(provider);
+
+        let response = use_case
+            .execute(media_id, render("4x4", ImageFit::Cover), None, Some(owner()))
+            .await
+            .unwrap();
+        let url = response.redirect_url.unwrap();
+        assert!(url.starts_with("https://recipes.imgix.net/"), "{url}");
+        assert!(url.contains("?fit=crop&h=4&w=4&s="), "{url}");
+
+        // Nothing is rendered or kept here, and sizes are still limited
+        let media = fixture.repository.find_by_id(media_id).await.unwrap().unwrap();
+        assert!(!media.variants.iter().any(|v| ImageRender::is_render_variant(&v.name)));
+        let error = use_case
+            .execute(media_id, render("5x5", ImageFit::Cover), None, Some(owner()))
+            .await
+            .unwrap_err();
+        assert!(matches!(error, AppError::BadRequest { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_cached_renders_are_served_from_memory() {
+        let fixture = Fixture::new();
+        let media_id = fixture.upload(create_test_png(), "photo.png").await;
+        let cache = RenderCache::new(1024 * 1024);
+        let use_case = fixture.use_case().with_cache(cache.clone());
+
+        let first = use_case
+            .execute(media_id, render("x2", ImageFit::Fill), None, Some(owner()))
+            .await
+            .unwrap();
+        let hash = first.content_hash.clone();
+        let rendered = read(first).await;
+        fixture.storage.delete(&hash).await.unwrap();
+
+        let again = use_case
+            .execute(media_id, render("x2", ImageFit::Fill), None, Some(owner()))
+            .await
+            .unwrap();
+        assert_eq!(again.content_hash, hash);
+        assert_eq!(read(again).await, rendered);
+
+        assert_eq!(cache.purge(Some(media_id)).await, 1);
+        let error = use_case
+            .execute(media_id, render("x2", ImageFit::Fill), None, Some(owner()))
+            .await
+            .unwrap_err();
+        assert!(matches!(error, AppError::NotFound { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_ranges_of_renders_are_served() {
+        let fixture = Fixture::new();
+        let media_id = fixture.upload(create_test_png(), "photo.png").await;
+        let kept = fixture.use_case();
+        let cached = fixture.use_case().with_cache(RenderCache::new(1024 * 1024));
+
+        let whole = read(
+            kept.execute(media_id, render("4x4", ImageFit::Cover), None, Some(owner()))
+                .await
+                .unwrap(),
+        )
+        .await;
+        for use_case in [&kept, &cached, &cached] {
+            let response = use_case
+                .execute(media_id, render("4x4", ImageFit::Cover), Some("bytes=0-9"), Some(owner()))
+                .await
+                .unwrap();
+            assert_eq!(response.range, Some(ByteRange { start: 0, end: 9 }));
+            assert_eq!((response.content_length, response.file_size), (10, whole.len() as u64));
+            assert_eq!(read(response).await, whole[..10]);
+
+            let error = use_case
+                .execute(
+                    media_id,
+                    render("4x4", ImageFit::Cover),
+                    Some("bytes=100000-"),
+                    Some(owner()),
+                )
+                .await
+                .unwrap_err();
+            assert!(matches!(error, AppError::RangeNotSatisfiable { .. }));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_read_only_renders_without_keeping() {
+        let fixture = Fixture::new();
+        let media_id = fixture.upload(create_test_png(), "photo.png").await;
+        let use_case = fixture.use_case().with_read_only(ReadOnlyMode::new(true));
+
+        let response = use_case
+            .execute(media_id, render("x2", ImageFit::Fill), None, Some(owner()))
+            .await
+            .unwrap();
+        assert!(!fixture.storage.exists(&response.content_hash).await.unwrap());
+        let image = image::load_from_memory(&read(response).await).unwrap();
+        assert_eq!((image.width(), image.height()), (2, 2));
+
+        let media = fixture.repository.find_by_id(media_id).await.unwrap().unwrap();
+        assert!(media.variants.iter().all(|v| !ImageRender::is_render_variant(&v.name)));
+    }
+}
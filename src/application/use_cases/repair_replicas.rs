@@ -0,0 +1,157 @@
+use std::sync::Arc;
+
+use super::repository_error;
+use crate::{
+    domain::{entities::MediaId, repositories::MediaRepository, value_objects::ContentHash},
+    infrastructure::storage::{FileStorage, ReplicaRepair},
+    presentation::middleware::error::AppError,
+};
+
+/// Media read from the repository at a time
+const PAGE_SIZE: u32 = 100;
+
+/// Copies restored by one repair run
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReplicaRepairs {
+    /// Number of files whose copies were checked
+    pub checked: u64,
+    /// Files copied from the primary to the replica
+    pub copied_to_replica: u64,
+    /// Files copied back from the replica to the primary
+    pub copied_to_primary: u64,
+    /// Files neither backend has
+    pub lost: Vec<ContentHash>,
+    /// Files whose copies could not be checked or restored this run
+    pub failed: u64,
+}
+
+/// Use case for restoring files missing from the primary or the replica storage
+///
+/// Walks every media, copying its original and variants to whichever backend
+/// lacks them, e.g. because the background copy after an upload failed or a
+/// backend lost data. Files that cannot be repaired are logged and retried on
+/// the next run. Does nothing unless storage is mirrored.
+pub struct RepairReplicasUseCase<R: ?Sized, S: ?Sized> {
+    repository: Arc<R>,
+    storage: Arc<S>,
+}
+
+impl<R: ?Sized, S: ?Sized> RepairReplicasUseCase<R, S>
+where
+    R: MediaRepository,
+    S: FileStorage,
+{
+    pub fn new(repository: Arc<R>, storage: Arc<S>) -> Self {
+        Self { repository, storage }
+    }
+
+    /// Check the copies of every stored file, restoring missing ones
+    ///
+    /// # Errors
+    /// Returns `Database` if media could not be read
+    pub async fn execute(&self) -> Result<ReplicaRepairs, AppError> {
+        let mut repairs = ReplicaRepairs::default();
+        let Some(mirror) = self.storage.mirrored() else {
+            return Ok(repairs);
+        };
+
+        let mut after: Option<MediaId> = None;
+        loop {
+            let page = self
+                .repository
+                .find_after(after, PAGE_SIZE)
+                .await
+                .map_err(repository_error("Failed to query media"))?;
+            for media in page.iter().filter(|media| !media.is_awaiting_upload()) {
+                let hashes = std::iter::once(&media.content_hash)
+                    .chain(media.variants.iter().map(|variant| &variant.content_hash));
+                for hash in hashes {
+                    repairs.checked += 1;
+                    match mirror.repair(hash).await {
+                        Ok(ReplicaRepair::InSync) => {}
+                        Ok(ReplicaRepair::CopiedToReplica) => repairs.copied_to_replica += 1,
+                        Ok(ReplicaRepair::CopiedToPrimary) => repairs.copied_to_primary += 1,
+                        Ok(ReplicaRepair::Lost) => {
+                            tracing::error!(
+                                "{} of media {} is missing from both backends",
+                                hash,
+                                media.id
+                            );
+                            repairs.lost.push(hash.clone());
+                        }
+                        Err(e) => {
+                            tracing::warn!("Failed to repair copies of {}: {}", hash, e);
+                            repairs.failed += 1;
+                        }
+                    }
+                }
+            }
+            after = page.last().map(|media| media.id);
+            if page.len() < PAGE_SIZE as usize {
+                break;
+            }
+        }
+
+        if repairs.copied_to_replica + repairs.copied_to_primary > 0 {
+            tracing::info!(
+                "Replica repair restored {} copies to the replica and {} to the primary",
+                repairs.copied_to_replica,
+                repairs.copied_to_primary
+            );
+        }
+        Ok(repairs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        domain::{
+            entities::{UnsavedMedia, UserId},
+            value_objects::MediaType,
+        },
+        infrastructure::storage::{generate_content_hash, InMemoryStorage, MirroredStorage},
+        test_utils::mocks::InMemoryMediaRepository,
+    };
+
+    fn media(id: i64, content: &[u8]) -> crate::domain::entities::Media {
+        UnsavedMedia::new(
+            generate_content_hash(content).unwrap(),
+            format!("{id}.jpg"),
+            MediaType::new("image/jpeg"),
+            format!("aa/aa/aa/{id}"),
+            content.len() as u64,
+            UserId::new(),
+        )
+        .into_media(MediaId::new(id))
+    }
+
+    #[tokio::test]
+    async fn test_missing_copies_are_restored_on_both_sides() {
+        let primary = Arc::new(InMemoryStorage::new());
+        let replica = Arc::new(InMemoryStorage::new());
+        let first = media(1, b"first");
+        let second = media(2, b"second");
+        primary.store(&first.content_hash, &mut &b"first"[..]).await.unwrap();
+        replica.store(&second.content_hash, &mut &b"second"[..]).await.unwrap();
+        let repo = Arc::new(
+            InMemoryMediaRepository::new()
+                .with_media(first.clone())
+                .with_media(second.clone())
+                .with_media(media(3, b"lost")),
+        );
+        let storage = Arc::new(MirroredStorage::new(primary.clone(), replica.clone()));
+
+        let repairs = RepairReplicasUseCase::new(repo.clone(), storage).execute().await.unwrap();
+        assert_eq!(repairs.checked, 3);
+        assert_eq!((repairs.copied_to_replica, repairs.copied_to_primary), (1, 1));
+        assert_eq!(repairs.lost, vec![generate_content_hash(b"lost").unwrap()]);
+        assert!(replica.exists(&first.content_hash).await.unwrap());
+        assert!(primary.exists(&second.content_hash).await.unwrap());
+
+        // Without a mirror there is nothing to repair
+        let unmirrored = RepairReplicasUseCase::new(repo, primary).execute().await.unwrap();
+        assert_eq!(unmirrored, ReplicaRepairs::default());
+    }
+}
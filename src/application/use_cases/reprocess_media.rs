@@ -0,0 +1,126 @@
+use std::sync::Arc;
+
+use super::{ensure_owner, repository_error};
+use crate::{
+    domain::{
+        entities::{Media, MediaId, UserId},
+        repositories::MediaRepository,
+        value_objects::ProcessingStatus,
+    },
+    presentation::middleware::error::AppError,
+};
+
+/// Use case for queueing a stored original to be processed again
+///
+/// The media is reset to `Pending`, after which the caller processes it as it
+/// would a new upload, regenerating every variant with the current settings.
+/// Media that is still waiting for or undergoing processing is left alone.
+pub struct ReprocessMediaUseCase<R>
+where
+    R: MediaRepository + ?Sized,
+{
+    repository: Arc<R>,
+}
+
+impl<R> ReprocessMediaUseCase<R>
+where
+    R: MediaRepository + ?Sized,
+{
+    /// Create a new reprocess media use case
+    pub fn new(repository: Arc<R>) -> Self {
+        Self { repository }
+    }
+
+    /// Reset the media to `Pending` so it can be processed again
+    ///
+    /// # Errors
+    /// * `NotFound` - Media with the given ID doesn't exist
+    /// * `Authorization` - The media was uploaded by another user
+    /// * `Conflict` - The media is already pending or being processed
+    /// * `Database` - Reading or resetting the media failed
+    pub async fn execute(
+        &self,
+        media_id: MediaId,
+        requester: Option<UserId>,
+    ) -> Result<Media, AppError> {
+        tracing::info!("Queueing media {} for reprocessing", media_id);
+
+        let mut media = self
+            .repository
+            .find_by_id(media_id)
+            .await
+            .map_err(repository_error("Failed to query media"))?
+            .ok_or_else(|| AppError::NotFound { resource: format!("Media with ID {media_id}") })?;
+        ensure_owner(&media, requester)?;
+
+        if matches!(
+            media.processing_status,
+            ProcessingStatus::Pending | ProcessingStatus::Processing
+        ) {
+            return Err(AppError::Conflict {
+                message: format!(
+                    "Media {media_id} is already waiting for or undergoing processing"
+                ),
+            });
+        }
+
+        media.set_processing_status(ProcessingStatus::Pending);
+        self.repository.update(&media).await.map_err(repository_error(format!(
+            "Failed to reset media {media_id} for reprocessing"
+        )))?;
+
+        Ok(media)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        domain::{
+            entities::UnsavedMedia,
+            value_objects::{ContentHash, MediaType},
+        },
+        test_utils::mocks::InMemoryMediaRepository,
+    };
+
+    fn media(id: i64, status: ProcessingStatus) -> Media {
+        let mut media = UnsavedMedia::new(
+            ContentHash::new(&format!("{id:064}")).unwrap(),
+            "cover.jpg".to_string(),
+            MediaType::new("image/jpeg"),
+            format!("aa/aa/aa/{id}"),
+            2048,
+            UserId::new(),
+        )
+        .into_media(MediaId::new(id));
+        media.set_processing_status(status);
+        media
+    }
+
+    #[tokio::test]
+    async fn test_processed_media_is_reset_once() {
+        let complete = media(5, ProcessingStatus::Complete);
+        let owner = complete.uploaded_by;
+        let processing = Media { uploaded_by: owner, ..media(6, ProcessingStatus::Processing) };
+        let repo =
+            Arc::new(InMemoryMediaRepository::new().with_media(complete).with_media(processing));
+        let use_case = ReprocessMediaUseCase::new(repo.clone());
+
+        let reset = use_case.execute(MediaId::new(5), Some(owner)).await.unwrap();
+        assert_eq!(reset.processing_status, ProcessingStatus::Pending);
+        let stored = repo.find_by_id(MediaId::new(5)).await.unwrap().unwrap();
+        assert_eq!(stored.processing_status, ProcessingStatus::Pending);
+
+        // Until processing picks it up again, a second request is turned away
+        let again = use_case.execute(MediaId::new(5), Some(owner)).await.unwrap_err();
+        assert!(matches!(again, AppError::Conflict { .. }));
+        let busy = use_case.execute(MediaId::new(6), Some(owner)).await.unwrap_err();
+        assert!(matches!(busy, AppError::Conflict { .. }));
+
+        let other_user = use_case.execute(MediaId::new(5), Some(UserId::new())).await.unwrap_err();
+        assert!(matches!(other_user, AppError::Authorization { .. }));
+        let missing = use_case.execute(MediaId::new(7), None).await.unwrap_err();
+        assert!(matches!(missing, AppError::NotFound { .. }));
+    }
+}
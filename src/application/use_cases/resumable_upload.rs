@@ -0,0 +1,563 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::{
+    application::{dto::UploadMediaResponse, events::EventBus, events::EventPublisher},
+    domain::{
+        entities::{ResumableUpload, UploadId, UserId},
+        repositories::{MediaRepository, ResumableUploadRepository},
+        services::{Clock, SystemClock},
+        value_objects::{FormatPolicy, StorageQuota, UuidVersion},
+    },
+    infrastructure::{
+        business_metrics::{BusinessMetrics, UploadFlow},
+        storage::{FileStorage, StorageError, UploadStaging},
+    },
+    presentation::middleware::error::AppError,
+};
+
+use super::{ensure_within_quota, AllowedTypes, UploadLocks, UploadMediaUseCase};
+
+/// Most expired uploads discarded per sweep; the rest wait for the next one
+const EXPIRED_UPLOAD_BATCH: u32 = 500;
+
+/// Outcome of appending a chunk to a resumable upload
+#[derive(Debug, Clone)]
+pub struct AppendChunkResult {
+    /// Upload state after the chunk was written
+    pub upload: ResumableUpload,
+    /// Media created from the upload, once the final chunk has arrived
+    pub completed: Option<UploadMediaResponse>,
+}
+
+/// Use case for resumable (tus) uploads delivered in several chunks
+pub struct ResumableUploadUseCase<U, R, S>
+where
+    U: ResumableUploadRepository + ?Sized,
+    R: MediaRepository + ?Sized,
+    S: FileStorage + ?Sized,
+{
+    uploads: Arc<U>,
+    staging: UploadStaging,
+    repository: Arc<R>,
+    storage: Arc<S>,
+    max_file_size: u64,
+    expiry: Duration,
+    upload_locks: UploadLocks,
+    quota: StorageQuota,
+    clock: Arc<dyn Clock>,
+    uuid_version: UuidVersion,
+    allowed_types: AllowedTypes,
+    format_policy: FormatPolicy,
+    metrics: BusinessMetrics,
+    events: Arc<dyn EventPublisher>,
+}
+
+impl<U, R, S> ResumableUploadUseCase<U, R, S>
+where
+    U: ResumableUploadRepository + ?Sized,
+    R: MediaRepository + ?Sized,
+    S: FileStorage + ?Sized,
+{
+    /// Create a new resumable upload use case
+    pub fn new(
+        uploads: Arc<U>,
+        staging: UploadStaging,
+        repository: Arc<R>,
+        storage: Arc<S>,
+        max_file_size: u64,
+        expiry: Duration,
+    ) -> Self {
+        Self {
+            uploads,
+            staging,
+            repository,
+            storage,
+            max_file_size,
+            expiry,
+            upload_locks: UploadLocks::new(),
+            quota: StorageQuota::unlimited(),
+            clock: Arc::new(SystemClock),
+            uuid_version: UuidVersion::default(),
+            allowed_types: AllowedTypes::default(),
+            format_policy: FormatPolicy::unrestricted(),
+            metrics: BusinessMetrics::disabled(),
+            events: Arc::new(EventBus::new()),
+        }
+    }
+
+    /// Share an upload lock registry with other upload paths
+    #[must_use]
+    pub fn with_upload_locks(mut self, upload_locks: UploadLocks) -> Self {
+        self.upload_locks = upload_locks;
+        self
+    }
+
+    /// Limit how much each user may store
+    #[must_use]
+    pub fn with_quota(mut self, quota: StorageQuota) -> Self {
+        self.quota = quota;
+        self
+    }
+
+    /// Read the time from `clock` when starting and expiring uploads
+    #[must_use]
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Generate upload IDs from UUIDs of the given version
+    #[must_use]
+    pub fn with_uuid_version(mut self, uuid_version: UuidVersion) -> Self {
+        self.uuid_version = uuid_version;
+        self
+    }
+
+    /// Only accept content of these types; an empty list accepts any type
+    #[must_use]
+    pub fn with_allowed_types(mut self, allowed_types: impl Into<AllowedTypes>) -> Self {
+        self.allowed_types = allowed_types.into();
+        self
+    }
+
+    /// Limit the formats each tenant accepts, converting images into an accepted format
+    ///
+    /// The tenant is the one recorded on the upload when it was created.
+    #[must_use]
+    pub fn with_format_policy(mut self, format_policy: FormatPolicy) -> Self {
+        self.format_policy = format_policy;
+        self
+    }
+
+    /// Count created uploads as started and record how they end
+    #[must_use]
+    pub fn with_metrics(mut self, metrics: BusinessMetrics) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    /// Announce finished uploads to `events`
+    #[must_use]
+    pub fn with_events(mut self, events: Arc<dyn EventPublisher>) -> Self {
+        self.events = events;
+        self
+    }
+
+    /// Start a new upload of `upload_length` bytes
+    ///
+    /// # Errors
+    /// Returns `PayloadTooLarge` if the declared length exceeds the maximum file size,
+    /// and `QuotaExceeded` if it would take the user past their storage quota
+    pub async fn create(
+        &self,
+        user_id: UserId,
+        upload_length: u64,
+        filename: Option<String>,
+        content_type: Option<String>,
+    ) -> Result<ResumableUpload, AppError> {
+        self.create_for_tenant(None, user_id, upload_length, filename, content_type).await
+    }
+
+    /// Start a new upload on behalf of a tenant
+    ///
+    /// The tenant is recorded on the upload, so its format policy applies once the
+    /// last chunk has arrived.
+    ///
+    /// # Errors
+    /// As for [`Self::create`]
+    pub async fn create_for_tenant(
+        &self,
+        tenant: Option<&str>,
+        user_id: UserId,
+        upload_length: u64,
+        filename: Option<String>,
+        content_type: Option<String>,
+    ) -> Result<ResumableUpload, AppError> {
+        self.metrics.upload_started(UploadFlow::Resumable);
+        let result = self.start(tenant, user_id, upload_length, filename, content_type).await;
+        self.metrics.record_failure(UploadFlow::Resumable, &result);
+        result
+    }
+
+    /// Validate and stage a new upload
+    async fn start(
+        &self,
+        tenant: Option<&str>,
+        user_id: UserId,
+        upload_length: u64,
+        filename: Option<String>,
+        content_type: Option<String>,
+    ) -> Result<ResumableUpload, AppError> {
+        if upload_length > self.max_file_size {
+            return Err(AppError::PayloadTooLarge {
+                message: format!(
+                    "Upload length {upload_length} bytes exceeds maximum allowed size of {} bytes",
+                    self.max_file_size
+                ),
+            });
+        }
+
+        ensure_within_quota(&*self.repository, self.quota, user_id, upload_length).await?;
+
+        let upload = ResumableUpload {
+            tenant: tenant.map(String::from),
+            ..ResumableUpload::new(
+                UploadId::generate(self.uuid_version),
+                user_id,
+                upload_length,
+                filename,
+                content_type,
+                self.clock.now(),
+                self.expiry,
+            )
+        };
+
+        self.staging.create(upload.id).await.map_err(|e| AppError::Storage {
+            message: format!("Failed to create upload staging file: {e}"),
+        })?;
+
+        if let Err(e) = self.uploads.create(&upload).await {
+            let _ = self.staging.remove(upload.id).await;
+            return Err(AppError::Internal { message: format!("Failed to create upload: {e}") });
+        }
+
+        tracing::info!("Created resumable upload {} ({} bytes)", upload.id, upload_length);
+        Ok(upload)
+    }
+
+    /// Get the current state of an upload
+    ///
+    /// # Errors
+    /// Returns `NotFound` if the upload does not exist or has expired
+    pub async fn status(&self, id: UploadId) -> Result<ResumableUpload, AppError> {
+        self.find_active(id).await
+    }
+
+    /// Append a chunk at `offset`, finalizing the upload once all bytes have arrived
+    ///
+    /// Sending an empty chunk at the final offset retries finalization of an upload
+    /// whose last attempt failed.
+    ///
+    /// # Errors
+    /// Returns `Conflict` if `offset` does not match the current upload offset and
+    /// `PayloadTooLarge` if the chunk would exceed the declared upload length
+    pub async fn append(
+        &self,
+        id: UploadId,
+        offset: u64,
+        chunk: &[u8],
+    ) -> Result<AppendChunkResult, AppError> {
+        let _upload_guard = self.upload_locks.acquire_upload(id).await;
+
+        let mut upload = self.find_active(id).await?;
+
+        if offset != upload.upload_offset {
+            return Err(AppError::Conflict {
+                message: format!(
+                    "Upload-Offset {offset} does not match current offset {}",
+                    upload.upload_offset
+                ),
+            });
+        }
+
+        if chunk.len() as u64 > upload.remaining() {
+            return Err(AppError::PayloadTooLarge {
+                message: format!(
+                    "Chunk of {} bytes exceeds the {} bytes remaining in the upload",
+                    chunk.len(),
+                    upload.remaining()
+                ),
+            });
+        }
+
+        if !chunk.is_empty() {
+            let new_offset =
+                self.staging.write_chunk(id, offset, chunk).await.map_err(|e| match e {
+                    StorageError::FileNotFound { .. } => {
+                        AppError::NotFound { resource: format!("Upload {id}") }
+                    }
+                    _ => AppError::Storage { message: format!("Failed to write chunk: {e}") },
+                })?;
+
+            let updated =
+                self.uploads.update_offset(id, offset, new_offset).await.map_err(|e| {
+                    AppError::Internal { message: format!("Failed to update upload offset: {e}") }
+                })?;
+            if !updated {
+                return Err(AppError::Conflict {
+                    message: format!("Upload {id} was modified concurrently"),
+                });
+            }
+
+            upload.upload_offset = new_offset;
+        }
+
+        let completed =
+            if upload.is_complete() { Some(self.finalize(&upload).await?) } else { None };
+
+        Ok(AppendChunkResult { upload, completed })
+    }
+
+    /// Discard expired uploads, returning how many were removed
+    ///
+    /// Clients that disconnect for good never come back to trigger the expiry
+    /// check, so this runs periodically. It also removes staging files left
+    /// without an upload, such as after a restart lost in-memory upload state.
+    ///
+    /// # Errors
+    /// Returns `Internal` if expired uploads cannot be listed and `Storage` if
+    /// the staging area cannot be read
+    pub async fn remove_expired(&self) -> Result<usize, AppError> {
+        let now = self.clock.now();
+
+        let expired = self.uploads.find_expired(now, EXPIRED_UPLOAD_BATCH).await.map_err(|e| {
+            AppError::Internal { message: format!("Failed to query expired uploads: {e}") }
+        })?;
+        for &id in &expired {
+            let _upload_guard = self.upload_locks.acquire_upload(id).await;
+            self.discard(id).await;
+        }
+
+        // A staging file is only written while its upload is active, so one
+        // untouched for the whole expiry window belongs to an expired upload
+        let stale = self
+            .staging
+            .staged_before(now.checked_sub(self.expiry).unwrap_or(std::time::UNIX_EPOCH))
+            .await
+            .map_err(|e| AppError::Storage {
+                message: format!("Failed to list staged uploads: {e}"),
+            })?;
+        let mut orphaned = 0;
+        for id in stale {
+            let _upload_guard = self.upload_locks.acquire_upload(id).await;
+            if matches!(self.uploads.find_by_id(id).await, Ok(None))
+                && matches!(self.staging.remove(id).await, Ok(true))
+            {
+                orphaned += 1;
+            }
+        }
+
+        let removed = expired.len() + orphaned;
+        if removed > 0 {
+            tracing::info!("Removed {} expired resumable uploads", removed);
+        }
+        Ok(removed)
+    }
+
+    /// Hand the fully staged upload to the regular upload pipeline
+    async fn finalize(&self, upload: &ResumableUpload) -> Result<UploadMediaResponse, AppError> {
+        let file = self.staging.open(upload.id).await.map_err(|e| AppError::Storage {
+            message: format!("Failed to open staged upload: {e}"),
+        })?;
+
+        let filename = upload.filename.clone().unwrap_or_else(|| format!("upload_{}", upload.id));
+        let upload_use_case = UploadMediaUseCase::new(
+            self.repository.clone(),
+            self.storage.clone(),
+            self.max_file_size,
+        )
+        .with_upload_locks(self.upload_locks.clone())
+        .with_allowed_types(self.allowed_types.clone())
+        .with_format_policy(self.format_policy.clone())
+        .with_metrics(self.metrics, UploadFlow::Resumable)
+        .with_events(self.events.clone());
+
+        let result = Box::pin(upload_use_case.execute_for_tenant(
+            upload.tenant.as_deref(),
+            file,
+            filename,
+            upload.user_id,
+            upload.content_type.clone(),
+        ))
+        .await;
+
+        // Server-side failures keep the staged data so the client can retry finalization;
+        // anything else is final and the upload is discarded
+        if !matches!(
+            &result,
+            Err(AppError::Internal { .. } | AppError::Storage { .. } | AppError::Database { .. })
+        ) {
+            self.discard(upload.id).await;
+        }
+
+        let response = result?;
+        tracing::info!("Resumable upload {} completed as media {}", upload.id, response.media_id);
+        Ok(response)
+    }
+
+    /// Find an upload that can still be resumed, discarding it if it has expired
+    async fn find_active(&self, id: UploadId) -> Result<ResumableUpload, AppError> {
+        let upload = self
+            .uploads
+            .find_by_id(id)
+            .await
+            .map_err(|e| AppError::Internal { message: format!("Failed to query upload: {e}") })?
+            .ok_or_else(|| AppError::NotFound { resource: format!("Upload {id}") })?;
+
+        if upload.is_expired_at(self.clock.now()) {
+            tracing::info!("Resumable upload {} has expired", id);
+            self.discard(id).await;
+            return Err(AppError::NotFound { resource: format!("Upload {id}") });
+        }
+
+        Ok(upload)
+    }
+
+    async fn discard(&self, id: UploadId) {
+        if let Err(e) = self.staging.remove(id).await {
+            tracing::warn!("Failed to remove staged upload {}: {}", id, e);
+        }
+        if let Err(e) = self.uploads.delete(id).await {
+            tracing::warn!("Failed to delete upload {}: {}", id, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    use crate::{
+        infrastructure::{
+            persistence::InMemoryResumableUploadRepository, storage::FilesystemStorage,
+        },
+        test_utils::mocks::{InMemoryMediaRepository, ManualClock},
+    };
+
+    type TestUseCase = ResumableUploadUseCase<
+        InMemoryResumableUploadRepository,
+        InMemoryMediaRepository,
+        FilesystemStorage,
+    >;
+
+    fn create_use_case(temp_dir: &TempDir, expiry: Duration) -> TestUseCase {
+        ResumableUploadUseCase::new(
+            Arc::new(InMemoryResumableUploadRepository::new()),
+            UploadStaging::new(temp_dir.path().join("staging")),
+            Arc::new(InMemoryMediaRepository::new()),
+            Arc::new(FilesystemStorage::new(temp_dir.path().join("media"))),
+            1024,
+            expiry,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_chunked_upload_completes() {
+        let temp_dir = TempDir::new().unwrap();
+        let use_case = create_use_case(&temp_dir, Duration::from_hours(1));
+
+        let upload =
+            use_case.create(UserId::new(), 11, Some("hello.txt".to_string()), None).await.unwrap();
+
+        let first = use_case.append(upload.id, 0, b"hello ").await.unwrap();
+        assert_eq!(first.upload.upload_offset, 6);
+        assert!(first.completed.is_none());
+        assert_eq!(use_case.status(upload.id).await.unwrap().upload_offset, 6);
+
+        let last = use_case.append(upload.id, 6, b"world").await.unwrap();
+        let response = last.completed.unwrap();
+        assert_eq!(
+            response.content_hash,
+            "b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9"
+        );
+
+        // Completed uploads are discarded
+        assert!(matches!(use_case.status(upload.id).await, Err(AppError::NotFound { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_create_rejects_oversized_upload() {
+        let temp_dir = TempDir::new().unwrap();
+        let use_case = create_use_case(&temp_dir, Duration::from_hours(1));
+
+        let result = use_case.create(UserId::new(), 4096, None, None).await;
+        assert!(matches!(result, Err(AppError::PayloadTooLarge { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_append_rejects_offset_mismatch() {
+        let temp_dir = TempDir::new().unwrap();
+        let use_case = create_use_case(&temp_dir, Duration::from_hours(1));
+        let upload = use_case.create(UserId::new(), 10, None, None).await.unwrap();
+
+        use_case.append(upload.id, 0, b"abc").await.unwrap();
+
+        let result = use_case.append(upload.id, 0, b"abc").await;
+        assert!(matches!(result, Err(AppError::Conflict { .. })));
+        assert_eq!(use_case.status(upload.id).await.unwrap().upload_offset, 3);
+    }
+
+    #[tokio::test]
+    async fn test_append_rejects_chunk_past_declared_length() {
+        let temp_dir = TempDir::new().unwrap();
+        let use_case = create_use_case(&temp_dir, Duration::from_hours(1));
+        let upload = use_case.create(UserId::new(), 4, None, None).await.unwrap();
+
+        let result = use_case.append(upload.id, 0, b"too long").await;
+        assert!(matches!(result, Err(AppError::PayloadTooLarge { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_unknown_and_expired_uploads_are_not_found() {
+        let temp_dir = TempDir::new().unwrap();
+        let use_case = create_use_case(&temp_dir, Duration::ZERO);
+
+        assert!(matches!(use_case.status(UploadId::new()).await, Err(AppError::NotFound { .. })));
+
+        let upload = use_case.create(UserId::new(), 10, None, None).await.unwrap();
+        let result = use_case.append(upload.id, 0, b"abc").await;
+        assert!(matches!(result, Err(AppError::NotFound { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_upload_expires_when_clock_passes_expiry() {
+        let temp_dir = TempDir::new().unwrap();
+        let clock = ManualClock::default();
+        let use_case =
+            create_use_case(&temp_dir, Duration::from_hours(1)).with_clock(Arc::new(clock.clone()));
+        let upload = use_case.create(UserId::new(), 10, None, None).await.unwrap();
+
+        clock.advance(Duration::from_mins(59));
+        use_case.append(upload.id, 0, b"abc").await.unwrap();
+
+        clock.advance(Duration::from_mins(1));
+        assert!(matches!(use_case.status(upload.id).await, Err(AppError::NotFound { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_upload_ids_use_configured_uuid_version() {
+        let temp_dir = TempDir::new().unwrap();
+        let use_case = create_use_case(&temp_dir, Duration::from_hours(1));
+        let upload = use_case.create(UserId::new(), 10, None, None).await.unwrap();
+        assert_eq!(upload.id.as_uuid().get_version_num(), 7);
+
+        let use_case = use_case.with_uuid_version(UuidVersion::V4);
+        let upload = use_case.create(UserId::new(), 10, None, None).await.unwrap();
+        assert_eq!(upload.id.as_uuid().get_version_num(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_remove_expired_discards_abandoned_and_orphaned_uploads() {
+        let temp_dir = TempDir::new().unwrap();
+        let clock = ManualClock::default();
+        let use_case =
+            create_use_case(&temp_dir, Duration::from_hours(1)).with_clock(Arc::new(clock.clone()));
+        let abandoned = use_case.create(UserId::new(), 10, None, None).await.unwrap();
+        use_case.append(abandoned.id, 0, b"abc").await.unwrap();
+        let orphan = UploadId::new();
+        use_case.staging.create(orphan).await.unwrap();
+
+        assert_eq!(use_case.remove_expired().await.unwrap(), 0);
+
+        clock.advance(Duration::from_hours(2));
+        let active = use_case.create(UserId::new(), 10, None, None).await.unwrap();
+
+        assert_eq!(use_case.remove_expired().await.unwrap(), 2);
+        assert!(use_case.uploads.find_by_id(abandoned.id).await.unwrap().is_none());
+        assert!(!use_case.staging.remove(abandoned.id).await.unwrap());
+        assert!(!use_case.staging.remove(orphan).await.unwrap());
+        assert_eq!(use_case.status(active.id).await.unwrap().upload_offset, 0);
+    }
+}
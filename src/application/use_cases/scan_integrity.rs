@@ -0,0 +1,269 @@
+use std::sync::{Arc, Mutex, PoisonError};
+
+use super::repository_error;
+use crate::{
+    domain::{
+        entities::{Media, MediaId},
+        repositories::MediaRepository,
+        services::{Clock, SystemClock},
+        value_objects::ContentHash,
+    },
+    infrastructure::storage::{FileStorage, StorageError, VerifyingReader},
+    presentation::middleware::error::AppError,
+};
+
+/// Media read from the repository at a time
+const PAGE_SIZE: u32 = 100;
+
+/// Media checked by one integrity scan
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IntegrityScan {
+    /// Number of media whose content was read back
+    pub checked: u64,
+    /// Media whose content is missing or damaged, including media flagged before
+    pub corrupted: Vec<MediaId>,
+    /// Flagged media whose content was found intact again, e.g. after a restore
+    pub restored: Vec<MediaId>,
+}
+
+/// Use case for finding media whose stored content was damaged at rest
+///
+/// Reads back the original and every variant of each media and re-hashes them.
+/// Content that is missing or no longer matches its content hash flags the
+/// media with `corrupted_at`; the flag is cleared once the content is intact
+/// again. Each run checks up to `batch_size` media, continuing in media ID order
+/// where the previous run stopped and starting over after the last media, so
+/// successive runs cover the whole library. A `batch_size` of 0 checks every
+/// media on each run.
+pub struct ScanIntegrityUseCase<R: ?Sized, S: ?Sized> {
+    repository: Arc<R>,
+    storage: Arc<S>,
+    batch_size: u32,
+    clock: Arc<dyn Clock>,
+    resume_after: Mutex<Option<MediaId>>,
+}
+
+impl<R: ?Sized, S: ?Sized> ScanIntegrityUseCase<R, S>
+where
+    R: MediaRepository,
+    S: FileStorage,
+{
+    /// Create a scan checking `batch_size` media per run, or all of them for 0
+    pub fn new(repository: Arc<R>, storage: Arc<S>, batch_size: u32) -> Self {
+        Self {
+            repository,
+            storage,
+            batch_size,
+            clock: Arc::new(SystemClock),
+            resume_after: Mutex::new(None),
+        }
+    }
+
+    /// Read the time media is flagged at from `clock`; the default is the system clock
+    #[must_use]
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Check the next batch of media
+    ///
+    /// Each media checked counts in `media_integrity_checks_total`, labelled by
+    /// `outcome`. A run that fails is repeated from the same media by the next.
+    ///
+    /// # Errors
+    /// * `Database` - Reading media or flagging them failed
+    /// * `Internal` - Storage could not be read for another reason than missing content
+    pub async fn execute(&self) -> Result<IntegrityScan, AppError> {
+        let mut after = *self.resume_after.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut scan = IntegrityScan::default();
+        let mut examined = 0;
+        loop {
+            let limit = match self.batch_size {
+                0 => PAGE_SIZE,
+                size => (size - examined).min(PAGE_SIZE),
+            };
+            let page = self
+                .repository
+                .find_after(after, limit)
+                .await
+                .map_err(repository_error("Failed to query media"))?;
+            for media in &page {
+                self.check(media, &mut scan).await?;
+            }
+            examined += page.len() as u32;
+            after = page.last().map(|media| media.id);
+
+            if page.len() < limit as usize {
+                // Past the last media; the next run starts over
+                after = None;
+                break;
+            }
+            if examined == self.batch_size {
+                break;
+            }
+        }
+        *self.resume_after.lock().unwrap_or_else(PoisonError::into_inner) = after;
+
+        if !scan.corrupted.is_empty() {
+            tracing::warn!(
+                "Integrity scan checked {} media, {} corrupted",
+                scan.checked,
+                scan.corrupted.len()
+            );
+        }
+        Ok(scan)
+    }
+
+    /// Verify the content of one media, flagging or unflagging it on a change
+    async fn check(&self, media: &Media, scan: &mut IntegrityScan) -> Result<(), AppError> {
+        if media.is_awaiting_upload() {
+            return Ok(());
+        }
+
+        let hashes = std::iter::once(&media.content_hash)
+            .chain(media.variants.iter().map(|variant| &variant.content_hash));
+        let mut damage = None;
+        for hash in hashes {
+            if let Some(error) = self.verify(hash).await? {
+                damage = Some(error);
+                break;
+            }
+        }
+
+        scan.checked += 1;
+        let outcome = if damage.is_some() { "corrupted" } else { "intact" };
+        metrics::counter!("media_integrity_checks_total", "outcome" => outcome).increment(1);
+        match (damage, media.is_corrupted()) {
+            (Some(error), was_corrupted) => {
+                scan.corrupted.push(media.id);
+                if !was_corrupted {
+                    tracing::error!("Media {} is corrupted: {}", media.id, error);
+                    self.flag(media.id, true).await?;
+                }
+            }
+            (None, true) => {
+                tracing::info!("Media {} is intact again", media.id);
+                scan.restored.push(media.id);
+                self.flag(media.id, false).await?;
+            }
+            (None, false) => {}
+        }
+        Ok(())
+    }
+
+    /// Read `hash` back in full, returning why its content is damaged if it is
+    async fn verify(&self, hash: &ContentHash) -> Result<Option<StorageError>, AppError> {
+        let read = async {
+            let reader = self.storage.retrieve(hash).await?;
+            let mut reader = VerifyingReader::new(reader, hash.clone());
+            tokio::io::copy(&mut reader, &mut tokio::io::sink()).await?;
+            Ok::<_, StorageError>(())
+        };
+
+        match read.await {
+            Ok(()) => Ok(None),
+            Err(e @ (StorageError::FileNotFound { .. } | StorageError::HashMismatch { .. })) => {
+                Ok(Some(e))
+            }
+            Err(e) => Err(AppError::Internal {
+                message: format!("Failed to read content {hash} for an integrity check: {e}"),
+            }),
+        }
+    }
+
+    async fn flag(&self, media_id: MediaId, corrupted: bool) -> Result<(), AppError> {
+        let corrupted_at = corrupted.then(|| self.clock.now());
+        self.repository
+            .set_corrupted_at(media_id, corrupted_at)
+            .await
+            .map(|_| ())
+            .map_err(repository_error(format!("Failed to flag media {media_id}")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        domain::{
+            entities::{MediaVariant, UnsavedMedia, UserId},
+            value_objects::MediaType,
+        },
+        infrastructure::storage::{generate_content_hash, InMemoryStorage},
+        test_utils::mocks::InMemoryMediaRepository,
+    };
+
+    async fn stored_media(storage: &InMemoryStorage, id: i64, content: &[u8]) -> Media {
+        let hash = generate_content_hash(content).unwrap();
+        storage.store(&hash, &mut &content[..]).await.unwrap();
+        UnsavedMedia::new(
+            hash,
+            format!("{id}.jpg"),
+            MediaType::new("image/jpeg"),
+            format!("aa/aa/aa/{id}"),
+            content.len() as u64,
+            UserId::new(),
+        )
+        .into_media(MediaId::new(id))
+    }
+
+    #[tokio::test]
+    async fn test_damaged_and_missing_content_is_flagged_until_restored() {
+        let storage = Arc::new(InMemoryStorage::new());
+        let intact = stored_media(&storage, 1, b"intact").await;
+        let damaged = stored_media(&storage, 2, b"original").await;
+        let mut variant_missing = stored_media(&storage, 3, b"has a variant").await;
+        variant_missing.variants.push(MediaVariant {
+            name: "webp".to_string(),
+            content_hash: generate_content_hash(b"never stored").unwrap(),
+            media_type: MediaType::new("image/webp"),
+            file_size: 12,
+        });
+        // Bit rot: the stored bytes no longer hash to the media's content hash
+        let rotted = InMemoryStorage::new();
+        rotted.store(&damaged.content_hash, &mut &b"0riginal"[..]).await.unwrap();
+        let repo = Arc::new(
+            InMemoryMediaRepository::new()
+                .with_media(intact)
+                .with_media(damaged.clone())
+                .with_media(variant_missing),
+        );
+
+        let scan = ScanIntegrityUseCase::new(repo.clone(), Arc::new(rotted), 0);
+        let result = scan.execute().await.unwrap();
+        assert_eq!(result.checked, 3);
+        assert_eq!(result.corrupted, vec![MediaId::new(1), MediaId::new(2), MediaId::new(3)]);
+
+        let scan = ScanIntegrityUseCase::new(repo.clone(), storage, 0);
+        let result = scan.execute().await.unwrap();
+        assert_eq!(result.corrupted, vec![MediaId::new(3)]);
+        assert_eq!(result.restored, vec![MediaId::new(1), MediaId::new(2)]);
+        let flagged = repo.find_by_id(MediaId::new(3)).await.unwrap().unwrap();
+        assert!(flagged.is_corrupted());
+        assert!(!repo.find_by_id(MediaId::new(2)).await.unwrap().unwrap().is_corrupted());
+    }
+
+    #[tokio::test]
+    async fn test_batches_resume_where_the_last_run_stopped() {
+        let storage = Arc::new(InMemoryStorage::new());
+        let mut repo = InMemoryMediaRepository::new();
+        for id in 1..=5 {
+            repo =
+                repo.with_media(stored_media(&storage, id, format!("media {id}").as_bytes()).await);
+        }
+        let scan = ScanIntegrityUseCase::new(Arc::new(repo), storage, 2);
+
+        let checked: Vec<u64> = [
+            scan.execute().await.unwrap(),
+            scan.execute().await.unwrap(),
+            scan.execute().await.unwrap(),
+            scan.execute().await.unwrap(),
+        ]
+        .iter()
+        .map(|result| result.checked)
+        .collect();
+        // The third run reaches the end of the library, so the fourth starts over
+        assert_eq!(checked, vec![2, 2, 1, 2]);
+    }
+}
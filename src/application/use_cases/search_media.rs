@@ -0,0 +1,188 @@
+use std::sync::Arc;
+
+use super::{repository_error, tag_filter};
+use crate::{
+    application::dto::{MediaDto, PaginatedMediaResponse, PaginationInfo, SearchMediaQuery},
+    domain::{entities::UserId, repositories::MediaRepository, value_objects::MediaSearch},
+    infrastructure::persistence::pagination::{self, PageRequest},
+    presentation::middleware::error::AppError,
+};
+
+/// Use case for searching a user's media by filename, type, size, upload date and status
+pub struct SearchMediaUseCase<R>
+where
+    R: MediaRepository + ?Sized,
+{
+    repository: Arc<R>,
+}
+
+impl<R> SearchMediaUseCase<R>
+where
+    R: MediaRepository + ?Sized,
+{
+    /// Create a new search media use case
+    pub fn new(repository: Arc<R>) -> Self {
+        Self { repository }
+    }
+
+    /// Return one page of the user's media matching every filter in `query`
+    ///
+    /// # Errors
+    /// * `BadRequest` - The cursor is malformed, the type family or a tag is
+    ///   invalid, or a size or date range is empty
+    pub async fn execute(
+        &self,
+        query: SearchMediaQuery,
+        user_id: UserId,
+    ) -> Result<PaginatedMediaResponse, AppError> {
+        tracing::info!("Searching media for user: {} with query: {:?}", user_id, query);
+
+        let page_request = PageRequest::new(
+            query.cursor.as_deref(),
+            query.limit.unwrap_or(pagination::DEFAULT_PAGE_SIZE),
+        )?;
+        let search = media_search(&query)?;
+
+        let (media_list, next_cursor, has_more) = self
+            .repository
+            .search_by_user(user_id, &search, query.cursor.clone(), page_request.limit)
+            .await
+            .map_err(repository_error("Failed to search media"))?;
+
+        tracing::info!("Search matched {} media files for user (paginated)", media_list.len());
+
+        let data: Vec<MediaDto> = media_list.into_iter().map(MediaDto::from).collect();
+        let pagination = PaginationInfo {
+            next_cursor,
+            prev_cursor: None,
+            page_size: data.len() as u32,
+            has_next: has_more,
+            has_prev: query.cursor.is_some(),
+        };
+
+        Ok(PaginatedMediaResponse { data, pagination })
+    }
+}
+
+/// Validate the query's filters and turn them into search criteria
+fn media_search(query: &SearchMediaQuery) -> Result<MediaSearch, AppError> {
+    let bad_request = |message: &str| AppError::BadRequest { message: message.to_string() };
+
+    let type_family = match query.type_family.as_deref().map(str::trim) {
+        None | Some("") => None,
+        Some(family)
+            if family.len() <= 32
+                && family.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') =>
+        {
+            Some(family.to_ascii_lowercase())
+        }
+        Some(_) => return Err(bad_request("type must be a MIME type family such as 'image'")),
+    };
+    if let (Some(min), Some(max)) = (query.min_size, query.max_size) {
+        if min > max {
+            return Err(bad_request("min_size must not be greater than max_size"));
+        }
+    }
+    if let (Some(since), Some(before)) = (query.uploaded_since, query.uploaded_before) {
+        if since >= before {
+            return Err(bad_request("uploaded_since must be earlier than uploaded_before"));
+        }
+    }
+
+    Ok(MediaSearch {
+        filename: query
+            .filename
+            .as_deref()
+            .map(str::trim)
+            .filter(|f| !f.is_empty())
+            .map(Into::into),
+        type_family,
+        min_size: query.min_size,
+        max_size: query.max_size,
+        uploaded_since: query.uploaded_since.map(Into::into),
+        uploaded_before: query.uploaded_before.map(Into::into),
+        status: query.status.clone(),
+        tags: tag_filter(query.tags.as_deref())?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        domain::{
+            entities::{Media, MediaId, UnsavedMedia},
+            value_objects::{ContentHash, MediaType, ProcessingStatus},
+        },
+        test_utils::mocks::InMemoryMediaRepository,
+    };
+
+    fn media(id: i64, filename: &str, mime_type: &str, size: u64, owner: UserId) -> Media {
+        UnsavedMedia::new(
+            ContentHash::new(&format!("{id:064}")).unwrap(),
+            filename.to_string(),
+            MediaType::new(mime_type),
+            format!("aa/aa/aa/{id}"),
+            size,
+            owner,
+        )
+        .into_media(MediaId::new(id))
+    }
+
+    #[tokio::test]
+    async fn test_search_applies_filters_and_paginates() {
+        let owner = UserId::new();
+        let repo = InMemoryMediaRepository::new()
+            .with_media(media(1, "pancakes.jpg", "image/jpeg", 1000, owner))
+            .with_media(media(2, "Pancake-Stack.png", "image/png", 3000, owner))
+            .with_media(media(3, "pancakes.mp4", "video/mp4", 2000, owner))
+            .with_media(media(4, "waffles.jpg", "image/jpeg", 2000, owner))
+            .with_media(media(5, "pancakes.webp", "image/webp", 2000, UserId::new()))
+            .with_media(media(6, "pancake_batter.jpg", "image/jpeg", 5000, owner));
+        let use_case = SearchMediaUseCase::new(Arc::new(repo));
+
+        let query = SearchMediaQuery {
+            filename: Some("PANCAKE".to_string()),
+            type_family: Some("image".to_string()),
+            max_size: Some(4000),
+            limit: Some(1),
+            ..SearchMediaQuery::default()
+        };
+        let first = use_case.execute(query.clone(), owner).await.unwrap();
+        assert_eq!(first.data.iter().map(|m| m.id.as_i64()).collect::<Vec<_>>(), vec![1]);
+        assert!(first.pagination.has_next);
+
+        let query = SearchMediaQuery { cursor: first.pagination.next_cursor, ..query };
+        let second = use_case.execute(query, owner).await.unwrap();
+        assert_eq!(second.data.iter().map(|m| m.id.as_i64()).collect::<Vec<_>>(), vec![2]);
+        assert!(!second.pagination.has_next);
+        assert!(second.pagination.has_prev);
+
+        let query = SearchMediaQuery {
+            status: Some(ProcessingStatus::Complete),
+            ..SearchMediaQuery::default()
+        };
+        assert!(use_case.execute(query, owner).await.unwrap().data.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_search_rejects_invalid_filters() {
+        let use_case = SearchMediaUseCase::new(Arc::new(InMemoryMediaRepository::new()));
+        let now = chrono::Utc::now();
+
+        let queries = [
+            SearchMediaQuery { type_family: Some("image/%".to_string()), ..Default::default() },
+            SearchMediaQuery { min_size: Some(10), max_size: Some(9), ..Default::default() },
+            SearchMediaQuery {
+                uploaded_since: Some(now),
+                uploaded_before: Some(now),
+                ..Default::default()
+            },
+            SearchMediaQuery { tags: Some("plated,#dessert".to_string()), ..Default::default() },
+        ];
+        for query in queries {
+            let result = use_case.execute(query, UserId::new()).await;
+            assert!(matches!(result, Err(AppError::BadRequest { .. })));
+        }
+    }
+}
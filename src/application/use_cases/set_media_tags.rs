@@ -0,0 +1,162 @@
+use std::sync::Arc;
+
+use super::{ensure_owner, repository_error};
+use crate::{
+    application::{
+        dto::{MediaTagsResponse, SetMediaTagsRequest},
+        events::{DomainEvent, EventBus, EventPublisher},
+    },
+    domain::{
+        entities::{MediaId, UserId},
+        repositories::{MediaChange, MediaRepository},
+        value_objects::MediaTag,
+    },
+    presentation::middleware::error::AppError,
+};
+
+/// Use case for replacing the user-defined tags on a media file
+///
+/// Tags are normalized to lowercase and repeats are dropped, so the stored set
+/// may be shorter than the request.
+pub struct SetMediaTagsUseCase<R>
+where
+    R: MediaRepository + ?Sized,
+{
+    repository: Arc<R>,
+    events: Arc<dyn EventPublisher>,
+}
+
+impl<R> SetMediaTagsUseCase<R>
+where
+    R: MediaRepository + ?Sized,
+{
+    /// Create a new set media tags use case
+    pub fn new(repository: Arc<R>) -> Self {
+        Self { repository, events: Arc::new(EventBus::new()) }
+    }
+
+    /// Announce changed tags to `events`
+    #[must_use]
+    pub fn with_events(mut self, events: Arc<dyn EventPublisher>) -> Self {
+        self.events = events;
+        self
+    }
+
+    /// Replace the media's tags with those in `request`
+    ///
+    /// # Errors
+    /// * `BadRequest` - A tag is invalid or there are too many
+    /// * `NotFound` - Media with the given ID doesn't exist
+    /// * `Authorization` - The media was uploaded by another user
+    pub async fn execute(
+        &self,
+        media_id: MediaId,
+        request: SetMediaTagsRequest,
+        requester: Option<UserId>,
+    ) -> Result<MediaTagsResponse, AppError> {
+        tracing::info!("Setting {} tags on media {}", request.tags.len(), media_id);
+
+        let tags = MediaTag::normalize_all(&request.tags)
+            .map_err(|e| AppError::BadRequest { message: e.to_string() })?;
+
+        let not_found = || AppError::NotFound { resource: format!("Media with ID {media_id}") };
+        let media = self
+            .repository
+            .find_by_id(media_id)
+            .await
+            .map_err(repository_error("Failed to query media"))?
+            .ok_or_else(not_found)?;
+        ensure_owner(&media, requester)?;
+
+        // The media may have been deleted since it was read
+        let changed = DomainEvent::MediaTagsChanged { media_id, uploaded_by: media.uploaded_by };
+        let recorded = self
+            .repository
+            .apply(MediaChange::SetTags(media_id, &tags), std::slice::from_ref(&changed))
+            .await
+            .map_err(repository_error("Failed to set media tags"))?;
+        if !recorded.outcome {
+            return Err(not_found());
+        }
+        if !recorded.events_recorded {
+            self.events.publish(&changed);
+        }
+
+        Ok(MediaTagsResponse { media_id, tags: tags.into_iter().map(String::from).collect() })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        domain::{
+            entities::UnsavedMedia,
+            value_objects::{ContentHash, MediaSort, MediaType},
+        },
+        test_utils::mocks::InMemoryMediaRepository,
+    };
+
+    fn setup(
+        owner: UserId,
+    ) -> (SetMediaTagsUseCase<InMemoryMediaRepository>, Arc<InMemoryMediaRepository>) {
+        let mut repo = InMemoryMediaRepository::new();
+        for id in [5, 6] {
+            let media = UnsavedMedia::new(
+                ContentHash::new(&format!("{id:064}")).unwrap(),
+                "cover.jpg".to_string(),
+                MediaType::new("image/jpeg"),
+                format!("aa/aa/aa/{id}"),
+                2048,
+                owner,
+            )
+            .into_media(MediaId::new(id));
+            repo = repo.with_media(media);
+        }
+        let repo = Arc::new(repo);
+        (SetMediaTagsUseCase::new(repo.clone()), repo)
+    }
+
+    fn tags_request(tags: &[&str]) -> SetMediaTagsRequest {
+        SetMediaTagsRequest { tags: tags.iter().map(ToString::to_string).collect() }
+    }
+
+    #[tokio::test]
+    async fn test_set_tags_replaces_and_filters() {
+        let owner = UserId::new();
+        let (use_case, repo) = setup(owner);
+
+        use_case.execute(MediaId::new(5), tags_request(&["draft"]), Some(owner)).await.unwrap();
+        let response = use_case
+            .execute(MediaId::new(5), tags_request(&["Plated", "dessert", "plated"]), Some(owner))
+            .await
+            .unwrap();
+        assert_eq!(response.tags, vec!["dessert", "plated"]);
+        use_case.execute(MediaId::new(6), tags_request(&["plated"]), Some(owner)).await.unwrap();
+
+        let filter = MediaTag::normalize_all(&["dessert", "plated"]).unwrap();
+        let (media, _, _) = repo
+            .find_by_user_paginated(owner, None, 50, None, &filter, MediaSort::default())
+            .await
+            .unwrap();
+        assert_eq!(media.len(), 1);
+        assert_eq!(media[0].id, MediaId::new(5));
+        assert_eq!(media[0].tags, filter);
+    }
+
+    #[tokio::test]
+    async fn test_set_tags_rejects_invalid_missing_and_foreign_media() {
+        let owner = UserId::new();
+        let (use_case, _) = setup(owner);
+
+        let result = use_case.execute(MediaId::new(5), tags_request(&["a/b"]), Some(owner)).await;
+        assert!(matches!(result, Err(AppError::BadRequest { .. })));
+
+        let result = use_case.execute(MediaId::new(99), tags_request(&["a"]), Some(owner)).await;
+        assert!(matches!(result, Err(AppError::NotFound { .. })));
+
+        let result =
+            use_case.execute(MediaId::new(5), tags_request(&["a"]), Some(UserId::new())).await;
+        assert!(matches!(result, Err(AppError::Authorization { .. })));
+    }
+}
@@ -0,0 +1,165 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+use crate::{application::dto::InitiateUploadResponse, domain::entities::UserId};
+
+/// What identifies an upload before its content arrives
+///
+/// The hash prefix is reported by the client, computed over the file it is about
+/// to upload, so different files that share a name and size are told apart.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct UploadFingerprint {
+    pub user_id: UserId,
+    pub filename: String,
+    pub file_size: u64,
+    pub hash_prefix: String,
+}
+
+impl UploadFingerprint {
+    /// Key the fingerprint is locked under in [`super::UploadLocks`]
+    pub(crate) fn lock_key(&self) -> String {
+        format!(
+            "fingerprint:{}:{}:{}:{}",
+            self.user_id, self.file_size, self.hash_prefix, self.filename
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+struct RecentUpload {
+    initiated_at: SystemTime,
+    expires_at: SystemTime,
+    response: InitiateUploadResponse,
+}
+
+/// Presigned uploads initiated recently, by fingerprint
+///
+/// Initiating an upload with the same fingerprint again within the window, e.g.
+/// after a double-tapped submit, hands back the first upload's session instead of
+/// creating a second media record. Entries are held in memory, so only repeats
+/// that reach the same instance are collapsed. Disabled by default.
+#[derive(Debug, Clone, Default)]
+pub struct UploadFingerprints {
+    window: Option<Duration>,
+    recent: Arc<Mutex<HashMap<UploadFingerprint, RecentUpload>>>,
+}
+
+impl UploadFingerprints {
+    /// Collapse repeats arriving within `window` of the first upload
+    #[must_use]
+    pub fn new(window: Duration) -> Self {
+        Self { window: Some(window), recent: Arc::default() }
+    }
+
+    /// Never collapse uploads
+    #[must_use]
+    pub fn disabled() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn is_enabled(&self) -> bool {
+        self.window.is_some()
+    }
+
+    /// The session of an upload with this fingerprint still usable at `now`, if any
+    ///
+    /// # Panics
+    /// Panics if the internal registry mutex is poisoned
+    pub fn recent(
+        &self,
+        fingerprint: &UploadFingerprint,
+        now: SystemTime,
+    ) -> Option<InitiateUploadResponse> {
+        let window = self.window?;
+        let recent = self.recent.lock().expect("upload fingerprint registry poisoned");
+        recent
+            .get(fingerprint)
+            .filter(|upload| now < upload.initiated_at + window && now < upload.expires_at)
+            .map(|upload| upload.response.clone())
+    }
+
+    /// Remember the session created for an upload with this fingerprint
+    ///
+    /// # Panics
+    /// Panics if the internal registry mutex is poisoned
+    pub fn record(
+        &self,
+        fingerprint: UploadFingerprint,
+        initiated_at: SystemTime,
+        expires_at: SystemTime,
+        response: &InitiateUploadResponse,
+    ) {
+        let Some(window) = self.window else {
+            return;
+        };
+        let mut recent = self.recent.lock().expect("upload fingerprint registry poisoned");
+        recent.retain(|_, upload| initiated_at < upload.initiated_at + window);
+        recent.insert(
+            fingerprint,
+            RecentUpload { initiated_at, expires_at, response: response.clone() },
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{entities::MediaId, value_objects::ProcessingStatus};
+
+    fn fingerprint(user_id: UserId, hash_prefix: &str) -> UploadFingerprint {
+        UploadFingerprint {
+            user_id,
+            filename: "pancakes.jpg".to_string(),
+            file_size: 2048,
+            hash_prefix: hash_prefix.to_string(),
+        }
+    }
+
+    fn response(id: i64) -> InitiateUploadResponse {
+        InitiateUploadResponse {
+            media_id: MediaId::new(id),
+            upload_url: format!("http://localhost/upload/{id}"),
+            upload_token: format!("upload_{id}"),
+            expires_at: "2026-10-17T12:15:00Z".to_string(),
+            status: ProcessingStatus::Pending,
+        }
+    }
+
+    #[test]
+    fn test_repeats_within_window_are_recognised() {
+        let fingerprints = UploadFingerprints::new(Duration::from_secs(10));
+        let user_id = UserId::new();
+        let now = SystemTime::now();
+        let expires_at = now + Duration::from_mins(15);
+
+        fingerprints.record(fingerprint(user_id, "abcdef01"), now, expires_at, &response(1));
+
+        let repeat =
+            fingerprints.recent(&fingerprint(user_id, "abcdef01"), now + Duration::from_secs(9));
+        assert_eq!(repeat.unwrap().media_id, MediaId::new(1));
+        assert!(fingerprints
+            .recent(&fingerprint(user_id, "abcdef01"), now + Duration::from_secs(10))
+            .is_none());
+        assert!(fingerprints.recent(&fingerprint(user_id, "abcdef02"), now).is_none());
+        assert!(fingerprints.recent(&fingerprint(UserId::new(), "abcdef01"), now).is_none());
+    }
+
+    #[test]
+    fn test_disabled_registry_remembers_nothing() {
+        let fingerprints = UploadFingerprints::disabled();
+        let user_id = UserId::new();
+        let now = SystemTime::now();
+
+        fingerprints.record(
+            fingerprint(user_id, "abcdef01"),
+            now,
+            now + Duration::from_mins(15),
+            &response(1),
+        );
+
+        assert!(!fingerprints.is_enabled());
+        assert!(fingerprints.recent(&fingerprint(user_id, "abcdef01"), now).is_none());
+    }
+}
@@ -0,0 +1,240 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, Weak};
+use tokio::sync::{Mutex as AsyncMutex, OwnedMutexGuard};
+
+use super::UploadFingerprint;
+use crate::{
+    domain::{
+        entities::UploadId,
+        repositories::{ContentLease, ContentLockRepository},
+        value_objects::ContentHash,
+    },
+    presentation::middleware::error::AppError,
+};
+
+/// Per-key async locks for the upload pipeline
+///
+/// Uploads of identical content serialize on the same lock, so the second upload
+/// observes the media row written by the first instead of racing it to the database.
+/// Chunks of the same resumable upload, uploads to the same presigned session, and
+/// initiations of uploads with the same fingerprint are serialized the same way.
+/// Entries are held weakly and pruned once no upload references them.
+///
+/// Content hash locks are also taken in shared content locks when those are set,
+/// so uploads and deletions of the same content serialize across instances too.
+#[derive(Clone, Default)]
+pub struct UploadLocks {
+    locks: Arc<Mutex<HashMap<String, Weak<AsyncMutex<()>>>>>,
+    shared: Option<Arc<dyn ContentLockRepository<Error = AppError>>>,
+}
+
+/// Hold of a content hash's lock, released when dropped
+pub struct ContentGuard {
+    _local: OwnedMutexGuard<()>,
+    _shared: Option<ContentLease>,
+    exclusive: bool,
+}
+
+impl ContentGuard {
+    /// Whether no other instance can hold the lock either
+    ///
+    /// `false` when the shared lock could not be taken, so only uploads on this
+    /// instance are kept out.
+    #[must_use]
+    pub fn is_exclusive(&self) -> bool {
+        self.exclusive
+    }
+}
+
+impl UploadLocks {
+    /// Create an empty lock registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Also take content hash locks in `shared`, which every instance uses
+    #[must_use]
+    pub fn with_shared_locks(
+        mut self,
+        shared: Arc<dyn ContentLockRepository<Error = AppError>>,
+    ) -> Self {
+        self.shared = Some(shared);
+        self
+    }
+
+    /// Acquire the lock for a content hash, waiting for any in-flight upload of the same content
+    ///
+    /// The shared lock is taken after the local one, so only one upload per
+    /// instance waits on it. If it cannot be taken, the guard holds the local
+    /// lock only and says so with [`ContentGuard::is_exclusive`].
+    ///
+    /// # Panics
+    /// Panics if the internal registry mutex is poisoned
+    pub async fn acquire(&self, hash: &ContentHash) -> ContentGuard {
+        let local = self.acquire_key(hash.as_str()).await;
+        let Some(shared) = &self.shared else {
+            return ContentGuard { _local: local, _shared: None, exclusive: true };
+        };
+
+        match shared.lock(hash).await {
+            Ok(lease) => ContentGuard { _local: local, _shared: Some(lease), exclusive: true },
+            Err(e) => {
+                tracing::warn!("Failed to take the shared lock of content {}: {}", hash, e);
+                ContentGuard { _local: local, _shared: None, exclusive: false }
+            }
+        }
+    }
+
+    /// Acquire the lock for a resumable upload, waiting for any in-flight chunk of it
+    ///
+    /// # Panics
+    /// Panics if the internal registry mutex is poisoned
+    pub async fn acquire_upload(&self, id: UploadId) -> OwnedMutexGuard<()> {
+        self.acquire_key(&format!("upload:{id}")).await
+    }
+
+    /// Acquire the lock for a presigned upload session, waiting for any in-flight upload to it
+    ///
+    /// # Panics
+    /// Panics if the internal registry mutex is poisoned
+    pub async fn acquire_session(&self, upload_token: &str) -> OwnedMutexGuard<()> {
+        self.acquire_key(&format!("session:{upload_token}")).await
+    }
+
+    /// Acquire the lock for an upload fingerprint, waiting for any in-flight initiation of it
+    ///
+    /// # Panics
+    /// Panics if the internal registry mutex is poisoned
+    pub async fn acquire_fingerprint(
+        &self,
+        fingerprint: &UploadFingerprint,
+    ) -> OwnedMutexGuard<()> {
+        self.acquire_key(&fingerprint.lock_key()).await
+    }
+
+    async fn acquire_key(&self, key: &str) -> OwnedMutexGuard<()> {
+        let lock = {
+            let mut locks = self.locks.lock().expect("upload lock registry poisoned");
+            locks.retain(|_, lock| lock.strong_count() > 0);
+
+            if let Some(lock) = locks.get(key).and_then(Weak::upgrade) {
+                lock
+            } else {
+                let lock = Arc::new(AsyncMutex::new(()));
+                locks.insert(key.to_string(), Arc::downgrade(&lock));
+                lock
+            }
+        };
+
+        lock.lock_owned().await
+    }
+
+    /// Number of keys with an upload currently holding or waiting on a lock
+    ///
+    /// # Panics
+    /// Panics if the internal registry mutex is poisoned
+    pub fn in_flight(&self) -> usize {
+        let locks = self.locks.lock().expect("upload lock registry poisoned");
+        locks.values().filter(|lock| lock.strong_count() > 0).count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use std::time::Duration;
+
+    /// Shared locks of another process, here just another registry
+    struct SharedLocks(UploadLocks);
+
+    #[async_trait]
+    impl ContentLockRepository for SharedLocks {
+        type Error = AppError;
+
+        async fn lock(&self, hash: &ContentHash) -> Result<ContentLease, Self::Error> {
+            Ok(Box::new(self.0.acquire(hash).await))
+        }
+    }
+
+    struct UnavailableLocks;
+
+    #[async_trait]
+    impl ContentLockRepository for UnavailableLocks {
+        type Error = AppError;
+
+        async fn lock(&self, _hash: &ContentHash) -> Result<ContentLease, Self::Error> {
+            Err(AppError::Database { message: "connection refused".to_string() })
+        }
+    }
+
+    fn hash(byte: char) -> ContentHash {
+        ContentHash::new(&byte.to_string().repeat(64)).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_same_hash_serializes() {
+        let locks = UploadLocks::new();
+        let guard = locks.acquire(&hash('a')).await;
+
+        let waiter = {
+            let locks = locks.clone();
+            tokio::spawn(async move {
+                let _guard = locks.acquire(&hash('a')).await;
+            })
+        };
+
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!waiter.is_finished());
+
+        drop(guard);
+        waiter.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_different_hashes_do_not_block() {
+        let locks = UploadLocks::new();
+        let _first = locks.acquire(&hash('a')).await;
+        let _second = locks.acquire(&hash('b')).await;
+
+        assert_eq!(locks.in_flight(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_released_locks_are_pruned() {
+        let locks = UploadLocks::new();
+        drop(locks.acquire(&hash('a')).await);
+
+        assert_eq!(locks.in_flight(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_shared_locks_serialize_instances() {
+        let shared: Arc<dyn ContentLockRepository<Error = AppError>> =
+            Arc::new(SharedLocks(UploadLocks::new()));
+        let first = UploadLocks::new().with_shared_locks(shared.clone());
+        let second = UploadLocks::new().with_shared_locks(shared);
+        let guard = first.acquire(&hash('a')).await;
+        assert!(guard.is_exclusive());
+
+        let waiter = tokio::spawn(async move {
+            let _guard = second.acquire(&hash('a')).await;
+        });
+
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!waiter.is_finished());
+
+        drop(guard);
+        waiter.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_unavailable_shared_locks_leave_the_local_lock() {
+        let locks = UploadLocks::new().with_shared_locks(Arc::new(UnavailableLocks));
+
+        let guard = locks.acquire(&hash('a')).await;
+
+        assert!(!guard.is_exclusive());
+        assert_eq!(locks.in_flight(), 1);
+    }
+}
@@ -1,23 +1,32 @@
 use std::sync::Arc;
 use tokio::io::AsyncRead;
 
+use super::{
+    apply_format_policy, discard_unsaved_content, ensure_within_quota, file_too_large,
+    repository_error, verify_content_type, AllowedTypes,
+};
 use crate::{
-    application::dto::UploadMediaResponse,
+    application::{
+        dto::UploadMediaResponse,
+        events::{DomainEvent, EventBus, EventPublisher},
+    },
     domain::{
-        entities::{Media, UserId},
-        repositories::MediaRepository,
-        value_objects::MediaType,
+        entities::{UnsavedMedia, UserId},
+        repositories::{MediaRepository, Recorded, SaveOutcome},
+        value_objects::{FormatPolicy, MediaType, StorageQuota},
     },
-    infrastructure::storage::{
-        utils::{
-            detect_content_type, generate_content_hash_async, validate_content_type,
-            validate_file_size,
+    infrastructure::{
+        business_metrics::{BusinessMetrics, UploadFlow},
+        storage::{
+            utils::{generate_content_hash_async, validate_file_size},
+            FileStorage, StorageError,
         },
-        FileStorage, StorageError,
     },
     presentation::middleware::error::AppError,
 };
 
+use super::UploadLocks;
+
 /// Use case for uploading media files
 pub struct UploadMediaUseCase<R, S>
 where
@@ -27,6 +36,13 @@ where
     repository: Arc<R>,
     storage: Arc<S>,
     max_file_size: u64,
+    upload_locks: UploadLocks,
+    quota: StorageQuota,
+    allowed_types: AllowedTypes,
+    format_policy: FormatPolicy,
+    metrics: BusinessMetrics,
+    flow: UploadFlow,
+    events: Arc<dyn EventPublisher>,
 }
 
 impl<R, S> UploadMediaUseCase<R, S>
@@ -36,7 +52,64 @@ where
 {
     /// Create a new upload media use case
     pub fn new(repository: Arc<R>, storage: Arc<S>, max_file_size: u64) -> Self {
-        Self { repository, storage, max_file_size }
+        Self {
+            repository,
+            storage,
+            max_file_size,
+            upload_locks: UploadLocks::new(),
+            quota: StorageQuota::unlimited(),
+            allowed_types: AllowedTypes::default(),
+            format_policy: FormatPolicy::unrestricted(),
+            metrics: BusinessMetrics::disabled(),
+            flow: UploadFlow::Direct,
+            events: Arc::new(EventBus::new()),
+        }
+    }
+
+    /// Share an upload lock registry so concurrent uploads of identical content are serialized
+    #[must_use]
+    pub fn with_upload_locks(mut self, upload_locks: UploadLocks) -> Self {
+        self.upload_locks = upload_locks;
+        self
+    }
+
+    /// Limit how much each user may store
+    #[must_use]
+    pub fn with_quota(mut self, quota: StorageQuota) -> Self {
+        self.quota = quota;
+        self
+    }
+
+    /// Only accept content of these types; an empty list accepts any type
+    #[must_use]
+    pub fn with_allowed_types(mut self, allowed_types: impl Into<AllowedTypes>) -> Self {
+        self.allowed_types = allowed_types.into();
+        self
+    }
+
+    /// Limit the formats each tenant accepts, converting images into an accepted format
+    #[must_use]
+    pub fn with_format_policy(mut self, format_policy: FormatPolicy) -> Self {
+        self.format_policy = format_policy;
+        self
+    }
+
+    /// Record the outcome of uploads arriving through `flow`
+    ///
+    /// Only direct uploads are counted as started here; the other flows start
+    /// before their content reaches this use case.
+    #[must_use]
+    pub fn with_metrics(mut self, metrics: BusinessMetrics, flow: UploadFlow) -> Self {
+        self.metrics = metrics;
+        self.flow = flow;
+        self
+    }
+
+    /// Announce newly stored content to `events`
+    #[must_use]
+    pub fn with_events(mut self, events: Arc<dyn EventPublisher>) -> Self {
+        self.events = events;
+        self
     }
 
     /// Execute the upload media use case
@@ -47,27 +120,103 @@ where
         user_id: UserId,
         expected_content_type: Option<String>,
     ) -> Result<UploadMediaResponse, AppError>
+    where
+        Reader: AsyncRead + Send + Unpin,
+    {
+        Box::pin(self.execute_for_tenant(
+            None,
+            file_reader,
+            filename,
+            user_id,
+            expected_content_type,
+        ))
+        .await
+    }
+
+    /// Execute the upload media use case on behalf of a tenant
+    ///
+    /// Content the tenant's [`FormatPolicy`] doesn't accept is converted into the
+    /// first accepted format it can be converted to, and rejected if there is none.
+    pub async fn execute_for_tenant<Reader>(
+        &self,
+        tenant: Option<&str>,
+        file_reader: Reader,
+        filename: String,
+        user_id: UserId,
+        expected_content_type: Option<String>,
+    ) -> Result<UploadMediaResponse, AppError>
     where
         Reader: AsyncRead + Send + Unpin,
     {
         tracing::info!("Starting media upload for file: {}", filename);
+        if self.flow == UploadFlow::Direct {
+            self.metrics.upload_started(self.flow);
+        }
+
+        let result =
+            Box::pin(self.ingest(tenant, file_reader, filename, user_id, expected_content_type))
+                .await;
+        self.metrics.record_failure(self.flow, &result);
+        result
+    }
 
+    /// Validate, store and record an upload
+    #[allow(clippy::too_many_lines)]
+    async fn ingest<Reader>(
+        &self,
+        tenant: Option<&str>,
+        file_reader: Reader,
+        filename: String,
+        user_id: UserId,
+        expected_content_type: Option<String>,
+    ) -> Result<UploadMediaResponse, AppError>
+    where
+        Reader: AsyncRead + Send + Unpin,
+    {
         // Generate content hash and read file data
         let (content_hash, file_data) =
             generate_content_hash_async(file_reader).await.map_err(|e| AppError::BadRequest {
                 message: format!("Failed to process file: {e}"),
             })?;
+        let ingested = file_data.len() as u64;
 
         // Validate file size
-        validate_file_size(file_data.len() as u64, self.max_file_size)
-            .map_err(|e| AppError::BadRequest { message: format!("File too large: {e}") })?;
-
-        // Check if file already exists (deduplication)
-        if let Ok(Some(media)) = self.repository.find_by_content_hash(&content_hash).await {
+        validate_file_size(ingested, self.max_file_size)
+            .map_err(|_| file_too_large(ingested, self.max_file_size))?;
+
+        // Trust the magic bytes, not the client, for what the file is
+        let content_type = verify_content_type(
+            &file_data,
+            &filename,
+            expected_content_type.as_deref(),
+            &self.allowed_types.get(),
+        )?;
+
+        let (content_hash, file_data, content_type) = apply_format_policy(
+            &self.format_policy,
+            tenant,
+            self.max_file_size,
+            content_hash,
+            file_data,
+            content_type,
+            expected_content_type,
+        )
+        .await?;
+
+        // Serialize uploads of identical content so the dedup check below sees the
+        // row written by any upload that won the race
+        let upload_guard = self.upload_locks.acquire(&content_hash).await;
+
+        // Check if the uploader already has this file (deduplication is per owner;
+        // only the stored blob is shared between users)
+        if let Ok(Some(media)) =
+            self.repository.find_by_content_hash(&content_hash, Some(user_id)).await
+        {
             tracing::info!(
                 "File already exists with hash: {}, returning existing media",
                 content_hash.as_str()
             );
+            self.metrics.upload_completed(self.flow, ingested, true);
 
             return Ok(UploadMediaResponse {
                 media_id: media.id,
@@ -77,25 +226,21 @@ where
             });
         }
 
-        // Detect content type
-        let detected_content_type = detect_content_type(&file_data, Some(&filename));
+        // Duplicates reuse stored content, so only new files count against the quota
+        ensure_within_quota(&*self.repository, self.quota, user_id, file_data.len() as u64).await?;
 
-        // Validate content type if expected type is provided
-        if let Some(expected) = &expected_content_type {
-            validate_content_type(&file_data, expected).map_err(|e| AppError::BadRequest {
-                message: format!("Content type validation failed: {e}"),
-            })?;
-        }
+        let media_type = MediaType::new(&content_type);
 
-        // Create media type from detected content type
-        let media_type = MediaType::new(&detected_content_type);
+        // Content already in storage belongs to other media or variants, so it must
+        // survive a failed save below
+        let stored_before = self.storage.exists(&content_hash).await.unwrap_or(true);
 
         // Store file in storage system
-        let cursor = std::io::Cursor::new(&file_data);
+        let mut cursor = std::io::Cursor::new(&file_data);
         let storage_path =
-            self.storage.store(&content_hash, cursor).await.map_err(|e| match e {
+            self.storage.store(&content_hash, &mut cursor).await.map_err(|e| match e {
                 StorageError::StorageFull => {
-                    AppError::BadRequest { message: "Storage full or file too large".to_string() }
+                    AppError::BadRequest { message: "Storage is full".to_string() }
                 }
                 _ => AppError::Internal { message: format!("Storage error: {e}") },
             })?;
@@ -103,38 +248,75 @@ where
         tracing::info!("File stored at path: {}", storage_path);
 
         // Create media entity
-        let media = Media::new(
+        let media = UnsavedMedia::new(
             content_hash.clone(),
             filename,
             media_type,
             storage_path,
             file_data.len() as u64,
             user_id,
-        );
-
-        // Save media metadata to database
-        let media_id = match self.repository.save(&media).await {
-            Ok(id) => id,
-            Err(e) => {
-                // If database save fails, try to clean up stored file
-                let _ = self.storage.delete(&content_hash).await;
-
-                return Err(AppError::Internal {
-                    message: format!("Failed to save media metadata: {e}"),
-                });
-            }
-        };
+        )
+        .with_tenant(tenant);
+
+        // Save media metadata to database, reusing the row if a concurrent writer
+        // (e.g. another replica) inserted the same content first
+        // A new row is announced with the same transaction where the repository has an outbox
+        let mut events_recorded = false;
+        let (media_id, processing_status, deduplicated) =
+            match self.repository.save_or_reuse_announced(&media).await {
+                Ok(Recorded { outcome: SaveOutcome::Created(id), events_recorded: recorded }) => {
+                    events_recorded = recorded;
+                    (id, media.processing_status, false)
+                }
+                Ok(Recorded { outcome: SaveOutcome::Reused(id), .. }) => {
+                    tracing::info!(
+                        "Media with hash {} was saved concurrently, reusing ID: {}",
+                        content_hash.as_str(),
+                        id
+                    );
+                    // Report where the existing media is in processing, not the status of the
+                    // row that was never written
+                    let status = match self.repository.find_by_id(id).await {
+                        Ok(Some(existing)) => existing.processing_status,
+                        _ => media.processing_status,
+                    };
+                    (id, status, true)
+                }
+                Err(e) => {
+                    // If database save fails, try to clean up the file stored above
+                    if !stored_before {
+                        discard_unsaved_content(
+                            &*self.repository,
+                            &*self.storage,
+                            &content_hash,
+                            &upload_guard,
+                        )
+                        .await;
+                    }
+
+                    return Err(repository_error("Failed to save media metadata")(e));
+                }
+            };
 
         tracing::info!(
             "Media upload completed successfully for file: {} with ID: {}",
             media.original_filename,
             media_id
         );
+        self.metrics.upload_completed(self.flow, ingested, deduplicated);
+        if !deduplicated && !events_recorded {
+            self.events.publish(&DomainEvent::MediaUploaded {
+                media_id,
+                uploaded_by: media.uploaded_by,
+                media_type: media.media_type.clone(),
+                file_size: media.file_size,
+            });
+        }
 
         Ok(UploadMediaResponse {
             media_id,
             content_hash: content_hash.as_str().to_string(),
-            processing_status: media.processing_status,
+            processing_status,
             upload_url: None,
         })
     }
@@ -161,9 +343,12 @@ mod tests {
     use tempfile::TempDir;
 
     use crate::{
-        domain::value_objects::{ContentHash, ProcessingStatus},
+        domain::{
+            entities::MediaId,
+            value_objects::{ContentHash, ProcessingStatus},
+        },
         infrastructure::storage::FilesystemStorage,
-        test_utils::mocks::InMemoryMediaRepository,
+        test_utils::{images::create_test_png, mocks::InMemoryMediaRepository},
     };
 
     #[tokio::test]
@@ -214,10 +399,10 @@ mod tests {
 
         assert!(result.is_err());
         match result.unwrap_err() {
-            AppError::BadRequest { message } => {
-                assert!(message.contains("File too large"));
+            AppError::PayloadTooLarge { message } => {
+                assert!(message.contains("exceeds maximum allowed size of 5 bytes"));
             }
-            _ => panic!("Expected BadRequest error"),
+            _ => panic!("Expected PayloadTooLarge error"),
         }
     }
 
@@ -227,23 +412,25 @@ mod tests {
         let content_hash =
             ContentHash::new("b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9")
                 .unwrap();
-        let existing_media = Media::new(
+        let user_id = UserId::new();
+        let mut existing_media = UnsavedMedia::new(
             content_hash.clone(),
             "existing.txt".to_string(),
             MediaType::new("text/plain"),
             "/path/to/existing".to_string(),
             11,
-            UserId::new(),
-        );
+            user_id,
+        )
+        .into_media(MediaId::new(1));
+        existing_media.set_processing_status(ProcessingStatus::Complete);
 
         let repo = InMemoryMediaRepository::new().with_media(existing_media);
-        let storage = FilesystemStorage::new(temp_dir.path());
+        let storage = Arc::new(FilesystemStorage::new(temp_dir.path()));
 
-        let use_case = UploadMediaUseCase::new(Arc::new(repo), Arc::new(storage), 10_000_000);
+        let use_case = UploadMediaUseCase::new(Arc::new(repo), storage.clone(), 10_000_000);
 
         let file_data = b"hello world";
         let file_reader = Cursor::new(file_data);
-        let user_id = UserId::new();
 
         let result =
             use_case.execute(file_reader, "duplicate.txt".to_string(), user_id, None).await;
@@ -251,7 +438,307 @@ mod tests {
         assert!(result.is_ok());
         let response = result.unwrap();
         assert_eq!(response.content_hash, content_hash.as_str());
+        assert_eq!(response.media_id, MediaId::new(1));
+        assert_eq!(response.processing_status, ProcessingStatus::Complete);
+
+        // The existing blob is reused, so nothing is written to storage
+        assert!(!storage.exists(&content_hash).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_failed_save_keeps_content_stored_before_the_upload() {
+        use crate::infrastructure::persistence::DisconnectedMediaRepository;
+
+        let temp_dir = TempDir::new().unwrap();
+        let storage = Arc::new(FilesystemStorage::new(temp_dir.path()));
+        let content_hash =
+            ContentHash::new("b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9")
+                .unwrap();
+        storage.store(&content_hash, &mut Cursor::new(b"hello world")).await.unwrap();
+
+        let repo = DisconnectedMediaRepository::new("connection refused".to_string());
+        let use_case = UploadMediaUseCase::new(Arc::new(repo), storage.clone(), 10_000_000);
+
+        let result = use_case
+            .execute(Cursor::new(b"hello world"), "shared.txt".to_string(), UserId::new(), None)
+            .await;
+
+        assert!(matches!(result, Err(AppError::ServiceUnavailable { .. })));
+        assert!(storage.exists(&content_hash).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_identical_uploads_reuse_first_result() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Arc::new(InMemoryMediaRepository::new());
+        let storage = Arc::new(FilesystemStorage::new(temp_dir.path()));
+        let upload_locks = UploadLocks::new();
+
+        let first = UploadMediaUseCase::new(repo.clone(), storage.clone(), 10_000_000)
+            .with_upload_locks(upload_locks.clone());
+        let second = UploadMediaUseCase::new(repo.clone(), storage.clone(), 10_000_000)
+            .with_upload_locks(upload_locks.clone());
+
+        let user_id = UserId::new();
+        let (first_result, second_result) = tokio::join!(
+            first.execute(Cursor::new(b"same content"), "a.txt".to_string(), user_id, None),
+            second.execute(Cursor::new(b"same content"), "b.txt".to_string(), user_id, None),
+        );
+
+        let first_response = first_result.unwrap();
+        let second_response = second_result.unwrap();
+        assert_eq!(first_response.media_id, second_response.media_id);
+        assert_eq!(upload_locks.in_flight(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_users_uploading_the_same_bytes_each_get_their_own_media() {
+        use crate::application::use_cases::DownloadMediaUseCase;
+        use tokio::io::AsyncReadExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Arc::new(InMemoryMediaRepository::new());
+        let storage = Arc::new(FilesystemStorage::new(temp_dir.path()));
+        let use_case = UploadMediaUseCase::new(repo.clone(), storage.clone(), 10_000_000);
+        let download = DownloadMediaUseCase::new(repo.clone(), storage);
+
+        let alice = UserId::new();
+        let bob = UserId::new();
+        let upload = |filename: &str, user_id| {
+            use_case.execute(Cursor::new(b"shared bytes"), filename.to_string(), user_id, None)
+        };
+        let alices = upload("alice.txt", alice).await.unwrap();
+        let bobs = upload("bob.txt", bob).await.unwrap();
+        assert_ne!(alices.media_id, bobs.media_id);
+        assert_eq!(alices.content_hash, bobs.content_hash);
+
+        for (user_id, media_id, filename) in
+            [(alice, alices.media_id, "alice.txt"), (bob, bobs.media_id, "bob.txt")]
+        {
+            let mut media = repo.find_by_id(media_id).await.unwrap().unwrap();
+            assert_eq!(media.uploaded_by, user_id);
+            media.set_processing_status(ProcessingStatus::Complete);
+            repo.update(&media).await.unwrap();
+
+            let mut response =
+                download.execute_negotiated(media_id, None, None, Some(user_id)).await.unwrap();
+            assert_eq!(response.filename, filename);
+            let mut content = Vec::new();
+            response.content.read_to_end(&mut content).await.unwrap();
+            assert_eq!(content, b"shared bytes");
+
+            let hash = ContentHash::new(&alices.content_hash).unwrap();
+            let (found, _) =
+                download.execute_by_hash(&hash, None, None, Some(user_id)).await.unwrap();
+            assert_eq!(found, media_id);
+        }
+
+        let denied = download.execute_negotiated(alices.media_id, None, None, Some(bob)).await;
+        assert!(matches!(denied, Err(AppError::Authorization { .. })));
     }
 
     // Note: Additional integration tests with real filesystem storage would go in the integration test directory
+
+    #[tokio::test]
+    async fn test_upload_media_rejects_files_over_quota() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = Arc::new(FilesystemStorage::new(temp_dir.path()));
+        let use_case =
+            UploadMediaUseCase::new(Arc::new(InMemoryMediaRepository::new()), storage, 10_000_000)
+                .with_quota(StorageQuota { max_bytes: None, max_files: Some(1) });
+        let user_id = UserId::new();
+
+        let first = use_case.execute(Cursor::new(b"first"), "a.txt".to_string(), user_id, None);
+        first.await.unwrap();
+
+        // Re-uploading stored content takes no extra space and is still allowed
+        let duplicate =
+            use_case.execute(Cursor::new(b"first"), "b.txt".to_string(), user_id, None).await;
+        assert!(duplicate.is_ok());
+
+        let second =
+            use_case.execute(Cursor::new(b"second"), "c.txt".to_string(), user_id, None).await;
+        assert!(matches!(second, Err(AppError::QuotaExceeded { .. })));
+    }
+
+    fn png_upload_use_case(
+        temp_dir: &TempDir,
+    ) -> UploadMediaUseCase<InMemoryMediaRepository, FilesystemStorage> {
+        UploadMediaUseCase::new(
+            Arc::new(InMemoryMediaRepository::new()),
+            Arc::new(FilesystemStorage::new(temp_dir.path())),
+            10_000_000,
+        )
+        .with_allowed_types(vec!["image/png".to_string(), "text/plain".to_string()])
+    }
+
+    const PNG_HEADER: &[u8] = &[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A, 0, 0];
+
+    #[tokio::test]
+    async fn test_upload_media_uses_sniffed_content_type() {
+        let temp_dir = TempDir::new().unwrap();
+        let use_case = png_upload_use_case(&temp_dir);
+
+        let response = use_case
+            .execute(
+                Cursor::new(PNG_HEADER),
+                "photo.bin".to_string(),
+                UserId::new(),
+                Some("application/octet-stream".to_string()),
+            )
+            .await
+            .unwrap();
+
+        let media = use_case.repository.find_by_id(response.media_id).await.unwrap().unwrap();
+        assert_eq!(media.media_type.mime_type(), "image/png");
+    }
+
+    #[tokio::test]
+    async fn test_upload_media_rejects_declared_type_mismatch() {
+        let temp_dir = TempDir::new().unwrap();
+        let use_case = png_upload_use_case(&temp_dir);
+
+        let result = use_case
+            .execute(
+                Cursor::new(PNG_HEADER),
+                "photo.jpg".to_string(),
+                UserId::new(),
+                Some("image/jpeg".to_string()),
+            )
+            .await;
+
+        match result {
+            Err(AppError::UnprocessableContent { declared_type, detected_type, .. }) => {
+                assert_eq!(declared_type.as_deref(), Some("image/jpeg"));
+                assert_eq!(detected_type.as_deref(), Some("image/png"));
+            }
+            other => panic!("Expected UnprocessableContent, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_upload_media_rejects_content_missing_claimed_signature() {
+        let temp_dir = TempDir::new().unwrap();
+        let use_case = png_upload_use_case(&temp_dir);
+
+        let result = use_case
+            .execute(Cursor::new(b"not an image"), "fake.png".to_string(), UserId::new(), None)
+            .await;
+
+        assert!(matches!(result, Err(AppError::UnprocessableContent { detected_type: None, .. })));
+    }
+
+    #[tokio::test]
+    async fn test_upload_media_rejects_types_not_allowed() {
+        let temp_dir = TempDir::new().unwrap();
+        let use_case = png_upload_use_case(&temp_dir);
+        let gif = b"GIF89a\x01\x00\x01\x00";
+
+        let result = use_case
+            .execute(
+                Cursor::new(gif),
+                "anim.gif".to_string(),
+                UserId::new(),
+                Some("image/gif".to_string()),
+            )
+            .await;
+
+        match result {
+            Err(AppError::UnprocessableContent { detected_type, allowed_types, .. }) => {
+                assert_eq!(detected_type.as_deref(), Some("image/gif"));
+                assert_eq!(allowed_types, vec!["image/png".to_string(), "text/plain".to_string()]);
+            }
+            other => panic!("Expected UnprocessableContent, got {other:?}"),
+        }
+
+        // Formats without a signature are taken at their declared type
+        let text = use_case
+            .execute(
+                Cursor::new(b"notes"),
+                "notes.txt".to_string(),
+                UserId::new(),
+                Some("text/plain".to_string()),
+            )
+            .await;
+        assert!(text.is_ok());
+    }
+
+    fn tenant_upload_use_case(
+        temp_dir: &TempDir,
+    ) -> (
+        UploadMediaUseCase<InMemoryMediaRepository, FilesystemStorage>,
+        Arc<InMemoryMediaRepository>,
+    ) {
+        let repo = Arc::new(InMemoryMediaRepository::new());
+        let use_case = UploadMediaUseCase::new(
+            repo.clone(),
+            Arc::new(FilesystemStorage::new(temp_dir.path())),
+            10_000_000,
+        )
+        .with_format_policy(FormatPolicy::new(std::collections::HashMap::from([(
+            "web-app".to_string(),
+            vec!["image/webp".to_string()],
+        )])));
+        (use_case, repo)
+    }
+
+    #[tokio::test]
+    async fn test_upload_for_tenant_converts_to_an_accepted_format() {
+        let temp_dir = TempDir::new().unwrap();
+        let (use_case, repo) = tenant_upload_use_case(&temp_dir);
+
+        let response = use_case
+            .execute_for_tenant(
+                Some("web-app"),
+                Cursor::new(create_test_png()),
+                "photo.png".to_string(),
+                UserId::new(),
+                Some("image/png".to_string()),
+            )
+            .await
+            .unwrap();
+
+        let media = repo.find_by_id(response.media_id).await.unwrap().unwrap();
+        assert_eq!(media.media_type.mime_type(), "image/webp");
+        assert_eq!(media.content_hash.as_str(), response.content_hash);
+
+        // Other tenants keep the uploaded format
+        let response = use_case
+            .execute_for_tenant(
+                Some("recipe-service"),
+                Cursor::new(create_test_png()),
+                "photo.png".to_string(),
+                UserId::new(),
+                None,
+            )
+            .await
+            .unwrap();
+        let media = repo.find_by_id(response.media_id).await.unwrap().unwrap();
+        assert_eq!(media.media_type.mime_type(), "image/png");
+        assert_eq!(media.tenant.as_deref(), Some("recipe-service"));
+    }
+
+    #[tokio::test]
+    async fn test_upload_for_tenant_rejects_unconvertible_content() {
+        let temp_dir = TempDir::new().unwrap();
+        let (use_case, _) = tenant_upload_use_case(&temp_dir);
+
+        let result = use_case
+            .execute_for_tenant(
+                Some("web-app"),
+                Cursor::new(b"plain text"),
+                "notes.txt".to_string(),
+                UserId::new(),
+                Some("text/plain".to_string()),
+            )
+            .await;
+
+        match result {
+            Err(AppError::UnprocessableContent { detected_type, allowed_types, .. }) => {
+                assert_eq!(detected_type.as_deref(), Some("text/plain"));
+                assert_eq!(allowed_types, vec!["image/webp".to_string()]);
+            }
+            other => panic!("Expected UnprocessableContent, got {other:?}"),
+        }
+    }
 }
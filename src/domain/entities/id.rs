@@ -0,0 +1,87 @@
+/// Error returned when an identifier is built from a non-positive value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("{kind} must be a positive integer, got {value}")]
+pub struct InvalidIdError {
+    pub kind: &'static str,
+    pub value: i64,
+}
+
+/// Define a database identifier newtype that only holds positive values
+///
+/// IDs come from `BIGSERIAL` columns, so zero and negative values never name a
+/// row. Deserialization goes through `TryFrom<i64>`, which makes path and query
+/// extractors reject them before a handler runs.
+macro_rules! positive_id {
+    ($(#[$meta:meta])* $name:ident) => {
+        $(#[$meta])*
+        #[derive(
+            Debug,
+            Clone,
+            Copy,
+            PartialEq,
+            Eq,
+            Hash,
+            PartialOrd,
+            Ord,
+            serde::Serialize,
+            serde::Deserialize,
+        )]
+        #[serde(try_from = "i64", into = "i64")]
+        pub struct $name(std::num::NonZeroI64);
+
+        impl $name {
+            /// Create an ID from a value known to be positive
+            ///
+            /// # Panics
+            /// Panics if `id` is zero or negative; use [`Self::try_new`] for untrusted input
+            #[must_use]
+            pub fn new(id: i64) -> Self {
+                match Self::try_new(id) {
+                    Ok(id) => id,
+                    Err(e) => panic!("{e}"),
+                }
+            }
+
+            /// Create an ID, rejecting zero and negative values
+            ///
+            /// # Errors
+            /// Returns `InvalidIdError` if `id` is not positive
+            pub fn try_new(id: i64) -> Result<Self, $crate::domain::entities::InvalidIdError> {
+                std::num::NonZeroI64::new(id)
+                    .filter(|id| id.get() > 0)
+                    .map(Self)
+                    .ok_or($crate::domain::entities::InvalidIdError {
+                        kind: stringify!($name),
+                        value: id,
+                    })
+            }
+
+            #[must_use]
+            pub fn as_i64(&self) -> i64 {
+                self.0.get()
+            }
+        }
+
+        impl std::fmt::Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, "{}", self.0)
+            }
+        }
+
+        impl TryFrom<i64> for $name {
+            type Error = $crate::domain::entities::InvalidIdError;
+
+            fn try_from(id: i64) -> Result<Self, Self::Error> {
+                Self::try_new(id)
+            }
+        }
+
+        impl From<$name> for i64 {
+            fn from(id: $name) -> Self {
+                id.as_i64()
+            }
+        }
+    };
+}
+
+pub(crate) use positive_id;
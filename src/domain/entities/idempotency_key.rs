@@ -0,0 +1,34 @@
+use serde::{Deserialize, Serialize};
+use std::time::SystemTime;
+
+/// A request sent with an `Idempotency-Key`, and its response once there is one
+///
+/// `key` is the client's key scoped to the caller, and `request` identifies the
+/// endpoint it was first used on, so a key cannot replay a response to a
+/// different request. The record is in flight until the response is stored.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IdempotencyRecord {
+    pub key: String,
+    pub request: String,
+    /// Hex SHA-256 of the request body, which retries must send unchanged
+    pub body_hash: String,
+    pub response: Option<StoredResponse>,
+    pub created_at: SystemTime,
+    pub expires_at: SystemTime,
+}
+
+/// The parts of a response replayed to retries of its request
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StoredResponse {
+    pub status: u16,
+    pub content_type: Option<String>,
+    pub body: Vec<u8>,
+}
+
+impl IdempotencyRecord {
+    /// Check if the key can be used for a new request at `now`
+    #[must_use]
+    pub fn is_expired_at(&self, now: SystemTime) -> bool {
+        now >= self.expires_at
+    }
+}
@@ -1,7 +1,11 @@
 use serde::{Deserialize, Serialize};
 use std::time::SystemTime;
 
-use crate::domain::value_objects::{ContentHash, MediaType, ProcessingStatus};
+use super::id::positive_id;
+use crate::domain::value_objects::{ContentHash, MediaTag, MediaType, ProcessingStatus};
+
+/// `media_path` of a presigned upload's placeholder until its content arrives
+pub const PENDING_UPLOAD_PATH: &str = "pending";
 
 /// Core media entity representing a file in the system
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -16,38 +20,59 @@ pub struct Media {
     pub uploaded_by: crate::domain::entities::UserId,
     pub uploaded_at: SystemTime,
     pub updated_at: SystemTime,
+    /// Alternative encodings generated from the original (e.g. WebP, AVIF)
+    #[serde(default)]
+    pub variants: Vec<MediaVariant>,
+    /// Why the last processing run failed, if it did
+    #[serde(default)]
+    pub processing_error: Option<String>,
+    /// User-defined tags, sorted and without repeats
+    #[serde(default)]
+    pub tags: Vec<MediaTag>,
+    /// When an integrity scan found stored content no longer matching its hash
+    #[serde(default)]
+    pub corrupted_at: Option<SystemTime>,
+    /// Client ID of the token the media was uploaded with, if any
+    #[serde(default)]
+    pub tenant: Option<String>,
 }
 
-/// Unique identifier for media files (database BIGSERIAL)
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
-pub struct MediaId(i64);
-
-impl MediaId {
-    #[must_use]
-    pub fn new(id: i64) -> Self {
-        Self(id)
-    }
-
-    #[must_use]
-    pub fn as_i64(&self) -> i64 {
-        self.0
-    }
+/// An alternative encoding of a media file, stored by its own content hash
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MediaVariant {
+    /// Variant name, e.g. `webp` or `avif`
+    pub name: String,
+    pub content_hash: ContentHash,
+    pub media_type: MediaType,
+    pub file_size: u64,
 }
 
-impl std::fmt::Display for MediaId {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.0)
-    }
+positive_id! {
+    /// Unique identifier for media files (database BIGSERIAL)
+    MediaId
 }
 
-impl From<i64> for MediaId {
-    fn from(id: i64) -> Self {
-        Self(id)
-    }
+/// A media entity that has not been persisted yet
+///
+/// The database assigns the ID on insert; repositories return it from `save`,
+/// after which [`UnsavedMedia::into_media`] produces the persisted entity.
+#[derive(Debug, Clone)]
+pub struct UnsavedMedia {
+    pub content_hash: ContentHash,
+    pub original_filename: String,
+    pub media_type: MediaType,
+    pub media_path: String,
+    pub file_size: u64,
+    pub processing_status: ProcessingStatus,
+    pub uploaded_by: crate::domain::entities::UserId,
+    pub uploaded_at: SystemTime,
+    pub updated_at: SystemTime,
+    /// Client ID of the token the media is uploaded with, if any
+    pub tenant: Option<String>,
 }
 
-impl Media {
-    /// Create a new media entity (without database ID - will be assigned on save)
+impl UnsavedMedia {
+    /// Create a new media entity awaiting its database ID
     #[must_use]
     pub fn new(
         content_hash: ContentHash,
@@ -59,7 +84,6 @@ impl Media {
     ) -> Self {
         let now = SystemTime::now();
         Self {
-            id: MediaId::new(0), // Database will assign actual ID
             content_hash,
             original_filename,
             media_type,
@@ -69,9 +93,41 @@ impl Media {
             uploaded_by,
             uploaded_at: now,
             updated_at: now,
+            tenant: None,
         }
     }
 
+    /// Record the client the media is uploaded for
+    #[must_use]
+    pub fn with_tenant(mut self, tenant: Option<&str>) -> Self {
+        self.tenant = tenant.map(String::from);
+        self
+    }
+
+    /// Attach the ID assigned on save
+    #[must_use]
+    pub fn into_media(self, id: MediaId) -> Media {
+        Media {
+            id,
+            content_hash: self.content_hash,
+            original_filename: self.original_filename,
+            media_type: self.media_type,
+            media_path: self.media_path,
+            file_size: self.file_size,
+            processing_status: self.processing_status,
+            uploaded_by: self.uploaded_by,
+            uploaded_at: self.uploaded_at,
+            updated_at: self.updated_at,
+            variants: Vec::new(),
+            processing_error: None,
+            tags: Vec::new(),
+            corrupted_at: None,
+            tenant: self.tenant,
+        }
+    }
+}
+
+impl Media {
     /// Create a media entity with existing database ID
     #[must_use]
     pub fn with_id(
@@ -94,12 +150,25 @@ impl Media {
             uploaded_by: None,
             uploaded_at: None,
             updated_at: None,
+            variants: Vec::new(),
+            processing_error: None,
+            tags: Vec::new(),
+            corrupted_at: None,
+            tenant: None,
         }
     }
 
-    /// Update the processing status
+    /// Update the processing status, clearing any previous processing error
     pub fn set_processing_status(&mut self, status: ProcessingStatus) {
         self.processing_status = status;
+        self.processing_error = None;
+        self.updated_at = SystemTime::now();
+    }
+
+    /// Mark processing as failed with the reason
+    pub fn mark_failed(&mut self, error: impl Into<String>) {
+        self.processing_status = ProcessingStatus::Failed;
+        self.processing_error = Some(error.into());
         self.updated_at = SystemTime::now();
     }
 
@@ -114,6 +183,18 @@ impl Media {
     pub fn has_failed(&self) -> bool {
         matches!(self.processing_status, ProcessingStatus::Failed)
     }
+
+    /// Check if this is a presigned upload's placeholder still waiting for content
+    #[must_use]
+    pub fn is_awaiting_upload(&self) -> bool {
+        self.media_path == PENDING_UPLOAD_PATH
+    }
+
+    /// Check if the last integrity scan found the stored content damaged or missing
+    #[must_use]
+    pub fn is_corrupted(&self) -> bool {
+        self.corrupted_at.is_some()
+    }
 }
 
 /// Builder for creating Media entities with many fields
@@ -128,6 +209,11 @@ pub struct MediaBuilder {
     uploaded_by: Option<crate::domain::entities::UserId>,
     uploaded_at: Option<SystemTime>,
     updated_at: Option<SystemTime>,
+    variants: Vec<MediaVariant>,
+    processing_error: Option<String>,
+    tags: Vec<MediaTag>,
+    corrupted_at: Option<SystemTime>,
+    tenant: Option<String>,
 }
 
 impl MediaBuilder {
@@ -152,6 +238,41 @@ impl MediaBuilder {
         self
     }
 
+    /// Set the generated variants
+    #[must_use]
+    pub fn variants(mut self, variants: Vec<MediaVariant>) -> Self {
+        self.variants = variants;
+        self
+    }
+
+    /// Set the error from the last failed processing run
+    #[must_use]
+    pub fn processing_error(mut self, error: Option<String>) -> Self {
+        self.processing_error = error;
+        self
+    }
+
+    /// Set the user-defined tags
+    #[must_use]
+    pub fn tags(mut self, tags: Vec<MediaTag>) -> Self {
+        self.tags = tags;
+        self
+    }
+
+    /// Set when an integrity scan found the stored content damaged
+    #[must_use]
+    pub fn corrupted_at(mut self, timestamp: Option<SystemTime>) -> Self {
+        self.corrupted_at = timestamp;
+        self
+    }
+
+    /// Set the client ID the media was uploaded with
+    #[must_use]
+    pub fn tenant(mut self, tenant: Option<String>) -> Self {
+        self.tenant = tenant;
+        self
+    }
+
     /// Build the final Media entity
     #[must_use]
     pub fn build(self) -> Media {
@@ -167,6 +288,11 @@ impl MediaBuilder {
             uploaded_by: self.uploaded_by.unwrap_or_default(),
             uploaded_at: self.uploaded_at.unwrap_or(now),
             updated_at: self.updated_at.unwrap_or(now),
+            variants: self.variants,
+            processing_error: self.processing_error,
+            tags: self.tags,
+            corrupted_at: self.corrupted_at,
+            tenant: self.tenant,
         }
     }
 }
@@ -192,7 +318,7 @@ mod tests {
         let file_size = 1024;
         let user_id = create_test_user_id();
 
-        let media = Media::new(
+        let media = UnsavedMedia::new(
             content_hash.clone(),
             filename.clone(),
             media_type.clone(),
@@ -220,11 +346,33 @@ mod tests {
         assert_eq!(id1.as_i64(), 1);
         assert_eq!(id2.as_i64(), 2);
 
-        let id_from_i64 = MediaId::from(42);
+        let id_from_i64 = MediaId::try_from(42).unwrap();
         assert_eq!(id_from_i64.as_i64(), 42);
 
         let id_string = id1.to_string();
         assert_eq!(id_string, "1");
+
+        assert!(MediaId::try_new(0).is_err());
+        assert!(serde_json::from_str::<MediaId>("-1").is_err());
+    }
+
+    #[test]
+    fn test_unsaved_media_into_media() {
+        let unsaved = UnsavedMedia::new(
+            create_test_content_hash(),
+            "test.jpg".to_string(),
+            MediaType::new("image/jpeg"),
+            "ab/cd/ef/test".to_string(),
+            1024,
+            create_test_user_id(),
+        );
+
+        let media = unsaved.clone().into_media(MediaId::new(9));
+
+        assert_eq!(media.id, MediaId::new(9));
+        assert_eq!(media.content_hash, unsaved.content_hash);
+        assert_eq!(media.uploaded_at, unsaved.uploaded_at);
+        assert!(media.variants.is_empty());
     }
 
     #[test]
@@ -233,14 +381,15 @@ mod tests {
         let media_type = MediaType::new("video/mp4");
         let user_id = create_test_user_id();
 
-        let mut media = Media::new(
+        let mut media = UnsavedMedia::new(
             content_hash,
             "test.mp4".to_string(),
             media_type,
             "ab/cd/ef/test".to_string(),
             2048,
             user_id,
-        );
+        )
+        .into_media(MediaId::new(1));
 
         assert!(media.processing_status.is_pending());
         assert!(!media.is_ready());
@@ -263,20 +412,25 @@ mod tests {
         let media_type = MediaType::new("image/png");
         let user_id = create_test_user_id();
 
-        let mut media = Media::new(
+        let mut media = UnsavedMedia::new(
             content_hash,
             "test.png".to_string(),
             media_type,
             "ab/cd/ef/test".to_string(),
             512,
             user_id,
-        );
+        )
+        .into_media(MediaId::new(1));
 
-        media.set_processing_status(ProcessingStatus::Failed);
+        media.mark_failed("ffmpeg exited with status 1");
 
         assert!(media.processing_status.is_failed());
         assert!(!media.is_ready());
         assert!(media.has_failed());
+        assert_eq!(media.processing_error.as_deref(), Some("ffmpeg exited with status 1"));
+
+        media.set_processing_status(ProcessingStatus::Processing);
+        assert!(media.processing_error.is_none());
     }
 
     #[test]
@@ -285,14 +439,15 @@ mod tests {
         let media_type = MediaType::new("image/webp");
         let user_id = create_test_user_id();
 
-        let mut media = Media::new(
+        let mut media = UnsavedMedia::new(
             content_hash,
             "test.webp".to_string(),
             media_type,
             "ab/cd/ef/test".to_string(),
             1024,
             user_id,
-        );
+        )
+        .into_media(MediaId::new(1));
         let initial_updated_at = media.updated_at;
 
         std::thread::sleep(std::time::Duration::from_millis(1));
@@ -1,7 +1,15 @@
+mod id;
+pub mod idempotency_key;
 pub mod media;
 pub mod recipe;
+pub mod resumable_upload;
+pub mod upload_session;
 pub mod user;
 
+pub use id::InvalidIdError;
+pub use idempotency_key::*;
 pub use media::*;
 pub use recipe::*;
+pub use resumable_upload::*;
+pub use upload_session::*;
 pub use user::*;
@@ -1,86 +1,54 @@
 use serde::{Deserialize, Serialize};
 
-/// Unique identifier for recipes (database BIGSERIAL)
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
-pub struct RecipeId(i64);
-
-impl RecipeId {
-    #[must_use]
-    pub fn new(id: i64) -> Self {
-        Self(id)
-    }
+use super::id::positive_id;
 
-    #[must_use]
-    pub fn as_i64(&self) -> i64 {
-        self.0
-    }
+positive_id! {
+    /// Unique identifier for recipes (database BIGSERIAL)
+    RecipeId
 }
 
-impl std::fmt::Display for RecipeId {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.0)
-    }
+positive_id! {
+    /// Unique identifier for ingredients (database BIGSERIAL)
+    IngredientId
 }
 
-impl From<i64> for RecipeId {
-    fn from(id: i64) -> Self {
-        Self(id)
-    }
+positive_id! {
+    /// Unique identifier for recipe steps (database BIGSERIAL)
+    StepId
 }
 
-/// Unique identifier for ingredients (database BIGSERIAL)
+/// Where media is attached within a recipe
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
-pub struct IngredientId(i64);
-
-impl IngredientId {
-    #[must_use]
-    pub fn new(id: i64) -> Self {
-        Self(id)
-    }
-
-    #[must_use]
-    pub fn as_i64(&self) -> i64 {
-        self.0
-    }
-}
-
-impl std::fmt::Display for IngredientId {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.0)
-    }
+#[serde(rename_all = "snake_case")]
+pub enum MediaAssociation {
+    /// The recipe as a whole, such as its cover photo
+    Recipe(RecipeId),
+    /// One of the recipe's ingredients
+    Ingredient(RecipeId, IngredientId),
+    /// One of the recipe's steps
+    Step(RecipeId, StepId),
 }
 
-impl From<i64> for IngredientId {
-    fn from(id: i64) -> Self {
-        Self(id)
+impl MediaAssociation {
+    /// The recipe the media is attached to
+    pub fn recipe_id(self) -> RecipeId {
+        match self {
+            Self::Recipe(recipe_id) | Self::Ingredient(recipe_id, _) | Self::Step(recipe_id, _) => {
+                recipe_id
+            }
+        }
     }
 }
 
-/// Unique identifier for recipe steps (database BIGSERIAL)
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
-pub struct StepId(i64);
-
-impl StepId {
-    #[must_use]
-    pub fn new(id: i64) -> Self {
-        Self(id)
-    }
-
-    #[must_use]
-    pub fn as_i64(&self) -> i64 {
-        self.0
-    }
-}
-
-impl std::fmt::Display for StepId {
+impl std::fmt::Display for MediaAssociation {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.0)
-    }
-}
-
-impl From<i64> for StepId {
-    fn from(id: i64) -> Self {
-        Self(id)
+        match self {
+            Self::Recipe(recipe_id) => write!(f, "recipe {recipe_id}"),
+            Self::Ingredient(recipe_id, ingredient_id) => {
+                write!(f, "ingredient {ingredient_id} of recipe {recipe_id}")
+            }
+            Self::Step(recipe_id, step_id) => write!(f, "step {step_id} of recipe {recipe_id}"),
+        }
     }
 }
 
@@ -97,7 +65,7 @@ mod tests {
         assert_eq!(id1.as_i64(), 1);
         assert_eq!(id2.as_i64(), 2);
 
-        let id_from_i64 = RecipeId::from(42);
+        let id_from_i64 = RecipeId::try_from(42).unwrap();
         assert_eq!(id_from_i64.as_i64(), 42);
 
         let id_string = id1.to_string();
@@ -113,7 +81,7 @@ mod tests {
         assert_eq!(id1.as_i64(), 10);
         assert_eq!(id2.as_i64(), 20);
 
-        let id_from_i64 = IngredientId::from(99);
+        let id_from_i64 = IngredientId::try_from(99).unwrap();
         assert_eq!(id_from_i64.as_i64(), 99);
 
         let id_string = id1.to_string();
@@ -129,10 +97,48 @@ mod tests {
         assert_eq!(id1.as_i64(), 100);
         assert_eq!(id2.as_i64(), 200);
 
-        let id_from_i64 = StepId::from(555);
+        let id_from_i64 = StepId::try_from(555).unwrap();
         assert_eq!(id_from_i64.as_i64(), 555);
 
         let id_string = id1.to_string();
         assert_eq!(id_string, "100");
     }
+
+    #[test]
+    fn test_ids_reject_non_positive_values() {
+        assert!(RecipeId::try_new(0).is_err());
+        assert!(IngredientId::try_from(-1).is_err());
+
+        let error = StepId::try_new(-5).unwrap_err();
+        assert_eq!(error.kind, "StepId");
+        assert_eq!(error.to_string(), "StepId must be a positive integer, got -5");
+    }
+
+    #[test]
+    fn test_id_deserialization_validates() {
+        let id: RecipeId = serde_json::from_str("7").unwrap();
+        assert_eq!(id, RecipeId::new(7));
+        assert_eq!(serde_json::to_string(&id).unwrap(), "7");
+
+        assert!(serde_json::from_str::<RecipeId>("0").is_err());
+        assert!(serde_json::from_str::<StepId>("-3").is_err());
+    }
+
+    #[test]
+    fn test_media_association_display() {
+        let recipe = RecipeId::new(42);
+
+        assert_eq!(MediaAssociation::Recipe(recipe).to_string(), "recipe 42");
+        assert_eq!(
+            MediaAssociation::Ingredient(recipe, IngredientId::new(7)).to_string(),
+            "ingredient 7 of recipe 42"
+        );
+        assert_eq!(MediaAssociation::Step(recipe, StepId::new(3)).recipe_id(), RecipeId::new(42));
+    }
+
+    #[test]
+    #[should_panic(expected = "IngredientId must be a positive integer")]
+    fn test_new_panics_on_zero() {
+        let _ = IngredientId::new(0);
+    }
 }
@@ -0,0 +1,165 @@
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, SystemTime};
+
+use crate::domain::{entities::UserId, value_objects::UuidVersion};
+
+/// Identifier of a resumable (tus) upload
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct UploadId(uuid::Uuid);
+
+impl UploadId {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::generate(UuidVersion::default())
+    }
+
+    /// Generate an identifier from a UUID of the given version
+    #[must_use]
+    pub fn generate(version: UuidVersion) -> Self {
+        Self(version.new_uuid())
+    }
+
+    #[must_use]
+    pub fn from_uuid(uuid: uuid::Uuid) -> Self {
+        Self(uuid)
+    }
+
+    #[must_use]
+    pub fn as_uuid(&self) -> uuid::Uuid {
+        self.0
+    }
+}
+
+impl Default for UploadId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Display for UploadId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::str::FromStr for UploadId {
+    type Err = uuid::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        uuid::Uuid::parse_str(s).map(Self)
+    }
+}
+
+/// An upload whose content arrives in chunks over several requests
+///
+/// The declared length is fixed at creation; the offset advances as chunks are
+/// appended until it reaches the length, at which point the upload is complete.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResumableUpload {
+    pub id: UploadId,
+    pub user_id: UserId,
+    /// Client ID of the token the upload was created with, if any
+    pub tenant: Option<String>,
+    pub upload_length: u64,
+    pub upload_offset: u64,
+    pub filename: Option<String>,
+    pub content_type: Option<String>,
+    pub created_at: SystemTime,
+    pub expires_at: SystemTime,
+}
+
+impl ResumableUpload {
+    /// Create a new, empty resumable upload created at `now` that expires after `ttl`
+    #[must_use]
+    pub fn new(
+        id: UploadId,
+        user_id: UserId,
+        upload_length: u64,
+        filename: Option<String>,
+        content_type: Option<String>,
+        now: SystemTime,
+        ttl: Duration,
+    ) -> Self {
+        Self {
+            id,
+            user_id,
+            tenant: None,
+            upload_length,
+            upload_offset: 0,
+            filename,
+            content_type,
+            created_at: now,
+            expires_at: now + ttl,
+        }
+    }
+
+    /// Number of bytes still expected
+    #[must_use]
+    pub fn remaining(&self) -> u64 {
+        self.upload_length.saturating_sub(self.upload_offset)
+    }
+
+    /// Check if all declared bytes have been received
+    #[must_use]
+    pub fn is_complete(&self) -> bool {
+        self.upload_offset >= self.upload_length
+    }
+
+    /// Check if the upload can no longer be resumed at `now`
+    #[must_use]
+    pub fn is_expired_at(&self, now: SystemTime) -> bool {
+        now >= self.expires_at
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_upload_id_round_trips_through_string() {
+        let id = UploadId::new();
+        let parsed: UploadId = id.to_string().parse().unwrap();
+        assert_eq!(parsed, id);
+        assert!("not-a-uuid".parse::<UploadId>().is_err());
+    }
+
+    #[test]
+    fn test_new_upload_starts_empty() {
+        let now = SystemTime::now();
+        let upload = ResumableUpload::new(
+            UploadId::new(),
+            UserId::new(),
+            1024,
+            Some("video.mp4".to_string()),
+            Some("video/mp4".to_string()),
+            now,
+            Duration::from_hours(24),
+        );
+
+        assert_eq!(upload.upload_offset, 0);
+        assert_eq!(upload.remaining(), 1024);
+        assert!(!upload.is_complete());
+        assert!(!upload.is_expired_at(now));
+        assert!(upload.is_expired_at(now + Duration::from_hours(24)));
+    }
+
+    #[test]
+    fn test_upload_completion_and_expiry() {
+        let now = SystemTime::now();
+        let mut upload = ResumableUpload::new(
+            UploadId::new(),
+            UserId::new(),
+            10,
+            None,
+            None,
+            now,
+            Duration::ZERO,
+        );
+        upload.upload_offset = 10;
+
+        assert!(upload.is_complete());
+        assert_eq!(upload.remaining(), 0);
+        assert!(upload.is_expired_at(now));
+    }
+}
@@ -0,0 +1,60 @@
+use serde::{Deserialize, Serialize};
+use std::time::SystemTime;
+
+use crate::domain::entities::{MediaId, UserId};
+
+/// A presigned upload that has been initiated but not yet received
+///
+/// The upload token handed to the client maps back to the pending media record
+/// created at initiation, along with the file details the client committed to.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PresignedUploadSession {
+    pub upload_token: String,
+    pub media_id: MediaId,
+    pub user_id: UserId,
+    /// Client ID of the token the upload was initiated with, if any
+    pub tenant: Option<String>,
+    pub filename: String,
+    pub content_type: String,
+    pub expected_size: u64,
+    pub created_at: SystemTime,
+    pub expires_at: SystemTime,
+}
+
+impl PresignedUploadSession {
+    /// Check if the session can no longer accept an upload at `now`
+    #[must_use]
+    pub fn is_expired_at(&self, now: SystemTime) -> bool {
+        now >= self.expires_at
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn create_test_session(expires_at: SystemTime) -> PresignedUploadSession {
+        PresignedUploadSession {
+            upload_token: "upload_abc".to_string(),
+            media_id: MediaId::new(7),
+            user_id: UserId::new(),
+            tenant: None,
+            filename: "photo.jpg".to_string(),
+            content_type: "image/jpeg".to_string(),
+            expected_size: 1024,
+            created_at: SystemTime::now(),
+            expires_at,
+        }
+    }
+
+    #[test]
+    fn test_session_expiry() {
+        let now = SystemTime::now();
+
+        assert!(!create_test_session(now + Duration::from_mins(15)).is_expired_at(now));
+        assert!(create_test_session(now + Duration::from_mins(15))
+            .is_expired_at(now + Duration::from_mins(15)));
+        assert!(create_test_session(now - Duration::from_secs(1)).is_expired_at(now));
+    }
+}
@@ -0,0 +1,138 @@
+//! Events raised by changes to media
+//!
+//! Use cases publish them through the application's event bus; repositories
+//! with an outbox store them together with the change that raised them.
+
+use serde::{Deserialize, Serialize};
+
+use crate::domain::{
+    entities::{MediaAssociation, MediaId, UnsavedMedia, UserId},
+    value_objects::MediaType,
+};
+
+/// Something that happened to a media item
+///
+/// Every event names the media's uploader in `uploaded_by`, so it can be routed
+/// to them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DomainEvent {
+    /// New content was stored; deduplicated uploads reuse existing media and are not announced
+    MediaUploaded {
+        media_id: MediaId,
+        uploaded_by: UserId,
+        media_type: MediaType,
+        file_size: u64,
+    },
+    /// Variants were generated and the media is ready to serve
+    ///
+    /// `reprocessed` is set when existing media was processed again, e.g. by a backfill.
+    ProcessingCompleted {
+        media_id: MediaId,
+        uploaded_by: UserId,
+        media_type: MediaType,
+        reprocessed: bool,
+    },
+    /// Processing failed with `error`; the media is kept without variants
+    ProcessingFailed {
+        media_id: MediaId,
+        uploaded_by: UserId,
+        media_type: MediaType,
+        error: String,
+        reprocessed: bool,
+    },
+    MediaDeleted {
+        media_id: MediaId,
+        uploaded_by: UserId,
+    },
+    MediaTagsChanged {
+        media_id: MediaId,
+        uploaded_by: UserId,
+    },
+    /// Media was attached to a recipe, ingredient or step it was not attached to before
+    MediaAttached {
+        media_id: MediaId,
+        uploaded_by: UserId,
+        association: MediaAssociation,
+    },
+    MediaDetached {
+        media_id: MediaId,
+        uploaded_by: UserId,
+        association: MediaAssociation,
+    },
+}
+
+impl DomainEvent {
+    /// The event announcing `media`, newly stored as `media_id`
+    #[must_use]
+    pub fn uploaded(media_id: MediaId, media: &UnsavedMedia) -> Self {
+        Self::MediaUploaded {
+            media_id,
+            uploaded_by: media.uploaded_by,
+            media_type: media.media_type.clone(),
+            file_size: media.file_size,
+        }
+    }
+
+    /// Name of the event, labelling metrics and logs
+    #[must_use]
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::MediaUploaded { .. } => "media_uploaded",
+            Self::ProcessingCompleted { .. } => "processing_completed",
+            Self::ProcessingFailed { .. } => "processing_failed",
+            Self::MediaDeleted { .. } => "media_deleted",
+            Self::MediaTagsChanged { .. } => "media_tags_changed",
+            Self::MediaAttached { .. } => "media_attached",
+            Self::MediaDetached { .. } => "media_detached",
+        }
+    }
+
+    /// The media the event is about
+    #[must_use]
+    pub fn media_id(&self) -> MediaId {
+        match self {
+            Self::MediaUploaded { media_id, .. }
+            | Self::ProcessingCompleted { media_id, .. }
+            | Self::ProcessingFailed { media_id, .. }
+            | Self::MediaDeleted { media_id, .. }
+            | Self::MediaTagsChanged { media_id, .. }
+            | Self::MediaAttached { media_id, .. }
+            | Self::MediaDetached { media_id, .. } => *media_id,
+        }
+    }
+
+    /// The user who uploaded the media
+    #[must_use]
+    pub fn uploaded_by(&self) -> UserId {
+        match self {
+            Self::MediaUploaded { uploaded_by, .. }
+            | Self::ProcessingCompleted { uploaded_by, .. }
+            | Self::ProcessingFailed { uploaded_by, .. }
+            | Self::MediaDeleted { uploaded_by, .. }
+            | Self::MediaTagsChanged { uploaded_by, .. }
+            | Self::MediaAttached { uploaded_by, .. }
+            | Self::MediaDetached { uploaded_by, .. } => *uploaded_by,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::entities::{RecipeId, StepId};
+
+    #[test]
+    fn test_events_round_trip_through_json() {
+        let event = DomainEvent::MediaAttached {
+            media_id: MediaId::new(5),
+            uploaded_by: UserId::new(),
+            association: MediaAssociation::Step(RecipeId::new(7), StepId::new(2)),
+        };
+
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["type"], "media_attached");
+        assert_eq!(json["association"], serde_json::json!({ "step": [7, 2] }));
+        assert_eq!(serde_json::from_value::<DomainEvent>(json).unwrap(), event);
+    }
+}
@@ -1,4 +1,5 @@
 pub mod entities;
+pub mod events;
 pub mod repositories;
 pub mod services;
 pub mod value_objects;
@@ -1,25 +1,149 @@
-use crate::domain::entities::{IngredientId, Media, MediaId, RecipeId, StepId, UserId};
-use crate::domain::value_objects::{ContentHash, ProcessingStatus};
+use crate::domain::entities::{
+    IdempotencyRecord, IngredientId, Media, MediaAssociation, MediaId, MediaVariant,
+    PresignedUploadSession, RecipeId, ResumableUpload, StepId, UnsavedMedia, UploadId, UserId,
+};
+use crate::domain::events::DomainEvent;
+use crate::domain::value_objects::{
+    CapacityDimension, CapacityGrowth, ContentHash, MediaSearch, MediaSort, MediaTag,
+    ProcessingStatus, StorageUsage,
+};
 use async_trait::async_trait;
+use std::time::SystemTime;
+
+/// Result of persisting a media entity whose content may already be stored
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SaveOutcome {
+    /// A new media row was inserted
+    Created(MediaId),
+    /// A row with the same content hash already existed and was reused
+    Reused(MediaId),
+}
+
+impl SaveOutcome {
+    /// ID of the created or reused media row
+    pub fn media_id(self) -> MediaId {
+        match self {
+            Self::Created(id) | Self::Reused(id) => id,
+        }
+    }
+
+    /// Whether a new row was inserted
+    pub fn was_created(self) -> bool {
+        matches!(self, Self::Created(_))
+    }
+}
+
+/// A change to stored media that raises events once it takes effect
+#[derive(Debug, Clone, Copy)]
+pub enum MediaChange<'a> {
+    Update(&'a Media),
+    SetTags(MediaId, &'a [MediaTag]),
+    Delete(MediaId),
+    AddAssociation(MediaId, MediaAssociation),
+    RemoveAssociation(MediaId, MediaAssociation),
+}
+
+/// Outcome of a change, and whether the events it raised were stored with it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Recorded<T> {
+    pub outcome: T,
+    /// The events went into the outbox in the same transaction as the change,
+    /// and are published once relayed. Otherwise the caller publishes them.
+    pub events_recorded: bool,
+}
+
+impl<T> Recorded<T> {
+    /// An outcome whose events are left to the caller
+    pub fn unrecorded(outcome: T) -> Self {
+        Self { outcome, events_recorded: false }
+    }
+}
+
+/// Where relayed events are handed on, typically the event bus
+pub type EventSink = dyn Fn(&DomainEvent) + Send + Sync;
 
 /// Repository trait for media persistence
 #[async_trait]
 pub trait MediaRepository: Send + Sync {
     type Error: std::error::Error + Send + Sync + 'static;
 
-    /// Save a media entity and return the assigned database ID
-    async fn save(&self, media: &Media) -> Result<MediaId, Self::Error>;
+    /// Insert a new media entity and return the ID the database assigned
+    async fn save(&self, media: &UnsavedMedia) -> Result<MediaId, Self::Error>;
+
+    /// Save a media entity, reusing the row its uploader already stores the content hash in
+    ///
+    /// Implementations backed by a database should perform this atomically so that
+    /// concurrent writers never surface a unique-constraint violation. The default
+    /// implementation is a non-atomic lookup followed by an insert.
+    async fn save_or_reuse(&self, media: &UnsavedMedia) -> Result<SaveOutcome, Self::Error> {
+        match self.find_by_content_hash(&media.content_hash, Some(media.uploaded_by)).await? {
+            Some(existing) => Ok(SaveOutcome::Reused(existing.id)),
+            None => self.save(media).await.map(SaveOutcome::Created),
+        }
+    }
+
+    /// Save like [`Self::save_or_reuse`], recording a `MediaUploaded` event if a row is created
+    ///
+    /// The default records nothing, leaving the event to the caller.
+    async fn save_or_reuse_announced(
+        &self,
+        media: &UnsavedMedia,
+    ) -> Result<Recorded<SaveOutcome>, Self::Error> {
+        self.save_or_reuse(media).await.map(Recorded::unrecorded)
+    }
 
     /// Find media by ID
     async fn find_by_id(&self, id: MediaId) -> Result<Option<Media>, Self::Error>;
 
-    /// Find media by content hash
-    async fn find_by_content_hash(&self, hash: &ContentHash) -> Result<Option<Media>, Self::Error>;
+    /// Find every media with one of the given IDs, in no particular order
+    ///
+    /// IDs without media are skipped. The default implementation looks each ID up
+    /// separately; database-backed implementations should use a single query.
+    async fn find_by_ids(&self, ids: &[MediaId]) -> Result<Vec<Media>, Self::Error> {
+        let mut found = Vec::with_capacity(ids.len());
+        for id in ids {
+            if let Some(media) = self.find_by_id(*id).await? {
+                found.push(media);
+            }
+        }
+        Ok(found)
+    }
+
+    /// Find media by content hash, among the media of `owner` if given
+    ///
+    /// Uploads are deduplicated per owner, so several users may each have a row
+    /// for the same content; without an owner any one of them is returned.
+    async fn find_by_content_hash(
+        &self,
+        hash: &ContentHash,
+        owner: Option<UserId>,
+    ) -> Result<Option<Media>, Self::Error>;
 
     /// Find all media uploaded by a specific user
     async fn find_by_user(&self, user_id: UserId) -> Result<Vec<Media>, Self::Error>;
 
-    /// Find media by user with cursor-based pagination
+    /// Find media of every user in ascending ID order, starting after `after`
+    ///
+    /// Used by maintenance jobs that walk the whole library in pages.
+    async fn find_after(
+        &self,
+        after: Option<MediaId>,
+        limit: u32,
+    ) -> Result<Vec<Media>, Self::Error>;
+
+    /// Find media still `Pending` or `Processing` that was last updated before
+    /// `updated_before`, least recently updated first
+    ///
+    /// Presigned upload placeholders that never received content are left out.
+    async fn find_unprocessed_before(
+        &self,
+        updated_before: SystemTime,
+        limit: u32,
+    ) -> Result<Vec<Media>, Self::Error>;
+
+    /// Find media by user with cursor-based pagination, in `sort` order
+    ///
+    /// When `tags` is not empty, only media carrying every one of them is returned.
     /// Returns a tuple of (`media_list`, `next_cursor`, `has_more`)
     async fn find_by_user_paginated(
         &self,
@@ -27,14 +151,103 @@ pub trait MediaRepository: Send + Sync {
         cursor: Option<String>,
         limit: u32,
         status_filter: Option<ProcessingStatus>,
+        tags: &[MediaTag],
+        sort: MediaSort,
+    ) -> Result<(Vec<Media>, Option<String>, bool), Self::Error>;
+
+    /// Find media by user matching every criterion of `search`, with cursor-based pagination
+    /// Returns a tuple of (`media_list`, `next_cursor`, `has_more`)
+    async fn search_by_user(
+        &self,
+        user_id: UserId,
+        search: &MediaSearch,
+        cursor: Option<String>,
+        limit: u32,
     ) -> Result<(Vec<Media>, Option<String>, bool), Self::Error>;
 
     /// Update media entity
     async fn update(&self, media: &Media) -> Result<(), Self::Error>;
 
+    /// Flag media whose stored content an integrity scan found damaged, or clear the flag
+    ///
+    /// Returns false if the media does not exist. Only the flag is written, so a
+    /// concurrent update is never overwritten; the default implementation is a
+    /// non-atomic lookup followed by an update.
+    async fn set_corrupted_at(
+        &self,
+        media_id: MediaId,
+        corrupted_at: Option<SystemTime>,
+    ) -> Result<bool, Self::Error> {
+        let Some(mut media) = self.find_by_id(media_id).await? else {
+            return Ok(false);
+        };
+        media.corrupted_at = corrupted_at;
+        self.update(&media).await?;
+        Ok(true)
+    }
+
+    /// Replace the tags on a media file
+    /// Returns false if the media does not exist
+    async fn set_tags(&self, media_id: MediaId, tags: &[MediaTag]) -> Result<bool, Self::Error>;
+
+    /// Add `variant` to the variants of a media file
+    ///
+    /// Returns false if the media does not exist or already has a variant of the
+    /// same name. Only the variant is appended, so concurrent writers never lose
+    /// each other's variants; the default implementation is a non-atomic lookup
+    /// followed by an update.
+    async fn add_variant(
+        &self,
+        media_id: MediaId,
+        variant: &MediaVariant,
+    ) -> Result<bool, Self::Error> {
+        let Some(mut media) = self.find_by_id(media_id).await? else {
+            return Ok(false);
+        };
+        if media.variants.iter().any(|recorded| recorded.name == variant.name) {
+            return Ok(false);
+        }
+        media.variants.push(variant.clone());
+        self.update(&media).await?;
+        Ok(true)
+    }
+
     /// Delete media by ID
     async fn delete(&self, id: MediaId) -> Result<bool, Self::Error>;
 
+    /// Apply `change`, recording `events` in the same transaction if it takes effect
+    ///
+    /// Returns whether the change took effect, as the method it stands for
+    /// does; updates always do. The default records nothing, leaving `events`
+    /// to the caller.
+    async fn apply(
+        &self,
+        change: MediaChange<'_>,
+        _events: &[DomainEvent],
+    ) -> Result<Recorded<bool>, Self::Error> {
+        let applied = match change {
+            MediaChange::Update(media) => self.update(media).await.map(|()| true),
+            MediaChange::SetTags(media_id, tags) => self.set_tags(media_id, tags).await,
+            MediaChange::Delete(media_id) => self.delete(media_id).await,
+            MediaChange::AddAssociation(media_id, association) => {
+                self.add_association(media_id, association).await
+            }
+            MediaChange::RemoveAssociation(media_id, association) => {
+                self.remove_association(media_id, association).await
+            }
+        }?;
+        Ok(Recorded::unrecorded(applied))
+    }
+
+    /// Hand up to `limit` of the oldest recorded events to `relay`, then remove them
+    ///
+    /// Returns how many events were relayed. Events are removed only after
+    /// `relay` saw them, so one relayed just before a crash is relayed again.
+    /// The default has no outbox and relays nothing.
+    async fn relay_events(&self, _limit: u32, _relay: &EventSink) -> Result<usize, Self::Error> {
+        Ok(0)
+    }
+
     /// Check if media exists by content hash
     async fn exists_by_content_hash(&self, hash: &ContentHash) -> Result<bool, Self::Error>;
 
@@ -58,12 +271,272 @@ pub trait MediaRepository: Send + Sync {
         step_id: StepId,
     ) -> Result<Vec<MediaId>, Self::Error>;
 
+    /// Attach media to a recipe, ingredient or step
+    /// Returns false if the media was already attached there
+    async fn add_association(
+        &self,
+        media_id: MediaId,
+        association: MediaAssociation,
+    ) -> Result<bool, Self::Error>;
+
+    /// Detach media from a recipe, ingredient or step
+    /// Returns false if the media was not attached there
+    async fn remove_association(
+        &self,
+        media_id: MediaId,
+        association: MediaAssociation,
+    ) -> Result<bool, Self::Error>;
+
+    /// Find media associated with a recipe, fetched together with the association
+    /// Returns a tuple of (`media_list`, `next_cursor`, `has_more`) for one page
+    async fn find_media_by_recipe(
+        &self,
+        recipe_id: RecipeId,
+        cursor: Option<String>,
+        limit: u32,
+    ) -> Result<(Vec<Media>, Option<String>, bool), Self::Error>;
+
+    /// Find media associated with a recipe ingredient, fetched together with the association
+    /// Returns a tuple of (`media_list`, `next_cursor`, `has_more`) for one page
+    async fn find_media_by_recipe_ingredient(
+        &self,
+        recipe_id: RecipeId,
+        ingredient_id: IngredientId,
+        cursor: Option<String>,
+        limit: u32,
+    ) -> Result<(Vec<Media>, Option<String>, bool), Self::Error>;
+
+    /// Find media associated with a recipe step, fetched together with the association
+    /// Returns a tuple of (`media_list`, `next_cursor`, `has_more`) for one page
+    async fn find_media_by_recipe_step(
+        &self,
+        recipe_id: RecipeId,
+        step_id: StepId,
+        cursor: Option<String>,
+        limit: u32,
+    ) -> Result<(Vec<Media>, Option<String>, bool), Self::Error>;
+
+    /// Total bytes and number of media files owned by a user
+    ///
+    /// Counts every media row, including uploads that are still pending, so
+    /// reserved space counts against a quota. The default implementation loads
+    /// the user's media; database-backed repositories should aggregate instead.
+    async fn usage_by_user(&self, user_id: UserId) -> Result<StorageUsage, Self::Error> {
+        let media = self.find_by_user(user_id).await?;
+        Ok(StorageUsage {
+            bytes: media.iter().map(|m| m.file_size).sum(),
+            files: media.len() as u64,
+        })
+    }
+
+    /// Media and bytes added per week since `since`, broken down by `dimension`
+    ///
+    /// Running totals include media uploaded before `since`; see
+    /// [`CapacityGrowth::aggregate`]. The default implementation walks every
+    /// media row; database-backed repositories should aggregate instead.
+    async fn capacity_growth(
+        &self,
+        dimension: CapacityDimension,
+        since: SystemTime,
+    ) -> Result<Vec<CapacityGrowth>, Self::Error> {
+        let mut uploads = Vec::new();
+        let mut after = None;
+        loop {
+            let page = self.find_after(after, 500).await?;
+            let Some(last) = page.last() else { break };
+            after = Some(last.id);
+            uploads.extend(page.into_iter().map(|media| {
+                let key = match dimension {
+                    CapacityDimension::MediaType => media.media_type.mime_type().to_string(),
+                    CapacityDimension::Tenant => media.tenant.clone().unwrap_or_default(),
+                };
+                (media.uploaded_at, key, media.file_size)
+            }));
+        }
+        Ok(CapacityGrowth::aggregate(uploads, since))
+    }
+
     /// Health check for repository connectivity
     ///
     /// Performs a simple check to verify repository is accessible and responsive.
     /// Returns `Ok(())` if repository is accessible, `Err(Self::Error)` otherwise.
     async fn health_check(&self) -> Result<(), Self::Error>;
+
+    /// Version of the last schema migration applied, if the store is migrated
+    ///
+    /// The default reports none, for stores without a schema.
+    async fn migration_version(&self) -> Result<Option<i64>, Self::Error> {
+        Ok(None)
+    }
+}
+
+/// Repository trait for resumable upload state
+#[async_trait]
+pub trait ResumableUploadRepository: Send + Sync {
+    type Error: std::error::Error + Send + Sync + 'static;
+
+    /// Persist a newly created upload
+    async fn create(&self, upload: &ResumableUpload) -> Result<(), Self::Error>;
+
+    /// Find an upload by ID
+    async fn find_by_id(&self, id: UploadId) -> Result<Option<ResumableUpload>, Self::Error>;
+
+    /// Advance the upload offset if it still equals `expected_offset`
+    ///
+    /// Returns `false` when another request moved the offset first.
+    async fn update_offset(
+        &self,
+        id: UploadId,
+        expected_offset: u64,
+        new_offset: u64,
+    ) -> Result<bool, Self::Error>;
+
+    /// Delete an upload by ID
+    async fn delete(&self, id: UploadId) -> Result<bool, Self::Error>;
+
+    /// IDs of up to `limit` uploads that expired at or before `now`, oldest first
+    async fn find_expired(&self, now: SystemTime, limit: u32)
+        -> Result<Vec<UploadId>, Self::Error>;
+}
+
+/// Repository trait for presigned upload sessions
+#[async_trait]
+pub trait UploadSessionRepository: Send + Sync {
+    type Error: std::error::Error + Send + Sync + 'static;
+
+    /// Persist a newly initiated session
+    async fn create(&self, session: &PresignedUploadSession) -> Result<(), Self::Error>;
+
+    /// Find a session by its upload token
+    async fn find_by_token(
+        &self,
+        upload_token: &str,
+    ) -> Result<Option<PresignedUploadSession>, Self::Error>;
+
+    /// Delete a session by its upload token
+    async fn delete(&self, upload_token: &str) -> Result<bool, Self::Error>;
+}
+
+/// Requests made with an `Idempotency-Key` and the responses stored for them
+///
+/// A key is reserved while its first request is in flight, and holds the
+/// response afterwards; once a record expires its key is free to be reused.
+#[async_trait]
+pub trait IdempotencyRepository: Send + Sync {
+    type Error: std::error::Error + Send + Sync + 'static;
+
+    /// Record a request as in flight, unless an unexpired record holds its key
+    ///
+    /// Returns `false`, leaving the existing record alone, if the key is taken.
+    /// Records are judged expired as of the new record's `created_at`.
+    async fn reserve(&self, record: &IdempotencyRecord) -> Result<bool, Self::Error>;
+
+    /// Find the record holding `key`, unless it expired by `now`
+    async fn find(
+        &self,
+        key: &str,
+        now: SystemTime,
+    ) -> Result<Option<IdempotencyRecord>, Self::Error>;
+
+    /// Store the response and expiry of a reserved record
+    async fn complete(&self, record: &IdempotencyRecord) -> Result<(), Self::Error>;
+
+    /// Delete the record holding `key`, freeing the key
+    async fn delete(&self, key: &str) -> Result<bool, Self::Error>;
+
+    /// Delete every record that expired by `now`, returning how many were deleted
+    async fn delete_expired(&self, now: SystemTime) -> Result<u64, Self::Error>;
+}
+
+/// Registry of stored variant blobs and how many media variants reference each
+///
+/// Variants are content-addressed, so identical encodings generated for different
+/// originals share one blob. The reference count decides when that blob can be
+/// removed from storage.
+#[async_trait]
+pub trait VariantRepository: Send + Sync {
+    type Error: std::error::Error + Send + Sync + 'static;
+
+    /// Record a reference to a variant blob, registering it on first use
+    ///
+    /// Returns the reference count after the increment; `1` means no other media
+    /// referenced the blob, so it may still need to be written to storage.
+    async fn acquire(&self, variant: &MediaVariant) -> Result<u64, Self::Error>;
+
+    /// Drop a reference to a variant blob, unregistering it when none remain
+    ///
+    /// Returns the remaining reference count, or `None` if the blob is not
+    /// registered. Only a count of `Some(0)` means the blob can be deleted.
+    async fn release(&self, content_hash: &ContentHash) -> Result<Option<u64>, Self::Error>;
+
+    /// Current reference count of a variant blob, `0` if it is not registered
+    async fn reference_count(&self, content_hash: &ContentHash) -> Result<u64, Self::Error>;
+}
+
+/// A held content lock, released when dropped
+pub type ContentLease = Box<dyn Send + Sync>;
+
+/// Locks on content hashes shared by every instance using the same store
+///
+/// A blob can be shared by media on any instance, so deciding whether it is
+/// still referenced and deleting it must not interleave with an upload of the
+/// same content saving its record, wherever that upload runs.
+#[async_trait]
+pub trait ContentLockRepository: Send + Sync {
+    type Error: std::error::Error + Send + Sync + 'static;
+
+    /// Wait until no other holder has the lock of `hash`, then hold it until the
+    /// returned lease is dropped
+    ///
+    /// Implementations may give up after a bounded wait and return an error.
+    async fn lock(&self, hash: &ContentHash) -> Result<ContentLease, Self::Error>;
 }
 
 // Mock implementation moved to test utilities
 // This avoids complex generic type issues with mockall
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::value_objects::MediaType;
+    use crate::test_utils::mocks::InMemoryMediaRepository;
+
+    #[test]
+    fn test_save_outcome_accessors() {
+        let created = SaveOutcome::Created(MediaId::new(1));
+        let reused = SaveOutcome::Reused(MediaId::new(2));
+
+        assert_eq!(created.media_id(), MediaId::new(1));
+        assert!(created.was_created());
+        assert_eq!(reused.media_id(), MediaId::new(2));
+        assert!(!reused.was_created());
+    }
+
+    #[tokio::test]
+    async fn test_save_or_reuse_returns_existing_row_for_same_hash() {
+        let repo = InMemoryMediaRepository::new();
+        let hash =
+            ContentHash::new("abcdef1234567890abcdef1234567890abcdef1234567890abcdef1234567890")
+                .unwrap();
+        let owner = UserId::new();
+        let media = |name: &str, uploaded_by: UserId| {
+            UnsavedMedia::new(
+                hash.clone(),
+                name.to_string(),
+                MediaType::new("image/png"),
+                "ab/cd/ef/hash".to_string(),
+                1024,
+                uploaded_by,
+            )
+        };
+
+        let first = repo.save_or_reuse(&media("first.png", owner)).await.unwrap();
+        let second = repo.save_or_reuse(&media("second.png", owner)).await.unwrap();
+        let other = repo.save_or_reuse(&media("other.png", UserId::new())).await.unwrap();
+
+        assert!(first.was_created());
+        assert_eq!(second, SaveOutcome::Reused(first.media_id()));
+        assert!(other.was_created());
+        assert_ne!(other.media_id(), first.media_id());
+    }
+}
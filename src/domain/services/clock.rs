@@ -0,0 +1,21 @@
+use std::time::SystemTime;
+
+/// Source of the current time
+///
+/// Anything whose behaviour depends on the time (expiry checks, token validity,
+/// retention) takes a clock instead of reading the system time, so tests can
+/// control it.
+pub trait Clock: Send + Sync {
+    /// The current time
+    fn now(&self) -> SystemTime;
+}
+
+/// The system's wall clock
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
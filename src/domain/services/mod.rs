@@ -1,2 +1,3 @@
-// Domain services will be added here as needed
-// Examples: media validation, content analysis, etc.
+pub mod clock;
+
+pub use clock::*;
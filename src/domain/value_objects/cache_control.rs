@@ -0,0 +1,121 @@
+use std::fmt::Write;
+use std::time::Duration;
+
+/// How long clients and shared caches may keep each kind of response
+///
+/// Responses to requests made with a token are `private`, so only the client
+/// keeps them; the rest are `public` and, with a `shared_max_age`, cached by a
+/// CDN for that long. Shared caches are told which media and recipes a
+/// response shows through surrogate keys, so it can be purged when they change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CacheControlPolicy {
+    /// How long a download may be reused
+    pub content_max_age: Duration,
+    /// How long content addressed by its hash may be reused; it never changes
+    pub immutable_max_age: Duration,
+    /// How long media metadata may be reused; zero has clients revalidate every time
+    pub metadata_max_age: Duration,
+    /// How long a CDN may keep public responses, if at all
+    pub shared_max_age: Option<Duration>,
+    /// Whether responses name the media and recipes they show in `Surrogate-Key`
+    pub surrogate_keys: bool,
+}
+
+impl Default for CacheControlPolicy {
+    fn default() -> Self {
+        Self {
+            content_max_age: Duration::from_hours(1),
+            immutable_max_age: Duration::from_hours(365 * 24),
+            metadata_max_age: Duration::ZERO,
+            shared_max_age: None,
+            surrogate_keys: false,
+        }
+    }
+}
+
+impl CacheControlPolicy {
+    /// `Cache-Control` of a download, by whether it was made with a token
+    #[must_use]
+    pub fn content(&self, private: bool) -> String {
+        self.directives(private, self.content_max_age)
+    }
+
+    /// `Cache-Control` of content addressed by its hash
+    #[must_use]
+    pub fn immutable(&self, private: bool) -> String {
+        let mut value = self.directives(private, self.immutable_max_age);
+        value.push_str(", immutable");
+        value
+    }
+
+    /// `Cache-Control` of media metadata, by whether it was requested with a token
+    #[must_use]
+    pub fn metadata(&self, private: bool) -> String {
+        if self.metadata_max_age.is_zero() {
+            return "no-cache".to_string();
+        }
+        self.directives(private, self.metadata_max_age)
+    }
+
+    /// `Surrogate-Key` naming `media` and `recipes`, unless surrogate keys are off
+    #[must_use]
+    pub fn surrogate_key(&self, media: &[i64], recipes: &[i64]) -> Option<String> {
+        if !self.surrogate_keys || (media.is_empty() && recipes.is_empty()) {
+            return None;
+        }
+        let keys = media
+            .iter()
+            .map(|id| media_surrogate_key(*id))
+            .chain(recipes.iter().map(|id| recipe_surrogate_key(*id)));
+        Some(keys.collect::<Vec<_>>().join(" "))
+    }
+
+    fn directives(&self, private: bool, max_age: Duration) -> String {
+        if private {
+            return format!("private, max-age={}", max_age.as_secs());
+        }
+        let mut value = format!("public, max-age={}", max_age.as_secs());
+        if let Some(shared_max_age) = self.shared_max_age {
+            let _ = write!(value, ", s-maxage={}", shared_max_age.as_secs());
+        }
+        value
+    }
+}
+
+/// Surrogate key of responses showing the media with `id`
+#[must_use]
+pub fn media_surrogate_key(id: i64) -> String {
+    format!("media-{id}")
+}
+
+/// Surrogate key of responses listing the media of the recipe with `id`
+#[must_use]
+pub fn recipe_surrogate_key(id: i64) -> String {
+    format!("recipe-{id}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_directives_by_kind_and_audience() {
+        let policy = CacheControlPolicy {
+            metadata_max_age: Duration::from_secs(30),
+            shared_max_age: Some(Duration::from_hours(24)),
+            surrogate_keys: true,
+            ..CacheControlPolicy::default()
+        };
+
+        assert_eq!(policy.content(true), "private, max-age=3600");
+        assert_eq!(policy.content(false), "public, max-age=3600, s-maxage=86400");
+        assert_eq!(policy.immutable(true), "private, max-age=31536000, immutable");
+        assert_eq!(policy.metadata(false), "public, max-age=30, s-maxage=86400");
+        assert_eq!(CacheControlPolicy::default().metadata(false), "no-cache");
+        assert_eq!(CacheControlPolicy::default().content(false), "public, max-age=3600");
+
+        assert_eq!(policy.surrogate_key(&[5], &[2]).as_deref(), Some("media-5 recipe-2"));
+        assert_eq!(policy.surrogate_key(&[], &[]), None);
+        assert_eq!(CacheControlPolicy::default().surrogate_key(&[5], &[]), None);
+    }
+}
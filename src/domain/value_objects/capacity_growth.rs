@@ -0,0 +1,120 @@
+use std::collections::BTreeMap;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const WEEK: Duration = Duration::from_hours(7 * 24);
+
+/// The Unix epoch was a Thursday; weeks start three days later, on Monday
+const FIRST_MONDAY: Duration = Duration::from_hours(4 * 24);
+
+/// What capacity growth is broken down by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CapacityDimension {
+    /// The media's MIME type
+    MediaType,
+    /// The client the media was uploaded with, empty for media uploaded without a token
+    Tenant,
+}
+
+/// Media added during one week under one key, with running totals
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CapacityGrowth {
+    /// Start of the week, Monday 00:00 UTC
+    pub week_start: SystemTime,
+    /// MIME type or tenant, depending on the dimension
+    pub key: String,
+    pub media_added: u64,
+    pub bytes_added: u64,
+    /// Media under this key by the end of the week, including earlier weeks
+    pub total_media: u64,
+    /// Bytes under this key by the end of the week, including earlier weeks
+    pub total_bytes: u64,
+}
+
+impl CapacityGrowth {
+    /// Aggregate uploads, given as upload time, key and size, into weekly growth
+    ///
+    /// Only weeks starting at or after `since` are returned, ordered by week and
+    /// key, but their totals include earlier uploads. Weeks in which nothing was
+    /// added under a key have no entry for it.
+    pub fn aggregate(
+        uploads: impl IntoIterator<Item = (SystemTime, String, u64)>,
+        since: SystemTime,
+    ) -> Vec<Self> {
+        let mut weekly: BTreeMap<(String, SystemTime), (u64, u64)> = BTreeMap::new();
+        for (uploaded_at, key, bytes) in uploads {
+            let added = weekly.entry((key, week_start(uploaded_at))).or_default();
+            added.0 += 1;
+            added.1 += bytes;
+        }
+
+        let mut growth = Vec::new();
+        let (mut total_media, mut total_bytes) = (0, 0);
+        let mut previous_key: Option<&String> = None;
+        for ((key, week), (media_added, bytes_added)) in &weekly {
+            if previous_key != Some(key) {
+                (total_media, total_bytes) = (0, 0);
+                previous_key = Some(key);
+            }
+            total_media += media_added;
+            total_bytes += bytes_added;
+            if *week >= since {
+                growth.push(Self {
+                    week_start: *week,
+                    key: key.clone(),
+                    media_added: *media_added,
+                    bytes_added: *bytes_added,
+                    total_media,
+                    total_bytes,
+                });
+            }
+        }
+
+        growth.sort_by(|a, b| a.week_start.cmp(&b.week_start).then_with(|| a.key.cmp(&b.key)));
+        growth
+    }
+}
+
+/// Start of the week containing `time`, Monday 00:00 UTC
+#[must_use]
+pub fn week_start(time: SystemTime) -> SystemTime {
+    let Some(since_monday) = time.duration_since(UNIX_EPOCH + FIRST_MONDAY).ok() else {
+        return UNIX_EPOCH;
+    };
+    let weeks = since_monday.as_secs() / WEEK.as_secs();
+    UNIX_EPOCH + FIRST_MONDAY + WEEK * u32::try_from(weeks).unwrap_or(u32::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(rfc3339: &str) -> SystemTime {
+        chrono::DateTime::parse_from_rfc3339(rfc3339).unwrap().into()
+    }
+
+    #[test]
+    fn test_weeks_start_on_monday() {
+        assert_eq!(week_start(at("2025-01-15T10:30:00Z")), at("2025-01-13T00:00:00Z"));
+        assert_eq!(week_start(at("2025-01-13T00:00:00Z")), at("2025-01-13T00:00:00Z"));
+        assert_eq!(week_start(at("2025-01-12T23:59:59Z")), at("2025-01-06T00:00:00Z"));
+    }
+
+    #[test]
+    fn test_totals_include_weeks_before_since() {
+        let uploads = [
+            (at("2025-01-01T00:00:00Z"), "image/png".to_string(), 100),
+            (at("2025-01-14T00:00:00Z"), "image/png".to_string(), 50),
+            (at("2025-01-15T00:00:00Z"), "image/png".to_string(), 25),
+            (at("2025-01-15T00:00:00Z"), "video/mp4".to_string(), 1000),
+        ];
+
+        let growth = CapacityGrowth::aggregate(uploads, at("2025-01-13T00:00:00Z"));
+
+        assert_eq!(growth.len(), 2);
+        assert_eq!(growth[0].key, "image/png");
+        assert_eq!((growth[0].media_added, growth[0].bytes_added), (2, 75));
+        assert_eq!((growth[0].total_media, growth[0].total_bytes), (3, 175));
+        assert_eq!(growth[1].key, "video/mp4");
+        assert_eq!((growth[1].total_media, growth[1].total_bytes), (1, 1000));
+    }
+}
@@ -0,0 +1,36 @@
+use std::time::Duration;
+
+use crate::domain::entities::UserId;
+
+/// Which downloads are redirected to a URL on the storage backend
+///
+/// Redirected downloads are fetched by the client straight from storage instead
+/// of being proxied through the service. Only the uploader can download media,
+/// so only downloads with their token are redirected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DownloadRedirectPolicy {
+    /// Redirect downloads by the authenticated uploader
+    pub private: bool,
+    /// How long a redirect URL stays valid
+    pub expires_in: Duration,
+}
+
+impl Default for DownloadRedirectPolicy {
+    fn default() -> Self {
+        Self::disabled()
+    }
+}
+
+impl DownloadRedirectPolicy {
+    /// A policy that proxies every download
+    #[must_use]
+    pub const fn disabled() -> Self {
+        Self { private: false, expires_in: Duration::from_mins(5) }
+    }
+
+    /// Whether a download by `requester` is redirected
+    #[must_use]
+    pub fn applies_to(&self, requester: Option<UserId>) -> bool {
+        requester.is_some() && self.private
+    }
+}
@@ -0,0 +1,57 @@
+use std::collections::HashMap;
+
+/// Media formats each tenant accepts, keyed by the client ID of its tokens
+///
+/// Tenants without an entry accept every format the service allows.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FormatPolicy {
+    tenants: HashMap<String, Vec<String>>,
+}
+
+impl FormatPolicy {
+    /// Create a policy from allowed MIME types per tenant, in order of preference
+    ///
+    /// Types are lowercased and tenants with an empty list are left unrestricted.
+    #[must_use]
+    pub fn new(tenants: HashMap<String, Vec<String>>) -> Self {
+        let tenants = tenants
+            .into_iter()
+            .map(|(tenant, formats)| {
+                let formats = formats.iter().map(|f| f.trim().to_ascii_lowercase()).collect();
+                (tenant, formats)
+            })
+            .filter(|(_, formats): &(String, Vec<String>)| !formats.is_empty())
+            .collect();
+        Self { tenants }
+    }
+
+    /// A policy that restricts no tenant
+    #[must_use]
+    pub fn unrestricted() -> Self {
+        Self::default()
+    }
+
+    /// Formats `tenant` accepts, or `None` if it is unrestricted
+    #[must_use]
+    pub fn allowed_formats(&self, tenant: &str) -> Option<&[String]> {
+        self.tenants.get(tenant).map(Vec::as_slice)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_listed_tenants_are_restricted() {
+        let policy = FormatPolicy::new(HashMap::from([
+            ("web-app".to_string(), vec!["Image/WebP".to_string()]),
+            ("mobile-app".to_string(), Vec::new()),
+        ]));
+
+        assert_eq!(policy.allowed_formats("web-app"), Some(&["image/webp".to_string()][..]));
+        assert_eq!(policy.allowed_formats("mobile-app"), None);
+        assert_eq!(policy.allowed_formats("recipe-service"), None);
+        assert_eq!(FormatPolicy::unrestricted().allowed_formats("web-app"), None);
+    }
+}
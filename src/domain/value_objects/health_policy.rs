@@ -0,0 +1,69 @@
+use std::time::Duration;
+
+/// How the health and readiness checks judge the service's dependencies
+///
+/// Liveness is not affected; it never probes dependencies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HealthPolicy {
+    /// How long a dependency check may take before it counts as failed
+    pub check_timeout: Duration,
+    /// Whether the service is unhealthy and not ready without its database,
+    /// rather than degraded
+    pub database_required: bool,
+}
+
+impl Default for HealthPolicy {
+    fn default() -> Self {
+        Self { check_timeout: Duration::from_secs(2), database_required: false }
+    }
+}
+
+impl HealthPolicy {
+    /// Overall health given whether the database and storage checks passed
+    #[must_use]
+    pub fn health(&self, database_healthy: bool, storage_healthy: bool) -> HealthStatus {
+        match (database_healthy, storage_healthy) {
+            (true, true) => HealthStatus::Healthy,
+            // Storage working allows basic operation
+            (false, true) if !self.database_required => HealthStatus::Degraded,
+            _ => HealthStatus::Unhealthy,
+        }
+    }
+}
+
+/// Overall health of the service
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HealthStatus {
+    Healthy,
+    /// Serving what it can without a failed dependency
+    Degraded,
+    Unhealthy,
+}
+
+impl HealthStatus {
+    /// The status as reported by the health endpoint
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Healthy => "healthy",
+            Self::Degraded => "degraded",
+            Self::Unhealthy => "unhealthy",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_database_outage_degrades_unless_required() {
+        let policy = HealthPolicy::default();
+        assert_eq!(policy.health(true, true), HealthStatus::Healthy);
+        assert_eq!(policy.health(false, true), HealthStatus::Degraded);
+        assert_eq!(policy.health(true, false), HealthStatus::Unhealthy);
+
+        let strict = HealthPolicy { database_required: true, ..policy };
+        assert_eq!(strict.health(false, true), HealthStatus::Unhealthy);
+    }
+}
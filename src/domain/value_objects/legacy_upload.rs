@@ -0,0 +1,20 @@
+use std::time::SystemTime;
+
+/// Whether the deprecated direct (multipart) upload endpoint is still served
+///
+/// The direct upload buffers whole files in memory; clients should use the
+/// presigned or resumable upload flows instead. While enabled, responses
+/// announce the deprecation and, once known, the `sunset` after which the
+/// endpoint is removed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LegacyUploadPolicy {
+    pub enabled: bool,
+    /// When the endpoint is scheduled to stop being served
+    pub sunset: Option<SystemTime>,
+}
+
+impl Default for LegacyUploadPolicy {
+    fn default() -> Self {
+        Self { enabled: true, sunset: None }
+    }
+}
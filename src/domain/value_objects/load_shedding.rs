@@ -0,0 +1,25 @@
+use std::time::Duration;
+
+/// How many requests are served at once before more are turned away
+///
+/// Uploads hold a connection and storage IO for much longer than the other
+/// routes, so they are limited separately and cannot starve cheap reads.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LoadSheddingPolicy {
+    /// Uploads in flight at once
+    pub max_concurrent_uploads: usize,
+    /// Requests to every other route in flight at once
+    pub max_concurrent_requests: usize,
+    /// When a turned away client is told to try again
+    pub retry_after: Duration,
+}
+
+impl Default for LoadSheddingPolicy {
+    fn default() -> Self {
+        Self {
+            max_concurrent_uploads: 64,
+            max_concurrent_requests: 1024,
+            retry_after: Duration::from_secs(1),
+        }
+    }
+}
@@ -0,0 +1,99 @@
+use std::time::SystemTime;
+
+use crate::domain::{
+    entities::Media,
+    value_objects::{MediaTag, ProcessingStatus},
+};
+
+/// Criteria a media search filters by; unset criteria match all media
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MediaSearch {
+    /// Case-insensitive substring of the original filename
+    pub filename: Option<String>,
+    /// Top-level MIME type, such as `image` or `video`, in lowercase
+    pub type_family: Option<String>,
+    /// Smallest file size in bytes, inclusive
+    pub min_size: Option<u64>,
+    /// Largest file size in bytes, inclusive
+    pub max_size: Option<u64>,
+    /// Earliest upload time, inclusive
+    pub uploaded_since: Option<SystemTime>,
+    /// Latest upload time, exclusive
+    pub uploaded_before: Option<SystemTime>,
+    pub status: Option<ProcessingStatus>,
+    /// Tags the media must all carry
+    pub tags: Vec<MediaTag>,
+}
+
+impl MediaSearch {
+    /// Check whether `media` meets every set criterion
+    #[must_use]
+    pub fn matches(&self, media: &Media) -> bool {
+        let filename = self.filename.as_ref().is_none_or(|filename| {
+            media.original_filename.to_lowercase().contains(&filename.to_lowercase())
+        });
+        let type_family = self.type_family.as_ref().is_none_or(|family| {
+            media.media_type.mime_type().split_once('/').is_some_and(|(main, _)| main == family)
+        });
+
+        filename
+            && type_family
+            && self.min_size.is_none_or(|min| media.file_size >= min)
+            && self.max_size.is_none_or(|max| media.file_size <= max)
+            && self.uploaded_since.is_none_or(|since| media.uploaded_at >= since)
+            && self.uploaded_before.is_none_or(|before| media.uploaded_at < before)
+            && self.status.as_ref().is_none_or(|status| &media.processing_status == status)
+            && self.tags.iter().all(|tag| media.tags.contains(tag))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{
+        entities::{MediaId, UnsavedMedia, UserId},
+        value_objects::{ContentHash, MediaType},
+    };
+    use std::time::Duration;
+
+    #[test]
+    fn test_every_criterion_must_match() {
+        let uploaded_at = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let mut media = UnsavedMedia::new(
+            ContentHash::new(&"a".repeat(64)).unwrap(),
+            "Sourdough Crumb.JPG".to_string(),
+            MediaType::new("image/jpeg"),
+            "aa/aa/aa/aaaa".to_string(),
+            2048,
+            UserId::new(),
+        )
+        .into_media(MediaId::new(1));
+        media.uploaded_at = uploaded_at;
+        media.tags = MediaTag::normalize_all(&["bread", "crumb shot"]).unwrap();
+
+        let search = MediaSearch {
+            filename: Some("crumb".to_string()),
+            type_family: Some("image".to_string()),
+            min_size: Some(2048),
+            max_size: Some(4096),
+            uploaded_since: Some(uploaded_at),
+            uploaded_before: Some(uploaded_at + Duration::from_secs(1)),
+            status: Some(ProcessingStatus::Pending),
+            tags: vec![MediaTag::new("Bread").unwrap()],
+        };
+        assert!(search.matches(&media));
+        assert!(MediaSearch::default().matches(&media));
+
+        let misses = [
+            MediaSearch { filename: Some("rye".to_string()), ..search.clone() },
+            MediaSearch { type_family: Some("video".to_string()), ..search.clone() },
+            MediaSearch { min_size: Some(2049), ..search.clone() },
+            MediaSearch { uploaded_before: Some(uploaded_at), ..search.clone() },
+            MediaSearch { status: Some(ProcessingStatus::Complete), ..search.clone() },
+            MediaSearch { tags: vec![MediaTag::new("rye").unwrap()], ..search.clone() },
+        ];
+        for miss in misses {
+            assert!(!miss.matches(&media), "{miss:?}");
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use std::time::UNIX_EPOCH;
+
+use crate::domain::entities::Media;
+
+/// Field media listings can be sorted by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MediaSortField {
+    UploadedAt,
+    FileSize,
+    Filename,
+}
+
+/// Direction of a sort
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SortOrder {
+    #[default]
+    Asc,
+    Desc,
+}
+
+/// Order of a media listing: by `field`, or by media ID if none is given
+///
+/// Media with equal sort values are ordered by ID in the same direction, so the
+/// order is total and pages stay stable.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MediaSort {
+    pub field: Option<MediaSortField>,
+    pub order: SortOrder,
+}
+
+/// The value media is ordered by before its ID
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MediaSortKey {
+    /// Media ID, file size, or upload time in microseconds since the Unix epoch
+    Int(i64),
+    /// Original filename, compared byte by byte
+    Text(String),
+}
+
+impl MediaSort {
+    /// Ascending media ID, the order listings have always used
+    #[must_use]
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    #[must_use]
+    pub fn is_descending(&self) -> bool {
+        self.order == SortOrder::Desc
+    }
+
+    /// Stable name of the sort, e.g. `file_size.desc`
+    #[must_use]
+    pub fn name(&self) -> String {
+        let field = match self.field {
+            None => "id",
+            Some(MediaSortField::UploadedAt) => "uploaded_at",
+            Some(MediaSortField::FileSize) => "file_size",
+            Some(MediaSortField::Filename) => "filename",
+        };
+        let order = match self.order {
+            SortOrder::Asc => "asc",
+            SortOrder::Desc => "desc",
+        };
+        format!("{field}.{order}")
+    }
+
+    /// The sort value of `media`
+    #[must_use]
+    pub fn key(&self, media: &Media) -> MediaSortKey {
+        match self.field {
+            None => MediaSortKey::Int(media.id.as_i64()),
+            Some(MediaSortField::UploadedAt) => {
+                let micros = media.uploaded_at.duration_since(UNIX_EPOCH).unwrap_or_default();
+                MediaSortKey::Int(i64::try_from(micros.as_micros()).unwrap_or(i64::MAX))
+            }
+            Some(MediaSortField::FileSize) => {
+                MediaSortKey::Int(i64::try_from(media.file_size).unwrap_or(i64::MAX))
+            }
+            Some(MediaSortField::Filename) => MediaSortKey::Text(media.original_filename.clone()),
+        }
+    }
+}
+
+impl fmt::Display for MediaSortKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Int(value) => write!(f, "i:{value}"),
+            Self::Text(value) => write!(f, "s:{value}"),
+        }
+    }
+}
+
+impl FromStr for MediaSortKey {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(':') {
+            Some(("i", value)) => value.parse().map(Self::Int).map_err(|e| e.to_string()),
+            Some(("s", value)) => Ok(Self::Text(value.to_string())),
+            _ => Err(format!("Invalid media sort key: {s}")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sort_names() {
+        assert_eq!(MediaSort::default().name(), "id.asc");
+        assert!(MediaSort::default().is_default());
+
+        let sort = MediaSort { field: Some(MediaSortField::FileSize), order: SortOrder::Desc };
+        assert_eq!(sort.name(), "file_size.desc");
+        assert!(!sort.is_default());
+    }
+
+    #[test]
+    fn test_sort_key_round_trip() {
+        for key in [MediaSortKey::Int(-42), MediaSortKey::Text("a:b c.jpg".to_string())] {
+            assert_eq!(key.to_string().parse::<MediaSortKey>().unwrap(), key);
+        }
+        assert!("x:1".parse::<MediaSortKey>().is_err());
+        assert!("i:one".parse::<MediaSortKey>().is_err());
+    }
+}
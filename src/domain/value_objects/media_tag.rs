@@ -0,0 +1,114 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Longest tag, in characters
+pub const MAX_TAG_LENGTH: usize = 32;
+
+/// Most tags a single media file may carry
+pub const MAX_TAGS_PER_MEDIA: usize = 20;
+
+/// A user-defined label on media, such as `plated` or `step-by-step`
+///
+/// Tags are trimmed and lowercased, and may contain letters, digits, spaces,
+/// hyphens and underscores.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct MediaTag(String);
+
+impl MediaTag {
+    /// Normalize and validate a tag
+    ///
+    /// # Errors
+    /// Returns an error if the tag is empty, too long, or contains other characters
+    pub fn new(tag: &str) -> Result<Self, MediaTagError> {
+        let tag = tag.trim().to_lowercase();
+        if tag.is_empty() {
+            return Err(MediaTagError::Empty);
+        }
+        if tag.chars().count() > MAX_TAG_LENGTH {
+            return Err(MediaTagError::TooLong(tag));
+        }
+        if !tag.chars().all(|c| c.is_alphanumeric() || matches!(c, ' ' | '-' | '_')) {
+            return Err(MediaTagError::InvalidCharacters(tag));
+        }
+        Ok(Self(tag))
+    }
+
+    /// Normalize a list of tags, dropping repeats and sorting them
+    ///
+    /// # Errors
+    /// Returns an error if any tag is invalid or more than [`MAX_TAGS_PER_MEDIA`] remain
+    pub fn normalize_all<S: AsRef<str>>(tags: &[S]) -> Result<Vec<Self>, MediaTagError> {
+        let mut tags =
+            tags.iter().map(|tag| Self::new(tag.as_ref())).collect::<Result<Vec<_>, _>>()?;
+        tags.sort();
+        tags.dedup();
+        if tags.len() > MAX_TAGS_PER_MEDIA {
+            return Err(MediaTagError::TooMany(tags.len()));
+        }
+        Ok(tags)
+    }
+
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for MediaTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl TryFrom<String> for MediaTag {
+    type Error = MediaTagError;
+
+    fn try_from(tag: String) -> Result<Self, Self::Error> {
+        Self::new(&tag)
+    }
+}
+
+impl From<MediaTag> for String {
+    fn from(tag: MediaTag) -> Self {
+        tag.0
+    }
+}
+
+/// Errors that can occur when creating a media tag
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum MediaTagError {
+    #[error("Tags cannot be empty")]
+    Empty,
+    #[error("Tag '{0}' is longer than {MAX_TAG_LENGTH} characters")]
+    TooLong(String),
+    #[error("Tag '{0}' may only contain letters, digits, spaces, hyphens and underscores")]
+    InvalidCharacters(String),
+    #[error("Media can have at most {MAX_TAGS_PER_MEDIA} tags, got {0}")]
+    TooMany(usize),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tags_are_normalized() {
+        assert_eq!(MediaTag::new("  Step-By-Step ").unwrap().as_str(), "step-by-step");
+        assert_eq!(MediaTag::new("Crème brûlée").unwrap().as_str(), "crème brûlée");
+
+        let tags = MediaTag::normalize_all(&["plated", "Ingredients", "PLATED"]).unwrap();
+        let tags: Vec<&str> = tags.iter().map(MediaTag::as_str).collect();
+        assert_eq!(tags, vec!["ingredients", "plated"]);
+    }
+
+    #[test]
+    fn test_invalid_tags_are_rejected() {
+        assert_eq!(MediaTag::new("  "), Err(MediaTagError::Empty));
+        assert!(matches!(MediaTag::new(&"a".repeat(33)), Err(MediaTagError::TooLong(_))));
+        assert!(matches!(MediaTag::new("#plated"), Err(MediaTagError::InvalidCharacters(_))));
+
+        let too_many: Vec<String> = (0..21).map(|i| format!("tag-{i}")).collect();
+        assert_eq!(MediaTag::normalize_all(&too_many), Err(MediaTagError::TooMany(21)));
+    }
+}
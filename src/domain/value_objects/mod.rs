@@ -1,7 +1,37 @@
+pub mod cache_control;
+pub mod capacity_growth;
 pub mod content_hash;
+pub mod download_redirect;
+pub mod format_policy;
+pub mod health_policy;
+pub mod legacy_upload;
+pub mod load_shedding;
+pub mod media_search;
+pub mod media_sort;
+pub mod media_tag;
 pub mod media_type;
+pub mod processing_sla;
 pub mod processing_status;
+pub mod render;
+pub mod route_limits;
+pub mod storage_quota;
+pub mod uuid_version;
 
+pub use cache_control::*;
+pub use capacity_growth::*;
 pub use content_hash::*;
+pub use download_redirect::*;
+pub use format_policy::*;
+pub use health_policy::*;
+pub use legacy_upload::*;
+pub use load_shedding::*;
+pub use media_search::*;
+pub use media_sort::*;
+pub use media_tag::*;
 pub use media_type::*;
+pub use processing_sla::*;
 pub use processing_status::*;
+pub use render::*;
+pub use route_limits::*;
+pub use storage_quota::*;
+pub use uuid_version::*;
@@ -0,0 +1,64 @@
+use std::time::{Duration, SystemTime};
+
+/// How long uploaded media may wait for processing to finish
+///
+/// Measured from when the upload completed, so time spent queued behind other
+/// work counts. Media that takes longer has breached the SLA, whether it has
+/// finished processing or is still waiting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ProcessingSla {
+    /// Longest acceptable wait; `None` disables the SLA
+    pub threshold: Option<Duration>,
+}
+
+impl ProcessingSla {
+    /// An SLA of `threshold`
+    #[must_use]
+    pub const fn new(threshold: Duration) -> Self {
+        Self { threshold: Some(threshold) }
+    }
+
+    /// No SLA; nothing is ever overdue
+    #[must_use]
+    pub const fn disabled() -> Self {
+        Self { threshold: None }
+    }
+
+    /// Whether waiting `elapsed` breaches the SLA
+    #[must_use]
+    pub fn is_breached_by(&self, elapsed: Duration) -> bool {
+        self.threshold.is_some_and(|threshold| elapsed > threshold)
+    }
+
+    /// Media whose upload completed before this time, and is still unprocessed at
+    /// `now`, is overdue
+    #[must_use]
+    pub fn overdue_before(&self, now: SystemTime) -> Option<SystemTime> {
+        self.threshold.and_then(|threshold| now.checked_sub(threshold))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_breached_only_past_the_threshold() {
+        let sla = ProcessingSla::new(Duration::from_mins(5));
+
+        assert!(!sla.is_breached_by(Duration::from_mins(5)));
+        assert!(sla.is_breached_by(Duration::from_secs(301)));
+        assert!(!ProcessingSla::disabled().is_breached_by(Duration::from_hours(24)));
+    }
+
+    #[test]
+    fn test_overdue_cutoff() {
+        let now = SystemTime::now();
+
+        assert_eq!(
+            ProcessingSla::new(Duration::from_mins(5)).overdue_before(now),
+            Some(now - Duration::from_mins(5))
+        );
+        assert_eq!(ProcessingSla::disabled().overdue_before(now), None);
+    }
+}
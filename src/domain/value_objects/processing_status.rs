@@ -3,7 +3,7 @@ use std::fmt;
 use std::str::FromStr;
 
 /// Processing status for media files matching database enum
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
 pub enum ProcessingStatus {
     /// File has been uploaded and is waiting for processing
     Pending,
@@ -0,0 +1,173 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Prefix of the names of variants holding resized images
+const RENDER_PREFIX: &str = "render-";
+
+/// How an image is fitted to the dimensions it is rendered at
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ImageFit {
+    /// Scale down to fit within the dimensions, keeping the aspect ratio
+    #[default]
+    Contain,
+    /// Scale to cover the dimensions, cropping what overflows around the center
+    Cover,
+    /// Stretch to exactly the dimensions
+    Fill,
+}
+
+impl ImageFit {
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Contain => "contain",
+            Self::Cover => "cover",
+            Self::Fill => "fill",
+        }
+    }
+}
+
+/// Dimensions an image is rendered at, e.g. `320x240`
+///
+/// Either dimension may be left out (`320x`, `x240`) to follow the aspect ratio.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RenderSize {
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+}
+
+impl fmt::Display for RenderSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let dimension = |value: Option<u32>| value.map(|v| v.to_string()).unwrap_or_default();
+        write!(f, "{}x{}", dimension(self.width), dimension(self.height))
+    }
+}
+
+impl FromStr for RenderSize {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid =
+            || format!("Invalid render size {s:?}, expected WIDTHxHEIGHT, WIDTHx or xHEIGHT");
+        let (width, height) = s.trim().split_once('x').ok_or_else(invalid)?;
+        let dimension = |value: &str| match value {
+            "" => Ok(None),
+            value => match value.parse::<u32>() {
+                Ok(0) | Err(_) => Err(invalid()),
+                Ok(value) => Ok(Some(value)),
+            },
+        };
+
+        let size = Self { width: dimension(width)?, height: dimension(height)? };
+        if size.width.is_none() && size.height.is_none() {
+            return Err(invalid());
+        }
+        Ok(size)
+    }
+}
+
+/// A size and fit an image is rendered at
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImageRender {
+    pub size: RenderSize,
+    pub fit: ImageFit,
+}
+
+impl ImageRender {
+    /// Create a render, fitting images with only one dimension given by `contain`
+    ///
+    /// Cropping and stretching need both dimensions, so with one the fits are all
+    /// the same render.
+    #[must_use]
+    pub fn new(size: RenderSize, fit: ImageFit) -> Self {
+        let fit =
+            if size.width.is_some() && size.height.is_some() { fit } else { ImageFit::Contain };
+        Self { size, fit }
+    }
+
+    /// Name of the variant the render is stored as, e.g. `render-320x240-cover`
+    #[must_use]
+    pub fn variant_name(&self) -> String {
+        format!("{RENDER_PREFIX}{}-{}", self.size, self.fit.name())
+    }
+
+    /// Whether a variant holds a resized image rather than another encoding of the original
+    #[must_use]
+    pub fn is_render_variant(variant_name: &str) -> bool {
+        variant_name.starts_with(RENDER_PREFIX)
+    }
+}
+
+/// Sizes images may be rendered at
+///
+/// Every render is kept as a variant, so only listed sizes are rendered;
+/// otherwise a client could fill storage by asking for every size it can name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenderPolicy {
+    sizes: Vec<RenderSize>,
+}
+
+impl RenderPolicy {
+    #[must_use]
+    pub fn new(sizes: Vec<RenderSize>) -> Self {
+        Self { sizes }
+    }
+
+    /// Whether images may be rendered at `size`
+    #[must_use]
+    pub fn allows(&self, size: RenderSize) -> bool {
+        self.sizes.contains(&size)
+    }
+
+    #[must_use]
+    pub fn sizes(&self) -> &[RenderSize] {
+        &self.sizes
+    }
+}
+
+impl Default for RenderPolicy {
+    /// Thumbnails, common 4:3 and 16:9 display sizes, and widths for responsive images
+    fn default() -> Self {
+        let sizes = ["160x160", "320x240", "640x480", "1280x720", "320x", "640x", "1280x"];
+        Self::new(sizes.iter().filter_map(|size| size.parse().ok()).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_size_round_trip() {
+        for size in ["320x240", "320x", "x240"] {
+            assert_eq!(size.parse::<RenderSize>().unwrap().to_string(), size);
+        }
+        for size in ["x", "320", "0x240", "320x-1", "wide x tall", ""] {
+            assert!(size.parse::<RenderSize>().is_err(), "{size:?} should be rejected");
+        }
+    }
+
+    #[test]
+    fn test_renders_with_one_dimension_are_contained() {
+        let width_only = ImageRender::new("320x".parse().unwrap(), ImageFit::Cover);
+        assert_eq!(width_only.fit, ImageFit::Contain);
+        assert_eq!(width_only.variant_name(), "render-320x-contain");
+
+        let cover = ImageRender::new("320x240".parse().unwrap(), ImageFit::Cover);
+        assert_eq!(cover.variant_name(), "render-320x240-cover");
+        assert!(ImageRender::is_render_variant(&cover.variant_name()));
+        assert!(!ImageRender::is_render_variant("webp"));
+    }
+
+    #[test]
+    fn test_policy_allows_only_listed_sizes() {
+        let policy = RenderPolicy::default();
+        assert_eq!(policy.sizes().len(), 7);
+        assert!(policy.allows("320x240".parse().unwrap()));
+        assert!(policy.allows("640x".parse().unwrap()));
+        assert!(!policy.allows("321x240".parse().unwrap()));
+        assert!(!policy.allows("x640".parse().unwrap()));
+    }
+}
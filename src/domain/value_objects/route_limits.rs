@@ -0,0 +1,32 @@
+use std::time::Duration;
+
+/// How long a request to a class of routes may take and how large its body may be
+///
+/// The timeout runs until the response starts, so it bounds receiving an
+/// upload but not streaming a download.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RouteLimit {
+    pub timeout: Duration,
+    pub max_body_size: usize, // bytes
+}
+
+/// Limits of each class of routes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RouteLimits {
+    /// Direct, presigned and resumable uploads
+    pub upload: RouteLimit,
+    /// Metadata, downloads, health checks and every other non-admin route
+    pub read: RouteLimit,
+    /// Routes under `/admin`
+    pub admin: RouteLimit,
+}
+
+impl Default for RouteLimits {
+    fn default() -> Self {
+        Self {
+            upload: RouteLimit { timeout: Duration::from_mins(10), max_body_size: 100_000_000 },
+            read: RouteLimit { timeout: Duration::from_secs(30), max_body_size: 1_048_576 },
+            admin: RouteLimit { timeout: Duration::from_mins(1), max_body_size: 65_536 },
+        }
+    }
+}
@@ -0,0 +1,83 @@
+use serde::{Deserialize, Serialize};
+
+/// Storage a user currently consumes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StorageUsage {
+    /// Total size of the user's media in bytes
+    pub bytes: u64,
+    /// Number of media files the user owns
+    pub files: u64,
+}
+
+/// Per-user storage limits; a `None` limit is unlimited
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StorageQuota {
+    pub max_bytes: Option<u64>,
+    pub max_files: Option<u64>,
+}
+
+/// The limit an upload would exceed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuotaLimit {
+    Bytes,
+    Files,
+}
+
+impl StorageQuota {
+    /// A quota without any limits
+    #[must_use]
+    pub const fn unlimited() -> Self {
+        Self { max_bytes: None, max_files: None }
+    }
+
+    /// Check whether no limit is configured
+    #[must_use]
+    pub fn is_unlimited(&self) -> bool {
+        self.max_bytes.is_none() && self.max_files.is_none()
+    }
+
+    /// Find the limit that adding one file of `bytes` to `usage` would exceed
+    #[must_use]
+    pub fn exceeded_by(&self, usage: StorageUsage, bytes: u64) -> Option<QuotaLimit> {
+        if self.max_files.is_some_and(|max| usage.files.saturating_add(1) > max) {
+            return Some(QuotaLimit::Files);
+        }
+        if self.max_bytes.is_some_and(|max| usage.bytes.saturating_add(bytes) > max) {
+            return Some(QuotaLimit::Bytes);
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unlimited_quota_is_never_exceeded() {
+        let usage = StorageUsage { bytes: u64::MAX, files: u64::MAX };
+
+        assert!(StorageQuota::unlimited().is_unlimited());
+        assert_eq!(StorageQuota::unlimited().exceeded_by(usage, u64::MAX), None);
+    }
+
+    #[test]
+    fn test_byte_limit_includes_the_new_file() {
+        let quota = StorageQuota { max_bytes: Some(100), max_files: None };
+        let usage = StorageUsage { bytes: 60, files: 3 };
+
+        assert_eq!(quota.exceeded_by(usage, 40), None);
+        assert_eq!(quota.exceeded_by(usage, 41), Some(QuotaLimit::Bytes));
+    }
+
+    #[test]
+    fn test_file_limit_counts_the_new_file() {
+        let quota = StorageQuota { max_bytes: Some(1000), max_files: Some(3) };
+
+        assert_eq!(quota.exceeded_by(StorageUsage { bytes: 0, files: 2 }, 1), None);
+        assert_eq!(
+            quota.exceeded_by(StorageUsage { bytes: 0, files: 3 }, 1),
+            Some(QuotaLimit::Files)
+        );
+    }
+}
@@ -0,0 +1,46 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// UUID version used for generated identifiers
+///
+/// Version 7 embeds a millisecond timestamp, so identifiers sort by creation time
+/// in logs and database indexes. Version 4 is fully random.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UuidVersion {
+    V4,
+    #[default]
+    V7,
+}
+
+impl UuidVersion {
+    /// Generate a new UUID of this version
+    #[must_use]
+    pub fn new_uuid(self) -> Uuid {
+        match self {
+            Self::V4 => Uuid::new_v4(),
+            Self::V7 => Uuid::now_v7(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generates_requested_version() {
+        assert_eq!(UuidVersion::V4.new_uuid().get_version_num(), 4);
+        assert_eq!(UuidVersion::V7.new_uuid().get_version_num(), 7);
+    }
+
+    #[test]
+    fn test_v7_uuids_sort_by_creation_time() {
+        let earlier = UuidVersion::V7.new_uuid();
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        let later = UuidVersion::V7.new_uuid();
+
+        assert!(earlier < later);
+        assert!(earlier.to_string() < later.to_string());
+    }
+}
@@ -0,0 +1,213 @@
+//! Domain metrics for uploads, storage and processing
+//!
+//! Use cases report what happened to uploads and processing through a
+//! [`BusinessMetrics`] handle, which records nothing unless business metrics are
+//! enabled with `collect_business_metrics`.
+//!
+//! Metrics:
+//! - `media_uploads_started_total` - uploads begun, by `upload` flow
+//! - `media_uploads_completed_total` - uploads whose content was accepted, by `upload`
+//! - `media_uploads_failed_total` - uploads rejected or failed, by `upload` and error `reason`
+//! - `media_uploads_aborted_total` - uploads whose body stopped part way, usually because the
+//!   client disconnected, by `upload` and `route`
+//! - `media_upload_bytes_ingested_total` - bytes received by completed uploads, by `upload`
+//! - `media_upload_dedup_hits_total` - completed uploads that matched stored content, by `upload`
+//! - `media_storage_bytes_stored_total` - bytes written to storage, by `backend`
+//! - `media_processing_duration_seconds` - processing runs, by `media_kind` and `outcome`
+//!
+//! The processing queue depth is `background_job_backlog{job="media_processing"}`,
+//! exported by the [job registry](super::jobs).
+
+use std::time::Duration;
+
+use crate::{domain::value_objects::MediaType, presentation::middleware::error::AppError};
+
+/// How an upload reached the service
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UploadFlow {
+    /// The deprecated single-request `POST /media`
+    Direct,
+    /// `upload-request` followed by a PUT to the presigned URL
+    Presigned,
+    /// tus chunks
+    Resumable,
+}
+
+impl UploadFlow {
+    /// The `upload` label, matching `media_upload_requests_total`
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Direct => "direct",
+            Self::Presigned => "presigned",
+            Self::Resumable => "resumable",
+        }
+    }
+}
+
+/// Records domain metrics when enabled
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BusinessMetrics {
+    enabled: bool,
+}
+
+impl BusinessMetrics {
+    /// Record business metrics only if `enabled`
+    #[must_use]
+    pub fn new(enabled: bool) -> Self {
+        Self { enabled }
+    }
+
+    /// Record nothing
+    #[must_use]
+    pub fn disabled() -> Self {
+        Self::default()
+    }
+
+    /// Whether anything is recorded
+    #[must_use]
+    pub fn is_enabled(self) -> bool {
+        self.enabled
+    }
+
+    /// An upload has begun
+    pub fn upload_started(self, flow: UploadFlow) {
+        if self.enabled {
+            metrics::counter!("media_uploads_started_total", "upload" => flow.as_str())
+                .increment(1);
+        }
+    }
+
+    /// An upload of `bytes` was accepted, as new content or as a duplicate of stored content
+    pub fn upload_completed(self, flow: UploadFlow, bytes: u64, deduplicated: bool) {
+        if !self.enabled {
+            return;
+        }
+        let upload = flow.as_str();
+        metrics::counter!("media_uploads_completed_total", "upload" => upload).increment(1);
+        metrics::counter!("media_upload_bytes_ingested_total", "upload" => upload).increment(bytes);
+        if deduplicated {
+            metrics::counter!("media_upload_dedup_hits_total", "upload" => upload).increment(1);
+        }
+    }
+
+    /// An upload ended with `error`
+    pub fn upload_failed(self, flow: UploadFlow, error: &AppError) {
+        if self.enabled {
+            metrics::counter!(
+                "media_uploads_failed_total",
+                "upload" => flow.as_str(),
+                "reason" => error.error_type()
+            )
+            .increment(1);
+        }
+    }
+
+    /// The body of an upload to `route` stopped before it was complete
+    pub fn upload_aborted(self, flow: UploadFlow, route: &str) {
+        if self.enabled {
+            metrics::counter!(
+                "media_uploads_aborted_total",
+                "upload" => flow.as_str(),
+                "route" => route.to_string()
+            )
+            .increment(1);
+        }
+    }
+
+    /// Count the upload as failed if `result` is an error
+    pub fn record_failure<T>(self, flow: UploadFlow, result: &Result<T, AppError>) {
+        if let Err(e) = result {
+            self.upload_failed(flow, e);
+        }
+    }
+
+    /// `bytes` were written to the `backend` storage
+    pub fn bytes_stored(self, backend: &'static str, bytes: u64) {
+        if self.enabled {
+            metrics::counter!("media_storage_bytes_stored_total", "backend" => backend)
+                .increment(bytes);
+        }
+    }
+
+    /// A processing run for media of `media_type` finished after `duration`
+    pub fn processing_finished(self, media_type: &MediaType, succeeded: bool, duration: Duration) {
+        if !self.enabled {
+            return;
+        }
+        metrics::histogram!(
+            "media_processing_duration_seconds",
+            "media_kind" => media_kind(media_type),
+            "outcome" => outcome(succeeded)
+        )
+        .record(duration.as_secs_f64());
+    }
+}
+
+/// The `media_kind` label of processing metrics
+pub(crate) fn media_kind(media_type: &MediaType) -> &'static str {
+    if media_type.is_image() {
+        "image"
+    } else if media_type.is_video() {
+        "video"
+    } else {
+        "other"
+    }
+}
+
+/// The `outcome` label of processing metrics
+pub(crate) fn outcome(succeeded: bool) -> &'static str {
+    if succeeded {
+        "success"
+    } else {
+        "failure"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use metrics_exporter_prometheus::PrometheusBuilder;
+
+    fn render(record: impl FnOnce()) -> String {
+        let recorder = PrometheusBuilder::new().build_recorder();
+        let handle = recorder.handle();
+        metrics::with_local_recorder(&recorder, record);
+        handle.render()
+    }
+
+    #[test]
+    fn test_completed_upload_counts_bytes_and_dedup_hits() {
+        let rendered = render(|| {
+            let metrics = BusinessMetrics::new(true);
+            metrics.upload_started(UploadFlow::Presigned);
+            metrics.upload_completed(UploadFlow::Presigned, 2048, true);
+            metrics.upload_failed(
+                UploadFlow::Direct,
+                &AppError::BadRequest { message: String::new() },
+            );
+            metrics.upload_aborted(UploadFlow::Resumable, "/uploads/{id}");
+        });
+
+        assert!(rendered.contains(r#"media_uploads_started_total{upload="presigned"} 1"#));
+        assert!(rendered.contains(r#"media_upload_bytes_ingested_total{upload="presigned"} 2048"#));
+        assert!(rendered.contains(r#"media_upload_dedup_hits_total{upload="presigned"} 1"#));
+        assert!(rendered
+            .contains(r#"media_uploads_failed_total{upload="direct",reason="bad_request"} 1"#));
+        assert!(rendered.contains(
+            r#"media_uploads_aborted_total{upload="resumable",route="/uploads/{id}"} 1"#
+        ));
+    }
+
+    #[test]
+    fn test_disabled_metrics_record_nothing() {
+        let rendered = render(|| {
+            let metrics = BusinessMetrics::disabled();
+            metrics.upload_started(UploadFlow::Direct);
+            metrics.bytes_stored("filesystem", 10);
+        });
+
+        assert!(!rendered.contains("media_uploads_started_total"));
+        assert!(!rendered.contains("media_storage_bytes_stored_total"));
+    }
+}
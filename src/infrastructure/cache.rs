@@ -0,0 +1,145 @@
+//! Key-value caches for data read far more often than it changes
+//!
+//! Entries are serialized values that expire after the configured TTL. A cache
+//! is only ever a shortcut: callers treat every [`CacheError`] as a miss and
+//! read from the source instead.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use moka::future::Cache as MokaCache;
+use thiserror::Error;
+use tracing::{error, info};
+
+use crate::infrastructure::{
+    config::{CacheBackend, CacheConfig},
+    redis::RedisCache,
+};
+
+/// A [`Cache`] that could not be reached
+#[derive(Debug, Error)]
+#[error("Cache unavailable: {0}")]
+pub struct CacheError(pub String);
+
+/// Serialized values by key, expiring after a fixed TTL
+#[async_trait]
+pub trait Cache: Send + Sync + std::fmt::Debug {
+    /// The value of `key`, unless it is missing or expired
+    async fn get(&self, key: &str) -> Result<Option<String>, CacheError>;
+
+    /// The values of `keys`, in the same order
+    ///
+    /// The default implementation looks each key up separately.
+    async fn get_many(&self, keys: &[String]) -> Result<Vec<Option<String>>, CacheError> {
+        let mut values = Vec::with_capacity(keys.len());
+        for key in keys {
+            values.push(self.get(key).await?);
+        }
+        Ok(values)
+    }
+
+    /// Store `value` under `key`, replacing any previous value
+    async fn set(&self, key: &str, value: String) -> Result<(), CacheError>;
+
+    /// Remove `keys`, so the next lookups miss
+    async fn remove(&self, keys: &[String]) -> Result<(), CacheError>;
+}
+
+/// Cache in process memory, holding at most `max_entries` entries
+///
+/// Every instance has its own entries, so an entry removed on one instance may
+/// still be served by another until it expires.
+#[derive(Debug, Clone)]
+pub struct MemoryCache {
+    entries: MokaCache<String, String>,
+}
+
+impl MemoryCache {
+    #[must_use]
+    pub fn new(config: &CacheConfig) -> Self {
+        Self {
+            entries: MokaCache::builder()
+                .max_capacity(config.max_entries)
+                .time_to_live(config.ttl())
+                .build(),
+        }
+    }
+}
+
+#[async_trait]
+impl Cache for MemoryCache {
+    async fn get(&self, key: &str) -> Result<Option<String>, CacheError> {
+        Ok(self.entries.get(key).await)
+    }
+
+    async fn set(&self, key: &str, value: String) -> Result<(), CacheError> {
+        self.entries.insert(key.to_string(), value).await;
+        Ok(())
+    }
+
+    async fn remove(&self, keys: &[String]) -> Result<(), CacheError> {
+        for key in keys {
+            self.entries.invalidate(key).await;
+        }
+        Ok(())
+    }
+}
+
+/// The cache `config` asks for, or `None` when caching is disabled
+///
+/// An unusable Redis URL disables caching rather than failing startup.
+pub fn create_cache(config: &CacheConfig) -> Option<Arc<dyn Cache>> {
+    if !config.enabled {
+        return None;
+    }
+
+    match config.backend {
+        CacheBackend::Memory => {
+            info!(
+                "Caching media metadata in memory (TTL {}s, up to {} entries)",
+                config.ttl_seconds, config.max_entries
+            );
+            Some(Arc::new(MemoryCache::new(config)))
+        }
+        CacheBackend::Redis => match RedisCache::new(config.redis_url.expose(), config.ttl()) {
+            Ok(cache) => {
+                info!("Caching media metadata in Redis (TTL {}s)", config.ttl_seconds);
+                Some(Arc::new(cache))
+            }
+            Err(e) => {
+                error!("Invalid cache Redis URL, caching disabled: {}", e);
+                None
+            }
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_memory_cache_stores_and_removes_entries() {
+        let config = CacheConfig { enabled: true, ..CacheConfig::default() };
+        let cache = MemoryCache::new(&config);
+
+        cache.set("media:1", "one".to_string()).await.unwrap();
+        cache.set("media:2", "two".to_string()).await.unwrap();
+        let keys = ["media:1".to_string(), "media:2".to_string(), "media:3".to_string()];
+        assert_eq!(
+            cache.get_many(&keys).await.unwrap(),
+            [Some("one".to_string()), Some("two".to_string()), None]
+        );
+
+        cache.remove(&keys[..1]).await.unwrap();
+        assert_eq!(cache.get("media:1").await.unwrap(), None);
+        assert_eq!(cache.get("media:2").await.unwrap().as_deref(), Some("two"));
+    }
+
+    #[test]
+    fn test_disabled_cache_is_not_created() {
+        assert!(create_cache(&CacheConfig::default()).is_none());
+        let memory = CacheConfig { enabled: true, ..CacheConfig::default() };
+        assert!(create_cache(&memory).is_some());
+    }
+}
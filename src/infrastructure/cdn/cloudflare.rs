@@ -0,0 +1,112 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde_json::json;
+
+use crate::infrastructure::config::Secret;
+
+use super::{check_response, http_client, CdnError, CdnPurger};
+
+/// Base URL of the Cloudflare API
+const API_BASE_URL: &str = "https://api.cloudflare.com/client/v4";
+
+/// Most cache tags Cloudflare accepts in one purge request
+const MAX_TAGS_PER_REQUEST: usize = 30;
+
+/// Purges a Cloudflare zone by cache tag
+///
+/// Cloudflare reads the tags of a response from its `Cache-Tag` header, which
+/// is sent alongside `Surrogate-Key` with the same keys.
+#[derive(Debug, Clone)]
+pub struct CloudflarePurger {
+    client: reqwest::Client,
+    api_base_url: String,
+    api_token: Secret<String>,
+    zone_id: String,
+}
+
+impl CloudflarePurger {
+    /// Purge `zone_id` with `api_token`, which needs the Cache Purge permission
+    #[must_use]
+    pub fn new(api_token: &str, zone_id: &str, timeout: Duration) -> Self {
+        Self {
+            client: http_client(timeout),
+            api_base_url: API_BASE_URL.to_string(),
+            api_token: api_token.into(),
+            zone_id: zone_id.to_string(),
+        }
+    }
+
+    /// Send purges to `api_base_url` instead of the Cloudflare API
+    #[must_use]
+    pub fn with_api_base_url(mut self, api_base_url: &str) -> Self {
+        self.api_base_url = api_base_url.trim_end_matches('/').to_string();
+        self
+    }
+}
+
+#[async_trait]
+impl CdnPurger for CloudflarePurger {
+    fn provider(&self) -> &'static str {
+        "cloudflare"
+    }
+
+    async fn purge(&self, keys: &[String]) -> Result<(), CdnError> {
+        let url = format!("{}/zones/{}/purge_cache", self.api_base_url, self.zone_id);
+        for tags in keys.chunks(MAX_TAGS_PER_REQUEST) {
+            let response = self
+                .client
+                .post(&url)
+                .bearer_auth(self.api_token.expose())
+                .json(&json!({ "tags": tags }))
+                .send()
+                .await?;
+            check_response(response).await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        extract::Path,
+        http::{HeaderMap, StatusCode},
+        routing::post,
+        Json, Router,
+    };
+    use serde_json::Value;
+    use tokio::net::TcpListener;
+
+    #[tokio::test]
+    async fn test_purges_tags_of_the_zone() {
+        let app = Router::new().route(
+            "/zones/{zone}/purge_cache",
+            post(
+                |Path(zone): Path<String>, headers: HeaderMap, Json(body): Json<Value>| async move {
+                    if zone != "zone-1" {
+                        return (StatusCode::NOT_FOUND, Json(json!({ "success": false })));
+                    }
+                    assert_eq!(headers["authorization"], "Bearer cf-token");
+                    assert_eq!(body, json!({ "tags": ["media-5", "recipe-2"] }));
+                    (StatusCode::OK, Json(json!({ "success": true })))
+                },
+            ),
+        );
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let purger = CloudflarePurger::new("cf-token", "zone-1", Duration::from_secs(5))
+            .with_api_base_url(&format!("http://{addr}"));
+        purger.purge(&["media-5".to_string(), "recipe-2".to_string()]).await.unwrap();
+
+        let unknown_zone = CloudflarePurger::new("cf-token", "zone-2", Duration::from_secs(5))
+            .with_api_base_url(&format!("http://{addr}"));
+        let error = unknown_zone.purge(&["media-5".to_string()]).await.unwrap_err();
+        assert!(
+            matches!(error, CdnError::Rejected { status, .. } if status == StatusCode::NOT_FOUND)
+        );
+    }
+}
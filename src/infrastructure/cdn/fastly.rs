@@ -0,0 +1,107 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde_json::json;
+
+use crate::infrastructure::config::Secret;
+
+use super::{check_response, http_client, CdnError, CdnPurger};
+
+/// Base URL of the Fastly API
+const API_BASE_URL: &str = "https://api.fastly.com";
+
+/// Most surrogate keys Fastly accepts in one purge request
+const MAX_KEYS_PER_REQUEST: usize = 256;
+
+/// Purges a Fastly service by surrogate key
+#[derive(Debug, Clone)]
+pub struct FastlyPurger {
+    client: reqwest::Client,
+    api_base_url: String,
+    api_token: Secret<String>,
+    service_id: String,
+}
+
+impl FastlyPurger {
+    /// Purge `service_id` with `api_token`, which needs the `purge_select` scope
+    #[must_use]
+    pub fn new(api_token: &str, service_id: &str, timeout: Duration) -> Self {
+        Self {
+            client: http_client(timeout),
+            api_base_url: API_BASE_URL.to_string(),
+            api_token: api_token.into(),
+            service_id: service_id.to_string(),
+        }
+    }
+
+    /// Send purges to `api_base_url` instead of the Fastly API
+    #[must_use]
+    pub fn with_api_base_url(mut self, api_base_url: &str) -> Self {
+        self.api_base_url = api_base_url.trim_end_matches('/').to_string();
+        self
+    }
+}
+
+#[async_trait]
+impl CdnPurger for FastlyPurger {
+    fn provider(&self) -> &'static str {
+        "fastly"
+    }
+
+    async fn purge(&self, keys: &[String]) -> Result<(), CdnError> {
+        let url = format!("{}/service/{}/purge", self.api_base_url, self.service_id);
+        for surrogate_keys in keys.chunks(MAX_KEYS_PER_REQUEST) {
+            let response = self
+                .client
+                .post(&url)
+                .header("Fastly-Key", self.api_token.expose())
+                .json(&json!({ "surrogate_keys": surrogate_keys }))
+                .send()
+                .await?;
+            check_response(response).await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        extract::Path,
+        http::{HeaderMap, StatusCode},
+        routing::post,
+        Json, Router,
+    };
+    use serde_json::Value;
+    use tokio::net::TcpListener;
+
+    #[tokio::test]
+    async fn test_purges_surrogate_keys_of_the_service() {
+        let app = Router::new().route(
+            "/service/{service}/purge",
+            post(|Path(service): Path<String>, headers: HeaderMap, Json(body): Json<Value>| async move {
+                if headers["fastly-key"] != "fastly-token" {
+                    return (StatusCode::UNAUTHORIZED, Json(json!({ "msg": "Provided credentials are missing or invalid" })));
+                }
+                assert_eq!(service, "service-1");
+                assert_eq!(body, json!({ "surrogate_keys": ["media-5"] }));
+                (StatusCode::OK, Json(json!({ "media-5": "purge-id" })))
+            }),
+        );
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let purger = FastlyPurger::new("fastly-token", "service-1", Duration::from_secs(5))
+            .with_api_base_url(&format!("http://{addr}"));
+        purger.purge(&["media-5".to_string()]).await.unwrap();
+
+        let revoked = FastlyPurger::new("revoked", "service-1", Duration::from_secs(5))
+            .with_api_base_url(&format!("http://{addr}"));
+        let error = revoked.purge(&["media-5".to_string()]).await.unwrap_err();
+        assert!(
+            matches!(error, CdnError::Rejected { status, .. } if status == StatusCode::UNAUTHORIZED)
+        );
+    }
+}
@@ -0,0 +1,267 @@
+//! Purging of responses cached by a CDN
+//!
+//! Responses carry surrogate keys naming the media and recipes they show (see
+//! [`CacheControlPolicy`]). After a change, [`CdnPurges`] asks the CDN to drop
+//! every response carrying an affected key, so stale metadata and variants are
+//! not served until they expire. Purges run in the background as `cdn_purge`
+//! jobs and are retried with exponential backoff, so a CDN outage never fails
+//! the request that caused the change.
+//!
+//! Metrics, labelled by `provider`:
+//! - `cdn_purges_total` - purge attempts, also labelled by `outcome`
+//! - `cdn_purges_abandoned_total` - purges given up after the last attempt
+//!
+//! [`CacheControlPolicy`]: crate::domain::value_objects::CacheControlPolicy
+
+mod cloudflare;
+mod fastly;
+
+pub use cloudflare::CloudflarePurger;
+pub use fastly::FastlyPurger;
+
+use std::{sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use reqwest::StatusCode;
+use thiserror::Error;
+use tracing::{error, info};
+
+use crate::{
+    application::events::{DomainEvent, EventSubscriber},
+    domain::value_objects::{media_surrogate_key, recipe_surrogate_key},
+    infrastructure::{
+        config::{CdnConfig, CdnProvider, JobsConfig},
+        jobs::{self, JobRegistry},
+    },
+};
+
+/// A purge the CDN did not carry out
+#[derive(Debug, Error)]
+pub enum CdnError {
+    #[error("CDN request failed: {0}")]
+    Request(#[from] reqwest::Error),
+
+    #[error("CDN rejected the purge with {status}: {body}")]
+    Rejected { status: StatusCode, body: String },
+}
+
+/// Client of a CDN's purge API
+#[async_trait]
+pub trait CdnPurger: Send + Sync + std::fmt::Debug {
+    /// Name of the CDN, labelling metrics and logs
+    fn provider(&self) -> &'static str;
+
+    /// Drop every cached response carrying any of `keys`
+    async fn purge(&self, keys: &[String]) -> Result<(), CdnError>;
+}
+
+/// Background purges of changed media and recipes
+///
+/// Cloning is cheap; clones purge through the same CDN client.
+#[derive(Debug, Clone)]
+pub struct CdnPurges {
+    purger: Option<Arc<dyn CdnPurger>>,
+    jobs: JobRegistry,
+    max_attempts: u32,
+    initial_backoff: Duration,
+}
+
+impl CdnPurges {
+    /// Purge nothing, for deployments without a CDN
+    #[must_use]
+    pub fn disabled() -> Self {
+        Self {
+            purger: None,
+            jobs: JobRegistry::new(),
+            max_attempts: 1,
+            initial_backoff: Duration::ZERO,
+        }
+    }
+
+    /// Purge through `purger`, reporting attempts to `jobs`
+    ///
+    /// A failed purge is attempted up to `max_attempts` times in total, waiting
+    /// `initial_backoff` before the first retry and twice as long before each
+    /// further one.
+    #[must_use]
+    pub fn new(
+        purger: Arc<dyn CdnPurger>,
+        jobs: JobRegistry,
+        max_attempts: u32,
+        initial_backoff: Duration,
+    ) -> Self {
+        jobs.register(jobs::CDN_PURGE);
+        Self { purger: Some(purger), jobs, max_attempts: max_attempts.max(1), initial_backoff }
+    }
+
+    /// Purge responses showing `media` or listing the media of `recipes`, in the background
+    pub fn purge(&self, media: &[i64], recipes: &[i64]) {
+        let Some(cdn) = self.purger.clone() else {
+            return;
+        };
+        let keys: Vec<String> = media
+            .iter()
+            .map(|id| media_surrogate_key(*id))
+            .chain(recipes.iter().map(|id| recipe_surrogate_key(*id)))
+            .collect();
+        if keys.is_empty() {
+            return;
+        }
+
+        let purges = self.clone();
+        tokio::spawn(async move { purges.purge_with_retries(cdn.as_ref(), &keys).await });
+    }
+
+    /// Attempt a purge until the CDN accepts it or the attempts run out
+    ///
+    /// Every attempt is a run of the `cdn_purge` job, queued while it waits
+    /// out its backoff. Returns whether the purge went through.
+    async fn purge_with_retries(&self, purger: &dyn CdnPurger, keys: &[String]) -> bool {
+        let provider = purger.provider();
+        let mut backoff = self.initial_backoff;
+
+        for attempt in 1..=self.max_attempts {
+            let queued = self.jobs.enqueue(jobs::CDN_PURGE);
+            if attempt > 1 {
+                tokio::time::sleep(backoff).await;
+                backoff = backoff.saturating_mul(2);
+            }
+
+            // Failures are logged and counted by the job registry
+            let result = queued.run(purger.purge(keys)).await;
+            let outcome = if result.is_ok() { "success" } else { "failure" };
+            metrics::counter!("cdn_purges_total", "provider" => provider, "outcome" => outcome)
+                .increment(1);
+            if result.is_ok() {
+                info!(provider, keys = %keys.join(" "), attempt, "Purged CDN cache");
+                return true;
+            }
+        }
+
+        metrics::counter!("cdn_purges_abandoned_total", "provider" => provider).increment(1);
+        error!(
+            provider,
+            keys = %keys.join(" "),
+            attempts = self.max_attempts,
+            "Giving up purging CDN cache; stale responses are served until they expire"
+        );
+        false
+    }
+}
+
+/// Purges whatever a change made stale
+///
+/// Responses about the media itself carry its key; attaching or detaching media
+/// changes the listings of the recipe.
+impl EventSubscriber for CdnPurges {
+    fn handle(&self, event: &DomainEvent) {
+        match event {
+            DomainEvent::MediaUploaded { .. } => {}
+            DomainEvent::ProcessingCompleted { media_id, .. }
+            | DomainEvent::ProcessingFailed { media_id, .. }
+            | DomainEvent::MediaDeleted { media_id, .. }
+            | DomainEvent::MediaTagsChanged { media_id, .. } => {
+                self.purge(&[media_id.as_i64()], &[]);
+            }
+            DomainEvent::MediaAttached { association, .. }
+            | DomainEvent::MediaDetached { association, .. } => {
+                self.purge(&[], &[association.recipe_id().as_i64()]);
+            }
+        }
+    }
+}
+
+/// The purges `config` asks for, retried per `jobs_config`
+///
+/// Purging is disabled unless `config` enables it.
+pub fn create_cdn_purges(
+    config: &CdnConfig,
+    jobs_config: &JobsConfig,
+    jobs: &JobRegistry,
+) -> CdnPurges {
+    if !config.enabled {
+        return CdnPurges::disabled();
+    }
+
+    let purger: Arc<dyn CdnPurger> = match config.provider {
+        CdnProvider::Cloudflare => Arc::new(CloudflarePurger::new(
+            config.api_token.expose(),
+            &config.cloudflare_zone_id,
+            config.request_timeout(),
+        )),
+        CdnProvider::Fastly => Arc::new(FastlyPurger::new(
+            config.api_token.expose(),
+            &config.fastly_service_id,
+            config.request_timeout(),
+        )),
+    };
+    info!("Purging changed media from the {} CDN", purger.provider());
+    CdnPurges::new(purger, jobs.clone(), jobs_config.max_attempts, jobs_config.initial_backoff())
+}
+
+/// Client for calls to a CDN API, bounded by `timeout`
+fn http_client(timeout: Duration) -> reqwest::Client {
+    reqwest::Client::builder().timeout(timeout).build().unwrap_or_default()
+}
+
+/// `Ok` for a successful response, the rejection otherwise
+async fn check_response(response: reqwest::Response) -> Result<(), CdnError> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(());
+    }
+    let body = response.text().await.unwrap_or_default();
+    Err(CdnError::Rejected { status, body })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Mutex;
+
+    /// CDN that rejects the first `failures` purges
+    #[derive(Debug, Default)]
+    struct FlakyPurger {
+        failures: u32,
+        attempts: AtomicU32,
+        purged: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl CdnPurger for FlakyPurger {
+        fn provider(&self) -> &'static str {
+            "flaky"
+        }
+
+        async fn purge(&self, keys: &[String]) -> Result<(), CdnError> {
+            if self.attempts.fetch_add(1, Ordering::SeqCst) < self.failures {
+                return Err(CdnError::Rejected {
+                    status: StatusCode::SERVICE_UNAVAILABLE,
+                    body: "try again".to_string(),
+                });
+            }
+            self.purged.lock().unwrap().extend_from_slice(keys);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_failed_purges_are_retried_as_jobs() {
+        let cdn = Arc::new(FlakyPurger { failures: 2, ..FlakyPurger::default() });
+        let jobs = JobRegistry::new();
+        let purges = CdnPurges::new(cdn.clone(), jobs.clone(), 3, Duration::ZERO);
+        let keys = vec!["media-5".to_string(), "recipe-2".to_string()];
+
+        assert!(purges.purge_with_retries(cdn.as_ref(), &keys).await);
+        assert_eq!(*cdn.purged.lock().unwrap(), keys);
+        let status = &jobs.statuses()[0];
+        assert_eq!(status.name, jobs::CDN_PURGE);
+        assert_eq!((status.runs, status.failures, status.backlog), (3, 2, 0));
+
+        let cdn = Arc::new(FlakyPurger { failures: 5, ..FlakyPurger::default() });
+        let purges = CdnPurges::new(cdn.clone(), JobRegistry::new(), 2, Duration::ZERO);
+        assert!(!purges.purge_with_retries(cdn.as_ref(), &keys).await);
+        assert_eq!(cdn.attempts.load(Ordering::SeqCst), 2);
+    }
+}
@@ -0,0 +1,369 @@
+//! Environment variable overrides of configuration settings
+//!
+//! Every setting with a default is read from a variable named after its key:
+//! `MEDIA_SERVICE_`, then the key in upper case with dots as underscores, so
+//! `storage.s3.bucket` comes from `MEDIA_SERVICE_STORAGE_S3_BUCKET`. The value
+//! is parsed as the type of the setting's default, so a new setting needs only
+//! its default to be configurable. A value that does not parse is reported
+//! when the configuration is validated rather than silently ignored.
+//!
+//! Any variable can instead be given as the path of a file holding its value,
+//! in the variable with `_FILE` appended (`JWT_SECRET_FILE`), as Docker and
+//! Kubernetes mount secrets. The variable itself wins when both are set.
+
+use std::collections::{HashMap, HashSet};
+
+use config::{builder::DefaultState, ConfigBuilder, ConfigError, Map, Value, ValueKind};
+
+/// Prefix of the variable derived from each setting's key
+const ENV_PREFIX: &str = "MEDIA_SERVICE_";
+
+/// Suffix of the variable naming a file a variable's value is read from
+const FILE_SUFFIX: &str = "_FILE";
+
+/// Variables that predate the derived names or are shared with other services,
+/// and the setting each one sets
+///
+/// The derived variable wins when both are set.
+const ALIASES: &[(&str, &str)] = &[
+    ("POSTGRES_HOST", "postgres.host"),
+    ("POSTGRES_PORT", "postgres.port"),
+    ("POSTGRES_DB", "postgres.database"),
+    ("POSTGRES_SCHEMA", "postgres.schema"),
+    ("POSTGRES_MAX_CONNECTIONS", "postgres.max_connections"),
+    ("POSTGRES_MIN_CONNECTIONS", "postgres.min_connections"),
+    ("POSTGRES_AUTO_MIGRATE", "postgres.auto_migrate"),
+    ("POSTGRES_ACQUIRE_TIMEOUT_SECONDS", "postgres.acquire_timeout_seconds"),
+    ("MEDIA_MANAGEMENT_DB_USER", "postgres.user"),
+    ("MEDIA_MANAGEMENT_DB_PASSWORD", "postgres.password"),
+    ("OAUTH2_SERVICE_ENABLED", "middleware.oauth2.enabled"),
+    ("OAUTH2_SERVICE_TO_SERVICE_ENABLED", "middleware.oauth2.service_to_service_enabled"),
+    ("OAUTH2_INTROSPECTION_ENABLED", "middleware.oauth2.introspection_enabled"),
+    ("OAUTH2_JWT_FALLBACK_ENABLED", "middleware.oauth2.jwt_fallback_enabled"),
+    ("OAUTH2_CLIENT_ID", "middleware.oauth2.client_id"),
+    ("OAUTH2_CLIENT_SECRET", "middleware.oauth2.client_secret"),
+    ("OAUTH2_SERVICE_BASE_URL", "middleware.oauth2.service_base_url"),
+    ("OAUTH2_TOKEN_CACHE_TTL_SECONDS", "middleware.oauth2.token_cache_ttl_seconds"),
+    (
+        "OAUTH2_CLIENT_CREDENTIALS_CACHE_TTL_SECONDS",
+        "middleware.oauth2.client_credentials_cache_ttl_seconds",
+    ),
+    ("OAUTH2_REQUEST_TIMEOUT_SECONDS", "middleware.oauth2.request_timeout_seconds"),
+    ("OAUTH2_MAX_RETRIES", "middleware.oauth2.max_retries"),
+    ("OAUTH2_RETRY_DELAY_MS", "middleware.oauth2.retry_delay_ms"),
+    ("JWT_SECRET", "middleware.oauth2.jwt_secret"),
+    ("VAULT_ADDR", "secrets.vault_address"),
+    ("VAULT_TOKEN", "secrets.vault_token"),
+    ("AWS_REGION", "secrets.aws_region"),
+    ("AWS_ACCESS_KEY_ID", "secrets.aws_access_key_id"),
+    ("AWS_SECRET_ACCESS_KEY", "secrets.aws_secret_access_key"),
+    ("AWS_SESSION_TOKEN", "secrets.aws_session_token"),
+    ("MEDIA_SERVICE_MIDDLEWARE_SECURITY_HSTS_ENABLED", "middleware.security.features.hsts"),
+    (
+        "MEDIA_SERVICE_MIDDLEWARE_SECURITY_HSTS_INCLUDE_SUBDOMAINS",
+        "middleware.security.features.hsts_subdomains",
+    ),
+    ("MEDIA_SERVICE_MIDDLEWARE_SECURITY_HSTS_PRELOAD", "middleware.security.features.hsts_preload"),
+    (
+        "MEDIA_SERVICE_MIDDLEWARE_SECURITY_CONTENT_TYPE_OPTIONS",
+        "middleware.security.features.content_type_options",
+    ),
+    ("MEDIA_SERVICE_STORAGE_QUOTA_MAX_BYTES_PER_USER", "quota.max_bytes_per_user"),
+    ("MEDIA_SERVICE_STORAGE_QUOTA_MAX_FILES_PER_USER", "quota.max_files_per_user"),
+];
+
+/// Settings not read from the environment; the runtime mode comes from `RUN_MODE`
+const EXCLUDED: &[&str] = &["mode"];
+
+/// Settings whose values are matched case-insensitively
+const LOWERCASE: &[&str] = &[
+    "storage.backend",
+    "storage.durability",
+    "cache.backend",
+    "cdn.provider",
+    "processing.render.provider.kind",
+    "event_stream.backend",
+    "event_stream.format",
+    "middleware.rate_limiting.backend",
+    "secrets.provider",
+    "logging.export.sink",
+];
+
+/// Per-tenant allowed upload types, written `tenant=type,type;tenant=type`
+const TENANT_FORMATS: &str = "middleware.validation.tenant_formats";
+
+/// Name of the variable a setting is read from
+fn env_var_name(key: &str) -> String {
+    format!("{ENV_PREFIX}{}", key.replace('.', "_").to_uppercase())
+}
+
+/// Override settings from the variables `lookup` finds, parsing each value as
+/// the type of the setting's entry in `defaults`
+///
+/// Also returns a message naming each variable whose value did not parse; those
+/// settings keep their value from the earlier layers.
+///
+/// # Errors
+/// Returns an error if an override cannot be recorded
+pub(super) fn apply_env_overrides(
+    mut builder: ConfigBuilder<DefaultState>,
+    defaults: &Map<String, Value>,
+    lookup: impl Fn(&str) -> Option<String>,
+) -> Result<(ConfigBuilder<DefaultState>, Vec<String>), ConfigError> {
+    let mut settings = HashMap::new();
+    collect_settings("", defaults, &mut settings);
+
+    let derived = settings.keys().map(|key| (env_var_name(key), key.as_str()));
+    let mut variables: Vec<(String, &str)> =
+        ALIASES.iter().map(|&(name, key)| (name.to_string(), key)).collect();
+    variables.extend(derived);
+    variables.push((env_var_name(TENANT_FORMATS), TENANT_FORMATS));
+
+    // A `_FILE` variable that is a setting's own, like `..._ENCRYPTION_KEYS_FILE`, is left to it
+    let names: HashSet<String> = variables.iter().map(|(name, _)| name.clone()).collect();
+
+    // Later overrides win, so derived names are applied after their aliases
+    let mut invalid = Vec::new();
+    for (name, key) in &variables {
+        let Some(raw) = lookup_value(name, &names, &lookup)? else {
+            continue;
+        };
+        if *key == TENANT_FORMATS {
+            builder = builder.set_override(TENANT_FORMATS, parse_tenant_formats(&raw))?;
+        } else if let Some(default) = settings.get(*key) {
+            match parse_value(key, default, &raw) {
+                Ok(Some(value)) => builder = builder.set_override(*key, value)?,
+                Ok(None) => {}
+                Err(expected) => invalid.push(format!("{name} must be {expected}, not {raw:?}")),
+            }
+        }
+    }
+
+    Ok((builder, invalid))
+}
+
+/// The value of variable `name`, or the contents of the file its `_FILE`
+/// variable names, without a trailing line break
+///
+/// # Errors
+/// Returns an error if the named file cannot be read
+fn lookup_value(
+    name: &str,
+    names: &HashSet<String>,
+    lookup: impl Fn(&str) -> Option<String>,
+) -> Result<Option<String>, ConfigError> {
+    if let Some(value) = lookup(name) {
+        return Ok(Some(value));
+    }
+    let file_var = format!("{name}{FILE_SUFFIX}");
+    if names.contains(&file_var) {
+        return Ok(None);
+    }
+    // The k8s template renders an unset variable as an empty string
+    let Some(path) = lookup(&file_var).filter(|path| !path.trim().is_empty()) else {
+        return Ok(None);
+    };
+
+    let contents = std::fs::read_to_string(path.trim()).map_err(|e| {
+        ConfigError::Message(format!("Failed to read {file_var} from {}: {e}", path.trim()))
+    })?;
+    Ok(Some(contents.trim_end_matches(['\r', '\n']).to_string()))
+}
+
+/// Flatten `table` into the dotted key and default value of each setting
+fn collect_settings(
+    prefix: &str,
+    table: &Map<String, Value>,
+    out: &mut HashMap<String, ValueKind>,
+) {
+    for (name, value) in table {
+        let key = if prefix.is_empty() { name.clone() } else { format!("{prefix}.{name}") };
+        match &value.kind {
+            ValueKind::Table(table) => collect_settings(&key, table, out),
+            _ if EXCLUDED.contains(&key.as_str()) => {}
+            kind => {
+                out.insert(key, kind.clone());
+            }
+        }
+    }
+}
+
+/// Parse `raw` as the type of `default`, `None` if it leaves the setting unset
+///
+/// # Errors
+/// Returns what the value should have been if it does not parse
+fn parse_value(
+    key: &str,
+    default: &ValueKind,
+    raw: &str,
+) -> Result<Option<ValueKind>, &'static str> {
+    let value = raw.trim();
+    match default {
+        // Optional and scalar settings; the k8s template renders an unset variable as an empty string
+        ValueKind::Nil
+        | ValueKind::Boolean(_)
+        | ValueKind::I64(_)
+        | ValueKind::I128(_)
+        | ValueKind::U64(_)
+        | ValueKind::U128(_)
+        | ValueKind::Float(_)
+            if value.is_empty() =>
+        {
+            Ok(None)
+        }
+        ValueKind::Boolean(_) => {
+            value.parse().map(|v| Some(ValueKind::Boolean(v))).map_err(|_| "true or false")
+        }
+        ValueKind::I64(_) | ValueKind::I128(_) | ValueKind::U64(_) | ValueKind::U128(_) => {
+            value.parse().map(|v| Some(ValueKind::U64(v))).map_err(|_| "a whole number")
+        }
+        ValueKind::Float(_) => {
+            value.parse().map(|v| Some(ValueKind::Float(v))).map_err(|_| "a number")
+        }
+        ValueKind::Array(_) => {
+            let items: Vec<String> = raw
+                .split(',')
+                .map(|s| s.trim().trim_matches('"').to_string())
+                .filter(|s| !s.is_empty())
+                .collect();
+            Ok((!items.is_empty()).then(|| items.into()))
+        }
+        ValueKind::String(_) if LOWERCASE.contains(&key) => Ok(Some(raw.to_lowercase().into())),
+        ValueKind::Nil | ValueKind::String(_) | ValueKind::Table(_) => Ok(Some(raw.into())),
+    }
+}
+
+fn parse_tenant_formats(raw: &str) -> HashMap<String, Vec<String>> {
+    raw.split(';')
+        .filter_map(|entry| entry.split_once('='))
+        .map(|(tenant, types)| {
+            let types =
+                types.split(',').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect();
+            (tenant.trim().to_string(), types)
+        })
+        .filter(|(tenant, _)| !tenant.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::config::{AppConfig, CacheBackend, RuntimeMode, StorageBackend};
+
+    fn load(vars: &[(&str, &str)]) -> AppConfig {
+        let vars: HashMap<String, String> =
+            vars.iter().map(|&(name, value)| (name.to_string(), value.to_string())).collect();
+        AppConfig::load_layered(RuntimeMode::Production, "does-not-exist", |name| {
+            vars.get(name).cloned()
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_env_var_name_is_derived_from_key() {
+        assert_eq!(env_var_name("storage.s3.bucket"), "MEDIA_SERVICE_STORAGE_S3_BUCKET");
+    }
+
+    #[test]
+    fn test_settings_are_parsed_as_their_default_type() {
+        let config = load(&[
+            ("MEDIA_SERVICE_SERVER_PORT", "8080"),
+            ("MEDIA_SERVICE_SERVER_READ_ONLY", "true"),
+            ("MEDIA_SERVICE_STORAGE_BACKEND", "S3"),
+            ("MEDIA_SERVICE_QUOTA_MAX_FILES_PER_USER", "25"),
+            ("MEDIA_SERVICE_CACHE_BACKEND", "Redis"),
+            ("MEDIA_SERVICE_MIDDLEWARE_AUTH_REQUIRE_AUTH_ROUTES", "/admin, \"/upload\",,"),
+            ("MEDIA_SERVICE_MIDDLEWARE_VALIDATION_TENANT_FORMATS", "a=image/png,image/webp;b="),
+        ]);
+
+        assert_eq!(config.server.port, 8080);
+        assert!(config.server.read_only);
+        assert_eq!(config.storage.backend, StorageBackend::S3);
+        assert_eq!(config.quota.max_files_per_user, Some(25));
+        assert_eq!(config.cache.backend, CacheBackend::Redis);
+        assert_eq!(config.middleware.auth.require_auth_routes, ["/admin", "/upload"]);
+        assert_eq!(config.middleware.validation.tenant_formats["a"], ["image/png", "image/webp"]);
+        assert!(config.middleware.validation.tenant_formats["b"].is_empty());
+    }
+
+    #[test]
+    fn test_unparseable_values_keep_the_default_and_are_reported() {
+        let config = load(&[
+            ("MEDIA_SERVICE_SERVER_PORT", "abc"),
+            ("MEDIA_SERVICE_MIDDLEWARE_AUTH_ENABLED", "maybe"),
+            ("POSTGRES_MAX_CONNECTIONS", "-5"),
+            ("MEDIA_SERVICE_STORAGE_LEGACY_UPLOAD_SUNSET", ""),
+            ("MEDIA_SERVICE_SERVER_READ_ONLY", ""),
+        ]);
+
+        assert_eq!(config.server.port, 3000);
+        assert!(config.middleware.auth.enabled);
+        assert!(config.storage.legacy_upload.sunset.is_none());
+        assert!(!config.server.read_only);
+
+        let error = config.validate().unwrap_err();
+        let named = |variable: &str| error.violations.iter().any(|v| v.starts_with(variable));
+        assert!(named("MEDIA_SERVICE_SERVER_PORT must be a whole number, not \"abc\""), "{error}");
+        assert!(named("MEDIA_SERVICE_MIDDLEWARE_AUTH_ENABLED must be true or false"), "{error}");
+        assert!(named("POSTGRES_MAX_CONNECTIONS must be a whole number"), "{error}");
+        // Empty values leave settings unset, as the k8s template renders unset variables
+        assert!(!named("MEDIA_SERVICE_STORAGE_LEGACY_UPLOAD_SUNSET"), "{error}");
+        assert!(!named("MEDIA_SERVICE_SERVER_READ_ONLY"), "{error}");
+    }
+
+    #[test]
+    fn test_derived_name_wins_over_alias() {
+        let config = load(&[("POSTGRES_HOST", "alias-host"), ("POSTGRES_PORT", "6543")]);
+        assert_eq!(config.postgres.host, "alias-host");
+        assert_eq!(config.postgres.port, 6543);
+
+        let config = load(&[("MEDIA_SERVICE_STORAGE_QUOTA_MAX_BYTES_PER_USER", "1024")]);
+        assert_eq!(config.quota.max_bytes_per_user, Some(1024));
+
+        let config = load(&[
+            ("POSTGRES_HOST", "alias-host"),
+            ("MEDIA_SERVICE_POSTGRES_HOST", "derived-host"),
+            ("MEDIA_SERVICE_MIDDLEWARE_SECURITY_HSTS_ENABLED", "false"),
+        ]);
+        assert_eq!(config.postgres.host, "derived-host");
+        assert!(!config.middleware.security.features.hsts);
+    }
+
+    #[test]
+    fn test_values_are_read_from_file_variables() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("jwt_secret");
+        std::fs::write(&path, "from-file\n").unwrap();
+        let path = path.to_str().unwrap();
+
+        let config = load(&[
+            ("JWT_SECRET_FILE", path),
+            ("MEDIA_SERVICE_MIDDLEWARE_AUTH_JWT_SECRET_FILE", path),
+            ("MEDIA_MANAGEMENT_DB_PASSWORD", "from-variable"),
+            ("MEDIA_MANAGEMENT_DB_PASSWORD_FILE", path),
+            ("MEDIA_SERVICE_STORAGE_ENCRYPTION_KEYS_FILE", path),
+        ]);
+
+        assert_eq!(config.middleware.oauth2.jwt_secret.expose(), "from-file");
+        assert_eq!(config.middleware.auth.jwt_secret.expose(), "from-file");
+        assert_eq!(config.postgres.password.expose(), "from-variable");
+        assert!(config.storage.encryption.keys.is_empty());
+        assert_eq!(config.storage.encryption.keys_file.as_deref(), Some(path));
+    }
+
+    #[test]
+    fn test_unreadable_file_variable_is_an_error() {
+        let error = AppConfig::load_layered(RuntimeMode::Production, "does-not-exist", |name| {
+            (name == "JWT_SECRET_FILE").then(|| "/does/not/exist".to_string())
+        })
+        .unwrap_err();
+
+        assert!(error.to_string().contains("JWT_SECRET_FILE"));
+    }
+
+    #[test]
+    fn test_mode_is_not_read_from_the_environment() {
+        let config = load(&[("MEDIA_SERVICE_MODE", "local")]);
+        assert_eq!(config.mode, RuntimeMode::Production);
+    }
+}
@@ -0,0 +1,292 @@
+//! Settings read from a secret manager instead of the environment
+//!
+//! With `secrets.provider` set, one secret is read when the configuration is
+//! loaded: a `HashiCorp` Vault KV v2 entry or an AWS Secrets Manager secret
+//! holding a JSON object. Its entries are named like environment variables
+//! (`JWT_SECRET`, `MEDIA_MANAGEMENT_DB_PASSWORD`, ...) and set every variable
+//! the environment leaves unset.
+
+use std::{collections::HashMap, fmt::Write as _, time::Duration};
+
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+use super::{SecretsConfig, SecretsProvider};
+
+/// A secret the secret manager did not hand out
+#[derive(Debug, Error)]
+pub enum SecretManagerError {
+    #[error("Secret manager request failed: {0}")]
+    Request(#[from] reqwest::Error),
+
+    #[error("Secret manager rejected the request with {status}: {body}")]
+    Rejected { status: StatusCode, body: String },
+
+    #[error("Secret is malformed: {0}")]
+    Malformed(String),
+}
+
+/// Read the entries of the secret `config` names, none without a provider
+///
+/// # Errors
+/// Returns an error if the secret cannot be read or is not a JSON object of
+/// scalar values
+pub(super) async fn fetch_secrets(
+    config: &SecretsConfig,
+) -> Result<HashMap<String, String>, SecretManagerError> {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(config.request_timeout_seconds))
+        .build()?;
+
+    match config.provider {
+        SecretsProvider::None => Ok(HashMap::new()),
+        SecretsProvider::Vault => fetch_vault(&client, config).await,
+        SecretsProvider::Aws => fetch_aws(&client, config, Utc::now()).await,
+    }
+}
+
+/// Read a Vault KV v2 entry, `GET /v1/{mount}/data/{path}`
+async fn fetch_vault(
+    client: &reqwest::Client,
+    config: &SecretsConfig,
+) -> Result<HashMap<String, String>, SecretManagerError> {
+    let url = format!(
+        "{}/v1/{}/data/{}",
+        config.vault_address.trim_end_matches('/'),
+        config.vault_mount.trim_matches('/'),
+        config.vault_path.trim_matches('/')
+    );
+    let response =
+        client.get(url).header("x-vault-token", config.vault_token.expose()).send().await?;
+    let body: Value = check_response(response).await?.json().await?;
+
+    entries(&body["data"]["data"])
+}
+
+/// Read an AWS Secrets Manager secret through `GetSecretValue`, signed with Signature V4
+async fn fetch_aws(
+    client: &reqwest::Client,
+    config: &SecretsConfig,
+    timestamp: DateTime<Utc>,
+) -> Result<HashMap<String, String>, SecretManagerError> {
+    let endpoint = if config.aws_endpoint.is_empty() {
+        format!("https://secretsmanager.{}.amazonaws.com", config.aws_region)
+    } else {
+        config.aws_endpoint.trim_end_matches('/').to_string()
+    };
+    let host = endpoint.split_once("://").map_or(endpoint.as_str(), |(_, host)| host);
+    let payload = json!({ "SecretId": config.aws_secret_id }).to_string();
+    let amz_date = timestamp.format("%Y%m%dT%H%M%SZ").to_string();
+
+    // Signed headers, sorted by name
+    let mut headers = vec![
+        ("content-type", "application/x-amz-json-1.1"),
+        ("host", host),
+        ("x-amz-date", amz_date.as_str()),
+    ];
+    if !config.aws_session_token.is_empty() {
+        headers.push(("x-amz-security-token", config.aws_session_token.expose()));
+    }
+    headers.push(("x-amz-target", "secretsmanager.GetSecretValue"));
+    let authorization = aws_authorization(config, &headers, &payload, timestamp);
+
+    let mut request = client.post(format!("{endpoint}/"));
+    for (name, value) in headers.iter().filter(|(name, _)| *name != "host") {
+        request = request.header(*name, *value);
+    }
+    let response = request.header("authorization", authorization).body(payload).send().await?;
+    let body: Value = check_response(response).await?.json().await?;
+
+    let secret = body["SecretString"].as_str().ok_or_else(|| {
+        SecretManagerError::Malformed(format!("{} has no SecretString", config.aws_secret_id))
+    })?;
+    let secret: Value = serde_json::from_str(secret)
+        .map_err(|e| SecretManagerError::Malformed(format!("{}: {e}", config.aws_secret_id)))?;
+    entries(&secret)
+}
+
+/// `Authorization` header of a Secrets Manager request with `headers` and `payload`
+fn aws_authorization(
+    config: &SecretsConfig,
+    headers: &[(&str, &str)],
+    payload: &str,
+    timestamp: DateTime<Utc>,
+) -> String {
+    let date_stamp = timestamp.format("%Y%m%d").to_string();
+    let scope = format!("{date_stamp}/{}/secretsmanager/aws4_request", config.aws_region);
+
+    let canonical_headers = headers.iter().fold(String::new(), |mut acc, (name, value)| {
+        let _ = writeln!(acc, "{name}:{}", value.trim());
+        acc
+    });
+    let signed_headers = headers.iter().map(|(name, _)| *name).collect::<Vec<_>>().join(";");
+    let canonical_request = format!(
+        "POST\n/\n\n{canonical_headers}\n{signed_headers}\n{}",
+        sha256_hex(payload.as_bytes())
+    );
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{scope}\n{}",
+        timestamp.format("%Y%m%dT%H%M%SZ"),
+        sha256_hex(canonical_request.as_bytes())
+    );
+
+    let signing_key =
+        [date_stamp.as_str(), config.aws_region.as_str(), "secretsmanager", "aws4_request"]
+            .iter()
+            .fold(
+                format!("AWS4{}", config.aws_secret_access_key.expose()).into_bytes(),
+                |key, part| hmac_sha256(&key, part.as_bytes()),
+            );
+    let signature = hex::encode(hmac_sha256(&signing_key, string_to_sign.as_bytes()));
+
+    format!(
+        "AWS4-HMAC-SHA256 Credential={}/{scope}, SignedHeaders={signed_headers}, Signature={signature}",
+        config.aws_access_key_id
+    )
+}
+
+/// The entries of a secret, with numbers and booleans written as strings
+fn entries(secret: &Value) -> Result<HashMap<String, String>, SecretManagerError> {
+    let Some(object) = secret.as_object() else {
+        return Err(SecretManagerError::Malformed("expected a JSON object".to_string()));
+    };
+    object
+        .iter()
+        .map(|(name, value)| {
+            let value = match value {
+                Value::String(value) => value.clone(),
+                Value::Number(_) | Value::Bool(_) => value.to_string(),
+                _ => {
+                    return Err(SecretManagerError::Malformed(format!(
+                        "{name} must be a string, number or boolean"
+                    )))
+                }
+            };
+            Ok((name.clone(), value))
+        })
+        .collect()
+}
+
+/// The response, if successful
+async fn check_response(
+    response: reqwest::Response,
+) -> Result<reqwest::Response, SecretManagerError> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    let body = response.text().await.unwrap_or_default();
+    Err(SecretManagerError::Rejected { status, body })
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    hex::encode(Sha256::digest(data))
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC can take key of any size");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        extract::Path,
+        http::HeaderMap,
+        routing::{get, post},
+        Json, Router,
+    };
+    use tokio::net::TcpListener;
+
+    async fn serve(app: Router) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        format!("http://{addr}")
+    }
+
+    #[tokio::test]
+    async fn test_reads_vault_kv_entry() {
+        let app = Router::new().route(
+            "/v1/{mount}/data/{*path}",
+            get(|Path((mount, path)): Path<(String, String)>, headers: HeaderMap| async move {
+                if headers["x-vault-token"] != "vault-token" {
+                    return (
+                        StatusCode::FORBIDDEN,
+                        Json(json!({ "errors": ["permission denied"] })),
+                    );
+                }
+                assert_eq!((mount.as_str(), path.as_str()), ("kv", "media-service/prod"));
+                let data = json!({ "JWT_SECRET": "from-vault", "POSTGRES_PORT": 6432 });
+                (StatusCode::OK, Json(json!({ "data": { "data": data, "metadata": {} } })))
+            }),
+        );
+        let mut config = SecretsConfig {
+            provider: SecretsProvider::Vault,
+            vault_address: serve(app).await,
+            vault_token: "vault-token".into(),
+            vault_mount: "kv".to_string(),
+            vault_path: "media-service/prod".to_string(),
+            ..SecretsConfig::default()
+        };
+
+        let secrets = fetch_secrets(&config).await.unwrap();
+        assert_eq!(secrets["JWT_SECRET"], "from-vault");
+        assert_eq!(secrets["POSTGRES_PORT"], "6432");
+
+        config.vault_token = "wrong".into();
+        let error = fetch_secrets(&config).await.unwrap_err();
+        assert!(matches!(error, SecretManagerError::Rejected { status, .. } if status == 403));
+    }
+
+    #[tokio::test]
+    async fn test_reads_aws_secret_with_signed_request() {
+        let app = Router::new().route(
+            "/",
+            post(|headers: HeaderMap, body: String| async move {
+                assert_eq!(headers["x-amz-target"], "secretsmanager.GetSecretValue");
+                assert_eq!(headers["x-amz-security-token"], "session");
+                let authorization = headers["authorization"].to_str().unwrap();
+                assert!(authorization.starts_with("AWS4-HMAC-SHA256 Credential=AKID/"));
+                assert!(authorization.contains("/eu-west-1/secretsmanager/aws4_request"));
+                assert!(authorization.contains(
+                    "SignedHeaders=content-type;host;x-amz-date;x-amz-security-token;x-amz-target"
+                ));
+                let body: Value = serde_json::from_str(&body).unwrap();
+                assert_eq!(body, json!({ "SecretId": "media-service/prod" }));
+
+                let secret = json!({ "MEDIA_MANAGEMENT_DB_PASSWORD": "from-aws" }).to_string();
+                Json(json!({ "Name": "media-service/prod", "SecretString": secret }))
+            }),
+        );
+        let config = SecretsConfig {
+            provider: SecretsProvider::Aws,
+            aws_endpoint: serve(app).await,
+            aws_region: "eu-west-1".to_string(),
+            aws_secret_id: "media-service/prod".to_string(),
+            aws_access_key_id: "AKID".to_string(),
+            aws_secret_access_key: "secret".into(),
+            aws_session_token: "session".into(),
+            ..SecretsConfig::default()
+        };
+
+        let secrets = fetch_secrets(&config).await.unwrap();
+        assert_eq!(secrets["MEDIA_MANAGEMENT_DB_PASSWORD"], "from-aws");
+    }
+
+    #[test]
+    fn test_nested_values_are_malformed() {
+        assert!(entries(&json!({ "A": "a", "B": true })).is_ok());
+        assert!(matches!(
+            entries(&json!({ "A": { "nested": 1 } })),
+            Err(SecretManagerError::Malformed(_))
+        ));
+        assert!(matches!(entries(&json!("not an object")), Err(SecretManagerError::Malformed(_))));
+    }
+}
@@ -0,0 +1,509 @@
+//! Startup checks of invariants the configuration types cannot express
+//!
+//! Every violation is collected so an operator can fix them all at once,
+//! instead of meeting them one at a time as runtime failures.
+
+use std::path::Path;
+
+use axum::http::{HeaderName, HeaderValue, Method};
+use thiserror::Error;
+
+use crate::domain::value_objects::RenderSize;
+use crate::presentation::middleware::cors::is_valid_origin_pattern;
+
+use super::{
+    AppConfig, CacheBackend, CdnProvider, EventStreamBackend, LogSink, RateLimitBackend,
+    RuntimeMode, StorageBackend, WEBHOOK_EVENTS,
+};
+
+/// JWT secret the loader defaults to, which must be replaced outside local development
+const DEFAULT_JWT_SECRET: &str = "change-me-in-production";
+
+/// Smallest part S3 accepts in a multipart upload, other than the last (5 MiB)
+const MIN_S3_PART_SIZE: u64 = 5 * 1024 * 1024;
+
+/// Configuration that breaks one or more invariants
+#[derive(Debug, Error)]
+#[error("Invalid configuration:\n  - {}", violations.join("\n  - "))]
+pub struct ConfigValidationError {
+    /// One actionable message per violation, naming the setting to change
+    pub violations: Vec<String>,
+}
+
+impl AppConfig {
+    /// Check invariants across settings, reporting every violation found
+    ///
+    /// Storage paths are created if missing and probed for writability.
+    ///
+    /// # Errors
+    /// Returns every violation found
+    pub fn validate(&self) -> Result<(), ConfigValidationError> {
+        let mut violations = self.invalid_env.clone();
+        self.validate_postgres(&mut violations);
+        self.validate_auth(&mut violations);
+        self.validate_storage(&mut violations);
+        self.validate_security_headers(&mut violations);
+        self.validate_cors(&mut violations);
+        self.validate_rate_limits(&mut violations);
+        self.validate_jobs(&mut violations);
+        self.validate_cache(&mut violations);
+        self.validate_cdn(&mut violations);
+        self.validate_webhooks(&mut violations);
+        self.validate_event_stream(&mut violations);
+        self.validate_log_export(&mut violations);
+        self.validate_performance(&mut violations);
+        self.validate_render(&mut violations);
+
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(ConfigValidationError { violations })
+        }
+    }
+
+    fn validate_postgres(&self, violations: &mut Vec<String>) {
+        let postgres = &self.postgres;
+        if postgres.max_connections == 0 {
+            violations.push("POSTGRES_MAX_CONNECTIONS must be at least 1".to_string());
+        }
+        if postgres.min_connections > postgres.max_connections {
+            violations.push(format!(
+                "POSTGRES_MIN_CONNECTIONS ({}) must not exceed POSTGRES_MAX_CONNECTIONS ({})",
+                postgres.min_connections, postgres.max_connections
+            ));
+        }
+    }
+
+    fn validate_auth(&self, violations: &mut Vec<String>) {
+        let auth = &self.middleware.auth;
+        if auth.enabled && self.mode == RuntimeMode::Production {
+            let secret = auth.jwt_secret.expose().trim();
+            if secret.is_empty() || secret == DEFAULT_JWT_SECRET {
+                violations.push(
+                    "MEDIA_SERVICE_MIDDLEWARE_AUTH_JWT_SECRET must be set to a non-default secret in production"
+                        .to_string(),
+                );
+            }
+        }
+
+        let oauth2 = &self.middleware.oauth2;
+        if oauth2.enabled && oauth2.service_to_service_enabled && oauth2.client_secret.is_empty() {
+            violations.push(
+                "OAUTH2_CLIENT_SECRET must be set when service-to-service OAuth2 is enabled"
+                    .to_string(),
+            );
+        }
+    }
+
+    fn validate_storage(&self, violations: &mut Vec<String>) {
+        let storage = &self.storage;
+        if storage.backend == StorageBackend::Filesystem {
+            check_writable("MEDIA_SERVICE_STORAGE_BASE_PATH", &storage.base_path, violations);
+        }
+        // Resumable uploads are staged here whatever the backend
+        check_writable("MEDIA_SERVICE_STORAGE_TEMP_PATH", &storage.temp_path, violations);
+
+        if storage.s3.multipart_threshold_bytes < MIN_S3_PART_SIZE {
+            violations.push(format!(
+                "MEDIA_SERVICE_STORAGE_S3_MULTIPART_THRESHOLD_BYTES must be at least {MIN_S3_PART_SIZE}, the smallest part S3 accepts, not {}",
+                storage.s3.multipart_threshold_bytes
+            ));
+        }
+    }
+
+    fn validate_security_headers(&self, violations: &mut Vec<String>) {
+        let security = &self.middleware.security;
+        if !security.enabled {
+            return;
+        }
+
+        if let Some(csp) = &security.csp_policy {
+            if csp.trim().is_empty() || HeaderValue::from_str(csp).is_err() {
+                violations.push(
+                    "MEDIA_SERVICE_MIDDLEWARE_SECURITY_CSP_POLICY must be a non-empty header value; unset it to send no policy"
+                        .to_string(),
+                );
+            }
+        }
+
+        let frame_options = security.frame_options.as_str();
+        let valid_frame_options = matches!(frame_options, "DENY" | "SAMEORIGIN")
+            || frame_options
+                .strip_prefix("ALLOW-FROM ")
+                .is_some_and(|uri| !uri.trim().is_empty() && HeaderValue::from_str(uri).is_ok());
+        if !valid_frame_options {
+            violations.push(format!(
+                "MEDIA_SERVICE_MIDDLEWARE_SECURITY_FRAME_OPTIONS must be DENY, SAMEORIGIN or \"ALLOW-FROM <uri>\", not {frame_options:?}"
+            ));
+        }
+
+        if !matches!(security.xss_protection.as_str(), "0" | "1" | "1; mode=block") {
+            violations.push(format!(
+                "MEDIA_SERVICE_MIDDLEWARE_SECURITY_XSS_PROTECTION must be 0, 1 or \"1; mode=block\", not {:?}",
+                security.xss_protection
+            ));
+        }
+    }
+
+    fn validate_cors(&self, violations: &mut Vec<String>) {
+        let cors = &self.middleware.cors;
+        for origin in &cors.allowed_origins {
+            if !is_valid_origin_pattern(origin) {
+                violations.push(format!(
+                    "MEDIA_SERVICE_MIDDLEWARE_CORS_ALLOWED_ORIGINS entries must be *, an origin such as https://recipes.example.com or a wildcard subdomain such as https://*.example.com, not {origin:?}"
+                ));
+            }
+        }
+        if cors.allow_credentials && cors.allowed_origins.iter().any(|origin| origin.trim() == "*")
+        {
+            violations.push(
+                "MEDIA_SERVICE_MIDDLEWARE_CORS_ALLOW_CREDENTIALS must not be set while any origin (*) is allowed"
+                    .to_string(),
+            );
+        }
+        for method in &cors.allowed_methods {
+            if Method::from_bytes(method.as_bytes()).is_err() {
+                violations.push(format!(
+                    "MEDIA_SERVICE_MIDDLEWARE_CORS_ALLOWED_METHODS entries must be HTTP methods, not {method:?}"
+                ));
+            }
+        }
+        for header in &cors.allowed_headers {
+            if HeaderName::from_bytes(header.trim().as_bytes()).is_err() {
+                violations.push(format!(
+                    "MEDIA_SERVICE_MIDDLEWARE_CORS_ALLOWED_HEADERS entries must be header names, not {header:?}"
+                ));
+            }
+        }
+    }
+
+    fn validate_rate_limits(&self, violations: &mut Vec<String>) {
+        let rate_limiting = &self.middleware.rate_limiting;
+        if !rate_limiting.enabled {
+            return;
+        }
+
+        if rate_limiting.backend == RateLimitBackend::Redis {
+            if let Err(e) = ::redis::Client::open(rate_limiting.redis_url.expose().as_str()) {
+                violations.push(format!(
+                    "MEDIA_SERVICE_MIDDLEWARE_RATE_LIMITING_REDIS_URL must be a Redis URL when the redis backend is selected: {e}"
+                ));
+            }
+        }
+
+        let tiers = &rate_limiting.tiers;
+        let limits = [
+            ("DEFAULT_REQUESTS_PER_MINUTE", rate_limiting.default_requests_per_minute),
+            ("DEFAULT_BURST_CAPACITY", rate_limiting.default_burst_capacity),
+            ("TIERS_HEALTH_REQUESTS_PER_MINUTE", tiers.health_requests_per_minute),
+            ("TIERS_PUBLIC_REQUESTS_PER_MINUTE", tiers.public_requests_per_minute),
+            ("TIERS_AUTHENTICATED_REQUESTS_PER_MINUTE", tiers.authenticated_requests_per_minute),
+            ("TIERS_UPLOAD_REQUESTS_PER_MINUTE", tiers.upload_requests_per_minute),
+            ("TIERS_ADMIN_REQUESTS_PER_MINUTE", tiers.admin_requests_per_minute),
+        ];
+        for (name, limit) in limits {
+            if limit == 0 {
+                violations.push(format!(
+                    "MEDIA_SERVICE_MIDDLEWARE_RATE_LIMITING_{name} must be at least 1 while rate limiting is enabled"
+                ));
+            }
+        }
+
+        if tiers.authenticated_requests_per_minute < tiers.public_requests_per_minute {
+            violations.push(format!(
+                "MEDIA_SERVICE_MIDDLEWARE_RATE_LIMITING_TIERS_AUTHENTICATED_REQUESTS_PER_MINUTE ({}) must not be below the public tier ({}), or signing in lowers a client's limit",
+                tiers.authenticated_requests_per_minute, tiers.public_requests_per_minute
+            ));
+        }
+    }
+
+    fn validate_jobs(&self, violations: &mut Vec<String>) {
+        if self.jobs.max_attempts == 0 {
+            violations.push("MEDIA_SERVICE_JOBS_MAX_ATTEMPTS must be at least 1".to_string());
+        }
+    }
+
+    fn validate_cache(&self, violations: &mut Vec<String>) {
+        let cache = &self.cache;
+        if !cache.enabled {
+            return;
+        }
+
+        if cache.ttl_seconds == 0 {
+            violations.push(
+                "MEDIA_SERVICE_CACHE_TTL_SECONDS must be at least 1 while caching is enabled"
+                    .to_string(),
+            );
+        }
+        if cache.backend == CacheBackend::Redis {
+            if cache.redis_url.expose().trim().is_empty() {
+                violations.push(
+                    "MEDIA_SERVICE_CACHE_REDIS_URL must be set when the redis cache backend is enabled"
+                        .to_string(),
+                );
+            } else if let Err(e) = ::redis::Client::open(cache.redis_url.expose().as_str()) {
+                violations.push(format!(
+                    "MEDIA_SERVICE_CACHE_REDIS_URL must be a Redis URL when the redis cache backend is enabled: {e}"
+                ));
+            }
+        }
+    }
+
+    fn validate_cdn(&self, violations: &mut Vec<String>) {
+        let cdn = &self.cdn;
+        if !cdn.enabled {
+            return;
+        }
+
+        if cdn.api_token.expose().trim().is_empty() {
+            violations.push(
+                "MEDIA_SERVICE_CDN_API_TOKEN must be set while CDN purging is enabled".to_string(),
+            );
+        }
+        let (setting, id, provider) = match cdn.provider {
+            CdnProvider::Cloudflare => {
+                ("MEDIA_SERVICE_CDN_CLOUDFLARE_ZONE_ID", &cdn.cloudflare_zone_id, "cloudflare")
+            }
+            CdnProvider::Fastly => {
+                ("MEDIA_SERVICE_CDN_FASTLY_SERVICE_ID", &cdn.fastly_service_id, "fastly")
+            }
+        };
+        if id.trim().is_empty() {
+            violations.push(format!("{setting} must be set when the {provider} CDN is purged"));
+        }
+        if !self.server.cache_control.surrogate_keys {
+            violations.push(
+                "MEDIA_SERVICE_SERVER_CACHE_CONTROL_SURROGATE_KEYS must be true while CDN purging is enabled, or there is nothing to purge by"
+                    .to_string(),
+            );
+        }
+    }
+
+    fn validate_webhooks(&self, violations: &mut Vec<String>) {
+        let webhooks = &self.webhooks;
+        if !webhooks.enabled {
+            return;
+        }
+
+        if webhooks.endpoints.is_empty() {
+            violations.push(
+                "MEDIA_SERVICE_WEBHOOKS_ENDPOINTS must list at least one URL while webhooks are enabled"
+                    .to_string(),
+            );
+        }
+        for endpoint in &webhooks.endpoints {
+            if !endpoint.starts_with("https://") && !endpoint.starts_with("http://") {
+                violations.push(format!(
+                    "MEDIA_SERVICE_WEBHOOKS_ENDPOINTS entries must be http(s) URLs, not {endpoint:?}"
+                ));
+            }
+        }
+        for event in &webhooks.events {
+            if !WEBHOOK_EVENTS.contains(&event.as_str()) {
+                violations.push(format!(
+                    "MEDIA_SERVICE_WEBHOOKS_EVENTS entries must be one of {}, not {event:?}",
+                    WEBHOOK_EVENTS.join(", ")
+                ));
+            }
+        }
+        if self.mode == RuntimeMode::Production
+            && webhooks.signing_secret.expose().trim().is_empty()
+        {
+            violations.push(
+                "MEDIA_SERVICE_WEBHOOKS_SIGNING_SECRET must be set in production so receivers can verify deliveries"
+                    .to_string(),
+            );
+        }
+    }
+
+    fn validate_event_stream(&self, violations: &mut Vec<String>) {
+        let event_stream = &self.event_stream;
+        if !event_stream.enabled {
+            return;
+        }
+
+        if event_stream.brokers.is_empty() {
+            violations.push(
+                "MEDIA_SERVICE_EVENT_STREAM_BROKERS must list at least one broker while event publishing is enabled"
+                    .to_string(),
+            );
+        }
+        for broker in &event_stream.brokers {
+            match event_stream.backend {
+                EventStreamBackend::Kafka => {
+                    if !broker.starts_with("https://") && !broker.starts_with("http://") {
+                        violations.push(format!(
+                            "MEDIA_SERVICE_EVENT_STREAM_BROKERS entries must be http(s) URLs of Kafka REST Proxies, not {broker:?}"
+                        ));
+                    }
+                }
+                EventStreamBackend::Nats => {
+                    if let Err(e) = broker.parse::<async_nats::ServerAddr>() {
+                        violations.push(format!(
+                            "MEDIA_SERVICE_EVENT_STREAM_BROKERS entries must be NATS server addresses, not {broker:?}: {e}"
+                        ));
+                    }
+                }
+            }
+        }
+        let topic = event_stream.topic.trim();
+        if topic.is_empty() || topic.contains(char::is_whitespace) {
+            violations.push(format!(
+                "MEDIA_SERVICE_EVENT_STREAM_TOPIC must be a topic name without spaces, not {:?}",
+                event_stream.topic
+            ));
+        }
+    }
+
+    fn validate_log_export(&self, violations: &mut Vec<String>) {
+        let export = &self.logging.export;
+        let endpoint = export.endpoint.trim();
+        let valid = match export.sink {
+            LogSink::None => true,
+            LogSink::Syslog => endpoint.starts_with("udp://") || endpoint.starts_with("tcp://"),
+            LogSink::Loki | LogSink::Otlp => {
+                endpoint.starts_with("https://") || endpoint.starts_with("http://")
+            }
+        };
+        if !valid {
+            violations.push(format!(
+                "MEDIA_SERVICE_LOGGING_EXPORT_ENDPOINT must be a udp:// or tcp:// address for syslog, or an http(s) URL for loki and otlp, not {endpoint:?}"
+            ));
+        }
+    }
+
+    fn validate_performance(&self, violations: &mut Vec<String>) {
+        let performance = &self.performance;
+        if performance.max_concurrent_requests == 0 {
+            violations.push("PERFORMANCE_MAX_CONCURRENT_REQUESTS must be at least 1".to_string());
+        }
+        if performance.max_concurrent_uploads == 0 {
+            violations.push("PERFORMANCE_MAX_CONCURRENT_UPLOADS must be at least 1".to_string());
+        }
+    }
+
+    fn validate_render(&self, violations: &mut Vec<String>) {
+        for size in &self.processing.render.sizes {
+            if size.parse::<RenderSize>().is_err() {
+                violations.push(format!(
+                    "MEDIA_SERVICE_PROCESSING_RENDER_SIZES entries must be WIDTHxHEIGHT, WIDTHx or xHEIGHT, not {size:?}"
+                ));
+            }
+        }
+
+        let provider = &self.processing.render.provider;
+        if provider.enabled {
+            let base_url = provider.base_url.trim();
+            if !base_url.starts_with("https://") && !base_url.starts_with("http://") {
+                violations.push(format!(
+                    "MEDIA_SERVICE_PROCESSING_RENDER_PROVIDER_BASE_URL must be an http(s) URL while render delegation is enabled, not {base_url:?}"
+                ));
+            }
+            if provider.signing_key.is_empty() {
+                violations.push(
+                    "MEDIA_SERVICE_PROCESSING_RENDER_PROVIDER_SIGNING_KEY must be set while render delegation is enabled"
+                        .to_string(),
+                );
+            }
+        }
+    }
+}
+
+/// Create `path` if missing and check a file can be written in it
+fn check_writable(setting: &str, path: &str, violations: &mut Vec<String>) {
+    let probe = Path::new(path).join(format!(".write-check-{}", std::process::id()));
+    let result = std::fs::create_dir_all(path)
+        .and_then(|()| std::fs::write(&probe, b""))
+        .and_then(|()| std::fs::remove_file(&probe));
+    if let Err(e) = result {
+        violations.push(format!("{setting} ({path}) must be a writable directory: {e}"));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn valid_config(dir: &TempDir) -> AppConfig {
+        let mut config = super::super::tests::create_test_app_config();
+        config.mode = RuntimeMode::Local;
+        config.storage.base_path = dir.path().join("media").to_string_lossy().into_owned();
+        config.storage.temp_path = dir.path().join("temp").to_string_lossy().into_owned();
+        config
+    }
+
+    #[test]
+    fn test_valid_config_passes() {
+        let dir = TempDir::new().unwrap();
+        valid_config(&dir).validate().unwrap();
+    }
+
+    #[test]
+    fn test_every_violation_is_reported() {
+        let dir = TempDir::new().unwrap();
+        let mut config = valid_config(&dir);
+        config.mode = RuntimeMode::Production;
+        config.middleware.auth.enabled = true;
+        config.middleware.auth.jwt_secret = DEFAULT_JWT_SECRET.into();
+        config.postgres.min_connections = 20;
+        config.postgres.max_connections = 10;
+        config.middleware.security.enabled = true;
+        config.middleware.security.frame_options = "ALLOWALL".to_string();
+        config.middleware.cors.allowed_origins = vec!["recipes.example".to_string()];
+        config.middleware.rate_limiting.enabled = true;
+        config.middleware.rate_limiting.tiers.upload_requests_per_minute = 0;
+        config.cdn.enabled = true;
+        config.cdn.provider = CdnProvider::Fastly;
+        config.cdn.api_token = "fastly-token".into();
+        config.webhooks.enabled = true;
+        config.webhooks.endpoints = vec!["recipes.internal/hooks".to_string()];
+        config.event_stream.enabled = true;
+        config.logging.export.sink = LogSink::Loki;
+        config.logging.export.endpoint = "loki:3100".to_string();
+        config.performance.max_concurrent_uploads = 0;
+        config.processing.render.sizes = vec!["320x240".to_string(), "large".to_string()];
+        config.processing.render.provider.enabled = true;
+        let file = dir.path().join("not-a-directory");
+        std::fs::write(&file, b"").unwrap();
+        config.storage.temp_path = file.to_string_lossy().into_owned();
+        config.storage.s3.multipart_threshold_bytes = 1024;
+
+        let error = config.validate().unwrap_err();
+
+        let expected = [
+            "POSTGRES_MIN_CONNECTIONS",
+            "AUTH_JWT_SECRET",
+            "STORAGE_TEMP_PATH",
+            "STORAGE_S3_MULTIPART_THRESHOLD_BYTES",
+            "FRAME_OPTIONS",
+            "CORS_ALLOWED_ORIGINS",
+            "TIERS_UPLOAD_REQUESTS_PER_MINUTE",
+            "CDN_FASTLY_SERVICE_ID",
+            "CACHE_CONTROL_SURROGATE_KEYS",
+            "WEBHOOKS_ENDPOINTS",
+            "WEBHOOKS_SIGNING_SECRET",
+            "EVENT_STREAM_BROKERS",
+            "LOGGING_EXPORT_ENDPOINT",
+            "PERFORMANCE_MAX_CONCURRENT_UPLOADS",
+            "PROCESSING_RENDER_SIZES",
+            "PROCESSING_RENDER_PROVIDER_BASE_URL",
+            "PROCESSING_RENDER_PROVIDER_SIGNING_KEY",
+        ];
+        assert_eq!(error.violations.len(), expected.len(), "{error}");
+        for (violation, setting) in error.violations.iter().zip(expected) {
+            assert!(violation.contains(setting), "{violation} should name {setting}");
+        }
+        assert!(error.to_string().lines().count() > expected.len());
+    }
+
+    #[test]
+    fn test_default_jwt_secret_is_allowed_in_local_mode() {
+        let dir = TempDir::new().unwrap();
+        let mut config = valid_config(&dir);
+        config.middleware.auth.enabled = true;
+        config.middleware.auth.jwt_secret = DEFAULT_JWT_SECRET.into();
+
+        config.validate().unwrap();
+    }
+}
@@ -0,0 +1,147 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::Deserialize;
+use serde_json::json;
+
+use super::{EventBroker, EventStreamError};
+
+/// Content type of REST Proxy produce requests with base64-encoded keys and values
+const BINARY_V2: &str = "application/vnd.kafka.binary.v2+json";
+
+/// Produces to a Kafka topic through the Confluent REST Proxy
+///
+/// Events are produced as binary records, so JSON and protobuf payloads reach
+/// consumers byte for byte. Proxies are tried in order until one accepts.
+#[derive(Debug, Clone)]
+pub struct KafkaRestProducer {
+    client: reqwest::Client,
+    proxies: Vec<String>,
+    topic: String,
+}
+
+impl KafkaRestProducer {
+    /// Produce to `topic` through the REST Proxies at `proxies`
+    #[must_use]
+    pub fn new(proxies: &[String], topic: &str, timeout: Duration) -> Self {
+        Self {
+            client: reqwest::Client::builder().timeout(timeout).build().unwrap_or_default(),
+            proxies: proxies.iter().map(|proxy| proxy.trim_end_matches('/').to_string()).collect(),
+            topic: topic.to_string(),
+        }
+    }
+
+    async fn produce(
+        &self,
+        proxy: &str,
+        key: &str,
+        payload: &[u8],
+    ) -> Result<(), EventStreamError> {
+        let response = self
+            .client
+            .post(format!("{proxy}/topics/{}", self.topic))
+            .header(reqwest::header::CONTENT_TYPE, BINARY_V2)
+            .json(&json!({
+                "records": [{ "key": STANDARD.encode(key), "value": STANDARD.encode(payload) }]
+            }))
+            .send()
+            .await?;
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        if !status.is_success() {
+            return Err(EventStreamError::Rejected { status, body });
+        }
+
+        // The proxy answers 200 even when a record failed, with the error on its offset
+        let failed = serde_json::from_str::<ProduceResponse>(&body).is_ok_and(|produced| {
+            produced.offsets.iter().any(|offset| offset.error_code.is_some())
+        });
+        if failed {
+            return Err(EventStreamError::Rejected { status, body });
+        }
+        Ok(())
+    }
+}
+
+/// Response of a REST Proxy produce request
+#[derive(Debug, Deserialize)]
+struct ProduceResponse {
+    offsets: Vec<ProducedOffset>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ProducedOffset {
+    error_code: Option<i64>,
+}
+
+#[async_trait]
+impl EventBroker for KafkaRestProducer {
+    fn backend(&self) -> &'static str {
+        "kafka"
+    }
+
+    async fn publish(
+        &self,
+        _event_id: &str,
+        key: &str,
+        payload: &[u8],
+    ) -> Result<(), EventStreamError> {
+        let mut last_error = None;
+        for proxy in &self.proxies {
+            match self.produce(proxy, key, payload).await {
+                Ok(()) => return Ok(()),
+                Err(e) => {
+                    tracing::warn!("Kafka REST Proxy {} did not take the event: {}", proxy, e);
+                    last_error = Some(e);
+                }
+            }
+        }
+        Err(last_error.unwrap_or_else(|| EventStreamError::Rejected {
+            status: reqwest::StatusCode::SERVICE_UNAVAILABLE,
+            body: "No Kafka REST Proxy configured".to_string(),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        extract::Path,
+        http::{HeaderMap, StatusCode},
+        routing::post,
+        Json, Router,
+    };
+    use serde_json::Value;
+    use tokio::net::TcpListener;
+
+    #[tokio::test]
+    async fn test_produces_binary_records_through_the_first_proxy_that_answers() {
+        let app = Router::new().route(
+            "/topics/{topic}",
+            post(|Path(topic): Path<String>, headers: HeaderMap, Json(body): Json<Value>| async move {
+                assert_eq!(headers["content-type"], BINARY_V2);
+                let record = &body["records"][0];
+                assert_eq!(record["key"], STANDARD.encode("5"));
+                assert_eq!(record["value"], STANDARD.encode(r#"{"type":"media_deleted"}"#));
+                let error_code = if topic == "media.events" { Value::Null } else { json!(40403) };
+                (StatusCode::OK, Json(json!({ "offsets": [{ "partition": 0, "offset": 7, "error_code": error_code }] })))
+            }),
+        );
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        // Nothing listens on port 9, so the second proxy takes the event
+        let proxies = ["http://127.0.0.1:9".to_string(), format!("http://{addr}/")];
+        let producer = KafkaRestProducer::new(&proxies, "media.events", Duration::from_secs(5));
+        producer.publish("id", "5", br#"{"type":"media_deleted"}"#).await.unwrap();
+
+        let unknown_topic =
+            KafkaRestProducer::new(&proxies[1..], "missing", Duration::from_secs(5));
+        let error =
+            unknown_topic.publish("id", "5", br#"{"type":"media_deleted"}"#).await.unwrap_err();
+        assert!(matches!(error, EventStreamError::Rejected { .. }));
+    }
+}
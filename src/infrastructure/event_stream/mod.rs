@@ -0,0 +1,385 @@
+//! Publishing of media lifecycle events to a message broker
+//!
+//! Every [`DomainEvent`] is published as a [`MediaEvent`], serialized as JSON or
+//! protobuf, to a Kafka topic or NATS subject for downstream consumers such as
+//! analytics and search indexing. Messages are keyed by media ID, so consumers
+//! see the events of one media in order. Publishing runs in the background as
+//! `event_publish` jobs and is retried with exponential backoff, so a broker
+//! outage never fails the request that caused the event.
+//!
+//! Metrics, labelled by `backend`:
+//! - `event_stream_messages_total` - publish attempts, also labelled by `outcome`
+//! - `event_stream_messages_abandoned_total` - events given up after the last attempt
+
+mod kafka;
+mod nats;
+
+pub use kafka::KafkaRestProducer;
+pub use nats::NatsPublisher;
+
+use std::{sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use chrono::Utc;
+use prost::Message;
+use reqwest::StatusCode;
+use serde::Serialize;
+use thiserror::Error;
+use tracing::{error, info};
+use uuid::Uuid;
+
+use crate::{
+    application::events::{DomainEvent, EventSubscriber},
+    domain::entities::MediaAssociation,
+    infrastructure::{
+        config::{EventFormat, EventStreamBackend, EventStreamConfig, JobsConfig},
+        jobs::{self, JobRegistry},
+    },
+};
+
+/// An event the broker did not take
+#[derive(Debug, Error)]
+pub enum EventStreamError {
+    #[error("Broker request failed: {0}")]
+    Request(#[from] reqwest::Error),
+
+    #[error("Broker rejected the event with {status}: {body}")]
+    Rejected { status: StatusCode, body: String },
+
+    #[error("NATS publish failed: {0}")]
+    Nats(String),
+}
+
+/// Client of a message broker
+#[async_trait]
+pub trait EventBroker: Send + Sync + std::fmt::Debug {
+    /// Name of the broker, labelling metrics and logs
+    fn backend(&self) -> &'static str;
+
+    /// Publish `payload`, identified by `event_id` and partitioned by `key`
+    async fn publish(
+        &self,
+        event_id: &str,
+        key: &str,
+        payload: &[u8],
+    ) -> Result<(), EventStreamError>;
+}
+
+/// A media lifecycle event as published to the broker
+///
+/// Serialized either as JSON or as the `MediaEvent` message of
+/// `docs/api/media_events.proto`; fields that do not apply to an event are left out.
+#[derive(Clone, PartialEq, Serialize, Message)]
+pub struct MediaEvent {
+    /// Unique per event, so consumers can drop repeats of a retried publish
+    #[prost(string, tag = "1")]
+    pub id: String,
+    /// e.g. `media_uploaded`, `processing_completed` or `media_deleted`
+    #[prost(string, tag = "2")]
+    #[serde(rename = "type")]
+    pub event_type: String,
+    /// RFC 3339 time the event was published
+    #[prost(string, tag = "3")]
+    pub occurred_at: String,
+    #[prost(int64, tag = "4")]
+    pub media_id: i64,
+    #[prost(string, optional, tag = "5")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uploaded_by: Option<String>,
+    #[prost(string, optional, tag = "6")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub media_type: Option<String>,
+    #[prost(uint64, optional, tag = "7")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_size: Option<u64>,
+    /// Why processing failed
+    #[prost(string, optional, tag = "8")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Whether existing media was processed again, e.g. by a backfill
+    #[prost(bool, optional, tag = "9")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reprocessed: Option<bool>,
+    #[prost(int64, optional, tag = "10")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recipe_id: Option<i64>,
+    #[prost(int64, optional, tag = "11")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ingredient_id: Option<i64>,
+    #[prost(int64, optional, tag = "12")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub step_id: Option<i64>,
+}
+
+impl MediaEvent {
+    /// The message announcing `event`, with a fresh ID
+    #[must_use]
+    pub fn from_domain(event: &DomainEvent) -> Self {
+        let mut message = Self {
+            id: Uuid::new_v4().to_string(),
+            event_type: event.name().to_string(),
+            occurred_at: Utc::now().to_rfc3339(),
+            media_id: event.media_id().as_i64(),
+            ..Self::default()
+        };
+
+        match event {
+            DomainEvent::MediaUploaded { uploaded_by, media_type, file_size, .. } => {
+                message.uploaded_by = Some(uploaded_by.to_string());
+                message.media_type = Some(media_type.to_string());
+                message.file_size = Some(*file_size);
+            }
+            DomainEvent::ProcessingCompleted { media_type, reprocessed, .. } => {
+                message.media_type = Some(media_type.to_string());
+                message.reprocessed = Some(*reprocessed);
+            }
+            DomainEvent::ProcessingFailed { media_type, error, reprocessed, .. } => {
+                message.media_type = Some(media_type.to_string());
+                message.error = Some(error.clone());
+                message.reprocessed = Some(*reprocessed);
+            }
+            DomainEvent::MediaDeleted { .. } | DomainEvent::MediaTagsChanged { .. } => {}
+            DomainEvent::MediaAttached { association, .. }
+            | DomainEvent::MediaDetached { association, .. } => {
+                message.recipe_id = Some(association.recipe_id().as_i64());
+                match association {
+                    MediaAssociation::Recipe(_) => {}
+                    MediaAssociation::Ingredient(_, ingredient_id) => {
+                        message.ingredient_id = Some(ingredient_id.as_i64());
+                    }
+                    MediaAssociation::Step(_, step_id) => message.step_id = Some(step_id.as_i64()),
+                }
+            }
+        }
+        message
+    }
+
+    /// The message serialized as `format`
+    ///
+    /// # Panics
+    /// Never; the message holds nothing JSON cannot represent
+    #[must_use]
+    pub fn serialize(&self, format: EventFormat) -> Vec<u8> {
+        match format {
+            EventFormat::Json => serde_json::to_vec(self).expect("media events serialize to JSON"),
+            EventFormat::Protobuf => self.encode_to_vec(),
+        }
+    }
+}
+
+/// Background publishing of media lifecycle events
+///
+/// Cloning is cheap; clones publish through the same broker client.
+#[derive(Debug, Clone)]
+pub struct EventStream {
+    broker: Option<Arc<dyn EventBroker>>,
+    format: EventFormat,
+    jobs: JobRegistry,
+    max_attempts: u32,
+    initial_backoff: Duration,
+}
+
+impl EventStream {
+    /// Publish nothing, for deployments without a broker
+    #[must_use]
+    pub fn disabled() -> Self {
+        Self {
+            broker: None,
+            format: EventFormat::Json,
+            jobs: JobRegistry::new(),
+            max_attempts: 1,
+            initial_backoff: Duration::ZERO,
+        }
+    }
+
+    /// Publish through `broker` as `format`, reporting attempts to `jobs`
+    ///
+    /// A failed publish is attempted up to `max_attempts` times in total,
+    /// waiting `initial_backoff` before the first retry and twice as long
+    /// before each further one.
+    #[must_use]
+    pub fn new(
+        broker: Arc<dyn EventBroker>,
+        format: EventFormat,
+        jobs: JobRegistry,
+        max_attempts: u32,
+        initial_backoff: Duration,
+    ) -> Self {
+        jobs.register(jobs::EVENT_PUBLISH);
+        Self {
+            broker: Some(broker),
+            format,
+            jobs,
+            max_attempts: max_attempts.max(1),
+            initial_backoff,
+        }
+    }
+
+    /// Attempt a publish until the broker takes it or the attempts run out
+    ///
+    /// Every attempt is a run of the `event_publish` job, queued while it
+    /// waits out its backoff. Returns whether the event was published.
+    async fn publish_with_retries(&self, broker: &dyn EventBroker, event: &MediaEvent) -> bool {
+        let backend = broker.backend();
+        let key = event.media_id.to_string();
+        let payload = event.serialize(self.format);
+        let mut backoff = self.initial_backoff;
+
+        for attempt in 1..=self.max_attempts {
+            let queued = self.jobs.enqueue(jobs::EVENT_PUBLISH);
+            if attempt > 1 {
+                tokio::time::sleep(backoff).await;
+                backoff = backoff.saturating_mul(2);
+            }
+
+            // Failures are logged and counted by the job registry
+            let result = queued.run(broker.publish(&event.id, &key, &payload)).await;
+            let outcome = if result.is_ok() { "success" } else { "failure" };
+            metrics::counter!(
+                "event_stream_messages_total",
+                "backend" => backend,
+                "outcome" => outcome
+            )
+            .increment(1);
+            if result.is_ok() {
+                info!(backend, event = %event.event_type, event_id = %event.id, attempt, "Published event");
+                return true;
+            }
+        }
+
+        metrics::counter!("event_stream_messages_abandoned_total", "backend" => backend)
+            .increment(1);
+        error!(
+            backend,
+            event = %event.event_type,
+            event_id = %event.id,
+            attempts = self.max_attempts,
+            "Giving up publishing event"
+        );
+        false
+    }
+}
+
+impl EventSubscriber for EventStream {
+    fn handle(&self, event: &DomainEvent) {
+        let Some(broker) = self.broker.clone() else {
+            return;
+        };
+
+        let stream = self.clone();
+        let message = MediaEvent::from_domain(event);
+        tokio::spawn(async move { stream.publish_with_retries(broker.as_ref(), &message).await });
+    }
+}
+
+/// The publishing `config` asks for, retried per `jobs_config`
+///
+/// Nothing is published unless `config` enables it.
+pub fn create_event_stream(
+    config: &EventStreamConfig,
+    jobs_config: &JobsConfig,
+    jobs: &JobRegistry,
+) -> EventStream {
+    if !config.enabled {
+        return EventStream::disabled();
+    }
+
+    let broker: Arc<dyn EventBroker> = match config.backend {
+        EventStreamBackend::Kafka => Arc::new(KafkaRestProducer::new(
+            &config.brokers,
+            &config.topic,
+            config.request_timeout(),
+        )),
+        EventStreamBackend::Nats => {
+            Arc::new(NatsPublisher::new(&config.brokers, &config.topic, config.request_timeout()))
+        }
+    };
+    info!("Publishing media events to {} on {}", config.topic, broker.backend());
+    EventStream::new(
+        broker,
+        config.format,
+        jobs.clone(),
+        jobs_config.max_attempts,
+        jobs_config.initial_backoff(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::entities::{MediaId, RecipeId, StepId, UserId};
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Mutex;
+
+    /// Broker that rejects the first `failures` publishes
+    #[derive(Debug, Default)]
+    struct FlakyBroker {
+        failures: u32,
+        attempts: AtomicU32,
+        published: Mutex<Vec<(String, Vec<u8>)>>,
+    }
+
+    #[async_trait]
+    impl EventBroker for FlakyBroker {
+        fn backend(&self) -> &'static str {
+            "flaky"
+        }
+
+        async fn publish(
+            &self,
+            _event_id: &str,
+            key: &str,
+            payload: &[u8],
+        ) -> Result<(), EventStreamError> {
+            if self.attempts.fetch_add(1, Ordering::SeqCst) < self.failures {
+                return Err(EventStreamError::Nats("no responders".to_string()));
+            }
+            self.published.lock().unwrap().push((key.to_string(), payload.to_vec()));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_events_serialize_as_json_or_protobuf() {
+        let event = MediaEvent::from_domain(&DomainEvent::MediaAttached {
+            media_id: MediaId::new(5),
+            uploaded_by: UserId::new(),
+            association: MediaAssociation::Step(RecipeId::new(42), StepId::new(3)),
+        });
+
+        let json: serde_json::Value =
+            serde_json::from_slice(&event.serialize(EventFormat::Json)).unwrap();
+        assert_eq!(json["type"], "media_attached");
+        assert_eq!((json["media_id"].as_i64(), json["step_id"].as_i64()), (Some(5), Some(3)));
+        assert!(json.get("ingredient_id").is_none());
+
+        let decoded =
+            MediaEvent::decode(event.serialize(EventFormat::Protobuf).as_slice()).unwrap();
+        assert_eq!(decoded, event);
+        assert_eq!(decoded.recipe_id, Some(42));
+    }
+
+    #[tokio::test]
+    async fn test_failed_publishes_are_retried_as_jobs() {
+        let broker = Arc::new(FlakyBroker { failures: 1, ..FlakyBroker::default() });
+        let jobs = JobRegistry::new();
+        let stream = EventStream::new(
+            broker.clone(),
+            EventFormat::Protobuf,
+            jobs.clone(),
+            3,
+            Duration::ZERO,
+        );
+        let event = MediaEvent::from_domain(&DomainEvent::MediaDeleted {
+            media_id: MediaId::new(5),
+            uploaded_by: UserId::new(),
+        });
+
+        assert!(stream.publish_with_retries(broker.as_ref(), &event).await);
+        let published = broker.published.lock().unwrap().clone();
+        assert_eq!(published, [("5".to_string(), event.encode_to_vec())]);
+        let status = &jobs.statuses()[0];
+        assert_eq!(status.name, jobs::EVENT_PUBLISH);
+        assert_eq!((status.runs, status.failures), (2, 1));
+    }
+}
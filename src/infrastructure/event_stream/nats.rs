@@ -0,0 +1,96 @@
+use std::time::Duration;
+
+use async_nats::{Client, ConnectOptions, HeaderMap};
+use async_trait::async_trait;
+use bytes::Bytes;
+use tokio::sync::OnceCell;
+
+use super::{EventBroker, EventStreamError};
+
+/// Publishes to a NATS subject
+///
+/// The connection is opened on the first publish and reconnects on its own
+/// afterwards. Messages carry a `Nats-Msg-Id` header with the event ID, which
+/// a `JetStream` stream on the subject uses to drop repeats of a retried publish.
+#[derive(Debug)]
+pub struct NatsPublisher {
+    servers: Vec<String>,
+    subject: String,
+    timeout: Duration,
+    client: OnceCell<Client>,
+}
+
+impl NatsPublisher {
+    /// Publish to `subject` on any of `servers`
+    #[must_use]
+    pub fn new(servers: &[String], subject: &str, timeout: Duration) -> Self {
+        Self {
+            servers: servers.to_vec(),
+            subject: subject.to_string(),
+            timeout,
+            client: OnceCell::new(),
+        }
+    }
+
+    async fn client(&self) -> Result<&Client, EventStreamError> {
+        self.client
+            .get_or_try_init(|| {
+                ConnectOptions::new().connection_timeout(self.timeout).connect(self.servers.clone())
+            })
+            .await
+            .map_err(|e| EventStreamError::Nats(format!("Failed to connect: {e}")))
+    }
+}
+
+#[async_trait]
+impl EventBroker for NatsPublisher {
+    fn backend(&self) -> &'static str {
+        "nats"
+    }
+
+    async fn publish(
+        &self,
+        event_id: &str,
+        key: &str,
+        payload: &[u8],
+    ) -> Result<(), EventStreamError> {
+        let client = self.client().await?;
+        let mut headers = HeaderMap::new();
+        headers.insert("Nats-Msg-Id", event_id);
+        headers.insert("Media-Id", key);
+
+        // Flushing waits until the server has the message, or the connection is back
+        let published = tokio::time::timeout(self.timeout, async {
+            client
+                .publish_with_headers(
+                    self.subject.clone(),
+                    headers,
+                    Bytes::copy_from_slice(payload),
+                )
+                .await
+                .map_err(|e| EventStreamError::Nats(e.to_string()))?;
+            client.flush().await.map_err(|e| EventStreamError::Nats(e.to_string()))
+        })
+        .await;
+        published.unwrap_or_else(|_| {
+            Err(EventStreamError::Nats(format!("Timed out after {}s", self.timeout.as_secs_f64())))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_unreachable_servers_fail_the_publish() {
+        let publisher = NatsPublisher::new(
+            &["nats://127.0.0.1:9".to_string()],
+            "media.events",
+            Duration::from_millis(200),
+        );
+
+        let error = publisher.publish("id", "5", b"{}").await.unwrap_err();
+        assert!(matches!(error, EventStreamError::Nats(message) if message.contains("connect")));
+    }
+}
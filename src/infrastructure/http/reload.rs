@@ -0,0 +1,203 @@
+//! Hot reload of the settings that can change without a restart
+//!
+//! On `SIGHUP` the configuration is loaded again, from the config files, the
+//! environment and, in local mode, `.env.local`, and these settings are applied
+//! to the running server:
+//! - `logging.level` and `logging.filter`, unless `RUST_LOG` is set
+//! - `middleware.rate_limiting.tiers`
+//! - `middleware.validation.allowed_file_types`
+//! - `middleware.cors.allowed_origins`
+//!
+//! Other settings, including turning rate limiting on or off, still take a
+//! restart. A configuration that fails to load or validate is rejected, and the
+//! server keeps the settings it has.
+
+use std::sync::Arc;
+
+use tracing::{error, info};
+use tracing_subscriber::{reload, EnvFilter, Registry};
+
+use crate::{
+    application::use_cases::AllowedTypes,
+    infrastructure::{
+        config::{AppConfig, LoggingConfig, RateLimitBackend, RateLimitTiersConfig, RuntimeMode},
+        redis::RedisRateLimitStore,
+    },
+    presentation::middleware::{cors::CorsOrigins, RateLimitTier, TieredRateLimiter},
+};
+
+/// Handle replacing the log filter of the running subscriber
+pub type LogFilterHandle = reload::Handle<EnvFilter, Registry>;
+
+/// Settings a reload applies, shared with the parts of the server using them
+#[derive(Debug, Clone)]
+pub struct DynamicSettings {
+    pub rate_limiter: TieredRateLimiter,
+    pub allowed_types: AllowedTypes,
+    pub cors_origins: CorsOrigins,
+    log_filter: Option<LogFilterHandle>,
+}
+
+impl DynamicSettings {
+    /// Settings as `config` has them; reloads leave the log filter alone
+    /// unless [`DynamicSettings::with_log_filter`] is used
+    #[must_use]
+    pub fn new(config: &AppConfig) -> Self {
+        let rate_limiting = &config.middleware.rate_limiting;
+        let mut rate_limiter = TieredRateLimiter::new(
+            rate_limiting.trust_forwarded_headers,
+            rate_limiting.include_rate_limit_headers,
+        );
+        if rate_limiting.backend == RateLimitBackend::Redis {
+            match RedisRateLimitStore::new(rate_limiting.redis_url.expose()) {
+                Ok(store) => rate_limiter = rate_limiter.with_store(Arc::new(store)),
+                Err(e) => {
+                    error!("Invalid rate limiting Redis URL, using per-instance budgets: {}", e);
+                }
+            }
+        }
+        let settings = Self {
+            rate_limiter,
+            allowed_types: AllowedTypes::new(config.middleware.validation.upload_allowed_types()),
+            cors_origins: CorsOrigins::new(&config.middleware.cors.allowed_origins),
+            log_filter: None,
+        };
+        settings.apply_rate_limits(&rate_limiting.tiers);
+        settings
+    }
+
+    /// Let reloads replace the log filter through `handle`
+    #[must_use]
+    pub fn with_log_filter(self, handle: LogFilterHandle) -> Self {
+        Self { log_filter: Some(handle), ..self }
+    }
+
+    /// Apply the reloadable settings of `config`
+    ///
+    /// # Errors
+    /// Returns an error if the log filter cannot be replaced; the other settings are applied regardless
+    pub fn apply(&self, config: &AppConfig) -> Result<(), reload::Error> {
+        self.apply_rate_limits(&config.middleware.rate_limiting.tiers);
+        self.allowed_types.set(config.middleware.validation.upload_allowed_types());
+        self.cors_origins.set(&config.middleware.cors.allowed_origins);
+        match &self.log_filter {
+            Some(handle) => handle.reload(log_filter(&config.logging)),
+            None => Ok(()),
+        }
+    }
+
+    fn apply_rate_limits(&self, tiers: &RateLimitTiersConfig) {
+        let limits = [
+            (RateLimitTier::Health, tiers.health_requests_per_minute),
+            (RateLimitTier::Public, tiers.public_requests_per_minute),
+            (RateLimitTier::Authenticated, tiers.authenticated_requests_per_minute),
+            (RateLimitTier::Upload, tiers.upload_requests_per_minute),
+            (RateLimitTier::Admin, tiers.admin_requests_per_minute),
+        ];
+        for (tier, requests_per_minute) in limits {
+            self.rate_limiter.set_limit(tier, requests_per_minute);
+        }
+    }
+}
+
+/// The log filter `logging` asks for, unless `RUST_LOG` is set
+#[must_use]
+pub fn log_filter(logging: &LoggingConfig) -> EnvFilter {
+    EnvFilter::try_from_default_env().unwrap_or_else(|_| match &logging.filter {
+        Some(filter) => filter.as_str().into(),
+        None => format!("media_management_service={},tower_http={}", logging.level, logging.level)
+            .into(),
+    })
+}
+
+/// Reload the configuration of `mode` into `settings` on every `SIGHUP`
+///
+/// # Errors
+/// Returns an error if the signal handler cannot be installed
+#[cfg(unix)]
+pub fn reload_on_hangup(mode: RuntimeMode, settings: DynamicSettings) -> std::io::Result<()> {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut hangups = signal(SignalKind::hangup())?;
+    let handle = tokio::spawn(async move {
+        while hangups.recv().await.is_some() {
+            info!("Received SIGHUP, reloading configuration");
+            reload(mode, &settings).await;
+        }
+    });
+    std::mem::forget(handle);
+    Ok(())
+}
+
+/// Hot reload is driven by `SIGHUP`, which only Unix has
+///
+/// # Errors
+/// Never fails
+#[cfg(not(unix))]
+pub fn reload_on_hangup(_mode: RuntimeMode, _settings: DynamicSettings) -> std::io::Result<()> {
+    Ok(())
+}
+
+/// Load the configuration of `mode` again and apply it to `settings`
+async fn reload(mode: RuntimeMode, settings: &DynamicSettings) {
+    let loaded = match AppConfig::load_for_mode(mode) {
+        Ok(config) => config.with_secrets().await,
+        Err(e) => Err(e),
+    };
+    let config = match loaded {
+        Ok(config) => config,
+        Err(e) => {
+            error!("Configuration reload rejected, keeping current settings: {}", e);
+            return;
+        }
+    };
+    if let Err(e) = config.validate() {
+        error!("Configuration reload rejected, keeping current settings: {}", e);
+        return;
+    }
+
+    if let Err(e) = settings.apply(&config) {
+        error!("Failed to replace the log filter: {}", e);
+    }
+    let tiers = &config.middleware.rate_limiting.tiers;
+    info!(
+        log_level = %config.logging.level,
+        log_filter = ?config.logging.filter,
+        health_requests_per_minute = tiers.health_requests_per_minute,
+        public_requests_per_minute = tiers.public_requests_per_minute,
+        authenticated_requests_per_minute = tiers.authenticated_requests_per_minute,
+        upload_requests_per_minute = tiers.upload_requests_per_minute,
+        admin_requests_per_minute = tiers.admin_requests_per_minute,
+        allowed_file_types = ?settings.allowed_types.get(),
+        cors_allowed_origins = ?config.middleware.cors.allowed_origins,
+        "Configuration reloaded"
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+    use std::net::{IpAddr, Ipv4Addr};
+
+    #[tokio::test]
+    async fn test_apply_updates_shared_settings() {
+        let mut config =
+            AppConfig::load_layered(RuntimeMode::Production, "does-not-exist", |_| None).unwrap();
+        let settings = DynamicSettings::new(&config);
+        let running = settings.clone();
+
+        config.middleware.rate_limiting.tiers.upload_requests_per_minute = 1;
+        config.middleware.validation.allowed_file_types = vec!["image/png".to_string()];
+        config.middleware.cors.allowed_origins = vec!["https://*.recipes.example".to_string()];
+        settings.apply(&config).unwrap();
+
+        let ip = IpAddr::V4(Ipv4Addr::LOCALHOST);
+        let upload = running.rate_limiter.limiter(RateLimitTier::Upload);
+        assert_eq!(upload.check_rate_limit(ip).await.unwrap().limit, 1);
+        assert_eq!(&*running.allowed_types.get(), ["image/png"]);
+        assert!(running
+            .cors_origins
+            .allows(&HeaderValue::from_static("https://app.recipes.example")));
+    }
+}
@@ -0,0 +1,375 @@
+//! Bookkeeping and metrics for background jobs
+//!
+//! Work that runs outside a request, like processing an upload or retrying the
+//! database connection, reports each run to a [`JobRegistry`]. The registry
+//! exports Prometheus metrics and keeps the last run of every job, served at
+//! `/api/v1/media-management/admin/jobs`, so a job that keeps failing without
+//! affecting requests can still be alerted on.
+//!
+//! Metrics, labelled by `job`:
+//! - `background_job_runs_total` - finished runs, also labelled by `outcome`
+//! - `background_job_failures_total` - failed runs
+//! - `background_job_duration_seconds` - run duration
+//! - `background_job_backlog` - work queued or running
+//! - `background_job_backlog_age_seconds` - age of the oldest such work
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Display,
+    future::Future,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex, PoisonError,
+    },
+    time::{Duration, Instant, SystemTime},
+};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::domain::services::{Clock, SystemClock};
+
+/// Variant generation, scanning and transcoding of a new upload
+pub const MEDIA_PROCESSING: &str = "media_processing";
+
+/// Batches of an admin-started backfill of missing variants
+pub const VARIANT_BACKFILL: &str = "variant_backfill";
+
+/// Periodic search for media still unprocessed past the processing SLA
+pub const PROCESSING_SLA_CHECK: &str = "processing_sla_check";
+
+/// Periodic removal of resumable uploads abandoned past their expiry
+pub const RESUMABLE_UPLOAD_CLEANUP: &str = "resumable_upload_cleanup";
+
+/// Periodic removal of idempotency keys past their expiry
+pub const IDEMPOTENCY_KEY_CLEANUP: &str = "idempotency_key_cleanup";
+
+/// Periodic re-hashing of stored content to find media damaged at rest
+pub const INTEGRITY_SCAN: &str = "integrity_scan";
+
+/// Periodic restoring of files missing from the primary or replica storage
+pub const REPLICA_REPAIR: &str = "replica_repair";
+
+/// Periodic publishing of events recorded in the outbox with the changes raising them
+pub const EVENT_RELAY: &str = "event_relay";
+
+/// Purges of CDN-cached responses, retried until the CDN accepts them
+pub const CDN_PURGE: &str = "cdn_purge";
+
+/// Deliveries of media lifecycle events to webhook endpoints
+pub const WEBHOOK_DELIVERY: &str = "webhook_delivery";
+
+/// Publishes of media lifecycle events to a message broker
+pub const EVENT_PUBLISH: &str = "event_publish";
+
+/// Periodic attempt to reconnect to the database while it is unavailable
+pub const DATABASE_RECONNECTION: &str = "database_reconnection";
+
+/// How a job run ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum JobOutcome {
+    Success,
+    Failure,
+}
+
+impl JobOutcome {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Success => "success",
+            Self::Failure => "failure",
+        }
+    }
+}
+
+/// Run history and backlog of one job
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, utoipa::ToSchema)]
+pub struct JobStatus {
+    pub name: String,
+    /// Finished runs since startup
+    pub runs: u64,
+    /// Failed runs since startup
+    pub failures: u64,
+    /// Work queued or running
+    pub backlog: usize,
+    /// Age of the oldest queued or running work
+    pub oldest_backlog_age_seconds: Option<u64>,
+    pub last_started_at: Option<DateTime<Utc>>,
+    pub last_finished_at: Option<DateTime<Utc>>,
+    pub last_duration_ms: Option<u64>,
+    pub last_outcome: Option<JobOutcome>,
+    /// Error of the last run, if it failed
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Default)]
+struct JobState {
+    runs: u64,
+    failures: u64,
+    backlog: HashMap<u64, SystemTime>,
+    last_started_at: Option<SystemTime>,
+    last_finished_at: Option<SystemTime>,
+    last_duration: Option<Duration>,
+    last_outcome: Option<JobOutcome>,
+    last_error: Option<String>,
+}
+
+/// Shared record of background job runs
+///
+/// Cloning is cheap; clones share the same state.
+#[derive(Clone)]
+pub struct JobRegistry {
+    jobs: Arc<Mutex<BTreeMap<&'static str, JobState>>>,
+    next_ticket: Arc<AtomicU64>,
+    clock: Arc<dyn Clock>,
+}
+
+impl JobRegistry {
+    /// Create an empty registry using the system clock
+    #[must_use]
+    pub fn new() -> Self {
+        Self::with_clock(Arc::new(SystemClock))
+    }
+
+    /// Create an empty registry reading timestamps from `clock`
+    #[must_use]
+    pub fn with_clock(clock: Arc<dyn Clock>) -> Self {
+        Self { jobs: Arc::default(), next_ticket: Arc::default(), clock }
+    }
+
+    /// List a job before its first run
+    pub fn register(&self, job: &'static str) {
+        self.jobs().entry(job).or_default();
+    }
+
+    /// Add work to a job's backlog; it leaves the backlog when run or dropped
+    pub fn enqueue(&self, job: &'static str) -> QueuedJob {
+        let ticket = self.next_ticket.fetch_add(1, Ordering::Relaxed);
+        self.jobs().entry(job).or_default().backlog.insert(ticket, self.clock.now());
+        self.report_backlog();
+        QueuedJob { registry: self.clone(), job, ticket }
+    }
+
+    /// Run one iteration of a job, recording its duration and outcome
+    ///
+    /// # Errors
+    /// Returns the error of `run`, after recording it as a failure
+    pub async fn run<T, E, F>(&self, job: &'static str, run: F) -> Result<T, E>
+    where
+        E: Display,
+        F: Future<Output = Result<T, E>>,
+    {
+        self.jobs().entry(job).or_default().last_started_at = Some(self.clock.now());
+        let started = Instant::now();
+
+        let result = run.await;
+
+        let elapsed = started.elapsed();
+        let outcome = if result.is_ok() { JobOutcome::Success } else { JobOutcome::Failure };
+        {
+            let mut jobs = self.jobs();
+            let state = jobs.entry(job).or_default();
+            state.runs += 1;
+            state.last_finished_at = Some(self.clock.now());
+            state.last_duration = Some(elapsed);
+            state.last_outcome = Some(outcome);
+            state.last_error = result.as_ref().err().map(ToString::to_string);
+            if outcome == JobOutcome::Failure {
+                state.failures += 1;
+            }
+        }
+
+        metrics::counter!("background_job_runs_total", "job" => job, "outcome" => outcome.as_str())
+            .increment(1);
+        metrics::histogram!("background_job_duration_seconds", "job" => job)
+            .record(elapsed.as_secs_f64());
+        if let Err(e) = &result {
+            metrics::counter!("background_job_failures_total", "job" => job).increment(1);
+            tracing::warn!(job, duration_ms = elapsed.as_millis(), error = %e, "Background job failed");
+        }
+
+        result
+    }
+
+    /// Status of every job, ordered by name
+    #[must_use]
+    pub fn statuses(&self) -> Vec<JobStatus> {
+        let now = self.clock.now();
+        self.jobs()
+            .iter()
+            .map(|(name, state)| JobStatus {
+                name: (*name).to_string(),
+                runs: state.runs,
+                failures: state.failures,
+                backlog: state.backlog.len(),
+                oldest_backlog_age_seconds: oldest_age(state, now).map(|age| age.as_secs()),
+                last_started_at: state.last_started_at.map(DateTime::from),
+                last_finished_at: state.last_finished_at.map(DateTime::from),
+                last_duration_ms: state
+                    .last_duration
+                    .map(|d| u64::try_from(d.as_millis()).unwrap_or(u64::MAX)),
+                last_outcome: state.last_outcome,
+                last_error: state.last_error.clone(),
+            })
+            .collect()
+    }
+
+    /// Set the backlog gauges from the current backlog
+    ///
+    /// Backlog age grows between enqueues, so this also runs periodically; see
+    /// [`JobRegistry::start_backlog_reporter`].
+    pub fn report_backlog(&self) {
+        let now = self.clock.now();
+        for (job, state) in self.jobs().iter() {
+            let job = *job;
+            metrics::gauge!("background_job_backlog", "job" => job).set(state.backlog.len() as f64);
+            metrics::gauge!("background_job_backlog_age_seconds", "job" => job)
+                .set(oldest_age(state, now).map_or(0.0, |age| age.as_secs_f64()));
+        }
+    }
+
+    /// Spawn a task refreshing the backlog gauges every `interval`
+    pub fn start_backlog_reporter(self, interval: Duration) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(interval);
+            loop {
+                interval.tick().await;
+                self.report_backlog();
+            }
+        })
+    }
+
+    fn jobs(&self) -> std::sync::MutexGuard<'_, BTreeMap<&'static str, JobState>> {
+        self.jobs.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Default for JobRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for JobRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("JobRegistry").field("jobs", &*self.jobs()).finish_non_exhaustive()
+    }
+}
+
+fn oldest_age(state: &JobState, now: SystemTime) -> Option<Duration> {
+    state
+        .backlog
+        .values()
+        .min()
+        .map(|enqueued_at| now.duration_since(*enqueued_at).unwrap_or_default())
+}
+
+/// Work waiting in a job's backlog
+///
+/// The work stays in the backlog until [`QueuedJob::run`] finishes or the
+/// handle is dropped.
+#[must_use = "work leaves the backlog as soon as it is dropped"]
+pub struct QueuedJob {
+    registry: JobRegistry,
+    job: &'static str,
+    ticket: u64,
+}
+
+impl QueuedJob {
+    /// Run the queued work, recording it like [`JobRegistry::run`]
+    ///
+    /// # Errors
+    /// Returns the error of `run`
+    pub async fn run<T, E, F>(self, run: F) -> Result<T, E>
+    where
+        E: Display,
+        F: Future<Output = Result<T, E>>,
+    {
+        self.registry.run(self.job, run).await
+    }
+}
+
+impl Drop for QueuedJob {
+    fn drop(&mut self) {
+        if let Some(state) = self.registry.jobs().get_mut(self.job) {
+            state.backlog.remove(&self.ticket);
+        }
+        self.registry.report_backlog();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::mocks::ManualClock;
+
+    fn registry() -> (JobRegistry, ManualClock) {
+        let clock = ManualClock::new(SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000));
+        (JobRegistry::with_clock(Arc::new(clock.clone())), clock)
+    }
+
+    #[test]
+    fn test_registered_job_is_listed_before_running() {
+        let (jobs, _) = registry();
+        jobs.register(DATABASE_RECONNECTION);
+
+        let statuses = jobs.statuses();
+        assert_eq!(statuses.len(), 1);
+        assert_eq!(statuses[0].name, DATABASE_RECONNECTION);
+        assert_eq!(statuses[0].runs, 0);
+        assert!(statuses[0].last_outcome.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_run_records_outcomes() {
+        let (jobs, _) = registry();
+
+        jobs.run(MEDIA_PROCESSING, async { Ok::<_, String>(()) }).await.unwrap();
+        let result = jobs.run(MEDIA_PROCESSING, async { Err::<(), _>("disk full") }).await;
+        assert!(result.is_err());
+
+        let status = &jobs.statuses()[0];
+        assert_eq!(status.runs, 2);
+        assert_eq!(status.failures, 1);
+        assert_eq!(status.last_outcome, Some(JobOutcome::Failure));
+        assert_eq!(status.last_error.as_deref(), Some("disk full"));
+        assert_eq!(status.last_finished_at.unwrap().timestamp(), 1_700_000_000);
+    }
+
+    #[tokio::test]
+    async fn test_success_clears_last_error() {
+        let (jobs, _) = registry();
+
+        let _ = jobs.run(MEDIA_PROCESSING, async { Err::<(), _>("disk full") }).await;
+        jobs.run(MEDIA_PROCESSING, async { Ok::<_, String>(()) }).await.unwrap();
+
+        let status = &jobs.statuses()[0];
+        assert_eq!(status.last_outcome, Some(JobOutcome::Success));
+        assert!(status.last_error.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_backlog_tracks_oldest_unfinished_work() {
+        let (jobs, clock) = registry();
+
+        let first = jobs.enqueue(MEDIA_PROCESSING);
+        clock.advance(Duration::from_secs(30));
+        let second = jobs.enqueue(MEDIA_PROCESSING);
+        clock.advance(Duration::from_secs(15));
+
+        let status = &jobs.statuses()[0];
+        assert_eq!(status.backlog, 2);
+        assert_eq!(status.oldest_backlog_age_seconds, Some(45));
+
+        first.run(async { Ok::<_, String>(()) }).await.unwrap();
+        let status = &jobs.statuses()[0];
+        assert_eq!(status.backlog, 1);
+        assert_eq!(status.oldest_backlog_age_seconds, Some(15));
+
+        drop(second);
+        let status = &jobs.statuses()[0];
+        assert_eq!(status.backlog, 0);
+        assert_eq!(status.oldest_backlog_age_seconds, None);
+        assert_eq!(status.runs, 1);
+    }
+}
@@ -0,0 +1,515 @@
+//! Shipping of log events to a sink outside the container
+//!
+//! [`LogExportLayer`] queues every event the log filter lets through; a
+//! background task sends them in batches of `logging.export.batch_size`, or
+//! every `logging.export.flush_interval_ms`, to the configured sink:
+//! - `syslog` - RFC 5424 messages, one datagram each over UDP or octet-counted over TCP
+//! - `loki` - the Grafana Loki push API, one stream per level
+//! - `otlp` - OTLP logs, JSON-encoded over HTTP
+//!
+//! Events of the HTTP client stack and of this module are not shipped, so a
+//! failing sink cannot feed itself. Batches the sink refuses are dropped and
+//! logged locally.
+//!
+//! Metrics:
+//! - `log_export_events_dropped_total` - events never shipped, labelled by
+//!   `reason`: `queue_full` or `send_failed`
+
+use std::{collections::BTreeMap, fmt, fmt::Write as _, time::Duration};
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use metrics::counter;
+use reqwest::StatusCode;
+use serde_json::{json, Map, Value};
+use thiserror::Error;
+use tokio::{
+    io::AsyncWriteExt,
+    net::{TcpStream, UdpSocket},
+    sync::mpsc,
+};
+use tracing::{
+    field::{Field, Visit},
+    Event, Level, Subscriber,
+};
+use tracing_subscriber::{layer::Context, Layer};
+
+use crate::infrastructure::config::{LogExportConfig, LogSink, Secret};
+
+/// Targets whose events are never shipped
+const UNSHIPPED_TARGETS: &[&str] = &["hyper", "h2", "reqwest", "rustls", module_path!()];
+
+/// Syslog facility `local0`
+const SYSLOG_FACILITY: u8 = 16;
+
+/// A log sink that could not be set up or refused a batch
+#[derive(Debug, Error)]
+pub enum LogExportError {
+    #[error("Invalid log export endpoint: {0}")]
+    InvalidEndpoint(String),
+
+    #[error("Log export failed: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Log export request failed: {0}")]
+    Request(#[from] reqwest::Error),
+
+    #[error("Log sink rejected the batch with {status}: {body}")]
+    Rejected { status: StatusCode, body: String },
+}
+
+/// One log event, as shipped
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogRecord {
+    pub timestamp: DateTime<Utc>,
+    pub level: Level,
+    pub target: String,
+    pub message: String,
+    pub fields: Vec<(String, String)>,
+}
+
+impl LogRecord {
+    /// The message followed by the other fields, `key=value`
+    fn line(&self) -> String {
+        self.fields
+            .iter()
+            .fold(self.message.clone(), |line, (name, value)| format!("{line} {name}={value}"))
+    }
+}
+
+/// Collects the message and other fields of an event
+#[derive(Default)]
+struct FieldVisitor {
+    message: String,
+    fields: Vec<(String, String)>,
+}
+
+impl Visit for FieldVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message = value.to_string();
+        } else {
+            self.fields.push((field.name().to_string(), value.to_string()));
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            self.message = format!("{value:?}");
+        } else {
+            self.fields.push((field.name().to_string(), format!("{value:?}")));
+        }
+    }
+}
+
+/// Tracing layer handing events to the exporter task
+pub struct LogExportLayer {
+    queue: mpsc::Sender<LogRecord>,
+}
+
+impl LogExportLayer {
+    /// The layer shipping to the sink `config` names, none without a sink
+    ///
+    /// Must be called within a Tokio runtime, which runs the exporter.
+    ///
+    /// # Errors
+    /// Returns an error if the endpoint is not valid for the sink
+    pub fn from_config(config: &LogExportConfig) -> Result<Option<Self>, LogExportError> {
+        let Some(sink) = Sink::from_config(config)? else {
+            return Ok(None);
+        };
+
+        let (queue, records) = mpsc::channel(config.queue_capacity.max(1));
+        std::mem::forget(tokio::spawn(export(
+            sink,
+            records,
+            config.batch_size.max(1),
+            config.flush_interval(),
+        )));
+        Ok(Some(Self { queue }))
+    }
+}
+
+impl<S: Subscriber> Layer<S> for LogExportLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let metadata = event.metadata();
+        if UNSHIPPED_TARGETS.iter().any(|target| metadata.target().starts_with(target)) {
+            return;
+        }
+
+        let mut visitor = FieldVisitor::default();
+        event.record(&mut visitor);
+        let record = LogRecord {
+            timestamp: Utc::now(),
+            level: *metadata.level(),
+            target: metadata.target().to_string(),
+            message: visitor.message,
+            fields: visitor.fields,
+        };
+        if self.queue.try_send(record).is_err() {
+            counter!("log_export_events_dropped_total", "reason" => "queue_full").increment(1);
+        }
+    }
+}
+
+/// Send queued records in batches until every [`LogExportLayer`] is gone
+async fn export(
+    mut sink: Sink,
+    mut records: mpsc::Receiver<LogRecord>,
+    batch_size: usize,
+    flush_interval: Duration,
+) {
+    let mut batch = Vec::with_capacity(batch_size);
+    let mut ticks = tokio::time::interval(flush_interval);
+    loop {
+        let open = tokio::select! {
+            record = records.recv() => match record {
+                Some(record) => {
+                    batch.push(record);
+                    if batch.len() < batch_size {
+                        continue;
+                    }
+                    true
+                }
+                None => false,
+            },
+            _ = ticks.tick() => true,
+        };
+
+        if !batch.is_empty() {
+            if let Err(e) = sink.send(&batch).await {
+                tracing::warn!("Dropped {} log events: {}", batch.len(), e);
+                counter!("log_export_events_dropped_total", "reason" => "send_failed")
+                    .increment(batch.len() as u64);
+            }
+            batch.clear();
+        }
+        if !open {
+            return;
+        }
+    }
+}
+
+/// Where batches are sent, with its open connection if any
+enum Sink {
+    Udp { address: String, socket: Option<UdpSocket>, app_name: String },
+    Tcp { address: String, stream: Option<TcpStream>, app_name: String },
+    Loki { client: reqwest::Client, url: String, authorization: Secret<String>, service: String },
+    Otlp { client: reqwest::Client, url: String, authorization: Secret<String>, service: String },
+}
+
+impl Sink {
+    fn from_config(config: &LogExportConfig) -> Result<Option<Self>, LogExportError> {
+        let endpoint = config.endpoint.trim().trim_end_matches('/');
+        // RFC 5424 allows no spaces in APP-NAME
+        let app_name = config.service_name.replace(char::is_whitespace, "-");
+
+        match config.sink {
+            LogSink::None => Ok(None),
+            LogSink::Syslog => {
+                if let Some(address) = endpoint.strip_prefix("udp://") {
+                    let address = with_default_port(address, 514);
+                    Ok(Some(Self::Udp { address, socket: None, app_name }))
+                } else if let Some(address) = endpoint.strip_prefix("tcp://") {
+                    let address = with_default_port(address, 601);
+                    Ok(Some(Self::Tcp { address, stream: None, app_name }))
+                } else {
+                    Err(LogExportError::InvalidEndpoint(format!(
+                        "syslog needs a udp:// or tcp:// address, not {endpoint:?}"
+                    )))
+                }
+            }
+            LogSink::Loki | LogSink::Otlp => {
+                if !endpoint.starts_with("https://") && !endpoint.starts_with("http://") {
+                    return Err(LogExportError::InvalidEndpoint(format!(
+                        "expected an http(s) URL, not {endpoint:?}"
+                    )));
+                }
+                let client =
+                    reqwest::Client::builder().timeout(config.request_timeout()).build()?;
+                let authorization = config.authorization.clone();
+                let service = config.service_name.clone();
+                Ok(Some(if config.sink == LogSink::Loki {
+                    let url = format!("{endpoint}/loki/api/v1/push");
+                    Self::Loki { client, url, authorization, service }
+                } else {
+                    let url = format!("{endpoint}/v1/logs");
+                    Self::Otlp { client, url, authorization, service }
+                }))
+            }
+        }
+    }
+
+    async fn send(&mut self, batch: &[LogRecord]) -> Result<(), LogExportError> {
+        match self {
+            Self::Udp { address, socket, app_name } => {
+                if socket.is_none() {
+                    let bind = if address.starts_with('[') { "[::]:0" } else { "0.0.0.0:0" };
+                    let udp = UdpSocket::bind(bind).await?;
+                    udp.connect(address.as_str()).await?;
+                    *socket = Some(udp);
+                }
+                if let Some(udp) = socket {
+                    for record in batch {
+                        udp.send(syslog_message(record, app_name).as_bytes()).await?;
+                    }
+                }
+                Ok(())
+            }
+            Self::Tcp { address, stream, app_name } => {
+                if stream.is_none() {
+                    *stream = Some(TcpStream::connect(address.as_str()).await?);
+                }
+                // RFC 6587 octet counting
+                let framed = batch.iter().fold(String::new(), |mut framed, record| {
+                    let message = syslog_message(record, app_name);
+                    let _ = write!(framed, "{} {message}", message.len());
+                    framed
+                });
+                let written = match stream {
+                    Some(tcp) => tcp.write_all(framed.as_bytes()).await,
+                    None => Ok(()),
+                };
+                if written.is_err() {
+                    // Reconnect with the next batch
+                    *stream = None;
+                }
+                Ok(written?)
+            }
+            Self::Loki { client, url, authorization, service } => {
+                post(client, url, authorization, &loki_push(batch, service)).await
+            }
+            Self::Otlp { client, url, authorization, service } => {
+                post(client, url, authorization, &otlp_logs(batch, service)).await
+            }
+        }
+    }
+}
+
+/// `address` with `port` added unless it names one
+fn with_default_port(address: &str, port: u16) -> String {
+    if address.contains(':') {
+        address.to_string()
+    } else {
+        format!("{address}:{port}")
+    }
+}
+
+async fn post(
+    client: &reqwest::Client,
+    url: &str,
+    authorization: &Secret<String>,
+    body: &Value,
+) -> Result<(), LogExportError> {
+    let mut request = client.post(url).json(body);
+    if !authorization.is_empty() {
+        request = request.header("authorization", authorization.expose());
+    }
+    let response = request.send().await?;
+
+    let status = response.status();
+    if status.is_success() {
+        return Ok(());
+    }
+    let body = response.text().await.unwrap_or_default();
+    Err(LogExportError::Rejected { status, body })
+}
+
+/// RFC 5424 message of `record`, without structured data
+fn syslog_message(record: &LogRecord, app_name: &str) -> String {
+    let severity = match record.level {
+        Level::ERROR => 3,
+        Level::WARN => 4,
+        Level::INFO => 6,
+        Level::DEBUG | Level::TRACE => 7,
+    };
+    let hostname = std::env::var("HOSTNAME").unwrap_or_else(|_| "-".to_string());
+    format!(
+        "<{}>1 {} {hostname} {app_name} {} - - {}: {}",
+        SYSLOG_FACILITY * 8 + severity,
+        record.timestamp.to_rfc3339_opts(SecondsFormat::Micros, true),
+        std::process::id(),
+        record.target,
+        record.line()
+    )
+}
+
+/// Loki push request of `batch`, one stream per level
+fn loki_push(batch: &[LogRecord], service: &str) -> Value {
+    let mut streams: BTreeMap<String, Vec<Value>> = BTreeMap::new();
+    for record in batch {
+        let mut line = Map::new();
+        line.insert("target".to_string(), json!(record.target));
+        line.insert("message".to_string(), json!(record.message));
+        for (name, value) in &record.fields {
+            line.insert(name.clone(), json!(value));
+        }
+        let timestamp = record.timestamp.timestamp_nanos_opt().unwrap_or_default().to_string();
+        streams
+            .entry(record.level.as_str().to_lowercase())
+            .or_default()
+            .push(json!([timestamp, Value::Object(line).to_string()]));
+    }
+
+    let streams: Vec<Value> = streams
+        .into_iter()
+        .map(|(level, values)| {
+            json!({ "stream": { "service_name": service, "level": level }, "values": values })
+        })
+        .collect();
+    json!({ "streams": streams })
+}
+
+/// OTLP `ExportLogsServiceRequest` of `batch`, in its JSON encoding
+fn otlp_logs(batch: &[LogRecord], service: &str) -> Value {
+    let string_attribute =
+        |key: &str, value: &str| json!({ "key": key, "value": { "stringValue": value } });
+
+    let log_records: Vec<Value> = batch
+        .iter()
+        .map(|record| {
+            let severity_number = match record.level {
+                Level::TRACE => 1,
+                Level::DEBUG => 5,
+                Level::INFO => 9,
+                Level::WARN => 13,
+                Level::ERROR => 17,
+            };
+            let mut attributes = vec![string_attribute("target", &record.target)];
+            attributes
+                .extend(record.fields.iter().map(|(name, value)| string_attribute(name, value)));
+            json!({
+                "timeUnixNano": record.timestamp.timestamp_nanos_opt().unwrap_or_default().to_string(),
+                "severityNumber": severity_number,
+                "severityText": record.level.as_str(),
+                "body": { "stringValue": record.message },
+                "attributes": attributes,
+            })
+        })
+        .collect();
+
+    json!({
+        "resourceLogs": [{
+            "resource": { "attributes": [string_attribute("service.name", service)] },
+            "scopeLogs": [{
+                "scope": { "name": env!("CARGO_PKG_NAME"), "version": env!("CARGO_PKG_VERSION") },
+                "logRecords": log_records,
+            }],
+        }],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{routing::post, Router};
+    use chrono::TimeZone;
+    use tokio::net::TcpListener;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    fn record(level: Level, message: &str) -> LogRecord {
+        LogRecord {
+            timestamp: Utc.with_ymd_and_hms(2026, 3, 1, 12, 0, 0).unwrap(),
+            level,
+            target: "media_management_service::uploads".to_string(),
+            message: message.to_string(),
+            fields: vec![("media_id".to_string(), "42".to_string())],
+        }
+    }
+
+    fn export_config(sink: LogSink, endpoint: String) -> LogExportConfig {
+        LogExportConfig { sink, endpoint, flush_interval_ms: 10, ..LogExportConfig::default() }
+    }
+
+    #[test]
+    fn test_syslog_message_follows_rfc_5424() {
+        let message = syslog_message(&record(Level::WARN, "Upload stalled"), "media-service");
+
+        // local0.warning
+        assert!(message.starts_with("<132>1 2026-03-01T12:00:00.000000Z "), "{message}");
+        assert!(message.contains(&format!(" media-service {} - - ", std::process::id())));
+        assert!(message.ends_with("media_management_service::uploads: Upload stalled media_id=42"));
+    }
+
+    #[test]
+    fn test_otlp_logs_carry_severity_and_attributes() {
+        let body = otlp_logs(&[record(Level::ERROR, "Upload failed")], "media-service");
+
+        let resource = &body["resourceLogs"][0];
+        assert_eq!(resource["resource"]["attributes"][0]["value"]["stringValue"], "media-service");
+        let log = &resource["scopeLogs"][0]["logRecords"][0];
+        assert_eq!(log["severityNumber"], 17);
+        assert_eq!(log["body"]["stringValue"], "Upload failed");
+        assert_eq!(log["timeUnixNano"], "1772366400000000000");
+        assert_eq!(
+            log["attributes"][1],
+            json!({ "key": "media_id", "value": { "stringValue": "42" } })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_events_are_pushed_to_loki() {
+        let (sender, mut pushes) = mpsc::unbounded_channel();
+        let app = Router::new().route(
+            "/loki/api/v1/push",
+            post(move |body: String| async move {
+                sender.send(serde_json::from_str::<Value>(&body).unwrap()).unwrap();
+            }),
+        );
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let layer =
+            LogExportLayer::from_config(&export_config(LogSink::Loki, endpoint)).unwrap().unwrap();
+        tracing::subscriber::with_default(tracing_subscriber::registry().with(layer), || {
+            tracing::info!(target: "media_management_service::uploads", media_id = 42, "Upload completed");
+            tracing::info!(target: "hyper::client", "Not shipped");
+        });
+
+        let push = tokio::time::timeout(Duration::from_secs(5), pushes.recv()).await.unwrap();
+        let stream = &push.unwrap()["streams"][0];
+        assert_eq!(
+            stream["stream"],
+            json!({ "service_name": "media-management-service", "level": "info" })
+        );
+        let values = stream["values"].as_array().unwrap();
+        assert_eq!(values.len(), 1);
+        let line: Value = serde_json::from_str(values[0][1].as_str().unwrap()).unwrap();
+        assert_eq!(line["message"], "Upload completed");
+        assert_eq!(line["media_id"], "42");
+    }
+
+    #[tokio::test]
+    async fn test_events_are_sent_to_syslog_over_udp() {
+        let server = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("udp://{}", server.local_addr().unwrap());
+
+        let layer = LogExportLayer::from_config(&export_config(LogSink::Syslog, endpoint))
+            .unwrap()
+            .unwrap();
+        tracing::subscriber::with_default(tracing_subscriber::registry().with(layer), || {
+            tracing::error!(target: "media_management_service::storage", "Storage unavailable");
+        });
+
+        let mut datagram = [0; 1024];
+        let len = tokio::time::timeout(Duration::from_secs(5), server.recv(&mut datagram))
+            .await
+            .unwrap()
+            .unwrap();
+        let message = std::str::from_utf8(&datagram[..len]).unwrap();
+        assert!(message.starts_with("<131>1 "), "{message}");
+        assert!(message.ends_with("Storage unavailable"));
+    }
+
+    #[test]
+    fn test_endpoint_must_suit_the_sink() {
+        let syslog = export_config(LogSink::Syslog, "http://logs:514".to_string());
+        assert!(matches!(Sink::from_config(&syslog), Err(LogExportError::InvalidEndpoint(_))));
+
+        let loki = export_config(LogSink::Loki, "loki:3100".to_string());
+        assert!(matches!(Sink::from_config(&loki), Err(LogExportError::InvalidEndpoint(_))));
+
+        let none = export_config(LogSink::None, String::new());
+        assert!(Sink::from_config(&none).unwrap().is_none());
+    }
+}
@@ -1,5 +1,14 @@
+pub mod business_metrics;
+pub mod cache;
+pub mod cdn;
 pub mod config;
+pub mod event_stream;
 pub mod http;
+pub mod jobs;
+pub mod log_export;
 pub mod oauth2;
 pub mod persistence;
+pub mod processing;
+pub mod redis;
 pub mod storage;
+pub mod webhooks;
@@ -71,6 +71,9 @@ impl OAuth2Client {
         // Check cache first
         if let Some(cached) = self.token_cache.get_validation(token).await {
             debug!("Token validation cache hit");
+            if !cached.active {
+                return Err(OAuth2Error::InactiveToken);
+            }
             return Ok(cached);
         }
 
@@ -91,7 +94,7 @@ impl OAuth2Client {
                 .http_client
                 .post(&introspection_url)
                 .header("Content-Type", "application/x-www-form-urlencoded")
-                .basic_auth(&self.config.client_id, Some(&self.config.client_secret))
+                .basic_auth(&self.config.client_id, Some(self.config.client_secret.expose()))
                 .body(form_data)
                 .send()
                 .await;
@@ -187,7 +190,7 @@ impl OAuth2Client {
                 .http_client
                 .post(&token_url)
                 .header("Content-Type", "application/x-www-form-urlencoded")
-                .basic_auth(&self.config.client_id, Some(&self.config.client_secret))
+                .basic_auth(&self.config.client_id, Some(self.config.client_secret.expose()))
                 .body(form_data)
                 .send()
                 .await;
@@ -290,10 +293,11 @@ mod tests {
             enabled: true,
             service_to_service_enabled: true,
             introspection_enabled: true,
+            jwt_fallback_enabled: true,
             client_id: "test-client".to_string(),
-            client_secret: "test-secret".to_string(),
+            client_secret: "test-secret".into(),
             service_base_url: "http://localhost:8080/api/v1/auth".to_string(),
-            jwt_secret: "test-jwt-secret".to_string(),
+            jwt_secret: "test-jwt-secret".into(),
             token_cache_ttl_seconds: 300,
             client_credentials_cache_ttl_seconds: 1800,
             request_timeout_seconds: 10,
@@ -351,6 +355,28 @@ mod tests {
         assert_eq!(cached.unwrap().client_id, token_info.client_id);
     }
 
+    #[tokio::test]
+    async fn test_token_cache_drops_expired_tokens() {
+        let cache = TokenCache::new(300, 1800);
+
+        let token_info = CachedTokenInfo {
+            active: true,
+            client_id: Some("test-client".to_string()),
+            username: Some("test-user".to_string()),
+            scopes: vec!["read".to_string()],
+            token_type: Some("Bearer".to_string()),
+            expires_at: Some(chrono::Utc::now() - chrono::Duration::seconds(1)),
+            subject: Some("test-user".to_string()),
+            audience: None,
+            issuer: None,
+            cached_at: chrono::Utc::now(),
+        };
+
+        cache.cache_validation("expired-token".to_string(), token_info).await;
+
+        assert!(cache.get_validation("expired-token").await.is_none());
+    }
+
     #[tokio::test]
     async fn test_client_token_cache() {
         let cache = TokenCache::new(300, 1800);
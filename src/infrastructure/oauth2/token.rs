@@ -1,9 +1,14 @@
 use chrono::{DateTime, Utc};
+use moka::future::Cache;
 #[allow(unused_imports)]
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::time::Duration;
 use tokio::sync::RwLock;
 
+/// Most validation results kept at once, so a flood of distinct tokens can't grow the cache unbounded
+const VALIDATION_CACHE_CAPACITY: u64 = 10_000;
+
 /// Cached token validation result
 #[derive(Debug, Clone)]
 pub struct CachedTokenInfo {
@@ -30,7 +35,7 @@ pub struct CachedClientToken {
 
 /// Token cache for validation results and client credentials
 pub struct TokenCache {
-    validation_cache: RwLock<HashMap<String, CachedTokenInfo>>,
+    validation_cache: Cache<String, CachedTokenInfo>,
     client_cache: RwLock<HashMap<String, CachedClientToken>>, // Key: scope combination
     validation_ttl_seconds: u64,
     #[allow(dead_code)]
@@ -40,7 +45,10 @@ pub struct TokenCache {
 impl TokenCache {
     pub fn new(validation_ttl_seconds: u64, client_token_ttl_seconds: u64) -> Self {
         Self {
-            validation_cache: RwLock::new(HashMap::new()),
+            validation_cache: Cache::builder()
+                .max_capacity(VALIDATION_CACHE_CAPACITY)
+                .time_to_live(Duration::from_secs(validation_ttl_seconds))
+                .build(),
             client_cache: RwLock::new(HashMap::new()),
             validation_ttl_seconds,
             client_token_ttl_seconds,
@@ -48,27 +56,24 @@ impl TokenCache {
     }
 
     /// Get cached token validation result
+    ///
+    /// Results are dropped once the token itself expires, even when that comes
+    /// before the cache TTL.
     pub async fn get_validation(&self, token: &str) -> Option<CachedTokenInfo> {
-        let cache = self.validation_cache.read().await;
-        if let Some(cached) = cache.get(token) {
-            let now = Utc::now();
-            let cache_expires =
-                cached.cached_at + chrono::Duration::seconds(self.validation_ttl_seconds as i64);
-
-            if now < cache_expires {
-                Some(cached.clone())
-            } else {
-                None
-            }
-        } else {
-            None
+        let cached = self.validation_cache.get(token).await?;
+        let now = Utc::now();
+        let cache_expires =
+            cached.cached_at + chrono::Duration::seconds(self.validation_ttl_seconds as i64);
+        if now >= cache_expires || cached.expires_at.is_some_and(|expires_at| expires_at <= now) {
+            self.validation_cache.invalidate(token).await;
+            return None;
         }
+        Some(cached)
     }
 
     /// Cache token validation result
     pub async fn cache_validation(&self, token: String, info: CachedTokenInfo) {
-        let mut cache = self.validation_cache.write().await;
-        cache.insert(token, info);
+        self.validation_cache.insert(token, info).await;
     }
 
     /// Get cached client credentials token
@@ -102,15 +107,8 @@ impl TokenCache {
     pub async fn cleanup_expired(&self) {
         let now = Utc::now();
 
-        // Clean validation cache
-        {
-            let mut cache = self.validation_cache.write().await;
-            cache.retain(|_, cached| {
-                let cache_expires = cached.cached_at
-                    + chrono::Duration::seconds(self.validation_ttl_seconds as i64);
-                now < cache_expires
-            });
-        }
+        // Evicts validation results past the cache TTL
+        self.validation_cache.run_pending_tasks().await;
 
         // Clean client token cache
         {
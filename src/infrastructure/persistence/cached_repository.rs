@@ -0,0 +1,530 @@
+use crate::domain::entities::{
+    IngredientId, Media, MediaAssociation, MediaId, MediaVariant, RecipeId, StepId, UnsavedMedia,
+    UserId,
+};
+use crate::domain::events::DomainEvent;
+use crate::domain::repositories::{EventSink, MediaChange, MediaRepository, Recorded, SaveOutcome};
+use crate::domain::value_objects::{
+    CapacityDimension, CapacityGrowth, ContentHash, MediaSearch, MediaSort, MediaTag,
+    ProcessingStatus, StorageUsage,
+};
+use crate::infrastructure::cache::{Cache, CacheError};
+use async_trait::async_trait;
+use serde::{de::DeserializeOwned, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::SystemTime;
+use tracing::{info, warn};
+
+/// Key holding the generation of cached association lists
+const ASSOCIATIONS_GENERATION: &str = "associations:generation";
+
+/// A repository wrapper serving hot media metadata from a [`Cache`]
+///
+/// Media looked up by ID and the media IDs of recipes, ingredients and steps are
+/// cached; every other call goes straight to the wrapped repository. Updates,
+/// tag changes and deletes remove the media's entry, and association changes
+/// remove the affected list. Deleting media also starts a new generation of
+/// association lists, since any of them may have listed it.
+///
+/// The cache only ever saves a trip to the database: while it cannot be reached,
+/// every lookup is a miss and a warning is logged once.
+#[derive(Clone)]
+pub struct CachedMediaRepository<R> {
+    inner: R,
+    cache: Arc<dyn Cache>,
+    available: Arc<AtomicBool>,
+}
+
+impl<R> CachedMediaRepository<R>
+where
+    R: MediaRepository,
+{
+    /// Cache the lookups of `inner` in `cache`
+    pub fn new(inner: R, cache: Arc<dyn Cache>) -> Self {
+        Self { inner, cache, available: Arc::new(AtomicBool::new(true)) }
+    }
+
+    /// The value cached under `key`, if any
+    async fn cached<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        let value = self.outcome(self.cache.get(key).await).flatten();
+        Self::decode(key, value)
+    }
+
+    /// Cache `value` under `key`
+    async fn store<T: Serialize>(&self, key: &str, value: &T) {
+        if let Ok(value) = serde_json::to_string(value) {
+            self.outcome(self.cache.set(key, value).await);
+        }
+    }
+
+    /// Remove the entries of `keys`
+    async fn invalidate(&self, keys: &[String]) {
+        self.outcome(self.cache.remove(keys).await);
+    }
+
+    /// Parse a cached value, treating one this version cannot read as a miss
+    fn decode<T: DeserializeOwned>(key: &str, value: Option<String>) -> Option<T> {
+        let value = value?;
+        let decoded = serde_json::from_str(&value).ok();
+        if decoded.is_some() {
+            metrics::counter!("media_cache_lookups_total", "result" => "hit").increment(1);
+        } else {
+            tracing::debug!("Ignoring unreadable cache entry {}", key);
+        }
+        decoded
+    }
+
+    /// The value of a cache call, logging when the cache goes away or comes back
+    fn outcome<T>(&self, result: Result<T, CacheError>) -> Option<T> {
+        match result {
+            Ok(value) => {
+                if !self.available.swap(true, Ordering::Relaxed) {
+                    info!("Media cache reachable again");
+                }
+                Some(value)
+            }
+            Err(e) => {
+                metrics::counter!("media_cache_errors_total").increment(1);
+                if self.available.swap(false, Ordering::Relaxed) {
+                    warn!("{} - reading media from the database until it recovers", e);
+                }
+                None
+            }
+        }
+    }
+
+    /// The current generation of association lists, starting one if there is none
+    async fn associations_generation(&self) -> Option<String> {
+        match self.outcome(self.cache.get(ASSOCIATIONS_GENERATION).await)? {
+            Some(generation) => Some(generation),
+            None => self.new_associations_generation().await,
+        }
+    }
+
+    /// Start a new generation, so association lists cached so far are no longer read
+    async fn new_associations_generation(&self) -> Option<String> {
+        let generation = uuid::Uuid::new_v4().simple().to_string();
+        self.outcome(self.cache.set(ASSOCIATIONS_GENERATION, generation.clone()).await)?;
+        Some(generation)
+    }
+
+    /// Key of the media IDs of `association` in the current generation
+    async fn association_key(&self, association: MediaAssociation) -> Option<String> {
+        let generation = self.associations_generation().await?;
+        Some(match association {
+            MediaAssociation::Recipe(recipe_id) => format!("recipe:{generation}:{recipe_id}"),
+            MediaAssociation::Ingredient(recipe_id, ingredient_id) => {
+                format!("recipe:{generation}:{recipe_id}:ingredient:{ingredient_id}")
+            }
+            MediaAssociation::Step(recipe_id, step_id) => {
+                format!("recipe:{generation}:{recipe_id}:step:{step_id}")
+            }
+        })
+    }
+
+    /// The media IDs of `association`, from the cache or else `lookup`
+    async fn find_media_ids<F>(
+        &self,
+        association: MediaAssociation,
+        lookup: F,
+    ) -> Result<Vec<MediaId>, R::Error>
+    where
+        F: std::future::Future<Output = Result<Vec<MediaId>, R::Error>> + Send,
+    {
+        let Some(key) = self.association_key(association).await else {
+            return lookup.await;
+        };
+        if let Some(ids) = self.cached(&key).await {
+            return Ok(ids);
+        }
+
+        metrics::counter!("media_cache_lookups_total", "result" => "miss").increment(1);
+        let ids = lookup.await?;
+        self.store(&key, &ids).await;
+        Ok(ids)
+    }
+
+    /// Forget the cached media IDs of `association`
+    async fn invalidate_association(&self, association: MediaAssociation) {
+        if let Some(key) = self.association_key(association).await {
+            self.invalidate(&[key]).await;
+        }
+    }
+}
+
+/// Key of the media with `id`
+fn media_key(id: MediaId) -> String {
+    format!("media:{id}")
+}
+
+#[async_trait]
+impl<R> MediaRepository for CachedMediaRepository<R>
+where
+    R: MediaRepository,
+{
+    type Error = R::Error;
+
+    async fn save(&self, media: &UnsavedMedia) -> Result<MediaId, Self::Error> {
+        self.inner.save(media).await
+    }
+
+    async fn save_or_reuse(&self, media: &UnsavedMedia) -> Result<SaveOutcome, Self::Error> {
+        self.inner.save_or_reuse(media).await
+    }
+
+    async fn save_or_reuse_announced(
+        &self,
+        media: &UnsavedMedia,
+    ) -> Result<Recorded<SaveOutcome>, Self::Error> {
+        self.inner.save_or_reuse_announced(media).await
+    }
+
+    async fn find_by_id(&self, id: MediaId) -> Result<Option<Media>, Self::Error> {
+        let key = media_key(id);
+        if let Some(media) = self.cached(&key).await {
+            return Ok(Some(media));
+        }
+
+        // Only media that exists is cached, so an ID is never reported missing
+        // after the media it is assigned to was saved
+        metrics::counter!("media_cache_lookups_total", "result" => "miss").increment(1);
+        let media = self.inner.find_by_id(id).await?;
+        if let Some(media) = &media {
+            self.store(&key, media).await;
+        }
+        Ok(media)
+    }
+
+    async fn find_by_ids(&self, ids: &[MediaId]) -> Result<Vec<Media>, Self::Error> {
+        let keys: Vec<String> = ids.iter().copied().map(media_key).collect();
+        let cached = self.outcome(self.cache.get_many(&keys).await).unwrap_or_default();
+
+        let mut found = Vec::with_capacity(ids.len());
+        let mut missing = Vec::new();
+        for (index, id) in ids.iter().enumerate() {
+            let value = cached.get(index).cloned().flatten();
+            match Self::decode::<Media>(&keys[index], value) {
+                Some(media) => found.push(media),
+                None => missing.push(*id),
+            }
+        }
+        if missing.is_empty() {
+            return Ok(found);
+        }
+
+        metrics::counter!("media_cache_lookups_total", "result" => "miss")
+            .increment(missing.len() as u64);
+        let fetched = self.inner.find_by_ids(&missing).await?;
+        for media in &fetched {
+            self.store(&media_key(media.id), media).await;
+        }
+        found.extend(fetched);
+        Ok(found)
+    }
+
+    async fn find_by_content_hash(
+        &self,
+        hash: &ContentHash,
+        owner: Option<UserId>,
+    ) -> Result<Option<Media>, Self::Error> {
+        self.inner.find_by_content_hash(hash, owner).await
+    }
+
+    async fn find_by_user(&self, user_id: UserId) -> Result<Vec<Media>, Self::Error> {
+        self.inner.find_by_user(user_id).await
+    }
+
+    async fn find_after(
+        &self,
+        after: Option<MediaId>,
+        limit: u32,
+    ) -> Result<Vec<Media>, Self::Error> {
+        self.inner.find_after(after, limit).await
+    }
+
+    async fn find_unprocessed_before(
+        &self,
+        updated_before: SystemTime,
+        limit: u32,
+    ) -> Result<Vec<Media>, Self::Error> {
+        self.inner.find_unprocessed_before(updated_before, limit).await
+    }
+
+    async fn find_by_user_paginated(
+        &self,
+        user_id: UserId,
+        cursor: Option<String>,
+        limit: u32,
+        status_filter: Option<ProcessingStatus>,
+        tags: &[MediaTag],
+        sort: MediaSort,
+    ) -> Result<(Vec<Media>, Option<String>, bool), Self::Error> {
+        self.inner.find_by_user_paginated(user_id, cursor, limit, status_filter, tags, sort).await
+    }
+
+    async fn search_by_user(
+        &self,
+        user_id: UserId,
+        search: &MediaSearch,
+        cursor: Option<String>,
+        limit: u32,
+    ) -> Result<(Vec<Media>, Option<String>, bool), Self::Error> {
+        self.inner.search_by_user(user_id, search, cursor, limit).await
+    }
+
+    async fn update(&self, media: &Media) -> Result<(), Self::Error> {
+        let result = self.inner.update(media).await;
+        self.invalidate(&[media_key(media.id)]).await;
+        result
+    }
+
+    async fn set_corrupted_at(
+        &self,
+        media_id: MediaId,
+        corrupted_at: Option<SystemTime>,
+    ) -> Result<bool, Self::Error> {
+        let result = self.inner.set_corrupted_at(media_id, corrupted_at).await;
+        self.invalidate(&[media_key(media_id)]).await;
+        result
+    }
+
+    async fn add_variant(
+        &self,
+        media_id: MediaId,
+        variant: &MediaVariant,
+    ) -> Result<bool, Self::Error> {
+        let result = self.inner.add_variant(media_id, variant).await;
+        self.invalidate(&[media_key(media_id)]).await;
+        result
+    }
+
+    async fn set_tags(&self, media_id: MediaId, tags: &[MediaTag]) -> Result<bool, Self::Error> {
+        let result = self.inner.set_tags(media_id, tags).await;
+        self.invalidate(&[media_key(media_id)]).await;
+        result
+    }
+
+    async fn delete(&self, id: MediaId) -> Result<bool, Self::Error> {
+        let result = self.inner.delete(id).await;
+        self.invalidate(&[media_key(id)]).await;
+        if matches!(result, Ok(true)) {
+            self.new_associations_generation().await;
+        }
+        result
+    }
+
+    async fn apply(
+        &self,
+        change: MediaChange<'_>,
+        events: &[DomainEvent],
+    ) -> Result<Recorded<bool>, Self::Error> {
+        let result = self.inner.apply(change, events).await;
+        match change {
+            MediaChange::Update(media) => self.invalidate(&[media_key(media.id)]).await,
+            MediaChange::SetTags(media_id, _) => self.invalidate(&[media_key(media_id)]).await,
+            MediaChange::Delete(media_id) => {
+                self.invalidate(&[media_key(media_id)]).await;
+                if matches!(result, Ok(Recorded { outcome: true, .. })) {
+                    self.new_associations_generation().await;
+                }
+            }
+            MediaChange::AddAssociation(_, association)
+            | MediaChange::RemoveAssociation(_, association) => {
+                self.invalidate_association(association).await;
+            }
+        }
+        result
+    }
+
+    async fn relay_events(&self, limit: u32, relay: &EventSink) -> Result<usize, Self::Error> {
+        self.inner.relay_events(limit, relay).await
+    }
+
+    async fn exists_by_content_hash(&self, hash: &ContentHash) -> Result<bool, Self::Error> {
+        self.inner.exists_by_content_hash(hash).await
+    }
+
+    async fn find_media_ids_by_recipe(
+        &self,
+        recipe_id: RecipeId,
+    ) -> Result<Vec<MediaId>, Self::Error> {
+        self.find_media_ids(
+            MediaAssociation::Recipe(recipe_id),
+            self.inner.find_media_ids_by_recipe(recipe_id),
+        )
+        .await
+    }
+
+    async fn find_media_ids_by_recipe_ingredient(
+        &self,
+        recipe_id: RecipeId,
+        ingredient_id: IngredientId,
+    ) -> Result<Vec<MediaId>, Self::Error> {
+        self.find_media_ids(
+            MediaAssociation::Ingredient(recipe_id, ingredient_id),
+            self.inner.find_media_ids_by_recipe_ingredient(recipe_id, ingredient_id),
+        )
+        .await
+    }
+
+    async fn find_media_ids_by_recipe_step(
+        &self,
+        recipe_id: RecipeId,
+        step_id: StepId,
+    ) -> Result<Vec<MediaId>, Self::Error> {
+        self.find_media_ids(
+            MediaAssociation::Step(recipe_id, step_id),
+            self.inner.find_media_ids_by_recipe_step(recipe_id, step_id),
+        )
+        .await
+    }
+
+    async fn add_association(
+        &self,
+        media_id: MediaId,
+        association: MediaAssociation,
+    ) -> Result<bool, Self::Error> {
+        let result = self.inner.add_association(media_id, association).await;
+        self.invalidate_association(association).await;
+        result
+    }
+
+    async fn remove_association(
+        &self,
+        media_id: MediaId,
+        association: MediaAssociation,
+    ) -> Result<bool, Self::Error> {
+        let result = self.inner.remove_association(media_id, association).await;
+        self.invalidate_association(association).await;
+        result
+    }
+
+    async fn find_media_by_recipe(
+        &self,
+        recipe_id: RecipeId,
+        cursor: Option<String>,
+        limit: u32,
+    ) -> Result<(Vec<Media>, Option<String>, bool), Self::Error> {
+        self.inner.find_media_by_recipe(recipe_id, cursor, limit).await
+    }
+
+    async fn find_media_by_recipe_ingredient(
+        &self,
+        recipe_id: RecipeId,
+        ingredient_id: IngredientId,
+        cursor: Option<String>,
+        limit: u32,
+    ) -> Result<(Vec<Media>, Option<String>, bool), Self::Error> {
+        self.inner.find_media_by_recipe_ingredient(recipe_id, ingredient_id, cursor, limit).await
+    }
+
+    async fn find_media_by_recipe_step(
+        &self,
+        recipe_id: RecipeId,
+        step_id: StepId,
+        cursor: Option<String>,
+        limit: u32,
+    ) -> Result<(Vec<Media>, Option<String>, bool), Self::Error> {
+        self.inner.find_media_by_recipe_step(recipe_id, step_id, cursor, limit).await
+    }
+
+    async fn usage_by_user(&self, user_id: UserId) -> Result<StorageUsage, Self::Error> {
+        self.inner.usage_by_user(user_id).await
+    }
+
+    async fn capacity_growth(
+        &self,
+        dimension: CapacityDimension,
+        since: SystemTime,
+    ) -> Result<Vec<CapacityGrowth>, Self::Error> {
+        self.inner.capacity_growth(dimension, since).await
+    }
+
+    async fn health_check(&self) -> Result<(), Self::Error> {
+        self.inner.health_check().await
+    }
+
+    async fn migration_version(&self) -> Result<Option<i64>, Self::Error> {
+        self.inner.migration_version().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::value_objects::MediaType;
+    use crate::infrastructure::cache::MemoryCache;
+    use crate::infrastructure::config::CacheConfig;
+    use crate::test_utils::mocks::InMemoryMediaRepository;
+
+    fn cached(
+        repository: &InMemoryMediaRepository,
+    ) -> CachedMediaRepository<InMemoryMediaRepository> {
+        let config = CacheConfig { enabled: true, ..CacheConfig::default() };
+        CachedMediaRepository::new(repository.clone(), Arc::new(MemoryCache::new(&config)))
+    }
+
+    fn media(id: i64) -> Media {
+        UnsavedMedia::new(
+            ContentHash::new(&format!("{id:064x}")).unwrap(),
+            format!("photo-{id}.jpg"),
+            MediaType::new("image/jpeg"),
+            format!("aa/bb/cc/{id}"),
+            1024,
+            UserId::new(),
+        )
+        .into_media(MediaId::new(id))
+    }
+
+    #[tokio::test]
+    async fn test_lookups_are_served_from_cache_until_invalidated() {
+        let source = InMemoryMediaRepository::new().with_media(media(1)).with_media(media(2));
+        let repository = cached(&source);
+
+        assert_eq!(repository.find_by_id(MediaId::new(1)).await.unwrap().unwrap().file_size, 1024);
+        // Changes behind the cache's back are not seen while the entry lives
+        let mut changed = media(1);
+        changed.file_size = 2048;
+        source.update(&changed).await.unwrap();
+        assert_eq!(repository.find_by_id(MediaId::new(1)).await.unwrap().unwrap().file_size, 1024);
+
+        // Changes made through the cache are
+        repository.update(&changed).await.unwrap();
+        let ids = [MediaId::new(1), MediaId::new(2), MediaId::new(3)];
+        let mut found = repository.find_by_ids(&ids).await.unwrap();
+        found.sort_by_key(|media| media.id);
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].file_size, 2048);
+
+        repository.delete(MediaId::new(2)).await.unwrap();
+        assert!(repository.find_by_id(MediaId::new(2)).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_association_lists_follow_changes() {
+        let recipe_id = RecipeId::new(4);
+        let source = InMemoryMediaRepository::new().with_media(media(1)).with_media(media(2));
+        let repository = cached(&source);
+        let recipe = MediaAssociation::Recipe(recipe_id);
+
+        repository.add_association(MediaId::new(1), recipe).await.unwrap();
+        repository.add_association(MediaId::new(2), recipe).await.unwrap();
+        assert_eq!(repository.find_media_ids_by_recipe(recipe_id).await.unwrap().len(), 2);
+
+        repository.remove_association(MediaId::new(1), recipe).await.unwrap();
+        assert_eq!(
+            repository.find_media_ids_by_recipe(recipe_id).await.unwrap(),
+            [MediaId::new(2)]
+        );
+
+        // Deleting media drops every cached list, as the database drops its associations
+        source.remove_association(MediaId::new(2), recipe).await.unwrap();
+        assert_eq!(
+            repository.find_media_ids_by_recipe(recipe_id).await.unwrap(),
+            [MediaId::new(2)]
+        );
+        repository.delete(MediaId::new(2)).await.unwrap();
+        assert!(repository.find_media_ids_by_recipe(recipe_id).await.unwrap().is_empty());
+    }
+}
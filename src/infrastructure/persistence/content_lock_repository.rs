@@ -0,0 +1,123 @@
+use crate::presentation::middleware::error::AppError;
+use async_trait::async_trait;
+use sqlx::pool::PoolConnection;
+use sqlx::postgres::PgPoolOptions;
+use sqlx::{PgPool, Postgres};
+use std::time::Duration;
+use tokio::time::Instant;
+
+use crate::domain::repositories::{ContentLease, ContentLockRepository};
+use crate::domain::value_objects::ContentHash;
+
+/// Connections set aside for content locks, apart from the main pool
+const LOCK_POOL_SIZE: u32 = 8;
+
+/// Longest wait for a connection and the lock before giving up
+const LOCK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Pause between attempts to take a lock held by another instance
+const LOCK_RETRY_INTERVAL: Duration = Duration::from_millis(50);
+
+/// `PostgreSQL` implementation of `ContentLockRepository`, using advisory locks
+///
+/// Each lock is a session-level advisory lock keyed by a hash of the content
+/// hash, held for as long as the lease lives. Leases hold their connection that
+/// long too, so they come from a small pool of their own rather than starving
+/// queries of the main one, and a lock still taken after [`LOCK_TIMEOUT`] fails.
+#[derive(Clone)]
+pub struct PostgreSqlContentLockRepository {
+    pool: PgPool,
+}
+
+impl PostgreSqlContentLockRepository {
+    /// Create a new `PostgreSQL` content lock repository connecting like `pool`
+    ///
+    /// Lock connections are opened on demand.
+    #[must_use]
+    pub fn new(pool: &PgPool) -> Self {
+        let pool = PgPoolOptions::new()
+            .max_connections(LOCK_POOL_SIZE)
+            .min_connections(0)
+            .acquire_timeout(LOCK_TIMEOUT)
+            .connect_lazy_with((*pool.connect_options()).clone());
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl ContentLockRepository for PostgreSqlContentLockRepository {
+    type Error = AppError;
+
+    async fn lock(&self, hash: &ContentHash) -> Result<ContentLease, Self::Error> {
+        let deadline = Instant::now() + LOCK_TIMEOUT;
+        let conn = self.pool.acquire().await.map_err(AppError::from)?;
+        let mut lock = AdvisoryLock { conn: Some(conn), key: lock_key(hash), locked: false };
+
+        while let Some(conn) = lock.conn.as_mut() {
+            let locked: bool =
+                sqlx::query_scalar("SELECT pg_try_advisory_lock(hashtextextended($1, 0))")
+                    .bind(&lock.key)
+                    .fetch_one(&mut **conn)
+                    .await
+                    .map_err(AppError::from)?;
+            if locked {
+                lock.locked = true;
+                break;
+            }
+            if Instant::now() >= deadline {
+                return Err(AppError::ServiceUnavailable {
+                    message: format!("Content lock {} is still held elsewhere", lock.key),
+                    last_error: None,
+                    retry_after_seconds: None,
+                });
+            }
+            tokio::time::sleep(LOCK_RETRY_INTERVAL).await;
+        }
+
+        Ok(Box::new(lock))
+    }
+}
+
+/// Advisory lock key of a content hash, namespaced from other advisory locks
+fn lock_key(hash: &ContentHash) -> String {
+    format!("content:{}", hash.as_str())
+}
+
+/// An advisory lock held on a pooled connection
+struct AdvisoryLock {
+    conn: Option<PoolConnection<Postgres>>,
+    key: String,
+    /// Whether the lock was granted; an attempt cut short may still be granted later
+    locked: bool,
+}
+
+impl Drop for AdvisoryLock {
+    fn drop(&mut self) {
+        let Some(mut conn) = self.conn.take() else {
+            return;
+        };
+
+        // Session locks outlive the lease unless unlocked, so a connection that
+        // cannot be unlocked is closed instead of going back to the pool
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            conn.close_on_drop();
+            return;
+        };
+        if !self.locked {
+            conn.close_on_drop();
+            return;
+        }
+
+        let key = std::mem::take(&mut self.key);
+        runtime.spawn(async move {
+            let unlocked = sqlx::query("SELECT pg_advisory_unlock(hashtextextended($1, 0))")
+                .bind(&key)
+                .execute(&mut *conn)
+                .await;
+            if let Err(e) = unlocked {
+                tracing::warn!("Failed to release content lock {}: {}", key, e);
+                conn.close_on_drop();
+            }
+        });
+    }
+}
@@ -0,0 +1,260 @@
+use crate::presentation::middleware::error::AppError;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{PgPool, Row};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use crate::domain::entities::{IdempotencyRecord, StoredResponse};
+use crate::domain::repositories::IdempotencyRepository;
+
+/// `PostgreSQL` implementation of `IdempotencyRepository`
+#[derive(Clone)]
+pub struct PostgreSqlIdempotencyRepository {
+    pool: PgPool,
+}
+
+impl PostgreSqlIdempotencyRepository {
+    /// Create a new `PostgreSQL` idempotency key repository
+    #[must_use]
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl IdempotencyRepository for PostgreSqlIdempotencyRepository {
+    type Error = AppError;
+
+    async fn reserve(&self, record: &IdempotencyRecord) -> Result<bool, Self::Error> {
+        let created_at: DateTime<Utc> = record.created_at.into();
+        let expires_at: DateTime<Utc> = record.expires_at.into();
+
+        // Takes over an expired record in the same statement, so two requests
+        // racing for a key cannot both reserve it
+        let result = sqlx::query(
+            r"
+            INSERT INTO recipe_manager.idempotency_keys
+            (idempotency_key, request, body_hash, created_at, expires_at)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (idempotency_key) DO UPDATE
+            SET request = EXCLUDED.request,
+                body_hash = EXCLUDED.body_hash,
+                status = NULL,
+                content_type = NULL,
+                body = NULL,
+                created_at = EXCLUDED.created_at,
+                expires_at = EXCLUDED.expires_at
+            WHERE recipe_manager.idempotency_keys.expires_at <= EXCLUDED.created_at
+            ",
+        )
+        .bind(&record.key)
+        .bind(&record.request)
+        .bind(&record.body_hash)
+        .bind(created_at)
+        .bind(expires_at)
+        .execute(&self.pool)
+        .await
+        .map_err(AppError::from)?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn find(
+        &self,
+        key: &str,
+        now: SystemTime,
+    ) -> Result<Option<IdempotencyRecord>, Self::Error> {
+        let now: DateTime<Utc> = now.into();
+        let row = sqlx::query(
+            r"
+            SELECT idempotency_key, request, body_hash, status, content_type, body, created_at,
+                   expires_at
+            FROM recipe_manager.idempotency_keys
+            WHERE idempotency_key = $1 AND expires_at > $2
+            ",
+        )
+        .bind(key)
+        .bind(now)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(AppError::from)?;
+
+        Ok(row.as_ref().map(map_row_to_idempotency_record))
+    }
+
+    async fn complete(&self, record: &IdempotencyRecord) -> Result<(), Self::Error> {
+        let expires_at: DateTime<Utc> = record.expires_at.into();
+        let response = record.response.as_ref();
+
+        sqlx::query(
+            r"
+            UPDATE recipe_manager.idempotency_keys
+            SET status = $2, content_type = $3, body = $4, expires_at = $5
+            WHERE idempotency_key = $1
+            ",
+        )
+        .bind(&record.key)
+        .bind(response.map(|response| response.status as i16))
+        .bind(response.and_then(|response| response.content_type.as_deref()))
+        .bind(response.map(|response| response.body.as_slice()))
+        .bind(expires_at)
+        .execute(&self.pool)
+        .await
+        .map_err(AppError::from)?;
+
+        Ok(())
+    }
+
+    async fn delete(&self, key: &str) -> Result<bool, Self::Error> {
+        let result =
+            sqlx::query("DELETE FROM recipe_manager.idempotency_keys WHERE idempotency_key = $1")
+                .bind(key)
+                .execute(&self.pool)
+                .await
+                .map_err(AppError::from)?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn delete_expired(&self, now: SystemTime) -> Result<u64, Self::Error> {
+        let now: DateTime<Utc> = now.into();
+        let result =
+            sqlx::query("DELETE FROM recipe_manager.idempotency_keys WHERE expires_at <= $1")
+                .bind(now)
+                .execute(&self.pool)
+                .await
+                .map_err(AppError::from)?;
+
+        Ok(result.rows_affected())
+    }
+}
+
+/// Map a database row to an `IdempotencyRecord` entity
+fn map_row_to_idempotency_record(row: &sqlx::postgres::PgRow) -> IdempotencyRecord {
+    let status: Option<i16> = row.get("status");
+    let created_at: DateTime<Utc> = row.get("created_at");
+    let expires_at: DateTime<Utc> = row.get("expires_at");
+
+    IdempotencyRecord {
+        key: row.get("idempotency_key"),
+        request: row.get("request"),
+        body_hash: row.get("body_hash"),
+        response: status.map(|status| StoredResponse {
+            status: status as u16,
+            content_type: row.get("content_type"),
+            body: row.get::<Option<Vec<u8>>, _>("body").unwrap_or_default(),
+        }),
+        created_at: created_at.into(),
+        expires_at: expires_at.into(),
+    }
+}
+
+/// In-memory implementation of `IdempotencyRepository`
+///
+/// Used when the service starts without a database connection. Keys are only
+/// honored by the instance that stored them, and are forgotten on restart.
+#[derive(Clone, Default)]
+pub struct InMemoryIdempotencyRepository {
+    records: Arc<Mutex<HashMap<String, IdempotencyRecord>>>,
+}
+
+impl InMemoryIdempotencyRepository {
+    /// Create a new, empty in-memory repository
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn lock_error() -> AppError {
+        AppError::Internal { message: "Idempotency key store lock poisoned".to_string() }
+    }
+}
+
+#[async_trait]
+impl IdempotencyRepository for InMemoryIdempotencyRepository {
+    type Error = AppError;
+
+    async fn reserve(&self, record: &IdempotencyRecord) -> Result<bool, Self::Error> {
+        let mut records = self.records.lock().map_err(|_| Self::lock_error())?;
+        if records.get(&record.key).is_some_and(|held| !held.is_expired_at(record.created_at)) {
+            return Ok(false);
+        }
+        records.insert(record.key.clone(), record.clone());
+        Ok(true)
+    }
+
+    async fn find(
+        &self,
+        key: &str,
+        now: SystemTime,
+    ) -> Result<Option<IdempotencyRecord>, Self::Error> {
+        let records = self.records.lock().map_err(|_| Self::lock_error())?;
+        Ok(records.get(key).filter(|record| !record.is_expired_at(now)).cloned())
+    }
+
+    async fn complete(&self, record: &IdempotencyRecord) -> Result<(), Self::Error> {
+        let mut records = self.records.lock().map_err(|_| Self::lock_error())?;
+        if let Some(held) = records.get_mut(&record.key) {
+            held.response.clone_from(&record.response);
+            held.expires_at = record.expires_at;
+        }
+        Ok(())
+    }
+
+    async fn delete(&self, key: &str) -> Result<bool, Self::Error> {
+        let mut records = self.records.lock().map_err(|_| Self::lock_error())?;
+        Ok(records.remove(key).is_some())
+    }
+
+    async fn delete_expired(&self, now: SystemTime) -> Result<u64, Self::Error> {
+        let mut records = self.records.lock().map_err(|_| Self::lock_error())?;
+        let before = records.len();
+        records.retain(|_, record| !record.is_expired_at(now));
+        Ok((before - records.len()) as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn create_test_record(created_at: SystemTime) -> IdempotencyRecord {
+        IdempotencyRecord {
+            key: "user:retry-1".to_string(),
+            request: "POST /media/".to_string(),
+            body_hash: String::new(),
+            response: None,
+            created_at,
+            expires_at: created_at + Duration::from_mins(10),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_key_is_held_until_it_expires() {
+        let repository = InMemoryIdempotencyRepository::new();
+        let now = SystemTime::now();
+        let mut record = create_test_record(now);
+
+        assert!(repository.reserve(&record).await.unwrap());
+        assert!(!repository.reserve(&create_test_record(now)).await.unwrap());
+
+        record.response = Some(StoredResponse {
+            status: 201,
+            content_type: Some("application/json".to_string()),
+            body: br#"{"media_id":5}"#.to_vec(),
+        });
+        record.expires_at = now + Duration::from_hours(24);
+        repository.complete(&record).await.unwrap();
+        assert_eq!(repository.find(&record.key, now).await.unwrap(), Some(record.clone()));
+
+        // Past its expiry the key is free again, and swept by cleanup
+        let later = now + Duration::from_hours(24);
+        assert!(repository.find(&record.key, later).await.unwrap().is_none());
+        assert_eq!(repository.delete_expired(later).await.unwrap(), 1);
+        assert!(repository.reserve(&create_test_record(later)).await.unwrap());
+        assert!(repository.delete(&record.key).await.unwrap());
+    }
+}
@@ -1,12 +1,22 @@
 use crate::presentation::middleware::error::AppError;
 use async_trait::async_trait;
-use base64::{engine::general_purpose::STANDARD, Engine as _};
 use chrono::{DateTime, Utc};
-use sqlx::{PgPool, Row};
-
-use crate::domain::entities::{IngredientId, Media, MediaId, RecipeId, StepId, UserId};
-use crate::domain::repositories::MediaRepository;
-use crate::domain::value_objects::{ContentHash, MediaType, ProcessingStatus};
+use sqlx::{PgConnection, PgPool, Row};
+use std::time::SystemTime;
+
+use crate::domain::entities::{
+    IngredientId, Media, MediaAssociation, MediaId, MediaVariant, RecipeId, StepId, UnsavedMedia,
+    UserId, PENDING_UPLOAD_PATH,
+};
+use crate::domain::events::DomainEvent;
+use crate::domain::repositories::{EventSink, MediaChange, MediaRepository, Recorded, SaveOutcome};
+use crate::domain::value_objects::{
+    CapacityDimension, CapacityGrowth, ContentHash, MediaSearch, MediaSort, MediaSortField,
+    MediaSortKey, MediaTag, MediaType, ProcessingStatus, SortOrder, StorageUsage,
+};
+use crate::infrastructure::persistence::pagination::{
+    CursorError, KeysetPageRequest, Page, PageRequest,
+};
 
 /// `PostgreSQL` implementation of `MediaRepository`
 #[derive(Clone)]
@@ -20,45 +30,125 @@ impl PostgreSqlMediaRepository {
     pub fn new(pool: PgPool) -> Self {
         Self { pool }
     }
+
+    /// Page through a user's media in a non-default sort order using keyset cursors
+    async fn find_by_user_sorted(
+        &self,
+        user_id: UserId,
+        cursor: Option<&str>,
+        limit: u32,
+        status_filter: Option<ProcessingStatus>,
+        tags: &[MediaTag],
+        sort: MediaSort,
+    ) -> Result<(Vec<Media>, Option<String>, bool), AppError> {
+        let page_request = KeysetPageRequest::<MediaSortKey>::new(
+            sort.name(),
+            sort.is_descending(),
+            cursor,
+            limit,
+        )?;
+
+        // Filenames compare byte-wise so the order doesn't depend on the database locale
+        let column = match sort.field {
+            None => "media_id",
+            Some(MediaSortField::UploadedAt) => "created_at",
+            Some(MediaSortField::FileSize) => "file_size",
+            Some(MediaSortField::Filename) => r#"original_filename COLLATE "C""#,
+        };
+        let (direction, comparison) = match sort.order {
+            SortOrder::Asc => ("ASC", ">"),
+            SortOrder::Desc => ("DESC", "<"),
+        };
+
+        let mut query = sqlx::QueryBuilder::new(
+            r"
+            SELECT media_id, user_id, media_type, media_path, file_size, content_hash,
+                   original_filename, processing_status, created_at, updated_at,
+                   variants::text AS variants, processing_error, corrupted_at, tenant,
+                   ARRAY(SELECT t.tag FROM recipe_manager.media_tags t
+                         WHERE t.media_id = media.media_id ORDER BY t.tag) AS tags
+            FROM recipe_manager.media
+            WHERE user_id = ",
+        );
+        query.push_bind(user_id.as_uuid());
+
+        if let Some(status) = status_filter {
+            query.push(" AND processing_status = ").push_bind(status.to_string());
+        }
+        push_tag_filter(&mut query, tags);
+
+        // Rows after the cursor in (sort value, media_id) order
+        if let Some((key, id)) = page_request.after.clone() {
+            if sort.field.is_none() {
+                query.push(format!(" AND media_id {comparison} ")).push_bind(id);
+            } else {
+                query.push(format!(" AND ({column}, media_id) {comparison} ("));
+                match (sort.field, key) {
+                    (Some(MediaSortField::UploadedAt), MediaSortKey::Int(micros)) => {
+                        let uploaded_at = DateTime::<Utc>::from_timestamp_micros(micros)
+                            .ok_or(CursorError::InvalidData)?;
+                        query.push_bind(uploaded_at)
+                    }
+                    (Some(MediaSortField::FileSize), MediaSortKey::Int(size)) => {
+                        query.push_bind(size)
+                    }
+                    (Some(MediaSortField::Filename), MediaSortKey::Text(filename)) => {
+                        query.push_bind(filename)
+                    }
+                    _ => return Err(CursorError::InvalidData.into()),
+                };
+                query.push(", ").push_bind(id).push(")");
+            }
+        }
+
+        query.push(format!(" ORDER BY {column} {direction}, media_id {direction} LIMIT "));
+        query.push_bind(page_request.fetch_limit());
+
+        let rows = query.build().fetch_all(&self.pool).await.map_err(AppError::from)?;
+
+        let media_list = rows.iter().map(map_row_to_media).collect::<Result<Vec<_>, _>>()?;
+        let (media_list, next_cursor, has_more) =
+            Page::from_overfetched_keyset(media_list, &page_request, |media| {
+                (sort.key(media), media.id.as_i64())
+            })
+            .into_parts();
+
+        tracing::debug!(
+            "Sorted ({}) query returned {} items, has_more: {}",
+            sort.name(),
+            media_list.len(),
+            has_more
+        );
+
+        Ok((media_list, next_cursor, has_more))
+    }
 }
 
 #[async_trait]
 impl MediaRepository for PostgreSqlMediaRepository {
     type Error = AppError;
 
-    async fn save(&self, media: &Media) -> Result<MediaId, Self::Error> {
-        let user_id = media.uploaded_by.as_uuid();
-        let media_type_str = media.media_type.mime_type();
-        let content_hash_str = media.content_hash.as_str();
-        let processing_status_str = media.processing_status.to_string();
+    async fn save(&self, media: &UnsavedMedia) -> Result<MediaId, Self::Error> {
+        self.save_or_reuse(media).await.map(SaveOutcome::media_id)
+    }
 
-        // Convert SystemTime to chrono DateTime for database compatibility
-        let uploaded_at: DateTime<Utc> = media.uploaded_at.into();
-        let updated_at: DateTime<Utc> = media.updated_at.into();
+    async fn save_or_reuse(&self, media: &UnsavedMedia) -> Result<SaveOutcome, Self::Error> {
+        let mut conn = self.pool.acquire().await.map_err(AppError::from)?;
+        insert_or_reuse(&mut conn, media).await
+    }
 
-        let row = sqlx::query(
-            r"
-            INSERT INTO recipe_manager.media
-            (user_id, media_type, media_path, file_size, content_hash, original_filename, processing_status, created_at, updated_at)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
-            RETURNING media_id
-            ",
-        )
-        .bind(user_id)
-        .bind(media_type_str)
-        .bind(&media.media_path)
-        .bind(media.file_size as i64)
-        .bind(content_hash_str)
-        .bind(&media.original_filename)
-        .bind(processing_status_str)
-        .bind(uploaded_at)
-        .bind(updated_at)
-        .fetch_one(&self.pool)
-        .await
-        .map_err(AppError::from)?;
+    async fn save_or_reuse_announced(
+        &self,
+        media: &UnsavedMedia,
+    ) -> Result<Recorded<SaveOutcome>, Self::Error> {
+        let mut tx = self.pool.begin().await.map_err(AppError::from)?;
+        let outcome = insert_or_reuse(&mut tx, media).await?;
+        if let SaveOutcome::Created(media_id) = outcome {
+            record_events(&mut tx, &[DomainEvent::uploaded(media_id, media)]).await?;
+        }
+        tx.commit().await.map_err(AppError::from)?;
 
-        let media_id = MediaId::new(row.get("media_id"));
-        Ok(media_id)
+        Ok(Recorded { outcome, events_recorded: true })
     }
 
     async fn find_by_id(&self, id: MediaId) -> Result<Option<Media>, Self::Error> {
@@ -67,7 +157,10 @@ impl MediaRepository for PostgreSqlMediaRepository {
         let row = sqlx::query(
             r"
             SELECT media_id, user_id, media_type, media_path, file_size, content_hash,
-                   original_filename, processing_status, created_at, updated_at
+                   original_filename, processing_status, created_at, updated_at,
+                   variants::text AS variants, processing_error, corrupted_at, tenant,
+                   ARRAY(SELECT t.tag FROM recipe_manager.media_tags t
+                         WHERE t.media_id = media.media_id ORDER BY t.tag) AS tags
             FROM recipe_manager.media
             WHERE media_id = $1
             ",
@@ -86,18 +179,50 @@ impl MediaRepository for PostgreSqlMediaRepository {
         }
     }
 
-    async fn find_by_content_hash(&self, hash: &ContentHash) -> Result<Option<Media>, Self::Error> {
+    async fn find_by_ids(&self, ids: &[MediaId]) -> Result<Vec<Media>, Self::Error> {
+        let media_ids: Vec<i64> = ids.iter().map(MediaId::as_i64).collect();
+
+        let rows = sqlx::query(
+            r"
+            SELECT media_id, user_id, media_type, media_path, file_size, content_hash,
+                   original_filename, processing_status, created_at, updated_at,
+                   variants::text AS variants, processing_error, corrupted_at, tenant,
+                   ARRAY(SELECT t.tag FROM recipe_manager.media_tags t
+                         WHERE t.media_id = media.media_id ORDER BY t.tag) AS tags
+            FROM recipe_manager.media
+            WHERE media_id = ANY($1)
+            ",
+        )
+        .bind(media_ids)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(AppError::from)?;
+
+        rows.iter().map(map_row_to_media).collect()
+    }
+
+    async fn find_by_content_hash(
+        &self,
+        hash: &ContentHash,
+        owner: Option<UserId>,
+    ) -> Result<Option<Media>, Self::Error> {
         let hash_str = hash.as_str();
 
         let row = sqlx::query(
             r"
             SELECT media_id, user_id, media_type, media_path, file_size, content_hash,
-                   original_filename, processing_status, created_at, updated_at
+                   original_filename, processing_status, created_at, updated_at,
+                   variants::text AS variants, processing_error, corrupted_at, tenant,
+                   ARRAY(SELECT t.tag FROM recipe_manager.media_tags t
+                         WHERE t.media_id = media.media_id ORDER BY t.tag) AS tags
             FROM recipe_manager.media
-            WHERE content_hash = $1
+            WHERE content_hash = $1 AND ($2::uuid IS NULL OR user_id = $2)
+            ORDER BY media_id
+            LIMIT 1
             ",
         )
         .bind(hash_str)
+        .bind(owner.as_ref().map(UserId::as_uuid))
         .fetch_optional(&self.pool)
         .await
         .map_err(AppError::from)?;
@@ -117,7 +242,10 @@ impl MediaRepository for PostgreSqlMediaRepository {
         let rows = sqlx::query(
             r"
             SELECT media_id, user_id, media_type, media_path, file_size, content_hash,
-                   original_filename, processing_status, created_at, updated_at
+                   original_filename, processing_status, created_at, updated_at,
+                   variants::text AS variants, processing_error, corrupted_at, tenant,
+                   ARRAY(SELECT t.tag FROM recipe_manager.media_tags t
+                         WHERE t.media_id = media.media_id ORDER BY t.tag) AS tags
             FROM recipe_manager.media
             WHERE user_id = $1
             ORDER BY created_at DESC
@@ -137,51 +265,269 @@ impl MediaRepository for PostgreSqlMediaRepository {
         Ok(media_list)
     }
 
+    async fn find_after(
+        &self,
+        after: Option<MediaId>,
+        limit: u32,
+    ) -> Result<Vec<Media>, Self::Error> {
+        let rows = sqlx::query(
+            r"
+            SELECT media_id, user_id, media_type, media_path, file_size, content_hash,
+                   original_filename, processing_status, created_at, updated_at,
+                   variants::text AS variants, processing_error, corrupted_at, tenant,
+                   ARRAY(SELECT t.tag FROM recipe_manager.media_tags t
+                         WHERE t.media_id = media.media_id ORDER BY t.tag) AS tags
+            FROM recipe_manager.media
+            WHERE media_id > $1
+            ORDER BY media_id
+            LIMIT $2
+            ",
+        )
+        .bind(after.map_or(0, |id| id.as_i64()))
+        .bind(i64::from(limit))
+        .fetch_all(&self.pool)
+        .await
+        .map_err(AppError::from)?;
+
+        rows.iter().map(map_row_to_media).collect()
+    }
+
+    async fn find_unprocessed_before(
+        &self,
+        updated_before: SystemTime,
+        limit: u32,
+    ) -> Result<Vec<Media>, Self::Error> {
+        let updated_before: DateTime<Utc> = updated_before.into();
+        let rows = sqlx::query(
+            r"
+            SELECT media_id, user_id, media_type, media_path, file_size, content_hash,
+                   original_filename, processing_status, created_at, updated_at,
+                   variants::text AS variants, processing_error, corrupted_at, tenant,
+                   ARRAY(SELECT t.tag FROM recipe_manager.media_tags t
+                         WHERE t.media_id = media.media_id ORDER BY t.tag) AS tags
+            FROM recipe_manager.media
+            WHERE processing_status IN ($1, $2)
+              AND updated_at < $3
+              AND media_path <> $4
+            ORDER BY updated_at
+            LIMIT $5
+            ",
+        )
+        .bind(ProcessingStatus::Pending.to_string())
+        .bind(ProcessingStatus::Processing.to_string())
+        .bind(updated_before)
+        .bind(PENDING_UPLOAD_PATH)
+        .bind(i64::from(limit))
+        .fetch_all(&self.pool)
+        .await
+        .map_err(AppError::from)?;
+
+        rows.iter().map(map_row_to_media).collect()
+    }
+
+    async fn usage_by_user(&self, user_id: UserId) -> Result<StorageUsage, Self::Error> {
+        let row = sqlx::query(
+            r"
+            SELECT COALESCE(SUM(file_size), 0)::BIGINT AS bytes, COUNT(*) AS files
+            FROM recipe_manager.media
+            WHERE user_id = $1
+            ",
+        )
+        .bind(user_id.as_uuid())
+        .fetch_one(&self.pool)
+        .await
+        .map_err(AppError::from)?;
+
+        Ok(StorageUsage {
+            bytes: row.get::<i64, _>("bytes") as u64,
+            files: row.get::<i64, _>("files") as u64,
+        })
+    }
+
+    async fn capacity_growth(
+        &self,
+        dimension: CapacityDimension,
+        since: SystemTime,
+    ) -> Result<Vec<CapacityGrowth>, Self::Error> {
+        // Constant per dimension, so never user input
+        let key = match dimension {
+            CapacityDimension::MediaType => "media_type",
+            CapacityDimension::Tenant => "COALESCE(tenant, '')",
+        };
+        let since: DateTime<Utc> = since.into();
+        let rows = sqlx::query(&format!(
+            r"
+            WITH weekly AS (
+                SELECT date_trunc('week', created_at AT TIME ZONE 'UTC') AT TIME ZONE 'UTC' AS week_start,
+                       {key} AS key,
+                       COUNT(*) AS media_added,
+                       SUM(file_size)::BIGINT AS bytes_added
+                FROM recipe_manager.media
+                GROUP BY 1, 2
+            ), running AS (
+                SELECT week_start, key, media_added, bytes_added,
+                       SUM(media_added) OVER by_key AS total_media,
+                       SUM(bytes_added) OVER by_key AS total_bytes
+                FROM weekly
+                WINDOW by_key AS (PARTITION BY key ORDER BY week_start)
+            )
+            SELECT week_start, key, media_added, bytes_added,
+                   total_media::BIGINT AS total_media, total_bytes::BIGINT AS total_bytes
+            FROM running
+            WHERE week_start >= $1
+            ORDER BY week_start, key
+            "
+        ))
+        .bind(since)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(AppError::from)?;
+
+        Ok(rows
+            .iter()
+            .map(|row| CapacityGrowth {
+                week_start: row.get::<DateTime<Utc>, _>("week_start").into(),
+                key: row.get("key"),
+                media_added: row.get::<i64, _>("media_added") as u64,
+                bytes_added: row.get::<i64, _>("bytes_added") as u64,
+                total_media: row.get::<i64, _>("total_media") as u64,
+                total_bytes: row.get::<i64, _>("total_bytes") as u64,
+            })
+            .collect())
+    }
+
     async fn update(&self, media: &Media) -> Result<(), Self::Error> {
-        let media_id = media.id.as_i64();
-        let media_type_str = media.media_type.mime_type();
-        let content_hash_str = media.content_hash.as_str();
-        let processing_status_str = media.processing_status.to_string();
-        let updated_at: DateTime<Utc> = media.updated_at.into();
+        let mut conn = self.pool.acquire().await.map_err(AppError::from)?;
+        update_media(&mut conn, media).await
+    }
+
+    async fn set_corrupted_at(
+        &self,
+        media_id: MediaId,
+        corrupted_at: Option<SystemTime>,
+    ) -> Result<bool, Self::Error> {
+        let corrupted_at: Option<DateTime<Utc>> = corrupted_at.map(Into::into);
+
+        let result =
+            sqlx::query("UPDATE recipe_manager.media SET corrupted_at = $2 WHERE media_id = $1")
+                .bind(media_id.as_i64())
+                .bind(corrupted_at)
+                .execute(&self.pool)
+                .await
+                .map_err(AppError::from)?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn set_tags(&self, media_id: MediaId, tags: &[MediaTag]) -> Result<bool, Self::Error> {
+        let mut tx = self.pool.begin().await.map_err(AppError::from)?;
+        let updated = replace_tags(&mut tx, media_id, tags).await?;
+        if updated {
+            tx.commit().await.map_err(AppError::from)?;
+        }
+        Ok(updated)
+    }
 
-        sqlx::query(
+    async fn add_variant(
+        &self,
+        media_id: MediaId,
+        variant: &MediaVariant,
+    ) -> Result<bool, Self::Error> {
+        let variant_json = serde_json::to_string(variant).map_err(|e| AppError::Internal {
+            message: format!("Failed to serialize variant: {e}"),
+        })?;
+
+        // A concurrent append re-evaluates the name check against the updated row
+        let result = sqlx::query(
             r"
             UPDATE recipe_manager.media
-            SET media_type = $2, media_path = $3, file_size = $4, content_hash = $5,
-                original_filename = $6, processing_status = $7, updated_at = $8
+            SET variants = COALESCE(variants, '[]'::jsonb) || jsonb_build_array($2::jsonb)
             WHERE media_id = $1
+              AND NOT COALESCE(variants, '[]'::jsonb)
+                      @> jsonb_build_array(jsonb_build_object('name', $3::text))
             ",
         )
-        .bind(media_id)
-        .bind(media_type_str)
-        .bind(&media.media_path)
-        .bind(media.file_size as i64)
-        .bind(content_hash_str)
-        .bind(&media.original_filename)
-        .bind(processing_status_str)
-        .bind(updated_at)
+        .bind(media_id.as_i64())
+        .bind(variant_json)
+        .bind(&variant.name)
         .execute(&self.pool)
         .await
         .map_err(AppError::from)?;
 
-        Ok(())
+        Ok(result.rows_affected() > 0)
     }
 
     async fn delete(&self, id: MediaId) -> Result<bool, Self::Error> {
-        let media_id = id.as_i64();
+        let mut conn = self.pool.acquire().await.map_err(AppError::from)?;
+        delete_media(&mut conn, id).await
+    }
 
-        let result = sqlx::query(
+    async fn apply(
+        &self,
+        change: MediaChange<'_>,
+        events: &[DomainEvent],
+    ) -> Result<Recorded<bool>, Self::Error> {
+        let mut tx = self.pool.begin().await.map_err(AppError::from)?;
+        let applied = match change {
+            MediaChange::Update(media) => update_media(&mut tx, media).await.map(|()| true),
+            MediaChange::SetTags(media_id, tags) => replace_tags(&mut tx, media_id, tags).await,
+            MediaChange::Delete(media_id) => delete_media(&mut tx, media_id).await,
+            MediaChange::AddAssociation(media_id, association) => {
+                insert_association(&mut tx, media_id, association).await
+            }
+            MediaChange::RemoveAssociation(media_id, association) => {
+                delete_association(&mut tx, media_id, association).await
+            }
+        }?;
+        if applied {
+            record_events(&mut tx, events).await?;
+            tx.commit().await.map_err(AppError::from)?;
+        }
+
+        Ok(Recorded { outcome: applied, events_recorded: true })
+    }
+
+    async fn relay_events(&self, limit: u32, relay: &EventSink) -> Result<usize, Self::Error> {
+        let mut tx = self.pool.begin().await.map_err(AppError::from)?;
+
+        // Skipping locked rows lets every instance relay at once without
+        // handing out the same event twice
+        let rows = sqlx::query(
             r"
-            DELETE FROM recipe_manager.media
-            WHERE media_id = $1
+            SELECT event_id, event::TEXT AS event
+            FROM recipe_manager.event_outbox
+            ORDER BY event_id
+            LIMIT $1
+            FOR UPDATE SKIP LOCKED
             ",
         )
-        .bind(media_id)
-        .execute(&self.pool)
+        .bind(i64::from(limit))
+        .fetch_all(&mut *tx)
         .await
         .map_err(AppError::from)?;
+        if rows.is_empty() {
+            return Ok(0);
+        }
 
-        Ok(result.rows_affected() > 0)
+        let mut event_ids = Vec::with_capacity(rows.len());
+        for row in &rows {
+            let event_id: i64 = row.get("event_id");
+            match serde_json::from_str::<DomainEvent>(row.get("event")) {
+                Ok(event) => relay(&event),
+                // Retrying would not help; dropping it keeps the events behind it flowing
+                Err(e) => tracing::error!("Dropping undecodable outbox event {}: {}", event_id, e),
+            }
+            event_ids.push(event_id);
+        }
+
+        sqlx::query("DELETE FROM recipe_manager.event_outbox WHERE event_id = ANY($1)")
+            .bind(&event_ids)
+            .execute(&mut *tx)
+            .await
+            .map_err(AppError::from)?;
+        tx.commit().await.map_err(AppError::from)?;
+
+        Ok(rows.len())
     }
 
     async fn exists_by_content_hash(&self, hash: &ContentHash) -> Result<bool, Self::Error> {
@@ -282,39 +628,155 @@ impl MediaRepository for PostgreSqlMediaRepository {
         Ok(media_ids)
     }
 
+    async fn add_association(
+        &self,
+        media_id: MediaId,
+        association: MediaAssociation,
+    ) -> Result<bool, Self::Error> {
+        let mut conn = self.pool.acquire().await.map_err(AppError::from)?;
+        insert_association(&mut conn, media_id, association).await
+    }
+
+    async fn remove_association(
+        &self,
+        media_id: MediaId,
+        association: MediaAssociation,
+    ) -> Result<bool, Self::Error> {
+        let mut conn = self.pool.acquire().await.map_err(AppError::from)?;
+        delete_association(&mut conn, media_id, association).await
+    }
+
+    async fn find_media_by_recipe(
+        &self,
+        recipe_id: RecipeId,
+        cursor: Option<String>,
+        limit: u32,
+    ) -> Result<(Vec<Media>, Option<String>, bool), Self::Error> {
+        let page_request = PageRequest::new(cursor.as_deref(), limit)?;
+
+        let rows = sqlx::query(
+            r"
+            SELECT m.media_id, m.user_id, m.media_type, m.media_path, m.file_size, m.content_hash,
+                   m.original_filename, m.processing_status, m.created_at, m.updated_at,
+                   m.variants::text AS variants, m.processing_error, m.corrupted_at, m.tenant,
+                   ARRAY(SELECT t.tag FROM recipe_manager.media_tags t
+                         WHERE t.media_id = m.media_id ORDER BY t.tag) AS tags
+            FROM recipe_manager.recipe_media rm
+            JOIN recipe_manager.media m ON m.media_id = rm.media_id
+            WHERE rm.recipe_id = $1 AND ($2::BIGINT IS NULL OR m.media_id > $2)
+            ORDER BY m.media_id
+            LIMIT $3
+            ",
+        )
+        .bind(recipe_id.as_i64())
+        .bind(page_request.after)
+        .bind(page_request.fetch_limit())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(AppError::from)?;
+
+        let media = rows.iter().map(map_row_to_media).collect::<Result<Vec<_>, _>>()?;
+        Ok(Page::from_overfetched(media, page_request, |m| m.id.as_i64()).into_parts())
+    }
+
+    async fn find_media_by_recipe_ingredient(
+        &self,
+        recipe_id: RecipeId,
+        ingredient_id: IngredientId,
+        cursor: Option<String>,
+        limit: u32,
+    ) -> Result<(Vec<Media>, Option<String>, bool), Self::Error> {
+        let page_request = PageRequest::new(cursor.as_deref(), limit)?;
+
+        let rows = sqlx::query(
+            r"
+            SELECT m.media_id, m.user_id, m.media_type, m.media_path, m.file_size, m.content_hash,
+                   m.original_filename, m.processing_status, m.created_at, m.updated_at,
+                   m.variants::text AS variants, m.processing_error, m.corrupted_at, m.tenant,
+                   ARRAY(SELECT t.tag FROM recipe_manager.media_tags t
+                         WHERE t.media_id = m.media_id ORDER BY t.tag) AS tags
+            FROM recipe_manager.ingredient_media im
+            JOIN recipe_manager.media m ON m.media_id = im.media_id
+            WHERE im.recipe_id = $1 AND im.ingredient_id = $2
+              AND ($3::BIGINT IS NULL OR m.media_id > $3)
+            ORDER BY m.media_id
+            LIMIT $4
+            ",
+        )
+        .bind(recipe_id.as_i64())
+        .bind(ingredient_id.as_i64())
+        .bind(page_request.after)
+        .bind(page_request.fetch_limit())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(AppError::from)?;
+
+        let media = rows.iter().map(map_row_to_media).collect::<Result<Vec<_>, _>>()?;
+        Ok(Page::from_overfetched(media, page_request, |m| m.id.as_i64()).into_parts())
+    }
+
+    async fn find_media_by_recipe_step(
+        &self,
+        recipe_id: RecipeId,
+        step_id: StepId,
+        cursor: Option<String>,
+        limit: u32,
+    ) -> Result<(Vec<Media>, Option<String>, bool), Self::Error> {
+        let page_request = PageRequest::new(cursor.as_deref(), limit)?;
+
+        let rows = sqlx::query(
+            r"
+            SELECT m.media_id, m.user_id, m.media_type, m.media_path, m.file_size, m.content_hash,
+                   m.original_filename, m.processing_status, m.created_at, m.updated_at,
+                   m.variants::text AS variants, m.processing_error, m.corrupted_at, m.tenant,
+                   ARRAY(SELECT t.tag FROM recipe_manager.media_tags t
+                         WHERE t.media_id = m.media_id ORDER BY t.tag) AS tags
+            FROM recipe_manager.step_media sm
+            JOIN recipe_manager.media m ON m.media_id = sm.media_id
+            WHERE sm.recipe_id = $1 AND sm.step_id = $2
+              AND ($3::BIGINT IS NULL OR m.media_id > $3)
+            ORDER BY m.media_id
+            LIMIT $4
+            ",
+        )
+        .bind(recipe_id.as_i64())
+        .bind(step_id.as_i64())
+        .bind(page_request.after)
+        .bind(page_request.fetch_limit())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(AppError::from)?;
+
+        let media = rows.iter().map(map_row_to_media).collect::<Result<Vec<_>, _>>()?;
+        Ok(Page::from_overfetched(media, page_request, |m| m.id.as_i64()).into_parts())
+    }
+
     async fn find_by_user_paginated(
         &self,
         user_id: UserId,
         cursor: Option<String>,
         limit: u32,
         status_filter: Option<ProcessingStatus>,
+        tags: &[MediaTag],
+        sort: MediaSort,
     ) -> Result<(Vec<Media>, Option<String>, bool), Self::Error> {
+        if !sort.is_default() {
+            return self
+                .find_by_user_sorted(user_id, cursor.as_deref(), limit, status_filter, tags, sort)
+                .await;
+        }
+
         let user_uuid = user_id.as_uuid();
 
-        // Validate and constrain limit
-        let limit = limit.clamp(1, 100);
-        let fetch_limit = i64::from(limit + 1); // Fetch one extra to check if there's a next page
-
-        // Decode cursor to get the last media_id
-        let cursor_media_id = match cursor {
-            Some(cursor_str) => {
-                let decoded = STANDARD.decode(&cursor_str).map_err(|_| AppError::BadRequest {
-                    message: "Invalid cursor format".to_string(),
-                })?;
-                let cursor_data = String::from_utf8(decoded).map_err(|_| AppError::BadRequest {
-                    message: "Invalid cursor encoding".to_string(),
-                })?;
-                Some(cursor_data.parse::<i64>().map_err(|_| AppError::BadRequest {
-                    message: "Invalid cursor data".to_string(),
-                })?)
-            }
-            None => None,
-        };
+        let page_request = PageRequest::new(cursor.as_deref(), limit)?;
 
         // Build query with optional status filter and cursor pagination
         let mut query_str = r"
             SELECT media_id, user_id, media_type, media_path, file_size, content_hash,
-                   original_filename, processing_status, created_at, updated_at
+                   original_filename, processing_status, created_at, updated_at,
+                   variants::text AS variants, processing_error, corrupted_at, tenant,
+                   ARRAY(SELECT t.tag FROM recipe_manager.media_tags t
+                         WHERE t.media_id = media.media_id ORDER BY t.tag) AS tags
             FROM recipe_manager.media
             WHERE user_id = $1"
             .to_string();
@@ -328,8 +790,22 @@ impl MediaRepository for PostgreSqlMediaRepository {
             bind_index += 1;
         }
 
+        // Add tag filter if provided
+        if !tags.is_empty() {
+            use std::fmt::Write;
+            write!(
+                &mut query_str,
+                " AND media_id IN (SELECT media_id FROM recipe_manager.media_tags \
+                 WHERE tag = ANY(${}) GROUP BY media_id HAVING COUNT(*) = ${})",
+                bind_index,
+                bind_index + 1
+            )
+            .unwrap();
+            bind_index += 2;
+        }
+
         // Add cursor condition for pagination
-        if cursor_media_id.is_some() {
+        if page_request.after.is_some() {
             use std::fmt::Write;
             write!(&mut query_str, " AND media_id > ${bind_index}").unwrap();
             bind_index += 1;
@@ -347,39 +823,99 @@ impl MediaRepository for PostgreSqlMediaRepository {
             query = query.bind(status.to_string());
         }
 
+        // Bind tag filter if provided
+        if !tags.is_empty() {
+            query = query.bind(tag_strings(tags)).bind(tags.len() as i64);
+        }
+
         // Bind cursor media_id if provided
-        if let Some(id) = cursor_media_id {
+        if let Some(id) = page_request.after {
             query = query.bind(id);
         }
 
-        // Bind limit
-        query = query.bind(fetch_limit);
+        // Bind limit, fetching one extra row to check if there's a next page
+        query = query.bind(page_request.fetch_limit());
 
         let rows = query.fetch_all(&self.pool).await.map_err(AppError::from)?;
 
-        // Check if we have more items than requested (indicates next page exists)
-        let has_more = rows.len() > limit as usize;
+        let media_list = rows.iter().map(map_row_to_media).collect::<Result<Vec<_>, _>>()?;
+        let (media_list, next_cursor, has_more) =
+            Page::from_overfetched(media_list, page_request, |media| media.id.as_i64())
+                .into_parts();
+
+        tracing::debug!(
+            "Paginated query returned {} items, has_more: {}, cursor: {:?}",
+            media_list.len(),
+            has_more,
+            next_cursor
+        );
+
+        Ok((media_list, next_cursor, has_more))
+    }
 
-        // Take only the requested number of items
-        let media_rows = if has_more { &rows[..limit as usize] } else { &rows };
+    async fn search_by_user(
+        &self,
+        user_id: UserId,
+        search: &MediaSearch,
+        cursor: Option<String>,
+        limit: u32,
+    ) -> Result<(Vec<Media>, Option<String>, bool), Self::Error> {
+        let page_request = PageRequest::new(cursor.as_deref(), limit)?;
 
-        // Convert rows to Media entities
-        let mut media_list = Vec::new();
-        for row in media_rows {
-            let media = map_row_to_media(row)?;
-            media_list.push(media);
+        let mut query = sqlx::QueryBuilder::new(
+            r"
+            SELECT media_id, user_id, media_type, media_path, file_size, content_hash,
+                   original_filename, processing_status, created_at, updated_at,
+                   variants::text AS variants, processing_error, corrupted_at, tenant,
+                   ARRAY(SELECT t.tag FROM recipe_manager.media_tags t
+                         WHERE t.media_id = media.media_id ORDER BY t.tag) AS tags
+            FROM recipe_manager.media
+            WHERE user_id = ",
+        );
+        query.push_bind(user_id.as_uuid());
+
+        // Served by the trigram index on original_filename
+        if let Some(filename) = &search.filename {
+            query
+                .push(" AND original_filename ILIKE ")
+                .push_bind(format!("%{}%", escape_like(filename)))
+                .push(r" ESCAPE '\'");
+        }
+        if let Some(family) = &search.type_family {
+            query.push(" AND media_type LIKE ").push_bind(format!("{}/%", escape_like(family)));
+        }
+        if let Some(min_size) = search.min_size {
+            query.push(" AND file_size >= ").push_bind(i64::try_from(min_size).unwrap_or(i64::MAX));
+        }
+        if let Some(max_size) = search.max_size {
+            query.push(" AND file_size <= ").push_bind(i64::try_from(max_size).unwrap_or(i64::MAX));
+        }
+        if let Some(since) = search.uploaded_since {
+            query.push(" AND created_at >= ").push_bind(DateTime::<Utc>::from(since));
+        }
+        if let Some(before) = search.uploaded_before {
+            query.push(" AND created_at < ").push_bind(DateTime::<Utc>::from(before));
+        }
+        if let Some(status) = &search.status {
+            query.push(" AND processing_status = ").push_bind(status.to_string());
+        }
+        push_tag_filter(&mut query, &search.tags);
+        if let Some(id) = page_request.after {
+            query.push(" AND media_id > ").push_bind(id);
         }
 
-        // Generate next cursor if there are more items
-        let next_cursor = if has_more && !media_list.is_empty() {
-            let last_media_id = media_list.last().unwrap().id.as_i64();
-            Some(STANDARD.encode(last_media_id.to_string().as_bytes()))
-        } else {
-            None
-        };
+        // Order by media_id for consistent pagination, fetching one extra row
+        query.push(" ORDER BY media_id ASC LIMIT ").push_bind(page_request.fetch_limit());
+
+        let rows = query.build().fetch_all(&self.pool).await.map_err(AppError::from)?;
+
+        let media_list = rows.iter().map(map_row_to_media).collect::<Result<Vec<_>, _>>()?;
+        let (media_list, next_cursor, has_more) =
+            Page::from_overfetched(media_list, page_request, |media| media.id.as_i64())
+                .into_parts();
 
         tracing::debug!(
-            "Paginated query returned {} items, has_more: {}, cursor: {:?}",
+            "Search query returned {} items, has_more: {}, cursor: {:?}",
             media_list.len(),
             has_more,
             next_cursor
@@ -399,6 +935,301 @@ impl MediaRepository for PostgreSqlMediaRepository {
             .map_err(|e| AppError::Database { message: format!("Health check failed: {e}") })?;
         Ok(())
     }
+
+    async fn migration_version(&self) -> Result<Option<i64>, Self::Error> {
+        super::migration_version(&self.pool).await.map_err(|e| AppError::Database {
+            message: format!("Failed to read migration version: {e}"),
+        })
+    }
+}
+
+/// Store `events` in the outbox, to be relayed once the transaction commits
+async fn record_events(conn: &mut PgConnection, events: &[DomainEvent]) -> Result<(), AppError> {
+    if events.is_empty() {
+        return Ok(());
+    }
+    let events =
+        events.iter().map(serde_json::to_string).collect::<Result<Vec<_>, _>>().map_err(|e| {
+            AppError::Internal { message: format!("Failed to serialize event: {e}") }
+        })?;
+
+    sqlx::query(
+        r"
+        INSERT INTO recipe_manager.event_outbox (event)
+        SELECT event::JSONB FROM UNNEST($1::TEXT[]) WITH ORDINALITY AS e(event, position)
+        ORDER BY position
+        ",
+    )
+    .bind(events)
+    .execute(&mut *conn)
+    .await
+    .map_err(AppError::from)?;
+
+    Ok(())
+}
+
+/// Insert `media`, or find the row its uploader already stores its content in
+async fn insert_or_reuse(
+    conn: &mut PgConnection,
+    media: &UnsavedMedia,
+) -> Result<SaveOutcome, AppError> {
+    let user_id = media.uploaded_by.as_uuid();
+    let media_type_str = media.media_type.mime_type();
+    let content_hash_str = media.content_hash.as_str();
+    let processing_status_str = media.processing_status.to_string();
+
+    // Convert SystemTime to chrono DateTime for database compatibility
+    let uploaded_at: DateTime<Utc> = media.uploaded_at.into();
+    let updated_at: DateTime<Utc> = media.updated_at.into();
+
+    // Content is deduplicated per owner, so another user's copy is never handed out.
+    // The no-op DO UPDATE makes RETURNING yield the existing row on conflict, and
+    // xmax = 0 only holds for fresh inserts.
+    let row = sqlx::query(
+        r"
+        INSERT INTO recipe_manager.media
+        (user_id, media_type, media_path, file_size, content_hash, original_filename, processing_status, created_at, updated_at, tenant)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+        ON CONFLICT (content_hash, user_id) DO UPDATE
+            SET content_hash = EXCLUDED.content_hash
+        RETURNING media_id, (xmax = 0) AS inserted
+        ",
+    )
+    .bind(user_id)
+    .bind(media_type_str)
+    .bind(&media.media_path)
+    .bind(media.file_size as i64)
+    .bind(content_hash_str)
+    .bind(&media.original_filename)
+    .bind(processing_status_str)
+    .bind(uploaded_at)
+    .bind(updated_at)
+    .bind(&media.tenant)
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(AppError::from)?;
+
+    let media_id = MediaId::new(row.get("media_id"));
+    if row.get::<bool, _>("inserted") {
+        Ok(SaveOutcome::Created(media_id))
+    } else {
+        Ok(SaveOutcome::Reused(media_id))
+    }
+}
+
+/// Write every field of `media` to its row
+async fn update_media(conn: &mut PgConnection, media: &Media) -> Result<(), AppError> {
+    let media_id = media.id.as_i64();
+    let media_type_str = media.media_type.mime_type();
+    let content_hash_str = media.content_hash.as_str();
+    let processing_status_str = media.processing_status.to_string();
+    let updated_at: DateTime<Utc> = media.updated_at.into();
+    let variants = variants_to_json(media)?;
+
+    sqlx::query(
+        r"
+        UPDATE recipe_manager.media
+        SET media_type = $2, media_path = $3, file_size = $4, content_hash = $5,
+            original_filename = $6, processing_status = $7, updated_at = $8,
+            variants = $9::jsonb, processing_error = $10, corrupted_at = $11
+        WHERE media_id = $1
+        ",
+    )
+    .bind(media_id)
+    .bind(media_type_str)
+    .bind(&media.media_path)
+    .bind(media.file_size as i64)
+    .bind(content_hash_str)
+    .bind(&media.original_filename)
+    .bind(processing_status_str)
+    .bind(updated_at)
+    .bind(variants)
+    .bind(&media.processing_error)
+    .bind(media.corrupted_at.map(DateTime::<Utc>::from))
+    .execute(&mut *conn)
+    .await
+    .map_err(AppError::from)?;
+
+    Ok(())
+}
+
+/// Replace the tags of a media row, within a transaction
+async fn replace_tags(
+    conn: &mut PgConnection,
+    media_id: MediaId,
+    tags: &[MediaTag],
+) -> Result<bool, AppError> {
+    // Lock the media row so concurrent replacements apply one after the other
+    let exists = sqlx::query(
+        r"
+        SELECT 1 FROM recipe_manager.media WHERE media_id = $1 FOR UPDATE
+        ",
+    )
+    .bind(media_id.as_i64())
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(AppError::from)?
+    .is_some();
+    if !exists {
+        return Ok(false);
+    }
+
+    sqlx::query("DELETE FROM recipe_manager.media_tags WHERE media_id = $1")
+        .bind(media_id.as_i64())
+        .execute(&mut *conn)
+        .await
+        .map_err(AppError::from)?;
+
+    sqlx::query(
+        r"
+        INSERT INTO recipe_manager.media_tags (media_id, tag)
+        SELECT $1, tag FROM UNNEST($2::TEXT[]) AS tag
+        ON CONFLICT DO NOTHING
+        ",
+    )
+    .bind(media_id.as_i64())
+    .bind(tag_strings(tags))
+    .execute(&mut *conn)
+    .await
+    .map_err(AppError::from)?;
+
+    Ok(true)
+}
+
+/// Delete a media row, returning whether it existed
+async fn delete_media(conn: &mut PgConnection, id: MediaId) -> Result<bool, AppError> {
+    let media_id = id.as_i64();
+
+    let result = sqlx::query(
+        r"
+        DELETE FROM recipe_manager.media
+        WHERE media_id = $1
+        ",
+    )
+    .bind(media_id)
+    .execute(&mut *conn)
+    .await
+    .map_err(AppError::from)?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Attach media, returning false if it was already attached there
+async fn insert_association(
+    conn: &mut PgConnection,
+    media_id: MediaId,
+    association: MediaAssociation,
+) -> Result<bool, AppError> {
+    // The NOT EXISTS guard keeps duplicates out even if the table has no unique key
+    let query = match association {
+        MediaAssociation::Recipe(recipe_id) => sqlx::query(
+            r"
+            INSERT INTO recipe_manager.recipe_media (recipe_id, media_id)
+            SELECT $1, $2
+            WHERE NOT EXISTS (
+                SELECT 1 FROM recipe_manager.recipe_media
+                WHERE recipe_id = $1 AND media_id = $2
+            )
+            ON CONFLICT DO NOTHING
+            ",
+        )
+        .bind(recipe_id.as_i64())
+        .bind(media_id.as_i64()),
+        MediaAssociation::Ingredient(recipe_id, ingredient_id) => sqlx::query(
+            r"
+            INSERT INTO recipe_manager.ingredient_media (recipe_id, ingredient_id, media_id)
+            SELECT $1, $2, $3
+            WHERE NOT EXISTS (
+                SELECT 1 FROM recipe_manager.ingredient_media
+                WHERE recipe_id = $1 AND ingredient_id = $2 AND media_id = $3
+            )
+            ON CONFLICT DO NOTHING
+            ",
+        )
+        .bind(recipe_id.as_i64())
+        .bind(ingredient_id.as_i64())
+        .bind(media_id.as_i64()),
+        MediaAssociation::Step(recipe_id, step_id) => sqlx::query(
+            r"
+            INSERT INTO recipe_manager.step_media (recipe_id, step_id, media_id)
+            SELECT $1, $2, $3
+            WHERE NOT EXISTS (
+                SELECT 1 FROM recipe_manager.step_media
+                WHERE recipe_id = $1 AND step_id = $2 AND media_id = $3
+            )
+            ON CONFLICT DO NOTHING
+            ",
+        )
+        .bind(recipe_id.as_i64())
+        .bind(step_id.as_i64())
+        .bind(media_id.as_i64()),
+    };
+
+    let result = query.execute(&mut *conn).await.map_err(AppError::from)?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Detach media, returning false if it was not attached there
+async fn delete_association(
+    conn: &mut PgConnection,
+    media_id: MediaId,
+    association: MediaAssociation,
+) -> Result<bool, AppError> {
+    let query = match association {
+        MediaAssociation::Recipe(recipe_id) => sqlx::query(
+            r"
+            DELETE FROM recipe_manager.recipe_media
+            WHERE recipe_id = $1 AND media_id = $2
+            ",
+        )
+        .bind(recipe_id.as_i64())
+        .bind(media_id.as_i64()),
+        MediaAssociation::Ingredient(recipe_id, ingredient_id) => sqlx::query(
+            r"
+            DELETE FROM recipe_manager.ingredient_media
+            WHERE recipe_id = $1 AND ingredient_id = $2 AND media_id = $3
+            ",
+        )
+        .bind(recipe_id.as_i64())
+        .bind(ingredient_id.as_i64())
+        .bind(media_id.as_i64()),
+        MediaAssociation::Step(recipe_id, step_id) => sqlx::query(
+            r"
+            DELETE FROM recipe_manager.step_media
+            WHERE recipe_id = $1 AND step_id = $2 AND media_id = $3
+            ",
+        )
+        .bind(recipe_id.as_i64())
+        .bind(step_id.as_i64())
+        .bind(media_id.as_i64()),
+    };
+
+    let result = query.execute(&mut *conn).await.map_err(AppError::from)?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Restrict a media query to rows carrying every one of `tags`
+fn push_tag_filter(query: &mut sqlx::QueryBuilder<'_, sqlx::Postgres>, tags: &[MediaTag]) {
+    if tags.is_empty() {
+        return;
+    }
+    query
+        .push(" AND media_id IN (SELECT media_id FROM recipe_manager.media_tags WHERE tag = ANY(")
+        .push_bind(tag_strings(tags))
+        .push(") GROUP BY media_id HAVING COUNT(*) = ")
+        .push_bind(tags.len() as i64)
+        .push(")");
+}
+
+fn tag_strings(tags: &[MediaTag]) -> Vec<String> {
+    tags.iter().map(|tag| tag.as_str().to_string()).collect()
+}
+
+/// Escape `LIKE` wildcards so `value` matches literally
+fn escape_like(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
 }
 
 /// Helper function to map database row to Media entity
@@ -434,6 +1265,24 @@ fn map_row_to_media(row: &sqlx::postgres::PgRow) -> Result<Media, AppError> {
     let created_at: DateTime<Utc> = row.get("created_at");
     let updated_at: DateTime<Utc> = row.get("updated_at");
 
+    let variants_json: Option<String> = row.get("variants");
+    let variants: Vec<MediaVariant> = match variants_json {
+        Some(json) => serde_json::from_str(&json)
+            .map_err(|_| AppError::Database { message: "Invalid media variants".to_string() })?,
+        None => Vec::new(),
+    };
+
+    let processing_error: Option<String> = row.get("processing_error");
+    let corrupted_at: Option<DateTime<Utc>> = row.get("corrupted_at");
+    let tenant: Option<String> = row.get("tenant");
+
+    let tags: Vec<String> = row.get("tags");
+    let tags = tags
+        .iter()
+        .map(|tag| MediaTag::new(tag))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| AppError::Database { message: "Invalid media tag".to_string() })?;
+
     let media = Media::with_id(
         media_id,
         content_hash,
@@ -446,25 +1295,36 @@ fn map_row_to_media(row: &sqlx::postgres::PgRow) -> Result<Media, AppError> {
     .uploaded_by(user_id)
     .uploaded_at(created_at.into())
     .updated_at(updated_at.into())
+    .variants(variants)
+    .processing_error(processing_error)
+    .tags(tags)
+    .corrupted_at(corrupted_at.map(Into::into))
+    .tenant(tenant)
     .build();
 
     Ok(media)
 }
 
+/// Serialize a media entity's variants for the JSONB `variants` column
+fn variants_to_json(media: &Media) -> Result<String, AppError> {
+    serde_json::to_string(&media.variants)
+        .map_err(|e| AppError::Internal { message: format!("Failed to serialize variants: {e}") })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::domain::entities::UserId;
     use crate::domain::value_objects::{ContentHash, ProcessingStatus};
 
-    fn create_test_media() -> Media {
+    fn create_test_media() -> UnsavedMedia {
         let content_hash =
             ContentHash::new("abcdef1234567890abcdef1234567890abcdef1234567890abcdef1234567890")
                 .unwrap();
         let media_type = MediaType::new("image/jpeg");
         let user_id = UserId::new();
 
-        Media::new(
+        UnsavedMedia::new(
             content_hash,
             "test.jpg".to_string(),
             media_type,
@@ -594,8 +1454,7 @@ mod tests {
         let id = MediaId::new(123);
         assert_eq!(id.as_i64(), 123);
 
-        let id2 = MediaId::new(-1);
-        assert_eq!(id2.as_i64(), -1);
+        assert!(MediaId::try_new(-1).is_err());
     }
 
     #[test]
@@ -623,11 +1482,11 @@ mod tests {
         let result = repo.health_check().await;
         assert!(result.is_err());
 
-        if let Err(AppError::Database { message }) = result {
+        if let Err(AppError::ServiceUnavailable { message, last_error, .. }) = result {
             assert!(message.contains("Database unavailable"));
-            assert!(message.contains("test connection failed"));
+            assert_eq!(last_error.as_deref(), Some("test connection failed"));
         } else {
-            panic!("Expected Database error");
+            panic!("Expected ServiceUnavailable error");
         }
     }
 
@@ -647,15 +1506,30 @@ mod tests {
         // Test all methods fail appropriately
         assert!(repo.save(&test_media).await.is_err());
         assert!(repo.find_by_id(test_id).await.is_err());
-        assert!(repo.find_by_content_hash(&test_hash).await.is_err());
+        assert!(repo.find_by_content_hash(&test_hash, None).await.is_err());
         assert!(repo.find_by_user(test_user_id).await.is_err());
-        assert!(repo.find_by_user_paginated(test_user_id, None, 50, None).await.is_err());
-        assert!(repo.update(&test_media).await.is_err());
+        assert!(repo
+            .find_by_user_paginated(test_user_id, None, 50, None, &[], MediaSort::default())
+            .await
+            .is_err());
+        assert!(repo.update(&test_media.clone().into_media(test_id)).await.is_err());
         assert!(repo.delete(test_id).await.is_err());
         assert!(repo.exists_by_content_hash(&test_hash).await.is_err());
         assert!(repo.find_media_ids_by_recipe(recipe_id).await.is_err());
         assert!(repo.find_media_ids_by_recipe_ingredient(recipe_id, ingredient_id).await.is_err());
         assert!(repo.find_media_ids_by_recipe_step(recipe_id, step_id).await.is_err());
+        assert!(repo.find_media_by_recipe(recipe_id, None, 50).await.is_err());
+        assert!(repo
+            .find_media_by_recipe_ingredient(recipe_id, ingredient_id, None, 50)
+            .await
+            .is_err());
+        assert!(repo.find_media_by_recipe_step(recipe_id, step_id, None, 50).await.is_err());
+    }
+
+    #[test]
+    fn test_escape_like_matches_wildcards_literally() {
+        assert_eq!(escape_like("50%_off\\"), "50\\%\\_off\\\\");
+        assert_eq!(escape_like("crumb"), "crumb");
     }
 }
 
@@ -674,29 +1548,74 @@ impl DisconnectedMediaRepository {
     pub fn new(error_message: String) -> Self {
         Self { error_message }
     }
+
+    fn unavailable(&self) -> AppError {
+        AppError::ServiceUnavailable {
+            message: "Database unavailable".to_string(),
+            last_error: Some(self.error_message.clone()),
+            retry_after_seconds: None,
+        }
+    }
 }
 
 #[async_trait]
 impl MediaRepository for DisconnectedMediaRepository {
     type Error = AppError;
 
-    async fn save(&self, _media: &Media) -> Result<MediaId, Self::Error> {
-        Err(AppError::Database { message: format!("Database unavailable: {}", self.error_message) })
+    async fn save(&self, _media: &UnsavedMedia) -> Result<MediaId, Self::Error> {
+        Err(self.unavailable())
+    }
+
+    async fn save_or_reuse(&self, _media: &UnsavedMedia) -> Result<SaveOutcome, Self::Error> {
+        Err(self.unavailable())
     }
 
     async fn find_by_id(&self, _id: MediaId) -> Result<Option<Media>, Self::Error> {
-        Err(AppError::Database { message: format!("Database unavailable: {}", self.error_message) })
+        Err(self.unavailable())
+    }
+
+    async fn find_by_ids(&self, _ids: &[MediaId]) -> Result<Vec<Media>, Self::Error> {
+        Err(self.unavailable())
     }
 
     async fn find_by_content_hash(
         &self,
         _hash: &ContentHash,
+        _owner: Option<UserId>,
     ) -> Result<Option<Media>, Self::Error> {
-        Err(AppError::Database { message: format!("Database unavailable: {}", self.error_message) })
+        Err(self.unavailable())
     }
 
     async fn find_by_user(&self, _user_id: UserId) -> Result<Vec<Media>, Self::Error> {
-        Err(AppError::Database { message: format!("Database unavailable: {}", self.error_message) })
+        Err(self.unavailable())
+    }
+
+    async fn find_after(
+        &self,
+        _after: Option<MediaId>,
+        _limit: u32,
+    ) -> Result<Vec<Media>, Self::Error> {
+        Err(self.unavailable())
+    }
+
+    async fn find_unprocessed_before(
+        &self,
+        _updated_before: SystemTime,
+        _limit: u32,
+    ) -> Result<Vec<Media>, Self::Error> {
+        Err(self.unavailable())
+    }
+
+    async fn usage_by_user(&self, _user_id: UserId) -> Result<StorageUsage, Self::Error> {
+        Err(self.unavailable())
+    }
+
+    async fn capacity_growth(
+        &self,
+        _dimension: CapacityDimension,
+        _since: SystemTime,
+    ) -> Result<Vec<CapacityGrowth>, Self::Error> {
+        Err(self.unavailable())
     }
 
     async fn find_by_user_paginated(
@@ -705,27 +1624,43 @@ impl MediaRepository for DisconnectedMediaRepository {
         _cursor: Option<String>,
         _limit: u32,
         _status_filter: Option<ProcessingStatus>,
+        _tags: &[MediaTag],
+        _sort: MediaSort,
+    ) -> Result<(Vec<Media>, Option<String>, bool), Self::Error> {
+        Err(self.unavailable())
+    }
+
+    async fn search_by_user(
+        &self,
+        _user_id: UserId,
+        _search: &MediaSearch,
+        _cursor: Option<String>,
+        _limit: u32,
     ) -> Result<(Vec<Media>, Option<String>, bool), Self::Error> {
-        Err(AppError::Database { message: format!("Database unavailable: {}", self.error_message) })
+        Err(self.unavailable())
     }
 
     async fn update(&self, _media: &Media) -> Result<(), Self::Error> {
-        Err(AppError::Database { message: format!("Database unavailable: {}", self.error_message) })
+        Err(self.unavailable())
+    }
+
+    async fn set_tags(&self, _media_id: MediaId, _tags: &[MediaTag]) -> Result<bool, Self::Error> {
+        Err(self.unavailable())
     }
 
     async fn delete(&self, _id: MediaId) -> Result<bool, Self::Error> {
-        Err(AppError::Database { message: format!("Database unavailable: {}", self.error_message) })
+        Err(self.unavailable())
     }
 
     async fn exists_by_content_hash(&self, _hash: &ContentHash) -> Result<bool, Self::Error> {
-        Err(AppError::Database { message: format!("Database unavailable: {}", self.error_message) })
+        Err(self.unavailable())
     }
 
     async fn find_media_ids_by_recipe(
         &self,
         _recipe_id: RecipeId,
     ) -> Result<Vec<MediaId>, Self::Error> {
-        Err(AppError::Database { message: format!("Database unavailable: {}", self.error_message) })
+        Err(self.unavailable())
     }
 
     async fn find_media_ids_by_recipe_ingredient(
@@ -733,7 +1668,7 @@ impl MediaRepository for DisconnectedMediaRepository {
         _recipe_id: RecipeId,
         _ingredient_id: IngredientId,
     ) -> Result<Vec<MediaId>, Self::Error> {
-        Err(AppError::Database { message: format!("Database unavailable: {}", self.error_message) })
+        Err(self.unavailable())
     }
 
     async fn find_media_ids_by_recipe_step(
@@ -741,10 +1676,59 @@ impl MediaRepository for DisconnectedMediaRepository {
         _recipe_id: RecipeId,
         _step_id: StepId,
     ) -> Result<Vec<MediaId>, Self::Error> {
-        Err(AppError::Database { message: format!("Database unavailable: {}", self.error_message) })
+        Err(self.unavailable())
+    }
+
+    async fn add_association(
+        &self,
+        _media_id: MediaId,
+        _association: MediaAssociation,
+    ) -> Result<bool, Self::Error> {
+        Err(self.unavailable())
+    }
+
+    async fn remove_association(
+        &self,
+        _media_id: MediaId,
+        _association: MediaAssociation,
+    ) -> Result<bool, Self::Error> {
+        Err(self.unavailable())
+    }
+
+    async fn find_media_by_recipe(
+        &self,
+        _recipe_id: RecipeId,
+        _cursor: Option<String>,
+        _limit: u32,
+    ) -> Result<(Vec<Media>, Option<String>, bool), Self::Error> {
+        Err(self.unavailable())
+    }
+
+    async fn find_media_by_recipe_ingredient(
+        &self,
+        _recipe_id: RecipeId,
+        _ingredient_id: IngredientId,
+        _cursor: Option<String>,
+        _limit: u32,
+    ) -> Result<(Vec<Media>, Option<String>, bool), Self::Error> {
+        Err(self.unavailable())
+    }
+
+    async fn find_media_by_recipe_step(
+        &self,
+        _recipe_id: RecipeId,
+        _step_id: StepId,
+        _cursor: Option<String>,
+        _limit: u32,
+    ) -> Result<(Vec<Media>, Option<String>, bool), Self::Error> {
+        Err(self.unavailable())
     }
 
     async fn health_check(&self) -> Result<(), Self::Error> {
-        Err(AppError::Database { message: format!("Database unavailable: {}", self.error_message) })
+        Err(self.unavailable())
+    }
+
+    async fn migration_version(&self) -> Result<Option<i64>, Self::Error> {
+        Err(self.unavailable())
     }
 }
@@ -0,0 +1,72 @@
+//! Schema migrations embedded from the `migrations/` directory
+//!
+//! Migrations are applied whenever the service connects to the database if
+//! `postgres.auto_migrate` is set, or by running the binary with `--migrate`.
+//! sqlx records applied migrations in `_sqlx_migrations` and holds an advisory
+//! lock while applying them, so replicas starting together apply each migration
+//! once.
+
+use sqlx::{migrate::Migrator, PgPool};
+use tracing::{error, info};
+
+/// Every migration shipped with this build
+pub static MIGRATOR: Migrator = sqlx::migrate!();
+
+/// Postgres error code for a table that does not exist
+const UNDEFINED_TABLE: &str = "42P01";
+
+/// Apply pending migrations, returning the version the schema is at afterwards
+///
+/// # Errors
+/// Returns an error if a migration fails or was changed after being applied
+pub async fn run_migrations(pool: &PgPool) -> Result<Option<i64>, sqlx::migrate::MigrateError> {
+    MIGRATOR.run(pool).await?;
+    Ok(migration_version(pool).await?)
+}
+
+/// Apply pending migrations, logging the outcome rather than failing
+///
+/// The service keeps running on the schema it has when a migration fails; the
+/// health check reports the version it is at.
+pub async fn apply_migrations(pool: &PgPool) {
+    match run_migrations(pool).await {
+        Ok(version) => info!("Database schema is at migration {:?}", version),
+        Err(e) => error!("Failed to apply database migrations: {}", e),
+    }
+}
+
+/// Version of the last migration applied to the database, `None` before any
+///
+/// # Errors
+/// Returns an error if the migration history cannot be read
+pub async fn migration_version(pool: &PgPool) -> Result<Option<i64>, sqlx::Error> {
+    let version = sqlx::query_scalar("SELECT MAX(version) FROM _sqlx_migrations WHERE success")
+        .fetch_one(pool)
+        .await;
+
+    match version {
+        Ok(version) => Ok(version),
+        Err(sqlx::Error::Database(e)) if e.code().as_deref() == Some(UNDEFINED_TABLE) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Version of the newest migration shipped with this build
+#[must_use]
+pub fn latest_migration_version() -> Option<i64> {
+    MIGRATOR.iter().map(|migration| migration.version).max()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migrations_are_embedded_in_order() {
+        let versions: Vec<i64> = MIGRATOR.iter().map(|migration| migration.version).collect();
+
+        assert!(!versions.is_empty());
+        assert!(versions.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(latest_migration_version(), versions.last().copied());
+    }
+}
@@ -1,7 +1,28 @@
+pub mod cached_repository;
 pub mod connection;
+pub mod content_lock_repository;
+pub mod idempotency_repository;
 pub mod media_repository;
+pub mod migrations;
+pub mod pagination;
 pub mod reconnecting_repository;
+pub mod resumable_upload_repository;
+pub mod upload_session_repository;
+pub mod variant_repository;
 
+pub use cached_repository::CachedMediaRepository;
 pub use connection::Database;
+pub use content_lock_repository::PostgreSqlContentLockRepository;
+pub use idempotency_repository::{InMemoryIdempotencyRepository, PostgreSqlIdempotencyRepository};
 pub use media_repository::{DisconnectedMediaRepository, PostgreSqlMediaRepository};
+pub use migrations::{
+    apply_migrations, latest_migration_version, migration_version, run_migrations, MIGRATOR,
+};
 pub use reconnecting_repository::ReconnectingMediaRepository;
+pub use resumable_upload_repository::{
+    InMemoryResumableUploadRepository, PostgreSqlResumableUploadRepository,
+};
+pub use upload_session_repository::{
+    InMemoryUploadSessionRepository, PostgreSqlUploadSessionRepository,
+};
+pub use variant_repository::{InMemoryVariantRepository, PostgreSqlVariantRepository};
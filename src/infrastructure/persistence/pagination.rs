@@ -0,0 +1,340 @@
+//! Cursor-based pagination shared by every repository implementation
+//!
+//! Cursors are the base64-encoded ID of the last item on the previous page. Queries
+//! fetch one item more than the page size so `has_more` can be answered without a
+//! separate count.
+//!
+//! Listings sorted by something other than the ID use keyset cursors instead, which
+//! also carry the sort value of that item and the name of the sort they belong to.
+
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use std::fmt::Display;
+use std::str::FromStr;
+use thiserror::Error;
+
+/// Page size used when the client does not ask for one
+pub const DEFAULT_PAGE_SIZE: u32 = 50;
+
+/// Largest page size a client may request
+pub const MAX_PAGE_SIZE: u32 = 100;
+
+/// Errors from decoding a client-supplied cursor
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum CursorError {
+    #[error("Invalid cursor format")]
+    InvalidFormat,
+
+    #[error("Invalid cursor encoding")]
+    InvalidEncoding,
+
+    #[error("Invalid cursor data")]
+    InvalidData,
+}
+
+/// Clamp a requested page size to `1..=MAX_PAGE_SIZE`
+pub fn clamp_limit(limit: u32) -> u32 {
+    limit.clamp(1, MAX_PAGE_SIZE)
+}
+
+/// Encode the ID of the last item on a page as an opaque cursor
+pub fn encode_cursor(last_id: i64) -> String {
+    STANDARD.encode(last_id.to_string().as_bytes())
+}
+
+/// Decode a cursor back into the ID of the last item on the previous page
+///
+/// # Errors
+/// Returns a `CursorError` if the cursor was not produced by [`encode_cursor`]
+pub fn decode_cursor(cursor: &str) -> Result<i64, CursorError> {
+    let decoded = STANDARD.decode(cursor).map_err(|_| CursorError::InvalidFormat)?;
+    let cursor_data = String::from_utf8(decoded).map_err(|_| CursorError::InvalidEncoding)?;
+    cursor_data.parse::<i64>().map_err(|_| CursorError::InvalidData)
+}
+
+/// Encode the sort value and ID of the last item on a page sorted by `sort`
+pub fn encode_keyset_cursor<K: Display>(sort: &str, key: &K, last_id: i64) -> String {
+    STANDARD.encode(format!("{sort}:{last_id}:{key}").as_bytes())
+}
+
+/// Decode a keyset cursor issued for `sort` into the sort value and ID it encodes
+///
+/// # Errors
+/// Returns a `CursorError` if the cursor was not produced by [`encode_keyset_cursor`]
+/// for the same sort
+pub fn decode_keyset_cursor<K: FromStr>(cursor: &str, sort: &str) -> Result<(K, i64), CursorError> {
+    let decoded = STANDARD.decode(cursor).map_err(|_| CursorError::InvalidFormat)?;
+    let cursor_data = String::from_utf8(decoded).map_err(|_| CursorError::InvalidEncoding)?;
+
+    let mut parts = cursor_data.splitn(3, ':');
+    let (Some(cursor_sort), Some(id), Some(key)) = (parts.next(), parts.next(), parts.next())
+    else {
+        return Err(CursorError::InvalidData);
+    };
+    if cursor_sort != sort {
+        return Err(CursorError::InvalidData);
+    }
+    let id = id.parse::<i64>().map_err(|_| CursorError::InvalidData)?;
+    let key = key.parse::<K>().map_err(|_| CursorError::InvalidData)?;
+    Ok((key, id))
+}
+
+/// A validated page request: items after `after`, at most `limit` of them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PageRequest {
+    pub after: Option<i64>,
+    pub limit: u32,
+}
+
+impl PageRequest {
+    /// Decode the cursor and clamp the limit
+    ///
+    /// # Errors
+    /// Returns a `CursorError` if the cursor is malformed
+    pub fn new(cursor: Option<&str>, limit: u32) -> Result<Self, CursorError> {
+        let after = cursor.map(decode_cursor).transpose()?;
+        Ok(Self { after, limit: clamp_limit(limit) })
+    }
+
+    /// Number of rows to fetch: one extra to detect a following page
+    pub fn fetch_limit(&self) -> i64 {
+        i64::from(self.limit) + 1
+    }
+}
+
+/// A validated page request for a listing ordered by a sort value, then ID
+///
+/// Items come after `after`, the sort value and ID of the last item on the previous
+/// page, in ascending or descending order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeysetPageRequest<K> {
+    pub sort: String,
+    pub descending: bool,
+    pub after: Option<(K, i64)>,
+    pub limit: u32,
+}
+
+impl<K: FromStr> KeysetPageRequest<K> {
+    /// Decode the cursor and clamp the limit
+    ///
+    /// # Errors
+    /// Returns a `CursorError` if the cursor is malformed or was issued for another sort
+    pub fn new(
+        sort: impl Into<String>,
+        descending: bool,
+        cursor: Option<&str>,
+        limit: u32,
+    ) -> Result<Self, CursorError> {
+        let sort = sort.into();
+        let after = cursor.map(|cursor| decode_keyset_cursor(cursor, &sort)).transpose()?;
+        Ok(Self { sort, descending, after, limit: clamp_limit(limit) })
+    }
+
+    /// Number of rows to fetch: one extra to detect a following page
+    pub fn fetch_limit(&self) -> i64 {
+        i64::from(self.limit) + 1
+    }
+}
+
+/// One page of results
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub next_cursor: Option<String>,
+    pub has_more: bool,
+}
+
+impl<T> Page<T> {
+    /// Build a page from up to `fetch_limit()` items fetched in key order
+    pub fn from_overfetched<F>(items: Vec<T>, request: PageRequest, key: F) -> Self
+    where
+        F: Fn(&T) -> i64,
+    {
+        Self::truncated(items, request.limit, |item| encode_cursor(key(item)))
+    }
+
+    /// Build a page from up to `fetch_limit()` items fetched in `(sort value, ID)` order
+    pub fn from_overfetched_keyset<K, F>(
+        items: Vec<T>,
+        request: &KeysetPageRequest<K>,
+        key: F,
+    ) -> Self
+    where
+        K: Display,
+        F: Fn(&T) -> (K, i64),
+    {
+        Self::truncated(items, request.limit, |item| {
+            let (sort_key, id) = key(item);
+            encode_keyset_cursor(&request.sort, &sort_key, id)
+        })
+    }
+
+    fn truncated<F>(mut items: Vec<T>, limit: u32, cursor: F) -> Self
+    where
+        F: Fn(&T) -> String,
+    {
+        let limit = limit as usize;
+        let has_more = items.len() > limit;
+        items.truncate(limit);
+
+        let next_cursor = if has_more { items.last().map(cursor) } else { None };
+
+        Self { items, next_cursor, has_more }
+    }
+
+    /// Paginate items already held in memory, sorting them by key
+    pub fn from_unsorted<F>(mut items: Vec<T>, request: PageRequest, key: F) -> Self
+    where
+        F: Fn(&T) -> i64,
+    {
+        items.sort_by_key(&key);
+        if let Some(after) = request.after {
+            items.retain(|item| key(item) > after);
+        }
+        items.truncate(request.limit as usize + 1);
+
+        Self::from_overfetched(items, request, key)
+    }
+
+    /// Paginate items already held in memory, sorting them by `(sort value, ID)`
+    pub fn from_unsorted_keyset<K, F>(
+        mut items: Vec<T>,
+        request: &KeysetPageRequest<K>,
+        key: F,
+    ) -> Self
+    where
+        K: Ord + Display,
+        F: Fn(&T) -> (K, i64),
+    {
+        items.sort_by_cached_key(&key);
+        if request.descending {
+            items.reverse();
+        }
+        if let Some(after) = &request.after {
+            items.retain(|item| {
+                let position = key(item).cmp(after);
+                position.is_ne() && position.is_gt() != request.descending
+            });
+        }
+        items.truncate(request.limit as usize + 1);
+
+        Self::from_overfetched_keyset(items, request, key)
+    }
+
+    /// Split into `(items, next_cursor, has_more)`
+    pub fn into_parts(self) -> (Vec<T>, Option<String>, bool) {
+        (self.items, self.next_cursor, self.has_more)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(after: Option<i64>, limit: u32) -> PageRequest {
+        PageRequest::new(after.map(encode_cursor).as_deref(), limit).unwrap()
+    }
+
+    #[test]
+    fn test_clamp_limit() {
+        assert_eq!(clamp_limit(0), 1);
+        assert_eq!(clamp_limit(25), 25);
+        assert_eq!(clamp_limit(1000), MAX_PAGE_SIZE);
+    }
+
+    #[test]
+    fn test_cursor_round_trip() {
+        assert_eq!(decode_cursor(&encode_cursor(42)).unwrap(), 42);
+        assert_eq!(encode_cursor(42), "NDI=");
+    }
+
+    #[test]
+    fn test_decode_invalid_cursors() {
+        assert_eq!(decode_cursor("not base64!"), Err(CursorError::InvalidFormat));
+        assert_eq!(
+            decode_cursor(&STANDARD.encode([0xff, 0xfe])),
+            Err(CursorError::InvalidEncoding)
+        );
+        assert_eq!(decode_cursor(&STANDARD.encode("abc")), Err(CursorError::InvalidData));
+    }
+
+    #[test]
+    fn test_page_request() {
+        let request = PageRequest::new(Some(&encode_cursor(7)), 500).unwrap();
+        assert_eq!(request.after, Some(7));
+        assert_eq!(request.limit, MAX_PAGE_SIZE);
+        assert_eq!(request.fetch_limit(), 101);
+
+        assert!(PageRequest::new(Some("???"), 10).is_err());
+    }
+
+    #[test]
+    fn test_from_overfetched_with_more_pages() {
+        let page = Page::from_overfetched(vec![1, 2, 3, 4], request(None, 3), |id| *id);
+
+        assert_eq!(page.items, vec![1, 2, 3]);
+        assert!(page.has_more);
+        assert_eq!(page.next_cursor, Some(encode_cursor(3)));
+    }
+
+    #[test]
+    fn test_from_overfetched_last_page() {
+        let page = Page::from_overfetched(vec![1, 2], request(None, 3), |id| *id);
+
+        assert_eq!(page.items, vec![1, 2]);
+        assert!(!page.has_more);
+        assert_eq!(page.next_cursor, None);
+    }
+
+    #[test]
+    fn test_from_unsorted_walks_all_pages() {
+        let items = vec![5, 3, 9, 1, 7];
+
+        let first = Page::from_unsorted(items.clone(), request(None, 2), |id| *id);
+        assert_eq!(first.items, vec![1, 3]);
+
+        let second_request = PageRequest::new(first.next_cursor.as_deref(), 2).unwrap();
+        let second = Page::from_unsorted(items.clone(), second_request, |id| *id);
+        assert_eq!(second.items, vec![5, 7]);
+
+        let third_request = PageRequest::new(second.next_cursor.as_deref(), 2).unwrap();
+        let third = Page::from_unsorted(items, third_request, |id| *id);
+        assert_eq!(third.items, vec![9]);
+        assert!(!third.has_more);
+        assert_eq!(third.next_cursor, None);
+    }
+
+    #[test]
+    fn test_keyset_cursor_is_tied_to_its_sort() {
+        let cursor = encode_keyset_cursor("file_size.desc", &2048_i64, 7);
+
+        assert_eq!(decode_keyset_cursor::<i64>(&cursor, "file_size.desc"), Ok((2048, 7)));
+        assert_eq!(
+            decode_keyset_cursor::<i64>(&cursor, "file_size.asc"),
+            Err(CursorError::InvalidData)
+        );
+        assert_eq!(
+            decode_keyset_cursor::<i64>(&encode_cursor(7), "file_size.desc"),
+            Err(CursorError::InvalidData)
+        );
+    }
+
+    #[test]
+    fn test_from_unsorted_keyset_walks_descending_pages() {
+        // (size, id) pairs; equal sizes are ordered by ID
+        let items = vec![(10, 1), (30, 2), (20, 3), (30, 4), (10, 5)];
+        let mut cursor = None;
+        let mut seen = Vec::new();
+
+        loop {
+            let request = KeysetPageRequest::new("size.desc", true, cursor.as_deref(), 2).unwrap();
+            let page = Page::from_unsorted_keyset(items.clone(), &request, |item| *item);
+            seen.extend(page.items.iter().map(|(_, id)| *id));
+            cursor = page.next_cursor;
+            if cursor.is_none() {
+                break;
+            }
+        }
+
+        assert_eq!(seen, vec![4, 2, 3, 5, 1]);
+    }
+}
@@ -0,0 +1,358 @@
+use image::{
+    codecs::{avif::AvifEncoder, jpeg::JpegEncoder, png::PngEncoder, webp::WebPEncoder},
+    imageops::FilterType,
+    DynamicImage, ImageFormat,
+};
+
+use super::ProcessingError;
+use crate::domain::value_objects::{ImageFit, ImageRender, RenderSize};
+
+/// AVIF encoder speed (1 = slowest/smallest, 10 = fastest)
+const DEFAULT_AVIF_SPEED: u8 = 8;
+
+/// AVIF encoder quality (1-100)
+const DEFAULT_AVIF_QUALITY: u8 = 70;
+
+/// JPEG quality used when converting to JPEG (1-100)
+const JPEG_QUALITY: u8 = 90;
+
+/// Longest edge of the thumbnail variant, in pixels
+const THUMBNAIL_SIZE: u32 = 320;
+
+/// Formats [`ImageVariantEncoder::convert`] can produce
+const CONVERSION_TARGETS: [&str; 4] = ["image/webp", "image/avif", "image/png", "image/jpeg"];
+
+/// An alternative encoding of an image
+#[derive(Debug, Clone)]
+pub struct EncodedVariant {
+    /// Variant name, e.g. `webp`
+    pub name: &'static str,
+    pub content_type: &'static str,
+    pub data: Vec<u8>,
+}
+
+/// Encodes WebP, AVIF and thumbnail variants of uploaded images
+///
+/// Encoding is CPU-bound; callers on the async runtime should run it via
+/// `tokio::task::spawn_blocking`.
+#[derive(Debug, Clone, Copy)]
+pub struct ImageVariantEncoder {
+    avif_speed: u8,
+    avif_quality: u8,
+}
+
+impl Default for ImageVariantEncoder {
+    fn default() -> Self {
+        Self { avif_speed: DEFAULT_AVIF_SPEED, avif_quality: DEFAULT_AVIF_QUALITY }
+    }
+}
+
+impl ImageVariantEncoder {
+    /// Create an encoder with default AVIF speed and quality
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create an encoder with the given AVIF speed (1-10) and quality (1-100)
+    ///
+    /// Out-of-range values are clamped.
+    #[must_use]
+    pub fn with_avif(speed: u8, quality: u8) -> Self {
+        Self { avif_speed: speed.clamp(1, 10), avif_quality: quality.clamp(1, 100) }
+    }
+
+    /// Check whether variants can be generated from the given source type
+    #[must_use]
+    pub fn supports(content_type: &str) -> bool {
+        source_format(content_type).is_some()
+    }
+
+    /// Name of the variant holding a downscaled copy of the image
+    pub const THUMBNAIL: &'static str = "thumbnail";
+
+    /// Names of the variants [`Self::encode`] produces for the given source type
+    #[must_use]
+    pub fn variant_names(content_type: &str) -> &'static [&'static str] {
+        match source_format(content_type) {
+            Some(ImageFormat::WebP) => &["avif", Self::THUMBNAIL],
+            Some(_) => &["webp", "avif", Self::THUMBNAIL],
+            None => &[],
+        }
+    }
+
+    /// Encode all variants of an image, skipping the one matching the source format
+    ///
+    /// The thumbnail fits within 320x320 pixels and is rendered like
+    /// [`Self::render`] does, so images already that small keep their size.
+    ///
+    /// # Errors
+    /// Returns a `ProcessingError` if the source cannot be decoded or a variant fails to encode
+    pub fn encode(
+        &self,
+        data: &[u8],
+        content_type: &str,
+    ) -> Result<Vec<EncodedVariant>, ProcessingError> {
+        let (format, image) = decode(data, content_type)?;
+
+        let mut variants = Vec::with_capacity(3);
+
+        if format != ImageFormat::WebP {
+            let webp = self.encode_as(&image, "image/webp")?;
+            variants.push(EncodedVariant { name: "webp", content_type: "image/webp", data: webp });
+        }
+
+        let avif = self.encode_as(&image, "image/avif")?;
+        variants.push(EncodedVariant { name: "avif", content_type: "image/avif", data: avif });
+
+        let size = RenderSize { width: Some(THUMBNAIL_SIZE), height: Some(THUMBNAIL_SIZE) };
+        let (content_type, thumbnail) =
+            self.resize(&image, format, ImageRender::new(size, ImageFit::Contain))?;
+        variants.push(EncodedVariant { name: Self::THUMBNAIL, content_type, data: thumbnail });
+
+        Ok(variants)
+    }
+
+    /// Check whether an image of type `from` can be converted to type `to`
+    #[must_use]
+    pub fn can_convert(from: &str, to: &str) -> bool {
+        Self::supports(from) && CONVERSION_TARGETS.contains(&to)
+    }
+
+    /// Re-encode an image in another format
+    ///
+    /// WebP output is lossless and JPEG output drops any alpha channel.
+    ///
+    /// # Errors
+    /// Returns a `ProcessingError` if the source cannot be decoded or the target
+    /// format is not one of the conversion targets
+    pub fn convert(
+        &self,
+        data: &[u8],
+        content_type: &str,
+        target: &str,
+    ) -> Result<Vec<u8>, ProcessingError> {
+        if !CONVERSION_TARGETS.contains(&target) {
+            return Err(ProcessingError::UnsupportedFormat { content_type: target.to_string() });
+        }
+
+        let (_, image) = decode(data, content_type)?;
+        self.encode_as(&image, target)
+    }
+
+    /// Resize an image as `render` describes, returning its content type and data
+    ///
+    /// The image keeps its format, except that GIFs are rendered as PNG. Fitting
+    /// by `contain` never enlarges an image, which would add bytes but no detail.
+    ///
+    /// # Errors
+    /// Returns a `ProcessingError` if the source cannot be decoded or the result fails to encode
+    pub fn render(
+        &self,
+        data: &[u8],
+        content_type: &str,
+        render: ImageRender,
+    ) -> Result<(&'static str, Vec<u8>), ProcessingError> {
+        let (format, image) = decode(data, content_type)?;
+        self.resize(&image, format, render)
+    }
+
+    /// Resize a decoded image of `format` as `render` describes
+    fn resize(
+        self,
+        image: &DynamicImage,
+        format: ImageFormat,
+        render: ImageRender,
+    ) -> Result<(&'static str, Vec<u8>), ProcessingError> {
+        let filter = FilterType::Lanczos3;
+        let resized = match (render.fit, render.size.width, render.size.height) {
+            (ImageFit::Cover, Some(width), Some(height)) => {
+                image.resize_to_fill(width, height, filter)
+            }
+            (ImageFit::Fill, Some(width), Some(height)) => {
+                image.resize_exact(width, height, filter)
+            }
+            (_, width, height) => {
+                let (width, height) = (width.unwrap_or(u32::MAX), height.unwrap_or(u32::MAX));
+                if image.width() <= width && image.height() <= height {
+                    image.clone()
+                } else {
+                    image.resize(width, height, filter)
+                }
+            }
+        };
+
+        let target = match format {
+            ImageFormat::Jpeg => "image/jpeg",
+            ImageFormat::WebP => "image/webp",
+            _ => "image/png",
+        };
+        Ok((target, self.encode_as(&resized, target)?))
+    }
+
+    /// Encode a decoded image as one of the conversion targets
+    fn encode_as(self, image: &DynamicImage, target: &str) -> Result<Vec<u8>, ProcessingError> {
+        let mut data = Vec::new();
+        let result = match target {
+            "image/webp" => image.write_with_encoder(WebPEncoder::new_lossless(&mut data)),
+            "image/avif" => image.write_with_encoder(AvifEncoder::new_with_speed_quality(
+                &mut data,
+                self.avif_speed,
+                self.avif_quality,
+            )),
+            "image/png" => image.write_with_encoder(PngEncoder::new(&mut data)),
+            "image/jpeg" => DynamicImage::ImageRgb8(image.to_rgb8())
+                .write_with_encoder(JpegEncoder::new_with_quality(&mut data, JPEG_QUALITY)),
+            _ => {
+                return Err(ProcessingError::UnsupportedFormat { content_type: target.to_string() })
+            }
+        };
+
+        result.map_err(|e| ProcessingError::EncodeFailed {
+            format: target.trim_start_matches("image/").to_string(),
+            message: e.to_string(),
+        })?;
+        Ok(data)
+    }
+}
+
+/// Decode an image, normalized to 8-bit RGB(A), which every encoder accepts
+fn decode(data: &[u8], content_type: &str) -> Result<(ImageFormat, DynamicImage), ProcessingError> {
+    let format = source_format(content_type).ok_or_else(|| ProcessingError::UnsupportedFormat {
+        content_type: content_type.to_string(),
+    })?;
+
+    let decoded = image::load_from_memory_with_format(data, format)
+        .map_err(|e| ProcessingError::DecodeFailed { message: e.to_string() })?;
+
+    let image = if decoded.color().has_alpha() {
+        DynamicImage::ImageRgba8(decoded.into_rgba8())
+    } else {
+        DynamicImage::ImageRgb8(decoded.into_rgb8())
+    };
+    Ok((format, image))
+}
+
+/// Map a MIME type to a decodable source format
+fn source_format(content_type: &str) -> Option<ImageFormat> {
+    match content_type {
+        "image/jpeg" => Some(ImageFormat::Jpeg),
+        "image/png" => Some(ImageFormat::Png),
+        "image/gif" => Some(ImageFormat::Gif),
+        "image/webp" => Some(ImageFormat::WebP),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{ImageBuffer, Rgb};
+    use std::io::Cursor;
+
+    use crate::test_utils::images::create_test_png;
+
+    #[test]
+    fn test_supports() {
+        assert!(ImageVariantEncoder::supports("image/jpeg"));
+        assert!(ImageVariantEncoder::supports("image/png"));
+        assert!(!ImageVariantEncoder::supports("image/avif"));
+        assert!(!ImageVariantEncoder::supports("video/mp4"));
+    }
+
+    #[test]
+    fn test_encode_png_produces_webp_avif_and_thumbnail() {
+        let variants = ImageVariantEncoder::new().encode(&create_test_png(), "image/png").unwrap();
+
+        let names: Vec<&str> = variants.iter().map(|v| v.name).collect();
+        assert_eq!(names, vec!["webp", "avif", "thumbnail"]);
+        assert_eq!(names, ImageVariantEncoder::variant_names("image/png"));
+        assert_eq!(&variants[0].data[8..12], b"WEBP");
+        assert_eq!(&variants[1].data[4..12], b"ftypavif");
+        assert_eq!(variants[2].content_type, "image/png");
+    }
+
+    #[test]
+    fn test_thumbnail_fits_within_its_size() {
+        let image =
+            ImageBuffer::from_fn(640, 480, |x, y| Rgb([(x % 256) as u8, (y % 256) as u8, 0]));
+        let mut jpeg = Cursor::new(Vec::new());
+        image.write_to(&mut jpeg, ImageFormat::Jpeg).unwrap();
+
+        let variants = ImageVariantEncoder::new().encode(jpeg.get_ref(), "image/jpeg").unwrap();
+        let thumbnail = variants.iter().find(|v| v.name == ImageVariantEncoder::THUMBNAIL).unwrap();
+
+        assert_eq!(thumbnail.content_type, "image/jpeg");
+        let thumbnail = image::load_from_memory(&thumbnail.data).unwrap();
+        assert_eq!((thumbnail.width(), thumbnail.height()), (320, 240));
+    }
+
+    #[test]
+    fn test_encode_webp_source_skips_webp_variant() {
+        let png = create_test_png();
+        let webp = ImageVariantEncoder::new().encode(&png, "image/png").unwrap().remove(0).data;
+
+        let variants = ImageVariantEncoder::new().encode(&webp, "image/webp").unwrap();
+
+        let names: Vec<&str> = variants.iter().map(|v| v.name).collect();
+        assert_eq!(names, vec!["avif", "thumbnail"]);
+        assert_eq!(variants[1].content_type, "image/webp");
+    }
+
+    #[test]
+    fn test_convert_to_each_target() {
+        let encoder = ImageVariantEncoder::new();
+        let png = create_test_png();
+
+        let jpeg = encoder.convert(&png, "image/png", "image/jpeg").unwrap();
+        assert_eq!(&jpeg[..3], &[0xFF, 0xD8, 0xFF]);
+
+        let webp = encoder.convert(&jpeg, "image/jpeg", "image/webp").unwrap();
+        assert_eq!(&webp[8..12], b"WEBP");
+
+        let back = encoder.convert(&webp, "image/webp", "image/png").unwrap();
+        assert_eq!(&back[1..4], b"PNG");
+
+        assert!(ImageVariantEncoder::can_convert("image/gif", "image/avif"));
+        assert!(!ImageVariantEncoder::can_convert("image/png", "image/gif"));
+        assert!(!ImageVariantEncoder::can_convert("video/mp4", "image/webp"));
+        assert!(matches!(
+            encoder.convert(&png, "image/png", "image/gif"),
+            Err(ProcessingError::UnsupportedFormat { .. })
+        ));
+    }
+
+    #[test]
+    fn test_render_resizes_by_fit() {
+        let encoder = ImageVariantEncoder::new();
+        let png = create_test_png();
+        let render = |size: &str, fit| {
+            let render = ImageRender::new(size.parse().unwrap(), fit);
+            let (content_type, data) = encoder.render(&png, "image/png", render).unwrap();
+            assert_eq!(content_type, "image/png");
+            let image = image::load_from_memory_with_format(&data, ImageFormat::Png).unwrap();
+            (image.width(), image.height())
+        };
+
+        assert_eq!(render("4x2", ImageFit::Contain), (2, 2));
+        assert_eq!(render("4x2", ImageFit::Cover), (4, 2));
+        assert_eq!(render("4x2", ImageFit::Fill), (4, 2));
+        assert_eq!(render("x4", ImageFit::Cover), (4, 4));
+        // Contained images are never enlarged
+        assert_eq!(render("64x", ImageFit::Contain), (8, 8));
+    }
+
+    #[test]
+    fn test_encode_rejects_corrupt_and_unsupported_input() {
+        let encoder = ImageVariantEncoder::new();
+
+        assert!(matches!(
+            encoder.encode(b"not an image", "image/png"),
+            Err(ProcessingError::DecodeFailed { .. })
+        ));
+        assert!(matches!(
+            encoder.encode(b"data", "video/mp4"),
+            Err(ProcessingError::UnsupportedFormat { .. })
+        ));
+    }
+}
@@ -0,0 +1,45 @@
+mod image_variants;
+mod render_provider;
+mod rollout;
+mod scanner;
+mod video;
+
+pub use image_variants::{EncodedVariant, ImageVariantEncoder};
+pub use render_provider::RenderProvider;
+pub use rollout::{ImagePipelineRollout, PipelineVersion};
+pub use scanner::{ClamAvScanner, MalwareScan, ScanVerdict, Scanner};
+pub use video::VideoProcessor;
+
+/// Error types for media processing operations
+#[derive(Debug, thiserror::Error)]
+pub enum ProcessingError {
+    #[error("Unsupported source format: {content_type}")]
+    UnsupportedFormat { content_type: String },
+
+    #[error("Failed to decode media: {message}")]
+    DecodeFailed { message: String },
+
+    #[error("Failed to encode {format} variant: {message}")]
+    EncodeFailed { format: String, message: String },
+
+    #[error("{command} failed: {message}")]
+    CommandFailed { command: String, message: String },
+
+    #[error("Malware detected: {signature}")]
+    MalwareDetected { signature: String },
+
+    #[error("Malware scan failed: {message}")]
+    ScanFailed { message: String },
+
+    #[error("Storage error: {message}")]
+    Storage { message: String },
+
+    #[error("I/O error: {message}")]
+    Io { message: String },
+}
+
+impl From<std::io::Error> for ProcessingError {
+    fn from(error: std::io::Error) -> Self {
+        Self::Io { message: error.to_string() }
+    }
+}
@@ -0,0 +1,150 @@
+use base64::{engine::general_purpose::URL_SAFE, Engine as _};
+use md5::Md5;
+use sha1::{Digest, Sha1};
+
+use crate::domain::value_objects::{ContentHash, ImageFit, ImageRender};
+use crate::infrastructure::{
+    config::{RenderProviderConfig, RenderProviderKind},
+    storage::utils::content_addressable_path,
+};
+
+/// Image CDN that renders images on the service's behalf
+///
+/// Builds signed imgix- or Cloudinary-style URLs for an original's storage key
+/// (`ab/cd/ef/<hash>`), so the provider's source must be the storage originals are
+/// kept in. `contain` never enlarges the image, `cover` crops around the center and
+/// `fill` stretches, as when rendering locally.
+#[derive(Debug, Clone)]
+pub struct RenderProvider {
+    kind: RenderProviderKind,
+    base_url: String,
+    signing_key: String,
+}
+
+impl RenderProvider {
+    /// Create a provider from configuration, if delegation is enabled
+    #[must_use]
+    pub fn from_config(config: &RenderProviderConfig) -> Option<Self> {
+        config.enabled.then(|| Self {
+            kind: config.kind,
+            base_url: config.base_url.trim_end_matches('/').to_string(),
+            signing_key: config.signing_key.expose().clone(),
+        })
+    }
+
+    /// Name of the provider, for logs
+    #[must_use]
+    pub fn name(&self) -> &'static str {
+        match self.kind {
+            RenderProviderKind::Imgix => "imgix",
+            RenderProviderKind::Cloudinary => "cloudinary",
+        }
+    }
+
+    /// Signed URL of the original with content `hash`, resized per `render`
+    #[must_use]
+    pub fn url(&self, hash: &ContentHash, render: ImageRender) -> String {
+        let key = content_addressable_path(hash);
+        match self.kind {
+            RenderProviderKind::Imgix => self.imgix_url(&key, render),
+            RenderProviderKind::Cloudinary => self.cloudinary_url(&key, render),
+        }
+    }
+
+    /// `{base}/{key}?fit=..&h=..&w=..&s=<md5(token + path + "?" + query)>`
+    fn imgix_url(&self, key: &str, render: ImageRender) -> String {
+        let fit = match render.fit {
+            ImageFit::Contain => "max",
+            ImageFit::Cover => "crop",
+            ImageFit::Fill => "scale",
+        };
+        let mut params = vec![format!("fit={fit}")];
+        params.extend(render.size.height.map(|height| format!("h={height}")));
+        params.extend(render.size.width.map(|width| format!("w={width}")));
+        let query = params.join("&");
+
+        let path = format!("/{key}");
+        let signature = hex::encode(Md5::digest(format!("{}{path}?{query}", self.signing_key)));
+        format!("{}{path}?{query}&s={signature}", self.base_url)
+    }
+
+    /// `{base}/image/upload/s--<sig>--/{transformation}/{key}`, where `sig` is the
+    /// first 8 characters of the URL-safe Base64 SHA-1 of the transformation and
+    /// key followed by the API secret
+    fn cloudinary_url(&self, key: &str, render: ImageRender) -> String {
+        let crop = match render.fit {
+            ImageFit::Contain => "c_limit",
+            ImageFit::Cover => "c_fill",
+            ImageFit::Fill => "c_scale",
+        };
+        let mut parts = vec![crop.to_string()];
+        parts.extend(render.size.height.map(|height| format!("h_{height}")));
+        parts.extend(render.size.width.map(|width| format!("w_{width}")));
+        let transformation = parts.join(",");
+
+        let to_sign = format!("{transformation}/{key}{}", self.signing_key);
+        let digest = URL_SAFE.encode(Sha1::digest(to_sign));
+        format!("{}/image/upload/s--{}--/{transformation}/{key}", self.base_url, &digest[..8])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HASH: &str = "abcdef1234567890abcdef1234567890abcdef1234567890abcdef1234567890";
+    const KEY: &str = "ab/cd/ef/abcdef1234567890abcdef1234567890abcdef1234567890abcdef1234567890";
+
+    fn provider(kind: RenderProviderKind, base_url: &str) -> RenderProvider {
+        RenderProvider::from_config(&RenderProviderConfig {
+            enabled: true,
+            kind,
+            base_url: base_url.to_string(),
+            signing_key: "s3cr3t".into(),
+        })
+        .unwrap()
+    }
+
+    fn render(size: &str, fit: ImageFit) -> ImageRender {
+        ImageRender::new(size.parse().unwrap(), fit)
+    }
+
+    #[test]
+    fn test_disabled_provider_is_not_created() {
+        assert!(RenderProvider::from_config(&RenderProviderConfig::default()).is_none());
+    }
+
+    #[test]
+    fn test_imgix_url_is_signed_over_path_and_query() {
+        let provider = provider(RenderProviderKind::Imgix, "https://recipes.imgix.net/");
+        let hash = ContentHash::new(HASH).unwrap();
+
+        assert_eq!(
+            provider.url(&hash, render("320x240", ImageFit::Cover)),
+            format!(
+                "https://recipes.imgix.net/{KEY}?fit=crop&h=240&w=320&s={}",
+                hex::encode(Md5::digest(format!("s3cr3t/{KEY}?fit=crop&h=240&w=320")))
+            )
+        );
+        assert!(provider
+            .url(&hash, render("640x", ImageFit::Fill))
+            .starts_with(&format!("https://recipes.imgix.net/{KEY}?fit=max&w=640&s=")));
+    }
+
+    #[test]
+    fn test_cloudinary_url_is_signed_over_transformation_and_key() {
+        let provider = provider(RenderProviderKind::Cloudinary, "https://res.cloudinary.com/demo");
+        let hash = ContentHash::new(HASH).unwrap();
+
+        let url = provider.url(&hash, render("320x240", ImageFit::Contain));
+
+        let digest = URL_SAFE.encode(Sha1::digest(format!("c_limit,h_240,w_320/{KEY}s3cr3t")));
+        assert_eq!(
+            url,
+            format!(
+                "https://res.cloudinary.com/demo/image/upload/s--{}--/c_limit,h_240,w_320/{KEY}",
+                &digest[..8]
+            )
+        );
+    }
+}
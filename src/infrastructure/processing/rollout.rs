@@ -0,0 +1,261 @@
+//! Gradual rollout of a new image pipeline
+//!
+//! A candidate [`ImageVariantEncoder`] runs next to the stable one for a
+//! configurable share of images, so a new encoder or library can be validated
+//! on real uploads before it replaces the stable pipeline.
+//!
+//! Metrics, labelled by pipeline `version` (`stable` or `candidate`):
+//! - `image_pipeline_runs_total` - encodes, also labelled by `outcome`
+//! - `image_pipeline_duration_seconds` - encode duration
+//! - `image_pipeline_variant_bytes` - size of each variant, also labelled by `variant`
+//!
+//! With comparison enabled, images served by the candidate are also encoded by
+//! the stable pipeline:
+//! - `image_pipeline_comparisons_total` - by `result`: `match`, `mismatch`,
+//!   `candidate_failed` or `stable_failed`
+//! - `image_pipeline_size_ratio` - candidate size over stable size, by `variant`
+
+use std::time::Instant;
+
+use super::{EncodedVariant, ImageVariantEncoder, ProcessingError};
+use crate::domain::entities::MediaId;
+
+/// A version of the image pipeline
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PipelineVersion {
+    Stable,
+    Candidate,
+}
+
+impl PipelineVersion {
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Stable => "stable",
+            Self::Candidate => "candidate",
+        }
+    }
+}
+
+/// Splits image encoding between a stable and a candidate encoder
+///
+/// Media is assigned to a version by its ID, so reprocessing the same media
+/// always uses the same version. If the candidate fails, the stable pipeline
+/// produces the variants instead; the failure only shows in metrics.
+#[derive(Debug, Clone, Copy)]
+pub struct ImagePipelineRollout {
+    stable: ImageVariantEncoder,
+    candidate: ImageVariantEncoder,
+    candidate_percent: u8,
+    compare: bool,
+}
+
+impl Default for ImagePipelineRollout {
+    /// Only the stable pipeline, with default settings
+    fn default() -> Self {
+        Self::new(ImageVariantEncoder::new(), ImageVariantEncoder::new(), 0)
+    }
+}
+
+impl ImagePipelineRollout {
+    /// Send `candidate_percent` of images (capped at 100) to the candidate encoder
+    #[must_use]
+    pub fn new(
+        stable: ImageVariantEncoder,
+        candidate: ImageVariantEncoder,
+        candidate_percent: u8,
+    ) -> Self {
+        Self { stable, candidate, candidate_percent: candidate_percent.min(100), compare: false }
+    }
+
+    /// Also encode candidate images with the stable pipeline and compare the results
+    #[must_use]
+    pub fn with_comparison(mut self, compare: bool) -> Self {
+        self.compare = compare;
+        self
+    }
+
+    /// The version that serves the given media
+    #[must_use]
+    pub fn version_for(&self, media_id: MediaId) -> PipelineVersion {
+        if media_id.as_i64() % 100 < i64::from(self.candidate_percent) {
+            PipelineVersion::Candidate
+        } else {
+            PipelineVersion::Stable
+        }
+    }
+
+    /// Encode the variants of an image with the version assigned to `media_id`
+    ///
+    /// Encoding is CPU-bound; callers on the async runtime should run it via
+    /// `tokio::task::spawn_blocking`.
+    ///
+    /// # Errors
+    /// Returns the stable pipeline's `ProcessingError` if it fails
+    pub fn encode(
+        &self,
+        media_id: MediaId,
+        data: &[u8],
+        content_type: &str,
+    ) -> Result<(PipelineVersion, Vec<EncodedVariant>), ProcessingError> {
+        if self.version_for(media_id) == PipelineVersion::Stable {
+            let variants = self.run(PipelineVersion::Stable, data, content_type)?;
+            return Ok((PipelineVersion::Stable, variants));
+        }
+
+        let candidate = self.run(PipelineVersion::Candidate, data, content_type);
+        if !self.compare {
+            return match candidate {
+                Ok(variants) => Ok((PipelineVersion::Candidate, variants)),
+                Err(e) => {
+                    tracing::warn!(
+                        "Candidate image pipeline failed for media {}, using stable: {}",
+                        media_id,
+                        e
+                    );
+                    let variants = self.run(PipelineVersion::Stable, data, content_type)?;
+                    Ok((PipelineVersion::Stable, variants))
+                }
+            };
+        }
+
+        let stable = self.run(PipelineVersion::Stable, data, content_type);
+        record_comparison(media_id, &candidate, &stable);
+        match candidate {
+            Ok(variants) => Ok((PipelineVersion::Candidate, variants)),
+            Err(_) => stable.map(|variants| (PipelineVersion::Stable, variants)),
+        }
+    }
+
+    fn run(
+        self,
+        version: PipelineVersion,
+        data: &[u8],
+        content_type: &str,
+    ) -> Result<Vec<EncodedVariant>, ProcessingError> {
+        let encoder = match version {
+            PipelineVersion::Stable => self.stable,
+            PipelineVersion::Candidate => self.candidate,
+        };
+
+        let started = Instant::now();
+        let result = encoder.encode(data, content_type);
+        let elapsed = started.elapsed();
+
+        let version = version.as_str();
+        let outcome = if result.is_ok() { "success" } else { "failure" };
+        metrics::counter!("image_pipeline_runs_total", "version" => version, "outcome" => outcome)
+            .increment(1);
+        metrics::histogram!("image_pipeline_duration_seconds", "version" => version)
+            .record(elapsed.as_secs_f64());
+        for variant in result.iter().flatten() {
+            metrics::histogram!(
+                "image_pipeline_variant_bytes",
+                "version" => version,
+                "variant" => variant.name
+            )
+            .record(variant.data.len() as f64);
+        }
+
+        result
+    }
+}
+
+/// Compare the candidate's result against the stable pipeline's
+///
+/// Results match when both succeed with the same set of variants; encoded bytes
+/// are expected to differ and are compared by size instead.
+fn record_comparison(
+    media_id: MediaId,
+    candidate: &Result<Vec<EncodedVariant>, ProcessingError>,
+    stable: &Result<Vec<EncodedVariant>, ProcessingError>,
+) {
+    let result = match (candidate, stable) {
+        (Ok(candidate), Ok(stable)) => {
+            for variant in candidate {
+                let baseline = stable.iter().find(|v| v.name == variant.name);
+                if let Some(baseline) = baseline.filter(|v| !v.data.is_empty()) {
+                    metrics::histogram!("image_pipeline_size_ratio", "variant" => variant.name)
+                        .record(variant.data.len() as f64 / baseline.data.len() as f64);
+                }
+            }
+
+            let names = |variants: &[EncodedVariant]| {
+                let mut names: Vec<&str> = variants.iter().map(|v| v.name).collect();
+                names.sort_unstable();
+                names
+            };
+            if names(candidate) == names(stable) {
+                "match"
+            } else {
+                "mismatch"
+            }
+        }
+        (Err(_), Ok(_)) => "candidate_failed",
+        (Ok(_), Err(_)) => "stable_failed",
+        (Err(_), Err(_)) => "match",
+    };
+
+    if result != "match" {
+        tracing::warn!("Image pipeline comparison for media {}: {}", media_id, result);
+    }
+    metrics::counter!("image_pipeline_comparisons_total", "result" => result).increment(1);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::images::create_test_png;
+
+    #[test]
+    fn test_version_split_follows_percentage() {
+        let rollout =
+            ImagePipelineRollout::new(ImageVariantEncoder::new(), ImageVariantEncoder::new(), 25);
+
+        let candidates = (1..=1000)
+            .filter(|id| rollout.version_for(MediaId::new(*id)) == PipelineVersion::Candidate)
+            .count();
+
+        assert_eq!(candidates, 250);
+        assert_eq!(rollout.version_for(MediaId::new(24)), PipelineVersion::Candidate);
+        assert_eq!(rollout.version_for(MediaId::new(125)), PipelineVersion::Stable);
+    }
+
+    #[test]
+    fn test_default_only_uses_stable() {
+        let rollout = ImagePipelineRollout::default();
+
+        assert!(
+            (1..=100).all(|id| rollout.version_for(MediaId::new(id)) == PipelineVersion::Stable)
+        );
+    }
+
+    #[test]
+    fn test_candidate_encodes_with_its_own_settings() {
+        let stable = ImageVariantEncoder::new();
+        let candidate = ImageVariantEncoder::with_avif(10, 20);
+        let png = create_test_png();
+
+        let (version, variants) = ImagePipelineRollout::new(stable, candidate, 100)
+            .with_comparison(true)
+            .encode(MediaId::new(1), &png, "image/png")
+            .unwrap();
+
+        assert_eq!(version, PipelineVersion::Candidate);
+        let avif = variants.iter().find(|v| v.name == "avif").unwrap();
+        assert_eq!(avif.data, candidate.encode(&png, "image/png").unwrap()[1].data);
+    }
+
+    #[test]
+    fn test_failures_surface_the_stable_error() {
+        let rollout =
+            ImagePipelineRollout::new(ImageVariantEncoder::new(), ImageVariantEncoder::new(), 100);
+
+        for rollout in [rollout, rollout.with_comparison(true)] {
+            assert!(matches!(
+                rollout.encode(MediaId::new(1), b"not an image", "image/png"),
+                Err(ProcessingError::DecodeFailed { .. })
+            ));
+        }
+    }
+}
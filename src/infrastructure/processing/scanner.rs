@@ -0,0 +1,260 @@
+use async_trait::async_trait;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+use super::ProcessingError;
+use crate::domain::value_objects::ContentHash;
+
+/// Size of each chunk streamed to clamd
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Outcome of scanning a file for malware
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScanVerdict {
+    Clean,
+    Infected { signature: String },
+}
+
+/// Checks uploaded content for malware before it is processed or served
+#[async_trait]
+pub trait Scanner: Send + Sync {
+    /// Scan everything `source` yields
+    ///
+    /// # Errors
+    /// Returns a `ProcessingError` if the content could not be scanned; this is
+    /// not a verdict, so callers must not treat the content as clean
+    async fn scan(
+        &self,
+        source: &mut (dyn AsyncRead + Send + Unpin),
+    ) -> Result<ScanVerdict, ProcessingError>;
+}
+
+/// Scans content with a `ClamAV` daemon over TCP using the `INSTREAM` command
+#[derive(Debug, Clone)]
+pub struct ClamAvScanner {
+    address: String,
+    timeout: Duration,
+}
+
+impl ClamAvScanner {
+    /// Create a scanner for the clamd listening at `address` (`host:port`)
+    ///
+    /// `timeout` bounds the whole exchange, including streaming the content.
+    pub fn new<A: Into<String>>(address: A, timeout: Duration) -> Self {
+        Self { address: address.into(), timeout }
+    }
+
+    async fn scan_stream(
+        &self,
+        source: &mut (dyn AsyncRead + Send + Unpin),
+    ) -> std::io::Result<Vec<u8>> {
+        let mut stream = TcpStream::connect(&self.address).await?;
+        stream.write_all(b"zINSTREAM\0").await?;
+
+        // Each chunk is prefixed with its length; a zero length ends the stream
+        let mut chunk = vec![0; CHUNK_SIZE];
+        loop {
+            let read = source.read(&mut chunk).await?;
+            if read == 0 {
+                break;
+            }
+            let length = u32::try_from(read).map_err(std::io::Error::other)?;
+            stream.write_all(&length.to_be_bytes()).await?;
+            stream.write_all(&chunk[..read]).await?;
+        }
+        stream.write_all(&0u32.to_be_bytes()).await?;
+
+        let mut reply = Vec::new();
+        stream.read_to_end(&mut reply).await?;
+        Ok(reply)
+    }
+}
+
+#[async_trait]
+impl Scanner for ClamAvScanner {
+    async fn scan(
+        &self,
+        source: &mut (dyn AsyncRead + Send + Unpin),
+    ) -> Result<ScanVerdict, ProcessingError> {
+        let scan_failed = |message: String| ProcessingError::ScanFailed { message };
+
+        let reply = tokio::time::timeout(self.timeout, self.scan_stream(source))
+            .await
+            .map_err(|_| scan_failed(format!("clamd timed out after {}s", self.timeout.as_secs())))?
+            .map_err(|e| scan_failed(format!("clamd at {}: {e}", self.address)))?;
+
+        parse_reply(&reply)
+    }
+}
+
+/// Interpret clamd's reply, e.g. `stream: OK` or `stream: Eicar-Signature FOUND`
+fn parse_reply(reply: &[u8]) -> Result<ScanVerdict, ProcessingError> {
+    let reply = String::from_utf8_lossy(reply);
+    let reply = reply.trim_end_matches('\0').trim();
+    let result = reply.strip_prefix("stream:").map_or(reply, str::trim);
+
+    if result == "OK" {
+        Ok(ScanVerdict::Clean)
+    } else if let Some(signature) = result.strip_suffix(" FOUND") {
+        Ok(ScanVerdict::Infected { signature: signature.trim().to_string() })
+    } else {
+        Err(ProcessingError::ScanFailed { message: format!("unexpected clamd reply: {reply}") })
+    }
+}
+
+/// A scanner together with where infected files are moved out of storage
+#[derive(Clone)]
+pub struct MalwareScan {
+    scanner: Arc<dyn Scanner>,
+    quarantine_dir: PathBuf,
+}
+
+impl MalwareScan {
+    /// Scan with `scanner`, quarantining infected files under `quarantine_dir`
+    pub fn new<P: Into<PathBuf>>(scanner: Arc<dyn Scanner>, quarantine_dir: P) -> Self {
+        Self { scanner, quarantine_dir: quarantine_dir.into() }
+    }
+
+    /// The scanner content is checked with
+    #[must_use]
+    pub fn scanner(&self) -> &dyn Scanner {
+        &*self.scanner
+    }
+
+    /// Directory infected files are kept in for inspection
+    #[must_use]
+    pub fn quarantine_dir(&self) -> &Path {
+        &self.quarantine_dir
+    }
+
+    /// Copy infected content into the quarantine directory, named by its hash
+    ///
+    /// # Errors
+    /// Returns an I/O error if the quarantine directory or file cannot be written
+    pub async fn quarantine(
+        &self,
+        content_hash: &ContentHash,
+        source: &mut (dyn AsyncRead + Send + Unpin),
+    ) -> std::io::Result<PathBuf> {
+        tokio::fs::create_dir_all(&self.quarantine_dir).await?;
+
+        let path = self.quarantine_dir.join(content_hash.as_str());
+        let mut file = tokio::fs::File::create(&path).await?;
+        tokio::io::copy(source, &mut file).await?;
+        file.flush().await?;
+        Ok(path)
+    }
+}
+
+impl std::fmt::Debug for MalwareScan {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MalwareScan")
+            .field("quarantine_dir", &self.quarantine_dir)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    /// Serve one `INSTREAM` request, replying `FOUND` if the content contains `marker`
+    async fn fake_clamd(marker: &'static [u8]) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut command = [0; 10];
+            socket.read_exact(&mut command).await.unwrap();
+            assert_eq!(&command, b"zINSTREAM\0");
+
+            let mut content = Vec::new();
+            loop {
+                let length = socket.read_u32().await.unwrap() as usize;
+                if length == 0 {
+                    break;
+                }
+                let mut chunk = vec![0; length];
+                socket.read_exact(&mut chunk).await.unwrap();
+                content.extend_from_slice(&chunk);
+            }
+
+            let reply: &[u8] = if content.windows(marker.len()).any(|window| window == marker) {
+                b"stream: Eicar-Test-Signature FOUND\0"
+            } else {
+                b"stream: OK\0"
+            };
+            socket.write_all(reply).await.unwrap();
+        });
+
+        address
+    }
+
+    #[test]
+    fn test_parse_reply() {
+        assert_eq!(parse_reply(b"stream: OK\0").unwrap(), ScanVerdict::Clean);
+        assert_eq!(
+            parse_reply(b"stream: Win.Test.EICAR_HDB-1 FOUND\0").unwrap(),
+            ScanVerdict::Infected { signature: "Win.Test.EICAR_HDB-1".to_string() }
+        );
+        assert!(matches!(
+            parse_reply(b"INSTREAM size limit exceeded. ERROR\0"),
+            Err(ProcessingError::ScanFailed { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_clamav_scanner_reports_clean_content() {
+        let scanner = ClamAvScanner::new(fake_clamd(b"EICAR").await, Duration::from_secs(5));
+
+        let verdict = scanner.scan(&mut &b"just a recipe photo"[..]).await.unwrap();
+
+        assert_eq!(verdict, ScanVerdict::Clean);
+    }
+
+    #[tokio::test]
+    async fn test_clamav_scanner_streams_content_in_chunks() {
+        let scanner = ClamAvScanner::new(fake_clamd(b"EICAR").await, Duration::from_secs(5));
+        let mut content = vec![b'x'; CHUNK_SIZE * 2];
+        content.extend_from_slice(b"EICAR");
+
+        let verdict = scanner.scan(&mut content.as_slice()).await.unwrap();
+
+        assert_eq!(
+            verdict,
+            ScanVerdict::Infected { signature: "Eicar-Test-Signature".to_string() }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_clamav_scanner_fails_when_clamd_is_unreachable() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        drop(listener);
+
+        let scanner = ClamAvScanner::new(address, Duration::from_secs(5));
+        let result = scanner.scan(&mut &b"content"[..]).await;
+
+        assert!(matches!(result, Err(ProcessingError::ScanFailed { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_quarantine_copies_content_named_by_hash() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let scan = MalwareScan::new(
+            Arc::new(ClamAvScanner::new("127.0.0.1:3310", Duration::from_secs(1))),
+            temp_dir.path().join("quarantine"),
+        );
+        let hash = ContentHash::new(&"a".repeat(64)).unwrap();
+
+        let path = scan.quarantine(&hash, &mut &b"infected"[..]).await.unwrap();
+
+        assert_eq!(path, temp_dir.path().join("quarantine").join("a".repeat(64)));
+        assert_eq!(tokio::fs::read(path).await.unwrap(), b"infected");
+    }
+}
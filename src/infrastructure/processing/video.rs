@@ -0,0 +1,262 @@
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncWriteExt};
+use tokio::process::Command;
+
+use super::{EncodedVariant, ProcessingError};
+
+/// Trailing lines of ffmpeg's stderr kept in error messages
+const STDERR_TAIL_LINES: usize = 5;
+
+/// Transcodes uploaded videos by shelling out to ffmpeg
+///
+/// Produces a web-friendly MP4 (H.264/AAC with `faststart`, so playback can begin
+/// before the download finishes) and a JPEG poster frame. The original is spooled
+/// into a per-job scratch directory first because ffmpeg needs a seekable input.
+#[derive(Debug, Clone)]
+pub struct VideoProcessor {
+    ffmpeg_path: PathBuf,
+    work_dir: PathBuf,
+    timeout: Duration,
+}
+
+impl VideoProcessor {
+    /// Create a processor using the given ffmpeg binary and scratch directory
+    ///
+    /// `timeout` bounds each ffmpeg invocation; the process is killed when it elapses.
+    pub fn new<P: Into<PathBuf>, W: Into<PathBuf>>(
+        ffmpeg_path: P,
+        work_dir: W,
+        timeout: Duration,
+    ) -> Self {
+        Self { ffmpeg_path: ffmpeg_path.into(), work_dir: work_dir.into(), timeout }
+    }
+
+    /// Names of the variants [`Self::process`] produces
+    pub const VARIANT_NAMES: [&'static str; 2] = ["mp4", "poster"];
+
+    /// Check whether the given source type is a video
+    #[must_use]
+    pub fn supports(content_type: &str) -> bool {
+        content_type.starts_with("video/")
+    }
+
+    /// Transcode a video into its web MP4 and poster frame variants
+    ///
+    /// # Errors
+    /// Returns a `ProcessingError` if the source cannot be spooled to disk, ffmpeg
+    /// cannot be started, exits unsuccessfully, or exceeds the timeout
+    pub async fn process(
+        &self,
+        source: &mut (dyn AsyncRead + Send + Unpin),
+    ) -> Result<Vec<EncodedVariant>, ProcessingError> {
+        let job_dir = self.work_dir.join(format!("video-{}", uuid::Uuid::new_v4().simple()));
+        tokio::fs::create_dir_all(&job_dir).await?;
+
+        let result = self.process_in(&job_dir, source).await;
+
+        if let Err(e) = tokio::fs::remove_dir_all(&job_dir).await {
+            tracing::warn!("Failed to remove video scratch directory {:?}: {}", job_dir, e);
+        }
+
+        result
+    }
+
+    async fn process_in(
+        &self,
+        job_dir: &Path,
+        source: &mut (dyn AsyncRead + Send + Unpin),
+    ) -> Result<Vec<EncodedVariant>, ProcessingError> {
+        let input = job_dir.join("source");
+        let mut file = tokio::fs::File::create(&input).await?;
+        tokio::io::copy(source, &mut file).await?;
+        file.flush().await?;
+        drop(file);
+
+        let mp4 = job_dir.join("web.mp4");
+        self.run_ffmpeg(transcode_args(&input, &mp4)).await?;
+
+        let poster = job_dir.join("poster.jpg");
+        self.run_ffmpeg(poster_args(&input, &poster)).await?;
+
+        Ok(vec![
+            EncodedVariant {
+                name: "mp4",
+                content_type: "video/mp4",
+                data: tokio::fs::read(&mp4).await?,
+            },
+            EncodedVariant {
+                name: "poster",
+                content_type: "image/jpeg",
+                data: tokio::fs::read(&poster).await?,
+            },
+        ])
+    }
+
+    async fn run_ffmpeg(&self, args: Vec<OsString>) -> Result<(), ProcessingError> {
+        let command_failed = |message: String| ProcessingError::CommandFailed {
+            command: self.ffmpeg_path.display().to_string(),
+            message,
+        };
+
+        let child = Command::new(&self.ffmpeg_path)
+            .args(args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| command_failed(format!("failed to start: {e}")))?;
+
+        // Dropping the future on timeout drops the child, which kills it
+        let output = tokio::time::timeout(self.timeout, child.wait_with_output())
+            .await
+            .map_err(|_| command_failed(format!("timed out after {}s", self.timeout.as_secs())))?
+            .map_err(|e| command_failed(e.to_string()))?;
+
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(command_failed(format!("{}: {}", output.status, stderr_tail(&output.stderr))))
+        }
+    }
+}
+
+/// Arguments for an H.264/AAC MP4 playable in every mainstream browser
+fn transcode_args(input: &Path, output: &Path) -> Vec<OsString> {
+    let mut args: Vec<OsString> = ["-hide_banner", "-nostdin", "-y", "-i"].map(Into::into).into();
+    args.push(input.into());
+    args.extend(
+        [
+            "-map",
+            "0:v:0",
+            "-map",
+            "0:a:0?",
+            "-c:v",
+            "libx264",
+            "-preset",
+            "veryfast",
+            "-crf",
+            "23",
+            "-pix_fmt",
+            "yuv420p",
+            // yuv420p needs even dimensions
+            "-vf",
+            "scale=trunc(iw/2)*2:trunc(ih/2)*2",
+            "-c:a",
+            "aac",
+            "-b:a",
+            "128k",
+            "-movflags",
+            "+faststart",
+            "-f",
+            "mp4",
+        ]
+        .map(OsString::from),
+    );
+    args.push(output.into());
+    args
+}
+
+/// Arguments for a single representative JPEG frame
+fn poster_args(input: &Path, output: &Path) -> Vec<OsString> {
+    let mut args: Vec<OsString> = ["-hide_banner", "-nostdin", "-y", "-i"].map(Into::into).into();
+    args.push(input.into());
+    args.extend(
+        ["-vf", "thumbnail", "-frames:v", "1", "-q:v", "2", "-f", "image2"].map(OsString::from),
+    );
+    args.push(output.into());
+    args
+}
+
+/// Last few lines of ffmpeg's stderr, where it reports the actual failure
+fn stderr_tail(stderr: &[u8]) -> String {
+    let stderr = String::from_utf8_lossy(stderr);
+    let lines: Vec<&str> = stderr.lines().filter(|line| !line.trim().is_empty()).collect();
+    let tail = lines[lines.len().saturating_sub(STDERR_TAIL_LINES)..].join("; ");
+
+    if tail.is_empty() {
+        "no output".to_string()
+    } else {
+        tail
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_supports() {
+        assert!(VideoProcessor::supports("video/mp4"));
+        assert!(VideoProcessor::supports("video/quicktime"));
+        assert!(!VideoProcessor::supports("image/png"));
+        assert!(!VideoProcessor::supports("audio/mpeg"));
+    }
+
+    #[test]
+    fn test_transcode_args() {
+        let args = transcode_args(Path::new("/tmp/in"), Path::new("/tmp/out.mp4"));
+        let args: Vec<&str> = args.iter().map(|a| a.to_str().unwrap()).collect();
+
+        assert_eq!(&args[3..5], ["-i", "/tmp/in"]);
+        assert!(args.windows(2).any(|w| w == ["-c:v", "libx264"]));
+        assert!(args.windows(2).any(|w| w == ["-movflags", "+faststart"]));
+        assert_eq!(args.last(), Some(&"/tmp/out.mp4"));
+    }
+
+    #[test]
+    fn test_stderr_tail() {
+        let stderr = b"line 1\nline 2\n\nline 3\nline 4\nline 5\nline 6\n";
+
+        assert_eq!(stderr_tail(stderr), "line 2; line 3; line 4; line 5; line 6");
+        assert_eq!(stderr_tail(b""), "no output");
+    }
+
+    #[tokio::test]
+    async fn test_missing_ffmpeg_reports_error_and_cleans_up() {
+        let temp_dir = TempDir::new().unwrap();
+        let processor = VideoProcessor::new(
+            temp_dir.path().join("no-such-ffmpeg"),
+            temp_dir.path().join("work"),
+            Duration::from_secs(5),
+        );
+
+        let result = processor.process(&mut &b"not really a video"[..]).await;
+
+        match result {
+            Err(ProcessingError::CommandFailed { message, .. }) => {
+                assert!(message.contains("failed to start"));
+            }
+            other => panic!("Expected CommandFailed, got {other:?}"),
+        }
+        let leftovers = std::fs::read_dir(temp_dir.path().join("work")).unwrap().count();
+        assert_eq!(leftovers, 0);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_ffmpeg_failure_includes_stderr() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        let fake_ffmpeg = temp_dir.path().join("ffmpeg");
+        std::fs::write(&fake_ffmpeg, "#!/bin/sh\necho 'source: Invalid data found' >&2\nexit 1\n")
+            .unwrap();
+        std::fs::set_permissions(&fake_ffmpeg, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let processor =
+            VideoProcessor::new(fake_ffmpeg, temp_dir.path().join("work"), Duration::from_secs(5));
+        let result = processor.process(&mut &b"garbage"[..]).await;
+
+        match result {
+            Err(ProcessingError::CommandFailed { message, .. }) => {
+                assert!(message.contains("Invalid data found"), "{message}");
+            }
+            other => panic!("Expected CommandFailed, got {other:?}"),
+        }
+    }
+}
@@ -0,0 +1,89 @@
+use std::time::Duration;
+
+use ::redis::AsyncCommands;
+use async_trait::async_trait;
+
+use super::RedisConnection;
+use crate::infrastructure::cache::{Cache, CacheError};
+
+/// Prefix of every cache key
+const KEY_PREFIX: &str = "media-service:cache";
+
+/// Cache kept in Redis, shared by every replica
+#[derive(Debug)]
+pub struct RedisCache {
+    connection: RedisConnection,
+    ttl_ms: u64,
+}
+
+impl RedisCache {
+    /// Cache entries on the server at `url` for `ttl`, connecting on first use
+    ///
+    /// # Errors
+    /// Returns an error if `url` is not a valid Redis URL
+    pub fn new(url: &str, ttl: Duration) -> Result<Self, ::redis::RedisError> {
+        Ok(Self {
+            connection: RedisConnection::new(url)?,
+            ttl_ms: u64::try_from(ttl.as_millis()).unwrap_or(u64::MAX).max(1),
+        })
+    }
+}
+
+fn key(key: &str) -> String {
+    format!("{KEY_PREFIX}:{key}")
+}
+
+fn unavailable(e: &::redis::RedisError) -> CacheError {
+    CacheError(e.to_string())
+}
+
+#[async_trait]
+impl Cache for RedisCache {
+    async fn get(&self, key: &str) -> Result<Option<String>, CacheError> {
+        let mut connection = self.connection.get().await.map_err(|e| unavailable(&e))?;
+        connection.get(self::key(key)).await.map_err(|e| unavailable(&e))
+    }
+
+    async fn get_many(&self, keys: &[String]) -> Result<Vec<Option<String>>, CacheError> {
+        if keys.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut connection = self.connection.get().await.map_err(|e| unavailable(&e))?;
+        let keys: Vec<String> = keys.iter().map(|k| key(k)).collect();
+        connection.mget(keys).await.map_err(|e| unavailable(&e))
+    }
+
+    async fn set(&self, key: &str, value: String) -> Result<(), CacheError> {
+        let mut connection = self.connection.get().await.map_err(|e| unavailable(&e))?;
+        connection.pset_ex(self::key(key), value, self.ttl_ms).await.map_err(|e| unavailable(&e))
+    }
+
+    async fn remove(&self, keys: &[String]) -> Result<(), CacheError> {
+        if keys.is_empty() {
+            return Ok(());
+        }
+        let mut connection = self.connection.get().await.map_err(|e| unavailable(&e))?;
+        let keys: Vec<String> = keys.iter().map(|k| key(k)).collect();
+        connection.del(keys).await.map_err(|e| unavailable(&e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    #[ignore = "Requires a running Redis server at REDIS_URL"]
+    async fn test_cache_with_real_redis() {
+        let url = std::env::var("REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1:6379".into());
+        let cache = RedisCache::new(&url, Duration::from_mins(1)).unwrap();
+        let first = format!("test:{}", uuid::Uuid::new_v4());
+        let second = format!("test:{}", uuid::Uuid::new_v4());
+
+        cache.set(&first, "cached".to_string()).await.unwrap();
+        let keys = [first.clone(), second];
+        assert_eq!(cache.get_many(&keys).await.unwrap(), [Some("cached".to_string()), None]);
+        cache.remove(&keys).await.unwrap();
+        assert_eq!(cache.get(&first).await.unwrap(), None);
+    }
+}
@@ -0,0 +1,94 @@
+//! Redis, for state shared by every replica
+//!
+//! Connections are made on first use and re-established by the connection
+//! manager. While Redis cannot be reached, new connection attempts are spaced
+//! out so callers fall back quickly instead of each waiting on a connect.
+
+mod cache;
+mod rate_limit;
+
+pub use cache::RedisCache;
+pub use rate_limit::RedisRateLimitStore;
+
+use std::{
+    sync::{Mutex, PoisonError},
+    time::{Duration, Instant},
+};
+
+use ::redis::{
+    aio::{ConnectionManager, ConnectionManagerConfig},
+    Client, RedisError,
+};
+use tokio::sync::OnceCell;
+
+/// Longest a command or connection attempt may take before the caller falls back
+const TIMEOUT: Duration = Duration::from_millis(250);
+
+/// How long after a failed connection attempt the next one is made
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Lazily connected handle to a Redis server
+pub struct RedisConnection {
+    client: Client,
+    connection: OnceCell<ConnectionManager>,
+    next_attempt: Mutex<Option<Instant>>,
+}
+
+impl std::fmt::Debug for RedisConnection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RedisConnection")
+            .field("server", &self.client.get_connection_info().addr)
+            .field("connected", &self.connection.initialized())
+            .finish_non_exhaustive()
+    }
+}
+
+impl RedisConnection {
+    /// Handle to the server at `url`, connecting on first use
+    ///
+    /// # Errors
+    /// Returns an error if `url` is not a valid Redis URL
+    pub fn new(url: &str) -> Result<Self, RedisError> {
+        Ok(Self {
+            client: Client::open(url)?,
+            connection: OnceCell::new(),
+            next_attempt: Mutex::new(None),
+        })
+    }
+
+    /// The connection, connecting first if needed
+    ///
+    /// # Errors
+    /// Returns an error if the server cannot be reached, or a recent attempt failed
+    pub async fn get(&self) -> Result<ConnectionManager, RedisError> {
+        if let Some(connection) = self.connection.get() {
+            return Ok(connection.clone());
+        }
+
+        let now = Instant::now();
+        let next_attempt = *self.next_attempt.lock().unwrap_or_else(PoisonError::into_inner);
+        if next_attempt.is_some_and(|next_attempt| now < next_attempt) {
+            return Err(RedisError::from((
+                ::redis::ErrorKind::IoError,
+                "Redis unreachable, waiting before the next connection attempt",
+            )));
+        }
+
+        let config = ConnectionManagerConfig::new()
+            .set_connection_timeout(TIMEOUT)
+            .set_response_timeout(TIMEOUT)
+            .set_number_of_retries(1);
+        let connected = self
+            .connection
+            .get_or_try_init(|| ConnectionManager::new_with_config(self.client.clone(), config))
+            .await;
+        match connected {
+            Ok(connection) => Ok(connection.clone()),
+            Err(e) => {
+                *self.next_attempt.lock().unwrap_or_else(PoisonError::into_inner) =
+                    Some(now + RECONNECT_INTERVAL);
+                Err(e)
+            }
+        }
+    }
+}
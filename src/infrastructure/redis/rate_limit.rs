@@ -0,0 +1,132 @@
+use std::time::{Duration, Instant};
+
+use ::redis::Script;
+use async_trait::async_trait;
+
+use super::RedisConnection;
+use crate::presentation::middleware::{
+    rate_limit::RateLimitInfo, RateLimitDecision, RateLimitStore, RateLimitStoreError,
+};
+
+/// Prefix of every budget key
+const KEY_PREFIX: &str = "media-service:rate-limit";
+
+/// Token bucket holding `limit` tokens, refilled evenly over `window`
+///
+/// Returns whether a token was taken, the tokens left, and the milliseconds
+/// until the next token when none was. Uses the server's clock, so replicas
+/// with skewed clocks still agree.
+const TOKEN_BUCKET: &str = r"
+local limit = tonumber(ARGV[1])
+local window_ms = tonumber(ARGV[2])
+local time = redis.call('TIME')
+local now = tonumber(time[1]) * 1000 + math.floor(tonumber(time[2]) / 1000)
+
+local bucket = redis.call('HMGET', KEYS[1], 'tokens', 'updated')
+local tokens = tonumber(bucket[1]) or limit
+local updated = tonumber(bucket[2]) or now
+tokens = math.min(limit, tokens + (now - updated) * limit / window_ms)
+
+local allowed = 0
+local wait_ms = 0
+if tokens >= 1 then
+  tokens = tokens - 1
+  allowed = 1
+else
+  wait_ms = math.ceil((1 - tokens) * window_ms / limit)
+end
+
+redis.call('HSET', KEYS[1], 'tokens', tostring(tokens), 'updated', now)
+redis.call('PEXPIRE', KEYS[1], window_ms)
+return {allowed, math.floor(tokens), wait_ms}
+";
+
+/// Rate limit budgets kept in Redis as token buckets, shared by every replica
+#[derive(Debug)]
+pub struct RedisRateLimitStore {
+    connection: RedisConnection,
+    script: Script,
+}
+
+impl RedisRateLimitStore {
+    /// Store budgets on the server at `url`, connecting on first use
+    ///
+    /// # Errors
+    /// Returns an error if `url` is not a valid Redis URL
+    pub fn new(url: &str) -> Result<Self, ::redis::RedisError> {
+        Ok(Self { connection: RedisConnection::new(url)?, script: Script::new(TOKEN_BUCKET) })
+    }
+}
+
+#[async_trait]
+impl RateLimitStore for RedisRateLimitStore {
+    async fn acquire(
+        &self,
+        key: &str,
+        limit: u32,
+        window: Duration,
+    ) -> Result<RateLimitDecision, RateLimitStoreError> {
+        if limit == 0 {
+            return Ok(RateLimitDecision::Denied { retry_after: window });
+        }
+
+        let unavailable = |e: ::redis::RedisError| RateLimitStoreError(e.to_string());
+        let mut connection = self.connection.get().await.map_err(unavailable)?;
+        let window_ms = u64::try_from(window.as_millis()).unwrap_or(u64::MAX).max(1);
+        let (allowed, remaining, wait_ms): (u8, u32, u64) = self
+            .script
+            .key(format!("{KEY_PREFIX}:{key}"))
+            .arg(limit)
+            .arg(window_ms)
+            .invoke_async(&mut connection)
+            .await
+            .map_err(unavailable)?;
+
+        if allowed == 0 {
+            return Ok(RateLimitDecision::Denied { retry_after: Duration::from_millis(wait_ms) });
+        }
+        Ok(RateLimitDecision::Allowed(RateLimitInfo {
+            limit,
+            remaining,
+            reset_time: Instant::now() + window,
+            retry_after: None,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_unreachable_server_is_an_error() {
+        // Nothing listens on port 1
+        let store = RedisRateLimitStore::new("redis://127.0.0.1:1").unwrap();
+
+        let result = store.acquire("public:127.0.0.1", 10, Duration::from_mins(1)).await;
+        assert!(result.is_err());
+        // Later calls fail fast instead of connecting again
+        let started = Instant::now();
+        assert!(store.acquire("public:127.0.0.1", 10, Duration::from_mins(1)).await.is_err());
+        assert!(started.elapsed() < Duration::from_millis(50));
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires a running Redis server at REDIS_URL"]
+    async fn test_token_bucket_with_real_redis() {
+        let url = std::env::var("REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1:6379".into());
+        let store = RedisRateLimitStore::new(&url).unwrap();
+        let key = format!("test:{}", uuid::Uuid::new_v4());
+
+        for remaining in (0..3).rev() {
+            match store.acquire(&key, 3, Duration::from_mins(1)).await.unwrap() {
+                RateLimitDecision::Allowed(info) => assert_eq!(info.remaining, remaining),
+                RateLimitDecision::Denied { .. } => panic!("request within the limit denied"),
+            }
+        }
+        assert!(matches!(
+            store.acquire(&key, 3, Duration::from_mins(1)).await.unwrap(),
+            RateLimitDecision::Denied { .. }
+        ));
+    }
+}
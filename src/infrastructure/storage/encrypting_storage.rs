@@ -0,0 +1,418 @@
+use async_trait::async_trait;
+use aws_lc_rs::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use base64::Engine;
+use bytes::Bytes;
+use std::collections::HashMap;
+use std::io;
+use std::sync::Arc;
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio_util::io::StreamReader;
+
+use super::{FileMetadata, FileStorage, MirroredStorage, StorageError};
+use crate::domain::value_objects::ContentHash;
+use crate::infrastructure::config::EncryptionConfig;
+
+/// Marks an encrypted file, followed by the format version
+const MAGIC: &[u8; 4] = b"MMS\x01";
+
+/// Plaintext bytes sealed per chunk
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Bytes of the GCM tag appended to every chunk
+const TAG_LEN: usize = 16;
+
+/// Random bytes starting every chunk nonce; the rest is the chunk counter and last flag
+const NONCE_PREFIX_LEN: usize = NONCE_LEN - 5;
+
+/// Longest header: magic, key ID length, key ID and nonce prefix
+const MAX_HEADER_LEN: u64 = (MAGIC.len() + 1 + u8::MAX as usize + NONCE_PREFIX_LEN) as u64;
+
+/// AES-256 keys by key ID, and the ID of the key new files are encrypted with
+pub struct EncryptionKeys {
+    active: String,
+    keys: HashMap<String, LessSafeKey>,
+}
+
+impl EncryptionKeys {
+    /// Load the keys listed in `keys` and in `keys_file`
+    ///
+    /// Both hold `id:base64` entries, separated by commas or newlines. Keys no
+    /// longer used for new files stay listed so files encrypted with them can
+    /// still be read.
+    ///
+    /// # Errors
+    /// Returns `Encryption` if the keys file cannot be read, an entry is not a
+    /// 32-byte key, or the active key is not among the keys
+    pub fn from_config(config: &EncryptionConfig) -> Result<Self, StorageError> {
+        let mut entries = config.keys.expose().clone();
+        if let Some(path) = &config.keys_file {
+            let file = std::fs::read_to_string(path).map_err(|e| StorageError::Encryption {
+                message: format!("Failed to read encryption keys file {path}: {e}"),
+            })?;
+            entries.push('\n');
+            entries.push_str(&file);
+        }
+
+        let mut keys = HashMap::new();
+        for entry in entries.split([',', '\n']).map(str::trim).filter(|entry| !entry.is_empty()) {
+            let invalid = |reason: &str| StorageError::Encryption {
+                message: format!("Invalid encryption key entry: {reason}"),
+            };
+            let (id, key) = entry.split_once(':').ok_or_else(|| invalid("expected id:base64"))?;
+            if id.is_empty() || id.len() > usize::from(u8::MAX) {
+                return Err(invalid("key ID must be 1 to 255 bytes"));
+            }
+            let key = base64::engine::general_purpose::STANDARD
+                .decode(key)
+                .map_err(|_| invalid(&format!("key {id} is not base64")))?;
+            let key = UnboundKey::new(&AES_256_GCM, &key)
+                .map_err(|_| invalid(&format!("key {id} is not 32 bytes")))?;
+            keys.insert(id.to_string(), LessSafeKey::new(key));
+        }
+
+        if !keys.contains_key(&config.key_id) {
+            return Err(StorageError::Encryption {
+                message: format!("Active encryption key {:?} is not configured", config.key_id),
+            });
+        }
+        Ok(Self { active: config.key_id.clone(), keys })
+    }
+
+    fn get(&self, key_id: &str) -> Result<&LessSafeKey, StorageError> {
+        self.keys.get(key_id).ok_or_else(|| StorageError::Encryption {
+            message: format!("File is encrypted with unknown key {key_id:?}"),
+        })
+    }
+}
+
+/// Storage decorator that encrypts files with AES-256-GCM before they reach the backend
+///
+/// Files are sealed in 64 KiB chunks as they stream through, each chunk
+/// authenticated together with the file's content hash, so damaged, truncated
+/// or swapped files fail to read. Every file starts with a header naming the key
+/// it was encrypted with; new files use the active key, and older keys keep
+/// decrypting the files written with them. Files stored before encryption was
+/// enabled are read as they are. Direct download URLs are never handed out,
+/// since the backend only holds ciphertext. Range reads decrypt from the start
+/// of the file.
+pub struct EncryptingStorage {
+    inner: Arc<dyn FileStorage>,
+    keys: Arc<EncryptionKeys>,
+}
+
+impl EncryptingStorage {
+    /// Wrap a storage backend so that files are encrypted with `keys`
+    #[must_use]
+    pub fn new(inner: Arc<dyn FileStorage>, keys: Arc<EncryptionKeys>) -> Self {
+        Self { inner, keys }
+    }
+}
+
+#[async_trait]
+impl FileStorage for EncryptingStorage {
+    async fn store(
+        &self,
+        hash: &ContentHash,
+        reader: &mut (dyn AsyncRead + Send + Unpin),
+    ) -> Result<String, StorageError> {
+        let mut nonce_prefix = [0; NONCE_PREFIX_LEN];
+        aws_lc_rs::rand::fill(&mut nonce_prefix).map_err(|_| StorageError::Encryption {
+            message: "Failed to generate a nonce".to_string(),
+        })?;
+        let mut header = MAGIC.to_vec();
+        header.push(self.keys.active.len() as u8);
+        header.extend_from_slice(self.keys.active.as_bytes());
+        header.extend_from_slice(&nonce_prefix);
+
+        let key = self.keys.get(&self.keys.active)?;
+        let sealer = Sealer { key, aad: hash.as_str().as_bytes(), nonce_prefix, counter: 0 };
+        let chunks = futures_util::stream::try_unfold(
+            (reader, sealer, Some(header), false),
+            |(reader, mut sealer, header, done)| async move {
+                if let Some(header) = header {
+                    return Ok::<_, io::Error>(Some((
+                        Bytes::from(header),
+                        (reader, sealer, None, done),
+                    )));
+                }
+                if done {
+                    return Ok(None);
+                }
+                let mut chunk = vec![0; CHUNK_SIZE];
+                let read = read_full(reader, &mut chunk).await?;
+                chunk.truncate(read);
+                // Only the final chunk is short, possibly empty
+                let last = read < CHUNK_SIZE;
+                sealer.seal(&mut chunk, last)?;
+                Ok(Some((Bytes::from(chunk), (reader, sealer, None, last))))
+            },
+        );
+        let mut encrypted = StreamReader::new(Box::pin(chunks));
+        self.inner.store(hash, &mut encrypted).await
+    }
+
+    async fn retrieve(
+        &self,
+        hash: &ContentHash,
+    ) -> Result<Box<dyn AsyncRead + Send + Unpin>, StorageError> {
+        let mut reader = self.inner.retrieve(hash).await?;
+        let opened = match read_header(&mut reader).await? {
+            Opening::Plain(start) => {
+                return Ok(Box::new(AsyncReadExt::chain(io::Cursor::new(start), reader)));
+            }
+            Opening::Encrypted(header) => header,
+        };
+
+        let keys = self.keys.clone();
+        self.keys.get(&opened.key_id)?;
+        let aad = hash.as_str().as_bytes().to_vec();
+        let chunks = futures_util::stream::try_unfold(
+            (reader, opened, 0u32, false),
+            move |(mut reader, opened, counter, done)| {
+                let keys = keys.clone();
+                let aad = aad.clone();
+                async move {
+                    let mut chunk = vec![0; CHUNK_SIZE + TAG_LEN];
+                    let read = read_full(&mut reader, &mut chunk).await?;
+                    if done {
+                        return match read {
+                            0 => Ok(None),
+                            _ => Err(encryption_error("Data follows the final encrypted chunk")),
+                        };
+                    }
+                    if read == 0 {
+                        return Err(encryption_error("Encrypted file is truncated"));
+                    }
+                    chunk.truncate(read);
+                    let last = read < CHUNK_SIZE + TAG_LEN;
+                    let key = keys.get(&opened.key_id).map_err(to_io_error)?;
+                    let nonce = chunk_nonce(opened.nonce_prefix, counter, last);
+                    let plaintext = key
+                        .open_in_place(nonce, Aad::from(&aad), &mut chunk)
+                        .map_err(|_| encryption_error("Encrypted file failed authentication"))?;
+                    let plaintext = Bytes::copy_from_slice(plaintext);
+                    let counter = counter
+                        .checked_add(1)
+                        .ok_or_else(|| encryption_error("Encrypted file has too many chunks"))?;
+                    Ok(Some((plaintext, (reader, opened, counter, last))))
+                }
+            },
+        );
+        Ok(Box::new(StreamReader::new(Box::pin(chunks))))
+    }
+
+    async fn exists(&self, hash: &ContentHash) -> Result<bool, StorageError> {
+        self.inner.exists(hash).await
+    }
+
+    async fn delete(&self, hash: &ContentHash) -> Result<bool, StorageError> {
+        self.inner.delete(hash).await
+    }
+
+    fn get_path(&self, hash: &ContentHash) -> String {
+        self.inner.get_path(hash)
+    }
+
+    /// Reports the size of the decrypted content
+    async fn metadata(&self, hash: &ContentHash) -> Result<FileMetadata, StorageError> {
+        let mut metadata = self.inner.metadata(hash).await?;
+        let mut start = self.inner.retrieve_range(hash, 0, MAX_HEADER_LEN).await?;
+        if let Opening::Encrypted(header) = read_header(&mut start).await? {
+            let sealed = metadata.size.saturating_sub(header.len);
+            let chunk = (CHUNK_SIZE + TAG_LEN) as u64;
+            let remainder = (sealed % chunk).saturating_sub(TAG_LEN as u64);
+            metadata.size = sealed / chunk * CHUNK_SIZE as u64 + remainder;
+        }
+        Ok(metadata)
+    }
+
+    async fn health_check(&self) -> Result<(), StorageError> {
+        self.inner.health_check().await
+    }
+
+    fn mirrored(&self) -> Option<&MirroredStorage> {
+        self.inner.mirrored()
+    }
+}
+
+/// Seals the chunks of one file in order
+struct Sealer<'a> {
+    key: &'a LessSafeKey,
+    aad: &'a [u8],
+    nonce_prefix: [u8; NONCE_PREFIX_LEN],
+    counter: u32,
+}
+
+impl Sealer<'_> {
+    fn seal(&mut self, chunk: &mut Vec<u8>, last: bool) -> io::Result<()> {
+        let nonce = chunk_nonce(self.nonce_prefix, self.counter, last);
+        self.key
+            .seal_in_place_append_tag(nonce, Aad::from(self.aad), chunk)
+            .map_err(|_| encryption_error("Failed to encrypt chunk"))?;
+        self.counter = self
+            .counter
+            .checked_add(1)
+            .ok_or_else(|| encryption_error("File is too large to encrypt"))?;
+        Ok(())
+    }
+}
+
+/// The header of an encrypted file
+struct Header {
+    key_id: String,
+    nonce_prefix: [u8; NONCE_PREFIX_LEN],
+    len: u64,
+}
+
+/// How a stored file begins
+enum Opening {
+    Encrypted(Header),
+    /// Not encrypted; holds the bytes already read from the start of the file
+    Plain(Vec<u8>),
+}
+
+async fn read_header<R: AsyncRead + Unpin + ?Sized>(reader: &mut R) -> io::Result<Opening> {
+    let mut magic = vec![0; MAGIC.len()];
+    let read = read_full(reader, &mut magic).await?;
+    if magic[..read] != MAGIC[..] {
+        magic.truncate(read);
+        return Ok(Opening::Plain(magic));
+    }
+
+    let key_id_len = reader.read_u8().await? as usize;
+    let mut key_id = vec![0; key_id_len];
+    reader.read_exact(&mut key_id).await?;
+    let mut nonce_prefix = [0; NONCE_PREFIX_LEN];
+    reader.read_exact(&mut nonce_prefix).await?;
+
+    let key_id = String::from_utf8(key_id)
+        .map_err(|_| encryption_error("Encryption key ID is not UTF-8"))?;
+    let len = (MAGIC.len() + 1 + key_id_len + NONCE_PREFIX_LEN) as u64;
+    Ok(Opening::Encrypted(Header { key_id, nonce_prefix, len }))
+}
+
+/// Read until `buf` is full or the reader ends, returning the bytes read
+async fn read_full<R: AsyncRead + Unpin + ?Sized>(
+    reader: &mut R,
+    buf: &mut [u8],
+) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]).await? {
+            0 => break,
+            read => filled += read,
+        }
+    }
+    Ok(filled)
+}
+
+/// The nonce of one chunk: the file's random prefix, the chunk counter and
+/// whether the chunk is the last, so chunks cannot be reordered or dropped
+fn chunk_nonce(prefix: [u8; NONCE_PREFIX_LEN], counter: u32, last: bool) -> Nonce {
+    let mut nonce = [0; NONCE_LEN];
+    nonce[..NONCE_PREFIX_LEN].copy_from_slice(&prefix);
+    nonce[NONCE_PREFIX_LEN..NONCE_LEN - 1].copy_from_slice(&counter.to_be_bytes());
+    nonce[NONCE_LEN - 1] = u8::from(last);
+    Nonce::assume_unique_for_key(nonce)
+}
+
+fn encryption_error(message: &str) -> io::Error {
+    tracing::error!("{}", message);
+    to_io_error(StorageError::Encryption { message: message.to_string() })
+}
+
+/// Carry a storage error through `io::Error`; `StorageError::from` unwraps it again
+fn to_io_error(error: StorageError) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::storage::{generate_content_hash, InMemoryStorage};
+
+    fn config(key_id: &str, keys: &str) -> EncryptionConfig {
+        EncryptionConfig {
+            enabled: true,
+            key_id: key_id.to_string(),
+            keys: keys.into(),
+            keys_file: None,
+        }
+    }
+
+    fn key(byte: u8) -> String {
+        base64::engine::general_purpose::STANDARD.encode([byte; 32])
+    }
+
+    fn storage(inner: &Arc<InMemoryStorage>, key_id: &str) -> EncryptingStorage {
+        let keys = format!("2025:{},2026:{}", key(1), key(2));
+        let keys = EncryptionKeys::from_config(&config(key_id, &keys)).unwrap();
+        EncryptingStorage::new(inner.clone(), Arc::new(keys))
+    }
+
+    async fn read(storage: &dyn FileStorage, hash: &ContentHash) -> Result<Vec<u8>, StorageError> {
+        let mut content = Vec::new();
+        storage.retrieve(hash).await?.read_to_end(&mut content).await?;
+        Ok(content)
+    }
+
+    #[tokio::test]
+    async fn test_files_round_trip_across_key_rotation() {
+        let inner = Arc::new(InMemoryStorage::new());
+        let content: Vec<u8> = (0..CHUNK_SIZE * 2 + 10).map(|i| i as u8).collect();
+        let hash = generate_content_hash(&content).unwrap();
+        let exact = vec![7; CHUNK_SIZE];
+        let exact_hash = generate_content_hash(&exact).unwrap();
+
+        let before_rotation = storage(&inner, "2025");
+        before_rotation.store(&hash, &mut &content[..]).await.unwrap();
+        before_rotation.store(&exact_hash, &mut &exact[..]).await.unwrap();
+        let stored = read(inner.as_ref(), &hash).await.unwrap();
+        assert!(stored.starts_with(b"MMS\x01\x042025"));
+        assert!(!stored.windows(16).any(|window| window == &content[100..116]));
+
+        let after_rotation = storage(&inner, "2026");
+        assert_eq!(read(&after_rotation, &hash).await.unwrap(), content);
+        assert_eq!(read(&after_rotation, &exact_hash).await.unwrap(), exact);
+        assert_eq!(after_rotation.metadata(&hash).await.unwrap().size, content.len() as u64);
+        let mut range = Vec::new();
+        let mut reader = after_rotation.retrieve_range(&hash, 70_000, 4).await.unwrap();
+        reader.read_to_end(&mut range).await.unwrap();
+        assert_eq!(range, content[70_000..70_004]);
+    }
+
+    #[tokio::test]
+    async fn test_tampered_truncated_and_unencrypted_files() {
+        let inner = Arc::new(InMemoryStorage::new());
+        let storage = storage(&inner, "2026");
+        let hash = generate_content_hash(b"secret photo").unwrap();
+        storage.store(&hash, &mut &b"secret photo"[..]).await.unwrap();
+        let mut sealed = read(inner.as_ref(), &hash).await.unwrap();
+
+        // Stored under another hash, the same ciphertext fails authentication
+        let swapped = generate_content_hash(b"other photo").unwrap();
+        inner.store(&swapped, &mut &sealed[..]).await.unwrap();
+        let error = read(&storage, &swapped).await.unwrap_err();
+        assert!(matches!(error, StorageError::Encryption { .. }));
+
+        sealed.truncate(sealed.len() - 1);
+        let truncated = generate_content_hash(b"truncated").unwrap();
+        inner.store(&truncated, &mut &sealed[..]).await.unwrap();
+        assert!(matches!(read(&storage, &truncated).await, Err(StorageError::Encryption { .. })));
+
+        // Files stored before encryption was enabled are read as they are
+        let legacy = generate_content_hash(b"legacy").unwrap();
+        inner.store(&legacy, &mut &b"legacy"[..]).await.unwrap();
+        assert_eq!(read(&storage, &legacy).await.unwrap(), b"legacy");
+        assert_eq!(storage.metadata(&legacy).await.unwrap().size, 6);
+    }
+
+    #[test]
+    fn test_keys_are_validated() {
+        let valid = format!("2026:{}", key(2));
+        assert!(EncryptionKeys::from_config(&config("2026", &valid)).is_ok());
+        assert!(EncryptionKeys::from_config(&config("2027", &valid)).is_err());
+        assert!(EncryptionKeys::from_config(&config("2026", "2026:c2hvcnQ=")).is_err());
+        assert!(EncryptionKeys::from_config(&config("2026", "2026")).is_err());
+    }
+}
@@ -1,21 +1,87 @@
 use async_trait::async_trait;
-use std::path::PathBuf;
+use sha2::{Digest, Sha256};
+use std::io::{ErrorKind, SeekFrom};
+use std::path::{Path, PathBuf};
 use tokio::fs;
-use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncSeekExt, AsyncWriteExt, BufReader};
 
 use super::{utils::content_addressable_path, FileMetadata, FileStorage, StorageError};
 use crate::domain::value_objects::ContentHash;
+use crate::infrastructure::config::StorageDurability;
 
 /// Filesystem-based storage implementation using content-addressable storage
 #[derive(Clone)]
 pub struct FilesystemStorage {
     base_path: PathBuf,
+    temp_dir: Option<PathBuf>,
+    durability: StorageDurability,
 }
 
 impl FilesystemStorage {
     /// Create a new filesystem storage instance
     pub fn new(base_path: impl Into<PathBuf>) -> Self {
-        Self { base_path: base_path.into() }
+        Self { base_path: base_path.into(), temp_dir: None, durability: StorageDurability::None }
+    }
+
+    /// Sync stored files, and optionally their directories, to disk before returning
+    #[must_use]
+    pub fn with_durability(mut self, durability: StorageDurability) -> Self {
+        self.durability = durability;
+        self
+    }
+
+    /// Write incoming files to `temp_dir` before moving them into place
+    ///
+    /// Without a temp directory, files are written next to their final path.
+    #[must_use]
+    pub fn with_temp_dir(mut self, temp_dir: impl Into<PathBuf>) -> Self {
+        self.temp_dir = Some(temp_dir.into());
+        self
+    }
+
+    /// Warn when the temp directory and base path are on different filesystems
+    ///
+    /// Moves between them cannot be atomic renames and fall back to copying, which
+    /// is slower and needs space for two copies while it runs. Paths that do not
+    /// exist yet are checked through their nearest existing ancestor.
+    pub fn warn_if_cross_device(&self) {
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+
+            let device = |path: &Path| {
+                path.ancestors().find_map(|dir| std::fs::metadata(dir).ok()).map(|m| m.dev())
+            };
+
+            let Some(temp_dir) = &self.temp_dir else { return };
+            let (Some(base), Some(temp)) = (device(&self.base_path), device(temp_dir)) else {
+                return;
+            };
+
+            if base != temp {
+                tracing::warn!(
+                    "Storage temp path {} and base path {} are on different filesystems; \
+                     stored files will be copied instead of renamed",
+                    temp_dir.display(),
+                    self.base_path.display()
+                );
+            }
+        }
+    }
+
+    /// Pick a temporary path for a file being stored at `file_path`
+    ///
+    /// Every call gets its own path, so concurrent stores of the same content
+    /// never write to or move each other's temporary file.
+    async fn temp_path_for(&self, file_path: &Path) -> Result<PathBuf, StorageError> {
+        let temp_name = format!("{}.tmp", uuid::Uuid::new_v4().simple());
+        match &self.temp_dir {
+            Some(temp_dir) => {
+                fs::create_dir_all(temp_dir).await?;
+                Ok(temp_dir.join(temp_name))
+            }
+            None => Ok(file_path.with_extension(temp_name)),
+        }
     }
 
     /// Get the full filesystem path for a content hash
@@ -35,10 +101,11 @@ impl FilesystemStorage {
 
 #[async_trait]
 impl FileStorage for FilesystemStorage {
-    async fn store<R>(&self, hash: &ContentHash, mut reader: R) -> Result<String, StorageError>
-    where
-        R: AsyncRead + Send + Unpin,
-    {
+    async fn store(
+        &self,
+        hash: &ContentHash,
+        reader: &mut (dyn AsyncRead + Send + Unpin),
+    ) -> Result<String, StorageError> {
         let file_path = self.full_path(hash);
 
         // Check if file already exists (deduplication)
@@ -50,26 +117,23 @@ impl FileStorage for FilesystemStorage {
         // Ensure directory structure exists
         self.ensure_directory(&file_path).await?;
 
-        // Create temporary file first, then rename (atomic operation)
-        let temp_path = file_path.with_extension("tmp");
-
-        {
-            let mut file = fs::File::create(&temp_path).await?;
-            let mut buffer = [0u8; 8192];
+        // Create temporary file first, then move it into place
+        let temp_path = self.temp_path_for(&file_path).await?;
 
-            loop {
-                let n = reader.read(&mut buffer).await?;
-                if n == 0 {
-                    break;
-                }
-                file.write_all(&buffer[..n]).await?;
+        let result = async {
+            write_temp_file(&temp_path, reader, self.durability).await?;
+            move_file(&temp_path, &file_path).await?;
+            if self.durability == StorageDurability::FileAndDirectory {
+                sync_parent_directory(&file_path).await?;
             }
-
-            file.flush().await?;
+            Ok(())
         }
+        .await;
 
-        // Atomic rename
-        fs::rename(&temp_path, &file_path).await?;
+        if let Err(e) = result {
+            let _ = fs::remove_file(&temp_path).await;
+            return Err(e);
+        }
 
         tracing::info!("Stored file at path: {}", file_path.display());
         Ok(file_path.to_string_lossy().to_string())
@@ -93,6 +157,26 @@ impl FileStorage for FilesystemStorage {
         Ok(Box::new(reader))
     }
 
+    async fn retrieve_range(
+        &self,
+        hash: &ContentHash,
+        start: u64,
+        length: u64,
+    ) -> Result<Box<dyn AsyncRead + Send + Unpin>, StorageError> {
+        let file_path = self.full_path(hash);
+
+        if !file_path.exists() {
+            return Err(StorageError::FileNotFound {
+                path: file_path.to_string_lossy().to_string(),
+            });
+        }
+
+        let mut file = fs::File::open(&file_path).await?;
+        file.seek(SeekFrom::Start(start)).await?;
+
+        Ok(Box::new(BufReader::new(file).take(length)))
+    }
+
     async fn exists(&self, hash: &ContentHash) -> Result<bool, StorageError> {
         let file_path = self.full_path(hash);
         Ok(file_path.exists())
@@ -189,6 +273,106 @@ impl FileStorage for FilesystemStorage {
     }
 }
 
+/// Stream `reader` into a new file at `path`, syncing it if `durability` asks for it
+async fn write_temp_file(
+    path: &Path,
+    reader: &mut (dyn AsyncRead + Send + Unpin),
+    durability: StorageDurability,
+) -> Result<(), StorageError> {
+    let mut file = fs::File::create(path).await?;
+    let mut buffer = [0u8; 8192];
+
+    loop {
+        let n = reader.read(&mut buffer).await?;
+        if n == 0 {
+            break;
+        }
+        file.write_all(&buffer[..n]).await?;
+    }
+
+    file.flush().await?;
+    if durability != StorageDurability::None {
+        file.sync_all().await?;
+    }
+    Ok(())
+}
+
+/// Persist a rename or creation by syncing the directory that holds `path`
+async fn sync_parent_directory(path: &Path) -> Result<(), StorageError> {
+    #[cfg(unix)]
+    if let Some(parent) = path.parent() {
+        fs::File::open(parent).await?.sync_all().await?;
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+    Ok(())
+}
+
+/// Move a file into place, atomically when both paths share a filesystem
+///
+/// A rename across mounts fails with `EXDEV`; the file is then copied next to its
+/// destination, synced to disk, verified against the source and renamed over the
+/// destination, so readers never see a partial file. The source is removed last.
+async fn move_file(from: &Path, to: &Path) -> Result<(), StorageError> {
+    match fs::rename(from, to).await {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == ErrorKind::CrossesDevices => {
+            tracing::debug!("Rename from {} crosses filesystems, copying instead", from.display());
+            copy_across_devices(from, to).await
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+async fn copy_across_devices(from: &Path, to: &Path) -> Result<(), StorageError> {
+    let partial = to.with_extension("partial");
+
+    let result = async {
+        fs::copy(from, &partial).await?;
+        fs::File::open(&partial).await?.sync_all().await?;
+
+        let expected = file_digest(from).await?;
+        let actual = file_digest(&partial).await?;
+        if expected != actual {
+            return Err(StorageError::HashMismatch {
+                expected: hex::encode(expected),
+                actual: hex::encode(actual),
+            });
+        }
+
+        fs::rename(&partial, to).await?;
+        Ok(())
+    }
+    .await;
+
+    if result.is_err() {
+        let _ = fs::remove_file(&partial).await;
+        return result;
+    }
+
+    if let Err(e) = fs::remove_file(from).await {
+        tracing::warn!("Failed to remove {} after copying it: {}", from.display(), e);
+    }
+    Ok(())
+}
+
+/// SHA-256 of a file's contents
+async fn file_digest(path: &Path) -> Result<[u8; 32], StorageError> {
+    let mut file = fs::File::open(path).await?;
+    let mut hasher = Sha256::new();
+    let mut buffer = [0u8; 8192];
+
+    loop {
+        let n = file.read(&mut buffer).await?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buffer[..n]);
+    }
+
+    Ok(hasher.finalize().into())
+}
+
 impl FilesystemStorage {
     /// Clean up empty directories (best effort, ignore errors)
     async fn cleanup_empty_directories(&self, mut dir_path: &std::path::Path) {
@@ -239,10 +423,10 @@ mod tests {
         let hash = create_test_hash();
 
         let data = b"test file content";
-        let reader = Cursor::new(data);
+        let mut reader = Cursor::new(data);
 
         // Store file
-        let stored_path = storage.store(&hash, reader).await.unwrap();
+        let stored_path = storage.store(&hash, &mut reader).await.unwrap();
         assert!(stored_path.contains("ab/cd/ef"));
 
         // Verify file exists
@@ -264,10 +448,10 @@ mod tests {
         let hash = create_test_hash();
 
         let data = b"test file content for metadata";
-        let reader = Cursor::new(data);
+        let mut reader = Cursor::new(data);
 
         // Store file
-        storage.store(&hash, reader).await.unwrap();
+        storage.store(&hash, &mut reader).await.unwrap();
 
         // Get metadata
         let metadata = storage.metadata(&hash).await.unwrap();
@@ -282,10 +466,10 @@ mod tests {
         let hash = create_test_hash();
 
         let data = b"test file content";
-        let reader = Cursor::new(data);
+        let mut reader = Cursor::new(data);
 
         // Store file
-        storage.store(&hash, reader).await.unwrap();
+        storage.store(&hash, &mut reader).await.unwrap();
         assert!(storage.exists(&hash).await.unwrap());
 
         // Delete file
@@ -307,12 +491,12 @@ mod tests {
         let data = b"test file content";
 
         // Store file first time
-        let reader1 = Cursor::new(data);
-        let path1 = storage.store(&hash, reader1).await.unwrap();
+        let mut reader1 = Cursor::new(data);
+        let path1 = storage.store(&hash, &mut reader1).await.unwrap();
 
         // Store same file again (should deduplicate)
-        let reader2 = Cursor::new(data);
-        let path2 = storage.store(&hash, reader2).await.unwrap();
+        let mut reader2 = Cursor::new(data);
+        let path2 = storage.store(&hash, &mut reader2).await.unwrap();
 
         assert_eq!(path1, path2);
         assert!(storage.exists(&hash).await.unwrap());
@@ -435,4 +619,81 @@ mod tests {
             assert!(result.is_ok());
         }
     }
+
+    #[tokio::test]
+    async fn test_filesystem_storage_retrieve_range() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = FilesystemStorage::new(temp_dir.path());
+        let hash = create_test_hash();
+        storage.store(&hash, &mut Cursor::new(b"0123456789")).await.unwrap();
+
+        let mut range = Vec::new();
+        storage.retrieve_range(&hash, 3, 4).await.unwrap().read_to_end(&mut range).await.unwrap();
+        assert_eq!(range, b"3456");
+
+        let missing = ContentHash::new(&"f".repeat(64)).unwrap();
+        assert!(matches!(
+            storage.retrieve_range(&missing, 0, 1).await,
+            Err(StorageError::FileNotFound { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_store_with_each_durability() {
+        for durability in
+            [StorageDurability::None, StorageDurability::File, StorageDurability::FileAndDirectory]
+        {
+            let temp_dir = TempDir::new().unwrap();
+            let storage = FilesystemStorage::new(temp_dir.path().join("media"))
+                .with_temp_dir(temp_dir.path().join("tmp"))
+                .with_durability(durability);
+            let hash = create_test_hash();
+
+            storage.store(&hash, &mut Cursor::new(b"durable")).await.unwrap();
+
+            let mut stored = Vec::new();
+            storage.retrieve(&hash).await.unwrap().read_to_end(&mut stored).await.unwrap();
+            assert_eq!(stored, b"durable", "{durability:?}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_store_through_separate_temp_dir() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = FilesystemStorage::new(temp_dir.path().join("media"))
+            .with_temp_dir(temp_dir.path().join("tmp"));
+        let hash = create_test_hash();
+
+        storage.store(&hash, &mut Cursor::new(b"staged elsewhere")).await.unwrap();
+
+        let mut stored = Vec::new();
+        storage.retrieve(&hash).await.unwrap().read_to_end(&mut stored).await.unwrap();
+        assert_eq!(stored, b"staged elsewhere");
+        assert_eq!(std::fs::read_dir(temp_dir.path().join("tmp")).unwrap().count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_copy_across_devices_moves_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let from = temp_dir.path().join("source.tmp");
+        let to = temp_dir.path().join("final");
+        std::fs::write(&from, b"copied content").unwrap();
+
+        copy_across_devices(&from, &to).await.unwrap();
+
+        assert_eq!(std::fs::read(&to).unwrap(), b"copied content");
+        assert!(!from.exists());
+        assert!(!to.with_extension("partial").exists());
+    }
+
+    #[tokio::test]
+    async fn test_copy_across_devices_keeps_source_on_failure() {
+        let temp_dir = TempDir::new().unwrap();
+        let from = temp_dir.path().join("source.tmp");
+        let to = temp_dir.path().join("missing-dir").join("final");
+        std::fs::write(&from, b"content").unwrap();
+
+        assert!(copy_across_devices(&from, &to).await.is_err());
+        assert!(from.exists());
+    }
 }
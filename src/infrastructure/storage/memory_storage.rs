@@ -0,0 +1,184 @@
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::SystemTime;
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+use super::{
+    utils::{content_addressable_path, detect_content_type},
+    FileMetadata, FileStorage, StorageError,
+};
+use crate::domain::value_objects::ContentHash;
+
+/// A stored object held in memory
+#[derive(Debug, Clone)]
+struct StoredObject {
+    content: Vec<u8>,
+    last_modified: SystemTime,
+}
+
+/// In-memory storage implementation
+///
+/// Contents are lost when the process exits, so this backend is intended for
+/// local development, tests, and ephemeral preview environments.
+#[derive(Clone, Default)]
+pub struct InMemoryStorage {
+    objects: Arc<RwLock<HashMap<String, StoredObject>>>,
+}
+
+impl InMemoryStorage {
+    /// Create a new, empty in-memory storage instance
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of stored objects
+    ///
+    /// # Panics
+    /// Panics if the internal lock is poisoned
+    pub fn len(&self) -> usize {
+        self.objects.read().expect("storage lock poisoned").len()
+    }
+
+    /// Whether the storage holds no objects
+    ///
+    /// # Panics
+    /// Panics if the internal lock is poisoned
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn lock_error() -> StorageError {
+        StorageError::IoError { message: "In-memory storage lock poisoned".to_string() }
+    }
+}
+
+#[async_trait]
+impl FileStorage for InMemoryStorage {
+    async fn store(
+        &self,
+        hash: &ContentHash,
+        reader: &mut (dyn AsyncRead + Send + Unpin),
+    ) -> Result<String, StorageError> {
+        let key = content_addressable_path(hash);
+
+        let mut content = Vec::new();
+        reader.read_to_end(&mut content).await?;
+
+        let mut objects = self.objects.write().map_err(|_| Self::lock_error())?;
+        objects
+            .entry(key.clone())
+            .or_insert(StoredObject { content, last_modified: SystemTime::now() });
+
+        Ok(key)
+    }
+
+    async fn retrieve(
+        &self,
+        hash: &ContentHash,
+    ) -> Result<Box<dyn AsyncRead + Send + Unpin>, StorageError> {
+        let key = content_addressable_path(hash);
+        let objects = self.objects.read().map_err(|_| Self::lock_error())?;
+
+        match objects.get(&key) {
+            Some(object) => Ok(Box::new(std::io::Cursor::new(object.content.clone()))),
+            None => Err(StorageError::FileNotFound { path: key }),
+        }
+    }
+
+    async fn exists(&self, hash: &ContentHash) -> Result<bool, StorageError> {
+        let objects = self.objects.read().map_err(|_| Self::lock_error())?;
+        Ok(objects.contains_key(&content_addressable_path(hash)))
+    }
+
+    async fn delete(&self, hash: &ContentHash) -> Result<bool, StorageError> {
+        let mut objects = self.objects.write().map_err(|_| Self::lock_error())?;
+        Ok(objects.remove(&content_addressable_path(hash)).is_some())
+    }
+
+    fn get_path(&self, hash: &ContentHash) -> String {
+        content_addressable_path(hash)
+    }
+
+    async fn metadata(&self, hash: &ContentHash) -> Result<FileMetadata, StorageError> {
+        let key = content_addressable_path(hash);
+        let objects = self.objects.read().map_err(|_| Self::lock_error())?;
+
+        match objects.get(&key) {
+            Some(object) => Ok(FileMetadata {
+                size: object.content.len() as u64,
+                content_type: Some(detect_content_type(&object.content, None)),
+                last_modified: object.last_modified,
+            }),
+            None => Err(StorageError::FileNotFound { path: key }),
+        }
+    }
+
+    async fn health_check(&self) -> Result<(), StorageError> {
+        self.objects.read().map(|_| ()).map_err(|_| Self::lock_error())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_hash() -> ContentHash {
+        ContentHash::new("abcdef1234567890abcdef1234567890abcdef1234567890abcdef1234567890")
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_store_and_retrieve() {
+        let storage = InMemoryStorage::new();
+        let hash = test_hash();
+
+        let path = storage.store(&hash, &mut &b"test content"[..]).await.unwrap();
+        assert_eq!(path, content_addressable_path(&hash));
+
+        let mut reader = storage.retrieve(&hash).await.unwrap();
+        let mut content = Vec::new();
+        reader.read_to_end(&mut content).await.unwrap();
+        assert_eq!(content, b"test content");
+    }
+
+    #[tokio::test]
+    async fn test_store_deduplicates() {
+        let storage = InMemoryStorage::new();
+        let hash = test_hash();
+
+        storage.store(&hash, &mut &b"first"[..]).await.unwrap();
+        storage.store(&hash, &mut &b"second"[..]).await.unwrap();
+
+        assert_eq!(storage.len(), 1);
+        assert_eq!(storage.metadata(&hash).await.unwrap().size, 5);
+    }
+
+    #[tokio::test]
+    async fn test_exists_and_delete() {
+        let storage = InMemoryStorage::new();
+        let hash = test_hash();
+
+        assert!(!storage.exists(&hash).await.unwrap());
+        assert!(!storage.delete(&hash).await.unwrap());
+
+        storage.store(&hash, &mut &b"test content"[..]).await.unwrap();
+        assert!(storage.exists(&hash).await.unwrap());
+        assert!(storage.delete(&hash).await.unwrap());
+        assert!(storage.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_retrieve_and_metadata_missing() {
+        let storage = InMemoryStorage::new();
+        let hash = test_hash();
+
+        assert!(matches!(storage.retrieve(&hash).await, Err(StorageError::FileNotFound { .. })));
+        assert!(matches!(storage.metadata(&hash).await, Err(StorageError::FileNotFound { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_health_check() {
+        assert!(InMemoryStorage::new().health_check().await.is_ok());
+    }
+}
@@ -0,0 +1,144 @@
+use async_trait::async_trait;
+use std::io;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{ready, Context, Poll};
+use tokio::io::{AsyncRead, ReadBuf};
+
+use super::{FileMetadata, FileStorage, MirroredStorage, StorageError};
+use crate::domain::value_objects::ContentHash;
+use crate::infrastructure::business_metrics::BusinessMetrics;
+
+/// Storage decorator that counts the bytes written to a backend
+///
+/// Each successful `store` adds the bytes it consumed to
+/// `media_storage_bytes_stored_total` under the `backend` label. Everything else
+/// passes straight through to the wrapped storage.
+pub struct MeteredStorage {
+    inner: Arc<dyn FileStorage>,
+    backend: &'static str,
+    metrics: BusinessMetrics,
+}
+
+impl MeteredStorage {
+    /// Wrap a storage backend so that stored bytes are counted under `backend`
+    #[must_use]
+    pub fn new(
+        inner: Arc<dyn FileStorage>,
+        backend: &'static str,
+        metrics: BusinessMetrics,
+    ) -> Self {
+        Self { inner, backend, metrics }
+    }
+}
+
+#[async_trait]
+impl FileStorage for MeteredStorage {
+    async fn store(
+        &self,
+        hash: &ContentHash,
+        reader: &mut (dyn AsyncRead + Send + Unpin),
+    ) -> Result<String, StorageError> {
+        let mut counting = CountingReader { inner: reader, bytes: 0 };
+        let path = self.inner.store(hash, &mut counting).await?;
+        self.metrics.bytes_stored(self.backend, counting.bytes);
+        Ok(path)
+    }
+
+    async fn retrieve(
+        &self,
+        hash: &ContentHash,
+    ) -> Result<Box<dyn AsyncRead + Send + Unpin>, StorageError> {
+        self.inner.retrieve(hash).await
+    }
+
+    async fn retrieve_range(
+        &self,
+        hash: &ContentHash,
+        start: u64,
+        length: u64,
+    ) -> Result<Box<dyn AsyncRead + Send + Unpin>, StorageError> {
+        self.inner.retrieve_range(hash, start, length).await
+    }
+
+    async fn exists(&self, hash: &ContentHash) -> Result<bool, StorageError> {
+        self.inner.exists(hash).await
+    }
+
+    async fn delete(&self, hash: &ContentHash) -> Result<bool, StorageError> {
+        self.inner.delete(hash).await
+    }
+
+    fn get_path(&self, hash: &ContentHash) -> String {
+        self.inner.get_path(hash)
+    }
+
+    async fn download_url(
+        &self,
+        hash: &ContentHash,
+        filename: &str,
+        content_type: &str,
+        expires_in: std::time::Duration,
+    ) -> Result<Option<String>, StorageError> {
+        self.inner.download_url(hash, filename, content_type, expires_in).await
+    }
+
+    async fn metadata(&self, hash: &ContentHash) -> Result<FileMetadata, StorageError> {
+        self.inner.metadata(hash).await
+    }
+
+    async fn health_check(&self) -> Result<(), StorageError> {
+        self.inner.health_check().await
+    }
+
+    fn mirrored(&self) -> Option<&MirroredStorage> {
+        self.inner.mirrored()
+    }
+}
+
+/// Reader that counts the bytes passing through
+struct CountingReader<'a> {
+    inner: &'a mut (dyn AsyncRead + Send + Unpin),
+    bytes: u64,
+}
+
+impl AsyncRead for CountingReader<'_> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        let filled_before = buf.filled().len();
+        ready!(Pin::new(&mut *this.inner).poll_read(cx, buf))?;
+        this.bytes += (buf.filled().len() - filled_before) as u64;
+        Poll::Ready(Ok(()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::storage::{generate_content_hash, InMemoryStorage};
+    use metrics_exporter_prometheus::PrometheusBuilder;
+
+    #[tokio::test]
+    async fn test_store_counts_bytes_by_backend() {
+        let recorder = PrometheusBuilder::new().build_recorder();
+        let handle = recorder.handle();
+        let storage = MeteredStorage::new(
+            Arc::new(InMemoryStorage::new()),
+            "memory",
+            BusinessMetrics::new(true),
+        );
+        let hash = generate_content_hash(b"metered content").unwrap();
+
+        let _guard = metrics::set_default_local_recorder(&recorder);
+        storage.store(&hash, &mut &b"metered content"[..]).await.unwrap();
+
+        assert!(handle
+            .render()
+            .contains(r#"media_storage_bytes_stored_total{backend="memory"} 15"#));
+        assert!(storage.exists(&hash).await.unwrap());
+    }
+}
@@ -0,0 +1,260 @@
+use async_trait::async_trait;
+use std::sync::Arc;
+use tokio::io::AsyncRead;
+
+use super::{FileMetadata, FileStorage, StorageError, VerifyingReader};
+use crate::domain::value_objects::ContentHash;
+
+/// What repairing the copies of one file did
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplicaRepair {
+    /// Both backends have the file
+    InSync,
+    /// The file was copied from the primary to the replica
+    CopiedToReplica,
+    /// The file was copied back from the replica to the primary
+    CopiedToPrimary,
+    /// Neither backend has the file
+    Lost,
+}
+
+/// Storage decorator that mirrors every file to a secondary backend
+///
+/// Stores complete once the primary has the file; the copy to the replica is
+/// made in the background, read back from the primary and verified against its
+/// hash. Reads of files the primary no longer has are served from the replica,
+/// so losing the primary's copy does not lose the media. Copies that failed or
+/// were lost on either side are restored by [`MirroredStorage::repair`].
+/// Deletes remove both copies. Paths, download URLs and health come from the
+/// primary alone, so an unavailable replica never fails requests.
+pub struct MirroredStorage {
+    primary: Arc<dyn FileStorage>,
+    replica: Arc<dyn FileStorage>,
+}
+
+impl MirroredStorage {
+    /// Mirror files stored in `primary` to `replica`
+    #[must_use]
+    pub fn new(primary: Arc<dyn FileStorage>, replica: Arc<dyn FileStorage>) -> Self {
+        Self { primary, replica }
+    }
+
+    /// Make sure both backends have the file, copying it from whichever does
+    ///
+    /// # Errors
+    /// Returns a `StorageError` if either backend could not be checked, or the
+    /// copy failed, e.g. because the surviving copy no longer matches its hash
+    pub async fn repair(&self, hash: &ContentHash) -> Result<ReplicaRepair, StorageError> {
+        let in_primary = self.primary.exists(hash).await?;
+        let in_replica = self.replica.exists(hash).await?;
+        let repair = match (in_primary, in_replica) {
+            (true, true) => return Ok(ReplicaRepair::InSync),
+            (false, false) => return Ok(ReplicaRepair::Lost),
+            (true, false) => {
+                copy(self.primary.as_ref(), self.replica.as_ref(), hash).await?;
+                ReplicaRepair::CopiedToReplica
+            }
+            (false, true) => {
+                copy(self.replica.as_ref(), self.primary.as_ref(), hash).await?;
+                ReplicaRepair::CopiedToPrimary
+            }
+        };
+
+        let direction = match repair {
+            ReplicaRepair::CopiedToPrimary => "to_primary",
+            _ => "to_replica",
+        };
+        metrics::counter!("storage_replica_repairs_total", "direction" => direction).increment(1);
+        Ok(repair)
+    }
+
+    /// Copy a newly stored file to the replica without holding up the store
+    fn mirror(&self, hash: &ContentHash) {
+        let primary = self.primary.clone();
+        let replica = self.replica.clone();
+        let hash = hash.clone();
+        tokio::spawn(async move {
+            if let Err(e) = copy(primary.as_ref(), replica.as_ref(), &hash).await {
+                tracing::warn!("Failed to copy {} to the replica, left for repair: {}", hash, e);
+                metrics::counter!("storage_replica_write_failures_total").increment(1);
+            }
+        });
+    }
+
+    /// Record a read the primary could not serve, answered by the replica
+    fn fell_back(hash: &ContentHash) {
+        tracing::warn!("{} is missing from primary storage, reading it from the replica", hash);
+        metrics::counter!("storage_replica_fallback_reads_total").increment(1);
+    }
+}
+
+/// Copy a file from one backend to another, verifying it on the way
+async fn copy(
+    from: &dyn FileStorage,
+    to: &dyn FileStorage,
+    hash: &ContentHash,
+) -> Result<(), StorageError> {
+    let reader = from.retrieve(hash).await?;
+    let mut reader = VerifyingReader::new(reader, hash.clone());
+    to.store(hash, &mut reader).await.map(|_| ())
+}
+
+#[async_trait]
+impl FileStorage for MirroredStorage {
+    async fn store(
+        &self,
+        hash: &ContentHash,
+        reader: &mut (dyn AsyncRead + Send + Unpin),
+    ) -> Result<String, StorageError> {
+        let path = self.primary.store(hash, reader).await?;
+        self.mirror(hash);
+        Ok(path)
+    }
+
+    async fn retrieve(
+        &self,
+        hash: &ContentHash,
+    ) -> Result<Box<dyn AsyncRead + Send + Unpin>, StorageError> {
+        match self.primary.retrieve(hash).await {
+            Err(StorageError::FileNotFound { .. }) => {
+                Self::fell_back(hash);
+                self.replica.retrieve(hash).await
+            }
+            result => result,
+        }
+    }
+
+    async fn retrieve_range(
+        &self,
+        hash: &ContentHash,
+        start: u64,
+        length: u64,
+    ) -> Result<Box<dyn AsyncRead + Send + Unpin>, StorageError> {
+        match self.primary.retrieve_range(hash, start, length).await {
+            Err(StorageError::FileNotFound { .. }) => {
+                Self::fell_back(hash);
+                self.replica.retrieve_range(hash, start, length).await
+            }
+            result => result,
+        }
+    }
+
+    async fn exists(&self, hash: &ContentHash) -> Result<bool, StorageError> {
+        if self.primary.exists(hash).await? {
+            return Ok(true);
+        }
+        self.replica.exists(hash).await
+    }
+
+    async fn delete(&self, hash: &ContentHash) -> Result<bool, StorageError> {
+        let deleted = self.primary.delete(hash).await?;
+        match self.replica.delete(hash).await {
+            Ok(replica_deleted) => Ok(deleted || replica_deleted),
+            Err(e) => {
+                tracing::warn!("Failed to delete {} from the replica: {}", hash, e);
+                Ok(deleted)
+            }
+        }
+    }
+
+    fn get_path(&self, hash: &ContentHash) -> String {
+        self.primary.get_path(hash)
+    }
+
+    async fn download_url(
+        &self,
+        hash: &ContentHash,
+        filename: &str,
+        content_type: &str,
+        expires_in: std::time::Duration,
+    ) -> Result<Option<String>, StorageError> {
+        self.primary.download_url(hash, filename, content_type, expires_in).await
+    }
+
+    async fn metadata(&self, hash: &ContentHash) -> Result<FileMetadata, StorageError> {
+        match self.primary.metadata(hash).await {
+            Err(StorageError::FileNotFound { .. }) => self.replica.metadata(hash).await,
+            result => result,
+        }
+    }
+
+    async fn health_check(&self) -> Result<(), StorageError> {
+        self.primary.health_check().await
+    }
+
+    fn mirrored(&self) -> Option<&MirroredStorage> {
+        Some(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::storage::{generate_content_hash, InMemoryStorage};
+    use std::time::Duration;
+    use tokio::io::AsyncReadExt;
+
+    async fn read(storage: &dyn FileStorage, hash: &ContentHash) -> Vec<u8> {
+        let mut content = Vec::new();
+        storage.retrieve(hash).await.unwrap().read_to_end(&mut content).await.unwrap();
+        content
+    }
+
+    async fn wait_for(storage: &dyn FileStorage, hash: &ContentHash) {
+        for _ in 0..100 {
+            if storage.exists(hash).await.unwrap() {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("{hash} never reached the replica");
+    }
+
+    #[tokio::test]
+    async fn test_stores_are_mirrored_and_reads_fall_back_to_the_replica() {
+        let primary = Arc::new(InMemoryStorage::new());
+        let replica = Arc::new(InMemoryStorage::new());
+        let storage = MirroredStorage::new(primary.clone(), replica.clone());
+        let hash = generate_content_hash(b"mirrored").unwrap();
+
+        storage.store(&hash, &mut &b"mirrored"[..]).await.unwrap();
+        wait_for(replica.as_ref(), &hash).await;
+
+        // The primary lost its copy
+        primary.delete(&hash).await.unwrap();
+        assert!(storage.exists(&hash).await.unwrap());
+        assert_eq!(read(&storage, &hash).await, b"mirrored");
+        let mut range = Vec::new();
+        storage.retrieve_range(&hash, 2, 3).await.unwrap().read_to_end(&mut range).await.unwrap();
+        assert_eq!(range, b"rro");
+
+        assert!(storage.delete(&hash).await.unwrap());
+        assert!(!replica.exists(&hash).await.unwrap());
+        assert!(matches!(storage.retrieve(&hash).await, Err(StorageError::FileNotFound { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_repair_restores_missing_copies_from_either_side() {
+        let primary = Arc::new(InMemoryStorage::new());
+        let replica = Arc::new(InMemoryStorage::new());
+        let storage = MirroredStorage::new(primary.clone(), replica.clone());
+        let only_primary = generate_content_hash(b"only primary").unwrap();
+        let only_replica = generate_content_hash(b"only replica").unwrap();
+        let damaged = generate_content_hash(b"damaged").unwrap();
+        primary.store(&only_primary, &mut &b"only primary"[..]).await.unwrap();
+        replica.store(&only_replica, &mut &b"only replica"[..]).await.unwrap();
+        replica.store(&damaged, &mut &b"d4maged"[..]).await.unwrap();
+
+        assert_eq!(storage.repair(&only_primary).await.unwrap(), ReplicaRepair::CopiedToReplica);
+        assert_eq!(read(replica.as_ref(), &only_primary).await, b"only primary");
+        assert_eq!(storage.repair(&only_replica).await.unwrap(), ReplicaRepair::CopiedToPrimary);
+        assert_eq!(read(primary.as_ref(), &only_replica).await, b"only replica");
+        assert_eq!(storage.repair(&only_replica).await.unwrap(), ReplicaRepair::InSync);
+
+        // A damaged copy is not spread to the other side
+        assert!(matches!(storage.repair(&damaged).await, Err(StorageError::HashMismatch { .. })));
+        assert!(!primary.exists(&damaged).await.unwrap());
+        let lost = generate_content_hash(b"lost").unwrap();
+        assert_eq!(storage.repair(&lost).await.unwrap(), ReplicaRepair::Lost);
+    }
+}
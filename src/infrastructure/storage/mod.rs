@@ -1,17 +1,35 @@
 use async_trait::async_trait;
-use tokio::io::AsyncRead;
+use std::sync::Arc;
+use tokio::io::{AsyncRead, AsyncReadExt};
 
+mod encrypting_storage;
 mod filesystem_storage;
+mod memory_storage;
+mod metered_storage;
+mod mirrored_storage;
 pub mod presigned_urls;
+mod s3_storage;
+mod unavailable_storage;
+mod upload_staging;
 pub mod utils;
+mod verifying_storage;
 
+pub use encrypting_storage::{EncryptingStorage, EncryptionKeys};
 pub use filesystem_storage::FilesystemStorage;
+pub use memory_storage::InMemoryStorage;
+pub use metered_storage::MeteredStorage;
+pub use mirrored_storage::{MirroredStorage, ReplicaRepair};
 pub use presigned_urls::{
     PresignedUrlConfig, PresignedUrlError, PresignedUrlService, UploadSession,
 };
+pub use s3_storage::S3Storage;
+pub use unavailable_storage::UnavailableStorage;
+pub use upload_staging::UploadStaging;
 pub use utils::*;
+pub use verifying_storage::{VerifyingReader, VerifyingStorage};
 
 use crate::domain::value_objects::ContentHash;
+use crate::infrastructure::config::{S3StorageConfig, StorageBackend, StorageConfig};
 
 /// Error types for storage operations
 #[derive(Debug, thiserror::Error)]
@@ -30,11 +48,19 @@ pub enum StorageError {
 
     #[error("Hash mismatch: expected {expected}, got {actual}")]
     HashMismatch { expected: String, actual: String },
+
+    #[error("Encryption error: {message}")]
+    Encryption { message: String },
 }
 
 impl From<std::io::Error> for StorageError {
+    /// Unwraps storage errors carried through `io::Error`, e.g. a `HashMismatch`
+    /// raised by a [`VerifyingReader`]
     fn from(error: std::io::Error) -> Self {
-        StorageError::IoError { message: error.to_string() }
+        match error.downcast::<StorageError>() {
+            Ok(error) => error,
+            Err(error) => StorageError::IoError { message: error.to_string() },
+        }
     }
 }
 
@@ -42,9 +68,11 @@ impl From<std::io::Error> for StorageError {
 #[async_trait]
 pub trait FileStorage: Send + Sync {
     /// Store a file with its content hash
-    async fn store<R>(&self, hash: &ContentHash, reader: R) -> Result<String, StorageError>
-    where
-        R: AsyncRead + Send + Unpin;
+    async fn store(
+        &self,
+        hash: &ContentHash,
+        reader: &mut (dyn AsyncRead + Send + Unpin),
+    ) -> Result<String, StorageError>;
 
     /// Retrieve a file by its content hash
     async fn retrieve(
@@ -52,6 +80,21 @@ pub trait FileStorage: Send + Sync {
         hash: &ContentHash,
     ) -> Result<Box<dyn AsyncRead + Send + Unpin>, StorageError>;
 
+    /// Retrieve `length` bytes of a file starting at byte `start`
+    ///
+    /// The default implementation reads and discards everything before `start`;
+    /// backends that can seek should override it.
+    async fn retrieve_range(
+        &self,
+        hash: &ContentHash,
+        start: u64,
+        length: u64,
+    ) -> Result<Box<dyn AsyncRead + Send + Unpin>, StorageError> {
+        let mut reader = self.retrieve(hash).await?;
+        tokio::io::copy(&mut (&mut reader).take(start), &mut tokio::io::sink()).await?;
+        Ok(Box::new(reader.take(length)))
+    }
+
     /// Check if a file exists
     async fn exists(&self, hash: &ContentHash) -> Result<bool, StorageError>;
 
@@ -61,6 +104,21 @@ pub trait FileStorage: Send + Sync {
     /// Get the file path for a given hash (for serving via filesystem)
     fn get_path(&self, hash: &ContentHash) -> String;
 
+    /// A URL clients can download the file from directly, valid for `expires_in`
+    ///
+    /// Downloads through the URL are served as an attachment named `filename`
+    /// with the given content type. The default implementation returns `None`,
+    /// meaning the backend can only serve files through the service.
+    async fn download_url(
+        &self,
+        _hash: &ContentHash,
+        _filename: &str,
+        _content_type: &str,
+        _expires_in: std::time::Duration,
+    ) -> Result<Option<String>, StorageError> {
+        Ok(None)
+    }
+
     /// Get metadata about a stored file
     async fn metadata(&self, hash: &ContentHash) -> Result<FileMetadata, StorageError>;
 
@@ -76,6 +134,67 @@ pub trait FileStorage: Send + Sync {
     /// # Timeout
     /// Implementation should complete within 2 seconds to avoid hanging health checks
     async fn health_check(&self) -> Result<(), StorageError>;
+
+    /// The mirror behind this storage, if files are replicated to a second backend
+    ///
+    /// Decorators forward this to the storage they wrap.
+    fn mirrored(&self) -> Option<&MirroredStorage> {
+        None
+    }
+}
+
+/// Create the storage backend selected by configuration
+///
+/// # Errors
+/// Returns a `StorageError` if the selected backend is misconfigured
+pub fn create_storage(config: &StorageConfig) -> Result<Arc<dyn FileStorage>, StorageError> {
+    // Each backend is encrypted on its own, so the replica is copied plaintext
+    // that can be verified against its hash
+    let keys = if config.encryption.enabled {
+        Some(Arc::new(EncryptionKeys::from_config(&config.encryption)?))
+    } else {
+        None
+    };
+    let encrypted = |storage: Arc<dyn FileStorage>| -> Arc<dyn FileStorage> {
+        match &keys {
+            Some(keys) => Arc::new(EncryptingStorage::new(storage, keys.clone())),
+            None => storage,
+        }
+    };
+
+    let mut storage =
+        encrypted(create_backend(config.backend, &config.base_path, &config.s3, config)?);
+    if config.replica.enabled {
+        let replica = &config.replica;
+        let s3 = S3StorageConfig { bucket: replica.s3_bucket.clone(), ..config.s3.clone() };
+        let replica = encrypted(create_backend(replica.backend, &replica.base_path, &s3, config)?);
+        storage = Arc::new(MirroredStorage::new(storage, replica));
+    }
+
+    if config.verify_on_read {
+        return Ok(Arc::new(VerifyingStorage::new(storage)));
+    }
+    Ok(storage)
+}
+
+/// Create one backend, filesystem storage under `base_path` or S3 storage per `s3`
+fn create_backend(
+    backend: StorageBackend,
+    base_path: &str,
+    s3: &S3StorageConfig,
+    config: &StorageConfig,
+) -> Result<Arc<dyn FileStorage>, StorageError> {
+    Ok(match backend {
+        StorageBackend::Filesystem => {
+            let storage = FilesystemStorage::new(base_path)
+                .with_temp_dir(std::path::Path::new(&config.temp_path).join("storage"))
+                .with_durability(config.durability);
+            storage.warn_if_cross_device();
+            Arc::new(storage)
+        }
+        StorageBackend::S3 => Arc::new(S3Storage::new(s3)?),
+        StorageBackend::Memory => Arc::new(InMemoryStorage::new()),
+    })
 }
 
 /// File metadata information
@@ -89,6 +208,7 @@ pub struct FileMetadata {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::infrastructure::config::StorageDurability;
     use std::time::SystemTime;
 
     #[test]
@@ -188,7 +308,75 @@ mod tests {
         assert!(debug_str.contains("image/png"));
     }
 
-    // Note: FileStorage trait tests would require mock implementations
-    // These are better suited for integration tests with concrete implementations
-    // The trait itself is tested through its implementations (FilesystemStorage)
+    fn create_test_storage_config(backend: StorageBackend) -> StorageConfig {
+        StorageConfig {
+            backend,
+            base_path: "/tmp/media".to_string(),
+            temp_path: "/tmp/media/temp".to_string(),
+            max_file_size: 1024,
+            resumable_upload_expiry_seconds: 86400,
+            durability: StorageDurability::FileAndDirectory,
+            verify_on_read: false,
+            s3: crate::infrastructure::config::S3StorageConfig {
+                endpoint: "http://localhost:9000".to_string(),
+                bucket: "media".to_string(),
+                region: "us-east-1".to_string(),
+                access_key_id: "minioadmin".to_string(),
+                secret_access_key: "minioadmin".into(),
+                force_path_style: true,
+                request_timeout_seconds: 30,
+                multipart_threshold_bytes: 16 * 1024 * 1024,
+            },
+            scanning: crate::infrastructure::config::ScanningConfig::default(),
+            download_redirect: crate::infrastructure::config::DownloadRedirectConfig::default(),
+            upload_fingerprinting:
+                crate::infrastructure::config::UploadFingerprintingConfig::default(),
+            legacy_upload: crate::infrastructure::config::LegacyUploadConfig::default(),
+            integrity_scan: crate::infrastructure::config::IntegrityScanConfig::default(),
+            replica: crate::infrastructure::config::ReplicaConfig::default(),
+            encryption: crate::infrastructure::config::EncryptionConfig::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_storage_for_each_backend() {
+        let hash =
+            ContentHash::new("abcdef1234567890abcdef1234567890abcdef1234567890abcdef1234567890")
+                .unwrap();
+
+        let filesystem = create_storage(&create_test_storage_config(StorageBackend::Filesystem));
+        assert!(filesystem.unwrap().get_path(&hash).starts_with("/tmp/media/ab/cd/ef/"));
+
+        let s3 = create_storage(&create_test_storage_config(StorageBackend::S3));
+        assert_eq!(s3.unwrap().get_path(&hash), content_addressable_path(&hash));
+
+        let memory = create_storage(&create_test_storage_config(StorageBackend::Memory)).unwrap();
+        memory.store(&hash, &mut &b"test"[..]).await.unwrap();
+        assert!(memory.exists(&hash).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_create_storage_with_verify_on_read() {
+        let mut config = create_test_storage_config(StorageBackend::Memory);
+        config.verify_on_read = true;
+        let storage = create_storage(&config).unwrap();
+        let hash = generate_content_hash(b"expected").unwrap();
+        storage.store(&hash, &mut &b"tampered"[..]).await.unwrap();
+
+        let mut reader = storage.retrieve(&hash).await.unwrap();
+        let error = reader.read_to_end(&mut Vec::new()).await.unwrap_err();
+
+        assert!(matches!(StorageError::from(error), StorageError::HashMismatch { .. }));
+    }
+
+    #[test]
+    fn test_create_storage_rejects_invalid_s3_config() {
+        let mut config = create_test_storage_config(StorageBackend::S3);
+        config.s3.bucket = String::new();
+
+        assert!(create_storage(&config).is_err());
+    }
+
+    // The trait itself is tested through its implementations (FilesystemStorage, S3Storage,
+    // InMemoryStorage)
 }
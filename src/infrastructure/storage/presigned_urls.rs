@@ -3,9 +3,11 @@ use hmac::{Hmac, Mac};
 use rand::distr::Alphanumeric;
 use rand::RngExt;
 use sha2::Sha256;
+use std::sync::Arc;
 use std::time::Duration;
 
-use crate::domain::entities::MediaId;
+use crate::domain::entities::{MediaId, PresignedUploadSession};
+use crate::domain::services::{Clock, SystemClock};
 use crate::infrastructure::config::AppConfig;
 
 type HmacSha256 = Hmac<Sha256>;
@@ -28,8 +30,8 @@ impl Default for PresignedUrlConfig {
         Self {
             secret_key: "default-dev-secret-change-in-production".to_string(),
             base_url: "http://localhost:3000".to_string(),
-            default_expiration: Duration::from_secs(15 * 60), // 15 minutes
-            max_file_size: 50 * 1024 * 1024,                  // 50MB
+            default_expiration: Duration::from_mins(15), // 15 minutes
+            max_file_size: 50 * 1024 * 1024,             // 50MB
         }
     }
 }
@@ -49,12 +51,30 @@ pub struct UploadSession {
 #[derive(Clone)]
 pub struct PresignedUrlService {
     config: PresignedUrlConfig,
+    clock: Arc<dyn Clock>,
 }
 
 impl PresignedUrlService {
     /// Create a new presigned URL service
     pub fn new(config: PresignedUrlConfig) -> Self {
-        Self { config }
+        Self { config, clock: Arc::new(SystemClock) }
+    }
+
+    /// Read the time from `clock` when issuing and checking URL expiry
+    #[must_use]
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Largest file an upload session may be created for
+    #[must_use]
+    pub fn max_file_size(&self) -> u64 {
+        self.config.max_file_size
+    }
+
+    fn now(&self) -> DateTime<Utc> {
+        self.clock.now().into()
     }
 
     /// Create from app configuration
@@ -63,7 +83,7 @@ impl PresignedUrlService {
             secret_key: std::env::var("UPLOAD_URL_SECRET")
                 .unwrap_or_else(|_| "default-dev-secret-change-in-production".to_string()),
             base_url: format!("http://{}:{}", app_config.server.host, app_config.server.port),
-            default_expiration: Duration::from_secs(15 * 60),
+            default_expiration: Duration::from_mins(15),
             max_file_size: app_config.middleware.validation.max_file_size_mb * 1024 * 1024,
         };
         Self::new(config)
@@ -89,7 +109,7 @@ impl PresignedUrlService {
         let upload_token = Self::generate_upload_token();
 
         // Calculate expiration
-        let expires_at = Utc::now()
+        let expires_at = self.now()
             + chrono::Duration::from_std(self.config.default_expiration)
                 .map_err(|_| PresignedUrlError::InvalidExpiration)?;
 
@@ -140,7 +160,7 @@ impl PresignedUrlService {
         let expires_at = DateTime::from_timestamp(expires_timestamp, 0)
             .ok_or(PresignedUrlError::InvalidExpiration)?;
 
-        if Utc::now() > expires_at {
+        if self.now() > expires_at {
             return Err(PresignedUrlError::Expired { expired_at: expires_at });
         }
 
@@ -155,6 +175,33 @@ impl PresignedUrlService {
         Ok(())
     }
 
+    /// Verify that `signature` was issued for a persisted upload session
+    ///
+    /// # Errors
+    /// Returns `Expired` once the session has expired and `InvalidSignature` if the
+    /// signature does not match the session's token, media record and file details
+    pub fn verify_session_signature(
+        &self,
+        session: &PresignedUploadSession,
+        signature: &str,
+    ) -> Result<(), PresignedUrlError> {
+        let expires_at: DateTime<Utc> = session.expires_at.into();
+        if self.now() > expires_at {
+            return Err(PresignedUrlError::Expired { expired_at: expires_at });
+        }
+
+        let payload = Self::create_signature_payload(
+            &session.upload_token,
+            session.media_id,
+            &session.filename,
+            &session.content_type,
+            session.expected_size,
+            expires_at,
+        );
+
+        self.verify_signature(&payload, signature)
+    }
+
     /// Generate a unique upload token
     fn generate_upload_token() -> String {
         let random_part: String =
@@ -233,13 +280,14 @@ pub enum PresignedUrlError {
 mod tests {
     use super::*;
     use crate::domain::entities::MediaId;
+    use crate::test_utils::mocks::ManualClock;
 
     fn create_test_service() -> PresignedUrlService {
         let config = PresignedUrlConfig {
             secret_key: "test-secret-key".to_string(),
             base_url: "http://localhost:3000".to_string(),
-            default_expiration: Duration::from_secs(900), // 15 minutes
-            max_file_size: 10 * 1024 * 1024,              // 10MB
+            default_expiration: Duration::from_mins(15), // 15 minutes
+            max_file_size: 10 * 1024 * 1024,             // 10MB
         };
         PresignedUrlService::new(config)
     }
@@ -318,6 +366,62 @@ mod tests {
         assert!(verification.is_ok());
     }
 
+    #[test]
+    fn test_session_signature_verification() {
+        let service = create_test_service();
+        let media_id = MediaId::new(321);
+        let upload = service.create_upload_session(media_id, "a.png", "image/png", 512).unwrap();
+        let signature = upload.upload_url.split("signature=").nth(1).unwrap().split('&').next();
+
+        let mut session = PresignedUploadSession {
+            upload_token: upload.upload_token.clone(),
+            media_id,
+            user_id: crate::domain::entities::UserId::new(),
+            tenant: None,
+            filename: "a.png".to_string(),
+            content_type: "image/png".to_string(),
+            expected_size: 512,
+            created_at: std::time::SystemTime::now(),
+            expires_at: upload.expires_at.into(),
+        };
+        assert!(service.verify_session_signature(&session, signature.unwrap()).is_ok());
+
+        session.expected_size = 1024;
+        assert!(matches!(
+            service.verify_session_signature(&session, signature.unwrap()),
+            Err(PresignedUrlError::InvalidSignature)
+        ));
+    }
+
+    #[test]
+    fn test_session_expires_with_the_clock() {
+        let clock = ManualClock::default();
+        let service = create_test_service().with_clock(Arc::new(clock.clone()));
+        let media_id = MediaId::new(654);
+        let upload = service.create_upload_session(media_id, "b.png", "image/png", 64).unwrap();
+        let signature = upload.upload_url.split("signature=").nth(1).unwrap().split('&').next();
+        let session = PresignedUploadSession {
+            upload_token: upload.upload_token.clone(),
+            media_id,
+            user_id: crate::domain::entities::UserId::new(),
+            tenant: None,
+            filename: "b.png".to_string(),
+            content_type: "image/png".to_string(),
+            expected_size: 64,
+            created_at: clock.now(),
+            expires_at: upload.expires_at.into(),
+        };
+
+        clock.advance(Duration::from_mins(15));
+        assert!(service.verify_session_signature(&session, signature.unwrap()).is_ok());
+
+        clock.advance(Duration::from_secs(1));
+        assert!(matches!(
+            service.verify_session_signature(&session, signature.unwrap()),
+            Err(PresignedUrlError::Expired { .. })
+        ));
+    }
+
     #[test]
     fn test_invalid_signature_rejection() {
         let service = create_test_service();
@@ -0,0 +1,86 @@
+use async_trait::async_trait;
+use tokio::io::AsyncRead;
+
+use super::{utils::content_addressable_path, FileMetadata, FileStorage, StorageError};
+use crate::domain::value_objects::ContentHash;
+
+/// A storage implementation for when the configured backend could not be created
+///
+/// This implementation always fails health checks and storage operations,
+/// allowing the service to start but report proper status through health/readiness endpoints.
+#[derive(Clone)]
+pub struct UnavailableStorage {
+    error_message: String,
+}
+
+impl UnavailableStorage {
+    /// Create a new unavailable storage with an error message
+    #[must_use]
+    pub fn new(error_message: String) -> Self {
+        Self { error_message }
+    }
+
+    fn error(&self) -> StorageError {
+        StorageError::IoError { message: format!("Storage unavailable: {}", self.error_message) }
+    }
+}
+
+#[async_trait]
+impl FileStorage for UnavailableStorage {
+    async fn store(
+        &self,
+        _hash: &ContentHash,
+        _reader: &mut (dyn AsyncRead + Send + Unpin),
+    ) -> Result<String, StorageError> {
+        Err(self.error())
+    }
+
+    async fn retrieve(
+        &self,
+        _hash: &ContentHash,
+    ) -> Result<Box<dyn AsyncRead + Send + Unpin>, StorageError> {
+        Err(self.error())
+    }
+
+    async fn exists(&self, _hash: &ContentHash) -> Result<bool, StorageError> {
+        Err(self.error())
+    }
+
+    async fn delete(&self, _hash: &ContentHash) -> Result<bool, StorageError> {
+        Err(self.error())
+    }
+
+    fn get_path(&self, hash: &ContentHash) -> String {
+        content_addressable_path(hash)
+    }
+
+    async fn metadata(&self, _hash: &ContentHash) -> Result<FileMetadata, StorageError> {
+        Err(self.error())
+    }
+
+    async fn health_check(&self) -> Result<(), StorageError> {
+        Err(self.error())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_unavailable_storage_fails_all_operations() {
+        let storage = UnavailableStorage::new("S3 bucket name is empty".to_string());
+        let hash =
+            ContentHash::new("abcdef1234567890abcdef1234567890abcdef1234567890abcdef1234567890")
+                .unwrap();
+
+        assert!(storage.store(&hash, &mut &b"data"[..]).await.is_err());
+        assert!(storage.retrieve(&hash).await.is_err());
+        assert!(storage.exists(&hash).await.is_err());
+        assert!(storage.delete(&hash).await.is_err());
+        assert!(storage.metadata(&hash).await.is_err());
+
+        let error = storage.health_check().await.unwrap_err();
+        assert!(error.to_string().contains("S3 bucket name is empty"));
+    }
+}
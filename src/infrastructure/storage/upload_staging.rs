@@ -0,0 +1,198 @@
+use std::io::SeekFrom;
+use std::path::PathBuf;
+use std::time::SystemTime;
+use tokio::fs::{self, File, OpenOptions};
+use tokio::io::{AsyncSeekExt, AsyncWriteExt};
+
+use super::StorageError;
+use crate::domain::entities::UploadId;
+
+/// Local staging area for partially received resumable uploads
+///
+/// Each upload is buffered in its own file until every chunk has arrived and the
+/// content can be hashed and handed to the configured storage backend.
+#[derive(Debug, Clone)]
+pub struct UploadStaging {
+    root: PathBuf,
+}
+
+impl UploadStaging {
+    /// Create a staging area rooted at the given directory
+    pub fn new<P: Into<PathBuf>>(root: P) -> Self {
+        Self { root: root.into() }
+    }
+
+    fn path(&self, id: UploadId) -> PathBuf {
+        self.root.join(id.to_string())
+    }
+
+    /// Create an empty staging file for an upload
+    ///
+    /// # Errors
+    /// Returns an error if the staging directory or file cannot be created
+    pub async fn create(&self, id: UploadId) -> Result<(), StorageError> {
+        fs::create_dir_all(&self.root).await?;
+        File::create(self.path(id)).await?;
+        Ok(())
+    }
+
+    /// Write a chunk at `offset`, returning the new staged length
+    ///
+    /// Any bytes past `offset` left behind by an interrupted write are discarded first,
+    /// so the staged file always agrees with the persisted upload offset.
+    ///
+    /// # Errors
+    /// Returns an error if the staging file is missing or cannot be written
+    pub async fn write_chunk(
+        &self,
+        id: UploadId,
+        offset: u64,
+        chunk: &[u8],
+    ) -> Result<u64, StorageError> {
+        let path = self.path(id);
+        let mut file = OpenOptions::new().write(true).open(&path).await.map_err(|e| {
+            if e.kind() == std::io::ErrorKind::NotFound {
+                StorageError::FileNotFound { path: path.display().to_string() }
+            } else {
+                e.into()
+            }
+        })?;
+
+        file.set_len(offset).await?;
+        file.seek(SeekFrom::Start(offset)).await?;
+        file.write_all(chunk).await?;
+        file.flush().await?;
+
+        Ok(offset + chunk.len() as u64)
+    }
+
+    /// Open a staged upload for reading
+    ///
+    /// # Errors
+    /// Returns an error if the staging file does not exist
+    pub async fn open(&self, id: UploadId) -> Result<File, StorageError> {
+        let path = self.path(id);
+        File::open(&path).await.map_err(|e| {
+            if e.kind() == std::io::ErrorKind::NotFound {
+                StorageError::FileNotFound { path: path.display().to_string() }
+            } else {
+                e.into()
+            }
+        })
+    }
+
+    /// Remove a staged upload, returning whether it existed
+    ///
+    /// # Errors
+    /// Returns an error if the staging file exists but cannot be removed
+    pub async fn remove(&self, id: UploadId) -> Result<bool, StorageError> {
+        match fs::remove_file(self.path(id)).await {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Uploads whose staging file was last written before `cutoff`
+    ///
+    /// # Errors
+    /// Returns an error if the staging directory exists but cannot be read
+    pub async fn staged_before(&self, cutoff: SystemTime) -> Result<Vec<UploadId>, StorageError> {
+        let mut entries = match fs::read_dir(&self.root).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut stale = Vec::new();
+        while let Some(entry) = entries.next_entry().await? {
+            let Some(id) = entry.file_name().to_str().and_then(|name| name.parse().ok()) else {
+                continue;
+            };
+            if entry.metadata().await?.modified()? < cutoff {
+                stale.push(id);
+            }
+        }
+
+        Ok(stale)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+    use tokio::io::AsyncReadExt;
+
+    async fn read_staged(staging: &UploadStaging, id: UploadId) -> Vec<u8> {
+        let mut content = Vec::new();
+        staging.open(id).await.unwrap().read_to_end(&mut content).await.unwrap();
+        content
+    }
+
+    #[tokio::test]
+    async fn test_write_chunks_in_order() {
+        let temp_dir = TempDir::new().unwrap();
+        let staging = UploadStaging::new(temp_dir.path().join("uploads"));
+        let id = UploadId::new();
+
+        staging.create(id).await.unwrap();
+        assert_eq!(staging.write_chunk(id, 0, b"hello ").await.unwrap(), 6);
+        assert_eq!(staging.write_chunk(id, 6, b"world").await.unwrap(), 11);
+
+        assert_eq!(read_staged(&staging, id).await, b"hello world");
+    }
+
+    #[tokio::test]
+    async fn test_write_chunk_discards_bytes_past_offset() {
+        let temp_dir = TempDir::new().unwrap();
+        let staging = UploadStaging::new(temp_dir.path());
+        let id = UploadId::new();
+
+        staging.create(id).await.unwrap();
+        staging.write_chunk(id, 0, b"hello garbage").await.unwrap();
+        staging.write_chunk(id, 6, b"world").await.unwrap();
+
+        assert_eq!(read_staged(&staging, id).await, b"hello world");
+    }
+
+    #[tokio::test]
+    async fn test_missing_upload() {
+        let temp_dir = TempDir::new().unwrap();
+        let staging = UploadStaging::new(temp_dir.path());
+        let id = UploadId::new();
+
+        assert!(matches!(
+            staging.write_chunk(id, 0, b"data").await,
+            Err(StorageError::FileNotFound { .. })
+        ));
+        assert!(matches!(staging.open(id).await, Err(StorageError::FileNotFound { .. })));
+        assert!(!staging.remove(id).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_remove() {
+        let temp_dir = TempDir::new().unwrap();
+        let staging = UploadStaging::new(temp_dir.path());
+        let id = UploadId::new();
+
+        staging.create(id).await.unwrap();
+        assert!(staging.remove(id).await.unwrap());
+        assert!(matches!(staging.open(id).await, Err(StorageError::FileNotFound { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_staged_before() {
+        let temp_dir = TempDir::new().unwrap();
+        let staging = UploadStaging::new(temp_dir.path().join("uploads"));
+        assert!(staging.staged_before(SystemTime::now()).await.unwrap().is_empty());
+
+        let id = UploadId::new();
+        staging.create(id).await.unwrap();
+        std::fs::write(temp_dir.path().join("uploads").join("not-an-upload"), b"").unwrap();
+
+        let later = SystemTime::now() + std::time::Duration::from_secs(1);
+        assert_eq!(staging.staged_before(later).await.unwrap(), vec![id]);
+        assert!(staging.staged_before(SystemTime::UNIX_EPOCH).await.unwrap().is_empty());
+    }
+}
@@ -50,31 +50,73 @@ pub fn content_addressable_path(hash: &ContentHash) -> String {
     format!("{}/{}/{}/{}", &hash_str[0..2], &hash_str[2..4], &hash_str[4..6], hash_str)
 }
 
-/// Detect MIME type from file content
-pub fn detect_content_type(data: &[u8], filename: Option<&str>) -> String {
-    // First, try to detect from content
-    if data.len() >= 4 {
-        match &data[0..4] {
-            [0xFF, 0xD8, 0xFF, ..] => return "image/jpeg".to_string(),
-            [0x89, 0x50, 0x4E, 0x47] => return "image/png".to_string(),
-            [0x47, 0x49, 0x46, 0x38] => return "image/gif".to_string(),
-            [0x52, 0x49, 0x46, 0x46] if data.len() >= 12 && &data[8..12] == b"WEBP" => {
-                return "image/webp".to_string();
-            }
-            [0x66, 0x74, 0x79, 0x70] => return "video/mp4".to_string(),
-            _ => {}
+/// Content types [`sniff_content_type`] can recognize from their signatures
+const SIGNATURE_TYPES: &[&str] = &[
+    "image/jpeg",
+    "image/png",
+    "image/gif",
+    "image/webp",
+    "image/avif",
+    "video/mp4",
+    "video/quicktime",
+    "video/webm",
+    "video/x-msvideo",
+    "audio/mpeg",
+    "audio/wav",
+    "audio/flac",
+    "audio/ogg",
+];
+
+/// Identify a file's format from its magic bytes alone
+///
+/// Returns `None` for formats without a recognizable signature (e.g. plain text).
+pub fn sniff_content_type(data: &[u8]) -> Option<&'static str> {
+    let at = |offset: usize, signature: &[u8]| {
+        data.get(offset..offset + signature.len()) == Some(signature)
+    };
+
+    if at(0, &[0xFF, 0xD8, 0xFF]) {
+        Some("image/jpeg")
+    } else if at(0, &[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A]) {
+        Some("image/png")
+    } else if at(0, b"GIF87a") || at(0, b"GIF89a") {
+        Some("image/gif")
+    } else if at(0, b"RIFF") && at(8, b"WEBP") {
+        Some("image/webp")
+    } else if at(0, b"RIFF") && at(8, b"WAVE") {
+        Some("audio/wav")
+    } else if at(0, b"RIFF") && at(8, b"AVI ") {
+        Some("video/x-msvideo")
+    } else if at(4, b"ftyp") {
+        // ISO base media files carry their major brand after the `ftyp` box type
+        match data.get(8..12) {
+            Some(b"avif" | b"avis") => Some("image/avif"),
+            Some(b"qt  ") => Some("video/quicktime"),
+            Some(_) => Some("video/mp4"),
+            None => None,
         }
+    } else if at(0, &[0x1A, 0x45, 0xDF, 0xA3]) {
+        Some("video/webm")
+    } else if at(0, b"fLaC") {
+        Some("audio/flac")
+    } else if at(0, b"OggS") {
+        Some("audio/ogg")
+    } else if at(0, b"ID3") || data.len() >= 2 && data[0] == 0xFF && data[1] & 0xE0 == 0xE0 {
+        Some("audio/mpeg")
+    } else {
+        None
     }
+}
 
-    // Check for additional formats
-    if data.len() >= 8 {
-        // Check for AVIF
-        if data[4..8] == [0x66, 0x74, 0x79, 0x70]
-            && data.len() >= 12
-            && (&data[8..12] == b"avif" || &data[8..12] == b"avis")
-        {
-            return "image/avif".to_string();
-        }
+/// Check whether a format can be confirmed from the content's magic bytes
+pub fn has_known_signature(content_type: &str) -> bool {
+    SIGNATURE_TYPES.contains(&content_type)
+}
+
+/// Detect MIME type from file content, falling back to the filename extension
+pub fn detect_content_type(data: &[u8], filename: Option<&str>) -> String {
+    if let Some(content_type) = sniff_content_type(data) {
+        return content_type.to_string();
     }
 
     // Fall back to filename extension
@@ -104,23 +146,7 @@ pub fn detect_content_type(data: &[u8], filename: Option<&str>) -> String {
 pub fn validate_content_type(data: &[u8], expected_type: &str) -> Result<(), StorageError> {
     let detected_type = detect_content_type(data, None);
 
-    // Allow some flexibility in content type matching
-    let is_valid = match expected_type {
-        "image/jpeg" => detected_type == "image/jpeg",
-        "image/png" => detected_type == "image/png",
-        "image/gif" => detected_type == "image/gif",
-        "image/webp" => detected_type == "image/webp",
-        "image/avif" => detected_type == "image/avif",
-        "video/mp4" => detected_type == "video/mp4",
-        "video/webm" => detected_type == "video/webm",
-        "video/quicktime" => detected_type == "video/quicktime",
-        "audio/mpeg" => detected_type == "audio/mpeg",
-        "audio/wav" => detected_type == "audio/wav",
-        "audio/flac" => detected_type == "audio/flac",
-        _ => expected_type == detected_type,
-    };
-
-    if !is_valid {
+    if detected_type != expected_type {
         return Err(StorageError::InvalidPath {
             path: format!(
                 "Content type mismatch: expected {expected_type}, detected {detected_type}"
@@ -139,6 +165,29 @@ pub fn validate_file_size(size: u64, max_size: u64) -> Result<(), StorageError>
     Ok(())
 }
 
+/// `Content-Disposition` value offering `filename` as a download
+///
+/// The quoted `filename` is an ASCII fallback with quotes and backslashes escaped and
+/// anything else outside printable ASCII replaced; clients that understand RFC 6266
+/// use the exact name from the percent-encoded `filename*` parameter.
+pub fn content_disposition(filename: &str) -> String {
+    let fallback = filename.chars().fold(String::new(), |mut acc, c| {
+        match c {
+            '"' | '\\' => {
+                acc.push('\\');
+                acc.push(c);
+            }
+            ' '..='~' => acc.push(c),
+            _ => acc.push('_'),
+        }
+        acc
+    });
+    format!(
+        "attachment; filename=\"{fallback}\"; filename*=UTF-8''{}",
+        urlencoding::encode(filename)
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -196,6 +245,30 @@ mod tests {
         assert_eq!(content_type, "image/png");
     }
 
+    #[test]
+    fn test_sniff_content_type_from_magic_bytes() {
+        let mp4 = [&[0, 0, 0, 0x18][..], b"ftypisom", &[0; 8]].concat();
+        let avif = [&[0, 0, 0, 0x1C][..], b"ftypavif", &[0; 8]].concat();
+        let webp = [&b"RIFF"[..], &[0; 4], b"WEBPVP8 "].concat();
+
+        assert_eq!(sniff_content_type(&mp4), Some("video/mp4"));
+        assert_eq!(sniff_content_type(&avif), Some("image/avif"));
+        assert_eq!(sniff_content_type(&webp), Some("image/webp"));
+        assert_eq!(sniff_content_type(b"GIF89a\x01\x00"), Some("image/gif"));
+        assert_eq!(sniff_content_type(&[0x1A, 0x45, 0xDF, 0xA3, 0x01]), Some("video/webm"));
+        assert_eq!(sniff_content_type(b"ID3\x04\x00"), Some("audio/mpeg"));
+        assert_eq!(sniff_content_type(b"hello world"), None);
+        assert_eq!(sniff_content_type(&[]), None);
+    }
+
+    #[test]
+    fn test_has_known_signature() {
+        assert!(has_known_signature("image/png"));
+        assert!(has_known_signature("video/webm"));
+        assert!(!has_known_signature("text/plain"));
+        assert!(!has_known_signature("application/octet-stream"));
+    }
+
     #[test]
     fn test_detect_content_type_by_filename() {
         let data = [0x00, 0x00, 0x00, 0x00]; // Unknown content
@@ -249,4 +322,21 @@ mod tests {
         );
         assert_eq!(buffer, data.to_vec());
     }
+
+    #[test]
+    fn test_content_disposition_escapes_filename() {
+        assert_eq!(
+            content_disposition("photo.jpg"),
+            "attachment; filename=\"photo.jpg\"; filename*=UTF-8''photo.jpg"
+        );
+        assert_eq!(
+            content_disposition("my \"best\" \\ cake.png"),
+            "attachment; filename=\"my \\\"best\\\" \\\\ cake.png\"; \
+             filename*=UTF-8''my%20%22best%22%20%5C%20cake.png"
+        );
+        assert_eq!(
+            content_disposition("crème\r\n.png"),
+            "attachment; filename=\"cr_me__.png\"; filename*=UTF-8''cr%C3%A8me%0D%0A.png"
+        );
+    }
 }
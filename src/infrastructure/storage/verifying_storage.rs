@@ -0,0 +1,203 @@
+use async_trait::async_trait;
+use sha2::{Digest, Sha256};
+use std::io;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{ready, Context, Poll};
+use tokio::io::{AsyncRead, ReadBuf};
+
+use super::{FileMetadata, FileStorage, MirroredStorage, StorageError};
+use crate::domain::value_objects::ContentHash;
+
+/// Storage decorator that verifies content against its hash as it is read
+///
+/// Every full `retrieve` is hashed on the fly; at end of file the digest is
+/// compared to the requested `ContentHash` and a mismatch fails the read with
+/// [`StorageError::HashMismatch`]. Meant for backends we don't fully trust, at
+/// the cost of hashing every download. Range reads cannot be verified and are
+/// passed through unchecked. Direct download URLs are never handed out, since
+/// downloads through them would bypass verification.
+pub struct VerifyingStorage {
+    inner: Arc<dyn FileStorage>,
+}
+
+impl VerifyingStorage {
+    /// Wrap a storage backend so that reads are verified
+    #[must_use]
+    pub fn new(inner: Arc<dyn FileStorage>) -> Self {
+        Self { inner }
+    }
+}
+
+#[async_trait]
+impl FileStorage for VerifyingStorage {
+    async fn store(
+        &self,
+        hash: &ContentHash,
+        reader: &mut (dyn AsyncRead + Send + Unpin),
+    ) -> Result<String, StorageError> {
+        self.inner.store(hash, reader).await
+    }
+
+    async fn retrieve(
+        &self,
+        hash: &ContentHash,
+    ) -> Result<Box<dyn AsyncRead + Send + Unpin>, StorageError> {
+        let reader = self.inner.retrieve(hash).await?;
+        Ok(Box::new(VerifyingReader::new(reader, hash.clone())))
+    }
+
+    async fn retrieve_range(
+        &self,
+        hash: &ContentHash,
+        start: u64,
+        length: u64,
+    ) -> Result<Box<dyn AsyncRead + Send + Unpin>, StorageError> {
+        self.inner.retrieve_range(hash, start, length).await
+    }
+
+    async fn exists(&self, hash: &ContentHash) -> Result<bool, StorageError> {
+        self.inner.exists(hash).await
+    }
+
+    async fn delete(&self, hash: &ContentHash) -> Result<bool, StorageError> {
+        self.inner.delete(hash).await
+    }
+
+    fn get_path(&self, hash: &ContentHash) -> String {
+        self.inner.get_path(hash)
+    }
+
+    async fn metadata(&self, hash: &ContentHash) -> Result<FileMetadata, StorageError> {
+        self.inner.metadata(hash).await
+    }
+
+    async fn health_check(&self) -> Result<(), StorageError> {
+        self.inner.health_check().await
+    }
+
+    fn mirrored(&self) -> Option<&MirroredStorage> {
+        self.inner.mirrored()
+    }
+}
+
+/// Reader that hashes everything passing through and checks the digest at EOF
+///
+/// The mismatch surfaces as an `io::Error` wrapping `StorageError::HashMismatch`,
+/// which `StorageError::from` unwraps again.
+pub struct VerifyingReader<R> {
+    inner: R,
+    expected: ContentHash,
+    hasher: Option<Sha256>,
+}
+
+impl<R> VerifyingReader<R> {
+    /// Verify the content read from `inner` against `expected`
+    pub fn new(inner: R, expected: ContentHash) -> Self {
+        Self { inner, expected, hasher: Some(Sha256::new()) }
+    }
+
+    /// Compare the final digest with the expected hash, once
+    fn finish(&mut self) -> io::Result<()> {
+        let Some(hasher) = self.hasher.take() else {
+            return Ok(());
+        };
+
+        let actual = hex::encode(hasher.finalize());
+        if actual == self.expected.as_str() {
+            return Ok(());
+        }
+
+        tracing::error!(
+            "Content hash mismatch reading {}: stored data hashes to {}",
+            self.expected,
+            actual
+        );
+        metrics::counter!("storage_hash_mismatches_total").increment(1);
+
+        Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            StorageError::HashMismatch { expected: self.expected.to_string(), actual },
+        ))
+    }
+}
+
+impl<R: AsyncRead + Unpin> AsyncRead for VerifyingReader<R> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        let filled_before = buf.filled().len();
+        ready!(Pin::new(&mut this.inner).poll_read(cx, buf))?;
+
+        let read = &buf.filled()[filled_before..];
+        if !read.is_empty() {
+            if let Some(hasher) = this.hasher.as_mut() {
+                hasher.update(read);
+            }
+        } else if buf.remaining() > 0 {
+            this.finish()?;
+        }
+
+        Poll::Ready(Ok(()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::storage::{generate_content_hash, InMemoryStorage};
+    use tokio::io::AsyncReadExt;
+
+    async fn read_all(reader: &mut (dyn AsyncRead + Send + Unpin)) -> io::Result<Vec<u8>> {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data).await?;
+        Ok(data)
+    }
+
+    #[tokio::test]
+    async fn test_matching_content_reads_through() {
+        let inner = Arc::new(InMemoryStorage::new());
+        let hash = generate_content_hash(b"trusted content").unwrap();
+        inner.store(&hash, &mut &b"trusted content"[..]).await.unwrap();
+        let storage = VerifyingStorage::new(inner);
+
+        let mut reader = storage.retrieve(&hash).await.unwrap();
+
+        assert_eq!(read_all(&mut reader).await.unwrap(), b"trusted content");
+    }
+
+    #[tokio::test]
+    async fn test_corrupted_content_fails_with_hash_mismatch() {
+        let inner = Arc::new(InMemoryStorage::new());
+        let hash = generate_content_hash(b"original content").unwrap();
+        inner.store(&hash, &mut &b"corrupted content"[..]).await.unwrap();
+        let storage = VerifyingStorage::new(inner);
+
+        let mut reader = storage.retrieve(&hash).await.unwrap();
+        let error = read_all(&mut reader).await.unwrap_err();
+
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        match StorageError::from(error) {
+            StorageError::HashMismatch { expected, actual } => {
+                assert_eq!(expected, hash.as_str());
+                assert_eq!(actual, generate_content_hash(b"corrupted content").unwrap().as_str());
+            }
+            other => panic!("Expected HashMismatch, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_range_reads_are_not_verified() {
+        let inner = Arc::new(InMemoryStorage::new());
+        let hash = generate_content_hash(b"original content").unwrap();
+        inner.store(&hash, &mut &b"corrupted content"[..]).await.unwrap();
+        let storage = VerifyingStorage::new(inner);
+
+        let mut reader = storage.retrieve_range(&hash, 0, 9).await.unwrap();
+
+        assert_eq!(read_all(&mut reader).await.unwrap(), b"corrupted");
+    }
+}
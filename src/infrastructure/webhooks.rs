@@ -0,0 +1,571 @@
+//! Notification of other services about media lifecycle events
+//!
+//! Every subscribed event is posted as JSON to every configured endpoint. With a
+//! signing secret, requests carry `X-Webhook-Signature: t={unix time},v1={hex}`,
+//! where the hex digest is the HMAC-SHA256 of `{unix time}.{body}`, so receivers
+//! can check the sender and reject replays. Deliveries run in the background as
+//! `webhook_delivery` jobs and are retried with exponential backoff; the latest
+//! ones are kept for the admin deliveries endpoint.
+//!
+//! Metrics, labelled by `event`:
+//! - `webhook_deliveries_total` - delivery attempts, also labelled by `outcome`
+//! - `webhook_deliveries_abandoned_total` - deliveries given up after the last attempt
+
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex, PoisonError},
+    time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::Serialize;
+use sha2::Sha256;
+use tracing::{error, info};
+use uuid::Uuid;
+
+use crate::{
+    application::events::{DomainEvent, EventSubscriber},
+    domain::{entities::MediaId, value_objects::ProcessingStatus},
+    infrastructure::{
+        config::{JobsConfig, Secret, WebhookConfig},
+        jobs::{self, JobRegistry},
+    },
+};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Header carrying the signature of a delivery
+pub const SIGNATURE_HEADER: &str = "x-webhook-signature";
+
+/// Deliveries kept for the admin endpoint, oldest dropped first
+const DELIVERY_HISTORY: usize = 100;
+
+/// What happened to a media item
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, utoipa::ToSchema)]
+pub enum WebhookEventType {
+    /// An upload finished processing and is ready to serve
+    #[serde(rename = "upload.completed")]
+    UploadCompleted,
+    /// Processing an upload failed; it is kept but has no variants
+    #[serde(rename = "processing.failed")]
+    ProcessingFailed,
+    /// A media item was deleted
+    #[serde(rename = "media.deleted")]
+    MediaDeleted,
+}
+
+impl WebhookEventType {
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::UploadCompleted => "upload.completed",
+            Self::ProcessingFailed => "processing.failed",
+            Self::MediaDeleted => "media.deleted",
+        }
+    }
+
+    /// The event type named `name`, as listed in `webhooks.events`
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        [Self::UploadCompleted, Self::ProcessingFailed, Self::MediaDeleted]
+            .into_iter()
+            .find(|event| event.as_str() == name)
+    }
+}
+
+/// The media an event is about
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, utoipa::ToSchema)]
+pub struct WebhookMediaData {
+    #[schema(value_type = i64)]
+    pub media_id: MediaId,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub media_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub processing_status: Option<ProcessingStatus>,
+    /// Why processing failed, for `processing.failed`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Body of a webhook delivery
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, utoipa::ToSchema)]
+pub struct WebhookEvent {
+    /// Unique per event and shared by its deliveries, so receivers can drop repeats
+    pub id: Uuid,
+    #[serde(rename = "type")]
+    pub event_type: WebhookEventType,
+    pub occurred_at: DateTime<Utc>,
+    pub data: WebhookMediaData,
+}
+
+impl WebhookEvent {
+    fn new(event_type: WebhookEventType, data: WebhookMediaData) -> Self {
+        Self { id: Uuid::new_v4(), event_type, occurred_at: Utc::now(), data }
+    }
+
+    /// The webhook announcing `event`, if it is one webhooks deliver
+    ///
+    /// Processing is announced for new uploads only; reprocessing, e.g. by a
+    /// backfill, would otherwise report the same upload again.
+    #[must_use]
+    pub fn from_domain(event: &DomainEvent) -> Option<Self> {
+        match event {
+            DomainEvent::ProcessingCompleted {
+                media_id, media_type, reprocessed: false, ..
+            } => Some(Self::new(
+                WebhookEventType::UploadCompleted,
+                WebhookMediaData {
+                    media_id: *media_id,
+                    media_type: Some(media_type.to_string()),
+                    processing_status: Some(ProcessingStatus::Complete),
+                    error: None,
+                },
+            )),
+            DomainEvent::ProcessingFailed {
+                media_id,
+                media_type,
+                error,
+                reprocessed: false,
+                ..
+            } => Some(Self::new(
+                WebhookEventType::ProcessingFailed,
+                WebhookMediaData {
+                    media_id: *media_id,
+                    media_type: Some(media_type.to_string()),
+                    processing_status: Some(ProcessingStatus::Failed),
+                    error: Some(error.clone()),
+                },
+            )),
+            DomainEvent::MediaDeleted { media_id, .. } => Some(Self::deleted(*media_id)),
+            _ => None,
+        }
+    }
+
+    /// `media.deleted` for the media with `media_id`
+    #[must_use]
+    pub fn deleted(media_id: MediaId) -> Self {
+        Self::new(
+            WebhookEventType::MediaDeleted,
+            WebhookMediaData { media_id, media_type: None, processing_status: None, error: None },
+        )
+    }
+}
+
+/// Where a delivery stands
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DeliveryState {
+    /// Not attempted yet, or waiting to be retried
+    Pending,
+    Delivered,
+    /// Given up after the last attempt
+    Failed,
+}
+
+/// One event posted to one endpoint
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, utoipa::ToSchema)]
+pub struct WebhookDelivery {
+    pub event_id: Uuid,
+    pub event: WebhookEventType,
+    pub endpoint: String,
+    pub state: DeliveryState,
+    pub attempts: u32,
+    /// Status of the endpoint's last response, if it answered
+    pub last_status_code: Option<u16>,
+    /// Error of the last attempt, if it failed
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_attempt_at: Option<DateTime<Utc>>,
+}
+
+/// Endpoints and subscriptions of enabled webhooks
+#[derive(Debug)]
+struct WebhookSender {
+    client: reqwest::Client,
+    endpoints: Vec<String>,
+    events: Vec<WebhookEventType>,
+    signing_secret: Secret<String>,
+}
+
+/// Background delivery of media lifecycle events
+///
+/// Cloning is cheap; clones share the same endpoints and delivery history.
+#[derive(Debug, Clone)]
+pub struct Webhooks {
+    sender: Option<Arc<WebhookSender>>,
+    deliveries: Arc<Mutex<VecDeque<WebhookDelivery>>>,
+    jobs: JobRegistry,
+    max_attempts: u32,
+    initial_backoff: Duration,
+}
+
+impl Webhooks {
+    /// Deliver nothing, for deployments without subscribers
+    #[must_use]
+    pub fn disabled() -> Self {
+        Self {
+            sender: None,
+            deliveries: Arc::default(),
+            jobs: JobRegistry::new(),
+            max_attempts: 1,
+            initial_backoff: Duration::ZERO,
+        }
+    }
+
+    /// Deliver the events `config` subscribes to, reporting attempts to `jobs`
+    ///
+    /// A failed delivery is attempted up to `max_attempts` times in total,
+    /// waiting `initial_backoff` before the first retry and twice as long
+    /// before each further one.
+    #[must_use]
+    pub fn new(
+        config: &WebhookConfig,
+        jobs: JobRegistry,
+        max_attempts: u32,
+        initial_backoff: Duration,
+    ) -> Self {
+        jobs.register(jobs::WEBHOOK_DELIVERY);
+        let sender = WebhookSender {
+            client: reqwest::Client::builder()
+                .timeout(config.request_timeout())
+                .build()
+                .unwrap_or_default(),
+            endpoints: config.endpoints.clone(),
+            events: config.events.iter().filter_map(|e| WebhookEventType::from_name(e)).collect(),
+            signing_secret: config.signing_secret.clone(),
+        };
+        Self {
+            sender: Some(Arc::new(sender)),
+            deliveries: Arc::default(),
+            jobs,
+            max_attempts: max_attempts.max(1),
+            initial_backoff,
+        }
+    }
+
+    /// Post `event` to every endpoint in the background, if it is subscribed to
+    pub fn notify(&self, event: &WebhookEvent) {
+        let Some(sender) = self.sender.clone() else {
+            return;
+        };
+        if !sender.events.contains(&event.event_type) {
+            return;
+        }
+        let body = match serde_json::to_string(event) {
+            Ok(body) => Arc::new(body),
+            Err(e) => {
+                error!("Failed to serialize {} webhook: {}", event.event_type.as_str(), e);
+                return;
+            }
+        };
+
+        for endpoint in &sender.endpoints {
+            self.record(WebhookDelivery {
+                event_id: event.id,
+                event: event.event_type,
+                endpoint: endpoint.clone(),
+                state: DeliveryState::Pending,
+                attempts: 0,
+                last_status_code: None,
+                last_error: None,
+                created_at: Utc::now(),
+                last_attempt_at: None,
+            });
+
+            let webhooks = self.clone();
+            let sender = sender.clone();
+            let (event_id, event_type, endpoint, body) =
+                (event.id, event.event_type, endpoint.clone(), body.clone());
+            tokio::spawn(async move {
+                webhooks.deliver(&sender, event_id, event_type, &endpoint, &body).await
+            });
+        }
+    }
+
+    /// The latest deliveries, newest first
+    #[must_use]
+    pub fn deliveries(&self) -> Vec<WebhookDelivery> {
+        self.history().iter().rev().cloned().collect()
+    }
+
+    /// Attempt a delivery until the endpoint accepts it or the attempts run out
+    ///
+    /// Every attempt is a run of the `webhook_delivery` job, queued while it
+    /// waits out its backoff. Returns whether the event was delivered.
+    async fn deliver(
+        &self,
+        sender: &WebhookSender,
+        event_id: Uuid,
+        event_type: WebhookEventType,
+        endpoint: &str,
+        body: &str,
+    ) -> bool {
+        let event = event_type.as_str();
+        let mut backoff = self.initial_backoff;
+
+        for attempt in 1..=self.max_attempts {
+            let queued = self.jobs.enqueue(jobs::WEBHOOK_DELIVERY);
+            if attempt > 1 {
+                tokio::time::sleep(backoff).await;
+                backoff = backoff.saturating_mul(2);
+            }
+
+            // Failures are logged and counted by the job registry
+            let result = queued.run(post(sender, event_id, event, endpoint, body)).await;
+            let delivered = result.is_ok();
+            let outcome = if delivered { "success" } else { "failure" };
+            metrics::counter!("webhook_deliveries_total", "event" => event, "outcome" => outcome)
+                .increment(1);
+
+            let last_attempt = attempt == self.max_attempts;
+            self.update(event_id, endpoint, |delivery| {
+                delivery.attempts = attempt;
+                delivery.last_attempt_at = Some(Utc::now());
+                delivery.last_status_code = match &result {
+                    Ok(status) => Some(*status),
+                    Err(e) => e.status,
+                };
+                delivery.last_error = result.as_ref().err().map(|e| e.message.clone());
+                delivery.state = match (delivered, last_attempt) {
+                    (true, _) => DeliveryState::Delivered,
+                    (false, true) => DeliveryState::Failed,
+                    (false, false) => DeliveryState::Pending,
+                };
+            });
+            if delivered {
+                info!(event, endpoint, %event_id, attempt, "Delivered webhook");
+                return true;
+            }
+        }
+
+        metrics::counter!("webhook_deliveries_abandoned_total", "event" => event).increment(1);
+        error!(
+            event,
+            endpoint,
+            %event_id,
+            attempts = self.max_attempts,
+            "Giving up delivering webhook"
+        );
+        false
+    }
+
+    fn record(&self, delivery: WebhookDelivery) {
+        let mut history = self.history();
+        if history.len() == DELIVERY_HISTORY {
+            history.pop_front();
+        }
+        history.push_back(delivery);
+    }
+
+    /// Apply `change` to a delivery, unless it has left the history
+    fn update(&self, event_id: Uuid, endpoint: &str, change: impl FnOnce(&mut WebhookDelivery)) {
+        if let Some(delivery) = self
+            .history()
+            .iter_mut()
+            .rev()
+            .find(|delivery| delivery.event_id == event_id && delivery.endpoint == endpoint)
+        {
+            change(delivery);
+        }
+    }
+
+    fn history(&self) -> std::sync::MutexGuard<'_, VecDeque<WebhookDelivery>> {
+        self.deliveries.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl EventSubscriber for Webhooks {
+    fn handle(&self, event: &DomainEvent) {
+        if let Some(event) = WebhookEvent::from_domain(event) {
+            self.notify(&event);
+        }
+    }
+}
+
+/// A delivery attempt the endpoint did not accept
+#[derive(Debug)]
+struct DeliveryError {
+    status: Option<u16>,
+    message: String,
+}
+
+impl std::fmt::Display for DeliveryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+/// Post `body` to `endpoint` once, returning the status it answered with
+async fn post(
+    sender: &WebhookSender,
+    event_id: Uuid,
+    event: &str,
+    endpoint: &str,
+    body: &str,
+) -> Result<u16, DeliveryError> {
+    let mut request = sender
+        .client
+        .post(endpoint)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .header("x-webhook-id", event_id.to_string())
+        .header("x-webhook-event", event)
+        .body(body.to_string());
+    if !sender.signing_secret.is_empty() {
+        let signature = sign(sender.signing_secret.expose(), Utc::now().timestamp(), body);
+        request = request.header(SIGNATURE_HEADER, signature);
+    }
+
+    let response = request
+        .send()
+        .await
+        .map_err(|e| DeliveryError { status: None, message: format!("Request failed: {e}") })?;
+    let status = response.status();
+    if status.is_success() {
+        Ok(status.as_u16())
+    } else {
+        Err(DeliveryError {
+            status: Some(status.as_u16()),
+            message: format!("Endpoint answered {status}"),
+        })
+    }
+}
+
+/// Value of [`SIGNATURE_HEADER`] for `body` sent at `timestamp` (Unix seconds)
+///
+/// # Panics
+/// Never; HMAC takes keys of any length
+#[must_use]
+pub fn sign(secret: &str, timestamp: i64, body: &str) -> String {
+    let mut mac =
+        HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC can take key of any size");
+    mac.update(format!("{timestamp}.{body}").as_bytes());
+    format!("t={timestamp},v1={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// The webhooks `config` asks for, retried per `jobs_config`
+///
+/// Nothing is delivered unless `config` enables webhooks.
+pub fn create_webhooks(
+    config: &WebhookConfig,
+    jobs_config: &JobsConfig,
+    jobs: &JobRegistry,
+) -> Webhooks {
+    if !config.enabled {
+        return Webhooks::disabled();
+    }
+
+    info!(
+        "Delivering {} webhooks to {} endpoint(s)",
+        config.events.join(", "),
+        config.endpoints.len()
+    );
+    Webhooks::new(config, jobs.clone(), jobs_config.max_attempts, jobs_config.initial_backoff())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{entities::UserId, value_objects::MediaType};
+    use axum::{
+        http::{HeaderMap, StatusCode},
+        routing::post,
+        Router,
+    };
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use tokio::net::TcpListener;
+
+    #[test]
+    fn test_signature_covers_timestamp_and_body() {
+        let signature = sign("secret", 1_700_000_000, r#"{"type":"media.deleted"}"#);
+        assert!(signature.starts_with("t=1700000000,v1="));
+        assert_eq!(signature.len(), "t=1700000000,v1=".len() + 64);
+        assert_ne!(signature, sign("secret", 1_700_000_001, r#"{"type":"media.deleted"}"#));
+        assert_ne!(signature, sign("other", 1_700_000_000, r#"{"type":"media.deleted"}"#));
+    }
+
+    #[tokio::test]
+    async fn test_failed_deliveries_are_retried_and_recorded() {
+        // The endpoint fails the first attempt, then checks and accepts the retry
+        let calls = Arc::new(AtomicU32::new(0));
+        let app = Router::new().route(
+            "/hooks",
+            post({
+                let calls = calls.clone();
+                move |headers: HeaderMap, body: String| async move {
+                    if calls.fetch_add(1, Ordering::SeqCst) == 0 {
+                        return StatusCode::SERVICE_UNAVAILABLE;
+                    }
+                    let signature = headers[SIGNATURE_HEADER].to_str().unwrap();
+                    let timestamp: i64 =
+                        signature[2..signature.find(',').unwrap()].parse().unwrap();
+                    assert_eq!(signature, sign("secret", timestamp, &body));
+                    assert_eq!(headers["x-webhook-event"], "media.deleted");
+                    StatusCode::NO_CONTENT
+                }
+            }),
+        );
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let config = WebhookConfig {
+            enabled: true,
+            endpoints: vec![format!("http://{addr}/hooks")],
+            signing_secret: "secret".into(),
+            ..WebhookConfig::default()
+        };
+        let jobs = JobRegistry::new();
+        let webhooks = Webhooks::new(&config, jobs.clone(), 3, Duration::ZERO);
+        let event = WebhookEvent::deleted(MediaId::new(5));
+        webhooks.notify(&event);
+
+        for _ in 0..100 {
+            if webhooks.deliveries()[0].state != DeliveryState::Pending {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        let delivery = &webhooks.deliveries()[0];
+        assert_eq!(delivery.event_id, event.id);
+        assert_eq!(delivery.state, DeliveryState::Delivered);
+        assert_eq!(delivery.attempts, 2);
+        assert_eq!(delivery.last_status_code, Some(204));
+        assert_eq!(jobs.statuses()[0].failures, 1);
+    }
+
+    #[test]
+    fn test_unsubscribed_events_are_not_delivered() {
+        let config = WebhookConfig {
+            enabled: true,
+            endpoints: vec!["http://127.0.0.1:9/hooks".to_string()],
+            events: vec!["upload.completed".to_string()],
+            ..WebhookConfig::default()
+        };
+        let webhooks = Webhooks::new(&config, JobRegistry::new(), 1, Duration::ZERO);
+
+        webhooks.notify(&WebhookEvent::deleted(MediaId::new(5)));
+        assert!(webhooks.deliveries().is_empty());
+    }
+
+    #[test]
+    fn test_only_new_uploads_are_announced() {
+        let owner = UserId::new();
+        let processed = |reprocessed| DomainEvent::ProcessingCompleted {
+            media_id: MediaId::new(5),
+            uploaded_by: owner,
+            media_type: MediaType::new("image/png"),
+            reprocessed,
+        };
+
+        let event = WebhookEvent::from_domain(&processed(false)).unwrap();
+        assert_eq!(event.event_type, WebhookEventType::UploadCompleted);
+        assert_eq!(event.data.processing_status, Some(ProcessingStatus::Complete));
+        assert!(WebhookEvent::from_domain(&processed(true)).is_none());
+        assert!(WebhookEvent::from_domain(&DomainEvent::MediaTagsChanged {
+            media_id: MediaId::new(5),
+            uploaded_by: owner,
+        })
+        .is_none());
+    }
+}
@@ -20,7 +20,7 @@ pub mod domain;
 pub mod infrastructure;
 pub mod presentation;
 
-#[cfg(test)]
+#[cfg(any(test, feature = "test-utils"))]
 pub mod test_utils;
 
 // Re-export commonly used types
@@ -2,9 +2,14 @@
 #![deny(clippy::pedantic)]
 #![deny(warnings)]
 
-use media_management_service::infrastructure::{
-    config::{AppConfig, LogFormat, RotationPolicy},
-    http::start_server,
+use media_management_service::{
+    domain::services::{Clock, SystemClock},
+    infrastructure::{
+        config::{AppConfig, LogFormat, LogSink, LoggingConfig, RotationPolicy},
+        http::{log_filter, start_server, DynamicSettings, LogFilterHandle},
+        log_export::LogExportLayer,
+        persistence::{run_migrations, Database},
+    },
 };
 use std::{fs, path::Path, time::SystemTime};
 use tracing::{error, info, warn};
@@ -13,16 +18,34 @@ use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, Layer};
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    // Load configuration based on runtime mode
-    let config = AppConfig::load().map_err(|e| {
+    // Load configuration based on runtime mode, then fill in settings kept in a secret manager
+    let config = match AppConfig::load() {
+        Ok(config) => config.with_secrets().await,
+        Err(e) => Err(e),
+    }
+    .map_err(|e| {
         error!("Failed to load configuration: {}", e);
         e
     })?;
 
     // Initialize logging with mode-appropriate format
-    if let Err(e) = init_tracing(&config) {
-        error!("Failed to initialize logging: {}", e);
-        return Err(e);
+    let log_filter = match init_tracing(&config) {
+        Ok(log_filter) => log_filter,
+        Err(e) => {
+            error!("Failed to initialize logging: {}", e);
+            return Err(e);
+        }
+    };
+
+    // Apply schema migrations and exit, e.g. from a deploy job
+    if std::env::args().skip(1).any(|arg| arg == "--migrate") {
+        return migrate(&config).await;
+    }
+
+    // Report every invalid setting at once rather than failing on the first at runtime
+    if let Err(e) = config.validate() {
+        error!("{}", e);
+        return Err(e.into());
     }
 
     info!("Starting Media Management Service");
@@ -41,7 +64,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     }
 
     // Start the HTTP server
-    if let Err(e) = start_server(config).await {
+    // Settings a SIGHUP reloads without a restart
+    let settings = DynamicSettings::new(&config).with_log_filter(log_filter);
+    if let Err(e) = start_server(config, settings).await {
         error!("Server error: {}", e);
         return Err(e);
     }
@@ -49,31 +74,44 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+/// Apply pending schema migrations to the configured database
+async fn migrate(config: &AppConfig) -> Result<(), Box<dyn std::error::Error>> {
+    info!("Applying database migrations");
+    let database = Database::new(&config.postgres).await?;
+    let result = run_migrations(database.pool()).await;
+    database.close().await;
+
+    match result {
+        Ok(version) => {
+            info!("Database schema is at migration {:?}", version);
+            Ok(())
+        }
+        Err(e) => {
+            error!("Failed to apply database migrations: {}", e);
+            Err(e.into())
+        }
+    }
+}
+
 /// Initialize structured logging based on configuration
+///
+/// Returns the handle a configuration reload replaces the log filter through.
 #[allow(clippy::too_many_lines)]
-fn init_tracing(config: &AppConfig) -> Result<(), Box<dyn std::error::Error>> {
-    // Create environment filter
-    let env_filter = if let Some(ref custom_filter) = config.logging.filter {
-        tracing_subscriber::EnvFilter::try_from_default_env()
-            .unwrap_or_else(|_| custom_filter.clone().into())
-    } else {
-        tracing_subscriber::EnvFilter::try_from_default_env().unwrap_or_else(|_| {
-            format!(
-                "media_management_service={},tower_http={}",
-                config.logging.level, config.logging.level
-            )
-            .into()
-        })
-    };
+fn init_tracing(config: &AppConfig) -> Result<LogFilterHandle, Box<dyn std::error::Error>> {
+    let (env_filter, log_filter) =
+        tracing_subscriber::reload::Layer::new(log_filter(&config.logging));
+
+    // Shipping to an external sink, alongside the console and files
+    let export_layer = LogExportLayer::from_config(&config.logging.export)?;
 
-    let registry = tracing_subscriber::registry().with(env_filter);
+    let registry = tracing_subscriber::registry().with(env_filter).with(export_layer);
 
     // Handle the different combinations of console and file logging
     match (config.logging.console_enabled, config.logging.file_enabled) {
         (true, true) => {
             // Both console and file logging enabled
             fs::create_dir_all(&config.logging.file_path)?;
-            cleanup_old_log_files(config)?;
+            cleanup_old_log_files(&config.logging, &SystemClock)?;
 
             let file_appender = match config.logging.file_rotation {
                 RotationPolicy::Hourly => {
@@ -145,7 +183,7 @@ fn init_tracing(config: &AppConfig) -> Result<(), Box<dyn std::error::Error>> {
         (false, true) => {
             // File only
             fs::create_dir_all(&config.logging.file_path)?;
-            cleanup_old_log_files(config)?;
+            cleanup_old_log_files(&config.logging, &SystemClock)?;
 
             let file_appender = match config.logging.file_rotation {
                 RotationPolicy::Hourly => {
@@ -199,28 +237,35 @@ fn init_tracing(config: &AppConfig) -> Result<(), Box<dyn std::error::Error>> {
                 registry.with(file_layer).init();
             }
         }
+        (false, false) if config.logging.export.sink != LogSink::None => registry.init(),
         (false, false) => {
             return Err("No logging outputs enabled".into());
         }
     }
 
-    Ok(())
+    Ok(log_filter)
 }
 
-/// Clean up old log files based on retention policy
-fn cleanup_old_log_files(config: &AppConfig) -> Result<(), Box<dyn std::error::Error>> {
-    if !config.logging.file_enabled {
+/// Clean up log files older than the retention period as of `clock`'s time
+fn cleanup_old_log_files(
+    logging: &LoggingConfig,
+    clock: &dyn Clock,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if !logging.file_enabled {
         return Ok(());
     }
 
-    let log_dir = Path::new(&config.logging.file_path);
+    let log_dir = Path::new(&logging.file_path);
     if !log_dir.exists() {
         return Ok(());
     }
 
-    let retention_days = u64::from(config.logging.file_retention_days);
-    let cutoff_time = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH)?.as_secs()
-        - (retention_days * 24 * 60 * 60);
+    let retention_days = u64::from(logging.file_retention_days);
+    let cutoff_time = clock
+        .now()
+        .duration_since(SystemTime::UNIX_EPOCH)?
+        .as_secs()
+        .saturating_sub(retention_days * 24 * 60 * 60);
 
     let entries = fs::read_dir(log_dir)?;
     let mut deleted_count = 0;
@@ -231,7 +276,7 @@ fn cleanup_old_log_files(config: &AppConfig) -> Result<(), Box<dyn std::error::E
 
         // Only process files that match our log file pattern
         if let Some(file_name) = path.file_name().and_then(|n| n.to_str()) {
-            if file_name.starts_with(&config.logging.file_prefix) && path.is_file() {
+            if file_name.starts_with(&logging.file_prefix) && path.is_file() {
                 // Check file age
                 if let Ok(metadata) = fs::metadata(&path) {
                     if let Ok(modified) = metadata.modified() {
@@ -357,6 +402,46 @@ mod tests {
         assert!(cutoff < duration.as_secs());
     }
 
+    #[test]
+    fn test_cleanup_removes_log_files_past_retention() {
+        use media_management_service::{
+            infrastructure::config::LogExportConfig, test_utils::mocks::ManualClock,
+        };
+        use std::time::Duration;
+
+        let log_dir = tempfile::TempDir::new().unwrap();
+        let old_log = log_dir.path().join("media-service.2024-01-01");
+        let other_file = log_dir.path().join("notes.txt");
+        fs::write(&old_log, "log").unwrap();
+        fs::write(&other_file, "keep").unwrap();
+        let logging = LoggingConfig {
+            level: "info".to_string(),
+            filter: None,
+            console_enabled: false,
+            console_format: LogFormat::Pretty,
+            file_enabled: true,
+            file_format: LogFormat::Json,
+            file_path: log_dir.path().to_string_lossy().into_owned(),
+            file_prefix: "media-service".to_string(),
+            file_rotation: RotationPolicy::Daily,
+            file_retention_days: 7,
+            file_max_size_mb: None,
+            non_blocking: false,
+            buffer_size: None,
+            export: LogExportConfig::default(),
+        };
+        let clock = ManualClock::default();
+
+        clock.advance(Duration::from_hours(6 * 24));
+        cleanup_old_log_files(&logging, &clock).unwrap();
+        assert!(old_log.exists());
+
+        clock.advance(Duration::from_hours(2 * 24));
+        cleanup_old_log_files(&logging, &clock).unwrap();
+        assert!(!old_log.exists());
+        assert!(other_file.exists());
+    }
+
     #[test]
     fn test_rotation_policy_variants() {
         // Test that rotation policy enum variants exist
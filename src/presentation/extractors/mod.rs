@@ -1,2 +1,3 @@
-// Custom extractors will be added here as needed
-// Examples: authenticated user, file upload extractor
+mod path;
+
+pub use path::Path;
@@ -0,0 +1,158 @@
+use axum::{
+    extract::{
+        path::{ErrorKind, FailedToDeserializePathParams},
+        rejection::PathRejection,
+        FromRequestParts, RawPathParams,
+    },
+    http::request::Parts,
+};
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+
+use crate::presentation::middleware::error::AppError;
+
+/// Path parameters extractor with our error format
+///
+/// A drop-in replacement for `axum::extract::Path`. A parameter that does not
+/// parse is rejected with a 400 validation error naming the parameter and the
+/// expected type, instead of Axum's plain-text rejection.
+#[derive(Debug, Clone, Copy)]
+pub struct Path<T>(pub T);
+
+impl<T, S> FromRequestParts<S> for Path<T>
+where
+    T: DeserializeOwned + Send,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        match axum::extract::Path::<T>::from_request_parts(parts, state).await {
+            Ok(axum::extract::Path(value)) => Ok(Self(value)),
+            Err(PathRejection::FailedToDeserializePathParams(error)) => {
+                let names = match RawPathParams::from_request_parts(parts, state).await {
+                    Ok(params) => params.iter().map(|(name, _)| name.to_string()).collect(),
+                    Err(_) => Vec::new(),
+                };
+                Err(deserialize_error(error, &names))
+            }
+            Err(rejection) => {
+                Err(AppError::Internal { message: format!("Failed to read path: {rejection}") })
+            }
+        }
+    }
+}
+
+/// Turn a path deserialization failure into a validation error keyed by parameter
+///
+/// `names` are the route's parameter names in order, used to name parameters
+/// that Axum only reports by position.
+fn deserialize_error(error: FailedToDeserializePathParams, names: &[String]) -> AppError {
+    let name_at = |index: usize| names.get(index).cloned().unwrap_or_else(|| "path".to_string());
+
+    let (name, message) = match error.into_kind() {
+        ErrorKind::ParseErrorAtKey { key, value, expected_type } => {
+            (key, expected_message(&value, expected_type))
+        }
+        ErrorKind::ParseErrorAtIndex { index, value, expected_type } => {
+            (name_at(index), expected_message(&value, expected_type))
+        }
+        ErrorKind::ParseError { value, expected_type } => {
+            (name_at(0), expected_message(&value, expected_type))
+        }
+        ErrorKind::DeserializeError { key, value, message } => {
+            (key, format!("Invalid value '{value}': {message}"))
+        }
+        ErrorKind::InvalidUtf8InPathParam { key } => (key, "Value is not valid UTF-8".to_string()),
+        kind => {
+            return AppError::Internal { message: format!("Failed to deserialize path: {kind}") }
+        }
+    };
+
+    AppError::Validation { errors: HashMap::from([(name, message)]) }
+}
+
+fn expected_message(value: &str, expected_type: &str) -> String {
+    let expected = match expected_type {
+        "i8" | "i16" | "i32" | "i64" | "i128" | "isize" => "an integer",
+        "u8" | "u16" | "u32" | "u64" | "u128" | "usize" => "a non-negative integer",
+        "f32" | "f64" => "a number",
+        "bool" => "true or false",
+        other => other,
+    };
+    format!("Expected {expected}, got '{value}'")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::entities::{IngredientId, MediaId, RecipeId, UploadId};
+    use axum::{body::Body, http::Request, http::StatusCode, routing::get, Router};
+    use serde_json::Value;
+    use tower::ServiceExt;
+
+    async fn media(Path(id): Path<MediaId>) -> String {
+        id.to_string()
+    }
+
+    async fn ingredient(
+        Path((recipe_id, ingredient_id)): Path<(RecipeId, IngredientId)>,
+    ) -> String {
+        format!("{recipe_id}/{ingredient_id}")
+    }
+
+    async fn upload(Path(id): Path<UploadId>) -> String {
+        id.to_string()
+    }
+
+    fn app() -> Router {
+        Router::new()
+            .route("/media/{id}", get(media))
+            .route("/recipe/{recipe_id}/ingredient/{ingredient_id}", get(ingredient))
+            .route("/uploads/{id}", get(upload))
+    }
+
+    async fn get_json(uri: &str) -> (StatusCode, Value) {
+        let response =
+            app().oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap()).await.unwrap();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+    }
+
+    #[tokio::test]
+    async fn test_valid_path_is_extracted() {
+        let response =
+            app().oneshot(Request::builder().uri("/media/42").body(Body::empty()).unwrap()).await;
+
+        assert_eq!(response.unwrap().status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_malformed_id_names_parameter_and_type() {
+        let (status, body) = get_json("/media/abc").await;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["code"], "validation");
+        assert_eq!(body["details"]["validation_errors"]["id"], "Expected an integer, got 'abc'");
+    }
+
+    #[tokio::test]
+    async fn test_malformed_second_parameter_is_named() {
+        let (status, body) = get_json("/recipe/7/ingredient/x1").await;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(
+            body["details"]["validation_errors"]["ingredient_id"],
+            "Expected an integer, got 'x1'"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_malformed_uuid_is_rejected() {
+        let (status, body) = get_json("/uploads/not-a-uuid").await;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body["details"]["validation_errors"]["id"].is_string());
+    }
+}
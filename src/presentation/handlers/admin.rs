@@ -0,0 +1,496 @@
+//! Operational endpoints for the people running the service
+
+use std::fmt::Write;
+use std::time::Duration;
+
+use axum::{
+    extract::{Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Json, Response},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    application::{
+        dto::{
+            BackfillVariantsRequest, CapacityGrowthDto, CapacityReport, CapacityReportQuery,
+            ReportFormat, VariantBackfillProgress,
+        },
+        use_cases::{
+            BackfillBatch, DEFAULT_BACKFILL_BATCH_SIZE, DEFAULT_CAPACITY_REPORT_WEEKS,
+            MAX_BACKFILL_BATCH_SIZE,
+        },
+    },
+    domain::{entities::MediaId, value_objects::ProcessingStatus},
+    infrastructure::{
+        jobs::{self, JobStatus},
+        webhooks::WebhookDelivery,
+    },
+    presentation::{
+        handlers::media::{spawn_media_reprocessing, AppState},
+        middleware::error::{AppError, ErrorResponse},
+    },
+};
+
+/// Pause between backfill batches when the request does not say
+const DEFAULT_BACKFILL_BATCH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Status of every background job
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct JobsResponse {
+    pub jobs: Vec<JobStatus>,
+}
+
+/// List background jobs with their last run and backlog
+///
+/// Jobs are listed from startup, including ones that have not run yet.
+#[utoipa::path(
+    get,
+    path = "/api/v1/media-management/admin/jobs",
+    tag = "admin",
+    responses(
+        (status = 200, description = "Run history and backlog of every background job", body = JobsResponse),
+        (status = 401, description = "The request is not authenticated", body = ErrorResponse),
+        (status = 403, description = "The token lacks the admin scope", body = ErrorResponse)
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn list_jobs(State(app_state): State<AppState>) -> Json<JobsResponse> {
+    Json(JobsResponse { jobs: app_state.jobs.statuses() })
+}
+
+/// The latest webhook deliveries
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct WebhookDeliveriesResponse {
+    pub deliveries: Vec<WebhookDelivery>,
+}
+
+/// List the latest webhook deliveries, newest first
+///
+/// Each event gets one delivery per endpoint, `pending` until the endpoint
+/// accepts it or the last retry fails. Only this instance's deliveries since
+/// startup are listed, up to the last 100.
+#[utoipa::path(
+    get,
+    path = "/api/v1/media-management/admin/webhooks/deliveries",
+    tag = "admin",
+    responses(
+        (status = 200, description = "The latest webhook deliveries and their status", body = WebhookDeliveriesResponse),
+        (status = 401, description = "The request is not authenticated", body = ErrorResponse),
+        (status = 403, description = "The token lacks the admin scope", body = ErrorResponse)
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn list_webhook_deliveries(
+    State(app_state): State<AppState>,
+) -> Json<WebhookDeliveriesResponse> {
+    Json(WebhookDeliveriesResponse { deliveries: app_state.webhooks.deliveries() })
+}
+
+/// Whether the service refuses writes
+#[derive(Debug, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct ReadOnlyStatus {
+    pub read_only: bool,
+}
+
+/// Whether this instance is in read-only mode
+#[utoipa::path(
+    get,
+    path = "/api/v1/media-management/admin/read-only",
+    tag = "admin",
+    responses(
+        (status = 200, description = "Current read-only mode", body = ReadOnlyStatus),
+        (status = 401, description = "The request is not authenticated", body = ErrorResponse),
+        (status = 403, description = "The token lacks the admin scope", body = ErrorResponse)
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn get_read_only(State(app_state): State<AppState>) -> Json<ReadOnlyStatus> {
+    Json(ReadOnlyStatus { read_only: app_state.read_only.is_enabled() })
+}
+
+/// Switch read-only mode on or off
+///
+/// While on, mutating media endpoints answer 503 and reads and downloads keep
+/// working, e.g. during a storage failover. The switch applies to this instance
+/// only and lasts until it is changed again or the instance restarts, when
+/// `server.read_only` applies again.
+#[utoipa::path(
+    put,
+    path = "/api/v1/media-management/admin/read-only",
+    tag = "admin",
+    request_body = ReadOnlyStatus,
+    responses(
+        (status = 200, description = "Read-only mode after the change", body = ReadOnlyStatus),
+        (status = 401, description = "The request is not authenticated", body = ErrorResponse),
+        (status = 403, description = "The token lacks the admin scope", body = ErrorResponse)
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn set_read_only(
+    State(app_state): State<AppState>,
+    Json(request): Json<ReadOnlyStatus>,
+) -> Json<ReadOnlyStatus> {
+    let was_read_only = app_state.read_only.set(request.read_only);
+    if was_read_only != request.read_only {
+        tracing::warn!(read_only = request.read_only, "Read-only mode switched");
+    }
+    Json(ReadOnlyStatus { read_only: request.read_only })
+}
+
+/// Weekly growth of media count and bytes, by media type and by tenant
+///
+/// Covers the last `weeks` weeks, counting the current one; weeks start on
+/// Monday, UTC. Totals include media added before the first week covered.
+/// Tenants are the clients the media was uploaded with, and weeks in which
+/// nothing was added under a type or tenant have no entry for it. With
+/// `format=csv` the report is served as one CSV table, `dimension` telling
+/// `type` rows from `tenant` rows.
+///
+/// # Errors
+/// * `Database` - Aggregating media failed
+#[utoipa::path(
+    get,
+    path = "/api/v1/media-management/admin/reports/capacity",
+    tag = "admin",
+    params(CapacityReportQuery),
+    responses(
+        (status = 200, description = "Capacity growth per week", body = CapacityReport, content_type = "application/json"),
+        (status = 200, description = "Capacity growth per week as CSV", body = String, content_type = "text/csv"),
+        (status = 401, description = "The request is not authenticated", body = ErrorResponse),
+        (status = 403, description = "The token lacks the admin scope", body = ErrorResponse),
+        (status = 500, description = "Aggregating media failed", body = ErrorResponse)
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn get_capacity_report(
+    State(app_state): State<AppState>,
+    Query(query): Query<CapacityReportQuery>,
+) -> Result<Response, AppError> {
+    let weeks = query.weeks.unwrap_or(DEFAULT_CAPACITY_REPORT_WEEKS);
+    let report = app_state.use_cases.capacity_report.run(|uc| uc.execute(weeks)).await?;
+
+    Ok(match query.format.unwrap_or_default() {
+        ReportFormat::Json => Json(report).into_response(),
+        ReportFormat::Csv => {
+            ([(header::CONTENT_TYPE, "text/csv; charset=utf-8")], capacity_csv(&report))
+                .into_response()
+        }
+    })
+}
+
+/// Render a capacity report as CSV, one row per week and type or tenant
+fn capacity_csv(report: &CapacityReport) -> String {
+    let mut csv =
+        String::from("dimension,key,week_start,media_added,bytes_added,total_media,total_bytes\n");
+    let rows = report
+        .by_type
+        .iter()
+        .map(|growth| ("type", growth))
+        .chain(report.by_tenant.iter().map(|growth| ("tenant", growth)));
+    for (dimension, growth) in rows {
+        let CapacityGrowthDto {
+            week_start,
+            key,
+            media_added,
+            bytes_added,
+            total_media,
+            total_bytes,
+        } = growth;
+        let _ = writeln!(
+            csv,
+            "{dimension},{},{week_start},{media_added},{bytes_added},{total_media},{total_bytes}",
+            csv_field(key)
+        );
+    }
+    csv
+}
+
+/// Quote a CSV field if it contains a delimiter, quote or line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Start reprocessing existing media that lacks a generated variant
+///
+/// Media is walked in ID order and processed in batches of `batch_size`, pausing
+/// `batch_interval_ms` between batches that queued any work, so a large library
+/// is backfilled without starving new uploads. Progress is logged after each
+/// batch and served by [`get_variant_backfill`]; batches are also reported as
+/// the `variant_backfill` background job.
+///
+/// # Errors
+/// * `BadRequest` - The variant kind cannot be produced by this instance
+/// * `Conflict` - A backfill is already running
+#[utoipa::path(
+    post,
+    path = "/api/v1/media-management/admin/backfills/variants",
+    tag = "admin",
+    request_body = BackfillVariantsRequest,
+    responses(
+        (status = 202, description = "Backfill started", body = VariantBackfillProgress),
+        (status = 400, description = "Variant kind cannot be produced", body = ErrorResponse),
+        (status = 401, description = "The request is not authenticated", body = ErrorResponse),
+        (status = 403, description = "The token lacks the admin scope", body = ErrorResponse),
+        (status = 409, description = "A backfill is already running", body = ErrorResponse)
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn start_variant_backfill(
+    State(app_state): State<AppState>,
+    Json(request): Json<BackfillVariantsRequest>,
+) -> Result<(StatusCode, Json<VariantBackfillProgress>), AppError> {
+    app_state.use_cases.backfill_variants.inner().validate(request.kind)?;
+
+    let batch_size =
+        request.batch_size.unwrap_or(DEFAULT_BACKFILL_BATCH_SIZE).clamp(1, MAX_BACKFILL_BATCH_SIZE);
+    let progress = app_state.variant_backfills.start(&request, batch_size)?;
+    tracing::info!(
+        kind = request.kind.name(),
+        status = ?request.status,
+        batch_size,
+        "Starting variant backfill"
+    );
+
+    tokio::spawn(run_variant_backfill(app_state, request, batch_size));
+    Ok((StatusCode::ACCEPTED, Json(progress)))
+}
+
+/// Progress of the running or last variant backfill
+///
+/// # Errors
+/// * `NotFound` - No backfill has run since startup
+#[utoipa::path(
+    get,
+    path = "/api/v1/media-management/admin/backfills/variants",
+    tag = "admin",
+    responses(
+        (status = 200, description = "Progress of the running or last backfill", body = VariantBackfillProgress),
+        (status = 401, description = "The request is not authenticated", body = ErrorResponse),
+        (status = 403, description = "The token lacks the admin scope", body = ErrorResponse),
+        (status = 404, description = "No backfill has run since startup", body = ErrorResponse)
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn get_variant_backfill(
+    State(app_state): State<AppState>,
+) -> Result<Json<VariantBackfillProgress>, AppError> {
+    app_state
+        .variant_backfills
+        .current()
+        .map(Json)
+        .ok_or_else(|| AppError::NotFound { resource: "Variant backfill".to_string() })
+}
+
+/// Claim and process batches until the library has been walked
+async fn run_variant_backfill(
+    app_state: AppState,
+    request: BackfillVariantsRequest,
+    batch_size: u32,
+) {
+    let interval =
+        request.batch_interval_ms.map_or(DEFAULT_BACKFILL_BATCH_INTERVAL, Duration::from_millis);
+    let mut after = None;
+
+    loop {
+        let batch = app_state
+            .jobs
+            .run(
+                jobs::VARIANT_BACKFILL,
+                run_backfill_batch(&app_state, &request, after, batch_size),
+            )
+            .await;
+        let (batch, processed, failed) = match batch {
+            Ok(outcome) => outcome,
+            Err(e) => {
+                tracing::error!("Variant backfill stopped: {}", e);
+                app_state.variant_backfills.finish(Some(e.to_string()));
+                return;
+            }
+        };
+
+        if let Some(progress) = app_state.variant_backfills.record_batch(&batch, processed, failed)
+        {
+            tracing::info!(
+                kind = request.kind.name(),
+                scanned = progress.scanned,
+                enqueued = progress.enqueued,
+                processed = progress.processed,
+                failed = progress.failed,
+                last_media_id = ?progress.last_media_id,
+                "Variant backfill progress"
+            );
+        }
+
+        let Some(last_media_id) = batch.last_media_id else { break };
+        after = Some(last_media_id);
+        if !batch.media_ids.is_empty() {
+            tokio::time::sleep(interval).await;
+        }
+    }
+
+    tracing::info!(kind = request.kind.name(), "Variant backfill completed");
+    app_state.variant_backfills.finish(None);
+}
+
+/// Claim one batch and wait for it to be processed, counting processed and failed media
+async fn run_backfill_batch(
+    app_state: &AppState,
+    request: &BackfillVariantsRequest,
+    after: Option<crate::domain::entities::MediaId>,
+    batch_size: u32,
+) -> Result<(BackfillBatch, u64, u64), AppError> {
+    let batch = app_state
+        .use_cases
+        .backfill_variants
+        .run_once(|uc| uc.claim_batch(request.kind, request.status, after, batch_size))
+        .await?;
+
+    let processing: Vec<_> =
+        batch.media_ids.iter().map(|id| spawn_media_reprocessing(app_state, *id)).collect();
+    let (mut processed, mut failed) = (0, 0);
+    for handle in processing {
+        match handle.await {
+            Ok(Ok(media)) if media.processing_status == ProcessingStatus::Complete => {
+                processed += 1;
+            }
+            _ => failed += 1,
+        }
+    }
+
+    Ok((batch, processed, failed))
+}
+
+/// Which rendered images to drop from the render cache
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct RenderCachePurgeQuery {
+    /// Drop only the images rendered from this media; all of them when omitted
+    #[param(value_type = Option<i64>, minimum = 1)]
+    pub media_id: Option<MediaId>,
+}
+
+/// Rendered images dropped from the render cache
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct RenderCachePurge {
+    pub purged: u64,
+}
+
+/// Drop rendered images held in memory, e.g. after replacing a broken render
+///
+/// Later requests read the kept renders from storage again. Only this
+/// instance's cache is purged; kept renders are not deleted.
+#[utoipa::path(
+    delete,
+    path = "/api/v1/media-management/admin/render-cache",
+    tag = "admin",
+    params(RenderCachePurgeQuery),
+    responses(
+        (status = 200, description = "Number of rendered images dropped", body = RenderCachePurge),
+        (status = 401, description = "The request is not authenticated", body = ErrorResponse),
+        (status = 403, description = "The token lacks the admin scope", body = ErrorResponse)
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn purge_render_cache(
+    State(app_state): State<AppState>,
+    Query(query): Query<RenderCachePurgeQuery>,
+) -> Json<RenderCachePurge> {
+    let purged = app_state.render_cache.purge(query.media_id).await;
+    tracing::info!(media_id = ?query.media_id, purged, "Render cache purged");
+    Json(RenderCachePurge { purged })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        application::dto::{BackfillSelection, BackfillState, VariantKind},
+        infrastructure::{
+            jobs::{JobOutcome, JobRegistry, MEDIA_PROCESSING},
+            storage::{InMemoryStorage, PresignedUrlConfig, PresignedUrlService},
+        },
+        test_utils::mocks::InMemoryMediaRepository,
+    };
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_list_jobs_reports_registry() {
+        let jobs = JobRegistry::new();
+        let _ = jobs.run(MEDIA_PROCESSING, async { Err::<(), _>("ffmpeg exited with 1") }).await;
+
+        let app_state = AppState::new(
+            Arc::new(InMemoryMediaRepository::new()),
+            Arc::new(InMemoryStorage::new()),
+            PresignedUrlService::new(PresignedUrlConfig::default()),
+            1024,
+        )
+        .with_jobs(jobs);
+
+        let Json(response) = list_jobs(State(app_state)).await;
+
+        assert_eq!(response.jobs.len(), 1);
+        assert_eq!(response.jobs[0].name, MEDIA_PROCESSING);
+        assert_eq!(response.jobs[0].failures, 1);
+        assert_eq!(response.jobs[0].last_outcome, Some(JobOutcome::Failure));
+        assert_eq!(response.jobs[0].last_error.as_deref(), Some("ffmpeg exited with 1"));
+    }
+
+    #[tokio::test]
+    async fn test_variant_backfill_runs_to_completion() {
+        let app_state = AppState::new(
+            Arc::new(InMemoryMediaRepository::new()),
+            Arc::new(InMemoryStorage::new()),
+            PresignedUrlService::new(PresignedUrlConfig::default()),
+            1024,
+        );
+        let request = BackfillVariantsRequest {
+            kind: VariantKind::Webp,
+            status: BackfillSelection::Missing,
+            batch_size: None,
+            batch_interval_ms: Some(0),
+        };
+
+        let (status, Json(progress)) =
+            start_variant_backfill(State(app_state.clone()), Json(request)).await.unwrap();
+        assert_eq!(status, StatusCode::ACCEPTED);
+        assert_eq!(progress.batch_size, DEFAULT_BACKFILL_BATCH_SIZE);
+
+        let mut progress = get_variant_backfill(State(app_state.clone())).await.unwrap().0;
+        while progress.state == BackfillState::Running {
+            tokio::task::yield_now().await;
+            progress = get_variant_backfill(State(app_state.clone())).await.unwrap().0;
+        }
+        assert_eq!(progress.state, BackfillState::Completed);
+        assert_eq!(progress.enqueued, 0);
+    }
+
+    #[test]
+    fn test_capacity_csv_lists_types_then_tenants() {
+        let growth = |key: &str| CapacityGrowthDto {
+            week_start: "2025-01-13".to_string(),
+            key: key.to_string(),
+            media_added: 2,
+            bytes_added: 300,
+            total_media: 5,
+            total_bytes: 900,
+        };
+        let report = CapacityReport {
+            weeks: 1,
+            since: "2025-01-13".to_string(),
+            by_type: vec![growth("text/plain; charset=\"utf-8\"")],
+            by_tenant: vec![growth("tenant-1")],
+        };
+
+        assert_eq!(
+            capacity_csv(&report),
+            "dimension,key,week_start,media_added,bytes_added,total_media,total_bytes\n\
+             type,\"text/plain; charset=\"\"utf-8\"\"\",2025-01-13,2,300,5,900\n\
+             tenant,tenant-1,2025-01-13,2,300,5,900\n"
+        );
+    }
+}
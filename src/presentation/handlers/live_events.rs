@@ -0,0 +1,170 @@
+//! Live media events over a WebSocket
+//!
+//! `GET /media/ws` upgrades to a WebSocket that pushes an event whenever media
+//! uploaded by the caller changes, so screens showing many media items stay
+//! current without polling each of them. Events are those of the internal event
+//! bus, as JSON text frames in the same shape as the event stream.
+
+use axum::{
+    body::Bytes,
+    extract::{
+        ws::{Message, Utf8Bytes, WebSocket, WebSocketUpgrade},
+        State,
+    },
+    response::Response,
+};
+use serde_json::json;
+use std::time::Duration;
+use tokio::sync::broadcast::{self, error::RecvError};
+
+use crate::{
+    application::events::DomainEvent,
+    infrastructure::event_stream::MediaEvent,
+    presentation::{
+        handlers::media::AppState,
+        middleware::{
+            auth::UserContext,
+            error::{AppError, ErrorResponse},
+        },
+    },
+};
+
+/// How often an idle socket is pinged, so proxies do not close it
+const PING_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Follow the caller's media events over a WebSocket
+///
+/// Each text frame is one event about media the caller uploaded, e.g.
+/// `processing_completed`, `processing_failed` or `media_deleted`. A client
+/// that cannot keep up receives an `events_missed` frame with the number of
+/// events it skipped, and should reload what it shows. Messages from the
+/// client are ignored, apart from closing the socket.
+///
+/// # Errors
+/// Returns appropriate HTTP status codes for various error conditions:
+/// - 401 Unauthorized: The request is not authenticated
+/// - 400 Bad Request: The request is not a WebSocket upgrade
+#[utoipa::path(
+    get,
+    path = "/api/v1/media-management/media/ws",
+    tag = "media",
+    responses(
+        (status = 101, description = "Switched to a WebSocket streaming the caller's media events"),
+        (status = 400, description = "The request is not a WebSocket upgrade"),
+        (status = 401, description = "The request is not authenticated", body = ErrorResponse)
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn media_events_socket(
+    State(app_state): State<AppState>,
+    user: UserContext,
+    upgrade: WebSocketUpgrade,
+) -> Result<Response, AppError> {
+    let user_id = user.owner_id();
+    tracing::info!("Opening media event socket for user {}", user_id);
+
+    // Listen before upgrading, so nothing published meanwhile is lost
+    let events = app_state.live_events.listen(user_id);
+    Ok(upgrade.on_upgrade(move |socket| forward_events(socket, events)))
+}
+
+/// Send events to the socket until either side goes away
+async fn forward_events(mut socket: WebSocket, mut events: broadcast::Receiver<DomainEvent>) {
+    let mut ping = tokio::time::interval(PING_INTERVAL);
+    ping.reset();
+
+    loop {
+        let message = tokio::select! {
+            event = events.recv() => match event {
+                Ok(event) => event_message(&event),
+                Err(RecvError::Lagged(missed)) => {
+                    Message::Text(json!({ "type": "events_missed", "count": missed }).to_string().into())
+                }
+                Err(RecvError::Closed) => break,
+            },
+            received = socket.recv() => match received {
+                Some(Ok(Message::Close(_)) | Err(_)) | None => break,
+                Some(Ok(_)) => continue,
+            },
+            _ = ping.tick() => Message::Ping(Bytes::new()),
+        };
+
+        if socket.send(message).await.is_err() {
+            break;
+        }
+    }
+
+    tracing::debug!("Media event socket closed");
+}
+
+fn event_message(event: &DomainEvent) -> Message {
+    let json = serde_json::to_string(&MediaEvent::from_domain(event)).unwrap_or_default();
+    Message::Text(Utf8Bytes::from(json))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        application::events::{DomainEvent, EventSubscriber},
+        domain::entities::{MediaId, UserId},
+        infrastructure::storage::{InMemoryStorage, PresignedUrlConfig, PresignedUrlService},
+        presentation::{
+            handlers::media::AppState,
+            middleware::auth::{Claims, UserContext},
+        },
+        test_utils::mocks::InMemoryMediaRepository,
+    };
+    use axum::{extract::Request, middleware::Next, routing::get, Router};
+    use futures_util::StreamExt;
+    use std::sync::Arc;
+    use tokio::net::TcpListener;
+    use tokio_tungstenite::tungstenite;
+
+    #[tokio::test]
+    async fn test_socket_pushes_only_the_callers_events() {
+        let app_state = AppState::new(
+            Arc::new(InMemoryMediaRepository::new()),
+            Arc::new(InMemoryStorage::new()),
+            PresignedUrlService::new(PresignedUrlConfig::default()),
+            1024,
+        );
+        let user = UserContext::from(Claims::new_access_token(
+            "auth-service".to_string(),
+            vec!["media-service".to_string()],
+            uuid::Uuid::new_v4().to_string(),
+            "test-client".to_string(),
+            vec!["read".to_string()],
+            1,
+        ));
+        let owner = user.owner_id();
+        let live_events = app_state.live_events.clone();
+        let app = Router::new()
+            .route("/ws", get(super::media_events_socket))
+            .layer(axum::middleware::from_fn(move |mut request: Request, next: Next| {
+                request.extensions_mut().insert(user.clone());
+                next.run(request)
+            }))
+            .with_state(app_state);
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let (mut socket, _) =
+            tokio_tungstenite::connect_async(format!("ws://{addr}/ws")).await.unwrap();
+        live_events.handle(&DomainEvent::MediaDeleted {
+            media_id: MediaId::new(6),
+            uploaded_by: UserId::new(),
+        });
+        live_events
+            .handle(&DomainEvent::MediaDeleted { media_id: MediaId::new(5), uploaded_by: owner });
+
+        let tungstenite::Message::Text(text) = socket.next().await.unwrap().unwrap() else {
+            panic!("expected a text frame");
+        };
+        let event: serde_json::Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(
+            (event["type"].as_str(), event["media_id"].as_i64()),
+            (Some("media_deleted"), Some(5))
+        );
+    }
+}
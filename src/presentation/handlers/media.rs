@@ -1,57 +1,520 @@
 use axum::{
-    body::Body,
-    extract::{Multipart, Path, Query, State},
-    http::{header, StatusCode},
-    response::{Json, Response},
+    body::{Body, Bytes, HttpBody},
+    extract::{MatchedPath, Multipart, Query, State},
+    http::{header, HeaderMap, HeaderName, HeaderValue, StatusCode},
+    response::{IntoResponse, Json, Response},
 };
+use bytes::BytesMut;
+use futures_util::StreamExt;
 use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::AsyncRead;
+use tokio_util::io::ReaderStream;
 
 use crate::{
     application::{
+        container::{Container, Dependencies},
         dto::{
-            InitiateUploadRequest, InitiateUploadResponse, MediaDto, PaginatedMediaQuery,
-            PaginatedMediaResponse, UploadMediaResponse, UploadStatusResponse,
+            AssociatedMediaQuery, BatchDeleteMediaRequest, BatchDeleteMediaResponse,
+            BatchGetMediaRequest, BatchGetMediaResponse, InitiateUploadRequest,
+            InitiateUploadResponse, MediaDto, MediaTagsResponse, MediaVariantsResponse,
+            PaginatedMediaQuery, PaginatedMediaResponse, RenderQuery, SearchMediaQuery,
+            SetMediaTagsRequest, StorageUsageResponse, UploadMediaRequest, UploadMediaResponse,
+            UploadStatusResponse,
         },
+        events::LiveEvents,
         use_cases::{
-            DeleteMediaUseCase, DownloadMediaUseCase, GetMediaByIngredientUseCase,
-            GetMediaByRecipeUseCase, GetMediaByStepUseCase, GetMediaUseCase, InitiateUploadUseCase,
-            ListMediaUseCase, UploadMediaUseCase,
+            file_too_large, AllowedTypes, DownloadResponse, IdempotencyKeys, ProcessingProgress,
+            ReadOnlyMode, RenderCache, UploadFingerprints, UploadLocks, VariantBackfills,
         },
     },
     domain::{
-        entities::{IngredientId, MediaId, RecipeId, StepId, UserId},
-        repositories::MediaRepository,
+        entities::{IngredientId, Media, MediaAssociation, MediaId, RecipeId, StepId, UserId},
+        repositories::{
+            MediaRepository, ResumableUploadRepository, UploadSessionRepository, VariantRepository,
+        },
+        services::{Clock, SystemClock},
+        value_objects::{
+            CacheControlPolicy, ContentHash, DownloadRedirectPolicy, FormatPolicy, HealthPolicy,
+            LegacyUploadPolicy, LoadSheddingPolicy, ProcessingSla, RenderPolicy, RouteLimits,
+            StorageQuota, UuidVersion,
+        },
+    },
+    infrastructure::{
+        business_metrics::{BusinessMetrics, UploadFlow},
+        cdn::CdnPurges,
+        event_stream::EventStream,
+        jobs::{self, JobRegistry},
+        persistence::{
+            InMemoryIdempotencyRepository, InMemoryResumableUploadRepository,
+            InMemoryUploadSessionRepository, InMemoryVariantRepository,
+        },
+        processing::{ImagePipelineRollout, MalwareScan, RenderProvider, VideoProcessor},
+        storage::{utils::content_disposition, FileStorage, PresignedUrlService, UploadStaging},
+        webhooks::Webhooks,
+    },
+    presentation::{
+        extractors::Path,
+        middleware::{
+            auth::UserContext,
+            error::{AppError, ErrorResponse},
+        },
     },
-    infrastructure::storage::{FilesystemStorage, PresignedUrlService},
-    presentation::middleware::error::AppError,
 };
 
 /// Application state containing dependencies
+///
+/// `use_cases` is rebuilt from the other fields whenever a `with_*` builder
+/// replaces a dependency, so handlers always run against the configured ones.
 #[derive(Clone)]
 pub struct AppState {
     pub repository: Arc<dyn MediaRepository<Error = AppError>>,
-    pub storage: Arc<FilesystemStorage>,
+    pub storage: Arc<dyn FileStorage>,
     pub presigned_url_service: PresignedUrlService,
     pub max_file_size: u64,
+    pub upload_locks: UploadLocks,
+    pub upload_fingerprints: UploadFingerprints,
+    pub idempotency_keys: IdempotencyKeys,
+    pub variant_backfills: VariantBackfills,
+    pub processing_progress: ProcessingProgress,
+    pub live_events: LiveEvents,
+    pub jobs: JobRegistry,
+    pub resumable_uploads: Arc<dyn ResumableUploadRepository<Error = AppError>>,
+    pub upload_staging: UploadStaging,
+    pub resumable_upload_expiry: Duration,
+    pub upload_sessions: Arc<dyn UploadSessionRepository<Error = AppError>>,
+    pub variants: Arc<dyn VariantRepository<Error = AppError>>,
+    pub image_pipeline: ImagePipelineRollout,
+    pub video_processor: Option<VideoProcessor>,
+    pub malware_scan: Option<MalwareScan>,
+    pub quota: StorageQuota,
+    pub allowed_types: AllowedTypes,
+    pub format_policy: FormatPolicy,
+    pub download_redirect: DownloadRedirectPolicy,
+    pub legacy_upload: LegacyUploadPolicy,
+    pub processing_sla: ProcessingSla,
+    pub render: RenderPolicy,
+    pub render_cache: RenderCache,
+    pub render_provider: Option<RenderProvider>,
+    pub clock: Arc<dyn Clock>,
+    pub uuid_version: UuidVersion,
+    pub business_metrics: BusinessMetrics,
+    pub read_only: ReadOnlyMode,
+    pub health: HealthPolicy,
+    pub cache_control: CacheControlPolicy,
+    pub route_limits: RouteLimits,
+    pub load_shedding: LoadSheddingPolicy,
+    pub cdn: CdnPurges,
+    pub webhooks: Webhooks,
+    pub event_stream: EventStream,
+    pub use_cases: Arc<Container>,
 }
 
 impl AppState {
+    /// Create application state
+    ///
+    /// Resumable uploads, presigned upload sessions and the variant registry default
+    /// to in-memory state, with resumable uploads staged in the system temp
+    /// directory; use [`AppState::with_resumable_uploads`],
+    /// [`AppState::with_upload_sessions`] and [`AppState::with_variants`] to
+    /// persist them.
     pub fn new(
         repository: Arc<dyn MediaRepository<Error = AppError>>,
-        storage: Arc<FilesystemStorage>,
+        storage: Arc<dyn FileStorage>,
         presigned_url_service: PresignedUrlService,
         max_file_size: u64,
     ) -> Self {
-        Self { repository, storage, presigned_url_service, max_file_size }
+        Self::from_dependencies(Dependencies {
+            repository,
+            storage,
+            variants: Arc::new(InMemoryVariantRepository::new()),
+            upload_sessions: Arc::new(InMemoryUploadSessionRepository::new()),
+            resumable_uploads: Arc::new(InMemoryResumableUploadRepository::new()),
+            upload_staging: UploadStaging::new(std::env::temp_dir().join("media-service-uploads")),
+            presigned_url_service,
+            upload_locks: UploadLocks::new(),
+            upload_fingerprints: UploadFingerprints::disabled(),
+            idempotency_keys: IdempotencyKeys::new(
+                Arc::new(InMemoryIdempotencyRepository::new()),
+                Duration::from_hours(24),
+            ),
+            variant_backfills: VariantBackfills::new(),
+            processing_progress: ProcessingProgress::new(),
+            live_events: LiveEvents::new(),
+            jobs: JobRegistry::new(),
+            image_pipeline: ImagePipelineRollout::default(),
+            video_processor: None,
+            malware_scan: None,
+            max_file_size,
+            resumable_upload_expiry: Duration::from_hours(24),
+            quota: StorageQuota::unlimited(),
+            allowed_types: AllowedTypes::default(),
+            format_policy: FormatPolicy::unrestricted(),
+            download_redirect: DownloadRedirectPolicy::disabled(),
+            legacy_upload: LegacyUploadPolicy::default(),
+            processing_sla: ProcessingSla::disabled(),
+            render: RenderPolicy::default(),
+            render_cache: RenderCache::disabled(),
+            render_provider: None,
+            clock: Arc::new(SystemClock),
+            uuid_version: UuidVersion::default(),
+            business_metrics: BusinessMetrics::disabled(),
+            read_only: ReadOnlyMode::disabled(),
+            health: HealthPolicy::default(),
+            cache_control: CacheControlPolicy::default(),
+            route_limits: RouteLimits::default(),
+            load_shedding: LoadSheddingPolicy::default(),
+            cdn: CdnPurges::disabled(),
+            webhooks: Webhooks::disabled(),
+            event_stream: EventStream::disabled(),
+        })
+    }
+
+    /// Create application state and its use cases from a full set of dependencies
+    #[must_use]
+    pub fn from_dependencies(deps: Dependencies) -> Self {
+        let use_cases = Arc::new(Container::new(&deps));
+        Self {
+            repository: deps.repository,
+            storage: deps.storage,
+            presigned_url_service: deps.presigned_url_service,
+            max_file_size: deps.max_file_size,
+            upload_locks: deps.upload_locks,
+            upload_fingerprints: deps.upload_fingerprints,
+            idempotency_keys: deps.idempotency_keys,
+            variant_backfills: deps.variant_backfills,
+            processing_progress: deps.processing_progress,
+            live_events: deps.live_events,
+            jobs: deps.jobs,
+            resumable_uploads: deps.resumable_uploads,
+            upload_staging: deps.upload_staging,
+            resumable_upload_expiry: deps.resumable_upload_expiry,
+            upload_sessions: deps.upload_sessions,
+            variants: deps.variants,
+            image_pipeline: deps.image_pipeline,
+            video_processor: deps.video_processor,
+            malware_scan: deps.malware_scan,
+            quota: deps.quota,
+            allowed_types: deps.allowed_types,
+            format_policy: deps.format_policy,
+            download_redirect: deps.download_redirect,
+            legacy_upload: deps.legacy_upload,
+            processing_sla: deps.processing_sla,
+            render: deps.render,
+            render_cache: deps.render_cache,
+            render_provider: deps.render_provider,
+            clock: deps.clock,
+            uuid_version: deps.uuid_version,
+            business_metrics: deps.business_metrics,
+            read_only: deps.read_only,
+            health: deps.health,
+            cache_control: deps.cache_control,
+            route_limits: deps.route_limits,
+            load_shedding: deps.load_shedding,
+            cdn: deps.cdn,
+            webhooks: deps.webhooks,
+            event_stream: deps.event_stream,
+            use_cases,
+        }
+    }
+
+    /// The dependencies the use cases are built from
+    #[must_use]
+    pub fn dependencies(&self) -> Dependencies {
+        Dependencies {
+            repository: self.repository.clone(),
+            storage: self.storage.clone(),
+            variants: self.variants.clone(),
+            upload_sessions: self.upload_sessions.clone(),
+            resumable_uploads: self.resumable_uploads.clone(),
+            upload_staging: self.upload_staging.clone(),
+            presigned_url_service: self.presigned_url_service.clone(),
+            upload_locks: self.upload_locks.clone(),
+            upload_fingerprints: self.upload_fingerprints.clone(),
+            idempotency_keys: self.idempotency_keys.clone(),
+            variant_backfills: self.variant_backfills.clone(),
+            processing_progress: self.processing_progress.clone(),
+            live_events: self.live_events.clone(),
+            jobs: self.jobs.clone(),
+            image_pipeline: self.image_pipeline,
+            video_processor: self.video_processor.clone(),
+            malware_scan: self.malware_scan.clone(),
+            max_file_size: self.max_file_size,
+            resumable_upload_expiry: self.resumable_upload_expiry,
+            quota: self.quota,
+            allowed_types: self.allowed_types.clone(),
+            format_policy: self.format_policy.clone(),
+            download_redirect: self.download_redirect,
+            legacy_upload: self.legacy_upload,
+            processing_sla: self.processing_sla,
+            render: self.render.clone(),
+            render_cache: self.render_cache.clone(),
+            render_provider: self.render_provider.clone(),
+            clock: self.clock.clone(),
+            uuid_version: self.uuid_version,
+            business_metrics: self.business_metrics,
+            read_only: self.read_only.clone(),
+            health: self.health,
+            cache_control: self.cache_control,
+            route_limits: self.route_limits,
+            load_shedding: self.load_shedding,
+            cdn: self.cdn.clone(),
+            webhooks: self.webhooks.clone(),
+            event_stream: self.event_stream.clone(),
+        }
+    }
+
+    /// Configure where resumable upload state and partial content are kept
+    #[must_use]
+    pub fn with_resumable_uploads(
+        self,
+        resumable_uploads: Arc<dyn ResumableUploadRepository<Error = AppError>>,
+        upload_staging: UploadStaging,
+        resumable_upload_expiry: Duration,
+    ) -> Self {
+        Self::from_dependencies(Dependencies {
+            resumable_uploads,
+            upload_staging,
+            resumable_upload_expiry,
+            ..self.dependencies()
+        })
+    }
+
+    /// Configure where presigned upload sessions are kept
+    #[must_use]
+    pub fn with_upload_sessions(
+        self,
+        upload_sessions: Arc<dyn UploadSessionRepository<Error = AppError>>,
+    ) -> Self {
+        Self::from_dependencies(Dependencies { upload_sessions, ..self.dependencies() })
+    }
+
+    /// Configure where variant blob reference counts are kept
+    #[must_use]
+    pub fn with_variants(self, variants: Arc<dyn VariantRepository<Error = AppError>>) -> Self {
+        Self::from_dependencies(Dependencies { variants, ..self.dependencies() })
+    }
+
+    /// Configure the image pipeline rollout; the default only uses the stable pipeline
+    #[must_use]
+    pub fn with_image_pipeline(self, image_pipeline: ImagePipelineRollout) -> Self {
+        Self::from_dependencies(Dependencies { image_pipeline, ..self.dependencies() })
+    }
+
+    /// Configure video transcoding; without a processor videos are stored as uploaded
+    #[must_use]
+    pub fn with_video_processor(self, video_processor: Option<VideoProcessor>) -> Self {
+        Self::from_dependencies(Dependencies { video_processor, ..self.dependencies() })
+    }
+
+    /// Configure malware scanning of uploads; without it uploads are not scanned
+    #[must_use]
+    pub fn with_malware_scan(self, malware_scan: Option<MalwareScan>) -> Self {
+        Self::from_dependencies(Dependencies { malware_scan, ..self.dependencies() })
+    }
+
+    /// Configure per-user storage limits; the default is unlimited
+    #[must_use]
+    pub fn with_quota(self, quota: StorageQuota) -> Self {
+        Self::from_dependencies(Dependencies { quota, ..self.dependencies() })
+    }
+
+    /// Only accept uploads whose sniffed content type is listed; the default accepts any type
+    #[must_use]
+    pub fn with_allowed_types(self, allowed_types: impl Into<AllowedTypes>) -> Self {
+        Self::from_dependencies(Dependencies {
+            allowed_types: allowed_types.into(),
+            ..self.dependencies()
+        })
+    }
+
+    /// Limit the formats each tenant accepts on every upload flow; the default restricts none
+    #[must_use]
+    pub fn with_format_policy(self, format_policy: FormatPolicy) -> Self {
+        Self::from_dependencies(Dependencies { format_policy, ..self.dependencies() })
+    }
+
+    /// Serialize uploads and deletions of the same content with `upload_locks`;
+    /// the default only serializes them within this instance
+    #[must_use]
+    pub fn with_upload_locks(self, upload_locks: UploadLocks) -> Self {
+        Self::from_dependencies(Dependencies { upload_locks, ..self.dependencies() })
+    }
+
+    /// Replay responses to requests retried with an `Idempotency-Key` from
+    /// `idempotency_keys`; the default keeps them in memory for 24 hours
+    #[must_use]
+    pub fn with_idempotency_keys(self, idempotency_keys: IdempotencyKeys) -> Self {
+        Self::from_dependencies(Dependencies { idempotency_keys, ..self.dependencies() })
+    }
+
+    /// Collapse repeated presigned upload initiations into the first one; the
+    /// default creates a session for every request
+    #[must_use]
+    pub fn with_upload_fingerprints(self, upload_fingerprints: UploadFingerprints) -> Self {
+        Self::from_dependencies(Dependencies { upload_fingerprints, ..self.dependencies() })
+    }
+
+    /// Redirect downloads to the storage backend per `download_redirect`; the
+    /// default proxies every download
+    #[must_use]
+    pub fn with_download_redirect(self, download_redirect: DownloadRedirectPolicy) -> Self {
+        Self::from_dependencies(Dependencies { download_redirect, ..self.dependencies() })
+    }
+
+    /// Serve or retire the deprecated direct upload endpoint per `legacy_upload`;
+    /// the default keeps serving it without a sunset date
+    #[must_use]
+    pub fn with_legacy_upload(self, legacy_upload: LegacyUploadPolicy) -> Self {
+        Self::from_dependencies(Dependencies { legacy_upload, ..self.dependencies() })
+    }
+
+    /// Warn about media that takes longer than `processing_sla` from upload to
+    /// processed; the default has no SLA
+    #[must_use]
+    pub fn with_processing_sla(self, processing_sla: ProcessingSla) -> Self {
+        Self::from_dependencies(Dependencies { processing_sla, ..self.dependencies() })
+    }
+
+    /// Render images on demand only at the sizes `render` allows; the default
+    /// allows a few common display sizes
+    #[must_use]
+    pub fn with_render_policy(self, render: RenderPolicy) -> Self {
+        Self::from_dependencies(Dependencies { render, ..self.dependencies() })
+    }
+
+    /// Hold rendered images in `render_cache`; the default holds none
+    #[must_use]
+    pub fn with_render_cache(self, render_cache: RenderCache) -> Self {
+        Self::from_dependencies(Dependencies { render_cache, ..self.dependencies() })
+    }
+
+    /// Redirect renders to `render_provider` instead of rendering them; the
+    /// default renders every image here
+    #[must_use]
+    pub fn with_render_provider(self, render_provider: Option<RenderProvider>) -> Self {
+        Self::from_dependencies(Dependencies { render_provider, ..self.dependencies() })
+    }
+
+    /// Read the time from `clock` for upload timestamps and expiry, including
+    /// presigned URLs; the default is the system clock
+    #[must_use]
+    pub fn with_clock(self, clock: Arc<dyn Clock>) -> Self {
+        let presigned_url_service = self.presigned_url_service.clone().with_clock(clock.clone());
+        Self::from_dependencies(Dependencies {
+            presigned_url_service,
+            clock,
+            ..self.dependencies()
+        })
+    }
+
+    /// Report background work to `jobs`; the default is a registry of its own
+    #[must_use]
+    pub fn with_jobs(self, jobs: JobRegistry) -> Self {
+        Self::from_dependencies(Dependencies { jobs, ..self.dependencies() })
+    }
+
+    /// Choose the UUID version of generated upload IDs; the default is version 7
+    #[must_use]
+    pub fn with_uuid_version(self, uuid_version: UuidVersion) -> Self {
+        Self::from_dependencies(Dependencies { uuid_version, ..self.dependencies() })
+    }
+
+    /// Record upload and processing metrics; the default records none
+    #[must_use]
+    pub fn with_business_metrics(self, business_metrics: BusinessMetrics) -> Self {
+        Self::from_dependencies(Dependencies { business_metrics, ..self.dependencies() })
+    }
+
+    /// Refuse writes while `read_only` is enabled; the default accepts them
+    #[must_use]
+    pub fn with_read_only(self, read_only: ReadOnlyMode) -> Self {
+        Self::from_dependencies(Dependencies { read_only, ..self.dependencies() })
+    }
+
+    /// Judge dependencies by `health` in health and readiness checks; the default
+    /// gives each check 2 seconds and reports a database outage as degraded
+    #[must_use]
+    pub fn with_health_policy(self, health: HealthPolicy) -> Self {
+        Self::from_dependencies(Dependencies { health, ..self.dependencies() })
+    }
+
+    /// Set `Cache-Control` and `Surrogate-Key` headers per `cache_control`; the
+    /// default caches downloads for an hour and has metadata revalidated
+    #[must_use]
+    pub fn with_cache_control(self, cache_control: CacheControlPolicy) -> Self {
+        Self::from_dependencies(Dependencies { cache_control, ..self.dependencies() })
+    }
+
+    /// Bound the time and body size of requests to each class of routes by
+    /// `route_limits`; the default allows uploads 10 minutes and 100 MB
+    #[must_use]
+    pub fn with_route_limits(self, route_limits: RouteLimits) -> Self {
+        Self::from_dependencies(Dependencies { route_limits, ..self.dependencies() })
+    }
+
+    /// Turn away requests beyond the concurrency limits of `load_shedding`; the
+    /// default serves 64 uploads and 1024 other requests at once
+    #[must_use]
+    pub fn with_load_shedding(self, load_shedding: LoadSheddingPolicy) -> Self {
+        Self::from_dependencies(Dependencies { load_shedding, ..self.dependencies() })
+    }
+
+    /// Purge changed media and recipes from the CDN through `cdn`; the default
+    /// purges nothing
+    #[must_use]
+    pub fn with_cdn_purges(self, cdn: CdnPurges) -> Self {
+        Self::from_dependencies(Dependencies { cdn, ..self.dependencies() })
+    }
+
+    /// Post media lifecycle events to the endpoints of `webhooks`; the default
+    /// posts nothing
+    #[must_use]
+    pub fn with_webhooks(self, webhooks: Webhooks) -> Self {
+        Self::from_dependencies(Dependencies { webhooks, ..self.dependencies() })
+    }
+
+    /// Publish media lifecycle events to the broker of `event_stream`; the
+    /// default publishes nothing
+    #[must_use]
+    pub fn with_event_stream(self, event_stream: EventStream) -> Self {
+        Self::from_dependencies(Dependencies { event_stream, ..self.dependencies() })
     }
 }
 
 /// Upload a new media file
 ///
+/// Images in a format the client's tenant doesn't accept are converted into one
+/// it does; see [`FormatPolicy`].
+///
+/// Deprecated: the whole file is buffered in memory. Use the presigned or
+/// resumable upload flows instead; responses carry `Deprecation` and `Sunset`
+/// headers, and the endpoint answers 410 Gone once disabled.
+///
 /// # Errors
 /// Returns appropriate HTTP status codes for various error conditions
+#[utoipa::path(
+    post,
+    path = "/api/v1/media-management/media",
+    tag = "media",
+    request_body(
+        content = UploadMediaRequest,
+        content_type = "multipart/form-data",
+        description = "A `file` part with the content, and a `filename` part if the file part has none"
+    ),
+    responses(
+        (status = 200, description = "Media stored and queued for processing", body = UploadMediaResponse),
+        (status = 400, description = "Malformed multipart body or missing file", body = ErrorResponse),
+        (status = 413, description = "File or storage quota too large", body = ErrorResponse),
+        (status = 410, description = "Direct uploads have been retired", body = ErrorResponse),
+        (status = 422, description = "Content does not match its declared type, or is not accepted for the client and cannot be converted", body = ErrorResponse)
+    )
+)]
+#[deprecated(note = "Use the presigned or resumable upload flows")]
 pub async fn upload_media(
     State(app_state): State<AppState>,
+    route: MatchedPath,
+    user: Option<UserContext>,
     mut multipart: Multipart,
 ) -> Result<Json<UploadMediaResponse>, AppError> {
     tracing::info!("Processing media upload request");
@@ -80,13 +543,18 @@ pub async fn upload_media(
                 }
 
                 // Read file data
-                let data = field.bytes().await.map_err(|e| AppError::BadRequest {
-                    message: format!("Failed to read file data: {e}"),
+                let data = field.bytes().await.map_err(|e| {
+                    if e.status() != StatusCode::PAYLOAD_TOO_LARGE {
+                        app_state
+                            .business_metrics
+                            .upload_aborted(UploadFlow::Direct, route.as_str());
+                    }
+                    AppError::BadRequest { message: format!("Failed to read file data: {e}") }
                 })?;
 
                 // Check size limit
                 if data.len() as u64 > app_state.max_file_size {
-                    return Err(AppError::BadRequest { message: "File too large".to_string() });
+                    return Err(file_too_large(data.len() as u64, app_state.max_file_size));
                 }
 
                 file_data = Some(data.to_vec());
@@ -121,21 +589,19 @@ pub async fn upload_media(
         file_data.len()
     );
 
-    // Create upload use case and execute
-    let upload_use_case = UploadMediaUseCase::new(
-        app_state.repository.clone(),
-        app_state.storage.clone(),
-        app_state.max_file_size,
-    );
-
-    // For now, use a default user ID. In production, this would come from authentication
-    let user_id = UserId::new();
+    let user_id = owner_id(user.as_ref());
+    let tenant = user.as_ref().map(|user| user.client_id.as_str());
 
     let file_cursor = std::io::Cursor::new(file_data);
-    let response =
-        upload_use_case.execute(file_cursor, filename, user_id, content_type_detected).await?;
+    let response = app_state
+        .use_cases
+        .upload_media
+        .run_once(|uc| {
+            uc.execute_for_tenant(tenant, file_cursor, filename, user_id, content_type_detected)
+        })
+        .await?;
 
-    tracing::info!("Media upload completed successfully: {}", response.media_id);
+    spawn_media_processing(&app_state, response.media_id);
 
     Ok(Json(response))
 }
@@ -146,10 +612,31 @@ pub async fn upload_media(
 /// can use to upload the file directly. This enables better progress tracking
 /// and handling of large files.
 ///
+/// Example payloads for every outcome are in `docs/api/openapi.yaml`.
+///
 /// # Errors
-/// Returns appropriate HTTP status codes for various error conditions
+/// Returns appropriate HTTP status codes for various error conditions:
+/// - 400 Bad Request: Empty filename or size, dangerous extension, malformed content type
+/// - 413 Payload Too Large: The declared size exceeds the maximum file size
+/// - 503 Service Unavailable: The database is disconnected
+#[utoipa::path(
+    post,
+    path = "/api/v1/media-management/media/upload-request",
+    tag = "uploads",
+    request_body = InitiateUploadRequest,
+    params(
+        ("Idempotency-Key" = Option<String>, Header, description = "Replays the first response to authenticated retries with the same key and body")
+    ),
+    responses(
+        (status = 200, description = "Upload session created", body = InitiateUploadResponse),
+        (status = 400, description = "Invalid filename, size or content type", body = ErrorResponse),
+        (status = 413, description = "Declared size exceeds the maximum file size", body = ErrorResponse),
+        (status = 503, description = "The database is disconnected", body = ErrorResponse)
+    )
+)]
 pub async fn initiate_upload(
     State(app_state): State<AppState>,
+    user: Option<UserContext>,
     Json(request): Json<InitiateUploadRequest>,
 ) -> Result<Json<InitiateUploadResponse>, AppError> {
     tracing::info!(
@@ -158,22 +645,14 @@ pub async fn initiate_upload(
         request.file_size
     );
 
-    // For now, use a default user ID - in production this would come from authentication
-    let user_id = UserId::default();
+    let user_id = owner_id(user.as_ref());
+    let tenant = user.as_ref().map(|user| user.client_id.as_str());
 
-    let use_case = InitiateUploadUseCase::new(
-        app_state.repository.clone(),
-        app_state.presigned_url_service.clone(),
-        app_state.max_file_size,
-    );
-
-    let response = use_case.execute(request, user_id).await?;
-
-    tracing::info!(
-        "Upload session created successfully: media_id={}, expires={}",
-        response.media_id,
-        response.expires_at
-    );
+    let response = app_state
+        .use_cases
+        .initiate_upload
+        .run_once(|uc| uc.execute_for_tenant(tenant, request, user_id))
+        .await?;
 
     Ok(Json(response))
 }
@@ -184,29 +663,54 @@ pub async fn initiate_upload(
 /// This endpoint is used for polling the status of uploads initiated
 /// via the presigned URL system.
 ///
+/// Example payloads for each processing state are in `docs/api/openapi.yaml`.
+///
 /// # Errors
-/// Returns appropriate HTTP status codes for various error conditions
+/// Returns appropriate HTTP status codes for various error conditions:
+/// - 400 Bad Request: The media ID is not an integer
+/// - 403 Forbidden: The media belongs to another user, or no token was sent
+/// - 404 Not Found: Media with the given ID doesn't exist
+#[utoipa::path(
+    get,
+    path = "/api/v1/media-management/media/{id}/status",
+    tag = "uploads",
+    params(("id" = i64, Path, description = "Media ID", minimum = 1)),
+    responses(
+        (status = 200, description = "Current processing status", body = UploadStatusResponse),
+        (status = 400, description = "The media ID is not a positive integer", body = ErrorResponse),
+        (status = 403, description = "The media belongs to another user, or no token was sent", body = ErrorResponse),
+        (status = 404, description = "Media not found", body = ErrorResponse)
+    )
+)]
 pub async fn get_upload_status(
     State(app_state): State<AppState>,
+    user: Option<UserContext>,
     Path(media_id): Path<MediaId>,
 ) -> Result<Json<UploadStatusResponse>, AppError> {
     tracing::info!("Getting upload status for media_id: {}", media_id);
 
-    let get_media_use_case = GetMediaUseCase::new(app_state.repository.clone());
+    let requester = user.as_ref().map(UserContext::owner_id);
+    let media = app_state.use_cases.get_media.run(|uc| uc.execute(media_id, requester)).await?;
 
-    let media = get_media_use_case.execute(media_id).await?;
+    let progress = app_state.processing_progress.current(media_id);
+    Ok(Json(upload_status(media, progress)))
+}
 
-    // Convert Media to UploadStatusResponse
-    let response = UploadStatusResponse {
+/// Describe the processing state of `media` for status polling
+///
+/// `progress` is how far processing has got on this instance; media processed
+/// elsewhere reports no progress while `Processing`.
+fn upload_status(media: MediaDto, progress: Option<u8>) -> UploadStatusResponse {
+    UploadStatusResponse {
         media_id: media.id,
         status: media.processing_status.clone(),
         progress: match media.processing_status {
-            crate::domain::value_objects::ProcessingStatus::Processing => Some(50),
+            crate::domain::value_objects::ProcessingStatus::Processing => progress,
             crate::domain::value_objects::ProcessingStatus::Complete => Some(100),
             crate::domain::value_objects::ProcessingStatus::Pending
             | crate::domain::value_objects::ProcessingStatus::Failed => Some(0),
         },
-        error_message: None, // TODO: Add error message field to Media entity
+        error_message: media.processing_error,
         download_url: if media.processing_status.is_complete() {
             Some(format!("/api/v1/media-management/media/{}/download", media.id))
         } else {
@@ -219,89 +723,173 @@ pub async fn get_upload_status(
         } else {
             None
         },
-    };
-
-    Ok(Json(response))
+    }
 }
 
 /// Handle file upload to presigned URL
 ///
 /// This handler receives the actual file data for a presigned upload session.
-/// It validates the upload token/signature, processes the file, and updates
-/// the media record status.
+/// It resolves the upload token to its persisted session, verifies the signature
+/// against it, and fills in the media record reserved when the upload was initiated.
 ///
 /// # Errors
-/// Returns appropriate HTTP status codes for various error conditions
+/// Returns appropriate HTTP status codes for various error conditions:
+/// - 404 Not Found: No session exists for the upload token
+/// - 400 Bad Request: The session has expired or the file does not match it
+/// - 401 Unauthorized: The signature was not issued for this session
+#[utoipa::path(
+    put,
+    path = "/api/v1/media-management/media/upload/{token}",
+    tag = "uploads",
+    params(("token" = String, Path, description = "Upload token from the upload request"), UploadParams),
+    request_body(content = Vec<u8>, content_type = "application/octet-stream"),
+    responses(
+        (status = 200, description = "File stored and queued for processing", body = UploadMediaResponse),
+        (status = 400, description = "Session expired or file does not match it", body = ErrorResponse),
+        (status = 401, description = "Signature was not issued for this session", body = ErrorResponse),
+        (status = 404, description = "No session exists for the token", body = ErrorResponse),
+        (status = 413, description = "Body exceeds the session's size", body = ErrorResponse)
+    )
+)]
 pub async fn upload_file(
     State(app_state): State<AppState>,
+    route: MatchedPath,
     Path(upload_token): Path<String>,
     Query(params): Query<UploadParams>,
     body: Body,
 ) -> Result<Json<UploadMediaResponse>, AppError> {
     tracing::info!("Processing file upload for token: {}", upload_token);
 
-    // Validate the presigned URL parameters
-    app_state.presigned_url_service.validate_upload_url(
-        &upload_token,
-        &params.signature,
-        params.expires,
-        params.size,
-        &params.r#type,
-    )?;
-
-    // Collect the body into bytes
-    let body_bytes = match axum::body::to_bytes(body, params.size as usize).await {
-        Ok(bytes) => bytes,
-        Err(e) => {
-            tracing::error!("Failed to read request body: {}", e);
-            return Err(AppError::BadRequest {
-                message: "Failed to read uploaded file data".to_string(),
-            });
+    let upload_use_case = &app_state.use_cases.complete_presigned_upload;
+
+    // Reject unknown and expired sessions before reading the body
+    let session =
+        upload_use_case.run(|uc| uc.find_session(&upload_token, &params.signature)).await?;
+
+    // The client-supplied size is only trusted to lower the cap, never to raise it
+    let cap = params.size.min(session.expected_size).min(app_state.max_file_size);
+    let body_bytes = match read_body_capped(body, cap).await? {
+        BodyRead::Complete(bytes) => bytes,
+        // The session is left unconsumed, so the client can upload again
+        BodyRead::Interrupted(_) => {
+            app_state.business_metrics.upload_aborted(UploadFlow::Presigned, route.as_str());
+            return Err(upload_interrupted());
         }
     };
 
-    // Validate file size
-    if body_bytes.len() as u64 != params.size {
-        return Err(AppError::BadRequest {
-            message: format!(
-                "File size mismatch: expected {} bytes, got {} bytes",
-                params.size,
-                body_bytes.len()
-            ),
-        });
+    tracing::info!("Received file upload: {} bytes, type: {}", body_bytes.len(), params.r#type);
+
+    let response = upload_use_case
+        .run_once(|uc| {
+            uc.execute(&upload_token, &params.signature, std::io::Cursor::new(body_bytes))
+        })
+        .await?;
+
+    spawn_media_processing(&app_state, response.media_id);
+
+    Ok(Json(response))
+}
+
+/// A request body read up to its end or until the stream failed
+#[derive(Debug)]
+pub(crate) enum BodyRead {
+    Complete(Bytes),
+    /// The stream failed part way, usually because the client disconnected;
+    /// holds the bytes that arrived first
+    Interrupted(Bytes),
+}
+
+/// Read a request body, aborting with `PayloadTooLarge` as soon as it exceeds `cap` bytes
+///
+/// A declared `Content-Length` above the cap is rejected before any data is read.
+pub(crate) async fn read_body_capped(body: Body, cap: u64) -> Result<BodyRead, AppError> {
+    let too_large = || AppError::PayloadTooLarge {
+        message: format!("Upload exceeds the maximum of {cap} bytes"),
+    };
+
+    if body.size_hint().lower() > cap {
+        return Err(too_large());
     }
 
-    tracing::info!("Received file upload: {} bytes, type: {}", body_bytes.len(), params.r#type);
+    let mut buffer = BytesMut::new();
+    let mut stream = body.into_data_stream();
+    while let Some(chunk) = stream.next().await {
+        let chunk = match chunk {
+            Ok(chunk) => chunk,
+            Err(e) => {
+                tracing::warn!("Request body ended after {} bytes: {}", buffer.len(), e);
+                return Ok(BodyRead::Interrupted(buffer.freeze()));
+            }
+        };
 
-    // Find the media record created during upload initiation
-    // For now, extract media ID from upload token
-    // TODO: This should look up the media record from the upload session in database
-    let _media_id = crate::domain::entities::MediaId::new(1); // Placeholder - should come from database lookup
+        if (buffer.len() + chunk.len()) as u64 > cap {
+            return Err(too_large());
+        }
+        buffer.extend_from_slice(&chunk);
+    }
 
-    // Create a cursor from the uploaded bytes
-    let file_reader = std::io::Cursor::new(body_bytes);
+    Ok(BodyRead::Complete(buffer.freeze()))
+}
 
-    // Use the UploadMediaUseCase to process the file
-    let upload_use_case = crate::application::use_cases::UploadMediaUseCase::new(
-        app_state.repository.clone(),
-        app_state.storage.clone(),
-        app_state.max_file_size,
-    );
+/// The error for an upload whose body stopped before it was complete
+pub(crate) fn upload_interrupted() -> AppError {
+    AppError::BadRequest { message: "Failed to read uploaded file data".to_string() }
+}
 
-    // For now, use default user ID. In production, this would come from the upload session
-    let user_id = crate::domain::entities::UserId::new();
+/// The user that media created by this request belongs to
+///
+/// Unauthenticated requests get a fresh anonymous user, so nobody can list
+/// what they upload.
+pub(crate) fn owner_id(user: Option<&UserContext>) -> UserId {
+    user.map_or_else(UserId::new, UserContext::owner_id)
+}
 
-    // Extract filename from upload token (placeholder logic)
-    let filename = format!("upload_{upload_token}.bin");
+/// Generate variants for a newly uploaded file in the background
+///
+/// Processing failures are recorded on the media's status rather than surfaced
+/// to the uploader. The returned handle resolves to the processed media.
+pub(crate) fn spawn_media_processing(
+    app_state: &AppState,
+    media_id: MediaId,
+) -> tokio::task::JoinHandle<Result<Media, AppError>> {
+    spawn_processing(app_state, media_id, false)
+}
 
-    // Process the upload with content type validation
-    let response =
-        upload_use_case.execute(file_reader, filename, user_id, Some(params.r#type)).await?;
+/// Generate variants again for media reset to `Pending`, in the background
+///
+/// Used by the reprocess endpoint and by backfills, which wait on the outcome;
+/// it is published as reprocessing, so the upload is not announced again.
+pub(crate) fn spawn_media_reprocessing(
+    app_state: &AppState,
+    media_id: MediaId,
+) -> tokio::task::JoinHandle<Result<Media, AppError>> {
+    spawn_processing(app_state, media_id, true)
+}
 
-    Ok(Json(response))
+fn spawn_processing(
+    app_state: &AppState,
+    media_id: MediaId,
+    reprocess: bool,
+) -> tokio::task::JoinHandle<Result<Media, AppError>> {
+    let use_cases = app_state.use_cases.clone();
+    let queued = app_state.jobs.enqueue(jobs::MEDIA_PROCESSING);
+
+    tokio::spawn(async move {
+        // Failures are logged by the decorator and counted by the job registry
+        let processing = Box::pin(use_cases.process_media.run_once(|uc| async move {
+            if reprocess {
+                uc.reprocess(media_id).await
+            } else {
+                uc.execute(media_id).await
+            }
+        }));
+        queued.run(processing).await
+    })
 }
 
-#[derive(Debug, serde::Deserialize)]
+/// Query parameters signed into a presigned upload URL
+#[derive(Debug, serde::Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct UploadParams {
     pub signature: String,
     pub expires: i64,
@@ -316,43 +904,202 @@ pub struct UploadParams {
 ///
 /// # Errors
 /// Returns appropriate HTTP status codes for various error conditions
+#[utoipa::path(
+    get,
+    path = "/api/v1/media-management/media",
+    tag = "media",
+    params(PaginatedMediaQuery),
+    responses(
+        (status = 200, description = "One page of the caller's media", body = PaginatedMediaResponse),
+        (status = 400, description = "Invalid cursor or query parameter", body = ErrorResponse)
+    )
+)]
 pub async fn list_media(
     State(app_state): State<AppState>,
+    user: Option<UserContext>,
     Query(query): Query<PaginatedMediaQuery>,
 ) -> Result<Json<PaginatedMediaResponse>, AppError> {
     tracing::info!("Processing paginated media list request with query: {:?}", query);
 
-    let list_use_case = ListMediaUseCase::new(app_state.repository.clone());
+    let user_id = owner_id(user.as_ref());
 
-    // For now, use a default user ID. In production, this would come from authentication
-    let user_id = UserId::new();
+    let paginated_response =
+        app_state.use_cases.list_media.run(|uc| uc.execute(query.clone(), user_id)).await?;
 
-    let paginated_response = list_use_case.execute(query, user_id).await?;
+    Ok(Json(paginated_response))
+}
 
-    tracing::info!(
-        "Retrieved paginated response with {} media files",
-        paginated_response.data.len()
-    );
+/// Search media files with pagination
+///
+/// Filters by filename substring, MIME type family, size range, upload date range
+/// and processing status; only media matching every given filter is returned.
+///
+/// # Errors
+/// Returns appropriate HTTP status codes for various error conditions:
+/// - 400 Bad Request: Invalid cursor, or an invalid or empty filter range
+#[utoipa::path(
+    get,
+    path = "/api/v1/media-management/media/search",
+    tag = "media",
+    params(SearchMediaQuery),
+    responses(
+        (status = 200, description = "One page of the caller's matching media", body = PaginatedMediaResponse),
+        (status = 400, description = "Invalid cursor or filter", body = ErrorResponse)
+    )
+)]
+pub async fn search_media(
+    State(app_state): State<AppState>,
+    user: Option<UserContext>,
+    Query(query): Query<SearchMediaQuery>,
+) -> Result<Json<PaginatedMediaResponse>, AppError> {
+    tracing::info!("Processing media search request with query: {:?}", query);
 
-    Ok(Json(paginated_response))
+    let user_id = owner_id(user.as_ref());
+
+    let response =
+        app_state.use_cases.search_media.run(|uc| uc.execute(query.clone(), user_id)).await?;
+
+    Ok(Json(response))
+}
+
+/// Report the caller's storage consumption and quota
+///
+/// # Errors
+/// Returns appropriate HTTP status codes for various error conditions:
+/// - 401 Unauthorized: The request is not authenticated
+#[utoipa::path(
+    get,
+    path = "/api/v1/media-management/media/usage",
+    tag = "media",
+    responses(
+        (status = 200, description = "Storage used by the caller and their limits", body = StorageUsageResponse),
+        (status = 401, description = "The request is not authenticated", body = ErrorResponse)
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn get_storage_usage(
+    State(app_state): State<AppState>,
+    user: UserContext,
+) -> Result<Json<StorageUsageResponse>, AppError> {
+    let user_id = user.owner_id();
+
+    let usage = app_state.use_cases.get_storage_usage.run(|uc| uc.execute(user_id)).await?;
+
+    Ok(Json(usage))
 }
 
 /// Get media information by ID
 ///
+/// Only the uploader can see media; requests without a token are rejected.
+///
 /// # Errors
 /// Returns appropriate HTTP status codes for various error conditions
+#[utoipa::path(
+    get,
+    path = "/api/v1/media-management/media/{id}",
+    tag = "media",
+    params(("id" = i64, Path, description = "Media ID", minimum = 1)),
+    responses(
+        (status = 200, description = "Media metadata", body = MediaDto),
+        (status = 400, description = "The media ID is not a positive integer", body = ErrorResponse),
+        (status = 403, description = "The media belongs to another user, or no token was sent", body = ErrorResponse),
+        (status = 404, description = "Media not found", body = ErrorResponse)
+    )
+)]
 pub async fn get_media(
     State(app_state): State<AppState>,
+    user: Option<UserContext>,
     Path(id): Path<MediaId>,
-) -> Result<Json<MediaDto>, AppError> {
+) -> Result<Response, AppError> {
     tracing::info!("Processing get media request for ID: {}", id);
 
-    let get_use_case = GetMediaUseCase::new(app_state.repository.clone());
-    let media_dto = get_use_case.execute(id).await?;
+    let requester = user.as_ref().map(UserContext::owner_id);
+    let media_dto = app_state.use_cases.get_media.run(|uc| uc.execute(id, requester)).await?;
+
+    // Only the uploader gets here, so the response is never shared
+    let policy = &app_state.cache_control;
+    Ok(with_cache_headers(
+        Json(media_dto),
+        &policy.metadata(true),
+        policy.surrogate_key(&[id.as_i64()], &[]),
+    ))
+}
+
+/// List the renditions of a media file
+///
+/// Returns the original upload followed by each variant the processing
+/// pipeline generated, such as WebP, AVIF and thumbnail variants of images or the MP4
+/// encoding and poster frame of videos.
+///
+/// # Errors
+/// Returns appropriate HTTP status codes for various error conditions:
+/// - 403 Forbidden: The media belongs to another user, or no token was sent
+/// - 404 Not Found: Media with the given ID doesn't exist
+#[utoipa::path(
+    get,
+    path = "/api/v1/media-management/media/{id}/variants",
+    tag = "media",
+    params(("id" = i64, Path, description = "Media ID", minimum = 1)),
+    responses(
+        (status = 200, description = "The original and generated variants", body = MediaVariantsResponse),
+        (status = 400, description = "The media ID is not a positive integer", body = ErrorResponse),
+        (status = 403, description = "The media belongs to another user, or no token was sent", body = ErrorResponse),
+        (status = 404, description = "Media not found", body = ErrorResponse)
+    )
+)]
+pub async fn get_media_variants(
+    State(app_state): State<AppState>,
+    user: Option<UserContext>,
+    Path(id): Path<MediaId>,
+) -> Result<Response, AppError> {
+    tracing::info!("Processing get media variants request for ID: {}", id);
+
+    let requester = user.as_ref().map(UserContext::owner_id);
+    let response = app_state.use_cases.get_media.run(|uc| uc.list_variants(id, requester)).await?;
+
+    // Only the uploader gets here, so the response is never shared
+    let policy = &app_state.cache_control;
+    Ok(with_cache_headers(
+        Json(response),
+        &policy.metadata(true),
+        policy.surrogate_key(&[id.as_i64()], &[]),
+    ))
+}
 
-    tracing::info!("Retrieved media: {}", media_dto.original_filename);
+/// Get the metadata of up to 100 media in one request
+///
+/// Each distinct ID gets a result, in request order, marked `found`,
+/// `not_found` or `forbidden`; missing or foreign media don't fail the batch.
+///
+/// # Errors
+/// Returns 400 Bad Request if more than 100 distinct IDs are requested
+#[utoipa::path(
+    post,
+    path = "/api/v1/media-management/media/batch-get",
+    tag = "media",
+    request_body = BatchGetMediaRequest,
+    responses(
+        (status = 200, description = "One result per distinct requested ID", body = BatchGetMediaResponse),
+        (status = 400, description = "More than 100 distinct IDs were requested", body = ErrorResponse),
+        (status = 422, description = "An ID is not a positive integer"),
+        (status = 503, description = "The database is disconnected", body = ErrorResponse)
+    )
+)]
+pub async fn batch_get_media(
+    State(app_state): State<AppState>,
+    user: Option<UserContext>,
+    Json(request): Json<BatchGetMediaRequest>,
+) -> Result<Json<BatchGetMediaResponse>, AppError> {
+    tracing::info!("Processing batch get request for {} media IDs", request.ids.len());
+
+    let requester = user.as_ref().map(UserContext::owner_id);
+    let response = app_state
+        .use_cases
+        .batch_get_media
+        .run(|uc| uc.execute(request.ids.clone(), requester))
+        .await?;
 
-    Ok(Json(media_dto))
+    Ok(Json(response))
 }
 
 /// Delete media by ID
@@ -364,135 +1111,825 @@ pub async fn get_media(
 /// Returns appropriate HTTP status codes for various error conditions:
 /// - 404 Not Found: Media with the given ID doesn't exist
 /// - 500 Internal Server Error: Storage or database operation failed
+#[utoipa::path(
+    delete,
+    path = "/api/v1/media-management/media/{id}",
+    tag = "media",
+    params(("id" = i64, Path, description = "Media ID", minimum = 1)),
+    responses(
+        (status = 204, description = "Media deleted"),
+        (status = 403, description = "The media belongs to another user, or no token was sent", body = ErrorResponse),
+        (status = 404, description = "Media not found", body = ErrorResponse),
+        (status = 500, description = "Storage or database operation failed", body = ErrorResponse)
+    )
+)]
 pub async fn delete_media(
     State(app_state): State<AppState>,
+    user: Option<UserContext>,
     Path(id): Path<MediaId>,
 ) -> Result<StatusCode, AppError> {
     tracing::info!("Processing delete media request for ID: {}", id);
 
-    let delete_use_case =
-        DeleteMediaUseCase::new(app_state.repository.clone(), app_state.storage.clone());
-
-    delete_use_case.execute(id).await?;
-
-    tracing::info!("Successfully deleted media: {}", id);
+    let requester = user.as_ref().map(UserContext::owner_id);
+    app_state.use_cases.delete_media.run_once(|uc| uc.execute(id, requester)).await?;
 
     // Return 204 No Content to indicate successful deletion
     Ok(StatusCode::NO_CONTENT)
 }
 
+/// Delete up to 100 media in one request
+///
+/// Each distinct ID is deleted on its own and gets a result, in request order,
+/// marked `deleted`, `not_found`, `forbidden` or `failed`; one failure doesn't
+/// stop the others.
+///
+/// # Errors
+/// Returns 400 Bad Request if more than 100 distinct IDs are requested
+#[utoipa::path(
+    post,
+    path = "/api/v1/media-management/media/batch-delete",
+    tag = "media",
+    request_body = BatchDeleteMediaRequest,
+    responses(
+        (status = 200, description = "One result per distinct requested ID", body = BatchDeleteMediaResponse),
+        (status = 400, description = "More than 100 distinct IDs were requested", body = ErrorResponse),
+        (status = 422, description = "An ID is not a positive integer")
+    )
+)]
+pub async fn batch_delete_media(
+    State(app_state): State<AppState>,
+    user: Option<UserContext>,
+    Json(request): Json<BatchDeleteMediaRequest>,
+) -> Result<Json<BatchDeleteMediaResponse>, AppError> {
+    tracing::info!("Processing batch delete request for {} media IDs", request.ids.len());
+
+    let requester = user.as_ref().map(UserContext::owner_id);
+    let response = app_state
+        .use_cases
+        .delete_media
+        .run_once(|uc| uc.execute_many(request.ids, requester))
+        .await?;
+
+    Ok(Json(response))
+}
+
+/// Replace the tags on a media file
+///
+/// The request lists the complete new set of tags; tags are lowercased and
+/// repeats dropped, and an empty list removes every tag.
+///
+/// # Errors
+/// Returns appropriate HTTP status codes for various error conditions:
+/// - 400 Bad Request: A tag is invalid or more than 20 were given
+/// - 403 Forbidden: The media belongs to another user, or no token was sent
+/// - 404 Not Found: Media with the given ID doesn't exist
+#[utoipa::path(
+    put,
+    path = "/api/v1/media-management/media/{id}/tags",
+    tag = "media",
+    params(("id" = i64, Path, description = "Media ID", minimum = 1)),
+    request_body = SetMediaTagsRequest,
+    responses(
+        (status = 200, description = "The media's tags after the update", body = MediaTagsResponse),
+        (status = 400, description = "A tag is invalid or there are too many", body = ErrorResponse),
+        (status = 403, description = "The media belongs to another user, or no token was sent", body = ErrorResponse),
+        (status = 404, description = "Media not found", body = ErrorResponse)
+    )
+)]
+pub async fn set_media_tags(
+    State(app_state): State<AppState>,
+    user: Option<UserContext>,
+    Path(id): Path<MediaId>,
+    Json(request): Json<SetMediaTagsRequest>,
+) -> Result<Json<MediaTagsResponse>, AppError> {
+    tracing::info!("Processing set tags request for media ID: {}", id);
+
+    let requester = user.as_ref().map(UserContext::owner_id);
+    let response = app_state
+        .use_cases
+        .set_media_tags
+        .run_once(|uc| uc.execute(id, request, requester))
+        .await?;
+
+    Ok(Json(response))
+}
+
+/// Process a stored original again, regenerating its variants
+///
+/// Used after thumbnail sizes or encoding settings change. The media is reset
+/// to `Pending` and processed in the background like a new upload, so it
+/// cannot be downloaded until its status is `Complete` again.
+///
+/// # Errors
+/// Returns appropriate HTTP status codes for various error conditions:
+/// - 400 Bad Request: The media ID is not an integer
+/// - 403 Forbidden: The media belongs to another user, or no token was sent
+/// - 404 Not Found: Media with the given ID doesn't exist
+/// - 409 Conflict: The media is already pending or being processed
+#[utoipa::path(
+    post,
+    path = "/api/v1/media-management/media/{id}/reprocess",
+    tag = "media",
+    params(("id" = i64, Path, description = "Media ID", minimum = 1)),
+    responses(
+        (status = 202, description = "Reprocessing queued", body = UploadStatusResponse),
+        (status = 400, description = "The media ID is not a positive integer", body = ErrorResponse),
+        (status = 403, description = "The media belongs to another user, or no token was sent", body = ErrorResponse),
+        (status = 404, description = "Media not found", body = ErrorResponse),
+        (status = 409, description = "The media is already pending or being processed", body = ErrorResponse)
+    )
+)]
+pub async fn reprocess_media(
+    State(app_state): State<AppState>,
+    user: Option<UserContext>,
+    Path(id): Path<MediaId>,
+) -> Result<(StatusCode, Json<UploadStatusResponse>), AppError> {
+    tracing::info!("Processing reprocess request for media ID: {}", id);
+
+    let requester = user.as_ref().map(UserContext::owner_id);
+    let media =
+        app_state.use_cases.reprocess_media.run_once(|uc| uc.execute(id, requester)).await?;
+    spawn_media_reprocessing(&app_state, id);
+
+    Ok((StatusCode::ACCEPTED, Json(upload_status(media.into(), None))))
+}
+
 /// Download media file
 ///
+/// Images with generated variants are served in the smallest format the `Accept`
+/// header allows, so browsers that support AVIF or WebP receive those instead of
+/// the original. A single `Range` is honored with `206 Partial Content`, and the
+/// file is streamed from storage rather than buffered. When the deployment
+/// redirects downloads, the response is instead a `302` to a short-lived storage
+/// URL; see [`DownloadRedirectPolicy`].
+///
 /// # Errors
 /// Returns appropriate HTTP status codes for various error conditions
+#[utoipa::path(
+    get,
+    path = "/api/v1/media-management/media/{id}/download",
+    tag = "media",
+    params(
+        ("id" = i64, Path, description = "Media ID", minimum = 1),
+        ("Range" = Option<String>, Header, description = "A single byte range, e.g. `bytes=0-1023`")
+    ),
+    responses(
+        (status = 200, description = "The file content", body = Vec<u8>, content_type = "application/octet-stream"),
+        (status = 206, description = "The requested byte range", body = Vec<u8>, content_type = "application/octet-stream"),
+        (status = 302, description = "Download the file from the storage URL in `Location`", headers(("Location" = String, description = "Presigned storage URL"))),
+        (status = 401, description = "No token was sent", body = ErrorResponse),
+        (status = 403, description = "The media belongs to another user", body = ErrorResponse),
+        (status = 404, description = "Media not found", body = ErrorResponse),
+        (status = 416, description = "The range lies outside the file", body = ErrorResponse)
+    )
+)]
 pub async fn download_media(
     State(app_state): State<AppState>,
+    user: Option<UserContext>,
     Path(id): Path<MediaId>,
+    headers: HeaderMap,
 ) -> Result<Response<Body>, AppError> {
     tracing::info!("Processing download request for media ID: {}", id);
 
-    let download_use_case =
-        DownloadMediaUseCase::new(app_state.repository.clone(), app_state.storage.clone());
+    let accept = headers.get(header::ACCEPT).and_then(|v| v.to_str().ok());
+    let range = headers.get(header::RANGE).and_then(|v| v.to_str().ok());
+    let requester = user.as_ref().map(UserContext::owner_id);
+    let download_response = app_state
+        .use_cases
+        .download_media
+        .run(|uc| uc.execute_negotiated(id, accept, range, requester))
+        .await?;
+
+    let cache_control = app_state.cache_control.content(requester.is_some());
+    serve_download(&app_state, id, download_response, &cache_control, true)
+}
+
+/// Describe a media download without its content
+///
+/// Answers with the headers a `GET` of the same URL would: the length, type,
+/// `ETag` and caching of the representation the `Accept` header selects, read
+/// from storage metadata so nothing is opened. Download managers use it to
+/// check the size before fetching.
+///
+/// # Errors
+/// Returns appropriate HTTP status codes for various error conditions
+#[utoipa::path(
+    head,
+    path = "/api/v1/media-management/media/{id}/download",
+    tag = "media",
+    params(("id" = i64, Path, description = "Media ID", minimum = 1)),
+    responses(
+        (status = 200, description = "The file's headers, without content", headers(
+            ("Content-Length" = u64, description = "Size of the file in bytes"),
+            ("ETag" = String, description = "Hash of the file's content")
+        )),
+        (status = 302, description = "Download the file from the storage URL in `Location`", headers(("Location" = String, description = "Presigned storage URL"))),
+        (status = 401, description = "No token was sent"),
+        (status = 403, description = "The media belongs to another user"),
+        (status = 404, description = "Media not found")
+    )
+)]
+pub async fn head_media_download(
+    State(app_state): State<AppState>,
+    user: Option<UserContext>,
+    Path(id): Path<MediaId>,
+    headers: HeaderMap,
+) -> Result<Response<Body>, AppError> {
+    tracing::info!("Processing download HEAD request for media ID: {}", id);
+
+    let accept = headers.get(header::ACCEPT).and_then(|v| v.to_str().ok());
+    let requester = user.as_ref().map(UserContext::owner_id);
+    let download_response =
+        app_state.use_cases.download_media.run(|uc| uc.execute_head(id, accept, requester)).await?;
+
+    let cache_control = app_state.cache_control.content(requester.is_some());
+    serve_download(&app_state, id, download_response, &cache_control, false)
+}
+
+/// Download media content by its hash
+///
+/// `content_hash` is the SHA-256 of an original upload, optionally followed by
+/// the name of one of its generated variants, as in `{hash}.webp`. Content at
+/// such a URL never changes, so it is served with `immutable` caching, unlike
+/// the ID-based download URL, and never redirected. Only the caller's own media
+/// is found. A single `Range` is honored as on the ID-based download.
+///
+/// # Errors
+/// Returns appropriate HTTP status codes for various error conditions
+#[utoipa::path(
+    get,
+    path = "/api/v1/media-management/media/by-hash/{content_hash}",
+    tag = "media",
+    params(
+        ("content_hash" = String, Path, description = "SHA-256 of the original, optionally with a variant suffix, e.g. `{hash}.webp`"),
+        ("Range" = Option<String>, Header, description = "A single byte range, e.g. `bytes=0-1023`")
+    ),
+    responses(
+        (status = 200, description = "The file content", body = Vec<u8>, content_type = "application/octet-stream"),
+        (status = 206, description = "The requested byte range", body = Vec<u8>, content_type = "application/octet-stream"),
+        (status = 400, description = "The hash is not 64 hexadecimal characters", body = ErrorResponse),
+        (status = 401, description = "No token was sent", body = ErrorResponse),
+        (status = 404, description = "No media has this hash, or it has no such variant", body = ErrorResponse),
+        (status = 416, description = "The range lies outside the file", body = ErrorResponse)
+    )
+)]
+pub async fn download_media_by_hash(
+    State(app_state): State<AppState>,
+    user: Option<UserContext>,
+    Path(content_hash): Path<String>,
+    headers: HeaderMap,
+) -> Result<Response<Body>, AppError> {
+    tracing::info!("Processing download request for content hash: {}", content_hash);
+
+    let (hash, variant) = match content_hash.split_once('.') {
+        Some((hash, variant)) => (hash, Some(variant)),
+        None => (content_hash.as_str(), None),
+    };
+    let hash = ContentHash::new(hash)
+        .map_err(|e| AppError::BadRequest { message: format!("Invalid content hash: {e}") })?;
+    let range = headers.get(header::RANGE).and_then(|v| v.to_str().ok());
+    let requester = user.as_ref().map(UserContext::owner_id);
+    let (id, download_response) = app_state
+        .use_cases
+        .download_media
+        .run(|uc| uc.execute_by_hash(&hash, variant, range, requester))
+        .await?;
+
+    let cache_control = app_state.cache_control.immutable(requester.is_some());
+    serve_download(&app_state, id, download_response, &cache_control, true)
+}
+
+/// Download an image resized on demand
+///
+/// Give `w`, `h` or both; only the dimension combinations the service allows
+/// are rendered, and the error response lists them. Each size is rendered
+/// once and kept as a variant, so later requests are served from storage, or
+/// from memory while the render cache holds it. A single `Range` is honored as
+/// on downloads. With an image CDN configured, clients are redirected to its
+/// signed URL for the size instead.
+///
+/// # Errors
+/// Returns appropriate HTTP status codes for various error conditions
+#[utoipa::path(
+    get,
+    path = "/api/v1/media-management/media/{id}/render",
+    tag = "media",
+    params(
+        ("id" = i64, Path, description = "Media ID", minimum = 1),
+        RenderQuery,
+        ("Range" = Option<String>, Header, description = "A single byte range, e.g. `bytes=0-1023`")
+    ),
+    responses(
+        (status = 200, description = "The resized image", body = Vec<u8>, content_type = "application/octet-stream"),
+        (status = 206, description = "The requested byte range of the resized image", body = Vec<u8>, content_type = "application/octet-stream"),
+        (status = 302, description = "Render the image at the image CDN URL in `Location`", headers(("Location" = String, description = "Signed image CDN URL"))),
+        (status = 400, description = "The size is not allowed, or the media is not a processed image", body = ErrorResponse),
+        (status = 401, description = "No token was sent", body = ErrorResponse),
+        (status = 403, description = "The media belongs to another user", body = ErrorResponse),
+        (status = 404, description = "Media not found", body = ErrorResponse),
+        (status = 416, description = "The range lies outside the resized image", body = ErrorResponse)
+    )
+)]
+pub async fn render_media(
+    State(app_state): State<AppState>,
+    user: Option<UserContext>,
+    Path(id): Path<MediaId>,
+    Query(query): Query<RenderQuery>,
+    headers: HeaderMap,
+) -> Result<Response<Body>, AppError> {
+    tracing::info!("Processing render request for media ID: {}", id);
+
+    let render = query.render().ok_or_else(|| AppError::BadRequest {
+        message: "Give a width (w), a height (h) or both to render at".to_string(),
+    })?;
+    let range = headers.get(header::RANGE).and_then(|v| v.to_str().ok());
+    let requester = user.as_ref().map(UserContext::owner_id);
+    let download_response =
+        app_state.use_cases.render_media.run(|uc| uc.execute(id, render, range, requester)).await?;
+
+    let cache_control = app_state.cache_control.content(requester.is_some());
+    serve_download(&app_state, id, download_response, &cache_control, true)
+}
 
-    let download_response = download_use_case.execute(id).await?;
+/// The response for a download, streaming its content only with `with_content`
+fn serve_download(
+    app_state: &AppState,
+    id: MediaId,
+    download_response: DownloadResponse,
+    cache_control: &str,
+    with_content: bool,
+) -> Result<Response<Body>, AppError> {
+    if let Some(url) = download_response.redirect_url {
+        tracing::info!("Redirecting download of {}", download_response.filename);
+        let mut response = Response::builder()
+            .status(StatusCode::FOUND)
+            .header(header::LOCATION, url)
+            .header(header::CACHE_CONTROL, "no-store");
+        if download_response.negotiated {
+            response = response.header(header::VARY, "Accept");
+        }
+        return response
+            .body(Body::empty())
+            .map_err(|e| AppError::Internal { message: format!("Failed to build response: {e}") });
+    }
 
     tracing::info!(
-        "Serving download: {} ({} bytes)",
+        "Serving download: {} ({} of {} bytes)",
         download_response.filename,
-        download_response.content.len()
+        download_response.content_length,
+        download_response.file_size
     );
 
     // Create HTTP response with appropriate headers
-    let response = Response::builder()
-        .status(StatusCode::OK)
+    let mut response = Response::builder().header(header::ACCEPT_RANGES, "bytes");
+    if download_response.negotiated {
+        response = response.header(header::VARY, "Accept");
+    }
+    response = match download_response.range {
+        Some(range) => response.status(StatusCode::PARTIAL_CONTENT).header(
+            header::CONTENT_RANGE,
+            format!("bytes {}-{}/{}", range.start, range.end, download_response.file_size),
+        ),
+        None => response.status(StatusCode::OK),
+    };
+
+    let body = if with_content {
+        streaming_body(download_response.content, app_state.route_limits.read.timeout)
+    } else {
+        Body::empty()
+    };
+    let response = response
         .header(header::CONTENT_TYPE, download_response.content_type)
-        .header(header::CONTENT_LENGTH, download_response.content.len())
-        .header(
-            header::CONTENT_DISPOSITION,
-            format!("attachment; filename=\"{}\"", download_response.filename),
-        )
-        .header(header::CACHE_CONTROL, "private, max-age=3600") // Cache for 1 hour
-        .body(Body::from(download_response.content))
+        .header(header::CONTENT_LENGTH, download_response.content_length)
+        .header(header::ETAG, format!("\"{}\"", download_response.content_hash.as_str()))
+        .header(header::CONTENT_DISPOSITION, content_disposition(&download_response.filename))
+        .body(body)
         .map_err(|e| AppError::Internal { message: format!("Failed to build response: {e}") })?;
 
-    Ok(response)
+    let surrogate_key = app_state.cache_control.surrogate_key(&[id.as_i64()], &[]);
+    Ok(with_cache_headers(response, cache_control, surrogate_key))
 }
 
-/// Get media IDs associated with a recipe
+/// Stream `content` as a response body, failing it once storage has sent
+/// nothing for `idle`
+///
+/// The route timeout ends when the response starts, so this is what bounds a
+/// stalled download, without cutting off a large one that keeps progressing.
+/// Only the storage side counts: a slow client simply polls less often.
+fn streaming_body(content: Box<dyn AsyncRead + Send + Unpin>, idle: Duration) -> Body {
+    let chunks = ReaderStream::new(content);
+    Body::from_stream(futures_util::stream::unfold(Some(chunks), move |chunks| async move {
+        let mut chunks = chunks?;
+        match tokio::time::timeout(idle, chunks.next()).await {
+            Ok(Some(chunk)) => Some((chunk, Some(chunks))),
+            Ok(None) => None,
+            Err(_) => {
+                tracing::warn!("Download stalled: storage sent nothing for {:?}", idle);
+                let stalled = std::io::Error::new(
+                    std::io::ErrorKind::TimedOut,
+                    "storage stopped sending content",
+                );
+                Some((Err(stalled), None))
+            }
+        }
+    }))
+}
+
+/// Get media associated with a recipe
 ///
 /// # Errors
 /// Returns appropriate HTTP status codes for various error conditions
+#[utoipa::path(
+    get,
+    path = "/api/v1/media-management/media/recipe/{recipe_id}",
+    tag = "recipes",
+    params(("recipe_id" = i64, Path, description = "Recipe ID", minimum = 1), AssociatedMediaQuery),
+    responses(
+        (status = 200, description = "One page of the recipe's media, or a bare array of every media ID with `ids_only=true`", body = PaginatedMediaResponse),
+        (status = 400, description = "The recipe ID is not a positive integer", body = ErrorResponse)
+    )
+)]
 pub async fn get_media_by_recipe(
     State(app_state): State<AppState>,
     Path(recipe_id): Path<RecipeId>,
-) -> Result<Json<Vec<MediaId>>, AppError> {
+    Query(query): Query<AssociatedMediaQuery>,
+) -> Result<Response, AppError> {
     tracing::info!("Processing get media by recipe request for recipe ID: {}", recipe_id);
 
-    let use_case = GetMediaByRecipeUseCase::new(app_state.repository.clone());
-    let media_ids = use_case.execute(recipe_id).await?;
+    let use_case = &app_state.use_cases.get_media_by_recipe;
+    if query.ids_only {
+        let media_ids = use_case.run(|uc| uc.execute(recipe_id)).await?;
+        return Ok(association_response(&app_state, recipe_id, media_ids));
+    }
 
-    tracing::info!("Retrieved {} media IDs for recipe: {}", media_ids.len(), recipe_id);
+    let page = use_case.run(|uc| uc.execute_with_details(recipe_id, query.clone())).await?;
 
-    Ok(Json(media_ids))
+    Ok(association_page_response(&app_state, recipe_id, page))
 }
 
-/// Get media IDs associated with a recipe ingredient
+/// Get media associated with a recipe ingredient
 ///
 /// # Errors
 /// Returns appropriate HTTP status codes for various error conditions
+#[utoipa::path(
+    get,
+    path = "/api/v1/media-management/media/recipe/{recipe_id}/ingredient/{ingredient_id}",
+    tag = "recipes",
+    params(
+        ("recipe_id" = i64, Path, description = "Recipe ID", minimum = 1),
+        ("ingredient_id" = i64, Path, description = "Ingredient ID", minimum = 1),
+        AssociatedMediaQuery
+    ),
+    responses(
+        (status = 200, description = "One page of the ingredient's media, or a bare array of every media ID with `ids_only=true`", body = PaginatedMediaResponse),
+        (status = 400, description = "An ID is not a positive integer", body = ErrorResponse)
+    )
+)]
 pub async fn get_media_by_ingredient(
     State(app_state): State<AppState>,
     Path((recipe_id, ingredient_id)): Path<(RecipeId, IngredientId)>,
-) -> Result<Json<Vec<MediaId>>, AppError> {
+    Query(query): Query<AssociatedMediaQuery>,
+) -> Result<Response, AppError> {
     tracing::info!(
         "Processing get media by ingredient request for recipe ID: {}, ingredient ID: {}",
         recipe_id,
         ingredient_id
     );
 
-    let use_case = GetMediaByIngredientUseCase::new(app_state.repository.clone());
-    let media_ids = use_case.execute(recipe_id, ingredient_id).await?;
+    let use_case = &app_state.use_cases.get_media_by_ingredient;
+    if query.ids_only {
+        let media_ids = use_case.run(|uc| uc.execute(recipe_id, ingredient_id)).await?;
+        return Ok(association_response(&app_state, recipe_id, media_ids));
+    }
 
-    tracing::info!(
-        "Retrieved {} media IDs for recipe: {}, ingredient: {}",
-        media_ids.len(),
-        recipe_id,
-        ingredient_id
-    );
+    let page =
+        use_case.run(|uc| uc.execute_with_details(recipe_id, ingredient_id, query.clone())).await?;
 
-    Ok(Json(media_ids))
+    Ok(association_page_response(&app_state, recipe_id, page))
 }
 
-/// Get media IDs associated with a recipe step
+/// Get media associated with a recipe step
 ///
 /// # Errors
 /// Returns appropriate HTTP status codes for various error conditions
+#[utoipa::path(
+    get,
+    path = "/api/v1/media-management/media/recipe/{recipe_id}/step/{step_id}",
+    tag = "recipes",
+    params(
+        ("recipe_id" = i64, Path, description = "Recipe ID", minimum = 1),
+        ("step_id" = i64, Path, description = "Step ID", minimum = 1),
+        AssociatedMediaQuery
+    ),
+    responses(
+        (status = 200, description = "One page of the step's media, or a bare array of every media ID with `ids_only=true`", body = PaginatedMediaResponse),
+        (status = 400, description = "An ID is not a positive integer", body = ErrorResponse)
+    )
+)]
 pub async fn get_media_by_step(
     State(app_state): State<AppState>,
     Path((recipe_id, step_id)): Path<(RecipeId, StepId)>,
-) -> Result<Json<Vec<MediaId>>, AppError> {
+    Query(query): Query<AssociatedMediaQuery>,
+) -> Result<Response, AppError> {
     tracing::info!(
         "Processing get media by step request for recipe ID: {}, step ID: {}",
         recipe_id,
         step_id
     );
 
-    let use_case = GetMediaByStepUseCase::new(app_state.repository.clone());
-    let media_ids = use_case.execute(recipe_id, step_id).await?;
+    let use_case = &app_state.use_cases.get_media_by_step;
+    if query.ids_only {
+        let media_ids = use_case.run(|uc| uc.execute(recipe_id, step_id)).await?;
+        return Ok(association_response(&app_state, recipe_id, media_ids));
+    }
+
+    let page =
+        use_case.run(|uc| uc.execute_with_details(recipe_id, step_id, query.clone())).await?;
 
-    tracing::info!(
-        "Retrieved {} media IDs for recipe: {}, step: {}",
-        media_ids.len(),
-        recipe_id,
-        step_id
-    );
+    Ok(association_page_response(&app_state, recipe_id, page))
+}
+
+/// Header naming the media and recipes a response shows, for CDN purges
+static SURROGATE_KEY: HeaderName = HeaderName::from_static("surrogate-key");
+
+/// The same keys, comma-separated, for CDNs such as Cloudflare that read this header
+static CACHE_TAG: HeaderName = HeaderName::from_static("cache-tag");
+
+/// `response` with `cache_control` and, if given, `surrogate_key` headers
+fn with_cache_headers(
+    response: impl IntoResponse,
+    cache_control: &str,
+    surrogate_key: Option<String>,
+) -> Response {
+    let mut response = response.into_response();
+    let headers = response.headers_mut();
+    if let Ok(value) = HeaderValue::from_str(cache_control) {
+        headers.insert(header::CACHE_CONTROL, value);
+    }
+    if let Some(key) = surrogate_key {
+        if let Ok(value) = HeaderValue::from_str(&key.replace(' ', ",")) {
+            headers.insert(CACHE_TAG.clone(), value);
+        }
+        if let Ok(value) = HeaderValue::from_str(&key) {
+            headers.insert(SURROGATE_KEY.clone(), value);
+        }
+    }
+    response
+}
+
+/// The media IDs associated with a recipe, or one of its ingredients or steps
+///
+/// Associations are not private to anyone, so the response is public.
+fn association_response(
+    app_state: &AppState,
+    recipe_id: RecipeId,
+    media_ids: Vec<MediaId>,
+) -> Response {
+    let policy = &app_state.cache_control;
+    let media: Vec<i64> = media_ids.iter().map(MediaId::as_i64).collect();
+    let surrogate_key = policy.surrogate_key(&media, &[recipe_id.as_i64()]);
+    with_cache_headers(Json(media_ids), &policy.metadata(false), surrogate_key)
+}
+
+/// One page of the media associated with a recipe, or one of its ingredients or steps
+fn association_page_response(
+    app_state: &AppState,
+    recipe_id: RecipeId,
+    page: PaginatedMediaResponse,
+) -> Response {
+    let policy = &app_state.cache_control;
+    let media: Vec<i64> = page.data.iter().map(|media| media.id.as_i64()).collect();
+    let surrogate_key = policy.surrogate_key(&media, &[recipe_id.as_i64()]);
+    with_cache_headers(Json(page), &policy.metadata(false), surrogate_key)
+}
 
-    Ok(Json(media_ids))
+/// Attach the caller's media to a recipe, ingredient or step
+async fn associate(
+    app_state: &AppState,
+    user: Option<&UserContext>,
+    media_id: MediaId,
+    association: MediaAssociation,
+) -> Result<StatusCode, AppError> {
+    let requester = user.map(UserContext::owner_id);
+    let added = app_state
+        .use_cases
+        .associate_media
+        .run_once(|uc| uc.associate(media_id, association, requester))
+        .await?;
+
+    Ok(if added { StatusCode::CREATED } else { StatusCode::OK })
+}
+
+/// Detach the caller's media from a recipe, ingredient or step
+async fn dissociate(
+    app_state: &AppState,
+    user: Option<&UserContext>,
+    media_id: MediaId,
+    association: MediaAssociation,
+) -> Result<StatusCode, AppError> {
+    let requester = user.map(UserContext::owner_id);
+    app_state
+        .use_cases
+        .dissociate_media
+        .run_once(|uc| uc.dissociate(media_id, association, requester))
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Attach media to a recipe
+///
+/// Attaching media that is already attached succeeds with 200 instead of 201.
+///
+/// # Errors
+/// Returns appropriate HTTP status codes for various error conditions:
+/// - 403 Forbidden: The media belongs to another user, or no token was sent
+/// - 404 Not Found: Media with the given ID doesn't exist
+#[utoipa::path(
+    post,
+    path = "/api/v1/media-management/media/{id}/recipe/{recipe_id}",
+    tag = "recipes",
+    params(
+        ("id" = i64, Path, description = "Media ID", minimum = 1),
+        ("recipe_id" = i64, Path, description = "Recipe ID", minimum = 1),
+        ("Idempotency-Key" = Option<String>, Header, description = "Replays the first response to authenticated retries with the same key and body")
+    ),
+    responses(
+        (status = 201, description = "Media attached to the recipe"),
+        (status = 200, description = "Media was already attached to the recipe"),
+        (status = 403, description = "The media belongs to another user, or no token was sent", body = ErrorResponse),
+        (status = 404, description = "Media not found", body = ErrorResponse)
+    )
+)]
+pub async fn associate_recipe_media(
+    State(app_state): State<AppState>,
+    user: Option<UserContext>,
+    Path((id, recipe_id)): Path<(MediaId, RecipeId)>,
+) -> Result<StatusCode, AppError> {
+    associate(&app_state, user.as_ref(), id, MediaAssociation::Recipe(recipe_id)).await
+}
+
+/// Detach media from a recipe
+///
+/// # Errors
+/// Returns appropriate HTTP status codes for various error conditions:
+/// - 403 Forbidden: The media belongs to another user, or no token was sent
+/// - 404 Not Found: The media doesn't exist or isn't attached to the recipe
+#[utoipa::path(
+    delete,
+    path = "/api/v1/media-management/media/{id}/recipe/{recipe_id}",
+    tag = "recipes",
+    params(
+        ("id" = i64, Path, description = "Media ID", minimum = 1),
+        ("recipe_id" = i64, Path, description = "Recipe ID", minimum = 1),
+        ("Idempotency-Key" = Option<String>, Header, description = "Replays the first response to authenticated retries with the same key and body")
+    ),
+    responses(
+        (status = 204, description = "Media detached from the recipe"),
+        (status = 403, description = "The media belongs to another user, or no token was sent", body = ErrorResponse),
+        (status = 404, description = "Media not found or not attached to the recipe", body = ErrorResponse)
+    )
+)]
+pub async fn dissociate_recipe_media(
+    State(app_state): State<AppState>,
+    user: Option<UserContext>,
+    Path((id, recipe_id)): Path<(MediaId, RecipeId)>,
+) -> Result<StatusCode, AppError> {
+    dissociate(&app_state, user.as_ref(), id, MediaAssociation::Recipe(recipe_id)).await
+}
+
+/// Attach media to a recipe ingredient
+///
+/// Attaching media that is already attached succeeds with 200 instead of 201.
+///
+/// # Errors
+/// Returns appropriate HTTP status codes for various error conditions:
+/// - 403 Forbidden: The media belongs to another user, or no token was sent
+/// - 404 Not Found: Media with the given ID doesn't exist
+#[utoipa::path(
+    post,
+    path = "/api/v1/media-management/media/{id}/recipe/{recipe_id}/ingredient/{ingredient_id}",
+    tag = "recipes",
+    params(
+        ("id" = i64, Path, description = "Media ID", minimum = 1),
+        ("recipe_id" = i64, Path, description = "Recipe ID", minimum = 1),
+        ("ingredient_id" = i64, Path, description = "Ingredient ID", minimum = 1),
+        ("Idempotency-Key" = Option<String>, Header, description = "Replays the first response to authenticated retries with the same key and body")
+    ),
+    responses(
+        (status = 201, description = "Media attached to the ingredient"),
+        (status = 200, description = "Media was already attached to the ingredient"),
+        (status = 403, description = "The media belongs to another user, or no token was sent", body = ErrorResponse),
+        (status = 404, description = "Media not found", body = ErrorResponse)
+    )
+)]
+pub async fn associate_ingredient_media(
+    State(app_state): State<AppState>,
+    user: Option<UserContext>,
+    Path((id, recipe_id, ingredient_id)): Path<(MediaId, RecipeId, IngredientId)>,
+) -> Result<StatusCode, AppError> {
+    let association = MediaAssociation::Ingredient(recipe_id, ingredient_id);
+    associate(&app_state, user.as_ref(), id, association).await
+}
+
+/// Detach media from a recipe ingredient
+///
+/// # Errors
+/// Returns appropriate HTTP status codes for various error conditions:
+/// - 403 Forbidden: The media belongs to another user, or no token was sent
+/// - 404 Not Found: The media doesn't exist or isn't attached to the ingredient
+#[utoipa::path(
+    delete,
+    path = "/api/v1/media-management/media/{id}/recipe/{recipe_id}/ingredient/{ingredient_id}",
+    tag = "recipes",
+    params(
+        ("id" = i64, Path, description = "Media ID", minimum = 1),
+        ("recipe_id" = i64, Path, description = "Recipe ID", minimum = 1),
+        ("ingredient_id" = i64, Path, description = "Ingredient ID", minimum = 1),
+        ("Idempotency-Key" = Option<String>, Header, description = "Replays the first response to authenticated retries with the same key and body")
+    ),
+    responses(
+        (status = 204, description = "Media detached from the ingredient"),
+        (status = 403, description = "The media belongs to another user, or no token was sent", body = ErrorResponse),
+        (status = 404, description = "Media not found or not attached to the ingredient", body = ErrorResponse)
+    )
+)]
+pub async fn dissociate_ingredient_media(
+    State(app_state): State<AppState>,
+    user: Option<UserContext>,
+    Path((id, recipe_id, ingredient_id)): Path<(MediaId, RecipeId, IngredientId)>,
+) -> Result<StatusCode, AppError> {
+    let association = MediaAssociation::Ingredient(recipe_id, ingredient_id);
+    dissociate(&app_state, user.as_ref(), id, association).await
+}
+
+/// Attach media to a recipe step
+///
+/// Attaching media that is already attached succeeds with 200 instead of 201.
+///
+/// # Errors
+/// Returns appropriate HTTP status codes for various error conditions:
+/// - 403 Forbidden: The media belongs to another user, or no token was sent
+/// - 404 Not Found: Media with the given ID doesn't exist
+#[utoipa::path(
+    post,
+    path = "/api/v1/media-management/media/{id}/recipe/{recipe_id}/step/{step_id}",
+    tag = "recipes",
+    params(
+        ("id" = i64, Path, description = "Media ID", minimum = 1),
+        ("recipe_id" = i64, Path, description = "Recipe ID", minimum = 1),
+        ("step_id" = i64, Path, description = "Step ID", minimum = 1),
+        ("Idempotency-Key" = Option<String>, Header, description = "Replays the first response to authenticated retries with the same key and body")
+    ),
+    responses(
+        (status = 201, description = "Media attached to the step"),
+        (status = 200, description = "Media was already attached to the step"),
+        (status = 403, description = "The media belongs to another user, or no token was sent", body = ErrorResponse),
+        (status = 404, description = "Media not found", body = ErrorResponse)
+    )
+)]
+pub async fn associate_step_media(
+    State(app_state): State<AppState>,
+    user: Option<UserContext>,
+    Path((id, recipe_id, step_id)): Path<(MediaId, RecipeId, StepId)>,
+) -> Result<StatusCode, AppError> {
+    associate(&app_state, user.as_ref(), id, MediaAssociation::Step(recipe_id, step_id)).await
+}
+
+/// Detach media from a recipe step
+///
+/// # Errors
+/// Returns appropriate HTTP status codes for various error conditions:
+/// - 403 Forbidden: The media belongs to another user, or no token was sent
+/// - 404 Not Found: The media doesn't exist or isn't attached to the step
+#[utoipa::path(
+    delete,
+    path = "/api/v1/media-management/media/{id}/recipe/{recipe_id}/step/{step_id}",
+    tag = "recipes",
+    params(
+        ("id" = i64, Path, description = "Media ID", minimum = 1),
+        ("recipe_id" = i64, Path, description = "Recipe ID", minimum = 1),
+        ("step_id" = i64, Path, description = "Step ID", minimum = 1),
+        ("Idempotency-Key" = Option<String>, Header, description = "Replays the first response to authenticated retries with the same key and body")
+    ),
+    responses(
+        (status = 204, description = "Media detached from the step"),
+        (status = 403, description = "The media belongs to another user, or no token was sent", body = ErrorResponse),
+        (status = 404, description = "Media not found or not attached to the step", body = ErrorResponse)
+    )
+)]
+pub async fn dissociate_step_media(
+    State(app_state): State<AppState>,
+    user: Option<UserContext>,
+    Path((id, recipe_id, step_id)): Path<(MediaId, RecipeId, StepId)>,
+) -> Result<StatusCode, AppError> {
+    dissociate(&app_state, user.as_ref(), id, MediaAssociation::Step(recipe_id, step_id)).await
 }
 
 #[cfg(test)]
 mod tests {
     use crate::infrastructure::storage::{FileStorage, StorageError};
+    use crate::presentation::middleware::error::AppError;
     use crate::test_utils::mocks::InMemoryMediaRepository;
     use async_trait::async_trait;
     use std::collections::HashMap;
@@ -521,14 +1958,11 @@ mod tests {
 
     #[async_trait]
     impl FileStorage for MockStorage {
-        async fn store<R>(
+        async fn store(
             &self,
             hash: &crate::domain::value_objects::ContentHash,
-            mut reader: R,
-        ) -> Result<String, StorageError>
-        where
-            R: AsyncRead + Send + Unpin,
-        {
+            reader: &mut (dyn AsyncRead + Send + Unpin),
+        ) -> Result<String, StorageError> {
             let mut buffer = Vec::new();
             reader
                 .read_to_end(&mut buffer)
@@ -672,8 +2106,8 @@ mod tests {
         )
         .unwrap();
 
-        let cursor = std::io::Cursor::new(&content);
-        let path = storage.store(&hash, cursor).await.unwrap();
+        let mut cursor = std::io::Cursor::new(&content);
+        let path = storage.store(&hash, &mut cursor).await.unwrap();
         assert!(path.contains(hash.as_str()));
 
         let mut reader = storage.retrieve(&hash).await.unwrap();
@@ -760,6 +2194,7 @@ mod tests {
             ContentHash::new("abcdef1234567890abcdef1234567890abcdef1234567890abcdef1234567890")
                 .unwrap();
         let test_content = b"download test content".to_vec();
+        let owner = crate::domain::entities::UserId::new();
 
         let media = crate::domain::entities::Media::with_id(
             MediaId::new(1),
@@ -770,7 +2205,7 @@ mod tests {
             test_content.len() as u64,
             ProcessingStatus::Complete,
         )
-        .uploaded_by(crate::domain::entities::UserId::new())
+        .uploaded_by(owner)
         .build();
 
         let repository = Arc::new(InMemoryMediaRepository::new().with_media(media));
@@ -778,11 +2213,13 @@ mod tests {
             Arc::new(MockStorage::new().with_file(content_hash.as_str(), test_content.clone()));
 
         let download_use_case = DownloadMediaUseCase::new(repository, storage);
-        let result = download_use_case.execute(MediaId::new(1)).await;
+        let result = download_use_case.execute(MediaId::new(1), Some(owner)).await;
 
         assert!(result.is_ok());
-        let download_response = result.unwrap();
-        assert_eq!(download_response.content, test_content);
+        let mut download_response = result.unwrap();
+        let mut content = Vec::new();
+        download_response.content.read_to_end(&mut content).await.unwrap();
+        assert_eq!(content, test_content);
         assert_eq!(download_response.filename, "download_test.jpg");
     }
 
@@ -807,13 +2244,13 @@ mod tests {
             1024,
             ProcessingStatus::Complete,
         )
-        .uploaded_by(crate::domain::entities::UserId::new())
         .build();
+        let owner = media.uploaded_by;
 
         let repository = Arc::new(InMemoryMediaRepository::new().with_media(media));
         let get_use_case = GetMediaUseCase::new(repository);
 
-        let result = get_use_case.execute(MediaId::new(1)).await;
+        let result = get_use_case.execute(MediaId::new(1), Some(owner)).await;
         assert!(result.is_ok());
 
         let media_dto = result.unwrap();
@@ -846,9 +2283,18 @@ mod tests {
         .build();
 
         let repository = Arc::new(InMemoryMediaRepository::new().with_media(media));
-        let list_use_case = ListMediaUseCase::new(repository);
-
-        let query = PaginatedMediaQuery { cursor: None, limit: Some(10), status: None };
+        let storage = Arc::new(MockStorage::new());
+        let list_use_case = ListMediaUseCase::new(repository, storage);
+
+        let query = PaginatedMediaQuery {
+            cursor: None,
+            limit: Some(10),
+            status: None,
+            include: None,
+            sort_by: None,
+            order: None,
+            tags: None,
+        };
 
         let result = list_use_case.execute(query, crate::domain::entities::UserId::new()).await;
         assert!(result.is_ok());
@@ -893,13 +2339,13 @@ mod tests {
         let hash2_clone = hash2.clone();
 
         let handle1 = tokio::spawn(async move {
-            let cursor = std::io::Cursor::new(&content1);
-            storage1.store(&hash1_clone, cursor).await
+            let mut cursor = std::io::Cursor::new(&content1);
+            storage1.store(&hash1_clone, &mut cursor).await
         });
 
         let handle2 = tokio::spawn(async move {
-            let cursor = std::io::Cursor::new(&content2);
-            storage2.store(&hash2_clone, cursor).await
+            let mut cursor = std::io::Cursor::new(&content2);
+            storage2.store(&hash2_clone, &mut cursor).await
         });
 
         let result1 = handle1.await.unwrap();
@@ -937,9 +2383,9 @@ mod tests {
 
         // Create a large file (1MB)
         let large_content = vec![0u8; 1024 * 1024];
-        let cursor = std::io::Cursor::new(&large_content);
+        let mut cursor = std::io::Cursor::new(&large_content);
 
-        let result = storage.store(&hash, cursor).await;
+        let result = storage.store(&hash, &mut cursor).await;
         assert!(result.is_ok());
 
         let mut reader = storage.retrieve(&hash).await.unwrap();
@@ -973,7 +2419,7 @@ mod tests {
         let get_use_case = GetMediaUseCase::new(repository);
 
         // Test with non-existent media ID
-        let result = get_use_case.execute(MediaId::new(999)).await;
+        let result = get_use_case.execute(MediaId::new(999), None).await;
         assert!(result.is_err());
     }
 
@@ -1004,4 +2450,97 @@ mod tests {
         assert!(ingredient_result.unwrap().is_empty());
         assert!(step_result.unwrap().is_empty());
     }
+
+    #[tokio::test]
+    async fn test_app_state_builders_rebuild_use_cases() {
+        use crate::domain::entities::MediaId;
+        use crate::infrastructure::{
+            persistence::InMemoryVariantRepository,
+            storage::{PresignedUrlConfig, PresignedUrlService},
+        };
+
+        let app_state = super::AppState::new(
+            Arc::new(InMemoryMediaRepository::new()),
+            Arc::new(MockStorage::new()),
+            PresignedUrlService::new(PresignedUrlConfig::default()),
+            1024,
+        );
+        let original_use_cases = app_state.use_cases.clone();
+
+        let app_state = app_state.with_variants(Arc::new(InMemoryVariantRepository::new()));
+
+        assert!(!Arc::ptr_eq(&original_use_cases, &app_state.use_cases));
+        let result =
+            app_state.use_cases.get_media.run(|uc| uc.execute(MediaId::new(1), None)).await;
+        assert!(matches!(result, Err(AppError::NotFound { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_read_body_capped_accepts_body_within_cap() {
+        let body = super::read_body_capped(axum::body::Body::from("hello"), 5).await.unwrap();
+        assert!(matches!(body, super::BodyRead::Complete(bytes) if &bytes[..] == b"hello"));
+    }
+
+    #[tokio::test]
+    async fn test_read_body_capped_keeps_bytes_before_disconnect() {
+        use futures_util::stream;
+
+        let chunks = stream::iter([
+            Ok(b"hello ".to_vec()),
+            Err(std::io::Error::from(std::io::ErrorKind::ConnectionReset)),
+        ]);
+        let body = axum::body::Body::from_stream(chunks);
+
+        let body = super::read_body_capped(body, 4096).await.unwrap();
+        assert!(matches!(body, super::BodyRead::Interrupted(bytes) if &bytes[..] == b"hello "));
+    }
+
+    #[tokio::test]
+    async fn test_read_body_capped_rejects_declared_length_over_cap() {
+        let result = super::read_body_capped(axum::body::Body::from("hello"), 4).await;
+        assert!(matches!(result, Err(AppError::PayloadTooLarge { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_read_body_capped_aborts_streamed_body_over_cap() {
+        use futures_util::stream;
+
+        // The stream never ends, so the read only finishes if it aborts at the cap
+        let chunks = stream::repeat_with(|| Ok::<_, std::io::Error>(vec![0u8; 1024]));
+        let body = axum::body::Body::from_stream(chunks);
+
+        let result = super::read_body_capped(body, 4096).await;
+        assert!(matches!(result, Err(AppError::PayloadTooLarge { .. })));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_streaming_body_times_out_only_when_idle() {
+        use futures_util::StreamExt;
+        use std::time::Duration;
+        use tokio::io::AsyncWriteExt;
+
+        let (reader, mut writer) = tokio::io::duplex(64);
+        tokio::spawn(async move {
+            // Longer than the idle timeout in total, but never quiet for that long
+            for _ in 0..5 {
+                writer.write_all(b"chunk").await.unwrap();
+                tokio::time::sleep(Duration::from_millis(40)).await;
+            }
+            std::future::pending::<()>().await;
+        });
+
+        let body = super::streaming_body(Box::new(reader), Duration::from_millis(100));
+        let mut frames = body.into_data_stream();
+        let mut content = Vec::new();
+        let error = loop {
+            match frames.next().await.unwrap() {
+                Ok(bytes) => content.extend_from_slice(&bytes),
+                Err(e) => break e,
+            }
+        };
+
+        assert_eq!(content, b"chunk".repeat(5));
+        assert!(error.to_string().contains("storage stopped sending content"), "{error}");
+        assert!(frames.next().await.is_none());
+    }
 }
@@ -1 +1,5 @@
+pub mod admin;
+pub mod live_events;
 pub mod media;
+pub mod progress;
+pub mod resumable_uploads;
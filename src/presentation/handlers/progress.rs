@@ -0,0 +1,273 @@
+//! Live processing progress over Server-Sent Events
+//!
+//! `GET /media/{id}/progress` sends a `progress` event with the media's status
+//! and percentage whenever processing advances, and closes the stream once
+//! processing completes or fails. Progress is pushed by the instance processing
+//! the media; the stream also re-reads the media's status every few seconds, so
+//! it still finishes when another instance does the processing.
+
+use axum::{
+    extract::State,
+    response::sse::{Event, KeepAlive, Sse},
+};
+use futures_util::{stream, Stream};
+use std::time::Duration;
+use tokio::sync::watch;
+
+use crate::{
+    application::dto::ProcessingProgressEvent,
+    domain::entities::{MediaId, UserId},
+    presentation::{
+        extractors::Path,
+        handlers::media::AppState,
+        middleware::{
+            auth::UserContext,
+            error::{AppError, ErrorResponse},
+        },
+    },
+};
+
+/// How often the stream re-reads the media's status while waiting for progress
+const STATUS_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Stream processing progress as Server-Sent Events
+///
+/// Each `progress` event carries a [`ProcessingProgressEvent`]. The first is
+/// the current state; later ones follow as the pipeline advances, and the
+/// stream ends after the `Complete` or `Failed` event. Media that has already
+/// finished gets a single event.
+///
+/// # Errors
+/// Returns appropriate HTTP status codes for various error conditions:
+/// - 400 Bad Request: The media ID is not an integer
+/// - 403 Forbidden: The media belongs to another user
+/// - 404 Not Found: Media with the given ID doesn't exist
+#[utoipa::path(
+    get,
+    path = "/api/v1/media-management/media/{id}/progress",
+    tag = "uploads",
+    params(("id" = i64, Path, description = "Media ID", minimum = 1)),
+    responses(
+        (status = 200, description = "Stream of `progress` events", content_type = "text/event-stream", body = ProcessingProgressEvent),
+        (status = 400, description = "The media ID is not a positive integer", body = ErrorResponse),
+        (status = 403, description = "The media belongs to another user, or no token was sent", body = ErrorResponse),
+        (status = 404, description = "Media not found", body = ErrorResponse)
+    )
+)]
+pub async fn stream_processing_progress(
+    State(app_state): State<AppState>,
+    user: Option<UserContext>,
+    Path(media_id): Path<MediaId>,
+) -> Result<Sse<impl Stream<Item = Result<Event, axum::Error>>>, AppError> {
+    tracing::info!("Streaming processing progress for media_id: {}", media_id);
+
+    let requester = user.as_ref().map(UserContext::owner_id);
+    let current = current_progress(&app_state, media_id, requester).await?;
+    let updates =
+        (!current.is_final()).then(|| app_state.processing_progress.subscribe(current.clone()));
+
+    let follower = ProgressFollower {
+        app_state,
+        media_id,
+        requester,
+        updates,
+        next: Some(current),
+        last: None,
+    };
+    let events = stream::unfold(follower, |mut follower| async move {
+        let progress = follower.next_progress().await?;
+        let event = Event::default().event("progress").json_data(&progress);
+        Some((event, follower))
+    });
+
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()))
+}
+
+/// Read the media's progress, preferring live progress when it is processed here
+async fn current_progress(
+    app_state: &AppState,
+    media_id: MediaId,
+    requester: Option<UserId>,
+) -> Result<ProcessingProgressEvent, AppError> {
+    let media = app_state.use_cases.get_media.run(|uc| uc.execute(media_id, requester)).await?;
+    let mut progress =
+        ProcessingProgressEvent::new(media.id, media.processing_status, media.processing_error);
+    if progress.status.is_processing() {
+        progress.progress = app_state.processing_progress.current(media_id).unwrap_or(0);
+    }
+    Ok(progress)
+}
+
+/// State of one progress stream
+struct ProgressFollower {
+    app_state: AppState,
+    media_id: MediaId,
+    requester: Option<UserId>,
+    /// Live progress, until processing here ends or the tracker drops the media
+    updates: Option<watch::Receiver<ProcessingProgressEvent>>,
+    /// Progress to send before waiting for more
+    next: Option<ProcessingProgressEvent>,
+    /// Progress sent last
+    last: Option<ProcessingProgressEvent>,
+}
+
+impl ProgressFollower {
+    /// Wait for the next change in progress, or `None` once the stream is over
+    async fn next_progress(&mut self) -> Option<ProcessingProgressEvent> {
+        if self.last.as_ref().is_some_and(ProcessingProgressEvent::is_final) {
+            return None;
+        }
+
+        loop {
+            let progress = match self.next.take() {
+                Some(progress) => progress,
+                None => self.wait().await?,
+            };
+            if self.last.as_ref() != Some(&progress) {
+                self.last = Some(progress.clone());
+                return Some(progress);
+            }
+        }
+    }
+
+    /// The next live update, or the stored status after a poll interval passes
+    async fn wait(&mut self) -> Option<ProcessingProgressEvent> {
+        let live = if let Some(updates) = &mut self.updates {
+            tokio::select! {
+                changed = updates.changed() => Some(changed.map(|()| updates.borrow_and_update().clone())),
+                () = tokio::time::sleep(STATUS_POLL_INTERVAL) => None,
+            }
+        } else {
+            tokio::time::sleep(STATUS_POLL_INTERVAL).await;
+            None
+        };
+
+        match live {
+            Some(Ok(progress)) => Some(progress),
+            Some(Err(_)) => {
+                // Processing here ended without an outcome; fall back to polling
+                self.updates = None;
+                self.poll().await
+            }
+            None => self.poll().await,
+        }
+    }
+
+    /// The stored status, keeping the last percentage while still processing
+    async fn poll(&self) -> Option<ProcessingProgressEvent> {
+        let mut progress =
+            match current_progress(&self.app_state, self.media_id, self.requester).await {
+                Ok(progress) => progress,
+                Err(e) => {
+                    tracing::info!("Stopped streaming progress for media {}: {}", self.media_id, e);
+                    return None;
+                }
+            };
+        if let Some(last) = self.last.as_ref().filter(|last| last.status == progress.status) {
+            progress.progress = progress.progress.max(last.progress);
+        }
+        Some(progress)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        domain::{
+            entities::{Media, UnsavedMedia},
+            repositories::MediaRepository,
+            value_objects::{ContentHash, MediaType, ProcessingStatus},
+        },
+        infrastructure::storage::{InMemoryStorage, PresignedUrlConfig, PresignedUrlService},
+        test_utils::mocks::InMemoryMediaRepository,
+    };
+    use futures_util::StreamExt;
+    use std::sync::Arc;
+
+    /// The uploader of the media `setup` creates
+    fn user() -> UserContext {
+        crate::presentation::middleware::Claims::new_access_token(
+            "auth-service".to_string(),
+            vec!["media-management-service".to_string()],
+            "user-1".to_string(),
+            "web-client".to_string(),
+            vec![],
+            1,
+        )
+        .into()
+    }
+
+    fn setup(status: ProcessingStatus) -> (AppState, Arc<InMemoryMediaRepository>, Media) {
+        let mut media = UnsavedMedia::new(
+            ContentHash::new(&"a".repeat(64)).unwrap(),
+            "cover.jpg".to_string(),
+            MediaType::new("image/jpeg"),
+            "aa/aa/aa/a".to_string(),
+            2048,
+            user().owner_id(),
+        )
+        .into_media(MediaId::new(5));
+        if status.is_failed() {
+            media.mark_failed("Unsupported color profile");
+        } else {
+            media.set_processing_status(status);
+        }
+        let repository = Arc::new(InMemoryMediaRepository::new().with_media(media.clone()));
+        let app_state = AppState::new(
+            repository.clone(),
+            Arc::new(InMemoryStorage::new()),
+            PresignedUrlService::new(PresignedUrlConfig::default()),
+            1024,
+        );
+        (app_state, repository, media)
+    }
+
+    #[tokio::test]
+    async fn test_progress_is_followed_until_processing_ends() {
+        let (app_state, repository, media) = setup(ProcessingStatus::Pending);
+        let progress = app_state.processing_progress.clone();
+        let current =
+            current_progress(&app_state, media.id, Some(media.uploaded_by)).await.unwrap();
+        let mut follower = ProgressFollower {
+            app_state,
+            media_id: media.id,
+            requester: Some(media.uploaded_by),
+            updates: Some(progress.subscribe(current.clone())),
+            next: Some(current),
+            last: None,
+        };
+
+        assert_eq!(follower.next_progress().await.unwrap().status, ProcessingStatus::Pending);
+        progress.advance(media.id, 20);
+        assert_eq!(follower.next_progress().await.unwrap().progress, 20);
+
+        let mut processed = media.clone();
+        processed.set_processing_status(ProcessingStatus::Complete);
+        repository.update(&processed).await.unwrap();
+        progress.finish(&processed);
+        let finished = follower.next_progress().await.unwrap();
+        assert_eq!((finished.status, finished.progress), (ProcessingStatus::Complete, 100));
+        assert!(follower.next_progress().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_finished_media_gets_a_single_event() {
+        let (app_state, _, media) = setup(ProcessingStatus::Failed);
+
+        let response = stream_processing_progress(State(app_state), Some(user()), Path(media.id))
+            .await
+            .unwrap();
+        let body = axum::response::IntoResponse::into_response(response).into_body();
+        let chunks: Vec<_> = body.into_data_stream().collect().await;
+        let text: String = chunks
+            .into_iter()
+            .map(|chunk| String::from_utf8(chunk.unwrap().to_vec()).unwrap())
+            .collect();
+
+        assert!(text.starts_with("event: progress\ndata: "));
+        assert!(text.contains(r#""status":"Failed""#));
+        assert!(text.contains("Unsupported color profile"));
+        assert_eq!(text.matches("event: progress").count(), 1);
+    }
+}
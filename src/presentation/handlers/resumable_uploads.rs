@@ -0,0 +1,684 @@
+//! tus 1.0 resumable upload endpoints
+//!
+//! Implements the core protocol plus the `creation` and `expiration` extensions:
+//! `POST` creates an upload, `PATCH` appends a chunk at `Upload-Offset`, and
+//! `HEAD` reports how many bytes the server has received so clients can resume.
+
+use axum::{
+    body::Body,
+    extract::{MatchedPath, OriginalUri, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+};
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+
+use crate::{
+    domain::entities::{ResumableUpload, UploadId},
+    infrastructure::business_metrics::UploadFlow,
+    presentation::{
+        extractors::Path,
+        handlers::media::{
+            owner_id, read_body_capped, spawn_media_processing, upload_interrupted, AppState,
+            BodyRead,
+        },
+        middleware::{
+            auth::UserContext,
+            error::{AppError, ErrorResponse},
+        },
+    },
+};
+
+/// Protocol version implemented by this server
+pub const TUS_VERSION: &str = "1.0.0";
+
+/// Protocol extensions supported by this server
+pub const TUS_EXTENSIONS: &str = "creation,expiration";
+
+/// Content type required on `PATCH` requests
+const OFFSET_OCTET_STREAM: &str = "application/offset+octet-stream";
+
+const TUS_RESUMABLE: &str = "tus-resumable";
+const UPLOAD_LENGTH: &str = "upload-length";
+const UPLOAD_OFFSET: &str = "upload-offset";
+const UPLOAD_METADATA: &str = "upload-metadata";
+const UPLOAD_EXPIRES: &str = "upload-expires";
+const UPLOAD_DEFER_LENGTH: &str = "upload-defer-length";
+
+/// Describe the server's tus capabilities
+#[utoipa::path(
+    options,
+    path = "/api/v1/media-management/media/uploads",
+    tag = "uploads",
+    responses(
+        (status = 204, description = "Supported tus version, extensions and maximum size", headers(
+            ("Tus-Version" = String),
+            ("Tus-Extension" = String),
+            ("Tus-Max-Size" = u64)
+        ))
+    )
+)]
+pub async fn upload_options(State(app_state): State<AppState>) -> Response {
+    Response::builder()
+        .status(StatusCode::NO_CONTENT)
+        .header(TUS_RESUMABLE, TUS_VERSION)
+        .header("tus-version", TUS_VERSION)
+        .header("tus-extension", TUS_EXTENSIONS)
+        .header("tus-max-size", app_state.max_file_size)
+        .body(Body::empty())
+        .unwrap_or_else(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())
+}
+
+/// Create a new resumable upload
+///
+/// Requires `Upload-Length`; `Upload-Metadata` may carry `filename` and `filetype`.
+///
+/// # Errors
+/// Returns appropriate HTTP status codes for various error conditions
+#[utoipa::path(
+    post,
+    path = "/api/v1/media-management/media/uploads",
+    tag = "uploads",
+    params(
+        ("Tus-Resumable" = String, Header, description = "Must be `1.0.0`"),
+        ("Upload-Length" = u64, Header, description = "Total size of the upload in bytes"),
+        ("Upload-Metadata" = Option<String>, Header, description = "Comma-separated `key base64value` pairs")
+    ),
+    responses(
+        (status = 201, description = "Upload created", headers(
+            ("Location" = String, description = "URL to send chunks to"),
+            ("Upload-Offset" = u64),
+            ("Upload-Expires" = String)
+        )),
+        (status = 400, description = "Missing or invalid upload headers", body = ErrorResponse),
+        (status = 412, description = "Unsupported tus version")
+    )
+)]
+pub async fn create_upload(
+    State(app_state): State<AppState>,
+    user: Option<UserContext>,
+    OriginalUri(uri): OriginalUri,
+    headers: HeaderMap,
+) -> Response {
+    tus_response(&headers, async {
+        if headers.contains_key(UPLOAD_DEFER_LENGTH) {
+            return Err(AppError::BadRequest {
+                message: "Upload-Defer-Length is not supported".to_string(),
+            });
+        }
+
+        let upload_length = required_u64_header(&headers, UPLOAD_LENGTH)?;
+        let metadata = headers
+            .get(UPLOAD_METADATA)
+            .map(|value| {
+                value
+                    .to_str()
+                    .map_err(|_| AppError::BadRequest {
+                        message: "Invalid Upload-Metadata header".to_string(),
+                    })
+                    .and_then(parse_upload_metadata)
+            })
+            .transpose()?
+            .unwrap_or_default();
+
+        let filename = metadata.get("filename").or_else(|| metadata.get("name")).cloned();
+        let content_type = metadata.get("filetype").or_else(|| metadata.get("type")).cloned();
+
+        tracing::info!(
+            "Processing resumable upload creation: {} bytes, file: {:?}",
+            upload_length,
+            filename
+        );
+
+        let user_id = owner_id(user.as_ref());
+        let tenant = user.as_ref().map(|user| user.client_id.as_str());
+
+        let upload = app_state
+            .use_cases
+            .resumable_upload
+            .run_once(|uc| {
+                uc.create_for_tenant(tenant, user_id, upload_length, filename, content_type)
+            })
+            .await?;
+
+        let location = format!("{}/{}", uri.path().trim_end_matches('/'), upload.id);
+
+        Response::builder()
+            .status(StatusCode::CREATED)
+            .header(header::LOCATION, location)
+            .header(UPLOAD_OFFSET, upload.upload_offset)
+            .header(UPLOAD_EXPIRES, http_date(&upload))
+            .body(Body::empty())
+            .map_err(|e| AppError::Internal { message: format!("Failed to build response: {e}") })
+    })
+    .await
+}
+
+/// Report the current offset of a resumable upload
+///
+/// # Errors
+/// Returns appropriate HTTP status codes for various error conditions
+#[utoipa::path(
+    head,
+    path = "/api/v1/media-management/media/uploads/{id}",
+    tag = "uploads",
+    params(
+        ("id" = String, Path, description = "Resumable upload ID"),
+        ("Tus-Resumable" = String, Header, description = "Must be `1.0.0`")
+    ),
+    responses(
+        (status = 200, description = "Bytes received so far", headers(
+            ("Upload-Offset" = u64),
+            ("Upload-Length" = u64),
+            ("Upload-Expires" = String)
+        )),
+        (status = 404, description = "Upload not found or expired"),
+        (status = 412, description = "Unsupported tus version")
+    )
+)]
+pub async fn get_upload_offset(
+    State(app_state): State<AppState>,
+    Path(id): Path<UploadId>,
+    headers: HeaderMap,
+) -> Response {
+    tus_response(&headers, async {
+        let upload = app_state.use_cases.resumable_upload.run(|uc| uc.status(id)).await?;
+
+        Response::builder()
+            .status(StatusCode::OK)
+            .header(UPLOAD_OFFSET, upload.upload_offset)
+            .header(UPLOAD_LENGTH, upload.upload_length)
+            .header(UPLOAD_EXPIRES, http_date(&upload))
+            .header(header::CACHE_CONTROL, "no-store")
+            .body(Body::empty())
+            .map_err(|e| AppError::Internal { message: format!("Failed to build response: {e}") })
+    })
+    .await
+}
+
+/// Append a chunk to a resumable upload
+///
+/// When the final chunk arrives the media record is created and its ID is returned
+/// in the `X-Media-Id` header. If the client disconnects part way, the bytes that
+/// arrived are still appended.
+///
+/// # Errors
+/// Returns appropriate HTTP status codes for various error conditions
+#[utoipa::path(
+    patch,
+    path = "/api/v1/media-management/media/uploads/{id}",
+    tag = "uploads",
+    params(
+        ("id" = String, Path, description = "Resumable upload ID"),
+        ("Tus-Resumable" = String, Header, description = "Must be `1.0.0`"),
+        ("Upload-Offset" = u64, Header, description = "Offset the chunk starts at")
+    ),
+    request_body(content = Vec<u8>, content_type = "application/offset+octet-stream"),
+    responses(
+        (status = 204, description = "Chunk appended", headers(
+            ("Upload-Offset" = u64),
+            ("X-Media-Id" = i64, description = "Set once the final chunk completes the upload")
+        )),
+        (status = 400, description = "Chunk ended early; the bytes received were appended", body = ErrorResponse),
+        (status = 404, description = "Upload not found or expired", body = ErrorResponse),
+        (status = 409, description = "Offset does not match the bytes received", body = ErrorResponse),
+        (status = 413, description = "Chunk exceeds the bytes remaining in the upload", body = ErrorResponse),
+        (status = 412, description = "Unsupported tus version"),
+        (status = 415, description = "Content type is not `application/offset+octet-stream`", body = ErrorResponse)
+    )
+)]
+pub async fn append_upload_chunk(
+    State(app_state): State<AppState>,
+    route: MatchedPath,
+    Path(id): Path<UploadId>,
+    headers: HeaderMap,
+    body: Body,
+) -> Response {
+    tus_response(&headers, async {
+        let content_type = headers.get(header::CONTENT_TYPE).and_then(|v| v.to_str().ok());
+        if content_type != Some(OFFSET_OCTET_STREAM) {
+            return Err(AppError::UnsupportedMediaType {
+                content_type: content_type.unwrap_or("none").to_string(),
+            });
+        }
+
+        let offset = required_u64_header(&headers, UPLOAD_OFFSET)?;
+
+        // Whatever arrived before a disconnect is kept, so the client resumes from
+        // the offset a HEAD reports rather than resending the whole chunk
+        let (chunk, interrupted) = match read_body_capped(body, app_state.max_file_size).await? {
+            BodyRead::Complete(chunk) => (chunk, false),
+            BodyRead::Interrupted(chunk) => {
+                app_state.business_metrics.upload_aborted(UploadFlow::Resumable, route.as_str());
+                (chunk, true)
+            }
+        };
+        if interrupted && chunk.is_empty() {
+            return Err(upload_interrupted());
+        }
+
+        tracing::debug!("Appending {} bytes to upload {} at offset {}", chunk.len(), id, offset);
+
+        let result = app_state
+            .use_cases
+            .resumable_upload
+            .run_once(|uc| uc.append(id, offset, &chunk))
+            .await?;
+
+        if interrupted && result.completed.is_none() {
+            return Err(upload_interrupted());
+        }
+
+        let mut response = Response::builder()
+            .status(StatusCode::NO_CONTENT)
+            .header(UPLOAD_OFFSET, result.upload.upload_offset)
+            .header(UPLOAD_EXPIRES, http_date(&result.upload));
+
+        if let Some(completed) = result.completed {
+            spawn_media_processing(&app_state, completed.media_id);
+            response = response
+                .header("x-media-id", completed.media_id.as_i64())
+                .header("x-content-hash", completed.content_hash);
+        }
+
+        response
+            .body(Body::empty())
+            .map_err(|e| AppError::Internal { message: format!("Failed to build response: {e}") })
+    })
+    .await
+}
+
+/// Check the client's protocol version, run the handler, and tag the response as tus
+async fn tus_response<F>(headers: &HeaderMap, handler: F) -> Response
+where
+    F: std::future::Future<Output = Result<Response, AppError>>,
+{
+    let supported = headers.get(TUS_RESUMABLE).is_some_and(|v| v == TUS_VERSION);
+
+    let mut response = if supported {
+        handler.await.unwrap_or_else(IntoResponse::into_response)
+    } else {
+        let mut response = StatusCode::PRECONDITION_FAILED.into_response();
+        response.headers_mut().insert("tus-version", HeaderValue::from_static(TUS_VERSION));
+        response
+    };
+
+    response.headers_mut().insert(TUS_RESUMABLE, HeaderValue::from_static(TUS_VERSION));
+    response
+}
+
+fn required_u64_header(headers: &HeaderMap, name: &str) -> Result<u64, AppError> {
+    headers
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .ok_or_else(|| AppError::BadRequest {
+            message: format!("Missing or invalid {name} header"),
+        })
+}
+
+/// Parse `Upload-Metadata`: comma-separated `key base64value` pairs, value optional
+fn parse_upload_metadata(header: &str) -> Result<HashMap<String, String>, AppError> {
+    header
+        .split(',')
+        .map(str::trim)
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let mut parts = pair.splitn(2, ' ');
+            let key = parts.next().unwrap_or_default().to_string();
+            let value = match parts.next() {
+                Some(encoded) => STANDARD
+                    .decode(encoded.trim())
+                    .ok()
+                    .and_then(|bytes| String::from_utf8(bytes).ok())
+                    .ok_or_else(|| AppError::BadRequest {
+                        message: format!("Invalid Upload-Metadata value for key '{key}'"),
+                    })?,
+                None => String::new(),
+            };
+            Ok((key, value))
+        })
+        .collect()
+}
+
+/// Format the upload expiry as an RFC 9110 HTTP date
+fn http_date(upload: &ResumableUpload) -> String {
+    let expires_at: DateTime<Utc> = upload.expires_at.into();
+    expires_at.format("%a, %d %b %Y %H:%M:%S GMT").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        infrastructure::storage::{
+            InMemoryStorage, PresignedUrlConfig, PresignedUrlService, UploadStaging,
+        },
+        test_utils::mocks::InMemoryMediaRepository,
+    };
+    use axum::{
+        http::{Method, Request},
+        routing::{patch, post},
+        Router,
+    };
+    use std::{sync::Arc, time::Duration};
+    use tempfile::TempDir;
+    use tower::ServiceExt;
+
+    fn create_test_app(temp_dir: &TempDir) -> Router {
+        let app_state = AppState::new(
+            Arc::new(InMemoryMediaRepository::new()),
+            Arc::new(InMemoryStorage::new()),
+            PresignedUrlService::new(PresignedUrlConfig::default()),
+            1024,
+        )
+        .with_resumable_uploads(
+            Arc::new(crate::infrastructure::persistence::InMemoryResumableUploadRepository::new()),
+            UploadStaging::new(temp_dir.path()),
+            Duration::from_hours(1),
+        );
+
+        Router::new()
+            .route("/uploads", post(create_upload).options(upload_options))
+            .route("/uploads/{id}", patch(append_upload_chunk).head(get_upload_offset))
+            .with_state(app_state)
+    }
+
+    fn header<'a>(response: &'a Response, name: &str) -> Option<&'a str> {
+        response.headers().get(name).and_then(|v| v.to_str().ok())
+    }
+
+    async fn create(app: &Router, length: u64) -> String {
+        let response = app
+            .clone()
+            .oneshot(
+                Request::post("/uploads")
+                    .header(TUS_RESUMABLE, TUS_VERSION)
+                    .header(UPLOAD_LENGTH, length)
+                    .header(UPLOAD_METADATA, format!("filename {}", STANDARD.encode("a.txt")))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::CREATED);
+        assert_eq!(header(&response, UPLOAD_OFFSET), Some("0"));
+        header(&response, "location").unwrap().to_string()
+    }
+
+    /// A PATCH whose client disconnects after sending `sent`
+    fn interrupted_patch_request(
+        location: &str,
+        offset: u64,
+        sent: &'static [u8],
+    ) -> Request<Body> {
+        let chunks = futures_util::stream::iter([
+            Ok(sent.to_vec()),
+            Err(std::io::Error::from(std::io::ErrorKind::ConnectionReset)),
+        ]);
+
+        Request::patch(location)
+            .header(TUS_RESUMABLE, TUS_VERSION)
+            .header(header::CONTENT_TYPE, OFFSET_OCTET_STREAM)
+            .header(UPLOAD_OFFSET, offset)
+            .body(Body::from_stream(chunks))
+            .unwrap()
+    }
+
+    fn patch_request(location: &str, offset: u64, body: &'static [u8]) -> Request<Body> {
+        Request::patch(location)
+            .header(TUS_RESUMABLE, TUS_VERSION)
+            .header(header::CONTENT_TYPE, OFFSET_OCTET_STREAM)
+            .header(UPLOAD_OFFSET, offset)
+            .body(Body::from(body))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_tus_upload_flow() {
+        let temp_dir = TempDir::new().unwrap();
+        let app = create_test_app(&temp_dir);
+        let location = create(&app, 11).await;
+
+        let response = app.clone().oneshot(patch_request(&location, 0, b"hello ")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert_eq!(header(&response, UPLOAD_OFFSET), Some("6"));
+        assert!(header(&response, "x-media-id").is_none());
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::head(&location)
+                    .header(TUS_RESUMABLE, TUS_VERSION)
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(header(&response, UPLOAD_OFFSET), Some("6"));
+        assert_eq!(header(&response, UPLOAD_LENGTH), Some("11"));
+        assert_eq!(header(&response, "cache-control"), Some("no-store"));
+
+        let response = app.clone().oneshot(patch_request(&location, 6, b"world")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert_eq!(header(&response, UPLOAD_OFFSET), Some("11"));
+        assert!(header(&response, "x-media-id").is_some());
+        assert_eq!(
+            header(&response, "x-content-hash"),
+            Some("b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_tus_upload_follows_the_tenant_format_policy() {
+        use crate::{
+            domain::value_objects::FormatPolicy,
+            presentation::middleware::{Claims, UserContext},
+        };
+        use std::collections::HashMap;
+
+        let temp_dir = TempDir::new().unwrap();
+        let app_state = AppState::new(
+            Arc::new(InMemoryMediaRepository::new()),
+            Arc::new(InMemoryStorage::new()),
+            PresignedUrlService::new(PresignedUrlConfig::default()),
+            1024,
+        )
+        .with_resumable_uploads(
+            Arc::new(crate::infrastructure::persistence::InMemoryResumableUploadRepository::new()),
+            UploadStaging::new(temp_dir.path()),
+            Duration::from_hours(1),
+        )
+        .with_format_policy(FormatPolicy::new(HashMap::from([(
+            "web-client".to_string(),
+            vec!["image/webp".to_string()],
+        )])));
+        let app = Router::new()
+            .route("/uploads", post(create_upload))
+            .route("/uploads/{id}", patch(append_upload_chunk))
+            .with_state(app_state);
+
+        let user: UserContext = Claims::new_access_token(
+            "auth-service".to_string(),
+            vec!["media-management-service".to_string()],
+            "user-1".to_string(),
+            "web-client".to_string(),
+            vec![],
+            1,
+        )
+        .into();
+        let response = app
+            .clone()
+            .oneshot(
+                Request::post("/uploads")
+                    .header(TUS_RESUMABLE, TUS_VERSION)
+                    .header(UPLOAD_LENGTH, 11)
+                    .header(UPLOAD_METADATA, format!("filename {}", STANDARD.encode("a.txt")))
+                    .extension(user)
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let location = header(&response, "location").unwrap().to_string();
+
+        // The tenant only accepts WebP, which text cannot be converted to
+        let response =
+            app.clone().oneshot(patch_request(&location, 0, b"hello world")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    #[tokio::test]
+    async fn test_upload_resumes_after_client_disconnect() {
+        let temp_dir = TempDir::new().unwrap();
+        let app = create_test_app(&temp_dir);
+        let location = create(&app, 11).await;
+
+        let response =
+            app.clone().oneshot(interrupted_patch_request(&location, 0, b"hello ")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::head(&location)
+                    .header(TUS_RESUMABLE, TUS_VERSION)
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(header(&response, UPLOAD_OFFSET), Some("6"));
+
+        // A disconnect before any bytes arrive leaves the offset where it was
+        let response =
+            app.clone().oneshot(interrupted_patch_request(&location, 6, b"")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response = app.clone().oneshot(patch_request(&location, 6, b"world")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert_eq!(header(&response, UPLOAD_OFFSET), Some("11"));
+        assert!(header(&response, "x-media-id").is_some());
+    }
+
+    #[tokio::test]
+    async fn test_patch_with_wrong_offset_conflicts() {
+        let temp_dir = TempDir::new().unwrap();
+        let app = create_test_app(&temp_dir);
+        let location = create(&app, 11).await;
+
+        let response = app.clone().oneshot(patch_request(&location, 3, b"hello")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        assert_eq!(header(&response, TUS_RESUMABLE), Some(TUS_VERSION));
+    }
+
+    #[tokio::test]
+    async fn test_patch_requires_offset_content_type() {
+        let temp_dir = TempDir::new().unwrap();
+        let app = create_test_app(&temp_dir);
+        let location = create(&app, 11).await;
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::patch(&location)
+                    .header(TUS_RESUMABLE, TUS_VERSION)
+                    .header(header::CONTENT_TYPE, "application/octet-stream")
+                    .header(UPLOAD_OFFSET, 0)
+                    .body(Body::from("hello"))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    }
+
+    #[tokio::test]
+    async fn test_unsupported_protocol_version() {
+        let temp_dir = TempDir::new().unwrap();
+        let app = create_test_app(&temp_dir);
+
+        let response = app
+            .oneshot(
+                Request::post("/uploads")
+                    .header(TUS_RESUMABLE, "0.2.2")
+                    .header(UPLOAD_LENGTH, 10)
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::PRECONDITION_FAILED);
+        assert_eq!(header(&response, "tus-version"), Some(TUS_VERSION));
+    }
+
+    #[tokio::test]
+    async fn test_create_rejects_oversized_and_missing_length() {
+        let temp_dir = TempDir::new().unwrap();
+        let app = create_test_app(&temp_dir);
+
+        let too_large = app
+            .clone()
+            .oneshot(
+                Request::post("/uploads")
+                    .header(TUS_RESUMABLE, TUS_VERSION)
+                    .header(UPLOAD_LENGTH, 4096)
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(too_large.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+        let missing = app
+            .oneshot(
+                Request::post("/uploads")
+                    .header(TUS_RESUMABLE, TUS_VERSION)
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(missing.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_options_advertises_capabilities() {
+        let temp_dir = TempDir::new().unwrap();
+        let app = create_test_app(&temp_dir);
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(Method::OPTIONS)
+                    .uri("/uploads")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert_eq!(header(&response, "tus-version"), Some(TUS_VERSION));
+        assert_eq!(header(&response, "tus-extension"), Some(TUS_EXTENSIONS));
+        assert_eq!(header(&response, "tus-max-size"), Some("1024"));
+    }
+
+    #[test]
+    fn test_parse_upload_metadata() {
+        let header = format!(
+            "filename {},filetype {},is_confidential",
+            STANDARD.encode("video.mp4"),
+            STANDARD.encode("video/mp4")
+        );
+        let metadata = parse_upload_metadata(&header).unwrap();
+
+        assert_eq!(metadata.get("filename").map(String::as_str), Some("video.mp4"));
+        assert_eq!(metadata.get("filetype").map(String::as_str), Some("video/mp4"));
+        assert_eq!(metadata.get("is_confidential").map(String::as_str), Some(""));
+        assert!(parse_upload_metadata("filename !!!").is_err());
+    }
+}
@@ -1,17 +1,24 @@
 use axum::{
-    extract::{FromRequestParts, Request},
-    http::{header::AUTHORIZATION, request::Parts},
+    extract::{FromRequestParts, OptionalFromRequestParts, Request, State},
+    http::{header::AUTHORIZATION, request::Parts, HeaderMap},
     middleware::Next,
     response::Response,
 };
 use jsonwebtoken::{decode, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
 use serde::{Deserialize, Serialize};
+use std::convert::Infallible;
 use std::fmt;
+use std::sync::Arc;
+use std::time::SystemTime;
 use thiserror::Error;
 use tracing::{debug, error, warn};
 use uuid::Uuid;
 
 use super::error::AppError;
+use crate::domain::entities::UserId;
+use crate::domain::services::{Clock, SystemClock};
+use crate::infrastructure::config::{AuthConfig, OAuth2Config};
+use crate::infrastructure::oauth2::{CachedTokenInfo, OAuth2Client, OAuth2Error};
 
 /// JWT token claims - `OAuth2` compatible format
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -157,6 +164,16 @@ impl UserContext {
     pub fn effective_user_id(&self) -> &str {
         self.user_id.as_ref().unwrap_or(&self.client_id)
     }
+
+    /// The `UserId` media uploaded with this token is owned by
+    ///
+    /// Subjects that are UUIDs map to that UUID. Anything else, such as a client
+    /// ID, maps to a name-based UUID so the same subject always owns the same media.
+    pub fn owner_id(&self) -> UserId {
+        let uuid = Uuid::parse_str(&self.subject)
+            .unwrap_or_else(|_| Uuid::new_v5(&Uuid::NAMESPACE_URL, self.subject.as_bytes()));
+        UserId::from_uuid(uuid)
+    }
 }
 
 impl From<Claims> for UserContext {
@@ -184,12 +201,64 @@ impl fmt::Display for UserContext {
     }
 }
 
+/// Build a user context from an active introspection result
+///
+/// Introspection responses carry no token ID, so `token_id` is left empty.
+///
+/// # Errors
+/// Returns `InvalidToken` if the response names no subject, user or client
+impl TryFrom<CachedTokenInfo> for UserContext {
+    type Error = JwtError;
+
+    fn try_from(info: CachedTokenInfo) -> Result<Self, Self::Error> {
+        let subject = info
+            .subject
+            .clone()
+            .or_else(|| info.username.clone())
+            .or_else(|| info.client_id.clone())
+            .ok_or(JwtError::InvalidToken)?;
+
+        Ok(Self {
+            user_id: info.username,
+            client_id: info.client_id.unwrap_or_default(),
+            subject,
+            scopes: info.scopes,
+            token_type: info.token_type.unwrap_or_else(|| "access_token".to_string()),
+            token_id: String::new(),
+            issuer: info.issuer.unwrap_or_default(),
+            audience: info.audience.unwrap_or_default(),
+        })
+    }
+}
+
+/// Extract the token from an `Authorization: Bearer` header
+///
+/// Returns `Ok(None)` when no `Authorization` header is present.
+///
+/// # Errors
+/// Returns `InvalidHeaderFormat` if the header is not a non-empty bearer token
+pub fn bearer_token(headers: &HeaderMap) -> Result<Option<&str>, JwtError> {
+    let Some(header) = headers.get(AUTHORIZATION) else {
+        return Ok(None);
+    };
+
+    header
+        .to_str()
+        .ok()
+        .and_then(|header| header.strip_prefix("Bearer "))
+        .map(str::trim)
+        .filter(|token| !token.is_empty())
+        .map(Some)
+        .ok_or(JwtError::InvalidHeaderFormat)
+}
+
 /// JWT service for token operations
 #[derive(Clone)]
 pub struct JwtService {
     encoding_key: EncodingKey,
     decoding_key: DecodingKey,
     validation: Validation,
+    clock: Arc<dyn Clock>,
 }
 
 impl JwtService {
@@ -199,9 +268,15 @@ impl JwtService {
     }
 
     /// Create new JWT service with secret and optional audience validation
+    ///
+    /// Tokens must carry a valid HS256 signature and be within their `nbf`..`exp`
+    /// window, give or take the validation leeway; the audience is only checked when
+    /// one is required.
     pub fn new_with_validation(secret: &str, required_audience: Option<&str>) -> Self {
         let mut validation = Validation::new(Algorithm::HS256);
-        validation.validate_exp = true;
+        // `exp` and `nbf` are checked against the service's clock in `decode_token`
+        validation.validate_exp = false;
+        validation.validate_nbf = false;
 
         if let Some(aud) = required_audience {
             validation.validate_aud = true;
@@ -214,9 +289,38 @@ impl JwtService {
             encoding_key: EncodingKey::from_secret(secret.as_ref()),
             decoding_key: DecodingKey::from_secret(secret.as_ref()),
             validation,
+            clock: Arc::new(SystemClock),
         }
     }
 
+    /// Read the time from `clock` when checking token expiry
+    #[must_use]
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Create the JWT service described by the auth middleware configuration
+    #[must_use]
+    pub fn from_config(config: &AuthConfig) -> Self {
+        Self::new_with_validation(config.jwt_secret.expose(), config.jwt_audience.as_deref())
+    }
+
+    /// Authenticate a request from its `Authorization: Bearer` header
+    ///
+    /// Returns `Ok(None)` when no `Authorization` header is present.
+    ///
+    /// # Errors
+    /// Returns a `JwtError` if the header is not a bearer token or the token
+    /// fails validation
+    pub fn authenticate(&self, headers: &HeaderMap) -> Result<Option<UserContext>, JwtError> {
+        let Some(token) = bearer_token(headers)? else {
+            return Ok(None);
+        };
+
+        self.decode_token(token).map(|claims| Some(claims.into()))
+    }
+
     /// Encode claims into JWT token
     pub fn encode_claims(&self, claims: &Claims) -> Result<String, JwtError> {
         encode(&Header::default(), claims, &self.encoding_key).map_err(|e| {
@@ -227,17 +331,34 @@ impl JwtService {
 
     /// Decode JWT token and extract claims
     pub fn decode_token(&self, token: &str) -> Result<Claims, JwtError> {
-        decode::<Claims>(token, &self.decoding_key, &self.validation)
+        let claims = decode::<Claims>(token, &self.decoding_key, &self.validation)
             .map(|token_data| token_data.claims)
             .map_err(|e| {
                 debug!("Failed to decode JWT: {}", e);
                 match e.kind() {
                     jsonwebtoken::errors::ErrorKind::ExpiredSignature => JwtError::Expired,
+                    jsonwebtoken::errors::ErrorKind::ImmatureSignature => JwtError::NotYetValid,
+                    jsonwebtoken::errors::ErrorKind::InvalidAudience => JwtError::InvalidAudience,
                     jsonwebtoken::errors::ErrorKind::InvalidSignature => JwtError::InvalidSignature,
                     jsonwebtoken::errors::ErrorKind::InvalidToken => JwtError::InvalidToken,
                     _ => JwtError::DecodingError(e.to_string()),
                 }
-            })
+            })?;
+
+        let now = self
+            .clock
+            .now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_or(0, |elapsed| usize::try_from(elapsed.as_secs()).unwrap_or(usize::MAX));
+        let leeway = usize::try_from(self.validation.leeway).unwrap_or(usize::MAX);
+        if claims.exp.saturating_add(leeway) < now {
+            return Err(JwtError::Expired);
+        }
+        if claims.nbf > now.saturating_add(leeway) {
+            return Err(JwtError::NotYetValid);
+        }
+
+        Ok(claims)
     }
 
     /// Create access token for user
@@ -282,6 +403,12 @@ pub enum JwtError {
     #[error("Token has expired")]
     Expired,
 
+    #[error("Token is not yet valid")]
+    NotYetValid,
+
+    #[error("Token audience is not accepted")]
+    InvalidAudience,
+
     #[error("Invalid token signature")]
     InvalidSignature,
 
@@ -307,20 +434,32 @@ impl From<JwtError> for AppError {
             JwtError::Expired => {
                 AppError::Authentication { message: "Token has expired".to_string() }
             }
-            JwtError::InvalidSignature | JwtError::InvalidToken | JwtError::InvalidHeaderFormat => {
+            JwtError::NotYetValid => {
+                AppError::Authentication { message: "Token is not yet valid".to_string() }
+            }
+            JwtError::InvalidAudience => {
+                AppError::Authentication { message: "Token audience is not accepted".to_string() }
+            }
+            JwtError::InvalidHeaderFormat => AppError::Authentication {
+                message: "Invalid authorization header format".to_string(),
+            },
+            // Malformed tokens come from the client, so they are not server errors
+            JwtError::InvalidSignature | JwtError::InvalidToken | JwtError::DecodingError(_) => {
                 AppError::Authentication { message: "Invalid token".to_string() }
             }
             JwtError::MissingHeader => {
                 AppError::Authentication { message: "Authorization header required".to_string() }
             }
-            JwtError::EncodingError(msg) | JwtError::DecodingError(msg) => {
+            JwtError::EncodingError(msg) => {
                 AppError::Internal { message: format!("JWT processing error: {msg}") }
             }
         }
     }
 }
 
-/// Extract user context from JWT token in request
+/// Extract the user context established by the authentication middleware
+///
+/// Rejects the request with 401 when no valid bearer token was presented.
 impl<S> FromRequestParts<S> for UserContext
 where
     S: Send + Sync,
@@ -328,111 +467,215 @@ where
     type Rejection = AppError;
 
     async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
-        // This is a placeholder implementation
-        // In a real implementation, you'd extract the JWT service from app state
-        // and decode the token from the Authorization header
-
-        // Extract the Authorization header manually
-        let auth_header =
-            parts.headers.get(AUTHORIZATION).and_then(|header| header.to_str().ok()).ok_or_else(
-                || AppError::Authentication { message: "Missing Authorization header".to_string() },
-            )?;
-
-        if !auth_header.starts_with("Bearer ") {
-            return Err(AppError::Authentication {
-                message: "Invalid Authorization header format".to_string(),
-            });
-        }
-
-        let token = &auth_header[7..]; // Remove "Bearer " prefix
+        parts.extensions.get::<UserContext>().cloned().ok_or_else(|| AppError::Authentication {
+            message: "Authentication required".to_string(),
+        })
+    }
+}
 
-        // For now, return a mock user context
-        // TODO: Replace with actual JWT decoding using service from app state
-        warn!("Using mock user context - implement JWT decoding with proper service");
-        debug!("Token received: {}", token.len()); // Just use the token somehow
+/// `Option<UserContext>` is `None` when the request was not authenticated
+impl<S> OptionalFromRequestParts<S> for UserContext
+where
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
 
-        Ok(UserContext {
-            user_id: Some("mock-user-id".to_string()),
-            client_id: "mock-client-id".to_string(),
-            subject: "mock-user-id".to_string(),
-            scopes: vec!["read".to_string(), "write".to_string()],
-            token_type: "access_token".to_string(),
-            token_id: Uuid::new_v4().to_string(),
-            issuer: "mock-issuer".to_string(),
-            audience: vec!["mock-audience".to_string()],
-        })
+    async fn from_request_parts(
+        parts: &mut Parts,
+        _state: &S,
+    ) -> Result<Option<Self>, Self::Rejection> {
+        Ok(parts.extensions.get::<UserContext>().cloned())
     }
 }
 
-/// Authentication middleware that validates JWT tokens
-pub async fn auth_middleware(mut request: Request, next: Next) -> Result<Response, AppError> {
-    // Extract authorization header
-    let auth_header =
-        request.headers().get(AUTHORIZATION).and_then(|h| h.to_str().ok()).ok_or_else(|| {
-            AppError::Authentication { message: "Authorization header required".to_string() }
-        })?;
-
-    // Extract bearer token
-    let token = auth_header.strip_prefix("Bearer ").ok_or_else(|| AppError::Authentication {
-        message: "Invalid authorization header format".to_string(),
-    })?;
-
-    // TODO: Get JWT service from app state and validate token
-    // For now, we'll do basic validation
-    if token.is_empty() || token == "invalid" {
-        return Err(AppError::Authentication { message: "Invalid token".to_string() });
-    }
-
-    // TODO: Decode token, validate claims, and add user context to request extensions
-    // For now, add a mock user context
-    let user_context = UserContext {
-        user_id: Some("mock-user-id".to_string()),
-        client_id: "mock-client-id".to_string(),
-        subject: "mock-user-id".to_string(),
-        scopes: vec!["read".to_string(), "write".to_string()],
-        token_type: "access_token".to_string(),
-        token_id: Uuid::new_v4().to_string(),
-        issuer: "mock-issuer".to_string(),
-        audience: vec!["mock-audience".to_string()],
-    };
+/// Authentication middleware that requires a valid JWT bearer token
+///
+/// # Errors
+/// Returns 401 if the token is missing, malformed, expired, not yet valid, for
+/// another audience, or has a bad signature
+pub async fn auth_middleware(
+    State(jwt_service): State<JwtService>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    let user_context =
+        jwt_service.authenticate(request.headers())?.ok_or(JwtError::MissingHeader)?;
 
     debug!("Authenticated user: {}", user_context);
+    request.extensions_mut().insert(user_context.clone());
 
-    // Add user context to request extensions
-    request.extensions_mut().insert(user_context);
-
-    // Continue with request
-    Ok(next.run(request).await)
+    Ok(with_user(next.run(request).await, Some(user_context)))
 }
 
 /// Optional authentication middleware that doesn't fail on missing auth
-pub async fn optional_auth_middleware(mut request: Request, next: Next) -> Response {
-    // Try to extract and validate token, but don't fail if missing
-    if let Some(auth_header) = request.headers().get(AUTHORIZATION).and_then(|h| h.to_str().ok()) {
-        if let Some(token) = auth_header.strip_prefix("Bearer ") {
-            if !token.is_empty() && token != "invalid" {
-                // TODO: Actual token validation
-                let user_context = UserContext {
-                    user_id: Some("mock-user-id".to_string()),
-                    client_id: "mock-client-id".to_string(),
-                    subject: "mock-user-id".to_string(),
-                    scopes: vec!["read".to_string(), "write".to_string()],
-                    token_type: "access_token".to_string(),
-                    token_id: Uuid::new_v4().to_string(),
-                    issuer: "mock-issuer".to_string(),
-                    audience: vec!["mock-audience".to_string()],
-                };
-
-                debug!("Optional auth: authenticated user: {}", user_context);
-                request.extensions_mut().insert(user_context);
+///
+/// A token that is presented must still be valid.
+///
+/// # Errors
+/// Returns 401 if an `Authorization` header is present but invalid
+pub async fn optional_auth_middleware(
+    State(jwt_service): State<JwtService>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    let user_context = jwt_service.authenticate(request.headers())?;
+    if let Some(user_context) = &user_context {
+        debug!("Optional auth: authenticated user: {}", user_context);
+        request.extensions_mut().insert(user_context.clone());
+    }
+
+    Ok(with_user(next.run(request).await, user_context))
+}
+
+/// Hand the authenticated user back on the response, for the request log
+fn with_user(mut response: Response, user_context: Option<UserContext>) -> Response {
+    if let Some(user_context) = user_context {
+        response.extensions_mut().insert(user_context);
+    }
+    response
+}
+
+/// Which routes need a token, built from `AuthConfig`
+///
+/// Paths under a `require_auth_routes` prefix must be authenticated unless they
+/// also fall under an `optional_auth_routes` prefix. Everywhere else a token is
+/// optional, but one that is presented is always validated.
+///
+/// With introspection configured, tokens are validated by the auth service
+/// instead of locally; see [`AuthPolicy::with_introspection`].
+#[derive(Clone)]
+pub struct AuthPolicy {
+    jwt_service: JwtService,
+    required_audience: Option<String>,
+    introspection: Option<Introspection>,
+    require_auth_routes: Vec<String>,
+    optional_auth_routes: Vec<String>,
+}
+
+/// Online token validation through the auth service
+#[derive(Clone)]
+struct Introspection {
+    client: OAuth2Client,
+    jwt_fallback: bool,
+}
+
+impl AuthPolicy {
+    /// Create the policy described by the auth middleware configuration
+    #[must_use]
+    pub fn from_config(config: &AuthConfig) -> Self {
+        Self {
+            jwt_service: JwtService::from_config(config),
+            required_audience: config.jwt_audience.clone(),
+            introspection: None,
+            require_auth_routes: config.require_auth_routes.clone(),
+            optional_auth_routes: config.optional_auth_routes.clone(),
+        }
+    }
+
+    /// Validate tokens with the auth service's introspection endpoint
+    ///
+    /// Results are cached for `token_cache_ttl_seconds`, or until the token
+    /// expires if sooner. When the endpoint cannot be reached or answers with an
+    /// error, tokens are validated locally as JWTs if `jwt_fallback_enabled` is
+    /// set, and rejected with 503 otherwise.
+    #[must_use]
+    pub fn with_introspection(mut self, client: OAuth2Client, config: &OAuth2Config) -> Self {
+        self.introspection =
+            Some(Introspection { client, jwt_fallback: config.jwt_fallback_enabled });
+        self
+    }
+
+    /// Authenticate a request from its `Authorization: Bearer` header
+    ///
+    /// Returns `Ok(None)` when no `Authorization` header is present.
+    ///
+    /// # Errors
+    /// Returns `Authentication` if the token is invalid, and `ServiceUnavailable`
+    /// if it cannot be introspected and local validation is disabled
+    pub async fn authenticate(&self, headers: &HeaderMap) -> Result<Option<UserContext>, AppError> {
+        let Some(token) = bearer_token(headers)? else {
+            return Ok(None);
+        };
+
+        let Some(introspection) = &self.introspection else {
+            return Ok(Some(self.jwt_service.decode_token(token)?.into()));
+        };
+
+        match introspection.client.introspect_token(token).await {
+            Ok(info) => {
+                let user_context = UserContext::try_from(info)?;
+                if let Some(audience) = &self.required_audience {
+                    if !user_context.audience.contains(audience) {
+                        return Err(JwtError::InvalidAudience.into());
+                    }
+                }
+                Ok(Some(user_context))
+            }
+            // Only the auth service saying the token is inactive is the caller's fault;
+            // a rejected or failed introspection call is an outage like any other
+            Err(OAuth2Error::InactiveToken) => Err(JwtError::InvalidToken.into()),
+            Err(e) if introspection.jwt_fallback => {
+                warn!("Token introspection unavailable, validating locally: {}", e);
+                Ok(Some(self.jwt_service.decode_token(token)?.into()))
             }
+            Err(e) => {
+                error!("Token introspection failed: {}", e);
+                Err(AppError::ServiceUnavailable {
+                    message: "Token validation is temporarily unavailable".to_string(),
+                    last_error: Some(e.to_string()),
+                    retry_after_seconds: None,
+                })
+            }
+        }
+    }
+
+    /// Whether a request path must carry a valid token
+    #[must_use]
+    pub fn requires_auth(&self, path: &str) -> bool {
+        let under = |prefix: &String| {
+            let prefix = prefix.trim_end_matches('/');
+            path == prefix || path.strip_prefix(prefix).is_some_and(|rest| rest.starts_with('/'))
+        };
+
+        self.require_auth_routes.iter().any(under) && !self.optional_auth_routes.iter().any(under)
+    }
+}
+
+/// Authentication middleware applying an [`AuthPolicy`] by request path
+///
+/// # Errors
+/// Returns 401 if a required token is missing, or any presented token is invalid
+pub async fn policy_auth_middleware(
+    State(policy): State<AuthPolicy>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    let user_context = policy.authenticate(request.headers()).await?;
+    match &user_context {
+        Some(user_context) => {
+            debug!("Authenticated user: {}", user_context);
+            request.extensions_mut().insert(user_context.clone());
+        }
+        None if policy.requires_auth(request.uri().path()) => {
+            return Err(JwtError::MissingHeader.into());
         }
+        None => {}
     }
 
-    next.run(request).await
+    Ok(with_user(next.run(request).await, user_context))
 }
 
+/// Scope a token needs for the admin endpoints
+pub const ADMIN_SCOPE: &str = "admin";
+
 /// Role-based authorization middleware
+///
+/// Expects the user context inserted by an authentication middleware layered
+/// outside it.
+///
+/// # Errors
+/// Returns 401 if the request is not authenticated, and 403 if its token has
+/// none of `required_roles` as a scope
 pub fn require_roles(
     required_roles: Vec<&'static str>,
 ) -> impl Fn(
@@ -480,8 +723,11 @@ mod tests {
         Router,
     };
     use serde_json::json;
+    use std::time::Duration;
     use tower::ServiceExt;
 
+    use crate::test_utils::mocks::ManualClock;
+
     async fn protected_handler() -> Json<serde_json::Value> {
         Json(json!({"message": "Protected resource accessed"}))
     }
@@ -619,92 +865,290 @@ mod tests {
         assert!(matches!(result.unwrap_err(), JwtError::Expired));
     }
 
-    #[tokio::test]
-    async fn test_auth_middleware_missing_header() {
-        let app = Router::new()
+    #[test]
+    fn test_jwt_service_expiry_allows_leeway() {
+        let clock = ManualClock::new(SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000));
+        let service = JwtService::new("test-secret-key").with_clock(Arc::new(clock.clone()));
+        let mut claims = test_claims();
+        claims.nbf = 1_000_000;
+        claims.iat = 1_000_000;
+        claims.exp = 1_003_600;
+        let token = service.encode_claims(&claims).unwrap();
+
+        clock.advance(Duration::from_mins(61));
+        assert!(service.decode_token(&token).is_ok());
+
+        clock.advance(Duration::from_secs(1));
+        assert!(matches!(service.decode_token(&token), Err(JwtError::Expired)));
+    }
+
+    #[test]
+    fn test_jwt_service_rejects_token_before_nbf() {
+        let clock = ManualClock::new(SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000));
+        let service = JwtService::new("test-secret-key").with_clock(Arc::new(clock.clone()));
+        let mut claims = test_claims();
+        claims.nbf = 1_000_061;
+        claims.exp = 1_003_600;
+        let token = service.encode_claims(&claims).unwrap();
+
+        assert!(matches!(service.decode_token(&token), Err(JwtError::NotYetValid)));
+
+        clock.advance(Duration::from_secs(1));
+        assert!(service.decode_token(&token).is_ok());
+    }
+
+    const TEST_SECRET: &str = "test-secret-key";
+
+    fn test_claims() -> Claims {
+        Claims::new_access_token(
+            "auth-service".to_string(),
+            vec!["media-service".to_string()],
+            "user123".to_string(),
+            "test-client".to_string(),
+            vec!["read".to_string()],
+            1,
+        )
+    }
+
+    fn test_token(claims: &Claims) -> String {
+        JwtService::new(TEST_SECRET).encode_claims(claims).unwrap()
+    }
+
+    fn protected_app(jwt_service: JwtService) -> Router {
+        Router::new()
             .route("/protected", get(protected_handler))
-            .layer(axum::middleware::from_fn(auth_middleware));
+            .layer(axum::middleware::from_fn_with_state(jwt_service, auth_middleware))
+    }
 
-        let request = Request::builder().uri("/protected").body(Body::empty()).unwrap();
+    async fn status_with_auth(app: Router, uri: &str, authorization: Option<&str>) -> StatusCode {
+        let mut request = Request::builder().uri(uri);
+        if let Some(authorization) = authorization {
+            request = request.header("Authorization", authorization);
+        }
 
-        let response = app.oneshot(request).await.unwrap();
-        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        app.oneshot(request.body(Body::empty()).unwrap()).await.unwrap().status()
     }
 
     #[tokio::test]
-    async fn test_auth_middleware_invalid_header() {
-        let app = Router::new()
-            .route("/protected", get(protected_handler))
-            .layer(axum::middleware::from_fn(auth_middleware));
+    async fn test_auth_middleware_missing_header() {
+        let app = protected_app(JwtService::new(TEST_SECRET));
 
-        let request = Request::builder()
-            .uri("/protected")
-            .header("Authorization", "InvalidFormat")
-            .body(Body::empty())
-            .unwrap();
+        assert_eq!(status_with_auth(app, "/protected", None).await, StatusCode::UNAUTHORIZED);
+    }
 
-        let response = app.oneshot(request).await.unwrap();
-        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    #[tokio::test]
+    async fn test_auth_middleware_invalid_header() {
+        let app = protected_app(JwtService::new(TEST_SECRET));
+
+        let status = status_with_auth(app, "/protected", Some("InvalidFormat")).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
     }
 
     #[tokio::test]
     async fn test_auth_middleware_empty_token() {
-        let app = Router::new()
-            .route("/protected", get(protected_handler))
-            .layer(axum::middleware::from_fn(auth_middleware));
-
-        let request = Request::builder()
-            .uri("/protected")
-            .header("Authorization", "Bearer ")
-            .body(Body::empty())
-            .unwrap();
+        let app = protected_app(JwtService::new(TEST_SECRET));
 
-        let response = app.oneshot(request).await.unwrap();
-        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(
+            status_with_auth(app, "/protected", Some("Bearer ")).await,
+            StatusCode::UNAUTHORIZED
+        );
     }
 
     #[tokio::test]
     async fn test_auth_middleware_valid_token() {
-        let app = Router::new()
-            .route("/protected", get(protected_handler))
-            .layer(axum::middleware::from_fn(auth_middleware));
+        let app = protected_app(JwtService::new(TEST_SECRET));
+        let authorization = format!("Bearer {}", test_token(&test_claims()));
+
+        let status = status_with_auth(app, "/protected", Some(&authorization)).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_auth_middleware_rejects_invalid_tokens() {
+        let mut expired = test_claims();
+        expired.exp = 0;
+        let mut immature = test_claims();
+        immature.nbf = immature.exp;
+        let forged = JwtService::new("other-secret").encode_claims(&test_claims()).unwrap();
+
+        for token in [
+            "not-a-jwt".to_string(),
+            "invalid.token.here".to_string(),
+            test_token(&expired),
+            test_token(&immature),
+            forged,
+        ] {
+            let app = protected_app(JwtService::new(TEST_SECRET));
+            let authorization = format!("Bearer {token}");
+
+            let status = status_with_auth(app, "/protected", Some(&authorization)).await;
+            assert_eq!(status, StatusCode::UNAUTHORIZED, "token {token} was accepted");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_auth_middleware_checks_audience() {
+        let authorization = format!("Bearer {}", test_token(&test_claims()));
+
+        let accepted =
+            protected_app(JwtService::new_with_validation(TEST_SECRET, Some("media-service")));
+        let rejected =
+            protected_app(JwtService::new_with_validation(TEST_SECRET, Some("other-service")));
+
+        assert_eq!(
+            status_with_auth(accepted, "/protected", Some(&authorization)).await,
+            StatusCode::OK
+        );
+        assert_eq!(
+            status_with_auth(rejected, "/protected", Some(&authorization)).await,
+            StatusCode::UNAUTHORIZED
+        );
+    }
+
+    #[tokio::test]
+    async fn test_user_context_extracted_from_claims() {
+        async fn whoami(user: UserContext) -> String {
+            format!("{}:{}", user.effective_user_id(), user.scopes.join(","))
+        }
 
+        let app = Router::new().route("/whoami", get(whoami)).layer(
+            axum::middleware::from_fn_with_state(JwtService::new(TEST_SECRET), auth_middleware),
+        );
         let request = Request::builder()
-            .uri("/protected")
-            .header("Authorization", "Bearer valid-token")
+            .uri("/whoami")
+            .header("Authorization", format!("Bearer {}", test_token(&test_claims())))
             .body(Body::empty())
             .unwrap();
 
         let response = app.oneshot(request).await.unwrap();
-        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+
+        assert_eq!(&body[..], b"user123:read");
+    }
+
+    #[tokio::test]
+    async fn test_user_context_extractor_without_middleware() {
+        async fn whoami(user: UserContext) -> String {
+            user.subject
+        }
+
+        let app = Router::new().route("/whoami", get(whoami));
+
+        assert_eq!(status_with_auth(app, "/whoami", None).await, StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_optional_user_context_extractor() {
+        async fn whoami(user: Option<UserContext>) -> String {
+            user.map_or_else(|| "anonymous".to_string(), |user| user.subject)
+        }
+
+        async fn call(app: Router, authorization: Option<String>) -> String {
+            let mut request = Request::builder().uri("/whoami");
+            if let Some(authorization) = authorization {
+                request = request.header("Authorization", authorization);
+            }
+            let response = app.oneshot(request.body(Body::empty()).unwrap()).await.unwrap();
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            String::from_utf8(body.to_vec()).unwrap()
+        }
+
+        let app = Router::new().route("/whoami", get(whoami)).layer(
+            axum::middleware::from_fn_with_state(
+                JwtService::new(TEST_SECRET),
+                optional_auth_middleware,
+            ),
+        );
+
+        assert_eq!(call(app.clone(), None).await, "anonymous");
+        let token = test_token(&test_claims());
+        assert_eq!(call(app, Some(format!("Bearer {token}"))).await, "user123");
+    }
+
+    #[test]
+    fn test_user_context_owner_id() {
+        let user_uuid = Uuid::new_v4();
+        let mut claims = test_claims();
+        claims.sub = user_uuid.to_string();
+        let context = UserContext::from(claims);
+        assert_eq!(context.owner_id(), UserId::from_uuid(user_uuid));
+
+        let mut claims = test_claims();
+        claims.sub = "recipe-service".to_string();
+        let first = UserContext::from(claims.clone()).owner_id();
+        let second = UserContext::from(claims).owner_id();
+        assert_eq!(first, second);
+        assert_ne!(first.as_uuid(), Uuid::nil());
     }
 
     #[tokio::test]
     async fn test_optional_auth_middleware_no_header() {
-        let app = Router::new()
-            .route("/optional", get(protected_handler))
-            .layer(axum::middleware::from_fn(optional_auth_middleware));
+        let app = Router::new().route("/optional", get(protected_handler)).layer(
+            axum::middleware::from_fn_with_state(
+                JwtService::new(TEST_SECRET),
+                optional_auth_middleware,
+            ),
+        );
 
-        let request = Request::builder().uri("/optional").body(Body::empty()).unwrap();
+        assert_eq!(status_with_auth(app, "/optional", None).await, StatusCode::OK);
+    }
 
-        let response = app.oneshot(request).await.unwrap();
-        assert_eq!(response.status(), StatusCode::OK);
+    #[tokio::test]
+    async fn test_optional_auth_middleware_tokens() {
+        let app = Router::new().route("/optional", get(protected_handler)).layer(
+            axum::middleware::from_fn_with_state(
+                JwtService::new(TEST_SECRET),
+                optional_auth_middleware,
+            ),
+        );
+        let valid = format!("Bearer {}", test_token(&test_claims()));
+
+        assert_eq!(status_with_auth(app.clone(), "/optional", Some(&valid)).await, StatusCode::OK);
+        assert_eq!(
+            status_with_auth(app, "/optional", Some("Bearer invalid")).await,
+            StatusCode::UNAUTHORIZED
+        );
+    }
+
+    fn test_policy() -> AuthPolicy {
+        AuthPolicy::from_config(&AuthConfig {
+            enabled: true,
+            jwt_secret: TEST_SECRET.into(),
+            jwt_expiry_hours: 1,
+            jwt_audience: None,
+            require_auth_routes: vec!["/api/media".to_string()],
+            optional_auth_routes: vec!["/api/media/public/".to_string()],
+        })
+    }
+
+    #[test]
+    fn test_auth_policy_requires_auth() {
+        let policy = test_policy();
+
+        assert!(policy.requires_auth("/api/media"));
+        assert!(policy.requires_auth("/api/media/42"));
+        assert!(!policy.requires_auth("/api/media-other"));
+        assert!(!policy.requires_auth("/api/media/public"));
+        assert!(!policy.requires_auth("/api/media/public/42"));
+        assert!(!policy.requires_auth("/health"));
     }
 
     #[tokio::test]
-    async fn test_optional_auth_middleware_valid_token() {
+    async fn test_policy_auth_middleware() {
         let app = Router::new()
-            .route("/optional", get(protected_handler))
-            .layer(axum::middleware::from_fn(optional_auth_middleware));
-
-        let request = Request::builder()
-            .uri("/optional")
-            .header("Authorization", "Bearer valid-token")
-            .body(Body::empty())
-            .unwrap();
+            .route("/api/media", get(protected_handler))
+            .route("/health", get(protected_handler))
+            .layer(axum::middleware::from_fn_with_state(test_policy(), policy_auth_middleware));
 
-        let response = app.oneshot(request).await.unwrap();
-        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            status_with_auth(app.clone(), "/api/media", None).await,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(status_with_auth(app.clone(), "/health", None).await, StatusCode::OK);
+        assert_eq!(
+            status_with_auth(app, "/health", Some("Bearer invalid")).await,
+            StatusCode::UNAUTHORIZED
+        );
     }
 
     #[test]
@@ -715,6 +1159,9 @@ mod tests {
         let invalid_error: AppError = JwtError::InvalidToken.into();
         assert!(matches!(invalid_error, AppError::Authentication { .. }));
 
+        let decoding_error: AppError = JwtError::DecodingError("test".to_string()).into();
+        assert!(matches!(decoding_error, AppError::Authentication { .. }));
+
         let encoding_error: AppError = JwtError::EncodingError("test".to_string()).into();
         assert!(matches!(encoding_error, AppError::Internal { .. }));
     }
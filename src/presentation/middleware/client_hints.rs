@@ -0,0 +1,133 @@
+//! Client hints on upload requests
+//!
+//! Apps may describe themselves with optional headers on upload requests, so
+//! upload failures reported from the field can be segmented by client:
+//! - `X-Client-Version` - app version, e.g. `3.14.0`
+//! - `X-Client-Platform` - e.g. `ios`, `android` or `web`
+//! - `X-Client-Network` - network type, e.g. `wifi` or `cellular`
+//!
+//! Every upload request is counted in `media_upload_requests_total`, labelled by
+//! `upload` (`direct`, `presigned` or `resumable`), response `status` and the
+//! three hints, and logged with the hints and the authenticated client. Hints
+//! are capped at 32 characters of `[A-Za-z0-9._+-]`; missing or malformed hints
+//! are reported as `unknown`.
+
+use axum::{
+    extract::Request,
+    http::{HeaderMap, HeaderName},
+    middleware::Next,
+    response::Response,
+};
+
+use crate::presentation::middleware::auth::UserContext;
+
+pub const CLIENT_VERSION_HEADER: HeaderName = HeaderName::from_static("x-client-version");
+pub const CLIENT_PLATFORM_HEADER: HeaderName = HeaderName::from_static("x-client-platform");
+pub const CLIENT_NETWORK_HEADER: HeaderName = HeaderName::from_static("x-client-network");
+
+const MAX_HINT_LEN: usize = 32;
+const UNKNOWN: &str = "unknown";
+
+/// Self-reported details of the client making a request
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ClientHints {
+    pub app_version: Option<String>,
+    pub platform: Option<String>,
+    pub network_type: Option<String>,
+}
+
+impl ClientHints {
+    /// Read the hints from request headers, dropping malformed values
+    #[must_use]
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        let hint = |name: &HeaderName| headers.get(name)?.to_str().ok().and_then(sanitize);
+        Self {
+            app_version: hint(&CLIENT_VERSION_HEADER),
+            platform: hint(&CLIENT_PLATFORM_HEADER).map(|p| p.to_ascii_lowercase()),
+            network_type: hint(&CLIENT_NETWORK_HEADER).map(|n| n.to_ascii_lowercase()),
+        }
+    }
+}
+
+fn sanitize(value: &str) -> Option<String> {
+    let value = value.trim();
+    let valid = !value.is_empty()
+        && value.len() <= MAX_HINT_LEN
+        && value.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '+' | '-'));
+    valid.then(|| value.to_string())
+}
+
+fn label(hint: Option<&String>) -> &str {
+    hint.map_or(UNKNOWN, String::as_str)
+}
+
+/// Middleware recording the client hints and outcome of `upload` requests
+///
+/// The hints are also added to the request extensions as [`ClientHints`].
+pub fn track_upload_clients(
+    upload: &'static str,
+) -> impl Fn(Request, Next) -> std::pin::Pin<Box<dyn std::future::Future<Output = Response> + Send>>
+       + Clone {
+    move |mut request: Request, next: Next| {
+        Box::pin(async move {
+            let hints = ClientHints::from_headers(request.headers());
+            let client_id =
+                request.extensions().get::<UserContext>().map(|user| user.client_id.clone());
+            request.extensions_mut().insert(hints.clone());
+
+            let response = next.run(request).await;
+
+            let status = response.status();
+            metrics::counter!(
+                "media_upload_requests_total",
+                "upload" => upload,
+                "status" => status.as_u16().to_string(),
+                "app_version" => label(hints.app_version.as_ref()).to_string(),
+                "platform" => label(hints.platform.as_ref()).to_string(),
+                "network_type" => label(hints.network_type.as_ref()).to_string()
+            )
+            .increment(1);
+            tracing::info!(
+                upload,
+                status = status.as_u16(),
+                client_id = client_id.as_deref().unwrap_or(UNKNOWN),
+                app_version = label(hints.app_version.as_ref()),
+                platform = label(hints.platform.as_ref()),
+                network_type = label(hints.network_type.as_ref()),
+                "Upload request finished"
+            );
+
+            response
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    #[test]
+    fn test_hints_are_read_and_normalized() {
+        let mut headers = HeaderMap::new();
+        headers.insert(CLIENT_VERSION_HEADER, HeaderValue::from_static(" 3.14.0-beta+2 "));
+        headers.insert(CLIENT_PLATFORM_HEADER, HeaderValue::from_static("iOS"));
+        headers.insert(CLIENT_NETWORK_HEADER, HeaderValue::from_static("Cellular"));
+
+        let hints = ClientHints::from_headers(&headers);
+
+        assert_eq!(hints.app_version.as_deref(), Some("3.14.0-beta+2"));
+        assert_eq!(hints.platform.as_deref(), Some("ios"));
+        assert_eq!(hints.network_type.as_deref(), Some("cellular"));
+    }
+
+    #[test]
+    fn test_malformed_hints_are_dropped() {
+        let mut headers = HeaderMap::new();
+        headers.insert(CLIENT_VERSION_HEADER, HeaderValue::from_static("1.0 (build 7)"));
+        headers.insert(CLIENT_PLATFORM_HEADER, HeaderValue::from_str(&"a".repeat(33)).unwrap());
+        headers.insert(CLIENT_NETWORK_HEADER, HeaderValue::from_static(""));
+
+        assert_eq!(ClientHints::from_headers(&headers), ClientHints::default());
+    }
+}
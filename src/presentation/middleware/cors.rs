@@ -0,0 +1,174 @@
+//! CORS alongside routes that answer `OPTIONS` themselves
+//!
+//! `CorsLayer` answers every `OPTIONS` request as a preflight, which would hide
+//! the tus discovery response of `OPTIONS /media/uploads` from clients. Only a
+//! request carrying `Access-Control-Request-Method` is a preflight, so
+//! [`pass_plain_options`] disguises any other `OPTIONS` request as a `GET` on its
+//! way through the CORS layer, where it gets the headers of an actual
+//! cross-origin request, and [`restore_plain_options`], layered inside the CORS
+//! layer, turns it back into the `OPTIONS` request the route expects.
+//!
+//! The origins allowed cross-origin access are held by [`CorsOrigins`], which a
+//! configuration reload can replace while the server runs.
+
+use std::sync::{Arc, PoisonError, RwLock};
+
+use axum::{
+    extract::Request,
+    http::{header::ACCESS_CONTROL_REQUEST_METHOD, HeaderValue, Method},
+    middleware::Next,
+    response::Response,
+};
+
+/// Marks a request disguised by [`pass_plain_options`]
+#[derive(Debug, Clone, Copy)]
+struct PlainOptions;
+
+/// Middleware letting `OPTIONS` requests that are not preflights through the CORS layer
+///
+/// Layer it outside the CORS layer, with [`restore_plain_options`] inside it.
+pub async fn pass_plain_options(mut request: Request, next: Next) -> Response {
+    if request.method() == Method::OPTIONS
+        && !request.headers().contains_key(ACCESS_CONTROL_REQUEST_METHOD)
+    {
+        *request.method_mut() = Method::GET;
+        request.extensions_mut().insert(PlainOptions);
+    }
+    next.run(request).await
+}
+
+/// Middleware restoring the method of requests disguised by [`pass_plain_options`]
+pub async fn restore_plain_options(mut request: Request, next: Next) -> Response {
+    if request.extensions_mut().remove::<PlainOptions>().is_some() {
+        *request.method_mut() = Method::OPTIONS;
+    }
+    next.run(request).await
+}
+
+/// Origins allowed to make cross-origin requests
+///
+/// Each entry is `*` for any origin, an exact origin such as
+/// `https://recipes.example.com`, or a scheme and a wildcard subdomain such as
+/// `https://*.example.com`, which matches subdomains at any depth but not
+/// `example.com` itself. Cloning is cheap; clones share the same list, so a
+/// configuration reload applies to the running CORS layer.
+#[derive(Debug, Clone, Default)]
+pub struct CorsOrigins {
+    patterns: Arc<RwLock<Arc<[OriginPattern]>>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum OriginPattern {
+    Any,
+    Exact(String),
+    Subdomain { scheme: String, domain: String },
+}
+
+impl CorsOrigins {
+    /// Allow the origins `origins` lists; entries that are no valid pattern are ignored
+    #[must_use]
+    pub fn new(origins: &[String]) -> Self {
+        let allowed = Self::default();
+        allowed.set(origins);
+        allowed
+    }
+
+    /// Replace the allowed origins
+    pub fn set(&self, origins: &[String]) {
+        let patterns: Arc<[OriginPattern]> =
+            origins.iter().filter_map(|origin| parse_pattern(origin)).collect();
+        *self.patterns.write().unwrap_or_else(PoisonError::into_inner) = patterns;
+    }
+
+    /// Whether `origin` may make cross-origin requests
+    #[must_use]
+    pub fn allows(&self, origin: &HeaderValue) -> bool {
+        let Ok(origin) = origin.to_str() else {
+            return false;
+        };
+        let origin = origin.to_ascii_lowercase();
+        let patterns = self.patterns.read().unwrap_or_else(PoisonError::into_inner).clone();
+        patterns.iter().any(|pattern| match pattern {
+            OriginPattern::Any => true,
+            OriginPattern::Exact(allowed) => *allowed == origin,
+            OriginPattern::Subdomain { scheme, domain } => origin
+                .strip_prefix(scheme.as_str())
+                .and_then(|host| host.strip_suffix(domain.as_str()))
+                .is_some_and(|subdomain| {
+                    subdomain.ends_with('.')
+                        && subdomain.len() > 1
+                        && subdomain
+                            .chars()
+                            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '.')
+                }),
+        })
+    }
+}
+
+/// Whether `origin` is a valid entry of the allowed origins
+#[must_use]
+pub fn is_valid_origin_pattern(origin: &str) -> bool {
+    parse_pattern(origin).is_some()
+}
+
+fn parse_pattern(origin: &str) -> Option<OriginPattern> {
+    let origin = origin.trim().trim_end_matches('/').to_ascii_lowercase();
+    if origin == "*" {
+        return Some(OriginPattern::Any);
+    }
+
+    let (scheme, host) = origin.split_once("://")?;
+    let valid_host = |host: &str| {
+        !host.is_empty()
+            && host.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '.' | ':'))
+    };
+    if !matches!(scheme, "http" | "https") {
+        return None;
+    }
+    match host.strip_prefix("*.") {
+        Some(domain) if valid_host(domain) => Some(OriginPattern::Subdomain {
+            scheme: format!("{scheme}://"),
+            domain: domain.to_string(),
+        }),
+        None if valid_host(host) => Some(OriginPattern::Exact(origin.clone())),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn origins(list: &[&str]) -> CorsOrigins {
+        CorsOrigins::new(&list.iter().map(ToString::to_string).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn test_origins_match_exactly_or_by_subdomain() {
+        let allowed = origins(&["https://recipes.example", "https://*.example.com", "bogus"]);
+        let allows = |origin: &'static str| allowed.allows(&HeaderValue::from_static(origin));
+
+        assert!(allows("https://recipes.example"));
+        assert!(allows("https://RECIPES.example"));
+        assert!(allows("https://app.example.com"));
+        assert!(allows("https://eu.app.example.com"));
+        assert!(!allows("https://example.com"));
+        assert!(!allows("http://app.example.com"));
+        assert!(!allows("https://app.example.com.evil.test"));
+        assert!(!allows("https://evil.test/.example.com"));
+        assert!(!allows("https://other.example"));
+
+        allowed.set(&["*".to_string()]);
+        assert!(allows("https://other.example"));
+    }
+
+    #[test]
+    fn test_origin_patterns_are_validated() {
+        assert!(is_valid_origin_pattern("https://recipes.example:8443"));
+        assert!(is_valid_origin_pattern("*"));
+        assert!(!is_valid_origin_pattern("recipes.example"));
+        assert!(!is_valid_origin_pattern("ftp://recipes.example"));
+        assert!(!is_valid_origin_pattern("https://*"));
+        assert!(!is_valid_origin_pattern("https://recipes.example/path"));
+    }
+}
@@ -0,0 +1,157 @@
+//! Deadline propagation from the gateway
+//!
+//! Callers, usually the API gateway, can say how long they will wait for a
+//! response with either header:
+//! - `X-Request-Deadline` - the wall-clock time by which the response is needed,
+//!   as an RFC 3339 timestamp or Unix milliseconds
+//! - `grpc-timeout` - the remaining budget, as up to eight digits followed by a
+//!   unit: `H`ours, `M`inutes, `S`econds, `m`illiseconds, `u`microseconds or
+//!   `n`anoseconds
+//!
+//! When both are sent the earlier deadline applies; malformed values are ignored.
+//! The request runs within the resulting [`Deadline`], so database connection
+//! acquisition, storage IO and any other work still pending when it passes is
+//! cancelled and the caller gets `504 Gateway Timeout` instead of a response
+//! nobody is waiting for. A request arriving with its deadline already passed is
+//! rejected without being handled. Either way `request_deadline_exceeded_total`
+//! is incremented, labelled by the `stage` at which the deadline was noticed.
+//!
+//! Processing is queued on its own task once an upload has been stored, so it is
+//! not cancelled by the deadline of the upload request.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use axum::{
+    extract::Request,
+    http::{HeaderMap, HeaderName},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use chrono::DateTime;
+
+use crate::application::deadline::Deadline;
+
+pub const REQUEST_DEADLINE_HEADER: HeaderName = HeaderName::from_static("x-request-deadline");
+pub const GRPC_TIMEOUT_HEADER: HeaderName = HeaderName::from_static("grpc-timeout");
+
+/// Middleware running each request within the deadline set by its caller, if any
+pub async fn propagate_deadline(request: Request, next: Next) -> Response {
+    let Some(deadline) = request_deadline(request.headers()) else {
+        return next.run(request).await;
+    };
+
+    if deadline.is_expired() {
+        metrics::counter!("request_deadline_exceeded_total", "stage" => "arrival").increment(1);
+        tracing::debug!(path = request.uri().path(), "Request arrived after its deadline");
+        return deadline.exceeded().into_response();
+    }
+
+    match deadline.scope(next.run(request)).await {
+        Ok(response) => response,
+        Err(e) => {
+            metrics::counter!("request_deadline_exceeded_total", "stage" => "handling")
+                .increment(1);
+            e.into_response()
+        }
+    }
+}
+
+/// The earliest deadline set by the request's headers
+fn request_deadline(headers: &HeaderMap) -> Option<Deadline> {
+    let absolute = headers
+        .get(REQUEST_DEADLINE_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(parse_request_deadline)
+        .map(Deadline::at);
+    let relative = headers
+        .get(GRPC_TIMEOUT_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(parse_grpc_timeout)
+        .map(Deadline::after);
+
+    match (absolute, relative) {
+        (Some(absolute), Some(relative)) => Some(absolute.min(relative)),
+        (deadline, None) | (None, deadline) => deadline,
+    }
+}
+
+/// Parse an RFC 3339 timestamp or Unix milliseconds
+fn parse_request_deadline(value: &str) -> Option<SystemTime> {
+    let value = value.trim();
+    if let Ok(millis) = value.parse::<u64>() {
+        return UNIX_EPOCH.checked_add(Duration::from_millis(millis));
+    }
+    DateTime::parse_from_rfc3339(value).ok().map(SystemTime::from)
+}
+
+/// Parse a gRPC timeout such as `250m` or `5S`
+fn parse_grpc_timeout(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    let unit = value.chars().last()?;
+    let amount = &value[..value.len() - unit.len_utf8()];
+    if amount.is_empty() || amount.len() > 8 || !amount.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let amount: u64 = amount.parse().ok()?;
+
+    match unit {
+        'H' => Some(Duration::from_secs(amount * 3600)),
+        'M' => Some(Duration::from_secs(amount * 60)),
+        'S' => Some(Duration::from_secs(amount)),
+        'm' => Some(Duration::from_millis(amount)),
+        'u' => Some(Duration::from_micros(amount)),
+        'n' => Some(Duration::from_nanos(amount)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::StatusCode, middleware::from_fn, routing::get, Router};
+    use tower::ServiceExt;
+
+    async fn call(header: HeaderName, value: &str) -> Response {
+        let app = Router::new()
+            .route(
+                "/media",
+                get(|| async {
+                    tokio::time::sleep(Duration::from_millis(200)).await;
+                    "listed"
+                }),
+            )
+            .layer(from_fn(propagate_deadline));
+        let request = Request::get("/media").header(header, value).body(Body::empty()).unwrap();
+        app.oneshot(request).await.unwrap()
+    }
+
+    #[test]
+    fn test_parse_grpc_timeout() {
+        assert_eq!(parse_grpc_timeout("250m"), Some(Duration::from_millis(250)));
+        assert_eq!(parse_grpc_timeout("2M"), Some(Duration::from_mins(2)));
+        assert_eq!(parse_grpc_timeout("123456789S"), None);
+        assert_eq!(parse_grpc_timeout("5x"), None);
+        assert_eq!(parse_grpc_timeout("m"), None);
+    }
+
+    #[test]
+    fn test_earlier_deadline_wins() {
+        let mut headers = HeaderMap::new();
+        headers.insert(GRPC_TIMEOUT_HEADER, "1S".parse().unwrap());
+        headers.insert(REQUEST_DEADLINE_HEADER, "2099-01-01T00:00:00Z".parse().unwrap());
+
+        let remaining = request_deadline(&headers).unwrap().remaining();
+        assert!(remaining <= Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn test_exhausted_budget_returns_gateway_timeout() {
+        assert_eq!(call(GRPC_TIMEOUT_HEADER, "20m").await.status(), StatusCode::GATEWAY_TIMEOUT);
+        assert_eq!(
+            call(REQUEST_DEADLINE_HEADER, "2000-01-01T00:00:00Z").await.status(),
+            StatusCode::GATEWAY_TIMEOUT
+        );
+        assert_eq!(call(GRPC_TIMEOUT_HEADER, "5S").await.status(), StatusCode::OK);
+        assert_eq!(call(GRPC_TIMEOUT_HEADER, "soon").await.status(), StatusCode::OK);
+    }
+}
@@ -0,0 +1,116 @@
+//! Deprecation of the legacy direct upload endpoint
+//!
+//! `POST /media` reads whole files into memory, so clients are moving to the
+//! presigned and resumable upload flows. While the endpoint is still served its
+//! responses carry `Deprecation: true` (RFC 9745), a `Link` to the presigned
+//! flow as its successor and, once a date is set, `Sunset` (RFC 8594). When it
+//! is disabled, requests are answered with `410 Gone` and the same headers.
+//!
+//! Every call is counted in `media_legacy_upload_requests_total`, labelled by
+//! `outcome` (`served` or `rejected`), and logged with the authenticated client
+//! so remaining callers can be tracked down before the sunset.
+
+use axum::{
+    extract::Request,
+    http::{HeaderMap, HeaderName, HeaderValue},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, Utc};
+
+use crate::{
+    domain::value_objects::LegacyUploadPolicy,
+    presentation::middleware::{auth::UserContext, error::AppError},
+};
+
+pub const DEPRECATION_HEADER: HeaderName = HeaderName::from_static("deprecation");
+pub const SUNSET_HEADER: HeaderName = HeaderName::from_static("sunset");
+
+/// Where clients should upload instead
+const SUCCESSOR_LINK: &str =
+    r#"</api/v1/media-management/media/upload-request>; rel="successor-version""#;
+
+/// Middleware announcing the deprecation of the direct upload, or rejecting it once disabled
+pub fn deprecate_legacy_upload(
+    policy: LegacyUploadPolicy,
+) -> impl Fn(Request, Next) -> std::pin::Pin<Box<dyn std::future::Future<Output = Response> + Send>>
+       + Clone {
+    move |request: Request, next: Next| {
+        Box::pin(async move {
+            let client_id =
+                request.extensions().get::<UserContext>().map(|user| user.client_id.clone());
+            let outcome = if policy.enabled { "served" } else { "rejected" };
+            metrics::counter!("media_legacy_upload_requests_total", "outcome" => outcome)
+                .increment(1);
+            tracing::warn!(
+                outcome,
+                client_id = client_id.as_deref().unwrap_or("unknown"),
+                "Deprecated direct upload endpoint called"
+            );
+
+            let mut response = if policy.enabled {
+                next.run(request).await
+            } else {
+                AppError::Gone {
+                    message: "Direct uploads have been retired; use POST /media/upload-request \
+                              or resumable uploads instead"
+                        .to_string(),
+                }
+                .into_response()
+            };
+            insert_deprecation_headers(response.headers_mut(), policy);
+            response
+        })
+    }
+}
+
+fn insert_deprecation_headers(headers: &mut HeaderMap, policy: LegacyUploadPolicy) {
+    headers.insert(DEPRECATION_HEADER, HeaderValue::from_static("true"));
+    headers.append(axum::http::header::LINK, HeaderValue::from_static(SUCCESSOR_LINK));
+    if let Some(sunset) = policy.sunset {
+        let sunset: DateTime<Utc> = sunset.into();
+        if let Ok(value) =
+            HeaderValue::from_str(&sunset.format("%a, %d %b %Y %H:%M:%S GMT").to_string())
+        {
+            headers.insert(SUNSET_HEADER, value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::StatusCode, middleware::from_fn, routing::post, Router};
+    use tower::ServiceExt;
+
+    async fn call(policy: LegacyUploadPolicy) -> Response {
+        let app = Router::new()
+            .route("/media", post(|| async { "stored" }))
+            .layer(from_fn(deprecate_legacy_upload(policy)));
+        let request = Request::post("/media").body(Body::empty()).unwrap();
+        app.oneshot(request).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_served_uploads_announce_deprecation() {
+        let sunset = DateTime::parse_from_rfc3339("2027-01-01T00:00:00Z").unwrap().into();
+        let response = call(LegacyUploadPolicy { enabled: true, sunset: Some(sunset) }).await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[DEPRECATION_HEADER], "true");
+        assert_eq!(response.headers()[SUNSET_HEADER], "Fri, 01 Jan 2027 00:00:00 GMT");
+        assert!(response.headers()[axum::http::header::LINK]
+            .to_str()
+            .unwrap()
+            .contains("successor-version"));
+    }
+
+    #[tokio::test]
+    async fn test_disabled_uploads_are_gone() {
+        let response = call(LegacyUploadPolicy { enabled: false, sunset: None }).await;
+
+        assert_eq!(response.status(), StatusCode::GONE);
+        assert_eq!(response.headers()[DEPRECATION_HEADER], "true");
+        assert!(!response.headers().contains_key(SUNSET_HEADER));
+    }
+}
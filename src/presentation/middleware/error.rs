@@ -1,9 +1,9 @@
 use axum::{
+    body::Body,
     extract::Request,
-    http::{HeaderValue, StatusCode},
+    http::{header, HeaderValue, StatusCode},
     middleware::Next,
     response::{IntoResponse, Response},
-    Json,
 };
 use serde_json::{json, Value};
 use std::collections::HashMap;
@@ -11,7 +11,45 @@ use thiserror::Error;
 use tracing::{error, warn};
 use uuid::Uuid;
 
+use crate::{
+    domain::value_objects::{StorageQuota, StorageUsage},
+    infrastructure::config::RuntimeMode,
+};
+
+/// Media type of error response bodies (RFC 7807)
+pub const PROBLEM_JSON: &str = "application/problem+json";
+
+/// Prefix of the `type` URI of every problem; the error code follows it
+pub const PROBLEM_TYPE_PREFIX: &str = "urn:problem-type:media-management:";
+
+/// How much of an error's internals [`global_error_handler`] reveals in responses
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ErrorVerbosity {
+    /// Adds the error's debug representation and source chain under `debug`
+    Detailed,
+    /// Describes server-side failures only by their title and error ID, leaving
+    /// messages that may name hosts, paths or queries to the logs
+    #[default]
+    Minimal,
+}
+
+impl ErrorVerbosity {
+    /// Detailed errors in local mode, minimal ones in production
+    #[must_use]
+    pub fn for_mode(mode: RuntimeMode) -> Self {
+        match mode {
+            RuntimeMode::Local => Self::Detailed,
+            RuntimeMode::Production => Self::Minimal,
+        }
+    }
+}
+
 /// Application error types that can be converted to HTTP responses
+///
+/// Every variant maps to one HTTP status and one stable error code, served as
+/// `code` and at the end of the `type` URI of the problem details body (see
+/// [`AppError::status_code`] and [`AppError::error_type`]). Clients match on the
+/// code, so existing codes must never change.
 #[derive(Error, Debug)]
 pub enum AppError {
     #[error("Authentication failed: {message}")]
@@ -38,9 +76,29 @@ pub enum AppError {
     #[error("Request too large: {message}")]
     PayloadTooLarge { message: String },
 
+    /// The request would take the caller past a storage quota
+    #[error("Quota exceeded: {message}")]
+    QuotaExceeded { message: String, usage: StorageUsage, quota: StorageQuota },
+
     #[error("Unsupported media type: {content_type}")]
     UnsupportedMediaType { content_type: String },
 
+    /// Uploaded content whose actual format contradicts its declared type or is not allowed
+    #[error("Unprocessable content: {message}")]
+    UnprocessableContent {
+        message: String,
+        declared_type: Option<String>,
+        detected_type: Option<String>,
+        allowed_types: Vec<String>,
+    },
+
+    /// An `Idempotency-Key` sent again with a different request or body
+    #[error("Idempotency key mismatch: {message}")]
+    IdempotencyKeyMismatch { message: String, original_request: String },
+
+    #[error("Range not satisfiable: content is {size} bytes")]
+    RangeNotSatisfiable { size: u64 },
+
     #[error("Database error: {message}")]
     Database { message: String },
 
@@ -53,11 +111,21 @@ pub enum AppError {
     #[error("Internal server error: {message}")]
     Internal { message: String },
 
+    /// A dependency is down; `retry_after_seconds` is when recovery is next attempted
     #[error("Service temporarily unavailable: {message}")]
-    ServiceUnavailable { message: String },
+    ServiceUnavailable {
+        message: String,
+        last_error: Option<String>,
+        retry_after_seconds: Option<u64>,
+    },
 
+    /// The work did not finish within its time budget
     #[error("Request timeout: {message}")]
     Timeout { message: String },
+
+    /// The endpoint has been retired
+    #[error("Gone: {message}")]
+    Gone { message: String },
 }
 
 impl AppError {
@@ -70,18 +138,25 @@ impl AppError {
             AppError::NotFound { .. } => StatusCode::NOT_FOUND,
             AppError::Conflict { .. } => StatusCode::CONFLICT,
             AppError::RateLimit { .. } => StatusCode::TOO_MANY_REQUESTS,
-            AppError::PayloadTooLarge { .. } => StatusCode::PAYLOAD_TOO_LARGE,
+            AppError::PayloadTooLarge { .. } | AppError::QuotaExceeded { .. } => {
+                StatusCode::PAYLOAD_TOO_LARGE
+            }
             AppError::UnsupportedMediaType { .. } => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            AppError::UnprocessableContent { .. } | AppError::IdempotencyKeyMismatch { .. } => {
+                StatusCode::UNPROCESSABLE_ENTITY
+            }
+            AppError::RangeNotSatisfiable { .. } => StatusCode::RANGE_NOT_SATISFIABLE,
             AppError::Database { .. } | AppError::Storage { .. } | AppError::Internal { .. } => {
                 StatusCode::INTERNAL_SERVER_ERROR
             }
             AppError::ExternalService { .. } => StatusCode::BAD_GATEWAY,
             AppError::ServiceUnavailable { .. } => StatusCode::SERVICE_UNAVAILABLE,
-            AppError::Timeout { .. } => StatusCode::REQUEST_TIMEOUT,
+            AppError::Timeout { .. } => StatusCode::GATEWAY_TIMEOUT,
+            AppError::Gone { .. } => StatusCode::GONE,
         }
     }
 
-    /// Get the error type for logging and metrics
+    /// Get the stable error code used in response bodies, logs and metrics
     pub fn error_type(&self) -> &'static str {
         match self {
             AppError::Authentication { .. } => "authentication",
@@ -92,13 +167,44 @@ impl AppError {
             AppError::RateLimit { .. } => "rate_limit",
             AppError::BadRequest { .. } => "bad_request",
             AppError::PayloadTooLarge { .. } => "payload_too_large",
+            AppError::QuotaExceeded { .. } => "quota_exceeded",
             AppError::UnsupportedMediaType { .. } => "unsupported_media_type",
+            AppError::UnprocessableContent { .. } => "unprocessable_content",
+            AppError::IdempotencyKeyMismatch { .. } => "idempotency_key_mismatch",
+            AppError::RangeNotSatisfiable { .. } => "range_not_satisfiable",
             AppError::Database { .. } => "database",
             AppError::Storage { .. } => "storage",
             AppError::ExternalService { .. } => "external_service",
             AppError::Internal { .. } => "internal",
             AppError::ServiceUnavailable { .. } => "service_unavailable",
             AppError::Timeout { .. } => "timeout",
+            AppError::Gone { .. } => "gone",
+        }
+    }
+
+    /// Short summary of the problem, the same for every error of this type
+    pub fn title(&self) -> &'static str {
+        match self {
+            AppError::Authentication { .. } => "Authentication failed",
+            AppError::Authorization { .. } => "Not allowed",
+            AppError::Validation { .. } => "Validation failed",
+            AppError::NotFound { .. } => "Resource not found",
+            AppError::Conflict { .. } => "Conflict with the current state",
+            AppError::RateLimit { .. } => "Rate limit exceeded",
+            AppError::BadRequest { .. } => "Invalid request",
+            AppError::PayloadTooLarge { .. } => "Request too large",
+            AppError::QuotaExceeded { .. } => "Storage quota exceeded",
+            AppError::UnsupportedMediaType { .. } => "Unsupported media type",
+            AppError::UnprocessableContent { .. } => "Unprocessable content",
+            AppError::IdempotencyKeyMismatch { .. } => "Idempotency key reused",
+            AppError::RangeNotSatisfiable { .. } => "Range not satisfiable",
+            AppError::Database { .. } => "Database error",
+            AppError::Storage { .. } => "Storage error",
+            AppError::ExternalService { .. } => "External service error",
+            AppError::Internal { .. } => "Internal server error",
+            AppError::ServiceUnavailable { .. } => "Service temporarily unavailable",
+            AppError::Timeout { .. } => "Request timed out",
+            AppError::Gone { .. } => "No longer available",
         }
     }
 
@@ -114,22 +220,87 @@ impl AppError {
         )
     }
 
-    /// Create error response with proper structure
+    /// Check if the failed operation may succeed when attempted again
+    ///
+    /// `ServiceUnavailable` is excluded: it already reports when the dependency
+    /// will next be tried.
+    pub fn is_transient(&self) -> bool {
+        matches!(
+            self,
+            AppError::Database { .. }
+                | AppError::Storage { .. }
+                | AppError::ExternalService { .. }
+                | AppError::Timeout { .. }
+        )
+    }
+
+    /// Create the problem details describing this error
+    ///
+    /// `instance` is left unset; [`global_error_handler`] fills it, and the
+    /// request ID, in for responses it passes on.
     pub fn to_error_response(&self, request_id: Option<&str>) -> ErrorResponse {
-        let error_id = Uuid::new_v4().to_string();
+        let code = self.error_type();
 
         ErrorResponse {
-            error: ErrorDetail {
-                id: error_id,
-                error_type: self.error_type().to_string(),
-                message: self.to_string(),
-                details: self.get_details(),
-                request_id: request_id.map(String::from),
-                timestamp: chrono::Utc::now().to_rfc3339(),
-            },
+            problem_type: format!("{PROBLEM_TYPE_PREFIX}{code}"),
+            title: self.title().to_string(),
+            status: self.status_code().as_u16(),
+            detail: self.to_string(),
+            instance: None,
+            code: code.to_string(),
+            error_id: Uuid::new_v4().to_string(),
+            request_id: request_id.map(String::from),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            details: self.get_details(),
+            debug: None,
         }
     }
 
+    /// Check if the message may reveal internals such as hosts, paths or queries
+    fn exposes_internals(&self) -> bool {
+        matches!(
+            self,
+            AppError::Database { .. }
+                | AppError::Storage { .. }
+                | AppError::ExternalService { .. }
+                | AppError::Internal { .. }
+        )
+    }
+
+    /// Shape `problem`, created from this error, for `verbosity`
+    fn with_verbosity(
+        &self,
+        mut problem: ErrorResponse,
+        verbosity: ErrorVerbosity,
+    ) -> ErrorResponse {
+        match verbosity {
+            ErrorVerbosity::Detailed => {
+                let mut chain = vec![self.to_string()];
+                let mut source = std::error::Error::source(self);
+                while let Some(error) = source {
+                    chain.push(error.to_string());
+                    source = error.source();
+                }
+                problem.debug = Some(json!({ "error": format!("{self:?}"), "chain": chain }));
+            }
+            ErrorVerbosity::Minimal => {
+                if self.exposes_internals() {
+                    problem.detail = format!(
+                        "{}; quote error ID {} when reporting it",
+                        problem.title, problem.error_id
+                    );
+                }
+                if let Some(Value::Object(details)) = &mut problem.details {
+                    details.remove("last_error");
+                    if details.is_empty() {
+                        problem.details = None;
+                    }
+                }
+            }
+        }
+        problem
+    }
+
     /// Get additional error details
     fn get_details(&self) -> Option<Value> {
         match self {
@@ -138,72 +309,163 @@ impl AppError {
             AppError::UnsupportedMediaType { content_type } => {
                 Some(json!({ "content_type": content_type }))
             }
+            AppError::UnprocessableContent {
+                declared_type, detected_type, allowed_types, ..
+            } => Some(json!({
+                "declared_type": declared_type,
+                "detected_type": detected_type,
+                "allowed_types": allowed_types,
+            })),
+            AppError::IdempotencyKeyMismatch { original_request, .. } => {
+                Some(json!({ "original_request": original_request }))
+            }
             AppError::ExternalService { service, .. } => Some(json!({ "service": service })),
+            AppError::QuotaExceeded { usage, quota, .. } => {
+                Some(json!({ "usage": usage, "quota": quota }))
+            }
+            AppError::ServiceUnavailable { last_error, retry_after_seconds, .. } => {
+                let mut details = serde_json::Map::new();
+                if let Some(last_error) = last_error {
+                    details.insert("last_error".to_string(), json!(last_error));
+                }
+                if let Some(retry_after_seconds) = retry_after_seconds {
+                    details.insert("retry_after_seconds".to_string(), json!(retry_after_seconds));
+                }
+                (!details.is_empty()).then_some(Value::Object(details))
+            }
             _ => None,
         }
     }
 }
 
-/// Structured error response
-#[derive(serde::Serialize, Debug)]
+/// Problem details body of every error response (RFC 7807)
+///
+/// Served as `application/problem+json`. Besides the standard members it
+/// carries the stable error `code`, an `error_id` matching the logged error,
+/// the request ID and variant-specific `details`.
+#[derive(serde::Serialize, Debug, Clone, utoipa::ToSchema)]
 pub struct ErrorResponse {
-    pub error: ErrorDetail,
-}
-
-#[derive(serde::Serialize, Debug)]
-pub struct ErrorDetail {
-    pub id: String,
+    /// URI of the problem type, `urn:problem-type:media-management:` followed by the code
     #[serde(rename = "type")]
-    pub error_type: String,
-    pub message: String,
+    pub problem_type: String,
+    /// Short summary of the problem type
+    pub title: String,
+    /// HTTP status code
+    pub status: u16,
+    /// Explanation of this occurrence of the problem
+    pub detail: String,
+    /// Path of the request that failed
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub details: Option<Value>,
+    pub instance: Option<String>,
+    /// Stable error code, e.g. `not_found` or `quota_exceeded`
+    pub code: String,
+    /// ID of the error in the service logs
+    pub error_id: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub request_id: Option<String>,
     pub timestamp: String,
+    /// Variant-specific context, e.g. `validation_errors` or `retry_after_seconds`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>)]
+    pub details: Option<Value>,
+    /// The error's debug representation and source `chain`, in local mode only
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>)]
+    pub debug: Option<Value>,
+}
+
+/// An error response as recorded for [`global_error_handler`], in each verbosity
+#[derive(Debug, Clone)]
+struct RecordedProblem {
+    detailed: ErrorResponse,
+    minimal: ErrorResponse,
+}
+
+impl RecordedProblem {
+    fn for_verbosity(&self, verbosity: ErrorVerbosity) -> &ErrorResponse {
+        match verbosity {
+            ErrorVerbosity::Detailed => &self.detailed,
+            ErrorVerbosity::Minimal => &self.minimal,
+        }
+    }
 }
 
 impl IntoResponse for ErrorResponse {
     fn into_response(self) -> Response {
-        Json(self).into_response()
+        let status = StatusCode::from_u16(self.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        match serde_json::to_vec(&self) {
+            Ok(body) => {
+                (status, [(header::CONTENT_TYPE, HeaderValue::from_static(PROBLEM_JSON))], body)
+                    .into_response()
+            }
+            Err(e) => {
+                error!("Failed to serialize problem details: {}", e);
+                status.into_response()
+            }
+        }
     }
 }
 
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
-        let status = self.status_code();
         let error_response = self.to_error_response(None);
 
         // Log the error appropriately
         if self.should_log_as_error() {
             error!(
                 error_type = self.error_type(),
-                error_id = error_response.error.id,
+                error_id = error_response.error_id,
                 "Application error: {}",
                 self
             );
         } else {
             warn!(
                 error_type = self.error_type(),
-                error_id = error_response.error.id,
+                error_id = error_response.error_id,
                 "Application warning: {}",
                 self
             );
         }
 
-        (status, Json(error_response)).into_response()
+        // Kept so the global error handler can add the request ID and path
+        let recorded = RecordedProblem {
+            detailed: self.with_verbosity(error_response.clone(), ErrorVerbosity::Detailed),
+            minimal: self.with_verbosity(error_response.clone(), ErrorVerbosity::Minimal),
+        };
+        let mut response = error_response.into_response();
+        response.extensions_mut().insert(recorded);
+        match self {
+            AppError::ServiceUnavailable { retry_after_seconds: Some(seconds), .. } => {
+                response.headers_mut().insert(header::RETRY_AFTER, HeaderValue::from(seconds));
+            }
+            AppError::RangeNotSatisfiable { size } => {
+                if let Ok(value) = HeaderValue::from_str(&format!("bytes */{size}")) {
+                    response.headers_mut().insert(header::CONTENT_RANGE, value);
+                }
+            }
+            _ => {}
+        }
+        response
     }
 }
 
-/// Global error handling middleware
-pub async fn global_error_handler(request: Request, next: Next) -> Response {
-    let request_id = extract_request_id(&request);
-
-    // Simply run the next handler and enhance the response if needed
-    let response = next.run(request).await;
-
-    // Check if the response is an error status and enhance it
-    enhance_error_response(response, request_id.as_deref())
+/// Global error handling middleware, serving problem details with `verbosity`
+pub fn global_error_handler(
+    verbosity: ErrorVerbosity,
+) -> impl Fn(Request, Next) -> std::pin::Pin<Box<dyn std::future::Future<Output = Response> + Send>>
+       + Clone {
+    move |request: Request, next: Next| {
+        Box::pin(async move {
+            let request_id = extract_request_id(&request);
+            let instance = request.uri().path().to_string();
+
+            // Simply run the next handler and enhance the response if needed
+            let response = next.run(request).await;
+
+            // Check if the response is an error status and enhance it
+            enhance_error_response(response, request_id.as_deref(), &instance, verbosity)
+        })
+    }
 }
 
 /// Extract request ID from request headers
@@ -212,7 +474,12 @@ fn extract_request_id(request: &Request) -> Option<String> {
 }
 
 /// Enhance error responses with consistent structure
-fn enhance_error_response(response: Response, request_id: Option<&str>) -> Response {
+fn enhance_error_response(
+    response: Response,
+    request_id: Option<&str>,
+    instance: &str,
+    verbosity: ErrorVerbosity,
+) -> Response {
     let status = response.status();
 
     // Only enhance error responses (4xx, 5xx)
@@ -220,8 +487,16 @@ fn enhance_error_response(response: Response, request_id: Option<&str>) -> Respo
         return response;
     }
 
-    // If response already has proper error structure, just add request ID
-    let mut enhanced_response = response;
+    // Problem details from an `AppError` are rewritten with the request they describe
+    let recorded = response.extensions().get::<RecordedProblem>();
+    let mut enhanced_response = match recorded.map(|r| r.for_verbosity(verbosity).clone()) {
+        Some(mut problem) => {
+            problem.instance = Some(instance.to_string());
+            problem.request_id = request_id.map(String::from).or(problem.request_id);
+            with_problem(response, &problem)
+        }
+        None => response,
+    };
 
     if let Some(req_id) = request_id {
         if let Ok(header_value) = req_id.parse::<HeaderValue>() {
@@ -233,6 +508,18 @@ fn enhance_error_response(response: Response, request_id: Option<&str>) -> Respo
     enhanced_response
 }
 
+/// Replace the body of `response` with `problem`, keeping its status and headers
+fn with_problem(response: Response, problem: &ErrorResponse) -> Response {
+    let Ok(body) = serde_json::to_vec(problem) else {
+        return response;
+    };
+    let (mut parts, _) = response.into_parts();
+    // The original body may already have been compressed or sized
+    parts.headers.remove(header::CONTENT_ENCODING);
+    parts.headers.remove(header::CONTENT_LENGTH);
+    Response::from_parts(parts, Body::from(body))
+}
+
 /// Convert common errors to `AppError`
 impl From<sqlx::Error> for AppError {
     fn from(err: sqlx::Error) -> Self {
@@ -252,6 +539,12 @@ impl From<serde_json::Error> for AppError {
     }
 }
 
+impl From<crate::infrastructure::persistence::pagination::CursorError> for AppError {
+    fn from(err: crate::infrastructure::persistence::pagination::CursorError) -> Self {
+        AppError::BadRequest { message: err.to_string() }
+    }
+}
+
 impl From<crate::infrastructure::storage::presigned_urls::PresignedUrlError> for AppError {
     fn from(err: crate::infrastructure::storage::presigned_urls::PresignedUrlError) -> Self {
         use crate::infrastructure::storage::presigned_urls::PresignedUrlError;
@@ -350,17 +643,178 @@ mod tests {
         assert!(!AppError::BadRequest { message: "test".to_string() }.should_log_as_error());
     }
 
+    /// One instance of every variant with its expected status and code
+    fn catalog() -> Vec<(AppError, StatusCode, &'static str)> {
+        let message = || "test".to_string();
+        vec![
+            (
+                AppError::Authentication { message: message() },
+                StatusCode::UNAUTHORIZED,
+                "authentication",
+            ),
+            (
+                AppError::Authorization { message: message() },
+                StatusCode::FORBIDDEN,
+                "authorization",
+            ),
+            (
+                AppError::Validation { errors: HashMap::new() },
+                StatusCode::BAD_REQUEST,
+                "validation",
+            ),
+            (AppError::NotFound { resource: message() }, StatusCode::NOT_FOUND, "not_found"),
+            (AppError::Conflict { message: message() }, StatusCode::CONFLICT, "conflict"),
+            (
+                AppError::RateLimit { message: message() },
+                StatusCode::TOO_MANY_REQUESTS,
+                "rate_limit",
+            ),
+            (AppError::BadRequest { message: message() }, StatusCode::BAD_REQUEST, "bad_request"),
+            (
+                AppError::PayloadTooLarge { message: message() },
+                StatusCode::PAYLOAD_TOO_LARGE,
+                "payload_too_large",
+            ),
+            (
+                AppError::QuotaExceeded {
+                    message: message(),
+                    usage: StorageUsage::default(),
+                    quota: StorageQuota::unlimited(),
+                },
+                StatusCode::PAYLOAD_TOO_LARGE,
+                "quota_exceeded",
+            ),
+            (
+                AppError::UnsupportedMediaType { content_type: message() },
+                StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                "unsupported_media_type",
+            ),
+            (
+                AppError::UnprocessableContent {
+                    message: message(),
+                    declared_type: None,
+                    detected_type: None,
+                    allowed_types: Vec::new(),
+                },
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "unprocessable_content",
+            ),
+            (
+                AppError::IdempotencyKeyMismatch {
+                    message: message(),
+                    original_request: message(),
+                },
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "idempotency_key_mismatch",
+            ),
+            (
+                AppError::RangeNotSatisfiable { size: 10 },
+                StatusCode::RANGE_NOT_SATISFIABLE,
+                "range_not_satisfiable",
+            ),
+            (
+                AppError::Database { message: message() },
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "database",
+            ),
+            (
+                AppError::Storage { message: message() },
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "storage",
+            ),
+            (
+                AppError::ExternalService { service: message(), message: message() },
+                StatusCode::BAD_GATEWAY,
+                "external_service",
+            ),
+            (
+                AppError::Internal { message: message() },
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "internal",
+            ),
+            (
+                AppError::ServiceUnavailable {
+                    message: message(),
+                    last_error: None,
+                    retry_after_seconds: None,
+                },
+                StatusCode::SERVICE_UNAVAILABLE,
+                "service_unavailable",
+            ),
+            (AppError::Timeout { message: message() }, StatusCode::GATEWAY_TIMEOUT, "timeout"),
+            (AppError::Gone { message: message() }, StatusCode::GONE, "gone"),
+        ]
+    }
+
+    /// Fails to compile when a variant is added, so it must also be added to `catalog`
+    fn catalog_index(error: &AppError) -> usize {
+        match error {
+            AppError::Authentication { .. } => 0,
+            AppError::Authorization { .. } => 1,
+            AppError::Validation { .. } => 2,
+            AppError::NotFound { .. } => 3,
+            AppError::Conflict { .. } => 4,
+            AppError::RateLimit { .. } => 5,
+            AppError::BadRequest { .. } => 6,
+            AppError::PayloadTooLarge { .. } => 7,
+            AppError::QuotaExceeded { .. } => 8,
+            AppError::UnsupportedMediaType { .. } => 9,
+            AppError::UnprocessableContent { .. } => 10,
+            AppError::IdempotencyKeyMismatch { .. } => 11,
+            AppError::RangeNotSatisfiable { .. } => 12,
+            AppError::Database { .. } => 13,
+            AppError::Storage { .. } => 14,
+            AppError::ExternalService { .. } => 15,
+            AppError::Internal { .. } => 16,
+            AppError::ServiceUnavailable { .. } => 17,
+            AppError::Timeout { .. } => 18,
+            AppError::Gone { .. } => 19,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_every_variant_maps_to_status_and_code() {
+        let catalog = catalog();
+        let mut codes = std::collections::HashSet::new();
+
+        for (index, (error, status, code)) in catalog.into_iter().enumerate() {
+            assert_eq!(catalog_index(&error), index, "catalog out of order at {code}");
+            assert!(codes.insert(code), "duplicate error code {code}");
+            assert_eq!(error.status_code(), status, "status of {code}");
+            assert_eq!(error.error_type(), code);
+
+            let message = error.to_string();
+            let title = error.title();
+            let response = error.into_response();
+            assert_eq!(response.status(), status, "response status of {code}");
+            assert_eq!(response.headers()[header::CONTENT_TYPE], PROBLEM_JSON);
+
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            let body: Value = serde_json::from_slice(&body).unwrap();
+            assert_eq!(body["type"], format!("{PROBLEM_TYPE_PREFIX}{code}"));
+            assert_eq!(body["code"], code);
+            assert_eq!(body["title"], title);
+            assert_eq!(body["status"], status.as_u16());
+            assert_eq!(body["detail"], message);
+            assert!(body["error_id"].is_string());
+            assert!(body["timestamp"].is_string());
+        }
+
+        assert_eq!(codes.len(), catalog_index(&AppError::Gone { message: String::new() }) + 1);
+    }
+
     #[test]
     fn test_error_response_structure() {
         let error = AppError::NotFound { resource: "user".to_string() };
         let response = error.to_error_response(Some("test-request-id"));
 
-        assert_eq!(response.error.error_type, "not_found");
-        assert!(response.error.message.contains("not found"));
-        assert_eq!(response.error.request_id, Some("test-request-id".to_string()));
-        assert!(response.error.details.is_some());
+        assert_eq!(response.code, "not_found");
+        assert_eq!(response.status, 404);
+        assert!(response.detail.contains("not found"));
+        assert_eq!(response.request_id, Some("test-request-id".to_string()));
+        assert!(response.details.is_some());
 
-        if let Some(details) = response.error.details {
+        if let Some(details) = response.details {
             assert_eq!(details["resource"], "user");
         }
     }
@@ -374,8 +828,8 @@ mod tests {
         let error = AppError::Validation { errors };
         let response = error.to_error_response(None);
 
-        assert!(response.error.details.is_some());
-        if let Some(details) = response.error.details {
+        assert!(response.details.is_some());
+        if let Some(details) = response.details {
             assert!(details["validation_errors"]["email"]
                 .as_str()
                 .unwrap()
@@ -387,11 +841,28 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_service_unavailable_details_and_retry_after() {
+        let error = AppError::ServiceUnavailable {
+            message: "Database unavailable".to_string(),
+            last_error: Some("connection refused".to_string()),
+            retry_after_seconds: Some(12),
+        };
+
+        let details = error.to_error_response(None).details.unwrap();
+        assert_eq!(details["last_error"], "connection refused");
+        assert_eq!(details["retry_after_seconds"], 12);
+
+        let response = error.into_response();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(response.headers()[header::RETRY_AFTER], "12");
+    }
+
     #[tokio::test]
     async fn test_global_error_handler_success() {
         let app = Router::new()
             .route("/success", get(test_success_handler))
-            .layer(axum::middleware::from_fn(global_error_handler));
+            .layer(axum::middleware::from_fn(global_error_handler(ErrorVerbosity::Detailed)));
 
         let request = Request::builder()
             .uri("/success")
@@ -408,7 +879,7 @@ mod tests {
     async fn test_global_error_handler_app_error() {
         let app = Router::new()
             .route("/error", get(test_app_error_handler))
-            .layer(axum::middleware::from_fn(global_error_handler));
+            .layer(axum::middleware::from_fn(global_error_handler(ErrorVerbosity::Detailed)));
 
         let request = Request::builder()
             .uri("/error")
@@ -420,6 +891,61 @@ mod tests {
 
         assert_eq!(response.status(), StatusCode::NOT_FOUND);
         assert!(response.headers().get("x-request-id").is_some());
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["instance"], "/error");
+        assert_eq!(body["request_id"], "test-id-456");
+        assert_eq!(body["code"], "not_found");
+    }
+
+    #[tokio::test]
+    async fn test_minimal_errors_do_not_leak_internals() {
+        async fn failing_handler() -> Result<Json<Value>, AppError> {
+            Err(AppError::Storage {
+                message: "Failed to write /var/lib/media/ab/cd: password=hunter2".to_string(),
+            })
+        }
+        async fn unavailable_handler() -> Result<Json<Value>, AppError> {
+            Err(AppError::ServiceUnavailable {
+                message: "Database unavailable".to_string(),
+                last_error: Some("connection to 10.0.0.5:5432 refused".to_string()),
+                retry_after_seconds: Some(30),
+            })
+        }
+
+        for verbosity in [ErrorVerbosity::Minimal, ErrorVerbosity::Detailed] {
+            let app = Router::new()
+                .route("/storage", get(failing_handler))
+                .route("/unavailable", get(unavailable_handler))
+                .layer(axum::middleware::from_fn(global_error_handler(verbosity)));
+
+            for uri in ["/storage", "/unavailable"] {
+                let request =
+                    Request::builder().uri(uri).header("x-request-id", "req-1").body(Body::empty());
+                let response = app.clone().oneshot(request.unwrap()).await.unwrap();
+                let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                let text = String::from_utf8(body.to_vec()).unwrap();
+                let body: Value = serde_json::from_str(&text).unwrap();
+
+                assert_eq!(body["request_id"], "req-1");
+                assert!(body["code"].is_string());
+                let leaked = ["/var/lib", "hunter2", "10.0.0.5"].iter().any(|s| text.contains(s));
+                if verbosity == ErrorVerbosity::Minimal {
+                    assert!(!leaked, "{uri} leaked internals: {text}");
+                    assert!(body.get("debug").is_none());
+                } else {
+                    assert!(leaked);
+                    assert!(body["debug"]["chain"].is_array());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_error_verbosity_follows_runtime_mode() {
+        assert_eq!(ErrorVerbosity::for_mode(RuntimeMode::Local), ErrorVerbosity::Detailed);
+        assert_eq!(ErrorVerbosity::for_mode(RuntimeMode::Production), ErrorVerbosity::Minimal);
     }
 
     #[test]
@@ -0,0 +1,304 @@
+//! `Idempotency-Key` support on endpoints that create media or associations
+//!
+//! Mobile clients retry requests that time out, even when the first attempt
+//! went through. A client sending the same `Idempotency-Key` header with every
+//! attempt gets the first attempt's response replayed, marked with
+//! `Idempotent-Replayed: true`, instead of creating a second media record.
+//! Keys are scoped to the authenticated caller and hold the response of one
+//! endpoint and body for `MEDIA_SERVICE_SERVER_IDEMPOTENCY_KEY_TTL_SECONDS`;
+//! requests without a token, which no caller can be told apart by, ignore the
+//! header. Only successful responses are stored, so a retry of a failed request
+//! runs it again. Only routes this middleware is layered on honor the header.
+
+use axum::{
+    body::{Body, Bytes},
+    extract::{FromRequest, OriginalUri, Request},
+    http::{header, HeaderName, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    application::use_cases::{IdempotencyKeys, IdempotentRequest},
+    domain::entities::{IdempotencyRecord, StoredResponse},
+    presentation::middleware::{auth::UserContext, error::AppError},
+};
+
+pub const IDEMPOTENCY_KEY_HEADER: HeaderName = HeaderName::from_static("idempotency-key");
+pub const IDEMPOTENT_REPLAYED_HEADER: HeaderName = HeaderName::from_static("idempotent-replayed");
+
+const MAX_KEY_LEN: usize = 255;
+
+/// Largest response body stored for replay; responses here are small JSON documents
+const MAX_STORED_BODY_BYTES: usize = 1024 * 1024;
+
+/// Middleware storing responses by `Idempotency-Key` in `keys` and replaying them to retries
+///
+/// Bodies of requests with a key are read up front, within the route's body
+/// limit, to be compared with the first request's.
+pub fn replay_idempotent_requests(
+    keys: IdempotencyKeys,
+) -> impl Fn(Request, Next) -> std::pin::Pin<Box<dyn std::future::Future<Output = Response> + Send>>
+       + Clone {
+    move |request: Request, next: Next| {
+        let keys = keys.clone();
+        Box::pin(async move {
+            let Some(key) = request.headers().get(&IDEMPOTENCY_KEY_HEADER) else {
+                return next.run(request).await;
+            };
+            let Some(owner) = request.extensions().get::<UserContext>().map(UserContext::owner_id)
+            else {
+                return next.run(request).await;
+            };
+            let Some(key) = key.to_str().ok().filter(|key| is_valid_key(key)) else {
+                return AppError::BadRequest {
+                    message: format!(
+                        "Idempotency-Key must be 1 to {MAX_KEY_LEN} printable ASCII characters"
+                    ),
+                }
+                .into_response();
+            };
+            let key = key.to_string();
+
+            let path = request
+                .extensions()
+                .get::<OriginalUri>()
+                .map_or_else(|| request.uri().path(), |uri| uri.path());
+            let endpoint = format!("{} {}", request.method(), path);
+
+            let (parts, body) = request.into_parts();
+            let body = match read_body(Request::from_parts(parts.clone(), body)).await {
+                Ok(body) => body,
+                Err(e) => return e.into_response(),
+            };
+            let body_hash = hex::encode(Sha256::digest(&body));
+            let request = Request::from_parts(parts, Body::from(body));
+
+            match keys.begin(&key, owner, &endpoint, &body_hash).await {
+                Ok(IdempotentRequest::First(record)) => {
+                    let response = next.run(request).await;
+                    store_response(&keys, record, response).await
+                }
+                Ok(IdempotentRequest::Replay(stored)) => replay(stored),
+                Err(e) => e.into_response(),
+            }
+        })
+    }
+}
+
+fn is_valid_key(key: &str) -> bool {
+    !key.is_empty() && key.len() <= MAX_KEY_LEN && key.bytes().all(|b| b.is_ascii_graphic())
+}
+
+/// Read the body of `request`, within the body limit of its route
+async fn read_body(request: Request) -> Result<Bytes, AppError> {
+    Bytes::from_request(request, &()).await.map_err(|rejection| {
+        if rejection.status() == StatusCode::PAYLOAD_TOO_LARGE {
+            AppError::PayloadTooLarge { message: rejection.body_text() }
+        } else {
+            AppError::BadRequest { message: rejection.body_text() }
+        }
+    })
+}
+
+/// Store a successful response for replay, or free the key of a failed request
+async fn store_response(
+    keys: &IdempotencyKeys,
+    record: IdempotencyRecord,
+    response: Response,
+) -> Response {
+    if !response.status().is_success() {
+        if let Err(e) = keys.release(&record).await {
+            tracing::warn!("Idempotency key stays reserved until its lease ends: {}", e);
+        }
+        return response;
+    }
+
+    let (parts, body) = response.into_parts();
+    let body = match axum::body::to_bytes(body, MAX_STORED_BODY_BYTES).await {
+        Ok(body) => body,
+        Err(e) => {
+            let _ = keys.release(&record).await;
+            return AppError::Internal { message: format!("Failed to read response body: {e}") }
+                .into_response();
+        }
+    };
+
+    let stored = StoredResponse {
+        status: parts.status.as_u16(),
+        content_type: parts
+            .headers
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string),
+        body: body.to_vec(),
+    };
+    if let Err(e) = keys.complete(record.clone(), stored).await {
+        // Retries will run the request again, as they would without a key
+        tracing::warn!("Failed to store response for idempotent retries: {}", e);
+        let _ = keys.release(&record).await;
+    }
+
+    Response::from_parts(parts, Body::from(body))
+}
+
+/// Rebuild a stored response, marked as replayed
+fn replay(stored: StoredResponse) -> Response {
+    let mut response = Response::new(Body::from(stored.body));
+    *response.status_mut() = StatusCode::from_u16(stored.status).unwrap_or(StatusCode::OK);
+    let headers = response.headers_mut();
+    if let Some(content_type) =
+        stored.content_type.and_then(|value| HeaderValue::from_str(&value).ok())
+    {
+        headers.insert(header::CONTENT_TYPE, content_type);
+    }
+    headers.insert(IDEMPOTENT_REPLAYED_HEADER, HeaderValue::from_static("true"));
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::persistence::InMemoryIdempotencyRepository;
+    use axum::{
+        extract::DefaultBodyLimit, http::Method, middleware::from_fn, routing::post, Router,
+    };
+    use std::sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    };
+    use std::time::Duration;
+    use tower::ServiceExt;
+
+    fn app(created: Arc<AtomicU32>) -> Router {
+        let keys = IdempotencyKeys::new(
+            Arc::new(InMemoryIdempotencyRepository::new()),
+            Duration::from_hours(24),
+        );
+        Router::new()
+            .route(
+                "/media",
+                post(move || {
+                    let id = created.fetch_add(1, Ordering::SeqCst) + 1;
+                    async move {
+                        (StatusCode::CREATED, axum::Json(serde_json::json!({ "media_id": id })))
+                    }
+                }),
+            )
+            .route("/media/fail", post(|| async { StatusCode::SERVICE_UNAVAILABLE }))
+            .layer(from_fn(replay_idempotent_requests(keys)))
+            .layer(DefaultBodyLimit::max(1024))
+    }
+
+    fn user() -> UserContext {
+        crate::presentation::middleware::Claims::new_access_token(
+            "auth-service".to_string(),
+            vec!["media-management-service".to_string()],
+            "user-1".to_string(),
+            "web-client".to_string(),
+            vec![],
+            1,
+        )
+        .into()
+    }
+
+    /// Send `body` as an authenticated user, with `key` if given
+    async fn send(app: &Router, uri: &str, key: Option<&str>, body: impl Into<Body>) -> Response {
+        let mut request = Request::builder().method(Method::POST).uri(uri).extension(user());
+        if let Some(key) = key {
+            request = request.header(IDEMPOTENCY_KEY_HEADER, key);
+        }
+        app.clone().oneshot(request.body(body.into()).unwrap()).await.unwrap()
+    }
+
+    async fn call(app: &Router, uri: &str, key: Option<&str>) -> Response {
+        send(app, uri, key, "").await
+    }
+
+    async fn body(response: Response) -> String {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        String::from_utf8(bytes.to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_retries_with_the_same_key_replay_the_first_response() {
+        let created = Arc::new(AtomicU32::new(0));
+        let app = app(created.clone());
+
+        let first = call(&app, "/media", Some("retry-1")).await;
+        assert_eq!(first.status(), StatusCode::CREATED);
+        assert!(first.headers().get(IDEMPOTENT_REPLAYED_HEADER).is_none());
+        assert_eq!(body(first).await, r#"{"media_id":1}"#);
+
+        let retry = call(&app, "/media", Some("retry-1")).await;
+        assert_eq!(retry.status(), StatusCode::CREATED);
+        assert_eq!(retry.headers()[IDEMPOTENT_REPLAYED_HEADER], "true");
+        assert_eq!(retry.headers()[header::CONTENT_TYPE], "application/json");
+        assert_eq!(body(retry).await, r#"{"media_id":1}"#);
+        assert_eq!(created.load(Ordering::SeqCst), 1);
+
+        // Without a key, or with another one, every request runs
+        assert_eq!(body(call(&app, "/media", None).await).await, r#"{"media_id":2}"#);
+        assert_eq!(body(call(&app, "/media", Some("retry-2")).await).await, r#"{"media_id":3}"#);
+
+        let other_endpoint = call(&app, "/media/fail", Some("retry-1")).await;
+        assert_eq!(other_endpoint.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    #[tokio::test]
+    async fn test_retries_must_send_the_same_body() {
+        let created = Arc::new(AtomicU32::new(0));
+        let app = app(created.clone());
+
+        let first = send(&app, "/media", Some("retry-1"), r#"{"recipe": 1}"#).await;
+        assert_eq!(first.status(), StatusCode::CREATED);
+        let retry = send(&app, "/media", Some("retry-1"), r#"{"recipe": 1}"#).await;
+        assert_eq!(retry.headers()[IDEMPOTENT_REPLAYED_HEADER], "true");
+
+        let other_body = send(&app, "/media", Some("retry-1"), r#"{"recipe": 2}"#).await;
+        assert_eq!(other_body.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let problem: serde_json::Value = serde_json::from_str(&body(other_body).await).unwrap();
+        assert_eq!(problem["code"], "idempotency_key_mismatch");
+        assert_eq!(problem["details"]["original_request"], "POST /media");
+        assert_eq!(created.load(Ordering::SeqCst), 1);
+
+        let too_large = send(&app, "/media", Some("retry-2"), "x".repeat(1025)).await;
+        assert_eq!(too_large.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[tokio::test]
+    async fn test_requests_without_a_token_are_not_replayed() {
+        let created = Arc::new(AtomicU32::new(0));
+        let app = app(created.clone());
+
+        for _ in 0..2 {
+            let request = Request::builder()
+                .method(Method::POST)
+                .uri("/media")
+                .header(IDEMPOTENCY_KEY_HEADER, "retry-1")
+                .body(Body::empty())
+                .unwrap();
+            let response = app.clone().oneshot(request).await.unwrap();
+            assert_eq!(response.status(), StatusCode::CREATED);
+            assert!(response.headers().get(IDEMPOTENT_REPLAYED_HEADER).is_none());
+        }
+        assert_eq!(created.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_failed_and_malformed_requests_are_not_stored() {
+        let app = app(Arc::new(AtomicU32::new(0)));
+
+        for _ in 0..2 {
+            let failed = call(&app, "/media/fail", Some("retry-1")).await;
+            assert_eq!(failed.status(), StatusCode::SERVICE_UNAVAILABLE);
+            assert!(failed.headers().get(IDEMPOTENT_REPLAYED_HEADER).is_none());
+        }
+
+        let malformed = call(&app, "/media", Some("no spaces")).await;
+        assert_eq!(malformed.status(), StatusCode::BAD_REQUEST);
+        let too_long = call(&app, "/media", Some(&"k".repeat(MAX_KEY_LEN + 1))).await;
+        assert_eq!(too_long.status(), StatusCode::BAD_REQUEST);
+    }
+}
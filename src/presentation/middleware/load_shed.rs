@@ -0,0 +1,82 @@
+//! Concurrency limiting and load shedding
+//!
+//! Each class of routes shares one pool of permits. A request arriving while
+//! every permit is taken is not queued but answered at once with
+//! `503 Service Unavailable` and `Retry-After`, so a saturated instance fails
+//! fast and the load balancer can retry elsewhere. Turned away requests are
+//! counted in `requests_shed_total`, labelled by route `class`.
+
+use std::{sync::Arc, time::Duration};
+
+use axum::{error_handling::HandleErrorLayer, response::IntoResponse, BoxError, Router};
+use tokio::sync::Semaphore;
+use tower::{limit::GlobalConcurrencyLimitLayer, load_shed::LoadShedLayer, ServiceBuilder};
+
+use super::AppError;
+
+/// Turn away requests to `routes` while every permit of `permits` is taken
+///
+/// The permits are shared by every route, where `ConcurrencyLimitLayer`
+/// would give each route a pool of its own.
+pub fn shed_load<S: Clone + Send + Sync + 'static>(
+    routes: Router<S>,
+    permits: &Arc<Semaphore>,
+    class: &'static str,
+    retry_after: Duration,
+) -> Router<S> {
+    routes.layer(
+        ServiceBuilder::new()
+            .layer(HandleErrorLayer::new(move |_: BoxError| async move {
+                metrics::counter!("requests_shed_total", "class" => class).increment(1);
+                AppError::ServiceUnavailable {
+                    message: "The service is at capacity, please retry shortly".to_string(),
+                    last_error: None,
+                    retry_after_seconds: Some(retry_after.as_secs().max(1)),
+                }
+                .into_response()
+            }))
+            .layer(LoadShedLayer::new())
+            .layer(GlobalConcurrencyLimitLayer::with_semaphore(permits.clone())),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        body::Body,
+        http::{header, Request, StatusCode},
+        routing::get,
+    };
+    use tokio::sync::Notify;
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn test_requests_beyond_the_limit_are_shed() {
+        let (started, release) = (Arc::new(Notify::new()), Arc::new(Notify::new()));
+        let (on_start, on_release) = (started.clone(), release.clone());
+        let routes = Router::new()
+            .route(
+                "/slow",
+                get(move || async move {
+                    on_start.notify_one();
+                    on_release.notified().await;
+                }),
+            )
+            .route("/fast", get(|| async { "ok" }));
+        let app = shed_load(routes, &Arc::new(Semaphore::new(1)), "read", Duration::from_secs(2));
+        let get = |uri: &str| app.clone().oneshot(Request::get(uri).body(Body::empty()).unwrap());
+
+        let slow = tokio::spawn(get("/slow"));
+        started.notified().await;
+
+        // The permit is shared, so another route is turned away too
+        let response = get("/fast").await.unwrap();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(response.headers()[header::RETRY_AFTER], "2");
+
+        release.notify_one();
+        assert_eq!(slow.await.unwrap().unwrap().status(), StatusCode::OK);
+        assert_eq!(get("/fast").await.unwrap().status(), StatusCode::OK);
+    }
+}
@@ -1,6 +1,6 @@
 use axum::{
-    body::{to_bytes, Body, Bytes},
-    extract::Request,
+    body::{to_bytes, Body, Bytes, HttpBody},
+    extract::{MatchedPath, Request},
     http::{HeaderMap, Method, StatusCode, Uri, Version},
     middleware::Next,
     response::Response,
@@ -12,6 +12,12 @@ use std::{
 };
 use tracing::{info, warn};
 
+use super::{auth::UserContext, metrics::UNMATCHED_ROUTE};
+use crate::infrastructure::config::RequestLoggingConfig;
+
+/// What an excluded header's value is logged as
+const REDACTED: &str = "[REDACTED]";
+
 /// Logging configuration for request/response middleware
 #[derive(Debug, Clone)]
 #[allow(clippy::struct_excessive_bools)]
@@ -26,7 +32,7 @@ pub struct LoggingConfig {
     pub log_request_headers: bool,
     /// Log response headers
     pub log_response_headers: bool,
-    /// Headers to exclude from logging (for sensitive data)
+    /// Headers whose values are redacted from logs (for sensitive data)
     pub excluded_headers: Vec<String>,
     /// Log performance timing
     pub log_timing: bool,
@@ -60,6 +66,8 @@ impl Default for LoggingConfig {
 pub struct RequestInfo {
     pub method: Method,
     pub uri: Uri,
+    /// Route template, e.g. `/api/v1/media-management/media/{id}`
+    pub route: String,
     pub version: Version,
     pub headers: HeaderMap,
     pub body: Option<Bytes>,
@@ -75,9 +83,26 @@ pub struct ResponseInfo {
     pub headers: HeaderMap,
     pub body: Option<Bytes>,
     pub duration: Duration,
+    /// Authenticated user, or `OAuth2` client for client credentials
+    pub user_id: Option<String>,
 }
 
 impl LoggingConfig {
+    /// Create the logging config set by `middleware.request_logging`
+    pub fn from_config(config: &RequestLoggingConfig) -> Self {
+        Self {
+            log_request_body: config.log_request_body,
+            log_response_body: config.log_response_body,
+            max_body_size: usize::try_from(config.max_body_size_kb.saturating_mul(1024))
+                .unwrap_or(usize::MAX),
+            log_request_headers: config.log_request_headers,
+            log_response_headers: config.log_response_headers,
+            excluded_headers: config.excluded_headers.clone(),
+            log_timing: config.log_timing,
+            slow_request_threshold_ms: config.slow_request_threshold_ms,
+        }
+    }
+
     /// Create a development-friendly logging config
     pub fn development() -> Self {
         Self {
@@ -126,18 +151,18 @@ impl LoggingConfig {
             .any(|excluded| header_name.to_lowercase() == excluded.to_lowercase())
     }
 
-    /// Filter headers for logging
+    /// Filter headers for logging, redacting the values of excluded headers
     fn filter_headers(&self, headers: &HeaderMap) -> Value {
         let mut filtered = serde_json::Map::new();
 
         for (name, value) in headers {
             let name_str = name.as_str();
-            if self.should_log_header(name_str) {
-                if let Ok(value_str) = value.to_str() {
-                    filtered.insert(name_str.to_string(), json!(value_str));
-                } else {
-                    filtered.insert(name_str.to_string(), json!("<binary>"));
-                }
+            if !self.should_log_header(name_str) {
+                filtered.insert(name_str.to_string(), json!(REDACTED));
+            } else if let Ok(value_str) = value.to_str() {
+                filtered.insert(name_str.to_string(), json!(value_str));
+            } else {
+                filtered.insert(name_str.to_string(), json!("<binary>"));
             }
         }
 
@@ -202,6 +227,30 @@ impl LoggingConfig {
     }
 }
 
+/// Buffer `body` for logging when its length is known and within the body limit
+///
+/// Bodies of unknown length, or too large or binary to log, pass through
+/// untouched, so large uploads and downloads still stream.
+async fn capture_body(
+    config: &LoggingConfig,
+    headers: &HeaderMap,
+    body: Body,
+) -> (Body, Option<Bytes>) {
+    let loggable = body
+        .size_hint()
+        .exact()
+        .and_then(|size| usize::try_from(size).ok())
+        .is_some_and(|size| config.should_log_body(headers, size));
+    if !loggable {
+        return (body, None);
+    }
+
+    match to_bytes(body, config.max_body_size).await {
+        Ok(bytes) => (Body::from(bytes.clone()), Some(bytes)),
+        Err(_) => (Body::empty(), None),
+    }
+}
+
 /// Extract client IP from request
 fn extract_client_ip(headers: &HeaderMap) -> Option<IpAddr> {
     // Try X-Forwarded-For first
@@ -240,6 +289,10 @@ pub fn logging_middleware(
             // Extract request information
             let method = request.method().clone();
             let uri = request.uri().clone();
+            let route = request
+                .extensions()
+                .get::<MatchedPath>()
+                .map_or_else(|| UNMATCHED_ROUTE.to_string(), |path| path.as_str().to_string());
             let version = request.version();
             let headers = request.headers().clone();
             let client_ip = extract_client_ip(&headers);
@@ -251,27 +304,23 @@ pub fn logging_middleware(
             // Extract request body if needed
             let (request, request_body) = if config.log_request_body {
                 let (parts, body) = request.into_parts();
-                if let Ok(bytes) = to_bytes(body, config.max_body_size).await {
-                    let new_request = Request::from_parts(parts, Body::from(bytes.clone()));
-                    (new_request, Some(bytes))
-                } else {
-                    let new_request = Request::from_parts(parts, Body::empty());
-                    (new_request, None)
-                }
+                let (body, captured) = capture_body(&config, &parts.headers, body).await;
+                (Request::from_parts(parts, body), captured)
             } else {
                 (request, None)
             };
 
             // Log request
             let request_info = RequestInfo {
-                method: method.clone(),
-                uri: uri.clone(),
+                method,
+                uri,
+                route,
                 version,
-                headers: headers.clone(),
+                headers,
                 body: request_body,
                 client_ip,
                 user_agent,
-                request_id: request_id.clone(),
+                request_id,
                 start_time,
             };
 
@@ -284,24 +333,29 @@ pub fn logging_middleware(
             let status = response.status();
             let response_headers = response.headers().clone();
             let duration = start_time.elapsed();
+            // Authentication runs inside this layer, so it hands the user back on the response
+            let user_id = response
+                .extensions()
+                .get::<UserContext>()
+                .map(|user| user.user_id.clone().unwrap_or_else(|| user.subject.clone()));
 
             // Extract response body if needed
             let (response, response_body) = if config.log_response_body {
                 let (parts, body) = response.into_parts();
-                if let Ok(bytes) = to_bytes(body, config.max_body_size).await {
-                    let new_response = Response::from_parts(parts, Body::from(bytes.clone()));
-                    (new_response, Some(bytes))
-                } else {
-                    let new_response = Response::from_parts(parts, Body::empty());
-                    (new_response, None)
-                }
+                let (body, captured) = capture_body(&config, &parts.headers, body).await;
+                (Response::from_parts(parts, body), captured)
             } else {
                 (response, None)
             };
 
             // Log response
-            let response_info =
-                ResponseInfo { status, headers: response_headers, body: response_body, duration };
+            let response_info = ResponseInfo {
+                status,
+                headers: response_headers,
+                body: response_body,
+                duration,
+                user_id,
+            };
 
             log_response(&config, &request_info, &response_info);
 
@@ -315,6 +369,7 @@ fn log_request(config: &LoggingConfig, info: &RequestInfo) {
     let mut log_data = json!({
         "type": "request",
         "method": info.method.as_str(),
+        "route": info.route,
         "uri": info.uri.to_string(),
         "version": format!("{:?}", info.version),
         "timestamp": chrono::Utc::now().to_rfc3339(),
@@ -355,6 +410,7 @@ fn log_response(config: &LoggingConfig, request_info: &RequestInfo, response_inf
     let mut log_data = json!({
         "type": "response",
         "method": request_info.method.as_str(),
+        "route": request_info.route,
         "uri": request_info.uri.to_string(),
         "status": response_info.status.as_u16(),
         "status_text": response_info.status.canonical_reason().unwrap_or("Unknown"),
@@ -372,6 +428,11 @@ fn log_response(config: &LoggingConfig, request_info: &RequestInfo, response_inf
         log_data["client_ip"] = json!(client_ip.to_string());
     }
 
+    // Add user ID if authenticated
+    if let Some(user_id) = &response_info.user_id {
+        log_data["user_id"] = json!(user_id);
+    }
+
     // Add response headers if configured
     if config.log_response_headers {
         log_data["headers"] = config.filter_headers(&response_info.headers);
@@ -408,10 +469,12 @@ fn log_response(config: &LoggingConfig, request_info: &RequestInfo, response_inf
         warn!(
             target: "slow_requests",
             method = request_info.method.as_str(),
+            route = request_info.route,
             uri = request_info.uri.to_string(),
             status = response_info.status.as_u16(),
             duration_ms = response_info.duration.as_millis(),
             request_id = request_info.request_id.as_deref().unwrap_or("unknown"),
+            user_id = response_info.user_id.as_deref().unwrap_or("anonymous"),
             "Slow request detected"
         );
     }
@@ -495,9 +558,9 @@ mod tests {
 
         let filtered = config.filter_headers(&headers);
 
-        assert!(filtered.get("content-type").is_some());
-        assert!(filtered.get("x-request-id").is_some());
-        assert!(filtered.get("authorization").is_none());
+        assert_eq!(filtered["content-type"], "application/json");
+        assert_eq!(filtered["x-request-id"], "req-456");
+        assert_eq!(filtered["authorization"], "[REDACTED]");
     }
 
     #[test]
@@ -602,6 +665,47 @@ mod tests {
         assert_eq!(response.status(), StatusCode::OK);
     }
 
+    #[tokio::test]
+    async fn test_bodies_over_the_limit_pass_through_unlogged() {
+        let config = LoggingConfig { max_body_size: 16, ..LoggingConfig::development() };
+        let app = Router::new()
+            .route("/echo", axum::routing::post(|body: String| async move { body }))
+            .layer(axum::middleware::from_fn(logging_middleware(config)));
+
+        let body = "x".repeat(1024);
+        let request = Request::builder()
+            .method("POST")
+            .uri("/echo")
+            .header("content-length", body.len())
+            .body(Body::from(body.clone()))
+            .unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let echoed = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(echoed, body.as_bytes());
+    }
+
+    #[test]
+    fn test_from_config() {
+        let config = LoggingConfig::from_config(&RequestLoggingConfig {
+            enabled: true,
+            log_request_body: true,
+            log_response_body: false,
+            max_body_size_kb: 2,
+            log_request_headers: true,
+            log_response_headers: false,
+            excluded_headers: vec!["x-secret".to_string()],
+            log_timing: true,
+            slow_request_threshold_ms: 250,
+        });
+
+        assert_eq!(config.max_body_size, 2048);
+        assert!(config.log_request_body);
+        assert!(!config.should_log_header("X-Secret"));
+        assert_eq!(config.slow_request_threshold_ms, 250);
+    }
+
     #[tokio::test]
     async fn test_request_info_creation() {
         let method = Method::GET;
@@ -613,6 +717,7 @@ mod tests {
         let info = RequestInfo {
             method: method.clone(),
             uri,
+            route: "/test".to_string(),
             version: Version::HTTP_11,
             headers: headers.clone(),
             body: None,
@@ -1,5 +1,5 @@
 use axum::{
-    extract::Request,
+    extract::{MatchedPath, Request},
     http::{Method, StatusCode, Uri},
     middleware::Next,
     response::Response,
@@ -7,18 +7,34 @@ use axum::{
     Router,
 };
 use metrics::{counter, describe_counter, describe_gauge, describe_histogram, gauge, histogram};
-use metrics_exporter_prometheus::{PrometheusBuilder, PrometheusHandle};
+use metrics_exporter_prometheus::{Matcher, PrometheusBuilder, PrometheusHandle};
 use std::{
     collections::HashMap,
     net::IpAddr,
     sync::{
         atomic::{AtomicU64, Ordering},
-        Arc,
+        Arc, Mutex, PoisonError,
     },
     time::{Duration, Instant},
 };
+use tokio::net::TcpListener;
 use tracing::debug;
 
+/// Route label for requests that matched no route, so probes of random paths
+/// cannot grow the label set without bound
+pub const UNMATCHED_ROUTE: &str = "unmatched";
+
+/// Histogram buckets for `*_seconds` metrics, from 5ms to 60s
+const DURATION_BUCKETS: [f64; 13] =
+    [0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0];
+
+/// Histogram buckets for the time from upload to processed, from 1s to 1h
+const PROCESSING_LATENCY_BUCKETS: [f64; 10] =
+    [1.0, 5.0, 15.0, 30.0, 60.0, 120.0, 300.0, 600.0, 1800.0, 3600.0];
+
+/// Handle of the installed Prometheus recorder; a process can only install one
+static PROMETHEUS_HANDLE: Mutex<Option<PrometheusHandle>> = Mutex::new(None);
+
 /// Metrics configuration
 #[derive(Debug, Clone)]
 #[allow(clippy::struct_excessive_bools)]
@@ -31,7 +47,7 @@ pub struct MetricsConfig {
     pub error_metrics: bool,
     /// Enable business metrics (custom metrics)
     pub business_metrics: bool,
-    /// Group similar routes (e.g., /api/users/{id} becomes /api/users/:id)
+    /// Label requests by their route template (e.g. `/media/{id}`) instead of the raw path
     pub normalize_routes: bool,
     /// Metrics collection interval for gauges
     pub collection_interval: Duration,
@@ -103,6 +119,111 @@ impl MetricsCollector {
 
         describe_gauge!("media_storage_bytes", "Total bytes of media storage used");
 
+        describe_counter!(
+            "media_scans_total",
+            "Total number of uploads scanned for malware, by result (clean, infected, error)"
+        );
+
+        describe_counter!(
+            "media_upload_requests_total",
+            "Total number of upload requests, by upload kind, status and client version, platform and network type"
+        );
+
+        describe_counter!(
+            "media_legacy_upload_requests_total",
+            "Total number of calls to the deprecated direct upload endpoint, by whether it was served or rejected"
+        );
+
+        describe_counter!(
+            "media_upload_fingerprint_hits_total",
+            "Total number of presigned upload initiations collapsed into a recent identical one"
+        );
+
+        describe_counter!(
+            "media_format_conversions_total",
+            "Total number of uploads converted into a format their tenant accepts, by source and target type"
+        );
+
+        describe_counter!(
+            "storage_hash_mismatches_total",
+            "Total number of stored files whose content did not match their hash when read"
+        );
+
+        describe_business_metrics();
+        describe_processing_sla_metrics();
+
+        describe_counter!(
+            "render_cache_requests_total",
+            "Total number of render cache lookups, by result (hit or miss)"
+        );
+
+        // Image pipeline rollout metrics
+        describe_counter!(
+            "image_pipeline_runs_total",
+            "Total number of image encodes, by pipeline version and outcome"
+        );
+
+        describe_histogram!(
+            "image_pipeline_duration_seconds",
+            "Duration of image encodes in seconds, by pipeline version"
+        );
+
+        describe_histogram!(
+            "image_pipeline_variant_bytes",
+            "Size of encoded image variants in bytes, by pipeline version and variant"
+        );
+
+        describe_counter!(
+            "image_pipeline_comparisons_total",
+            "Total number of candidate encodes compared against the stable pipeline, by result"
+        );
+
+        describe_histogram!(
+            "image_pipeline_size_ratio",
+            "Candidate variant size divided by the stable variant size, by variant"
+        );
+
+        // Use case metrics
+        describe_histogram!(
+            "use_case_duration_seconds",
+            "Duration of use case executions in seconds, by use case and outcome"
+        );
+
+        describe_counter!(
+            "use_case_retries_total",
+            "Total number of use case attempts retried after a transient error"
+        );
+
+        // Background job metrics
+        describe_counter!(
+            "background_job_runs_total",
+            "Total number of finished background job runs, by job and outcome"
+        );
+
+        describe_counter!(
+            "background_job_failures_total",
+            "Total number of failed background job runs, by job"
+        );
+
+        describe_histogram!(
+            "background_job_duration_seconds",
+            "Duration of background job runs in seconds, by job"
+        );
+
+        describe_gauge!("background_job_backlog", "Background work queued or running, by job");
+
+        describe_gauge!(
+            "background_job_backlog_age_seconds",
+            "Age of the oldest queued or running background work in seconds, by job"
+        );
+
+        describe_gauge!("service_read_only", "1 while the service rejects writes, otherwise 0");
+
+        describe_counter!(
+            "request_deadline_exceeded_total",
+            "Requests answered with 504 because the caller's deadline passed, by stage"
+        );
+
         // Authentication metrics
         describe_counter!("auth_attempts_total", "Total authentication attempts");
 
@@ -114,17 +235,23 @@ impl MetricsCollector {
         debug!("Metrics initialized");
     }
 
+    /// The `route` label for a request
+    ///
+    /// With route normalization this is the template of the route that matched,
+    /// e.g. `/api/v1/media-management/media/{id}`, or [`UNMATCHED_ROUTE`].
+    #[must_use]
+    pub fn route_label(&self, uri: &Uri, matched_path: Option<&MatchedPath>) -> String {
+        if !self.config.normalize_routes {
+            return uri.path().to_string();
+        }
+        matched_path.map_or_else(|| UNMATCHED_ROUTE.to_string(), |path| path.as_str().to_string())
+    }
+
     /// Record HTTP request start
-    pub fn record_request_start(&self, method: &Method, uri: &Uri) -> RequestMetrics {
+    pub fn record_request_start(&self, method: &Method, route: String) -> RequestMetrics {
         self.active_requests.fetch_add(1, Ordering::Relaxed);
         self.total_requests.fetch_add(1, Ordering::Relaxed);
 
-        let route = if self.config.normalize_routes {
-            normalize_route_path(uri.path())
-        } else {
-            uri.path().to_string()
-        };
-
         if self.config.request_metrics {
             counter!("http_requests_total", "method" => method.to_string(), "route" => route.clone()).increment(1);
         }
@@ -210,6 +337,68 @@ impl MetricsCollector {
     }
 }
 
+/// Describe the upload, storage and processing metrics recorded by
+/// [`BusinessMetrics`](crate::infrastructure::business_metrics::BusinessMetrics)
+fn describe_business_metrics() {
+    describe_counter!(
+        "media_uploads_started_total",
+        "Total number of uploads begun, by upload kind (direct, presigned, resumable)"
+    );
+
+    describe_counter!(
+        "media_uploads_completed_total",
+        "Total number of uploads whose content was accepted, by upload kind"
+    );
+
+    describe_counter!(
+        "media_uploads_failed_total",
+        "Total number of uploads that failed after starting, by upload kind and error type"
+    );
+
+    describe_counter!(
+        "media_uploads_aborted_total",
+        "Total number of uploads whose body stopped before it was complete, usually because the client disconnected, by upload kind and route"
+    );
+
+    describe_counter!(
+        "media_upload_bytes_ingested_total",
+        "Total bytes received by completed uploads, by upload kind"
+    );
+
+    describe_counter!(
+        "media_upload_dedup_hits_total",
+        "Total number of completed uploads that matched content already stored, by upload kind"
+    );
+
+    describe_counter!(
+        "media_storage_bytes_stored_total",
+        "Total bytes written to storage, by storage backend"
+    );
+
+    describe_histogram!(
+        "media_processing_duration_seconds",
+        "Duration of media processing in seconds, by media kind and outcome"
+    );
+}
+
+/// Describe the processing SLA metrics
+fn describe_processing_sla_metrics() {
+    describe_histogram!(
+        "media_processing_latency_seconds",
+        "Time from upload completed to processing finished in seconds, by media kind and outcome"
+    );
+
+    describe_counter!(
+        "media_processing_sla_breaches_total",
+        "Total number of media over the processing SLA, by media kind and stage (finished, unfinished)"
+    );
+
+    describe_gauge!(
+        "media_processing_sla_overdue",
+        "Number of media still unprocessed past the processing SLA at the last check"
+    );
+}
+
 /// Metrics data for a single request
 #[derive(Debug, Clone)]
 pub struct RequestMetrics {
@@ -219,16 +408,47 @@ pub struct RequestMetrics {
 }
 
 /// Initialize Prometheus metrics exporter
+///
+/// The recorder is installed on the first call; later calls return its handle,
+/// so building the app more than once keeps exporting the same metrics.
+///
+/// # Errors
+/// Returns an error if another metrics recorder is already installed
 pub fn initialize_prometheus_exporter() -> Result<PrometheusHandle, Box<dyn std::error::Error>> {
-    let recorder = PrometheusBuilder::new().build_recorder();
+    let mut installed = PROMETHEUS_HANDLE.lock().unwrap_or_else(PoisonError::into_inner);
+    if let Some(handle) = installed.as_ref() {
+        return Ok(handle.clone());
+    }
+
+    let recorder = PrometheusBuilder::new()
+        .set_buckets_for_metric(Matcher::Suffix("_seconds".to_string()), &DURATION_BUCKETS)?
+        .set_buckets_for_metric(
+            Matcher::Full("media_processing_latency_seconds".to_string()),
+            &PROCESSING_LATENCY_BUCKETS,
+        )?
+        .build_recorder();
     let handle = recorder.handle();
     metrics::set_global_recorder(recorder)?;
+    *installed = Some(handle.clone());
     Ok(handle)
 }
 
-/// Create /metrics endpoint for Prometheus scraping
-pub fn create_metrics_endpoint(handle: PrometheusHandle) -> Router {
-    Router::new().route("/metrics", get(move || async move { handle.render() }))
+/// Create the Prometheus scrape endpoint at `path`, e.g. `/metrics`
+pub fn create_metrics_endpoint(handle: PrometheusHandle, path: &str) -> Router {
+    let path = if path.starts_with('/') { path.to_string() } else { format!("/{path}") };
+    Router::new().route(&path, get(move || async move { handle.render() }))
+}
+
+/// Serve the scrape endpoint on its own listener, e.g. a port only Prometheus can reach
+///
+/// # Errors
+/// Returns an error if accepting connections fails
+pub async fn serve_metrics(
+    listener: TcpListener,
+    handle: PrometheusHandle,
+    path: &str,
+) -> std::io::Result<()> {
+    axum::serve(listener, create_metrics_endpoint(handle, path)).await
 }
 
 /// Metrics middleware for collecting HTTP metrics
@@ -241,7 +461,7 @@ pub fn metrics_middleware(
         Box::pin(async move {
             // Extract request info
             let method = request.method().clone();
-            let uri = request.uri().clone();
+            let route = collector.route_label(request.uri(), request.extensions().get());
 
             // Get request body size if available
             let request_size = request
@@ -251,7 +471,7 @@ pub fn metrics_middleware(
                 .and_then(|cl| cl.parse::<usize>().ok());
 
             // Start request metrics
-            let request_metrics = collector.record_request_start(&method, &uri);
+            let request_metrics = collector.record_request_start(&method, route);
 
             // Process request
             let response = next.run(request).await;
@@ -279,33 +499,6 @@ pub fn metrics_middleware(
     }
 }
 
-/// Normalize route paths for consistent metrics
-fn normalize_route_path(path: &str) -> String {
-    // Common patterns to normalize
-    let patterns = [
-        (r"/api/v\d+/", "/api/v*/"),
-        (r"/media/[0-9a-fA-F-]{36}/?", "/media/:id"),
-        (r"/media/\d+/?", "/media/:id"),
-        (r"/users/[0-9a-fA-F-]{36}/?", "/users/:id"),
-        (r"/users/\d+/?", "/users/:id"),
-    ];
-
-    let mut normalized = path.to_string();
-
-    for (pattern, replacement) in &patterns {
-        if let Ok(re) = regex::Regex::new(pattern) {
-            normalized = re.replace_all(&normalized, *replacement).to_string();
-        }
-    }
-
-    // Handle trailing slashes consistently
-    if normalized != "/" && normalized.ends_with('/') {
-        normalized.pop();
-    }
-
-    normalized
-}
-
 /// Get HTTP status class for metrics
 fn get_status_class(status: StatusCode) -> &'static str {
     match status.as_u16() {
@@ -434,15 +627,17 @@ mod tests {
     }
 
     #[test]
-    fn test_normalize_route_path() {
-        assert_eq!(normalize_route_path("/api/v1/users"), "/api/v*/users");
-        assert_eq!(normalize_route_path("/api/v2/media"), "/api/v*/media");
-        assert_eq!(
-            normalize_route_path("/media/123e4567-e89b-12d3-a456-426614174000"),
-            "/media/:id"
-        );
-        assert_eq!(normalize_route_path("/users/123/"), "/users/:id");
-        assert_eq!(normalize_route_path("/static/file.css"), "/static/file.css");
+    fn test_route_label_without_a_matched_route() {
+        let uri: Uri = "/media/123".parse().unwrap();
+
+        let normalized = MetricsCollector::new(MetricsConfig::default());
+        assert_eq!(normalized.route_label(&uri, None), UNMATCHED_ROUTE);
+
+        let raw = MetricsCollector::new(MetricsConfig {
+            normalize_routes: false,
+            ..MetricsConfig::default()
+        });
+        assert_eq!(raw.route_label(&uri, None), "/media/123");
     }
 
     #[test]
@@ -463,8 +658,7 @@ mod tests {
 
         // Record request start
         let method = Method::GET;
-        let uri = "/test".parse().unwrap();
-        let request_metrics = collector.record_request_start(&method, &uri);
+        let request_metrics = collector.record_request_start(&method, "/test".to_string());
 
         assert_eq!(collector.active_requests.load(Ordering::Relaxed), 1);
         assert_eq!(collector.total_requests.load(Ordering::Relaxed), 1);
@@ -9,22 +9,40 @@
 //! - Request/response logging
 //! - Global error handling
 //! - Request ID enhancement
+//! - Client hints on uploads
+//! - Deprecation of the legacy direct upload
+//! - Read-only mode during storage failovers
+//! - Replay of requests retried with an `Idempotency-Key`
+//! - Deadline propagation from the gateway
+//! - CORS alongside routes answering `OPTIONS` themselves
+//! - Load shedding beyond the concurrency limit of each class of routes
 
 pub mod auth;
+pub mod client_hints;
+pub mod cors;
+pub mod deadline;
+pub mod deprecation;
 pub mod error;
+pub mod idempotency;
+pub mod load_shed;
 pub mod logging;
 pub mod metrics;
 pub mod rate_limit;
+pub mod read_only;
 pub mod request_id;
 pub mod security;
 pub mod validation;
 
 // Re-export commonly used types
 pub use auth::{Claims, JwtService, UserContext};
+pub use client_hints::ClientHints;
 pub use error::{AppError, ErrorResponse};
 pub use logging::LoggingConfig as RequestLoggingConfig;
 pub use metrics::{MetricsCollector, MetricsConfig as MiddlewareMetricsConfig};
-pub use rate_limit::{RateLimitConfig, RateLimitTier, SimpleRateLimiter};
+pub use rate_limit::{
+    RateLimitConfig, RateLimitDecision, RateLimitKey, RateLimitStore, RateLimitStoreError,
+    RateLimitTier, SimpleRateLimiter, TieredRateLimiter,
+};
 pub use request_id::EnhancedRequestId;
 pub use security::{
     development_security_config, production_security_config,
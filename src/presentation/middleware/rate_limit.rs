@@ -1,22 +1,27 @@
+use async_trait::async_trait;
 use axum::{
-    extract::{ConnectInfo, Request},
-    http::HeaderMap,
+    extract::{ConnectInfo, MatchedPath, Request},
+    http::{HeaderMap, Method},
     middleware::Next,
     response::Response,
 };
 use std::{
     collections::HashMap,
     net::{IpAddr, SocketAddr},
-    sync::Arc,
+    sync::{
+        atomic::{AtomicBool, AtomicU32, Ordering},
+        Arc,
+    },
     time::{Duration, Instant},
 };
+use thiserror::Error;
 use tokio::sync::RwLock;
 // Note: tower_governor API has changed significantly in v0.8.0
 // For now, we'll use our SimpleRateLimiter implementation
 // TODO: Update to tower_governor 0.8.0 API when stable
-use tracing::{debug, warn};
+use tracing::{debug, info, warn};
 
-use super::error::AppError;
+use super::{auth::UserContext, error::AppError};
 
 /// Rate limiting configuration
 #[derive(Debug, Clone)]
@@ -37,7 +42,7 @@ impl Default for RateLimitConfig {
     fn default() -> Self {
         Self {
             max_requests: 100,
-            window_duration: Duration::from_secs(60),
+            window_duration: Duration::from_mins(1),
             burst_capacity: 10,
             trust_forwarded_headers: false,
             include_headers: true,
@@ -46,7 +51,7 @@ impl Default for RateLimitConfig {
 }
 
 /// Different rate limit tiers for different endpoints
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum RateLimitTier {
     /// Health check endpoints - very high limits
     Health,
@@ -65,35 +70,35 @@ impl RateLimitTier {
         match self {
             RateLimitTier::Health => RateLimitConfig {
                 max_requests: 1000,
-                window_duration: Duration::from_secs(60),
+                window_duration: Duration::from_mins(1),
                 burst_capacity: 100,
                 trust_forwarded_headers: false,
                 include_headers: true,
             },
             RateLimitTier::Public => RateLimitConfig {
                 max_requests: 60,
-                window_duration: Duration::from_secs(60),
+                window_duration: Duration::from_mins(1),
                 burst_capacity: 10,
                 trust_forwarded_headers: false,
                 include_headers: true,
             },
             RateLimitTier::Authenticated => RateLimitConfig {
                 max_requests: 200,
-                window_duration: Duration::from_secs(60),
+                window_duration: Duration::from_mins(1),
                 burst_capacity: 20,
                 trust_forwarded_headers: false,
                 include_headers: true,
             },
             RateLimitTier::Upload => RateLimitConfig {
                 max_requests: 10,
-                window_duration: Duration::from_secs(60),
+                window_duration: Duration::from_mins(1),
                 burst_capacity: 2,
                 trust_forwarded_headers: false,
                 include_headers: true,
             },
             RateLimitTier::Admin => RateLimitConfig {
                 max_requests: 500,
-                window_duration: Duration::from_secs(60),
+                window_duration: Duration::from_mins(1),
                 burst_capacity: 50,
                 trust_forwarded_headers: false,
                 include_headers: true,
@@ -102,11 +107,104 @@ impl RateLimitTier {
     }
 }
 
+impl RateLimitTier {
+    /// Name of the tier, used in shared budget keys
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Health => "health",
+            Self::Public => "public",
+            Self::Authenticated => "authenticated",
+            Self::Upload => "upload",
+            Self::Admin => "admin",
+        }
+    }
+
+    /// Tier of a request, by its route and whether its caller was authenticated
+    ///
+    /// Health checks, admin endpoints and uploads have their own tiers; any
+    /// other request is public unless the auth middleware identified a user.
+    pub fn for_request(request: &Request) -> Self {
+        let path = request
+            .extensions()
+            .get::<MatchedPath>()
+            .map_or_else(|| request.uri().path(), MatchedPath::as_str);
+        let method = request.method();
+
+        if path.ends_with("/live") || path.ends_with("/health") || path.ends_with("/ready") {
+            Self::Health
+        } else if path.contains("/admin/") {
+            Self::Admin
+        } else if is_upload(method, path) {
+            Self::Upload
+        } else if request.extensions().get::<UserContext>().is_some() {
+            Self::Authenticated
+        } else {
+            Self::Public
+        }
+    }
+}
+
+/// Whose budget a request is taken from
+///
+/// Upload and admin requests of an authenticated caller are limited per user,
+/// or per `OAuth2` client for client-credentials tokens, so callers sharing an
+/// address keep separate budgets and one caller cannot dodge its budget by
+/// switching addresses. Every other request is limited per client IP.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum RateLimitKey {
+    Ip(IpAddr),
+    User(String),
+    Client(String),
+}
+
+impl RateLimitKey {
+    /// Key of a request in `tier`, from `client_ip`
+    pub fn for_request(request: &Request, tier: RateLimitTier, client_ip: IpAddr) -> Self {
+        if !matches!(tier, RateLimitTier::Upload | RateLimitTier::Admin) {
+            return Self::Ip(client_ip);
+        }
+        match request.extensions().get::<UserContext>() {
+            Some(UserContext { user_id: Some(user_id), .. }) => Self::User(user_id.clone()),
+            Some(user) => Self::Client(user.client_id.clone()),
+            None => Self::Ip(client_ip),
+        }
+    }
+}
+
+impl std::fmt::Display for RateLimitKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Ip(ip) => write!(f, "ip:{ip}"),
+            Self::User(user_id) => write!(f, "user:{user_id}"),
+            Self::Client(client_id) => write!(f, "client:{client_id}"),
+        }
+    }
+}
+
+/// Whether a request starts or continues an upload
+fn is_upload(method: &Method, path: &str) -> bool {
+    match *method {
+        Method::POST => {
+            path.ends_with("/media")
+                || path.ends_with("/media/")
+                || path.ends_with("/upload-request")
+                || path.ends_with("/uploads")
+        }
+        Method::PUT => path.contains("/media/upload/"),
+        Method::PATCH => path.contains("/media/uploads/"),
+        _ => false,
+    }
+}
+
 /// Simple in-memory rate limiter for basic use cases
+///
+/// Clones share their request history and limit, so [`SimpleRateLimiter::set_max_requests`]
+/// applies to every clone.
 #[derive(Debug, Clone)]
 pub struct SimpleRateLimiter {
     config: RateLimitConfig,
-    state: Arc<RwLock<HashMap<IpAddr, RequestHistory>>>,
+    max_requests: Arc<AtomicU32>,
+    state: Arc<RwLock<HashMap<RateLimitKey, RequestHistory>>>,
 }
 
 #[derive(Debug, Clone)]
@@ -117,7 +215,23 @@ struct RequestHistory {
 
 impl SimpleRateLimiter {
     pub fn new(config: RateLimitConfig) -> Self {
-        Self { config, state: Arc::new(RwLock::new(HashMap::new())) }
+        let max_requests = Arc::new(AtomicU32::new(config.max_requests));
+        Self { config, max_requests, state: Arc::new(RwLock::new(HashMap::new())) }
+    }
+
+    /// Change the number of requests allowed per window
+    pub fn set_max_requests(&self, max_requests: u32) {
+        self.max_requests.store(max_requests, Ordering::Relaxed);
+    }
+
+    /// Number of requests currently allowed per window
+    pub fn max_requests(&self) -> u32 {
+        self.max_requests.load(Ordering::Relaxed)
+    }
+
+    /// Window the limit applies to
+    pub fn window(&self) -> Duration {
+        self.config.window_duration
     }
 
     /// Check rate limit for an IP address
@@ -126,11 +240,21 @@ impl SimpleRateLimiter {
     ///
     /// Panics if the current time is before the window duration (should not occur in normal operation)
     pub async fn check_rate_limit(&self, ip: IpAddr) -> Result<RateLimitInfo, AppError> {
+        self.check_key(&RateLimitKey::Ip(ip)).await
+    }
+
+    /// Check rate limit for the budget of `key`
+    ///
+    /// # Panics
+    ///
+    /// Panics if the current time is before the window duration (should not occur in normal operation)
+    pub async fn check_key(&self, key: &RateLimitKey) -> Result<RateLimitInfo, AppError> {
         let now = Instant::now();
+        let max_requests = self.max_requests.load(Ordering::Relaxed);
         let mut state = self.state.write().await;
 
         let history = state
-            .entry(ip)
+            .entry(key.clone())
             .or_insert_with(|| RequestHistory { requests: Vec::new(), last_cleanup: now });
 
         // Clean up old requests
@@ -139,24 +263,19 @@ impl SimpleRateLimiter {
 
         // Check if we're over the limit
         let current_count = history.requests.len() as u32;
-        if current_count >= self.config.max_requests {
+        if current_count >= max_requests {
             let oldest_request = history.requests.first().copied().unwrap_or(now);
             let reset_time = oldest_request + self.config.window_duration;
             let retry_after = reset_time.duration_since(now);
 
-            return Err(AppError::RateLimit {
-                message: format!(
-                    "Rate limit exceeded. Try again in {} seconds",
-                    retry_after.as_secs()
-                ),
-            });
+            return Err(rate_limit_exceeded(retry_after));
         }
 
         // Add current request
         history.requests.push(now);
 
         // Periodic cleanup to prevent memory leaks
-        let should_cleanup = now.duration_since(history.last_cleanup) > Duration::from_secs(300);
+        let should_cleanup = now.duration_since(history.last_cleanup) > Duration::from_mins(5);
         if should_cleanup {
             history.last_cleanup = now;
             // Clean up after we're done with history reference
@@ -167,14 +286,45 @@ impl SimpleRateLimiter {
         }
 
         Ok(RateLimitInfo {
-            limit: self.config.max_requests,
-            remaining: self.config.max_requests - current_count - 1,
+            limit: max_requests,
+            remaining: max_requests - current_count - 1,
             reset_time: now + self.config.window_duration,
             retry_after: None,
         })
     }
 }
 
+/// Error answering a request over its budget
+fn rate_limit_exceeded(retry_after: Duration) -> AppError {
+    AppError::RateLimit {
+        message: format!("Rate limit exceeded. Try again in {} seconds", retry_after.as_secs()),
+    }
+}
+
+/// Outcome of taking a request from a budget kept in a [`RateLimitStore`]
+#[derive(Debug, Clone)]
+pub enum RateLimitDecision {
+    Allowed(RateLimitInfo),
+    Denied { retry_after: Duration },
+}
+
+/// A [`RateLimitStore`] that could not be reached
+#[derive(Debug, Error)]
+#[error("Rate limit store unavailable: {0}")]
+pub struct RateLimitStoreError(pub String);
+
+/// Budgets kept outside the process, so every replica draws from the same one
+#[async_trait]
+pub trait RateLimitStore: Send + Sync + std::fmt::Debug {
+    /// Take one request from the budget of `key`, which allows `limit` requests per `window`
+    async fn acquire(
+        &self,
+        key: &str,
+        limit: u32,
+        window: Duration,
+    ) -> Result<RateLimitDecision, RateLimitStoreError>;
+}
+
 /// Rate limit information
 #[derive(Debug, Clone)]
 pub struct RateLimitInfo {
@@ -279,6 +429,142 @@ pub fn simple_rate_limit_middleware(
     }
 }
 
+/// Rate limiter keeping a separate budget per [`RateLimitTier`]
+///
+/// Cloning is cheap; clones share budgets and limits, so limits changed with
+/// [`TieredRateLimiter::set_limit`] apply to requests already being served.
+/// With a [`RateLimitStore`] budgets are shared across replicas; while the
+/// store cannot be reached, each instance falls back to its own budgets.
+#[derive(Debug, Clone)]
+pub struct TieredRateLimiter {
+    health: SimpleRateLimiter,
+    public: SimpleRateLimiter,
+    authenticated: SimpleRateLimiter,
+    upload: SimpleRateLimiter,
+    admin: SimpleRateLimiter,
+    store: Option<Arc<dyn RateLimitStore>>,
+    store_available: Arc<AtomicBool>,
+    trust_forwarded_headers: bool,
+    include_headers: bool,
+}
+
+impl TieredRateLimiter {
+    /// Create a limiter with the default limit of each tier
+    pub fn new(trust_forwarded_headers: bool, include_headers: bool) -> Self {
+        let limiter = |tier: RateLimitTier| {
+            SimpleRateLimiter::new(RateLimitConfig {
+                trust_forwarded_headers,
+                include_headers,
+                ..tier.to_config()
+            })
+        };
+        Self {
+            health: limiter(RateLimitTier::Health),
+            public: limiter(RateLimitTier::Public),
+            authenticated: limiter(RateLimitTier::Authenticated),
+            upload: limiter(RateLimitTier::Upload),
+            admin: limiter(RateLimitTier::Admin),
+            store: None,
+            store_available: Arc::new(AtomicBool::new(true)),
+            trust_forwarded_headers,
+            include_headers,
+        }
+    }
+
+    /// Keep budgets in `store`, shared with other replicas
+    #[must_use]
+    pub fn with_store(self, store: Arc<dyn RateLimitStore>) -> Self {
+        Self { store: Some(store), ..self }
+    }
+
+    /// The limiter of `tier`
+    pub fn limiter(&self, tier: RateLimitTier) -> &SimpleRateLimiter {
+        match tier {
+            RateLimitTier::Health => &self.health,
+            RateLimitTier::Public => &self.public,
+            RateLimitTier::Authenticated => &self.authenticated,
+            RateLimitTier::Upload => &self.upload,
+            RateLimitTier::Admin => &self.admin,
+        }
+    }
+
+    /// Change how many requests per minute each client may make in `tier`
+    pub fn set_limit(&self, tier: RateLimitTier, requests_per_minute: u32) {
+        self.limiter(tier).set_max_requests(requests_per_minute);
+    }
+
+    /// Take one request from the budget of `key` in `tier`
+    ///
+    /// # Errors
+    /// Returns [`AppError::RateLimit`] if the budget is exhausted
+    pub async fn check(
+        &self,
+        tier: RateLimitTier,
+        key: &RateLimitKey,
+    ) -> Result<RateLimitInfo, AppError> {
+        let limiter = self.limiter(tier);
+        if let Some(store) = &self.store {
+            let key = format!("{}:{}", tier.as_str(), key);
+            match store.acquire(&key, limiter.max_requests(), limiter.window()).await {
+                Ok(decision) => {
+                    if !self.store_available.swap(true, Ordering::Relaxed) {
+                        info!("Rate limit store reachable again, sharing budgets across replicas");
+                    }
+                    return match decision {
+                        RateLimitDecision::Allowed(info) => Ok(info),
+                        RateLimitDecision::Denied { retry_after } => {
+                            Err(rate_limit_exceeded(retry_after))
+                        }
+                    };
+                }
+                Err(e) => {
+                    metrics::counter!("rate_limit_store_fallbacks_total").increment(1);
+                    if self.store_available.swap(false, Ordering::Relaxed) {
+                        warn!("{} - limiting with per-instance budgets until it recovers", e);
+                    }
+                }
+            }
+        }
+        limiter.check_key(key).await
+    }
+}
+
+/// Middleware limiting each client's requests per minute by the tier of the request
+///
+/// Layer it inside the auth middleware so authenticated requests get their tier
+/// and their per-user budget; see [`RateLimitKey`].
+pub fn tiered_rate_limit_middleware(
+    limiter: TieredRateLimiter,
+) -> impl Fn(
+    Request,
+    Next,
+)
+    -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<Response, AppError>> + Send>>
+       + Clone {
+    move |request: Request, next: Next| {
+        let limiter = limiter.clone();
+        Box::pin(async move {
+            let tier = RateLimitTier::for_request(&request);
+            let client_ip = extract_client_ip(&request, limiter.trust_forwarded_headers);
+            let key = RateLimitKey::for_request(&request, tier, client_ip);
+
+            match limiter.check(tier, &key).await {
+                Ok(info) => {
+                    let mut response = next.run(request).await;
+                    if limiter.include_headers {
+                        info.add_headers(response.headers_mut());
+                    }
+                    Ok(response)
+                }
+                Err(rate_limit_error) => {
+                    warn!("Rate limit exceeded for {} in tier {:?}", key, tier);
+                    Err(rate_limit_error)
+                }
+            }
+        })
+    }
+}
+
 // Tower Governor integration has been simplified for now
 // The SimpleRateLimiter above provides the core functionality
 // TODO: Add tower_governor integration when API stabilizes
@@ -311,7 +597,7 @@ mod tests {
     async fn test_simple_rate_limiter() {
         let config = RateLimitConfig {
             max_requests: 3,
-            window_duration: Duration::from_secs(60),
+            window_duration: Duration::from_mins(1),
             burst_capacity: 1,
             trust_forwarded_headers: false,
             include_headers: true,
@@ -410,7 +696,7 @@ mod tests {
         let info = RateLimitInfo {
             limit: 100,
             remaining: 85,
-            reset_time: Instant::now() + Duration::from_secs(60),
+            reset_time: Instant::now() + Duration::from_mins(1),
             retry_after: Some(Duration::from_secs(30)),
         };
 
@@ -427,7 +713,7 @@ mod tests {
     fn test_default_rate_limit_config() {
         let config = RateLimitConfig::default();
         assert_eq!(config.max_requests, 100);
-        assert_eq!(config.window_duration, Duration::from_secs(60));
+        assert_eq!(config.window_duration, Duration::from_mins(1));
         assert_eq!(config.burst_capacity, 10);
         assert!(!config.trust_forwarded_headers);
         assert!(config.include_headers);
@@ -437,7 +723,7 @@ mod tests {
     async fn test_rate_limit_different_ips() {
         let config = RateLimitConfig {
             max_requests: 2,
-            window_duration: Duration::from_secs(60),
+            window_duration: Duration::from_mins(1),
             burst_capacity: 1,
             trust_forwarded_headers: false,
             include_headers: true,
@@ -458,6 +744,133 @@ mod tests {
         assert!(limiter.check_rate_limit(ip2).await.is_err()); // IP2 exhausted
     }
 
+    #[test]
+    fn test_tier_for_request() {
+        let tier = |method: Method, path: &str| {
+            let request = Request::builder()
+                .method(method)
+                .uri(format!("/api/v1/media-management{path}"))
+                .body(Body::empty())
+                .unwrap();
+            RateLimitTier::for_request(&request)
+        };
+
+        assert_eq!(tier(Method::GET, "/health"), RateLimitTier::Health);
+        assert_eq!(tier(Method::GET, "/admin/jobs"), RateLimitTier::Admin);
+        assert_eq!(tier(Method::POST, "/media/"), RateLimitTier::Upload);
+        assert_eq!(tier(Method::PATCH, "/media/uploads/abc"), RateLimitTier::Upload);
+        assert_eq!(tier(Method::GET, "/media/"), RateLimitTier::Public);
+    }
+
+    #[tokio::test]
+    async fn test_tier_limits_change_while_running() {
+        let limiter = TieredRateLimiter::new(false, true);
+        let shared = limiter.clone();
+        let ip = IpAddr::V4(Ipv4Addr::new(192, 168, 1, 3));
+
+        limiter.set_limit(RateLimitTier::Upload, 1);
+        let upload = shared.limiter(RateLimitTier::Upload);
+        assert_eq!(upload.check_rate_limit(ip).await.unwrap().limit, 1);
+        assert!(upload.check_rate_limit(ip).await.is_err());
+        // Other tiers keep their own budget
+        assert!(shared.limiter(RateLimitTier::Public).check_rate_limit(ip).await.is_ok());
+
+        limiter.set_limit(RateLimitTier::Upload, 3);
+        assert_eq!(upload.check_rate_limit(ip).await.unwrap().remaining, 1);
+    }
+
+    #[derive(Debug)]
+    struct FakeStore {
+        available: bool,
+        remaining: u32,
+    }
+
+    #[async_trait]
+    impl RateLimitStore for FakeStore {
+        async fn acquire(
+            &self,
+            _key: &str,
+            limit: u32,
+            _window: Duration,
+        ) -> Result<RateLimitDecision, RateLimitStoreError> {
+            if !self.available {
+                return Err(RateLimitStoreError("connection refused".to_string()));
+            }
+            Ok(match self.remaining {
+                0 => RateLimitDecision::Denied { retry_after: Duration::from_secs(5) },
+                remaining => RateLimitDecision::Allowed(RateLimitInfo {
+                    limit,
+                    remaining: remaining - 1,
+                    reset_time: Instant::now(),
+                    retry_after: None,
+                }),
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_store_budgets_with_fallback_to_local_budgets() {
+        let ip = RateLimitKey::Ip(IpAddr::V4(Ipv4Addr::new(192, 168, 1, 4)));
+        let limiter = |available: bool, remaining: u32| {
+            TieredRateLimiter::new(false, true)
+                .with_store(Arc::new(FakeStore { available, remaining }))
+        };
+
+        let shared = limiter(true, 7);
+        assert_eq!(shared.check(RateLimitTier::Upload, &ip).await.unwrap().remaining, 6);
+        let exhausted = limiter(true, 0);
+        assert!(matches!(
+            exhausted.check(RateLimitTier::Public, &ip).await,
+            Err(AppError::RateLimit { .. })
+        ));
+
+        let unavailable = limiter(false, 7);
+        unavailable.set_limit(RateLimitTier::Upload, 1);
+        assert_eq!(unavailable.check(RateLimitTier::Upload, &ip).await.unwrap().limit, 1);
+        assert!(unavailable.check(RateLimitTier::Upload, &ip).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_upload_budgets_follow_the_authenticated_caller() {
+        let ip = IpAddr::V4(Ipv4Addr::new(192, 168, 1, 5));
+        let key = |user_id: Option<&str>, client_id: &str, method: Method, path: &str| {
+            let mut request =
+                Request::builder().method(method).uri(path).body(Body::empty()).unwrap();
+            if !client_id.is_empty() {
+                request.extensions_mut().insert(UserContext {
+                    user_id: user_id.map(str::to_string),
+                    client_id: client_id.to_string(),
+                    subject: String::new(),
+                    scopes: Vec::new(),
+                    token_type: "access_token".to_string(),
+                    token_id: String::new(),
+                    issuer: String::new(),
+                    audience: Vec::new(),
+                });
+            }
+            let tier = RateLimitTier::for_request(&request);
+            RateLimitKey::for_request(&request, tier, ip)
+        };
+
+        let alice = key(Some("alice"), "web", Method::POST, "/media/");
+        assert_eq!(alice, RateLimitKey::User("alice".to_string()));
+        assert_eq!(
+            key(None, "worker", Method::GET, "/admin/jobs"),
+            RateLimitKey::Client("worker".into())
+        );
+        assert_eq!(key(None, "", Method::POST, "/media/"), RateLimitKey::Ip(ip));
+        // Other tiers stay keyed on the address
+        assert_eq!(key(Some("alice"), "web", Method::GET, "/media/"), RateLimitKey::Ip(ip));
+
+        let limiter = TieredRateLimiter::new(false, true);
+        limiter.set_limit(RateLimitTier::Upload, 1);
+        let bob = key(Some("bob"), "web", Method::POST, "/media/");
+        assert_eq!(limiter.check(RateLimitTier::Upload, &alice).await.unwrap().remaining, 0);
+        assert!(limiter.check(RateLimitTier::Upload, &alice).await.is_err());
+        // Bob shares Alice's address but not her budget
+        assert_eq!(limiter.check(RateLimitTier::Upload, &bob).await.unwrap().remaining, 0);
+    }
+
     // Tower Governor tests removed for now - using SimpleRateLimiter instead
     // TODO: Add tower_governor tests when API is updated
 }
@@ -0,0 +1,86 @@
+//! Read-only mode for storage failovers
+//!
+//! While [`ReadOnlyMode`] is enabled, requests that would change media or its
+//! content are answered with `503 Service Unavailable` and a `Retry-After`, so
+//! clients back off until writes are accepted again. Reads and downloads keep
+//! being served. Only routes this middleware is layered on are affected, which
+//! keeps read-only `POST` endpoints such as batch lookups and the admin toggle
+//! itself available.
+
+use axum::{
+    extract::Request,
+    http::Method,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use crate::{application::use_cases::ReadOnlyMode, presentation::middleware::error::AppError};
+
+/// How long clients are asked to wait before retrying a rejected write
+const RETRY_AFTER_SECONDS: u64 = 30;
+
+/// Middleware rejecting mutating requests while `mode` is enabled
+pub fn reject_writes_when_read_only(
+    mode: ReadOnlyMode,
+) -> impl Fn(Request, Next) -> std::pin::Pin<Box<dyn std::future::Future<Output = Response> + Send>>
+       + Clone {
+    move |request: Request, next: Next| {
+        let mode = mode.clone();
+        Box::pin(async move {
+            if !mode.is_enabled() || !is_mutating(request.method()) {
+                return next.run(request).await;
+            }
+
+            tracing::debug!(
+                method = %request.method(),
+                path = request.uri().path(),
+                "Rejected write in read-only mode"
+            );
+            AppError::ServiceUnavailable {
+                message: "The service is read-only for maintenance; retry the request later"
+                    .to_string(),
+                last_error: None,
+                retry_after_seconds: Some(RETRY_AFTER_SECONDS),
+            }
+            .into_response()
+        })
+    }
+}
+
+fn is_mutating(method: &Method) -> bool {
+    matches!(*method, Method::POST | Method::PUT | Method::PATCH | Method::DELETE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        body::Body,
+        http::{header, StatusCode},
+        middleware::from_fn,
+        routing::get,
+        Router,
+    };
+    use tower::ServiceExt;
+
+    async fn call(mode: ReadOnlyMode, method: Method) -> Response {
+        let app = Router::new()
+            .route("/media", get(|| async { "listed" }).post(|| async { "stored" }))
+            .layer(from_fn(reject_writes_when_read_only(mode)));
+        let request = Request::builder().method(method).uri("/media").body(Body::empty()).unwrap();
+        app.oneshot(request).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_read_only_rejects_writes_but_serves_reads() {
+        let mode = ReadOnlyMode::new(true);
+
+        let response = call(mode.clone(), Method::POST).await;
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(response.headers()[header::RETRY_AFTER], "30");
+        assert_eq!(call(mode.clone(), Method::GET).await.status(), StatusCode::OK);
+
+        mode.set(false);
+        assert_eq!(call(mode, Method::POST).await.status(), StatusCode::OK);
+    }
+}
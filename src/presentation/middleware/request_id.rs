@@ -1,15 +1,28 @@
 use axum::{extract::Request, http::HeaderValue, middleware::Next, response::Response};
 use tower_http::request_id::{MakeRequestId, RequestId};
 use tracing::Span;
-use uuid::Uuid;
 
-/// Enhanced request ID maker that creates UUID v4 request IDs
-#[derive(Clone, Debug)]
-pub struct EnhancedRequestId;
+use crate::domain::value_objects::UuidVersion;
+
+/// Enhanced request ID maker that creates UUID request IDs
+///
+/// IDs are version 7 UUIDs by default, so they sort by time in logs.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct EnhancedRequestId {
+    uuid_version: UuidVersion,
+}
+
+impl EnhancedRequestId {
+    /// Create a request ID maker generating UUIDs of the given version
+    #[must_use]
+    pub fn new(uuid_version: UuidVersion) -> Self {
+        Self { uuid_version }
+    }
+}
 
 impl MakeRequestId for EnhancedRequestId {
     fn make_request_id<B>(&mut self, _request: &Request<B>) -> Option<RequestId> {
-        let request_id = Uuid::new_v4().to_string();
+        let request_id = self.uuid_version.new_uuid().to_string();
         let header_value = HeaderValue::try_from(request_id).ok()?;
         Some(RequestId::new(header_value))
     }
@@ -59,6 +72,7 @@ mod tests {
     use serde_json::json;
     use tower::ServiceExt;
     use tower_http::request_id::SetRequestIdLayer;
+    use uuid::Uuid;
 
     async fn test_handler() -> Json<serde_json::Value> {
         Json(json!({"status": "ok"}))
@@ -66,7 +80,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_enhanced_request_id_make_request_id() {
-        let mut maker = EnhancedRequestId;
+        let mut maker = EnhancedRequestId::default();
         let request = Request::builder().body(Body::empty()).unwrap();
 
         let request_id = maker.make_request_id(&request);
@@ -80,6 +94,18 @@ mod tests {
         assert!(parsed_uuid.is_ok());
     }
 
+    #[test]
+    fn test_request_ids_use_configured_uuid_version() {
+        let request = Request::builder().body(Body::empty()).unwrap();
+        let version_of = |mut maker: EnhancedRequestId| {
+            let request_id = maker.make_request_id(&request).unwrap();
+            Uuid::parse_str(request_id.header_value().to_str().unwrap()).unwrap().get_version_num()
+        };
+
+        assert_eq!(version_of(EnhancedRequestId::default()), 7);
+        assert_eq!(version_of(EnhancedRequestId::new(UuidVersion::V4)), 4);
+    }
+
     #[tokio::test]
     async fn test_enhance_request_id_middleware() {
         let app = Router::new()
@@ -87,7 +113,7 @@ mod tests {
             .layer(axum::middleware::from_fn(enhance_request_id))
             .layer(SetRequestIdLayer::new(
                 axum::http::HeaderName::from_static("x-request-id"),
-                EnhancedRequestId,
+                EnhancedRequestId::default(),
             ));
 
         let request = Request::builder().uri("/test").body(Body::empty()).unwrap();
@@ -118,7 +144,7 @@ mod tests {
             .layer(axum::middleware::from_fn(enhance_request_id))
             .layer(SetRequestIdLayer::new(
                 axum::http::HeaderName::from_static("x-request-id"),
-                EnhancedRequestId,
+                EnhancedRequestId::default(),
             ));
 
         let existing_id = Uuid::new_v4().to_string();
@@ -147,7 +173,7 @@ mod tests {
             .layer(axum::middleware::from_fn(enhance_request_id))
             .layer(SetRequestIdLayer::new(
                 axum::http::HeaderName::from_static("x-request-id"),
-                EnhancedRequestId,
+                EnhancedRequestId::default(),
             ));
 
         // Make multiple requests
@@ -183,7 +209,7 @@ mod tests {
             .layer(axum::middleware::from_fn(enhance_request_id))
             .layer(SetRequestIdLayer::new(
                 axum::http::HeaderName::from_static("x-request-id"),
-                EnhancedRequestId,
+                EnhancedRequestId::default(),
             ));
 
         let request = Request::builder().uri("/test").body(Body::empty()).unwrap();
@@ -1,4 +1,5 @@
 pub mod extractors;
 pub mod handlers;
 pub mod middleware;
+pub mod openapi;
 pub mod routes;
@@ -0,0 +1,304 @@
+//! Generated `OpenAPI` 3 description of the HTTP API
+//!
+//! Paths come from the `#[utoipa::path]` attributes on the handlers and schemas
+//! from the DTOs, so the document cannot drift from the code. It is served at
+//! `/api/v1/media-management/openapi.json`; in local mode a Swagger UI for it is
+//! mounted at `/api/v1/media-management/docs`.
+
+use std::sync::LazyLock;
+
+use axum::{
+    http::{header, HeaderValue},
+    response::{IntoResponse, Response},
+};
+use utoipa::{
+    openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme},
+    openapi::RefOr,
+    Modify, OpenApi,
+};
+use utoipa_swagger_ui::SwaggerUi;
+
+use crate::{
+    application::dto::{
+        BackfillSelection, BackfillState, BackfillVariantsRequest, BatchDeleteMediaItem,
+        BatchDeleteMediaRequest, BatchDeleteMediaResponse, BatchDeleteStatus, BatchGetMediaItem,
+        BatchGetMediaRequest, BatchGetMediaResponse, BatchGetStatus, CapacityGrowthDto,
+        CapacityReport, InitiateUploadRequest, InitiateUploadResponse, MediaDto, MediaRenditionDto,
+        MediaTagsResponse, MediaVariantDto, MediaVariantsResponse, PaginatedMediaResponse,
+        PaginationInfo, ProcessingProgressEvent, ReportFormat, SetMediaTagsRequest,
+        StorageUsageResponse, UploadMediaRequest, UploadMediaResponse, UploadStatusResponse,
+        VariantBackfillProgress, VariantKind,
+    },
+    domain::value_objects::{ImageFit, MediaSortField, ProcessingStatus, SortOrder},
+    infrastructure::{
+        http,
+        jobs::{JobOutcome, JobStatus},
+        webhooks::{DeliveryState, WebhookDelivery, WebhookEventType},
+    },
+    presentation::{
+        handlers::{
+            admin::{
+                self, JobsResponse, ReadOnlyStatus, RenderCachePurge, WebhookDeliveriesResponse,
+            },
+            live_events, media, progress, resumable_uploads,
+        },
+        middleware::error::{ErrorResponse, PROBLEM_JSON},
+    },
+};
+
+/// Path the `OpenAPI` document is served at
+pub const OPENAPI_PATH: &str = "/api/v1/media-management/openapi.json";
+
+/// Path the Swagger UI is mounted at in local mode
+pub const SWAGGER_UI_PATH: &str = "/api/v1/media-management/docs";
+
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "Media Management Service",
+        description = "Upload, process and serve media for the recipe web application"
+    ),
+    paths(
+        http::liveness_check,
+        http::health_check_with_dependencies,
+        http::readiness_check_with_dependencies,
+        media::upload_media,
+        media::list_media,
+        media::search_media,
+        media::get_storage_usage,
+        live_events::media_events_socket,
+        media::initiate_upload,
+        media::upload_file,
+        media::get_upload_status,
+        progress::stream_processing_progress,
+        media::get_media,
+        media::get_media_variants,
+        media::batch_get_media,
+        media::delete_media,
+        media::batch_delete_media,
+        media::set_media_tags,
+        media::reprocess_media,
+        media::download_media,
+        media::head_media_download,
+        media::download_media_by_hash,
+        media::render_media,
+        media::get_media_by_recipe,
+        media::get_media_by_ingredient,
+        media::get_media_by_step,
+        media::associate_recipe_media,
+        media::dissociate_recipe_media,
+        media::associate_ingredient_media,
+        media::dissociate_ingredient_media,
+        media::associate_step_media,
+        media::dissociate_step_media,
+        resumable_uploads::upload_options,
+        resumable_uploads::create_upload,
+        resumable_uploads::get_upload_offset,
+        resumable_uploads::append_upload_chunk,
+        admin::list_jobs,
+        admin::start_variant_backfill,
+        admin::get_variant_backfill,
+        admin::get_read_only,
+        admin::set_read_only,
+        admin::get_capacity_report,
+        admin::list_webhook_deliveries,
+        admin::purge_render_cache,
+    ),
+    components(schemas(
+        MediaDto,
+        MediaVariantDto,
+        MediaVariantsResponse,
+        MediaRenditionDto,
+        BatchGetMediaRequest,
+        BatchGetMediaResponse,
+        BatchGetMediaItem,
+        BatchGetStatus,
+        BatchDeleteMediaRequest,
+        BatchDeleteMediaResponse,
+        BatchDeleteMediaItem,
+        BatchDeleteStatus,
+        SetMediaTagsRequest,
+        MediaTagsResponse,
+        UploadMediaRequest,
+        UploadMediaResponse,
+        InitiateUploadRequest,
+        InitiateUploadResponse,
+        UploadStatusResponse,
+        ProcessingProgressEvent,
+        StorageUsageResponse,
+        PaginationInfo,
+        PaginatedMediaResponse,
+        ProcessingStatus,
+        MediaSortField,
+        SortOrder,
+        ImageFit,
+        JobsResponse,
+        ReadOnlyStatus,
+        RenderCachePurge,
+        JobStatus,
+        JobOutcome,
+        WebhookDeliveriesResponse,
+        WebhookDelivery,
+        WebhookEventType,
+        DeliveryState,
+        BackfillVariantsRequest,
+        VariantBackfillProgress,
+        VariantKind,
+        BackfillSelection,
+        BackfillState,
+        CapacityReport,
+        CapacityGrowthDto,
+        ReportFormat,
+        ErrorResponse,
+    )),
+    modifiers(&BearerAuth, &ProblemJson),
+    tags(
+        (name = "media", description = "Media metadata, listing, download and deletion"),
+        (name = "uploads", description = "Presigned and resumable (tus) uploads"),
+        (name = "recipes", description = "Attaching media to recipes, ingredients and steps"),
+        (name = "health", description = "Liveness and readiness probes"),
+        (name = "admin", description = "Operational status for the people running the service"),
+    )
+)]
+pub struct ApiDoc;
+
+/// Register the bearer JWT scheme referenced by authenticated operations
+struct BearerAuth;
+
+impl Modify for BearerAuth {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "bearer_auth",
+            SecurityScheme::Http(
+                HttpBuilder::new().scheme(HttpAuthScheme::Bearer).bearer_format("JWT").build(),
+            ),
+        );
+    }
+}
+
+/// Document error responses with the `application/problem+json` media type they are served as
+struct ProblemJson;
+
+impl Modify for ProblemJson {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let operations = openapi.paths.paths.values_mut().flat_map(|item| {
+            [
+                &mut item.get,
+                &mut item.put,
+                &mut item.post,
+                &mut item.delete,
+                &mut item.options,
+                &mut item.head,
+                &mut item.patch,
+                &mut item.trace,
+            ]
+            .into_iter()
+            .flatten()
+        });
+        for operation in operations {
+            for response in operation.responses.responses.values_mut() {
+                let RefOr::T(response) = response else { continue };
+                let is_problem = response.content.get("application/json").is_some_and(|content| {
+                    matches!(&content.schema, Some(RefOr::Ref(schema))
+                        if schema.ref_location.ends_with("/ErrorResponse"))
+                });
+                if let Some(content) =
+                    is_problem.then(|| response.content.shift_remove("application/json")).flatten()
+                {
+                    response.content.insert(PROBLEM_JSON.to_string(), content);
+                }
+            }
+        }
+    }
+}
+
+/// The document, rendered once on first request
+static OPENAPI_JSON: LazyLock<String> = LazyLock::new(|| {
+    ApiDoc::openapi().to_json().unwrap_or_else(|e| {
+        tracing::error!("Failed to render OpenAPI document: {}", e);
+        "{}".to_string()
+    })
+});
+
+/// Serve the `OpenAPI` document
+pub async fn openapi_json() -> Response {
+    ([(header::CONTENT_TYPE, HeaderValue::from_static("application/json"))], OPENAPI_JSON.as_str())
+        .into_response()
+}
+
+/// Swagger UI pointed at the served `OpenAPI` document
+pub fn swagger_ui() -> SwaggerUi {
+    SwaggerUi::new(SWAGGER_UI_PATH).config(OPENAPI_PATH.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_document_covers_every_route() {
+        let doc = ApiDoc::openapi();
+        let paths = &doc.paths.paths;
+
+        for path in [
+            "/api/v1/media-management/live",
+            "/api/v1/media-management/health",
+            "/api/v1/media-management/ready",
+            "/api/v1/media-management/media",
+            "/api/v1/media-management/media/search",
+            "/api/v1/media-management/media/usage",
+            "/api/v1/media-management/media/ws",
+            "/api/v1/media-management/media/upload-request",
+            "/api/v1/media-management/media/upload/{token}",
+            "/api/v1/media-management/media/uploads",
+            "/api/v1/media-management/media/uploads/{id}",
+            "/api/v1/media-management/media/{id}",
+            "/api/v1/media-management/media/batch-get",
+            "/api/v1/media-management/media/batch-delete",
+            "/api/v1/media-management/media/{id}/status",
+            "/api/v1/media-management/media/{id}/progress",
+            "/api/v1/media-management/media/{id}/download",
+            "/api/v1/media-management/media/{id}/variants",
+            "/api/v1/media-management/media/{id}/tags",
+            "/api/v1/media-management/media/{id}/reprocess",
+            "/api/v1/media-management/media/recipe/{recipe_id}",
+            "/api/v1/media-management/media/recipe/{recipe_id}/ingredient/{ingredient_id}",
+            "/api/v1/media-management/media/recipe/{recipe_id}/step/{step_id}",
+            "/api/v1/media-management/media/{id}/recipe/{recipe_id}",
+            "/api/v1/media-management/media/{id}/recipe/{recipe_id}/ingredient/{ingredient_id}",
+            "/api/v1/media-management/media/{id}/recipe/{recipe_id}/step/{step_id}",
+            "/api/v1/media-management/admin/jobs",
+            "/api/v1/media-management/admin/backfills/variants",
+            "/api/v1/media-management/admin/read-only",
+            "/api/v1/media-management/admin/reports/capacity",
+            "/api/v1/media-management/admin/webhooks/deliveries",
+            "/api/v1/media-management/admin/render-cache",
+        ] {
+            assert!(paths.contains_key(path), "{path} is not documented");
+        }
+    }
+
+    #[test]
+    fn test_document_includes_dto_and_error_schemas() {
+        let doc = ApiDoc::openapi();
+        let schemas = &doc.components.as_ref().unwrap().schemas;
+
+        for name in ["MediaDto", "PaginatedMediaResponse", "ProcessingStatus", "ErrorResponse"] {
+            assert!(schemas.contains_key(name), "{name} schema is missing");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_openapi_json_serves_document() {
+        let response = openapi_json().await;
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+        assert_eq!(json["info"]["title"], "Media Management Service");
+        assert!(json["paths"]["/api/v1/media-management/media/{id}"]["get"]["responses"]["404"]
+            ["content"][PROBLEM_JSON]
+            .is_object());
+        assert!(json["components"]["securitySchemes"]["bearer_auth"].is_object());
+    }
+}
@@ -1,41 +1,168 @@
+use std::sync::Arc;
+
 use axum::{
-    routing::{delete, get, post, put},
+    extract::DefaultBodyLimit,
+    http::StatusCode,
+    middleware::from_fn,
+    routing::{delete, get, patch, post, put},
     Router,
 };
+use tokio::sync::Semaphore;
+use tower_http::timeout::TimeoutLayer;
 
 use crate::{
-    infrastructure::http::{health_check_with_dependencies, readiness_check_with_dependencies},
-    presentation::handlers::{self, media::AppState},
+    application::use_cases::{IdempotencyKeys, ReadOnlyMode},
+    domain::value_objects::{LegacyUploadPolicy, LoadSheddingPolicy, RouteLimit, RouteLimits},
+    infrastructure::http::{
+        health_check_with_dependencies, liveness_check, readiness_check_with_dependencies,
+    },
+    presentation::{
+        handlers::{self, media::AppState},
+        middleware::{
+            auth::{require_roles, ADMIN_SCOPE},
+            client_hints::track_upload_clients,
+            deprecation::deprecate_legacy_upload,
+            idempotency::replay_idempotent_requests,
+            load_shed::shed_load,
+            read_only::reject_writes_when_read_only,
+        },
+        openapi,
+    },
 };
 
 /// Create all application routes with application state
 pub fn create_routes(app_state: AppState) -> Router {
-    Router::new().nest("/api/v1/media-management", media_management_routes()).with_state(app_state)
+    let legacy_upload = app_state.legacy_upload;
+    let read_only = app_state.read_only.clone();
+    let idempotency_keys = app_state.idempotency_keys.clone();
+    let classes = RouteClasses::new(&app_state.route_limits, &app_state.load_shedding);
+    Router::new()
+        .nest(
+            "/api/v1/media-management",
+            media_management_routes(legacy_upload, read_only, &idempotency_keys, &classes),
+        )
+        .with_state(app_state)
+}
+
+/// Limits a class of routes is served with
+struct RouteClass {
+    name: &'static str,
+    limit: RouteLimit,
+    permits: Arc<Semaphore>,
+    retry_after: std::time::Duration,
+}
+
+impl RouteClass {
+    /// Shed load beyond the class's permits, time out requests to `routes`
+    /// and cap their bodies
+    fn limit(&self, routes: Router<AppState>) -> Router<AppState> {
+        let routes = routes
+            .layer(TimeoutLayer::with_status_code(StatusCode::REQUEST_TIMEOUT, self.limit.timeout))
+            .layer(DefaultBodyLimit::max(self.limit.max_body_size));
+        shed_load(routes, &self.permits, self.name, self.retry_after)
+    }
+}
+
+/// The classes of routes: uploads, admin, and reads for everything else
+struct RouteClasses {
+    upload: RouteClass,
+    read: RouteClass,
+    admin: RouteClass,
+}
+
+impl RouteClasses {
+    /// Admin routes share the permits of reads
+    fn new(limits: &RouteLimits, load_shedding: &LoadSheddingPolicy) -> Self {
+        let permits = |max: usize| Arc::new(Semaphore::new(max.min(Semaphore::MAX_PERMITS)));
+        let request_permits = permits(load_shedding.max_concurrent_requests);
+        let class = |name, limit, permits| RouteClass {
+            name,
+            limit,
+            permits,
+            retry_after: load_shedding.retry_after,
+        };
+        Self {
+            upload: class("upload", limits.upload, permits(load_shedding.max_concurrent_uploads)),
+            read: class("read", limits.read, request_permits.clone()),
+            admin: class("admin", limits.admin, request_permits),
+        }
+    }
 }
 
 /// Create media management service routes with state
-fn media_management_routes() -> Router<AppState> {
-    Router::new()
+fn media_management_routes(
+    legacy_upload: LegacyUploadPolicy,
+    read_only: ReadOnlyMode,
+    idempotency_keys: &IdempotencyKeys,
+    classes: &RouteClasses,
+) -> Router<AppState> {
+    let service_routes = Router::new()
+        .route("/live", get(liveness_check))
         .route("/health", get(health_check_with_dependencies))
         .route("/ready", get(readiness_check_with_dependencies))
-        .nest("/media", media_routes())
+        .route("/openapi.json", get(openapi::openapi_json));
+
+    classes
+        .read
+        .limit(service_routes)
+        .merge(classes.admin.limit(admin_routes(read_only.clone())))
+        .nest("/media", media_routes(legacy_upload, read_only, idempotency_keys, classes))
 }
 
-/// Create media-related routes with state
-fn media_routes() -> Router<AppState> {
+/// Create the admin routes, which need a token with the admin scope
+fn admin_routes(read_only: ReadOnlyMode) -> Router<AppState> {
     Router::new()
-        // Legacy direct upload endpoint (deprecated)
-        .route("/", post(handlers::media::upload_media))
+        .route("/admin/jobs", get(handlers::admin::list_jobs))
+        .route(
+            "/admin/backfills/variants",
+            get(handlers::admin::get_variant_backfill)
+                .post(handlers::admin::start_variant_backfill)
+                .route_layer(from_fn(reject_writes_when_read_only(read_only))),
+        )
+        .route(
+            "/admin/read-only",
+            get(handlers::admin::get_read_only).put(handlers::admin::set_read_only),
+        )
+        .route("/admin/render-cache", delete(handlers::admin::purge_render_cache))
+        .route("/admin/reports/capacity", get(handlers::admin::get_capacity_report))
+        .route("/admin/webhooks/deliveries", get(handlers::admin::list_webhook_deliveries))
+        .route_layer(from_fn(require_roles(vec![ADMIN_SCOPE])))
+}
+
+/// Create media-related routes with state
+///
+/// Every route but the batch lookup is layered with read-only mode, which only
+/// rejects the mutating methods. Uploads and association changes replay their
+/// response to requests retried with the same `Idempotency-Key`.
+fn media_routes(
+    legacy_upload: LegacyUploadPolicy,
+    read_only: ReadOnlyMode,
+    idempotency_keys: &IdempotencyKeys,
+    classes: &RouteClasses,
+) -> Router<AppState> {
+    let idempotent = || from_fn(replay_idempotent_requests(idempotency_keys.clone()));
+    let uploads = upload_routes(legacy_upload, idempotency_keys);
+    let media = Router::new()
         .route("/", get(handlers::media::list_media))
-        // New presigned URL upload endpoints
-        .route("/upload-request", post(handlers::media::initiate_upload))
-        .route("/upload/{token}", put(handlers::media::upload_file))
+        .route("/search", get(handlers::media::search_media))
+        .route("/usage", get(handlers::media::get_storage_usage))
+        .route("/ws", get(handlers::live_events::media_events_socket))
         // Status and retrieval endpoints
         .route("/{id}", get(handlers::media::get_media))
         .route("/{id}/status", get(handlers::media::get_upload_status))
-        .route("/{id}/download", get(handlers::media::download_media))
+        .route("/{id}/progress", get(handlers::progress::stream_processing_progress))
+        .route(
+            "/{id}/download",
+            get(handlers::media::download_media).head(handlers::media::head_media_download),
+        )
+        .route("/by-hash/{content_hash}", get(handlers::media::download_media_by_hash))
+        .route("/{id}/variants", get(handlers::media::get_media_variants))
+        .route("/{id}/tags", put(handlers::media::set_media_tags))
+        .route("/{id}/reprocess", post(handlers::media::reprocess_media))
+        .route("/{id}/render", get(handlers::media::render_media))
         // Delete endpoints
         .route("/{id}", delete(handlers::media::delete_media))
+        .route("/batch-delete", post(handlers::media::batch_delete_media))
         // Recipe-related endpoints
         .route("/recipe/{recipe_id}", get(handlers::media::get_media_by_recipe))
         .route(
@@ -43,6 +170,76 @@ fn media_routes() -> Router<AppState> {
             get(handlers::media::get_media_by_ingredient),
         )
         .route("/recipe/{recipe_id}/step/{step_id}", get(handlers::media::get_media_by_step))
+        // Recipe association management
+        .route(
+            "/{id}/recipe/{recipe_id}",
+            post(handlers::media::associate_recipe_media)
+                .delete(handlers::media::dissociate_recipe_media)
+                .layer(idempotent()),
+        )
+        .route(
+            "/{id}/recipe/{recipe_id}/ingredient/{ingredient_id}",
+            post(handlers::media::associate_ingredient_media)
+                .delete(handlers::media::dissociate_ingredient_media)
+                .layer(idempotent()),
+        )
+        .route(
+            "/{id}/recipe/{recipe_id}/step/{step_id}",
+            post(handlers::media::associate_step_media)
+                .delete(handlers::media::dissociate_step_media)
+                .layer(idempotent()),
+        );
+    // Lookup only, so still served in read-only mode
+    let lookups = Router::new().route("/batch-get", post(handlers::media::batch_get_media));
+
+    classes
+        .upload
+        .limit(uploads)
+        .merge(classes.read.limit(media))
+        .route_layer(from_fn(reject_writes_when_read_only(read_only)))
+        .merge(classes.read.limit(lookups))
+}
+
+/// Create the direct, presigned and resumable upload routes
+#[allow(deprecated)] // Still serves the legacy direct upload until it is switched off
+fn upload_routes(
+    legacy_upload: LegacyUploadPolicy,
+    idempotency_keys: &IdempotencyKeys,
+) -> Router<AppState> {
+    let idempotent = || from_fn(replay_idempotent_requests(idempotency_keys.clone()));
+    Router::new()
+        // Legacy direct upload endpoint (deprecated)
+        .route(
+            "/",
+            post(handlers::media::upload_media)
+                .layer(idempotent())
+                .layer(from_fn(deprecate_legacy_upload(legacy_upload)))
+                .layer(from_fn(track_upload_clients("direct"))),
+        )
+        // New presigned URL upload endpoints
+        .route(
+            "/upload-request",
+            post(handlers::media::initiate_upload)
+                .layer(idempotent())
+                .layer(from_fn(track_upload_clients("presigned"))),
+        )
+        .route(
+            "/upload/{token}",
+            put(handlers::media::upload_file).layer(from_fn(track_upload_clients("presigned"))),
+        )
+        // Resumable (tus) upload endpoints
+        .route(
+            "/uploads",
+            post(handlers::resumable_uploads::create_upload)
+                .layer(from_fn(track_upload_clients("resumable")))
+                .options(handlers::resumable_uploads::upload_options),
+        )
+        .route(
+            "/uploads/{id}",
+            patch(handlers::resumable_uploads::append_upload_chunk)
+                .layer(from_fn(track_upload_clients("resumable")))
+                .head(handlers::resumable_uploads::get_upload_offset),
+        )
 }
 
 #[cfg(test)]
@@ -50,6 +247,30 @@ mod tests {
     use super::*;
     use crate::infrastructure::storage::FileStorage;
 
+    /// A user with a valid token
+    fn signed_in_user() -> crate::presentation::middleware::auth::UserContext {
+        crate::presentation::middleware::auth::UserContext::from(
+            crate::presentation::middleware::auth::Claims::new_access_token(
+                "auth-service".to_string(),
+                vec!["media-service".to_string()],
+                uuid::Uuid::new_v4().to_string(),
+                "test-client".to_string(),
+                vec!["read".to_string()],
+                1,
+            ),
+        )
+    }
+
+    /// `app` as reached with the token of `user`, leaving out the auth middleware
+    fn signed_in(app: Router, user: crate::presentation::middleware::auth::UserContext) -> Router {
+        app.layer(axum::middleware::from_fn(
+            move |mut request: axum::extract::Request, next: axum::middleware::Next| {
+                request.extensions_mut().insert(user.clone());
+                next.run(request)
+            },
+        ))
+    }
+
     // Mock storage for route testing
     #[derive(Clone)]
     struct MockRoutesStorage {
@@ -64,14 +285,11 @@ mod tests {
 
     #[async_trait::async_trait]
     impl crate::infrastructure::storage::FileStorage for MockRoutesStorage {
-        async fn store<R>(
+        async fn store(
             &self,
             hash: &crate::domain::value_objects::ContentHash,
-            mut _reader: R,
-        ) -> Result<String, crate::infrastructure::storage::StorageError>
-        where
-            R: tokio::io::AsyncRead + Send + Unpin,
-        {
+            _reader: &mut (dyn tokio::io::AsyncRead + Send + Unpin),
+        ) -> Result<String, crate::infrastructure::storage::StorageError> {
             Ok(format!("{}/{}", self.base_path, hash.as_str()))
         }
 
@@ -128,8 +346,24 @@ mod tests {
     #[test]
     fn test_route_functions_exist() {
         // Test internal route functions
-        let media_routes = media_routes();
-        let media_mgmt_routes = media_management_routes();
+        let idempotency_keys = IdempotencyKeys::new(
+            std::sync::Arc::new(
+                crate::infrastructure::persistence::InMemoryIdempotencyRepository::new(),
+            ),
+            std::time::Duration::from_hours(24),
+        );
+        let media_routes = media_routes(
+            LegacyUploadPolicy::default(),
+            ReadOnlyMode::disabled(),
+            &idempotency_keys,
+            &RouteClasses::new(&RouteLimits::default(), &LoadSheddingPolicy::default()),
+        );
+        let media_mgmt_routes = media_management_routes(
+            LegacyUploadPolicy::default(),
+            ReadOnlyMode::disabled(),
+            &idempotency_keys,
+            &RouteClasses::new(&RouteLimits::default(), &LoadSheddingPolicy::default()),
+        );
 
         // Test that routes are created successfully (basic structure test)
         assert!(std::ptr::addr_of!(media_routes).is_aligned());
@@ -149,9 +383,9 @@ mod tests {
             "abcdef1234567890abcdef1234567890abcdef1234567890abcdef1234567890",
         )
         .unwrap();
-        let cursor = std::io::Cursor::new(b"test");
+        let mut cursor = std::io::Cursor::new(b"test");
 
-        let result = storage.store(&hash, cursor).await;
+        let result = storage.store(&hash, &mut cursor).await;
         assert!(result.is_ok());
         assert!(result.unwrap().contains(hash.as_str()));
     }
@@ -216,4 +450,607 @@ mod tests {
         let result = storage.metadata(&hash).await;
         assert!(result.is_err());
     }
+
+    #[tokio::test]
+    async fn test_association_routes_attach_and_detach_media() {
+        use crate::{
+            domain::{
+                entities::{MediaId, UnsavedMedia},
+                value_objects::{ContentHash, MediaType},
+            },
+            infrastructure::storage::{PresignedUrlConfig, PresignedUrlService},
+            test_utils::mocks::InMemoryMediaRepository,
+        };
+        use axum::{
+            body::Body,
+            http::{Method, Request, StatusCode},
+        };
+        use tower::ServiceExt;
+
+        let media = UnsavedMedia::new(
+            ContentHash::new(&"a".repeat(64)).unwrap(),
+            "step.jpg".to_string(),
+            MediaType::new("image/jpeg"),
+            "aa/aa/aa/aaaa".to_string(),
+            1024,
+            user_context(&[]).owner_id(),
+        )
+        .into_media(MediaId::new(5));
+        let app = create_routes(AppState::new(
+            std::sync::Arc::new(InMemoryMediaRepository::new().with_media(media)),
+            std::sync::Arc::new(MockRoutesStorage::new()),
+            PresignedUrlService::new(PresignedUrlConfig::default()),
+            1024,
+        ));
+        let send = |method: Method, uri: &str| {
+            let request = Request::builder()
+                .method(method)
+                .uri(uri)
+                .extension(user_context(&[]))
+                .body(Body::empty())
+                .unwrap();
+            app.clone().oneshot(request)
+        };
+        let association = "/api/v1/media-management/media/5/recipe/42/step/3";
+
+        assert_eq!(send(Method::POST, association).await.unwrap().status(), StatusCode::CREATED);
+        assert_eq!(send(Method::POST, association).await.unwrap().status(), StatusCode::OK);
+
+        let response =
+            send(Method::GET, "/api/v1/media-management/media/recipe/42/step/3?ids_only=true")
+                .await
+                .unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(&body[..], b"[5]");
+
+        assert_eq!(
+            send(Method::DELETE, association).await.unwrap().status(),
+            StatusCode::NO_CONTENT
+        );
+        assert_eq!(
+            send(Method::DELETE, association).await.unwrap().status(),
+            StatusCode::NOT_FOUND
+        );
+        assert_eq!(
+            send(Method::POST, "/api/v1/media-management/media/6/recipe/42")
+                .await
+                .unwrap()
+                .status(),
+            StatusCode::NOT_FOUND
+        );
+    }
+
+    #[tokio::test]
+    async fn test_batch_get_route_marks_missing_media() {
+        use crate::{
+            domain::{
+                entities::{MediaId, UnsavedMedia},
+                value_objects::{ContentHash, MediaType},
+            },
+            infrastructure::storage::{PresignedUrlConfig, PresignedUrlService},
+            test_utils::mocks::InMemoryMediaRepository,
+        };
+        use axum::{
+            body::Body,
+            http::{header, Method, Request, StatusCode},
+        };
+        use tower::ServiceExt;
+
+        let media = UnsavedMedia::new(
+            ContentHash::new(&"a".repeat(64)).unwrap(),
+            "cover.jpg".to_string(),
+            MediaType::new("image/jpeg"),
+            "aa/aa/aa/aaaa".to_string(),
+            1024,
+            user_context(&[]).owner_id(),
+        )
+        .into_media(MediaId::new(5));
+        let app = create_routes(AppState::new(
+            std::sync::Arc::new(InMemoryMediaRepository::new().with_media(media)),
+            std::sync::Arc::new(MockRoutesStorage::new()),
+            PresignedUrlService::new(PresignedUrlConfig::default()),
+            1024,
+        ));
+
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri("/api/v1/media-management/media/batch-get")
+            .header(header::CONTENT_TYPE, "application/json")
+            .extension(user_context(&[]))
+            .body(Body::from(r#"{"ids": [5, 6]}"#))
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["results"][0]["status"], "found");
+        assert_eq!(json["results"][0]["media"]["original_filename"], "cover.jpg");
+        assert_eq!(json["results"][1]["id"], 6);
+        assert_eq!(json["results"][1]["status"], "not_found");
+        assert!(json["results"][1].get("media").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_lookup_routes_send_cache_headers_with_surrogate_keys() {
+        use crate::{
+            domain::{
+                entities::{MediaId, UnsavedMedia},
+                value_objects::{CacheControlPolicy, ContentHash, MediaType},
+            },
+            infrastructure::storage::{PresignedUrlConfig, PresignedUrlService},
+            test_utils::mocks::InMemoryMediaRepository,
+        };
+        use axum::{
+            body::Body,
+            http::{header, Method, Request},
+        };
+        use std::time::Duration;
+        use tower::ServiceExt;
+
+        let media = UnsavedMedia::new(
+            ContentHash::new(&"a".repeat(64)).unwrap(),
+            "cover.jpg".to_string(),
+            MediaType::new("image/jpeg"),
+            "aa/aa/aa/aaaa".to_string(),
+            1024,
+            user_context(&[]).owner_id(),
+        )
+        .into_media(MediaId::new(5));
+        let app = create_routes(
+            AppState::new(
+                std::sync::Arc::new(InMemoryMediaRepository::new().with_media(media)),
+                std::sync::Arc::new(MockRoutesStorage::new()),
+                PresignedUrlService::new(PresignedUrlConfig::default()),
+                1024,
+            )
+            .with_cache_control(CacheControlPolicy {
+                metadata_max_age: Duration::from_secs(30),
+                shared_max_age: Some(Duration::from_hours(24)),
+                surrogate_keys: true,
+                ..CacheControlPolicy::default()
+            }),
+        );
+        let send = |method: Method, uri: &str| {
+            let request = Request::builder()
+                .method(method)
+                .uri(format!("/api/v1/media-management/media{uri}"))
+                .extension(user_context(&[]))
+                .body(Body::empty())
+                .unwrap();
+            app.clone().oneshot(request)
+        };
+        let header = |response: &axum::response::Response, name: &str| {
+            response.headers().get(name).map(|v| v.to_str().unwrap().to_string())
+        };
+
+        let response = send(Method::GET, "/5").await.unwrap();
+        assert_eq!(
+            header(&response, header::CACHE_CONTROL.as_str()).as_deref(),
+            Some("private, max-age=30")
+        );
+        assert_eq!(header(&response, "surrogate-key").as_deref(), Some("media-5"));
+
+        send(Method::POST, "/5/recipe/42").await.unwrap();
+        let response = send(Method::GET, "/recipe/42?ids_only=true").await.unwrap();
+        assert_eq!(
+            header(&response, header::CACHE_CONTROL.as_str()).as_deref(),
+            Some("public, max-age=30, s-maxage=86400")
+        );
+        assert_eq!(header(&response, "surrogate-key").as_deref(), Some("media-5 recipe-42"));
+        assert_eq!(header(&response, "cache-tag").as_deref(), Some("media-5,recipe-42"));
+    }
+
+    #[tokio::test]
+    async fn test_read_only_mode_rejects_writes_but_serves_lookups() {
+        use crate::{
+            infrastructure::storage::{PresignedUrlConfig, PresignedUrlService},
+            test_utils::mocks::InMemoryMediaRepository,
+        };
+        use axum::{
+            body::Body,
+            http::{header, Method, Request, StatusCode},
+        };
+        use tower::ServiceExt;
+
+        let app = create_routes(
+            AppState::new(
+                std::sync::Arc::new(InMemoryMediaRepository::new()),
+                std::sync::Arc::new(MockRoutesStorage::new()),
+                PresignedUrlService::new(PresignedUrlConfig::default()),
+                1024,
+            )
+            .with_read_only(ReadOnlyMode::new(true)),
+        );
+        let send = |method: Method, uri: &str, body: &'static str| {
+            let request = Request::builder()
+                .method(method)
+                .uri(format!("/api/v1/media-management{uri}"))
+                .header(header::CONTENT_TYPE, "application/json")
+                .extension(user_context(&[ADMIN_SCOPE]))
+                .body(Body::from(body))
+                .unwrap();
+            app.clone().oneshot(request)
+        };
+
+        let status = |response: Result<axum::response::Response, _>| response.unwrap().status();
+        assert_eq!(
+            status(send(Method::DELETE, "/media/5", "").await),
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+        assert_eq!(
+            status(send(Method::POST, "/media/5/recipe/42", "").await),
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+        assert_eq!(status(send(Method::GET, "/media/5", "").await), StatusCode::NOT_FOUND);
+        assert_eq!(
+            status(send(Method::POST, "/media/batch-get", r#"{"ids": [5]}"#).await),
+            StatusCode::OK
+        );
+
+        assert_eq!(
+            status(send(Method::PUT, "/admin/read-only", r#"{"read_only": false}"#).await),
+            StatusCode::OK
+        );
+        assert_eq!(status(send(Method::DELETE, "/media/5", "").await), StatusCode::NOT_FOUND);
+    }
+
+    /// The context the auth middleware inserts for a token with `scopes`
+    fn user_context(scopes: &[&str]) -> crate::presentation::middleware::UserContext {
+        crate::presentation::middleware::Claims::new_access_token(
+            "auth-service".to_string(),
+            vec!["media-management-service".to_string()],
+            "user-1".to_string(),
+            "web-client".to_string(),
+            scopes.iter().map(ToString::to_string).collect(),
+            1,
+        )
+        .into()
+    }
+
+    #[tokio::test]
+    async fn test_admin_routes_need_the_admin_scope() {
+        use crate::{
+            infrastructure::storage::{PresignedUrlConfig, PresignedUrlService},
+            test_utils::mocks::InMemoryMediaRepository,
+        };
+        use axum::{
+            body::Body,
+            http::{header, Method, Request, StatusCode},
+        };
+        use tower::ServiceExt;
+
+        let app = create_routes(AppState::new(
+            std::sync::Arc::new(InMemoryMediaRepository::new()),
+            std::sync::Arc::new(MockRoutesStorage::new()),
+            PresignedUrlService::new(PresignedUrlConfig::default()),
+            1024,
+        ));
+        let send = |method: Method, uri: &str, body: &'static str, scopes: Option<&[&str]>| {
+            let mut request = Request::builder()
+                .method(method)
+                .uri(format!("/api/v1/media-management{uri}"))
+                .header(header::CONTENT_TYPE, "application/json");
+            if let Some(scopes) = scopes {
+                request = request.extension(user_context(scopes));
+            }
+            app.clone().oneshot(request.body(Body::from(body)).unwrap())
+        };
+
+        let admin_requests = [
+            (Method::PUT, "/admin/read-only", r#"{"read_only": false}"#),
+            (Method::POST, "/admin/backfills/variants", r#"{"kind": "webp", "status": "missing"}"#),
+            (Method::GET, "/admin/reports/capacity?weeks=4", ""),
+            (Method::GET, "/admin/webhooks/deliveries", ""),
+            (Method::DELETE, "/admin/render-cache?media_id=1", ""),
+        ];
+        for (method, uri, body) in admin_requests {
+            let anonymous = send(method.clone(), uri, body, None).await.unwrap();
+            assert_eq!(anonymous.status(), StatusCode::UNAUTHORIZED, "{method} {uri}");
+            let reader = send(method.clone(), uri, body, Some(&["media:read"])).await.unwrap();
+            assert_eq!(reader.status(), StatusCode::FORBIDDEN, "{method} {uri}");
+        }
+
+        let admin =
+            send(Method::PUT, "/admin/read-only", r#"{"read_only": true}"#, Some(&[ADMIN_SCOPE]));
+        assert_eq!(admin.await.unwrap().status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_body_limits_follow_the_route_class() {
+        use crate::{
+            infrastructure::storage::{InMemoryStorage, PresignedUrlConfig, PresignedUrlService},
+            test_utils::mocks::InMemoryMediaRepository,
+        };
+        use axum::{
+            body::Body,
+            http::{header, Method, Request, StatusCode},
+        };
+        use std::time::Duration;
+        use tower::ServiceExt;
+
+        let small = RouteLimit { timeout: Duration::from_secs(30), max_body_size: 16 };
+        let app = create_routes(
+            AppState::new(
+                std::sync::Arc::new(InMemoryMediaRepository::new()),
+                std::sync::Arc::new(InMemoryStorage::new()),
+                PresignedUrlService::new(PresignedUrlConfig::default()),
+                1024,
+            )
+            .with_route_limits(RouteLimits {
+                read: small,
+                admin: small,
+                ..RouteLimits::default()
+            }),
+        );
+        let send = |method: Method, uri: &str, body: &'static str| {
+            let request = Request::builder()
+                .method(method)
+                .uri(format!("/api/v1/media-management{uri}"))
+                .header(header::CONTENT_TYPE, "application/json")
+                .extension(user_context(&[ADMIN_SCOPE]))
+                .body(Body::from(body))
+                .unwrap();
+            app.clone().oneshot(request)
+        };
+        let status = |response: Result<axum::response::Response, _>| response.unwrap().status();
+
+        assert_eq!(
+            status(send(Method::POST, "/media/batch-get", r#"{"ids": [1, 2, 3, 4, 5]}"#).await),
+            StatusCode::PAYLOAD_TOO_LARGE
+        );
+        assert_eq!(
+            status(send(Method::PUT, "/admin/read-only", r#"{"read_only": false}"#).await),
+            StatusCode::PAYLOAD_TOO_LARGE
+        );
+        assert_eq!(
+            status(
+                send(
+                    Method::POST,
+                    "/media/upload-request",
+                    r#"{"filename": "notes.txt", "content_type": "text/plain", "file_size": 11}"#
+                )
+                .await
+            ),
+            StatusCode::OK
+        );
+    }
+
+    #[tokio::test]
+    async fn test_head_download_sends_the_headers_of_get_without_content() {
+        use crate::{
+            domain::{
+                entities::{MediaId, UnsavedMedia},
+                value_objects::{ContentHash, MediaType, ProcessingStatus},
+            },
+            infrastructure::storage::{InMemoryStorage, PresignedUrlConfig, PresignedUrlService},
+            test_utils::mocks::InMemoryMediaRepository,
+        };
+
+        let user = signed_in_user();
+        let hash = ContentHash::new(&"b".repeat(64)).unwrap();
+        let storage = InMemoryStorage::new();
+        storage.store(&hash, &mut &b"hello world"[..]).await.unwrap();
+        let mut media = UnsavedMedia::new(
+            hash,
+            "notes.txt".to_string(),
+            MediaType::new("text/plain"),
+            "bb/bb/bb/bbbb".to_string(),
+            11,
+            user.owner_id(),
+        )
+        .into_media(MediaId::new(7));
+        media.processing_status = ProcessingStatus::Complete;
+        let app = create_routes(AppState::new(
+            std::sync::Arc::new(InMemoryMediaRepository::new().with_media(media)),
+            std::sync::Arc::new(storage),
+            PresignedUrlService::new(PresignedUrlConfig::default()),
+            1024,
+        ));
+        let app = signed_in(app, user);
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!(
+            "http://{}/api/v1/media-management/media/7/download",
+            listener.local_addr().unwrap()
+        );
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        let client = reqwest::Client::new();
+
+        let head = client.head(&url).send().await.unwrap();
+        let get = client.get(&url).send().await.unwrap();
+
+        assert_eq!(head.status(), reqwest::StatusCode::OK);
+        for name in ["content-length", "content-type", "etag", "cache-control"] {
+            assert!(head.headers().contains_key(name), "HEAD should send {name}");
+            assert_eq!(head.headers()[name], get.headers()[name], "{name}");
+        }
+        assert_eq!(head.headers()["content-length"], "11");
+        assert_eq!(head.headers()["etag"], format!("\"{}\"", "b".repeat(64)).as_str());
+        assert!(head.bytes().await.unwrap().is_empty());
+        assert_eq!(get.bytes().await.unwrap(), "hello world");
+    }
+
+    #[tokio::test]
+    async fn test_download_by_hash_is_cached_as_immutable() {
+        use crate::{
+            domain::{
+                entities::{MediaId, UnsavedMedia},
+                value_objects::{ContentHash, MediaType, ProcessingStatus},
+            },
+            infrastructure::storage::{InMemoryStorage, PresignedUrlConfig, PresignedUrlService},
+            test_utils::mocks::InMemoryMediaRepository,
+        };
+        use axum::{
+            body::Body,
+            http::{header, Request, StatusCode},
+        };
+        use tower::ServiceExt;
+
+        let hash = "c".repeat(64);
+        let storage = InMemoryStorage::new();
+        let content_hash = ContentHash::new(&hash).unwrap();
+        storage.store(&content_hash, &mut &b"hello world"[..]).await.unwrap();
+        let user = signed_in_user();
+        let mut media = UnsavedMedia::new(
+            content_hash,
+            "notes.txt".to_string(),
+            MediaType::new("text/plain"),
+            "cc/cc/cc/cccc".to_string(),
+            11,
+            user.owner_id(),
+        )
+        .into_media(MediaId::new(9));
+        media.processing_status = ProcessingStatus::Complete;
+        let anonymous = create_routes(AppState::new(
+            std::sync::Arc::new(InMemoryMediaRepository::new().with_media(media)),
+            std::sync::Arc::new(storage),
+            PresignedUrlService::new(PresignedUrlConfig::default()),
+            1024,
+        ));
+        let app = signed_in(anonymous.clone(), user);
+        let get = |path: String| {
+            let uri = format!("/api/v1/media-management/media/by-hash/{path}");
+            app.clone().oneshot(Request::get(uri).body(Body::empty()).unwrap())
+        };
+
+        let response = get(hash.clone()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[header::CACHE_CONTROL],
+            "private, max-age=31536000, immutable"
+        );
+        assert_eq!(response.headers()[header::ETAG], format!("\"{hash}\"").as_str());
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(&body[..], b"hello world");
+
+        assert_eq!(get(format!("{hash}.webp")).await.unwrap().status(), StatusCode::NOT_FOUND);
+        assert_eq!(get("not-a-hash".to_string()).await.unwrap().status(), StatusCode::BAD_REQUEST);
+
+        let uri = format!("/api/v1/media-management/media/by-hash/{hash}");
+        let response =
+            anonymous.oneshot(Request::get(uri).body(Body::empty()).unwrap()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_presigned_upload_survives_client_disconnect() {
+        use crate::{
+            infrastructure::storage::{InMemoryStorage, PresignedUrlConfig, PresignedUrlService},
+            test_utils::mocks::InMemoryMediaRepository,
+        };
+        use axum::{
+            body::Body,
+            http::{header, Request, StatusCode},
+        };
+        use tower::ServiceExt;
+
+        let app = create_routes(AppState::new(
+            std::sync::Arc::new(InMemoryMediaRepository::new()),
+            std::sync::Arc::new(InMemoryStorage::new()),
+            PresignedUrlService::new(PresignedUrlConfig::default()),
+            1024,
+        ));
+
+        let request = Request::post("/api/v1/media-management/media/upload-request")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(
+                r#"{"filename": "notes.txt", "content_type": "text/plain", "file_size": 11}"#,
+            ))
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let upload_url = json["upload_url"].as_str().unwrap();
+        let upload_path = &upload_url[upload_url.find("/api/").unwrap()..];
+
+        let chunks = futures_util::stream::iter([
+            Ok(b"hello ".to_vec()),
+            Err(std::io::Error::from(std::io::ErrorKind::ConnectionReset)),
+        ]);
+        let request = Request::put(upload_path).body(Body::from_stream(chunks)).unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        // The session was not consumed, so the client can upload again
+        let request = Request::put(upload_path).body(Body::from("hello world")).unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_presigned_upload_follows_the_tenant_format_policy() {
+        use crate::{
+            domain::value_objects::FormatPolicy,
+            infrastructure::storage::{InMemoryStorage, PresignedUrlConfig, PresignedUrlService},
+            test_utils::{images::create_test_png, mocks::InMemoryMediaRepository},
+        };
+        use axum::{
+            body::Body,
+            http::{header, Request, StatusCode},
+        };
+        use std::collections::HashMap;
+        use tower::ServiceExt;
+
+        let app = create_routes(
+            AppState::new(
+                std::sync::Arc::new(InMemoryMediaRepository::new()),
+                std::sync::Arc::new(InMemoryStorage::new()),
+                PresignedUrlService::new(PresignedUrlConfig::default()),
+                1024 * 1024,
+            )
+            .with_format_policy(FormatPolicy::new(HashMap::from([(
+                "web-client".to_string(),
+                vec!["image/webp".to_string()],
+            )]))),
+        );
+
+        // Initiates a presigned upload as the restricted tenant, then uploads `data`
+        let upload = |filename: &str, content_type: &str, data: Vec<u8>| {
+            let app = app.clone();
+            let request = Request::post("/api/v1/media-management/media/upload-request")
+                .header(header::CONTENT_TYPE, "application/json")
+                .extension(user_context(&[]))
+                .body(Body::from(
+                    serde_json::json!({
+                        "filename": filename,
+                        "content_type": content_type,
+                        "file_size": data.len(),
+                    })
+                    .to_string(),
+                ))
+                .unwrap();
+            async move {
+                let response = app.clone().oneshot(request).await.unwrap();
+                assert_eq!(response.status(), StatusCode::OK);
+                let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+                let upload_url = json["upload_url"].as_str().unwrap();
+                let upload_path = &upload_url[upload_url.find("/api/").unwrap()..];
+
+                let request = Request::put(upload_path).body(Body::from(data)).unwrap();
+                app.oneshot(request).await.unwrap()
+            }
+        };
+
+        let response = upload("photo.png", "image/png", create_test_png()).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let media_id = json["media_id"].as_i64().unwrap();
+
+        let request = Request::get(format!("/api/v1/media-management/media/{media_id}"))
+            .extension(user_context(&[]))
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["media_type"], "image/webp");
+
+        // Content that cannot be converted to an allowed format is rejected
+        let response = upload("notes.txt", "text/plain", b"hello world".to_vec()).await;
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    }
 }
@@ -16,7 +16,7 @@ mock! {
 
         async fn save(&self, media: &Media) -> Result<MediaId, Self::Error>;
         async fn find_by_id(&self, id: MediaId) -> Result<Option<Media>, Self::Error>;
-        async fn find_by_content_hash(&self, hash: &ContentHash) -> Result<Option<Media>, Self::Error>;
+        async fn find_by_content_hash(&self, hash: &ContentHash, owner: Option<UserId>) -> Result<Option<Media>, Self::Error>;
         async fn find_by_user(&self, user_id: UserId) -> Result<Vec<Media>, Self::Error>;
         async fn update(&self, media: &Media) -> Result<(), Self::Error>;
         async fn delete(&self, id: MediaId) -> Result<bool, Self::Error>;
@@ -79,11 +79,16 @@ impl MediaRepository for InMemoryMediaRepository {
         Ok(storage.get(&id).cloned())
     }
 
-    async fn find_by_content_hash(&self, hash: &ContentHash) -> Result<Option<Media>, Self::Error> {
+    async fn find_by_content_hash(
+        &self,
+        hash: &ContentHash,
+        owner: Option<UserId>,
+    ) -> Result<Option<Media>, Self::Error> {
         let storage = self.storage.lock().unwrap();
         Ok(storage
             .values()
-            .find(|media| media.content_hash == *hash)
+            .filter(|media| media.content_hash == *hash)
+            .find(|media| owner.is_none_or(|owner| media.uploaded_by == owner))
             .cloned())
     }
 
@@ -0,0 +1,363 @@
+//! Provider verification against consumer contracts
+//!
+//! Every `*.json` file in `tests/contracts` is a Pact (v2) file published by a
+//! consumer of this service. Each interaction is replayed against the router,
+//! backed by in-memory repositories seeded for the interaction's provider state,
+//! and the response is checked against the contract's status, headers and body.
+//!
+//! Body matching follows Pact semantics: values must be equal unless a matching
+//! rule applies, objects may carry extra fields, and rules cascade to children.
+//! The supported rules are `type` (with an optional `min` for arrays) and `regex`.
+use std::{path::Path, sync::Arc, time::SystemTime};
+
+use axum::{
+    body::Body,
+    extract::Request,
+    http::{header, HeaderName, Method, StatusCode},
+    middleware::{self, Next},
+    response::Response,
+    Router,
+};
+use media_management_service::{
+    domain::{
+        entities::{IngredientId, Media, MediaId, RecipeId, StepId, UserId},
+        value_objects::{ContentHash, MediaType, ProcessingStatus},
+    },
+    infrastructure::storage::{
+        FileStorage, InMemoryStorage, PresignedUrlConfig, PresignedUrlService,
+    },
+    presentation::{handlers::media::AppState, middleware::auth::UserContext, routes},
+    test_utils::mocks::InMemoryMediaRepository,
+};
+use regex::Regex;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+use tower::ServiceExt;
+use uuid::Uuid;
+
+const CONTRACTS_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/contracts");
+
+/// Subject of the user the consumer's bearer token stands for
+const CONSUMER_SUBJECT: &str = "7d0f5c3e-2b1a-4c6e-9f8d-1a2b3c4d5e6f";
+
+const MEDIA_CONTENT: &[u8] = b"hello world";
+
+#[derive(Debug, Deserialize)]
+struct Pact {
+    consumer: Participant,
+    interactions: Vec<Interaction>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Participant {
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Interaction {
+    description: String,
+    provider_state: Option<String>,
+    request: ContractRequest,
+    response: ContractResponse,
+}
+
+#[derive(Debug, Deserialize)]
+struct ContractRequest {
+    method: String,
+    path: String,
+    query: Option<String>,
+    #[serde(default)]
+    headers: Map<String, Value>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ContractResponse {
+    status: u16,
+    #[serde(default)]
+    headers: Map<String, Value>,
+    body: Option<Value>,
+    #[serde(default)]
+    matching_rules: Map<String, Value>,
+}
+
+/// A matching rule with the path pattern it applies to
+struct Rule {
+    path: Regex,
+    matcher: Matcher,
+}
+
+#[derive(Clone)]
+enum Matcher {
+    Type { min: Option<usize> },
+    Regex(Regex),
+}
+
+fn parse_rules(rules: &Map<String, Value>) -> Vec<Rule> {
+    rules
+        .iter()
+        .map(|(path, rule)| {
+            let pattern =
+                regex::escape(path).replace(r"\[\*\]", r"\[\d+\]").replace(r"\.\*", r"\.[^.\[]+");
+            let matcher = match rule["match"].as_str() {
+                Some("regex") => {
+                    Matcher::Regex(Regex::new(rule["regex"].as_str().expect("regex rule")).unwrap())
+                }
+                _ => Matcher::Type { min: rule["min"].as_u64().map(|min| min as usize) },
+            };
+            Rule { path: Regex::new(&format!("^{pattern}$")).unwrap(), matcher }
+        })
+        .collect()
+}
+
+/// Compare `actual` to `expected` at `path`, collecting every mismatch
+fn check(
+    expected: &Value,
+    actual: &Value,
+    path: &str,
+    rules: &[Rule],
+    inherited: Option<&Matcher>,
+    mismatches: &mut Vec<String>,
+) {
+    let own = rules.iter().find(|rule| rule.path.is_match(path)).map(|rule| &rule.matcher);
+    let matcher = own.or(inherited);
+
+    match (expected, actual) {
+        (Value::Object(expected), Value::Object(actual)) => {
+            for (key, expected) in expected {
+                let child = format!("{path}.{key}");
+                match actual.get(key) {
+                    Some(actual) => check(expected, actual, &child, rules, matcher, mismatches),
+                    None => mismatches.push(format!("{child}: missing")),
+                }
+            }
+        }
+        (Value::Array(expected), Value::Array(actual)) => match matcher {
+            Some(Matcher::Type { min }) => {
+                let min = min.unwrap_or(0);
+                if actual.len() < min {
+                    mismatches.push(format!(
+                        "{path}: expected at least {min} items, got {}",
+                        actual.len()
+                    ));
+                }
+                if let Some(template) = expected.first() {
+                    for (index, actual) in actual.iter().enumerate() {
+                        let child = format!("{path}[{index}]");
+                        check(template, actual, &child, rules, matcher, mismatches);
+                    }
+                }
+            }
+            _ if expected.len() != actual.len() => mismatches.push(format!(
+                "{path}: expected {} items, got {}",
+                expected.len(),
+                actual.len()
+            )),
+            _ => {
+                for (index, (expected, actual)) in expected.iter().zip(actual).enumerate() {
+                    let child = format!("{path}[{index}]");
+                    check(expected, actual, &child, rules, matcher, mismatches);
+                }
+            }
+        },
+        (expected, actual) => match matcher {
+            Some(Matcher::Regex(regex)) => match actual.as_str() {
+                Some(value) if regex.is_match(value) => {}
+                _ => mismatches.push(format!("{path}: {actual} does not match /{regex}/")),
+            },
+            Some(Matcher::Type { .. }) => {
+                if std::mem::discriminant(expected) != std::mem::discriminant(actual) {
+                    mismatches
+                        .push(format!("{path}: expected the type of {expected}, got {actual}"));
+                }
+            }
+            None => {
+                if expected != actual {
+                    mismatches.push(format!("{path}: expected {expected}, got {actual}"));
+                }
+            }
+        },
+    }
+}
+
+fn media(id: i64, owner: UserId) -> Media {
+    let hash = hex::encode(Sha256::digest(MEDIA_CONTENT));
+    Media::with_id(
+        MediaId::new(id),
+        ContentHash::new(&hash).unwrap(),
+        "pancakes.jpg".to_string(),
+        MediaType::new("image/jpeg"),
+        format!("{}/{}/{}/{hash}", &hash[0..2], &hash[2..4], &hash[4..6]),
+        MEDIA_CONTENT.len() as u64,
+        ProcessingStatus::Complete,
+    )
+    .uploaded_by(owner)
+    .uploaded_at(SystemTime::UNIX_EPOCH)
+    .updated_at(SystemTime::UNIX_EPOCH)
+    .build()
+}
+
+/// Build the provider with the data a provider state describes
+async fn provider(state: Option<&str>) -> Router {
+    let owner = UserId::from_uuid(Uuid::parse_str(CONSUMER_SUBJECT).unwrap());
+    let storage = Arc::new(InMemoryStorage::new());
+    let recipe = RecipeId::new(42);
+
+    let repository = match state {
+        Some("the caller has uploaded media 1") => {
+            let media = media(1, owner);
+            storage.store(&media.content_hash, &mut &MEDIA_CONTENT[..]).await.unwrap();
+            InMemoryMediaRepository::new().with_media(media)
+        }
+        Some("recipe 42 has media 1 and 2") => InMemoryMediaRepository::new()
+            .with_recipe_media(recipe, vec![MediaId::new(1), MediaId::new(2)]),
+        Some("ingredient 7 of recipe 42 has media 1") => InMemoryMediaRepository::new()
+            .with_recipe_ingredient_media(recipe, IngredientId::new(7), vec![MediaId::new(1)]),
+        Some("step 3 of recipe 42 has media 2") => InMemoryMediaRepository::new()
+            .with_recipe_step_media(recipe, StepId::new(3), vec![MediaId::new(2)]),
+        Some("recipe 99 has no media" | "media 999 does not exist") | None => {
+            InMemoryMediaRepository::new()
+        }
+        Some(unknown) => panic!("unknown provider state: {unknown}"),
+    };
+
+    let app_state = AppState::new(
+        Arc::new(repository),
+        storage,
+        PresignedUrlService::new(PresignedUrlConfig::default()),
+        10 * 1024 * 1024,
+    );
+    routes::create_routes(app_state).layer(middleware::from_fn(authenticate_consumer))
+}
+
+/// Request filter standing in for token validation
+///
+/// Contracts carry a placeholder bearer token; any bearer token is accepted as
+/// the consumer's user so interactions exercise the authenticated code paths.
+async fn authenticate_consumer(mut request: Request, next: Next) -> Response {
+    if request.headers().contains_key(header::AUTHORIZATION) {
+        request.extensions_mut().insert(UserContext {
+            user_id: Some(CONSUMER_SUBJECT.to_string()),
+            client_id: "recipe-service".to_string(),
+            subject: CONSUMER_SUBJECT.to_string(),
+            scopes: vec!["media:read".to_string()],
+            token_type: "access_token".to_string(),
+            token_id: "contract-test".to_string(),
+            issuer: "auth-service".to_string(),
+            audience: vec!["media-management-service".to_string()],
+        });
+    }
+    next.run(request).await
+}
+
+/// Replay one interaction, returning every way the response breaks the contract
+async fn verify(interaction: &Interaction) -> Vec<String> {
+    let router = provider(interaction.provider_state.as_deref()).await;
+
+    let request = &interaction.request;
+    let uri = match &request.query {
+        Some(query) => format!("{}?{query}", request.path),
+        None => request.path.clone(),
+    };
+    let mut builder =
+        Request::builder().method(Method::from_bytes(request.method.as_bytes()).unwrap()).uri(uri);
+    for (name, value) in &request.headers {
+        builder = builder.header(name.as_str(), value.as_str().unwrap());
+    }
+    let response = router.oneshot(builder.body(Body::empty()).unwrap()).await.unwrap();
+
+    let expected = &interaction.response;
+    let rules = parse_rules(&expected.matching_rules);
+    let mut mismatches = Vec::new();
+
+    let status = StatusCode::from_u16(expected.status).unwrap();
+    if response.status() != status {
+        mismatches.push(format!("status: expected {status}, got {}", response.status()));
+    }
+
+    for (name, value) in &expected.headers {
+        let expected_value = Value::String(value.as_str().unwrap().to_string());
+        let actual_value = response
+            .headers()
+            .get(HeaderName::from_bytes(name.as_bytes()).unwrap())
+            .and_then(|value| value.to_str().ok());
+        match actual_value {
+            Some(actual) => check(
+                &expected_value,
+                &Value::String(actual.to_string()),
+                &format!("$.headers.{name}"),
+                &rules,
+                None,
+                &mut mismatches,
+            ),
+            None => mismatches.push(format!("$.headers.{name}: missing")),
+        }
+    }
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    if let Some(expected_body) = &expected.body {
+        match serde_json::from_slice::<Value>(&body) {
+            Ok(actual) => check(expected_body, &actual, "$.body", &rules, None, &mut mismatches),
+            Err(e) => mismatches.push(format!("$.body: not JSON: {e}")),
+        }
+    }
+
+    mismatches
+}
+
+fn load_pacts() -> Vec<Pact> {
+    let mut paths: Vec<_> = std::fs::read_dir(Path::new(CONTRACTS_DIR))
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    paths.sort();
+
+    paths
+        .iter()
+        .map(|path| {
+            let contents = std::fs::read_to_string(path).unwrap();
+            serde_json::from_str(&contents)
+                .unwrap_or_else(|e| panic!("{} is not a valid pact: {e}", path.display()))
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_provider_honours_consumer_contracts() {
+    let pacts = load_pacts();
+    assert!(!pacts.is_empty(), "no contracts found in {CONTRACTS_DIR}");
+
+    let mut failures = Vec::new();
+    for pact in &pacts {
+        for interaction in &pact.interactions {
+            for mismatch in verify(interaction).await {
+                failures.push(format!(
+                    "{} - {}: {mismatch}",
+                    pact.consumer.name, interaction.description
+                ));
+            }
+        }
+    }
+
+    assert!(failures.is_empty(), "contract verification failed:\n{}", failures.join("\n"));
+}
+
+#[test]
+fn test_type_rule_cascades_and_checks_min() {
+    let rules = parse_rules(
+        serde_json::json!({ "$.body.items": { "match": "type", "min": 2 } }).as_object().unwrap(),
+    );
+    let expected = serde_json::json!({ "items": [{ "id": 1 }] });
+    let mut mismatches = Vec::new();
+
+    let actual = serde_json::json!({ "items": [{ "id": 5 }, { "id": 6, "extra": true }] });
+    check(&expected, &actual, "$.body", &rules, None, &mut mismatches);
+    assert!(mismatches.is_empty(), "{mismatches:?}");
+
+    let actual = serde_json::json!({ "items": [{ "id": "5" }] });
+    check(&expected, &actual, "$.body", &rules, None, &mut mismatches);
+    assert_eq!(mismatches.len(), 2, "{mismatches:?}");
+}
@@ -79,6 +79,10 @@ async fn test_paginated_media_query_structure() {
         cursor: Some("eyJpZCI6MTIzfQ==".to_string()),
         limit: Some(50),
         status: None,
+        include: None,
+        sort_by: None,
+        order: None,
+        tags: None,
     };
 
     // Validate query can be created and accessed
@@ -166,12 +170,28 @@ async fn test_pagination_limit_constraints() {
     // Valid limits
     let valid_limits = [1, 25, 50, 100];
     for limit in valid_limits {
-        let query = PaginatedMediaQuery { cursor: None, limit: Some(limit), status: None };
+        let query = PaginatedMediaQuery {
+            cursor: None,
+            limit: Some(limit),
+            status: None,
+            include: None,
+            sort_by: None,
+            order: None,
+            tags: None,
+        };
         assert_eq!(query.limit, Some(limit));
     }
 
     // Test default behavior when no limit specified
-    let query_no_limit = PaginatedMediaQuery { cursor: None, limit: None, status: None };
+    let query_no_limit = PaginatedMediaQuery {
+        cursor: None,
+        limit: None,
+        status: None,
+        include: None,
+        sort_by: None,
+        order: None,
+        tags: None,
+    };
     assert!(query_no_limit.limit.is_none());
 
     // This documents the expected limit behavior:
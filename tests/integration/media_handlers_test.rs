@@ -243,6 +243,7 @@ async fn test_initiate_upload_handler() {
         filename: "test.jpg".to_string(),
         content_type: "image/jpeg".to_string(),
         file_size: 1024 * 1024, // 1MB
+        content_hash_prefix: None,
     };
 
     let response = app.post("/media/upload-request").json(&initiate_request).await;
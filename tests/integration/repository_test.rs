@@ -36,7 +36,7 @@ async fn test_find_by_content_hash() {
 
     repo.save(&media).await.unwrap();
 
-    let found = repo.find_by_content_hash(&hash).await.unwrap();
+    let found = repo.find_by_content_hash(&hash, None).await.unwrap();
     assert!(found.is_some());
     assert_eq!(found.unwrap().content_hash, hash);
 }
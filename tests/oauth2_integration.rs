@@ -1,8 +1,15 @@
 use anyhow::Result;
+use axum::http::{header::AUTHORIZATION, HeaderMap, HeaderValue};
 use chrono::{Duration, Utc};
-use media_management_service::infrastructure::{
-    config::OAuth2Config,
-    oauth2::{CachedClientToken, CachedTokenInfo, OAuth2Client, OAuth2Error},
+use media_management_service::{
+    infrastructure::{
+        config::{AuthConfig, OAuth2Config},
+        oauth2::{CachedClientToken, CachedTokenInfo, OAuth2Client, OAuth2Error},
+    },
+    presentation::middleware::{
+        auth::{AuthPolicy, JwtService},
+        error::AppError,
+    },
 };
 use serde_json::json;
 use wiremock::{
@@ -15,10 +22,11 @@ fn create_test_oauth2_config(base_url: &str) -> OAuth2Config {
         enabled: true,
         service_to_service_enabled: true,
         introspection_enabled: true,
+        jwt_fallback_enabled: true,
         client_id: "test-client-id".to_string(),
-        client_secret: "test-client-secret".to_string(),
+        client_secret: "test-client-secret".into(),
         service_base_url: base_url.to_string(),
-        jwt_secret: "test-jwt-secret-at-least-32-chars-long".to_string(), // gitleaks:allow
+        jwt_secret: "test-jwt-secret-at-least-32-chars-long".into(), // gitleaks:allow
         token_cache_ttl_seconds: 300,
         client_credentials_cache_ttl_seconds: 1800,
         request_timeout_seconds: 10,
@@ -519,3 +527,145 @@ async fn test_client_credentials_caching_behavior() -> Result<()> {
 
     Ok(())
 }
+
+const TEST_JWT_SECRET: &str = "test-auth-secret"; // gitleaks:allow
+
+fn create_test_auth_policy(base_url: &str, jwt_fallback_enabled: bool) -> Result<AuthPolicy> {
+    let auth_config = AuthConfig {
+        enabled: true,
+        jwt_secret: TEST_JWT_SECRET.into(),
+        jwt_expiry_hours: 1,
+        jwt_audience: None,
+        require_auth_routes: vec!["/api".to_string()],
+        optional_auth_routes: Vec::new(),
+    };
+    let mut oauth2_config = create_test_oauth2_config(base_url);
+    oauth2_config.jwt_fallback_enabled = jwt_fallback_enabled;
+    oauth2_config.max_retries = 0;
+
+    let client = OAuth2Client::new(oauth2_config.clone())?;
+    Ok(AuthPolicy::from_config(&auth_config).with_introspection(client, &oauth2_config))
+}
+
+fn bearer(token: &str) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert(AUTHORIZATION, HeaderValue::from_str(&format!("Bearer {token}")).unwrap());
+    headers
+}
+
+#[tokio::test]
+async fn test_auth_policy_accepts_active_opaque_token() -> Result<()> {
+    let mock_server = MockServer::start().await;
+
+    // The second authentication is served from the token cache
+    Mock::given(method("POST"))
+        .and(path("/oauth2/introspect"))
+        .and(body_string_contains("token=opaque-token"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "active": true,
+            "client_id": "recipe-web",
+            "username": "user-123",
+            "scope": "media:read media:write",
+            "sub": "user-123",
+            "aud": ["media-service"],
+            "iss": "auth-service"
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let policy = create_test_auth_policy(&mock_server.uri(), false)?;
+
+    for _ in 0..2 {
+        let user = policy.authenticate(&bearer("opaque-token")).await.unwrap().unwrap();
+        assert_eq!(user.subject, "user-123");
+        assert_eq!(user.client_id, "recipe-web");
+        assert!(user.has_scope("media:write"));
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_auth_policy_rejects_inactive_token() -> Result<()> {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/oauth2/introspect"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "active": false })))
+        .mount(&mock_server)
+        .await;
+
+    let policy = create_test_auth_policy(&mock_server.uri(), true)?;
+
+    for _ in 0..2 {
+        let result = policy.authenticate(&bearer("revoked-token")).await;
+        assert!(matches!(result, Err(AppError::Authentication { .. })));
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_auth_policy_falls_back_to_jwt_when_introspection_unavailable() -> Result<()> {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/oauth2/introspect"))
+        .respond_with(ResponseTemplate::new(503))
+        .mount(&mock_server)
+        .await;
+
+    let token = JwtService::new(TEST_JWT_SECRET).create_access_token(
+        "auth-service".to_string(),
+        vec!["media-service".to_string()],
+        "user-456".to_string(),
+        "recipe-web".to_string(),
+        vec!["media:read".to_string()],
+        1,
+    )?;
+
+    let with_fallback = create_test_auth_policy(&mock_server.uri(), true)?;
+    let user = with_fallback.authenticate(&bearer(&token)).await.unwrap().unwrap();
+    assert_eq!(user.subject, "user-456");
+
+    let rejected = with_fallback.authenticate(&bearer("opaque-token")).await;
+    assert!(matches!(rejected, Err(AppError::Authentication { .. })));
+
+    let without_fallback = create_test_auth_policy(&mock_server.uri(), false)?;
+    let unavailable = without_fallback.authenticate(&bearer(&token)).await;
+    assert!(matches!(unavailable, Err(AppError::ServiceUnavailable { .. })));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_auth_policy_does_not_blame_the_token_for_rejected_introspection() -> Result<()> {
+    let mock_server = MockServer::start().await;
+
+    // The auth service refusing this service's own credentials says nothing about the token
+    Mock::given(method("POST"))
+        .and(path("/oauth2/introspect"))
+        .respond_with(ResponseTemplate::new(401).set_body_string("invalid_client"))
+        .mount(&mock_server)
+        .await;
+
+    let token = JwtService::new(TEST_JWT_SECRET).create_access_token(
+        "auth-service".to_string(),
+        vec!["media-service".to_string()],
+        "user-789".to_string(),
+        "recipe-web".to_string(),
+        vec!["media:read".to_string()],
+        1,
+    )?;
+
+    let with_fallback = create_test_auth_policy(&mock_server.uri(), true)?;
+    let user = with_fallback.authenticate(&bearer(&token)).await.unwrap().unwrap();
+    assert_eq!(user.subject, "user-789");
+
+    let without_fallback = create_test_auth_policy(&mock_server.uri(), false)?;
+    let unavailable = without_fallback.authenticate(&bearer(&token)).await;
+    assert!(matches!(unavailable, Err(AppError::ServiceUnavailable { .. })));
+
+    Ok(())
+}
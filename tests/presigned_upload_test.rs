@@ -16,6 +16,7 @@ async fn test_presigned_upload_dto_serialization() {
         filename: "test.jpg".to_string(),
         content_type: "image/jpeg".to_string(),
         file_size: 1024 * 1024,
+        content_hash_prefix: None,
     };
 
     let json = serde_json::to_string(&request).unwrap();
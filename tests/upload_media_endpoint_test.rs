@@ -9,6 +9,7 @@ use axum::{http::StatusCode, routing::post, Router};
 use media_management_service::domain::entities::MediaId;
 
 #[tokio::test]
+#[allow(deprecated)] // The direct upload is deprecated but still served
 async fn test_upload_endpoint_configuration_exists() {
     // Test that the upload endpoint handler exists and compiles correctly
     // This validates that the upload functionality is properly implemented
@@ -71,13 +72,13 @@ async fn test_upload_media_error_scenarios_documentation() {
 
     // Expected error scenarios:
     // - 400 Bad Request: No file data provided
-    // - 400 Bad Request: File too large
+    // - 413 Payload Too Large: File too large
     // - 400 Bad Request: Invalid file format/content type
     // - 500 Internal Server Error: Database/storage failures
 
     let error_scenarios = [
         ("No file data provided", StatusCode::BAD_REQUEST),
-        ("File too large", StatusCode::BAD_REQUEST),
+        ("File too large", StatusCode::PAYLOAD_TOO_LARGE),
         ("Invalid content type", StatusCode::BAD_REQUEST),
         ("Storage failure", StatusCode::INTERNAL_SERVER_ERROR),
         ("Database failure", StatusCode::INTERNAL_SERVER_ERROR),